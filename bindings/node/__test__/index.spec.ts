@@ -6,7 +6,7 @@ import { join } from "node:path";
 // Note: in real usage this would be:
 // import { Authy } from "authy-cli";
 // For testing the built native module:
-const { Authy } = require("../index.js");
+const { Authy, AsyncAuthy } = require("../index.js");
 
 function isolatedHome() {
   const tmp = mkdtempSync(join(tmpdir(), "authy-test-"));
@@ -80,6 +80,12 @@ describe("Authy native binding", () => {
     expect(names).toContain("beta");
   });
 
+  it("should throw on filterSecrets with an unknown scope", () => {
+    const client = new Authy({ passphrase: "test-pass" });
+    client.initVault();
+    expect(() => client.filterSecrets("nope", ["a", "b"])).toThrow(/not found/i);
+  });
+
   it("should check isInitialized", () => {
     expect(Authy.isInitialized()).toBe(false);
     const client = new Authy({ passphrase: "test-pass" });
@@ -87,7 +93,96 @@ describe("Authy native binding", () => {
     expect(Authy.isInitialized()).toBe(true);
   });
 
+  it("should report vaultPath", () => {
+    const home = isolatedHome();
+    expect(Authy.vaultPath()).toBe(join(home, ".authy", "vault.age"));
+    const other = join(home, "other");
+    expect(Authy.vaultPath(other)).toBe(join(other, "vault.age"));
+    expect(Authy.isInitialized(other)).toBe(false);
+  });
+
+  it("should report metadata and set tags", () => {
+    const client = new Authy({ passphrase: "test-pass" });
+    client.initVault();
+    client.store("api-key", "sk-1");
+    client.rotate("api-key", "sk-2");
+    client.setTags("api-key", ["prod", "billing"]);
+    const info = client.getMetadata("api-key");
+    expect(info?.version).toBe(2);
+    expect(info?.tags).toEqual(["prod", "billing"]);
+    expect(info?.provenance?.method).toBe("rotate");
+    expect(client.getMetadata("missing")).toBeNull();
+    expect(client.listDetailed().map((i) => i.name)).toEqual(["api-key"]);
+  });
+
+  it("should create, update and delete policies", () => {
+    const client = new Authy({ passphrase: "test-pass" });
+    client.initVault();
+    client.createPolicy("app", { allow: ["app-*"], description: "App" });
+    expect(client.updatePolicy("app", { deny: ["app-admin*"] })).toBe(1);
+    const policy = client.getPolicy("app");
+    expect(policy?.allow).toEqual(["app-*"]);
+    expect(policy?.deny).toEqual(["app-admin*"]);
+    expect(policy?.description).toBe("App");
+    expect(client.listPolicies().map((p) => p.name)).toEqual(["app"]);
+    client.deletePolicy("app");
+    expect(client.getPolicy("app")).toBeNull();
+    expect(() => client.deletePolicy("app")).toThrow(/not found/i);
+  });
+
+  it("should create, validate and revoke sessions", () => {
+    const client = new Authy({ passphrase: "test-pass" });
+    client.initVault();
+    client.createPolicy("app", { allow: ["app-*"] });
+    const created = client.createSession("app", { ttlSecs: 3600, label: "ci" });
+    expect(created.token.startsWith("authy_v1.")).toBe(true);
+    const session = client.validateToken(created.token);
+    expect(session.id).toBe(created.session.id);
+    expect(session.status).toBe("active");
+    expect(client.listSessions().map((s) => s.id)).toEqual([session.id]);
+    client.revokeSession(session.id);
+    expect(client.listSessions()[0].status).toBe("revoked");
+    expect(() => client.validateToken(created.token)).toThrow();
+  });
+
+  it("should see other clients' writes with cacheTtlMs", () => {
+    const writer = new Authy({ passphrase: "test-pass" });
+    writer.initVault();
+    writer.store("api-key", "v1");
+    const cached = new Authy({ passphrase: "test-pass", cacheTtlMs: 300_000 });
+    expect(cached.get("api-key")).toBe("v1");
+    writer.rotate("api-key", "v2-longer");
+    expect(cached.get("api-key")).toBe("v2-longer");
+    cached.refresh();
+    expect(cached.list()).toEqual(["api-key"]);
+  });
+
   it("should require passphrase or keyfile", () => {
     expect(() => new Authy({})).toThrow();
   });
 });
+
+describe("AsyncAuthy native binding", () => {
+  beforeEach(() => {
+    isolatedHome();
+  });
+
+  it("should init vault and store/get a secret", async () => {
+    const client = new AsyncAuthy({ passphrase: "test-pass" });
+    await client.initVault();
+    await client.store("api-key", "sk-secret-123");
+    const [value, missing] = await Promise.all([
+      client.get("api-key"),
+      client.getOrNull("nonexistent"),
+    ]);
+    expect(value).toBe("sk-secret-123");
+    expect(missing).toBeNull();
+    expect(await client.list()).toEqual(["api-key"]);
+  });
+
+  it("should reject on get not found", async () => {
+    const client = new AsyncAuthy({ passphrase: "test-pass" });
+    await client.initVault();
+    await expect(client.get("nonexistent")).rejects.toThrow(/not found/i);
+  });
+});
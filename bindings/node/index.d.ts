@@ -4,6 +4,16 @@ export interface AuthyOptions {
   passphrase?: string;
   /** Path to an age keyfile for authentication. */
   keyfile?: string;
+  /** Session token (requires `keyfile`). The client may do what the token's capabilities allow, confined to the token's scope. */
+  token?: string;
+  /** Keep the decrypted vault in memory for this many milliseconds between calls. It is reloaded early when the vault file changes. */
+  cacheTtlMs?: number;
+}
+
+/** Options for reading a secret. */
+export interface GetOptions {
+  /** Return the value even if the secret is expired. */
+  allowExpired?: boolean;
 }
 
 /** Options for storing a secret. */
@@ -18,6 +28,96 @@ export interface ListOptions {
   scope?: string;
 }
 
+/** How a secret's current value got into the vault. */
+export interface Provenance {
+  /** `store`, `import`, `generate` or `rotate`. */
+  method: string;
+  source?: string;
+  location?: string;
+  actor: string;
+}
+
+/** A secret's metadata. Timestamps are RFC 3339 strings; the value is never included. */
+export interface SecretInfo {
+  name: string;
+  version: number;
+  createdAt: string;
+  modifiedAt: string;
+  tags: string[];
+  description?: string;
+  expiresAt?: string;
+  protected: boolean;
+  provenance?: Provenance;
+}
+
+/** A policy. Timestamps are RFC 3339 strings. */
+export interface PolicyInfo {
+  name: string;
+  description?: string;
+  allow: string[];
+  deny: string[];
+  extends: string[];
+  runOnly: boolean;
+  countOnly: boolean;
+  protected: boolean;
+  createdAt: string;
+  modifiedAt: string;
+}
+
+/** A session. Timestamps are RFC 3339 strings; the token is never included. */
+export interface SessionInfo {
+  id: string;
+  scope: string;
+  label?: string;
+  /** `active`, `expired` or `revoked`. */
+  status: string;
+  runOnly: boolean;
+  capabilities: string[];
+  env: Record<string, string>;
+  createdAt: string;
+  expiresAt: string;
+  revokedAt?: string;
+  sliding: boolean;
+  maxExpiresAt?: string;
+}
+
+/** A session returned by `createSession`. The token is not shown again. */
+export interface NewSession {
+  token: string;
+  session: SessionInfo;
+}
+
+/** Options for creating a session. */
+export interface CreateSessionOptions {
+  /** How long the token is valid, in seconds. */
+  ttlSecs: number;
+  label?: string;
+  /** Only allow injecting secrets via `run`. */
+  runOnly?: boolean;
+}
+
+/** Options for creating a policy. */
+export interface CreatePolicyOptions {
+  /** Glob patterns of secrets the policy allows. */
+  allow: string[];
+  /** Glob patterns of secrets the policy denies. */
+  deny?: string[];
+  description?: string;
+  /** Only allow injecting secrets via `run`. */
+  runOnly?: boolean;
+}
+
+/** Changes for `updatePolicy`. Omitted fields keep their value. */
+export interface UpdatePolicyOptions {
+  allow?: string[];
+  deny?: string[];
+  description?: string;
+  runOnly?: boolean;
+  countOnly?: boolean;
+  /** Policies to inherit rules from; `[]` clears them. */
+  extends?: string[];
+}
+
 /**
  * Native Authy client backed by the Rust vault engine.
  *
@@ -27,11 +127,11 @@ export interface ListOptions {
 export class Authy {
   constructor(opts: AuthyOptions);
 
-  /** Retrieve a secret value. Throws if not found. */
-  get(name: string): string;
+  /** Retrieve a secret value. Throws if not found, or if it is expired unless `allowExpired` is set. */
+  get(name: string, opts?: GetOptions): string;
 
   /** Retrieve a secret value, returning null if not found. */
-  getOrNull(name: string): string | null;
+  getOrNull(name: string, opts?: GetOptions): string | null;
 
   /** Store a secret. Throws if it already exists unless force is set. */
   store(name: string, value: string, opts?: StoreOptions): void;
@@ -45,15 +145,144 @@ export class Authy {
   /** List secret names, optionally filtered by a policy scope. */
   list(opts?: ListOptions): string[];
 
+  /** List secrets with their metadata (no values), sorted by name. */
+  listDetailed(opts?: ListOptions): SecretInfo[];
+
+  /** A secret's metadata (no value), or null if not found. */
+  getMetadata(name: string): SecretInfo | null;
+
+  /** Replace a secret's tags. */
+  setTags(name: string, tags: string[]): void;
+
   /** Build an environment variable map from secrets matching a policy scope. */
   buildEnvMap(scope: string, uppercase?: boolean, replaceDash?: string): Record<string, string>;
 
   /** Test whether a policy allows access to a secret. */
   testPolicy(scope: string, secretName: string): boolean;
 
+  /** Create a policy. */
+  createPolicy(name: string, opts: CreatePolicyOptions): void;
+
+  /** List all policies, sorted by name. */
+  listPolicies(): PolicyInfo[];
+
+  /** A policy, or null if not found. With `effective`, inherited rules are merged in. */
+  getPolicy(name: string, effective?: boolean): PolicyInfo | null;
+
+  /** Change a policy. Returns the revision number the previous version was saved as. */
+  updatePolicy(name: string, opts: UpdatePolicyOptions): number;
+
+  /** Remove a policy. */
+  deletePolicy(name: string): void;
+
+  /** Create a session token for a policy scope. */
+  createSession(scope: string, opts: CreateSessionOptions): NewSession;
+
+  /** List all sessions, including expired and revoked ones. */
+  listSessions(): SessionInfo[];
+
+  /** Revoke a session by ID. */
+  revokeSession(id: string): void;
+
+  /** Check a session token and return its session. Throws for invalid, revoked or expired tokens. */
+  validateToken(token: string): SessionInfo;
+
+  /** Return the names a policy allows, in input order (one vault read). */
+  filterSecrets(scope: string, names: string[]): string[];
+
   /** Initialize a new vault. */
   initVault(): void;
 
-  /** Check whether a vault is initialized (static, no auth needed). */
-  static isInitialized(): boolean;
+  /** Drop the cached vault (see `cacheTtlMs`) so the next call reads it again. */
+  refresh(): void;
+
+  /**
+   * Check whether a vault is initialized (static, no auth needed).
+   * `home` is an authy directory to check instead of AUTHY_HOME or ~/.authy.
+   */
+  static isInitialized(home?: string): boolean;
+
+  /**
+   * Where the vault is, or would be created by `initVault()`: a file path,
+   * or a URL for remote storage (static, no auth needed).
+   */
+  static vaultPath(home?: string): string;
+}
+
+/**
+ * Promise-based Authy client. Vault reads, writes and decryption run on a
+ * background thread pool, so they don't block the event loop.
+ */
+export class AsyncAuthy {
+  /** Create a new async client. Takes the same options as `Authy`. */
+  constructor(opts: AuthyOptions);
+
+  /** Retrieve a secret value. Rejects if not found, or if it is expired unless `allowExpired` is set. */
+  get(name: string, opts?: GetOptions): Promise<string>;
+
+  /** Retrieve a secret value, resolving to null if not found. */
+  getOrNull(name: string, opts?: GetOptions): Promise<string | null>;
+
+  /** Store a secret. Rejects if it already exists unless force is set. */
+  store(name: string, value: string, opts?: StoreOptions): Promise<void>;
+
+  /** Remove a secret. Resolves to true if it existed. */
+  remove(name: string): Promise<boolean>;
+
+  /** Rotate a secret to a new value. Resolves to the new version number. */
+  rotate(name: string, newValue: string): Promise<number>;
+
+  /** List secret names, optionally filtered by a policy scope. */
+  list(opts?: ListOptions): Promise<string[]>;
+
+  /** List secrets with their metadata (no values), sorted by name. */
+  listDetailed(opts?: ListOptions): Promise<SecretInfo[]>;
+
+  /** A secret's metadata (no value), or null if not found. */
+  getMetadata(name: string): Promise<SecretInfo | null>;
+
+  /** Replace a secret's tags. */
+  setTags(name: string, tags: string[]): Promise<void>;
+
+  /** Build an environment variable map from secrets matching a policy scope. */
+  buildEnvMap(scope: string, uppercase?: boolean, replaceDash?: string): Promise<Record<string, string>>;
+
+  /** Test whether a policy allows access to a secret. */
+  testPolicy(scope: string, secretName: string): Promise<boolean>;
+
+  /** Create a policy. */
+  createPolicy(name: string, opts: CreatePolicyOptions): Promise<void>;
+
+  /** List all policies, sorted by name. */
+  listPolicies(): Promise<PolicyInfo[]>;
+
+  /** A policy, or null if not found. With `effective`, inherited rules are merged in. */
+  getPolicy(name: string, effective?: boolean): Promise<PolicyInfo | null>;
+
+  /** Change a policy. Resolves to the revision number the previous version was saved as. */
+  updatePolicy(name: string, opts: UpdatePolicyOptions): Promise<number>;
+
+  /** Remove a policy. */
+  deletePolicy(name: string): Promise<void>;
+
+  /** Create a session token for a policy scope. */
+  createSession(scope: string, opts: CreateSessionOptions): Promise<NewSession>;
+
+  /** List all sessions, including expired and revoked ones. */
+  listSessions(): Promise<SessionInfo[]>;
+
+  /** Revoke a session by ID. */
+  revokeSession(id: string): Promise<void>;
+
+  /** Check a session token and return its session. Rejects for invalid, revoked or expired tokens. */
+  validateToken(token: string): Promise<SessionInfo>;
+
+  /** Return the names a policy allows, in input order (one vault read). */
+  filterSecrets(scope: string, names: string[]): Promise<string[]>;
+
+  /** Initialize a new vault. */
+  initVault(): Promise<void>;
+
+  /** Drop the cached vault (see `cacheTtlMs`) so the next call reads it again. */
+  refresh(): void;
 }
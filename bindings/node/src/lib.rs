@@ -1,19 +1,43 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
-use authy::api::AuthyClient;
+use authy::api::{AuthyClient, AuthyClientAsync, GetOptions as ApiGetOptions, PolicyUpdate};
 use authy::error::AuthyError;
 
 /// Convert an AuthyError into a napi Error.
 /// The error message includes the typed error code in brackets for programmatic use.
 fn to_napi_err(e: AuthyError) -> napi::Error {
-    let code = e.error_code();
+    let code = e.code();
     let msg = e.to_string();
     napi::Error::new(Status::GenericFailure, format!("[{code}] {msg}"))
 }
 
+/// Authenticate a client from constructor options.
+fn connect(opts: &AuthyOptions) -> napi::Result<AuthyClient> {
+    let client = if let Some(ref tok) = opts.token {
+        let kf = opts.keyfile.as_deref().ok_or_else(|| {
+            napi::Error::new(Status::InvalidArg, "token requires keyfile in options")
+        })?;
+        AuthyClient::with_token(tok, kf).map_err(to_napi_err)
+    } else if let Some(ref pass) = opts.passphrase {
+        AuthyClient::with_passphrase(pass).map_err(to_napi_err)
+    } else if let Some(ref kf) = opts.keyfile {
+        AuthyClient::with_keyfile(kf).map_err(to_napi_err)
+    } else {
+        Err(napi::Error::new(
+            Status::InvalidArg,
+            "Provide passphrase or keyfile in options",
+        ))
+    }?;
+    Ok(match opts.cache_ttl_ms {
+        Some(ms) => client.with_cache(Duration::from_millis(ms.into())),
+        None => client,
+    })
+}
+
 /// Options for creating an Authy client.
 #[napi(object)]
 pub struct AuthyOptions {
@@ -21,6 +45,31 @@ pub struct AuthyOptions {
     pub passphrase: Option<String>,
     /// Path to an age keyfile for authentication.
     pub keyfile: Option<String>,
+    /// Session token (requires `keyfile`). The client may do what the
+    /// token's capabilities allow, confined to the token's scope.
+    pub token: Option<String>,
+    /// Keep the decrypted vault in memory for this many milliseconds between
+    /// calls. It is reloaded early when the vault file changes.
+    pub cache_ttl_ms: Option<u32>,
+}
+
+/// Options for reading a secret.
+#[napi(object)]
+pub struct GetOptions {
+    /// Return the value even if the secret is expired.
+    pub allow_expired: Option<bool>,
+}
+
+impl GetOptions {
+    fn to_api(opts: Option<GetOptions>) -> ApiGetOptions {
+        ApiGetOptions {
+            allow_expired: opts.and_then(|o| o.allow_expired).unwrap_or(false),
+        }
+    }
+}
+
+fn required(name: &str, value: Option<String>) -> authy::error::Result<String> {
+    value.ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))
 }
 
 /// Options for storing a secret.
@@ -37,6 +86,205 @@ pub struct ListOptions {
     pub scope: Option<String>,
 }
 
+/// How a secret's current value got into the vault.
+#[napi(object)]
+pub struct Provenance {
+    /// `store`, `import`, `generate` or `rotate`.
+    pub method: String,
+    pub source: Option<String>,
+    pub location: Option<String>,
+    pub actor: String,
+}
+
+/// A secret's metadata. Timestamps are RFC 3339 strings; the value is
+/// never included.
+#[napi(object)]
+pub struct SecretInfo {
+    pub name: String,
+    pub version: u32,
+    pub created_at: String,
+    pub modified_at: String,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub expires_at: Option<String>,
+    pub protected: bool,
+    pub provenance: Option<Provenance>,
+}
+
+impl From<authy::api::SecretInfo> for SecretInfo {
+    fn from(info: authy::api::SecretInfo) -> Self {
+        Self {
+            name: info.name,
+            version: info.version,
+            created_at: info.created_at.to_rfc3339(),
+            modified_at: info.modified_at.to_rfc3339(),
+            tags: info.tags,
+            description: info.description,
+            expires_at: info.expires_at.map(|t| t.to_rfc3339()),
+            protected: info.protected,
+            provenance: info.provenance.map(|p| Provenance {
+                method: p.method,
+                source: p.source,
+                location: p.location,
+                actor: p.actor,
+            }),
+        }
+    }
+}
+
+fn to_infos(infos: Vec<authy::api::SecretInfo>) -> Vec<SecretInfo> {
+    infos.into_iter().map(SecretInfo::from).collect()
+}
+
+/// A policy. Timestamps are RFC 3339 strings.
+#[napi(object)]
+pub struct PolicyInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub extends: Vec<String>,
+    pub run_only: bool,
+    pub count_only: bool,
+    pub protected: bool,
+    pub created_at: String,
+    pub modified_at: String,
+}
+
+impl From<authy::policy::Policy> for PolicyInfo {
+    fn from(policy: authy::policy::Policy) -> Self {
+        Self {
+            name: policy.name,
+            description: policy.description,
+            allow: policy.allow,
+            deny: policy.deny,
+            extends: policy.extends,
+            run_only: policy.run_only,
+            count_only: policy.count_only,
+            protected: policy.protected,
+            created_at: policy.created_at.to_rfc3339(),
+            modified_at: policy.modified_at.to_rfc3339(),
+        }
+    }
+}
+
+fn to_policies(policies: Vec<authy::policy::Policy>) -> Vec<PolicyInfo> {
+    policies.into_iter().map(PolicyInfo::from).collect()
+}
+
+/// Options for creating a policy.
+#[napi(object)]
+pub struct CreatePolicyOptions {
+    /// Glob patterns of secrets the policy allows.
+    pub allow: Vec<String>,
+    /// Glob patterns of secrets the policy denies.
+    pub deny: Option<Vec<String>>,
+    pub description: Option<String>,
+    /// Only allow injecting secrets via `run`.
+    pub run_only: Option<bool>,
+}
+
+/// Changes for `updatePolicy`. Omitted fields keep their value.
+#[napi(object)]
+pub struct UpdatePolicyOptions {
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub run_only: Option<bool>,
+    pub count_only: Option<bool>,
+    /// Policies to inherit rules from; `[]` clears them.
+    pub extends: Option<Vec<String>>,
+}
+
+impl From<UpdatePolicyOptions> for PolicyUpdate {
+    fn from(opts: UpdatePolicyOptions) -> Self {
+        Self {
+            allow: opts.allow,
+            deny: opts.deny,
+            description: opts.description,
+            run_only: opts.run_only,
+            count_only: opts.count_only,
+            extends: opts.extends,
+        }
+    }
+}
+
+/// A session. Timestamps are RFC 3339 strings; the token is never
+/// included.
+#[napi(object)]
+pub struct SessionInfo {
+    pub id: String,
+    pub scope: String,
+    pub label: Option<String>,
+    /// `active`, `expired` or `revoked`.
+    pub status: String,
+    pub run_only: bool,
+    pub capabilities: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub created_at: String,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub sliding: bool,
+    pub max_expires_at: Option<String>,
+}
+
+impl From<authy::api::SessionInfo> for SessionInfo {
+    fn from(info: authy::api::SessionInfo) -> Self {
+        Self {
+            id: info.id,
+            scope: info.scope,
+            label: info.label,
+            status: info.status.as_str().to_string(),
+            run_only: info.run_only,
+            capabilities: info
+                .capabilities
+                .iter()
+                .map(|c| c.as_str().to_string())
+                .collect(),
+            env: info.env.into_iter().collect(),
+            created_at: info.created_at.to_rfc3339(),
+            expires_at: info.expires_at.to_rfc3339(),
+            revoked_at: info.revoked_at.map(|t| t.to_rfc3339()),
+            sliding: info.sliding,
+            max_expires_at: info.max_expires_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+fn to_sessions(sessions: Vec<authy::api::SessionInfo>) -> Vec<SessionInfo> {
+    sessions.into_iter().map(SessionInfo::from).collect()
+}
+
+/// A session returned by `createSession`. The token is not shown again.
+#[napi(object)]
+pub struct NewSession {
+    pub token: String,
+    pub session: SessionInfo,
+}
+
+impl From<authy::api::NewSession> for NewSession {
+    fn from(created: authy::api::NewSession) -> Self {
+        Self {
+            token: created.token,
+            session: created.session.into(),
+        }
+    }
+}
+
+/// Options for creating a session.
+#[napi(object)]
+pub struct CreateSessionOptions {
+    /// How long the token is valid, in seconds.
+    pub ttl_secs: u32,
+    pub label: Option<String>,
+    /// Only allow injecting secrets via `run`.
+    pub run_only: Option<bool>,
+}
+
+fn borrowed(tags: &[String]) -> Vec<&str> {
+    tags.iter().map(String::as_str).collect()
+}
+
 /// Native Authy client backed by the Rust vault engine.
 ///
 /// No authy binary on PATH needed — the vault engine is compiled
@@ -50,32 +298,31 @@ pub struct Authy {
 impl Authy {
     /// Create a new Authy client.
     ///
-    /// Authenticate with `{ passphrase: "..." }` or `{ keyfile: "/path/to/key" }`.
+    /// Authenticate with `{ passphrase: "..." }`, `{ keyfile: "/path/to/key" }`,
+    /// or `{ token: "...", keyfile: "/path/to/key" }`.
     #[napi(constructor)]
     pub fn new(opts: AuthyOptions) -> napi::Result<Self> {
-        let client = if let Some(ref pass) = opts.passphrase {
-            AuthyClient::with_passphrase(pass).map_err(to_napi_err)?
-        } else if let Some(ref kf) = opts.keyfile {
-            AuthyClient::with_keyfile(kf).map_err(to_napi_err)?
-        } else {
-            return Err(napi::Error::new(
-                Status::InvalidArg,
-                "Provide passphrase or keyfile in options",
-            ));
-        };
-        Ok(Self { client })
-    }
-
-    /// Retrieve a secret value. Throws if not found.
+        Ok(Self {
+            client: connect(&opts)?,
+        })
+    }
+
+    /// Retrieve a secret value. Throws if not found, or if it is expired
+    /// unless `allowExpired` is set.
     #[napi]
-    pub fn get(&self, name: String) -> napi::Result<String> {
-        self.client.get_or_err(&name).map_err(to_napi_err)
+    pub fn get(&self, name: String, opts: Option<GetOptions>) -> napi::Result<String> {
+        self.client
+            .get_with_options(&name, &GetOptions::to_api(opts))
+            .and_then(|v| required(&name, v))
+            .map_err(to_napi_err)
     }
 
     /// Retrieve a secret value, returning null if not found.
     #[napi(js_name = "getOrNull")]
-    pub fn get_or_null(&self, name: String) -> napi::Result<Option<String>> {
-        self.client.get(&name).map_err(to_napi_err)
+    pub fn get_or_null(&self, name: String, opts: Option<GetOptions>) -> napi::Result<Option<String>> {
+        self.client
+            .get_with_options(&name, &GetOptions::to_api(opts))
+            .map_err(to_napi_err)
     }
 
     /// Store a secret. Throws SecretAlreadyExists unless force is set.
@@ -109,6 +356,33 @@ impl Authy {
         self.client.list(scope).map_err(to_napi_err)
     }
 
+    /// List secrets with their metadata (no values), sorted by name.
+    #[napi(js_name = "listDetailed")]
+    pub fn list_detailed(&self, opts: Option<ListOptions>) -> napi::Result<Vec<SecretInfo>> {
+        let scope = opts.as_ref().and_then(|o| o.scope.as_deref());
+        self.client
+            .list_detailed(scope)
+            .map(to_infos)
+            .map_err(to_napi_err)
+    }
+
+    /// A secret's metadata (no value), or null if not found.
+    #[napi(js_name = "getMetadata")]
+    pub fn get_metadata(&self, name: String) -> napi::Result<Option<SecretInfo>> {
+        self.client
+            .get_metadata(&name)
+            .map(|info| info.map(SecretInfo::from))
+            .map_err(to_napi_err)
+    }
+
+    /// Replace a secret's tags.
+    #[napi(js_name = "setTags")]
+    pub fn set_tags(&self, name: String, tags: Vec<String>) -> napi::Result<()> {
+        self.client
+            .set_tags(&name, &borrowed(&tags))
+            .map_err(to_napi_err)
+    }
+
     /// Build an environment variable map from secrets matching a policy scope.
     #[napi(js_name = "buildEnvMap")]
     pub fn build_env_map(
@@ -132,15 +406,409 @@ impl Authy {
             .map_err(to_napi_err)
     }
 
+    /// Create a policy.
+    #[napi(js_name = "createPolicy")]
+    pub fn create_policy(&self, name: String, opts: CreatePolicyOptions) -> napi::Result<()> {
+        self.client
+            .create_policy(
+                &name,
+                opts.allow,
+                opts.deny.unwrap_or_default(),
+                opts.description.as_deref(),
+                opts.run_only.unwrap_or(false),
+            )
+            .map_err(to_napi_err)
+    }
+
+    /// List all policies, sorted by name.
+    #[napi(js_name = "listPolicies")]
+    pub fn list_policies(&self) -> napi::Result<Vec<PolicyInfo>> {
+        self.client
+            .list_policies()
+            .map(to_policies)
+            .map_err(to_napi_err)
+    }
+
+    /// A policy, or null if not found. With `effective`, rules inherited
+    /// through `extends` are merged in.
+    #[napi(js_name = "getPolicy")]
+    pub fn get_policy(
+        &self,
+        name: String,
+        effective: Option<bool>,
+    ) -> napi::Result<Option<PolicyInfo>> {
+        self.client
+            .get_policy(&name, effective.unwrap_or(false))
+            .map(|p| p.map(PolicyInfo::from))
+            .map_err(to_napi_err)
+    }
+
+    /// Change a policy. Returns the revision number the previous version
+    /// was saved as.
+    #[napi(js_name = "updatePolicy")]
+    pub fn update_policy(&self, name: String, opts: UpdatePolicyOptions) -> napi::Result<u32> {
+        self.client
+            .update_policy(&name, &opts.into())
+            .map_err(to_napi_err)
+    }
+
+    /// Remove a policy.
+    #[napi(js_name = "deletePolicy")]
+    pub fn delete_policy(&self, name: String) -> napi::Result<()> {
+        self.client.delete_policy(&name).map_err(to_napi_err)
+    }
+
+    /// Create a session token for a policy scope.
+    #[napi(js_name = "createSession")]
+    pub fn create_session(
+        &self,
+        scope: String,
+        opts: CreateSessionOptions,
+    ) -> napi::Result<NewSession> {
+        self.client
+            .create_session(
+                &scope,
+                Duration::from_secs(opts.ttl_secs.into()),
+                opts.label.as_deref(),
+                opts.run_only.unwrap_or(false),
+            )
+            .map(NewSession::from)
+            .map_err(to_napi_err)
+    }
+
+    /// List all sessions, including expired and revoked ones.
+    #[napi(js_name = "listSessions")]
+    pub fn list_sessions(&self) -> napi::Result<Vec<SessionInfo>> {
+        self.client
+            .list_sessions()
+            .map(to_sessions)
+            .map_err(to_napi_err)
+    }
+
+    /// Revoke a session by ID.
+    #[napi(js_name = "revokeSession")]
+    pub fn revoke_session(&self, id: String) -> napi::Result<()> {
+        self.client.revoke_session(&id).map_err(to_napi_err)
+    }
+
+    /// Check a session token and return its session. Throws for invalid,
+    /// revoked or expired tokens.
+    #[napi(js_name = "validateToken")]
+    pub fn validate_token(&self, token: String) -> napi::Result<SessionInfo> {
+        self.client
+            .validate_token(&token)
+            .map(SessionInfo::from)
+            .map_err(to_napi_err)
+    }
+
+    /// Return the names a policy allows, in input order (one vault read).
+    #[napi(js_name = "filterSecrets")]
+    pub fn filter_secrets(&self, scope: String, names: Vec<String>) -> napi::Result<Vec<String>> {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.client
+            .filter_secrets(&scope, &names)
+            .map_err(to_napi_err)
+    }
+
     /// Initialize a new vault.
     #[napi(js_name = "initVault")]
     pub fn init_vault(&self) -> napi::Result<()> {
         self.client.init_vault().map_err(to_napi_err)
     }
 
+    /// Drop the cached vault (see `cacheTtlMs`) so the next call reads it again.
+    #[napi]
+    pub fn refresh(&self) {
+        self.client.refresh();
+    }
+
     /// Check whether a vault is initialized (static, no auth needed).
+    /// `home` is an authy directory to check instead of AUTHY_HOME or ~/.authy.
     #[napi(js_name = "isInitialized")]
-    pub fn is_initialized_check() -> bool {
-        AuthyClient::is_initialized()
+    pub fn is_initialized_check(home: Option<String>) -> bool {
+        match home {
+            Some(dir) => AuthyClient::is_initialized_at(dir),
+            None => AuthyClient::is_initialized(),
+        }
+    }
+
+    /// Where the vault is, or would be created by `initVault()`: a file path,
+    /// or a URL for remote storage (static, no auth needed).
+    #[napi(js_name = "vaultPath")]
+    pub fn vault_path(home: Option<String>) -> napi::Result<String> {
+        match home {
+            Some(dir) => AuthyClient::vault_location_at(dir),
+            None => AuthyClient::vault_location(),
+        }
+        .map_err(to_napi_err)
+    }
+}
+
+/// Promise-based Authy client. Vault reads, writes and decryption run on a
+/// background thread pool, so they don't block the event loop.
+#[napi]
+pub struct AsyncAuthy {
+    client: AuthyClientAsync,
+}
+
+#[napi]
+impl AsyncAuthy {
+    /// Create a new async client. Takes the same options as `Authy`.
+    #[napi(constructor)]
+    pub fn new(opts: AuthyOptions) -> napi::Result<Self> {
+        Ok(Self {
+            client: AuthyClientAsync::new(connect(&opts)?),
+        })
+    }
+
+    /// Retrieve a secret value. Rejects if not found, or if it is expired
+    /// unless `allowExpired` is set.
+    #[napi]
+    pub async fn get(&self, name: String, opts: Option<GetOptions>) -> napi::Result<String> {
+        self.client
+            .get_with_options(&name, &GetOptions::to_api(opts))
+            .await
+            .and_then(|v| required(&name, v))
+            .map_err(to_napi_err)
+    }
+
+    /// Retrieve a secret value, resolving to null if not found.
+    #[napi(js_name = "getOrNull")]
+    pub async fn get_or_null(
+        &self,
+        name: String,
+        opts: Option<GetOptions>,
+    ) -> napi::Result<Option<String>> {
+        self.client
+            .get_with_options(&name, &GetOptions::to_api(opts))
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Store a secret. Rejects with SecretAlreadyExists unless force is set.
+    #[napi]
+    pub async fn store(
+        &self,
+        name: String,
+        value: String,
+        opts: Option<StoreOptions>,
+    ) -> napi::Result<()> {
+        let force = opts.and_then(|o| o.force).unwrap_or(false);
+        self.client
+            .store(&name, &value, force)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Remove a secret. Resolves to true if it existed.
+    #[napi]
+    pub async fn remove(&self, name: String) -> napi::Result<bool> {
+        self.client.remove(&name).await.map_err(to_napi_err)
+    }
+
+    /// Rotate a secret to a new value. Resolves to the new version number.
+    #[napi]
+    pub async fn rotate(&self, name: String, new_value: String) -> napi::Result<u32> {
+        self.client
+            .rotate(&name, &new_value)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// List secret names, optionally filtered by a policy scope.
+    #[napi]
+    pub async fn list(&self, opts: Option<ListOptions>) -> napi::Result<Vec<String>> {
+        let scope = opts.and_then(|o| o.scope);
+        self.client
+            .list(scope.as_deref())
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// List secrets with their metadata (no values), sorted by name.
+    #[napi(js_name = "listDetailed")]
+    pub async fn list_detailed(&self, opts: Option<ListOptions>) -> napi::Result<Vec<SecretInfo>> {
+        let scope = opts.and_then(|o| o.scope);
+        self.client
+            .list_detailed(scope.as_deref())
+            .await
+            .map(to_infos)
+            .map_err(to_napi_err)
+    }
+
+    /// A secret's metadata (no value), or null if not found.
+    #[napi(js_name = "getMetadata")]
+    pub async fn get_metadata(&self, name: String) -> napi::Result<Option<SecretInfo>> {
+        self.client
+            .get_metadata(&name)
+            .await
+            .map(|info| info.map(SecretInfo::from))
+            .map_err(to_napi_err)
+    }
+
+    /// Replace a secret's tags.
+    #[napi(js_name = "setTags")]
+    pub async fn set_tags(&self, name: String, tags: Vec<String>) -> napi::Result<()> {
+        self.client
+            .set_tags(&name, &borrowed(&tags))
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Build an environment variable map from secrets matching a policy scope.
+    #[napi(js_name = "buildEnvMap")]
+    pub async fn build_env_map(
+        &self,
+        scope: String,
+        uppercase: Option<bool>,
+        replace_dash: Option<String>,
+    ) -> napi::Result<HashMap<String, String>> {
+        let uc = uppercase.unwrap_or(true);
+        let rd = replace_dash.and_then(|s| s.chars().next());
+        self.client
+            .build_env_map(&scope, uc, rd)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Test whether a policy allows access to a secret.
+    #[napi(js_name = "testPolicy")]
+    pub async fn test_policy(&self, scope: String, secret_name: String) -> napi::Result<bool> {
+        self.client
+            .test_policy(&scope, &secret_name)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Create a policy.
+    #[napi(js_name = "createPolicy")]
+    pub async fn create_policy(&self, name: String, opts: CreatePolicyOptions) -> napi::Result<()> {
+        self.client
+            .create_policy(
+                &name,
+                opts.allow,
+                opts.deny.unwrap_or_default(),
+                opts.description.as_deref(),
+                opts.run_only.unwrap_or(false),
+            )
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// List all policies, sorted by name.
+    #[napi(js_name = "listPolicies")]
+    pub async fn list_policies(&self) -> napi::Result<Vec<PolicyInfo>> {
+        self.client
+            .list_policies()
+            .await
+            .map(to_policies)
+            .map_err(to_napi_err)
+    }
+
+    /// A policy, or null if not found. With `effective`, rules inherited
+    /// through `extends` are merged in.
+    #[napi(js_name = "getPolicy")]
+    pub async fn get_policy(
+        &self,
+        name: String,
+        effective: Option<bool>,
+    ) -> napi::Result<Option<PolicyInfo>> {
+        self.client
+            .get_policy(&name, effective.unwrap_or(false))
+            .await
+            .map(|p| p.map(PolicyInfo::from))
+            .map_err(to_napi_err)
+    }
+
+    /// Change a policy. Returns the revision number the previous version
+    /// was saved as.
+    #[napi(js_name = "updatePolicy")]
+    pub async fn update_policy(
+        &self,
+        name: String,
+        opts: UpdatePolicyOptions,
+    ) -> napi::Result<u32> {
+        self.client
+            .update_policy(&name, &opts.into())
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Remove a policy.
+    #[napi(js_name = "deletePolicy")]
+    pub async fn delete_policy(&self, name: String) -> napi::Result<()> {
+        self.client.delete_policy(&name).await.map_err(to_napi_err)
+    }
+
+    /// Create a session token for a policy scope.
+    #[napi(js_name = "createSession")]
+    pub async fn create_session(
+        &self,
+        scope: String,
+        opts: CreateSessionOptions,
+    ) -> napi::Result<NewSession> {
+        self.client
+            .create_session(
+                &scope,
+                Duration::from_secs(opts.ttl_secs.into()),
+                opts.label.as_deref(),
+                opts.run_only.unwrap_or(false),
+            )
+            .await
+            .map(NewSession::from)
+            .map_err(to_napi_err)
+    }
+
+    /// List all sessions, including expired and revoked ones.
+    #[napi(js_name = "listSessions")]
+    pub async fn list_sessions(&self) -> napi::Result<Vec<SessionInfo>> {
+        self.client
+            .list_sessions()
+            .await
+            .map(to_sessions)
+            .map_err(to_napi_err)
+    }
+
+    /// Revoke a session by ID.
+    #[napi(js_name = "revokeSession")]
+    pub async fn revoke_session(&self, id: String) -> napi::Result<()> {
+        self.client.revoke_session(&id).await.map_err(to_napi_err)
+    }
+
+    /// Check a session token and return its session. Rejects for invalid,
+    /// revoked or expired tokens.
+    #[napi(js_name = "validateToken")]
+    pub async fn validate_token(&self, token: String) -> napi::Result<SessionInfo> {
+        self.client
+            .validate_token(&token)
+            .await
+            .map(SessionInfo::from)
+            .map_err(to_napi_err)
+    }
+
+    /// Return the names a policy allows, in input order (one vault read).
+    #[napi(js_name = "filterSecrets")]
+    pub async fn filter_secrets(
+        &self,
+        scope: String,
+        names: Vec<String>,
+    ) -> napi::Result<Vec<String>> {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.client
+            .filter_secrets(&scope, &names)
+            .await
+            .map_err(to_napi_err)
+    }
+
+    /// Initialize a new vault.
+    #[napi(js_name = "initVault")]
+    pub async fn init_vault(&self) -> napi::Result<()> {
+        self.client.init_vault().await.map_err(to_napi_err)
+    }
+
+    /// Drop the cached vault (see `cacheTtlMs`) so the next call reads it again.
+    #[napi]
+    pub fn refresh(&self) {
+        self.client.blocking().refresh();
     }
 }
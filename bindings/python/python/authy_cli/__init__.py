@@ -7,10 +7,19 @@ Usage::
     client = Authy(passphrase="my-vault-passphrase")
     client.store("api-key", "sk-secret-value")
     value = client.get("api-key")
+
+In asyncio code, ``AsyncAuthy`` takes the same arguments and its methods
+are coroutines::
+
+    from authy_cli import AsyncAuthy
+
+    client = AsyncAuthy(passphrase="my-vault-passphrase")
+    value = await client.get("api-key")
 """
 
 from authy_cli._native import (
     Authy,
+    AsyncAuthy,
     AuthyException as AuthyError,
     SecretNotFound,
     SecretAlreadyExists,
@@ -22,6 +31,7 @@ from authy_cli._native import (
 
 __all__ = [
     "Authy",
+    "AsyncAuthy",
     "AuthyError",
     "SecretNotFound",
     "SecretAlreadyExists",
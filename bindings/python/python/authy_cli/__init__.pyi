@@ -1,6 +1,56 @@
 """Type stubs for authy_cli."""
 
-from typing import Optional
+from typing import Optional, TypedDict
+
+class Provenance(TypedDict):
+    method: str
+    source: Optional[str]
+    location: Optional[str]
+    actor: str
+
+class SecretInfo(TypedDict):
+    """A secret's metadata. Timestamps are RFC 3339 strings."""
+    name: str
+    version: int
+    created_at: str
+    modified_at: str
+    tags: list[str]
+    description: Optional[str]
+    expires_at: Optional[str]
+    protected: bool
+    provenance: Optional[Provenance]
+
+class PolicyInfo(TypedDict):
+    """A policy. Timestamps are RFC 3339 strings."""
+    name: str
+    description: Optional[str]
+    allow: list[str]
+    deny: list[str]
+    extends: list[str]
+    run_only: bool
+    count_only: bool
+    protected: bool
+    created_at: str
+    modified_at: str
+
+class SessionInfo(TypedDict):
+    """A session. Timestamps are RFC 3339 strings; the token is never included."""
+    id: str
+    scope: str
+    label: Optional[str]
+    status: str
+    run_only: bool
+    capabilities: list[str]
+    env: dict[str, str]
+    created_at: str
+    expires_at: str
+    revoked_at: Optional[str]
+    sliding: bool
+    max_expires_at: Optional[str]
+
+class NewSession(TypedDict):
+    token: str
+    session: SessionInfo
 
 class AuthyError(Exception):
     """Base exception for all Authy errors."""
@@ -22,15 +72,20 @@ class Authy:
         *,
         passphrase: Optional[str] = None,
         keyfile: Optional[str] = None,
+        token: Optional[str] = None,
         from_env: bool = False,
+        cache_ttl: Optional[float] = None,
     ) -> None: ...
 
-    def get(self, name: str) -> str: ...
-    def get_or_none(self, name: str) -> Optional[str]: ...
+    def get(self, name: str, allow_expired: bool = False) -> str: ...
+    def get_or_none(self, name: str, allow_expired: bool = False) -> Optional[str]: ...
     def store(self, name: str, value: str, force: bool = False) -> None: ...
     def remove(self, name: str) -> bool: ...
     def rotate(self, name: str, new_value: str) -> int: ...
     def list(self, scope: Optional[str] = None) -> list[str]: ...
+    def list_detailed(self, scope: Optional[str] = None) -> list[SecretInfo]: ...
+    def get_metadata(self, name: str) -> Optional[SecretInfo]: ...
+    def set_tags(self, name: str, tags: list[str]) -> None: ...
     def build_env_map(
         self,
         scope: str,
@@ -38,7 +93,109 @@ class Authy:
         replace_dash: Optional[str] = "_",
     ) -> dict[str, str]: ...
     def test_policy(self, scope: str, secret_name: str) -> bool: ...
+    def create_policy(
+        self,
+        name: str,
+        allow: list[str],
+        deny: list[str] = ...,
+        description: Optional[str] = None,
+        run_only: bool = False,
+    ) -> None: ...
+    def list_policies(self) -> list[PolicyInfo]: ...
+    def get_policy(self, name: str, effective: bool = False) -> Optional[PolicyInfo]: ...
+    def update_policy(
+        self,
+        name: str,
+        *,
+        allow: Optional[list[str]] = None,
+        deny: Optional[list[str]] = None,
+        description: Optional[str] = None,
+        run_only: Optional[bool] = None,
+        count_only: Optional[bool] = None,
+        extends: Optional[list[str]] = None,
+    ) -> int: ...
+    def delete_policy(self, name: str) -> None: ...
+    def create_session(
+        self,
+        scope: str,
+        ttl: float,
+        label: Optional[str] = None,
+        run_only: bool = False,
+    ) -> NewSession: ...
+    def list_sessions(self) -> list[SessionInfo]: ...
+    def revoke_session(self, id: str) -> None: ...
+    def validate_token(self, token: str) -> SessionInfo: ...
+    def filter_secrets(self, scope: str, names: list[str]) -> list[str]: ...
     def init_vault(self) -> None: ...
+    def refresh(self) -> None: ...
+
+    @staticmethod
+    def is_initialized(path: Optional[str] = None) -> bool: ...
 
     @staticmethod
-    def is_initialized() -> bool: ...
+    def vault_path(path: Optional[str] = None) -> str: ...
+
+class AsyncAuthy:
+    """Asyncio Authy client; vault work runs on the loop's default executor."""
+
+    def __init__(
+        self,
+        *,
+        passphrase: Optional[str] = None,
+        keyfile: Optional[str] = None,
+        token: Optional[str] = None,
+        from_env: bool = False,
+        cache_ttl: Optional[float] = None,
+    ) -> None: ...
+
+    async def get(self, name: str, allow_expired: bool = False) -> str: ...
+    async def get_or_none(self, name: str, allow_expired: bool = False) -> Optional[str]: ...
+    async def store(self, name: str, value: str, force: bool = False) -> None: ...
+    async def remove(self, name: str) -> bool: ...
+    async def rotate(self, name: str, new_value: str) -> int: ...
+    async def list(self, scope: Optional[str] = None) -> list[str]: ...
+    async def list_detailed(self, scope: Optional[str] = None) -> list[SecretInfo]: ...
+    async def get_metadata(self, name: str) -> Optional[SecretInfo]: ...
+    async def set_tags(self, name: str, tags: list[str]) -> None: ...
+    async def build_env_map(
+        self,
+        scope: str,
+        uppercase: bool = True,
+        replace_dash: Optional[str] = "_",
+    ) -> dict[str, str]: ...
+    async def test_policy(self, scope: str, secret_name: str) -> bool: ...
+    async def create_policy(
+        self,
+        name: str,
+        allow: list[str],
+        deny: list[str] = ...,
+        description: Optional[str] = None,
+        run_only: bool = False,
+    ) -> None: ...
+    async def list_policies(self) -> list[PolicyInfo]: ...
+    async def get_policy(self, name: str, effective: bool = False) -> Optional[PolicyInfo]: ...
+    async def update_policy(
+        self,
+        name: str,
+        *,
+        allow: Optional[list[str]] = None,
+        deny: Optional[list[str]] = None,
+        description: Optional[str] = None,
+        run_only: Optional[bool] = None,
+        count_only: Optional[bool] = None,
+        extends: Optional[list[str]] = None,
+    ) -> int: ...
+    async def delete_policy(self, name: str) -> None: ...
+    async def create_session(
+        self,
+        scope: str,
+        ttl: float,
+        label: Optional[str] = None,
+        run_only: bool = False,
+    ) -> NewSession: ...
+    async def list_sessions(self) -> list[SessionInfo]: ...
+    async def revoke_session(self, id: str) -> None: ...
+    async def validate_token(self, token: str) -> SessionInfo: ...
+    async def filter_secrets(self, scope: str, names: list[str]) -> list[str]: ...
+    async def init_vault(self) -> None: ...
+    def refresh(self) -> None: ...
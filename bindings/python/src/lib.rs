@@ -1,11 +1,16 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use pyo3::exceptions::PyException;
 use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict};
+use pyo3::IntoPyObjectExt;
 use pyo3::create_exception;
 
-use authy::api::AuthyClient;
+use authy::api::{AuthyClient, GetOptions, NewSession, PolicyUpdate, SecretInfo, SessionInfo};
 use authy::error::AuthyError;
+use authy::policy::Policy;
 
 // ── Exception hierarchy ──────────────────────────────────────────
 
@@ -26,13 +31,18 @@ fn to_py_err(e: AuthyError) -> PyErr {
     let py_err = match &e {
         AuthyError::SecretNotFound(_) => SecretNotFound::new_err(msg),
         AuthyError::SecretAlreadyExists(_) => SecretAlreadyExists::new_err(msg),
-        AuthyError::AuthFailed(_) | AuthyError::Decryption(_) | AuthyError::InvalidKeyfile(_) => {
+        AuthyError::AuthFailed(_)
+        | AuthyError::PromptTimeout(_)
+        | AuthyError::Decryption(_)
+        | AuthyError::InvalidKeyfile(_) => {
             AuthFailed::new_err(msg)
         }
         AuthyError::PolicyNotFound(_) => PolicyNotFound::new_err(msg),
         AuthyError::AccessDenied { .. }
         | AuthyError::TokenReadOnly
-        | AuthyError::RunOnly => AccessDenied::new_err(msg),
+        | AuthyError::MissingCapability(_)
+        | AuthyError::RunOnly
+        | AuthyError::ApprovalRequired(_) => AccessDenied::new_err(msg),
         AuthyError::VaultNotInitialized => VaultNotInitialized::new_err(msg),
         _ => AuthyException::new_err(msg),
     };
@@ -48,6 +58,164 @@ fn to_py_err(e: AuthyError) -> PyErr {
     py_err
 }
 
+/// Read a secret, refusing an expired one unless `allow_expired`.
+fn get_value(client: &AuthyClient, name: &str, allow_expired: bool) -> authy::error::Result<Option<String>> {
+    client.get_with_options(name, &GetOptions { allow_expired })
+}
+
+/// Like [`get_value`], failing with `SecretNotFound` if it is missing.
+fn get_required(client: &AuthyClient, name: &str, allow_expired: bool) -> authy::error::Result<String> {
+    get_value(client, name, allow_expired)?
+        .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))
+}
+
+/// Authenticate a client from constructor arguments.
+fn connect(
+    passphrase: Option<&str>,
+    keyfile: Option<&str>,
+    token: Option<&str>,
+    from_env: bool,
+    cache_ttl: Option<f64>,
+) -> PyResult<AuthyClient> {
+    let client = if let Some(tok) = token {
+        let kf = keyfile.ok_or_else(|| AuthyException::new_err("token= requires keyfile="))?;
+        AuthyClient::with_token(tok, kf).map_err(to_py_err)
+    } else if let Some(pass) = passphrase {
+        AuthyClient::with_passphrase(pass).map_err(to_py_err)
+    } else if let Some(kf) = keyfile {
+        AuthyClient::with_keyfile(kf).map_err(to_py_err)
+    } else if from_env {
+        AuthyClient::from_env().map_err(to_py_err)
+    } else {
+        Err(AuthyException::new_err(
+            "Provide passphrase=, keyfile=, or from_env=True",
+        ))
+    }?;
+    match cache_ttl {
+        Some(secs) => Ok(client.with_cache(seconds("cache_ttl", secs)?)),
+        None => Ok(client),
+    }
+}
+
+/// A keyword argument given in seconds, as a Duration.
+fn seconds(arg: &str, secs: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(secs).map_err(|_| {
+        AuthyException::new_err(format!("{arg}= must be a non-negative number of seconds"))
+    })
+}
+
+/// A secret's metadata as a dict; timestamps are RFC 3339 strings.
+struct PySecretInfo(SecretInfo);
+
+impl<'py> IntoPyObject<'py> for PySecretInfo {
+    type Target = PyDict;
+    type Output = Bound<'py, PyDict>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        let info = self.0;
+        let dict = PyDict::new(py);
+        dict.set_item("name", info.name)?;
+        dict.set_item("version", info.version)?;
+        dict.set_item("created_at", info.created_at.to_rfc3339())?;
+        dict.set_item("modified_at", info.modified_at.to_rfc3339())?;
+        dict.set_item("tags", info.tags)?;
+        dict.set_item("description", info.description)?;
+        dict.set_item("expires_at", info.expires_at.map(|t| t.to_rfc3339()))?;
+        dict.set_item("protected", info.protected)?;
+        let provenance = match info.provenance {
+            Some(p) => {
+                let d = PyDict::new(py);
+                d.set_item("method", p.method)?;
+                d.set_item("source", p.source)?;
+                d.set_item("location", p.location)?;
+                d.set_item("actor", p.actor)?;
+                Some(d)
+            }
+            None => None,
+        };
+        dict.set_item("provenance", provenance)?;
+        Ok(dict)
+    }
+}
+
+fn py_infos(infos: Vec<SecretInfo>) -> Vec<PySecretInfo> {
+    infos.into_iter().map(PySecretInfo).collect()
+}
+
+/// A policy as a dict; timestamps are RFC 3339 strings.
+struct PyPolicy(Policy);
+
+impl<'py> IntoPyObject<'py> for PyPolicy {
+    type Target = PyDict;
+    type Output = Bound<'py, PyDict>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        let policy = self.0;
+        let dict = PyDict::new(py);
+        dict.set_item("name", policy.name)?;
+        dict.set_item("description", policy.description)?;
+        dict.set_item("allow", policy.allow)?;
+        dict.set_item("deny", policy.deny)?;
+        dict.set_item("extends", policy.extends)?;
+        dict.set_item("run_only", policy.run_only)?;
+        dict.set_item("count_only", policy.count_only)?;
+        dict.set_item("protected", policy.protected)?;
+        dict.set_item("created_at", policy.created_at.to_rfc3339())?;
+        dict.set_item("modified_at", policy.modified_at.to_rfc3339())?;
+        Ok(dict)
+    }
+}
+
+fn py_policies(policies: Vec<Policy>) -> Vec<PyPolicy> {
+    policies.into_iter().map(PyPolicy).collect()
+}
+
+/// A session as a dict; timestamps are RFC 3339 strings.
+struct PySessionInfo(SessionInfo);
+
+impl<'py> IntoPyObject<'py> for PySessionInfo {
+    type Target = PyDict;
+    type Output = Bound<'py, PyDict>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        let info = self.0;
+        let dict = PyDict::new(py);
+        dict.set_item("id", info.id)?;
+        dict.set_item("scope", info.scope)?;
+        dict.set_item("label", info.label)?;
+        dict.set_item("status", info.status.as_str())?;
+        dict.set_item("run_only", info.run_only)?;
+        let capabilities: Vec<&str> = info.capabilities.iter().map(|c| c.as_str()).collect();
+        dict.set_item("capabilities", capabilities)?;
+        dict.set_item("env", info.env)?;
+        dict.set_item("created_at", info.created_at.to_rfc3339())?;
+        dict.set_item("expires_at", info.expires_at.to_rfc3339())?;
+        dict.set_item("revoked_at", info.revoked_at.map(|t| t.to_rfc3339()))?;
+        dict.set_item("sliding", info.sliding)?;
+        dict.set_item("max_expires_at", info.max_expires_at.map(|t| t.to_rfc3339()))?;
+        Ok(dict)
+    }
+}
+
+/// A new session as `{"token": ..., "session": {...}}`.
+struct PyNewSession(NewSession);
+
+impl<'py> IntoPyObject<'py> for PyNewSession {
+    type Target = PyDict;
+    type Output = Bound<'py, PyDict>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> PyResult<Self::Output> {
+        let dict = PyDict::new(py);
+        dict.set_item("token", self.0.token)?;
+        dict.set_item("session", PySessionInfo(self.0.session))?;
+        Ok(dict)
+    }
+}
+
 // ── Python class ─────────────────────────────────────────────────
 
 /// Native Authy client backed by the Rust vault engine.
@@ -66,38 +234,37 @@ impl PyAuthy {
     /// Authenticate with one of:
     /// - `passphrase="..."` — vault passphrase
     /// - `keyfile="/path/to/key.age"` — age keyfile
-    /// - `from_env=True` — read AUTHY_KEYFILE or AUTHY_PASSPHRASE from env
+    /// - `token="authy_v1...", keyfile=...` — session token (scoped, with the token's capabilities)
+    /// - `from_env=True` — read AUTHY_KEYFILE or AUTHY_PASSPHRASE from env,
+    ///   falling back to the keyfile configured in .authy.toml or authy.toml
+    ///
+    /// `cache_ttl=` (seconds) keeps the decrypted vault in memory between
+    /// calls, reloading it when the vault file changes or the TTL runs out.
     #[new]
-    #[pyo3(signature = (*, passphrase=None, keyfile=None, from_env=false))]
+    #[pyo3(signature = (*, passphrase=None, keyfile=None, token=None, from_env=false, cache_ttl=None))]
     fn new(
         passphrase: Option<&str>,
         keyfile: Option<&str>,
+        token: Option<&str>,
         from_env: bool,
+        cache_ttl: Option<f64>,
     ) -> PyResult<Self> {
-        let client = if let Some(pass) = passphrase {
-            AuthyClient::with_passphrase(pass).map_err(to_py_err)?
-        } else if let Some(kf) = keyfile {
-            AuthyClient::with_keyfile(kf).map_err(to_py_err)?
-        } else if from_env {
-            AuthyClient::from_env().map_err(to_py_err)?
-        } else {
-            return Err(AuthyException::new_err(
-                "Provide passphrase=, keyfile=, or from_env=True",
-            ));
-        };
-        Ok(Self { client })
+        Ok(Self {
+            client: connect(passphrase, keyfile, token, from_env, cache_ttl)?,
+        })
     }
 
-    /// Retrieve a secret value. Raises SecretNotFound if missing.
-    fn get(&self, name: &str) -> PyResult<String> {
-        self.client
-            .get_or_err(name)
-            .map_err(to_py_err)
+    /// Retrieve a secret value. Raises SecretNotFound if missing, and an
+    /// error for an expired secret unless allow_expired=True.
+    #[pyo3(signature = (name, allow_expired=false))]
+    fn get(&self, name: &str, allow_expired: bool) -> PyResult<String> {
+        get_required(&self.client, name, allow_expired).map_err(to_py_err)
     }
 
     /// Retrieve a secret value, returning None if not found.
-    fn get_or_none(&self, name: &str) -> PyResult<Option<String>> {
-        self.client.get(name).map_err(to_py_err)
+    #[pyo3(signature = (name, allow_expired=false))]
+    fn get_or_none(&self, name: &str, allow_expired: bool) -> PyResult<Option<String>> {
+        get_value(&self.client, name, allow_expired).map_err(to_py_err)
     }
 
     /// Store a secret. Raises SecretAlreadyExists unless force=True.
@@ -122,6 +289,26 @@ impl PyAuthy {
         self.client.list(scope).map_err(to_py_err)
     }
 
+    /// List secrets with their metadata (no values), sorted by name.
+    #[pyo3(signature = (scope=None))]
+    fn list_detailed(&self, scope: Option<&str>) -> PyResult<Vec<PySecretInfo>> {
+        self.client.list_detailed(scope).map(py_infos).map_err(to_py_err)
+    }
+
+    /// A secret's metadata as a dict (no value), or None if not found.
+    fn get_metadata(&self, name: &str) -> PyResult<Option<PySecretInfo>> {
+        self.client
+            .get_metadata(name)
+            .map(|info| info.map(PySecretInfo))
+            .map_err(to_py_err)
+    }
+
+    /// Replace a secret's tags.
+    fn set_tags(&self, name: &str, tags: Vec<String>) -> PyResult<()> {
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        self.client.set_tags(name, &tags).map_err(to_py_err)
+    }
+
     /// Build an environment variable map from secrets matching a policy scope.
     ///
     /// Args:
@@ -145,15 +332,454 @@ impl PyAuthy {
         self.client.test_policy(scope, secret_name).map_err(to_py_err)
     }
 
+    /// Create a policy. Raises an error if one with that name exists.
+    #[pyo3(signature = (name, allow, deny=Vec::new(), description=None, run_only=false))]
+    fn create_policy(
+        &self,
+        name: &str,
+        allow: Vec<String>,
+        deny: Vec<String>,
+        description: Option<&str>,
+        run_only: bool,
+    ) -> PyResult<()> {
+        self.client
+            .create_policy(name, allow, deny, description, run_only)
+            .map_err(to_py_err)
+    }
+
+    /// List all policies as dicts, sorted by name.
+    fn list_policies(&self) -> PyResult<Vec<PyPolicy>> {
+        self.client.list_policies().map(py_policies).map_err(to_py_err)
+    }
+
+    /// A policy as a dict, or None if not found. With effective=True,
+    /// rules inherited through `extends` are merged in.
+    #[pyo3(signature = (name, effective=false))]
+    fn get_policy(&self, name: &str, effective: bool) -> PyResult<Option<PyPolicy>> {
+        self.client
+            .get_policy(name, effective)
+            .map(|p| p.map(PyPolicy))
+            .map_err(to_py_err)
+    }
+
+    /// Change a policy; omitted arguments keep their value. Returns the
+    /// revision number the previous version was saved as.
+    #[pyo3(signature = (name, *, allow=None, deny=None, description=None, run_only=None, count_only=None, extends=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn update_policy(
+        &self,
+        name: &str,
+        allow: Option<Vec<String>>,
+        deny: Option<Vec<String>>,
+        description: Option<String>,
+        run_only: Option<bool>,
+        count_only: Option<bool>,
+        extends: Option<Vec<String>>,
+    ) -> PyResult<u32> {
+        let update = PolicyUpdate {
+            allow,
+            deny,
+            description,
+            run_only,
+            count_only,
+            extends,
+        };
+        self.client.update_policy(name, &update).map_err(to_py_err)
+    }
+
+    /// Remove a policy. Raises PolicyNotFound if missing.
+    fn delete_policy(&self, name: &str) -> PyResult<()> {
+        self.client.delete_policy(name).map_err(to_py_err)
+    }
+
+    /// Create a session token for a policy scope, valid for `ttl` seconds.
+    /// Returns `{"token": ..., "session": {...}}`; the token is not shown again.
+    #[pyo3(signature = (scope, ttl, label=None, run_only=false))]
+    fn create_session(
+        &self,
+        scope: &str,
+        ttl: f64,
+        label: Option<&str>,
+        run_only: bool,
+    ) -> PyResult<PyNewSession> {
+        self.client
+            .create_session(scope, seconds("ttl", ttl)?, label, run_only)
+            .map(PyNewSession)
+            .map_err(to_py_err)
+    }
+
+    /// List all sessions as dicts, including expired and revoked ones.
+    fn list_sessions(&self) -> PyResult<Vec<PySessionInfo>> {
+        self.client
+            .list_sessions()
+            .map(|s| s.into_iter().map(PySessionInfo).collect())
+            .map_err(to_py_err)
+    }
+
+    /// Revoke a session by ID.
+    fn revoke_session(&self, id: &str) -> PyResult<()> {
+        self.client.revoke_session(id).map_err(to_py_err)
+    }
+
+    /// Check a session token and return its session. Raises AuthyError for
+    /// invalid, revoked or expired tokens.
+    fn validate_token(&self, token: &str) -> PyResult<PySessionInfo> {
+        self.client
+            .validate_token(token)
+            .map(PySessionInfo)
+            .map_err(to_py_err)
+    }
+
+    /// Return the names a policy allows, in input order (one vault read).
+    fn filter_secrets(&self, scope: &str, names: Vec<String>) -> PyResult<Vec<String>> {
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.client.filter_secrets(scope, &names).map_err(to_py_err)
+    }
+
     /// Initialize a new vault.
     fn init_vault(&self) -> PyResult<()> {
         self.client.init_vault().map_err(to_py_err)
     }
 
+    /// Drop the cached vault (see `cache_ttl=`) so the next call reads it again.
+    fn refresh(&self) {
+        self.client.refresh();
+    }
+
     /// Check whether a vault is initialized (static, no auth needed).
+    ///
+    /// Args:
+    ///     path: Authy directory to check instead of AUTHY_HOME or ~/.authy
+    #[staticmethod]
+    #[pyo3(signature = (path=None))]
+    fn is_initialized(path: Option<&str>) -> bool {
+        match path {
+            Some(dir) => AuthyClient::is_initialized_at(dir),
+            None => AuthyClient::is_initialized(),
+        }
+    }
+
+    /// Where the vault is, or would be created by init_vault(): a file
+    /// path, or a URL for remote storage (static, no auth needed).
+    ///
+    /// Args:
+    ///     path: Authy directory to use instead of AUTHY_HOME or ~/.authy
     #[staticmethod]
-    fn is_initialized() -> bool {
-        AuthyClient::is_initialized()
+    #[pyo3(signature = (path=None))]
+    fn vault_path(path: Option<&str>) -> PyResult<String> {
+        match path {
+            Some(dir) => AuthyClient::vault_location_at(dir),
+            None => AuthyClient::vault_location(),
+        }
+        .map_err(to_py_err)
+    }
+}
+
+// ── Async Python class ───────────────────────────────────────────
+
+/// Run a vault call on the event loop's default executor and return the
+/// asyncio future for its result. The call runs without the GIL. Executor
+/// threads are Python threads that `asyncio.run` joins on shutdown, so no
+/// call can still be touching Python while the interpreter finalizes.
+fn run_in_executor<'py, T, F>(py: Python<'py>, f: F) -> PyResult<Bound<'py, PyAny>>
+where
+    T: for<'a> IntoPyObject<'a> + Send,
+    F: FnOnce() -> authy::error::Result<T> + Send + 'static,
+{
+    let call = Mutex::new(Some(f));
+    let func = PyCFunction::new_closure(py, None, None, move |args, _kwargs| {
+        let py = args.py();
+        let f = call
+            .lock()
+            .ok()
+            .and_then(|mut f| f.take())
+            .ok_or_else(|| AuthyException::new_err("AsyncAuthy call already ran"))?;
+        py.allow_threads(f).map_err(to_py_err)?.into_py_any(py)
+    })?;
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    event_loop.call_method1("run_in_executor", (py.None(), func))
+}
+
+/// Asyncio Authy client. Vault reads, writes and decryption run on the
+/// event loop's default executor, so they don't block the loop.
+#[pyclass(name = "AsyncAuthy", frozen)]
+struct PyAsyncAuthy {
+    client: Arc<AuthyClient>,
+}
+
+#[pymethods]
+impl PyAsyncAuthy {
+    /// Create a new async client. Takes the same arguments as `Authy`.
+    #[new]
+    #[pyo3(signature = (*, passphrase=None, keyfile=None, token=None, from_env=false, cache_ttl=None))]
+    fn new(
+        passphrase: Option<&str>,
+        keyfile: Option<&str>,
+        token: Option<&str>,
+        from_env: bool,
+        cache_ttl: Option<f64>,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            client: Arc::new(connect(passphrase, keyfile, token, from_env, cache_ttl)?),
+        })
+    }
+
+    /// Retrieve a secret value. Raises SecretNotFound if missing, and an
+    /// error for an expired secret unless allow_expired=True.
+    #[pyo3(signature = (name, allow_expired=false))]
+    fn get<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        allow_expired: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || get_required(&client, &name, allow_expired))
+    }
+
+    /// Retrieve a secret value, returning None if not found.
+    #[pyo3(signature = (name, allow_expired=false))]
+    fn get_or_none<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        allow_expired: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || get_value(&client, &name, allow_expired))
+    }
+
+    /// Store a secret. Raises SecretAlreadyExists unless force=True.
+    #[pyo3(signature = (name, value, force=false))]
+    fn store<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        value: String,
+        force: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.store(&name, &value, force))
+    }
+
+    /// Remove a secret. Returns True if it existed.
+    fn remove<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.remove(&name))
+    }
+
+    /// Rotate a secret to a new value. Returns the new version number.
+    fn rotate<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        new_value: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.rotate(&name, &new_value))
+    }
+
+    /// List secret names, optionally filtered by a policy scope.
+    #[pyo3(signature = (scope=None))]
+    fn list<'py>(&self, py: Python<'py>, scope: Option<String>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.list(scope.as_deref()))
+    }
+
+    /// List secrets with their metadata (no values), sorted by name.
+    #[pyo3(signature = (scope=None))]
+    fn list_detailed<'py>(
+        &self,
+        py: Python<'py>,
+        scope: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.list_detailed(scope.as_deref()).map(py_infos))
+    }
+
+    /// A secret's metadata as a dict (no value), or None if not found.
+    fn get_metadata<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            client.get_metadata(&name).map(|info| info.map(PySecretInfo))
+        })
+    }
+
+    /// Replace a secret's tags.
+    fn set_tags<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        tags: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+            client.set_tags(&name, &tags)
+        })
+    }
+
+    /// Build an environment variable map from secrets matching a policy scope.
+    #[pyo3(signature = (scope, uppercase=true, replace_dash=Some('_')))]
+    fn build_env_map<'py>(
+        &self,
+        py: Python<'py>,
+        scope: String,
+        uppercase: bool,
+        replace_dash: Option<char>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.build_env_map(&scope, uppercase, replace_dash))
+    }
+
+    /// Test whether a policy allows access to a secret.
+    fn test_policy<'py>(
+        &self,
+        py: Python<'py>,
+        scope: String,
+        secret_name: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.test_policy(&scope, &secret_name))
+    }
+
+    /// Create a policy. Raises an error if one with that name exists.
+    #[pyo3(signature = (name, allow, deny=Vec::new(), description=None, run_only=false))]
+    fn create_policy<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        allow: Vec<String>,
+        deny: Vec<String>,
+        description: Option<String>,
+        run_only: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            client.create_policy(&name, allow, deny, description.as_deref(), run_only)
+        })
+    }
+
+    /// List all policies as dicts, sorted by name.
+    fn list_policies<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.list_policies().map(py_policies))
+    }
+
+    /// A policy as a dict, or None if not found. With effective=True,
+    /// rules inherited through `extends` are merged in.
+    #[pyo3(signature = (name, effective=false))]
+    fn get_policy<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        effective: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            client.get_policy(&name, effective).map(|p| p.map(PyPolicy))
+        })
+    }
+
+    /// Change a policy; omitted arguments keep their value. Returns the
+    /// revision number the previous version was saved as.
+    #[pyo3(signature = (name, *, allow=None, deny=None, description=None, run_only=None, count_only=None, extends=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn update_policy<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        allow: Option<Vec<String>>,
+        deny: Option<Vec<String>>,
+        description: Option<String>,
+        run_only: Option<bool>,
+        count_only: Option<bool>,
+        extends: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let update = PolicyUpdate {
+            allow,
+            deny,
+            description,
+            run_only,
+            count_only,
+            extends,
+        };
+        run_in_executor(py, move || client.update_policy(&name, &update))
+    }
+
+    /// Remove a policy. Raises PolicyNotFound if missing.
+    fn delete_policy<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.delete_policy(&name))
+    }
+
+    /// Create a session token for a policy scope, valid for `ttl` seconds.
+    /// Returns `{"token": ..., "session": {...}}`; the token is not shown again.
+    #[pyo3(signature = (scope, ttl, label=None, run_only=false))]
+    fn create_session<'py>(
+        &self,
+        py: Python<'py>,
+        scope: String,
+        ttl: f64,
+        label: Option<String>,
+        run_only: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        let ttl = seconds("ttl", ttl)?;
+        run_in_executor(py, move || {
+            client
+                .create_session(&scope, ttl, label.as_deref(), run_only)
+                .map(PyNewSession)
+        })
+    }
+
+    /// List all sessions as dicts, including expired and revoked ones.
+    fn list_sessions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            client
+                .list_sessions()
+                .map(|s| s.into_iter().map(PySessionInfo).collect::<Vec<_>>())
+        })
+    }
+
+    /// Revoke a session by ID.
+    fn revoke_session<'py>(&self, py: Python<'py>, id: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.revoke_session(&id))
+    }
+
+    /// Check a session token and return its session. Raises AuthyError for
+    /// invalid, revoked or expired tokens.
+    fn validate_token<'py>(&self, py: Python<'py>, token: String) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.validate_token(&token).map(PySessionInfo))
+    }
+
+    /// Return the names a policy allows, in input order (one vault read).
+    fn filter_secrets<'py>(
+        &self,
+        py: Python<'py>,
+        scope: String,
+        names: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            client.filter_secrets(&scope, &names)
+        })
+    }
+
+    /// Initialize a new vault.
+    fn init_vault<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let client = self.client.clone();
+        run_in_executor(py, move || client.init_vault())
+    }
+
+    /// Drop the cached vault (see `cache_ttl=`) so the next call reads it again.
+    fn refresh(&self) {
+        self.client.refresh();
     }
 }
 
@@ -163,6 +789,7 @@ impl PyAuthy {
 #[pymodule]
 fn _native(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyAuthy>()?;
+    m.add_class::<PyAsyncAuthy>()?;
     m.add("AuthyException", m.py().get_type::<AuthyException>())?;
     m.add("SecretNotFound", m.py().get_type::<SecretNotFound>())?;
     m.add("SecretAlreadyExists", m.py().get_type::<SecretAlreadyExists>())?;
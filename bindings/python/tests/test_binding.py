@@ -1,5 +1,6 @@
 """Tests for the authy_cli native binding."""
 
+import asyncio
 import os
 import tempfile
 
@@ -100,6 +101,14 @@ class TestAuthy:
         client.init_vault()
         assert Authy.is_initialized() is True
 
+    def test_vault_path(self):
+        from authy_cli import Authy
+
+        assert Authy.vault_path() == os.path.join(self._home, ".authy", "vault.age")
+        other = os.path.join(self._home, "other")
+        assert Authy.vault_path(path=other) == os.path.join(other, "vault.age")
+        assert Authy.is_initialized(path=other) is False
+
     def test_build_env_map(self):
         from authy_cli import Authy
 
@@ -112,6 +121,78 @@ class TestAuthy:
         # We need create_policy — not exposed, so test via build_env_map
         # This test will need a policy; skip if create_policy not available
 
+    def test_filter_secrets_unknown_scope_raises(self):
+        from authy_cli import Authy, AuthyError
+
+        client = Authy(passphrase="test-pass")
+        client.init_vault()
+        with pytest.raises(AuthyError):
+            client.filter_secrets("nope", ["a", "b"])
+
+    def test_metadata_and_tags(self):
+        from authy_cli import Authy
+
+        client = Authy(passphrase="test-pass")
+        client.init_vault()
+        client.store("api-key", "sk-1")
+        client.rotate("api-key", "sk-2")
+        client.set_tags("api-key", ["prod", "billing"])
+        info = client.get_metadata("api-key")
+        assert info["version"] == 2
+        assert info["tags"] == ["prod", "billing"]
+        assert info["provenance"]["method"] == "rotate"
+        assert "value" not in info
+        assert client.get_metadata("missing") is None
+        assert [i["name"] for i in client.list_detailed()] == ["api-key"]
+
+    def test_policy_crud(self):
+        from authy_cli import Authy, PolicyNotFound
+
+        client = Authy(passphrase="test-pass")
+        client.init_vault()
+        client.create_policy("app", ["app-*"], description="App")
+        assert client.update_policy("app", deny=["app-admin*"]) == 1
+        policy = client.get_policy("app")
+        assert policy["allow"] == ["app-*"]
+        assert policy["deny"] == ["app-admin*"]
+        assert policy["description"] == "App"
+        assert [p["name"] for p in client.list_policies()] == ["app"]
+        client.delete_policy("app")
+        assert client.get_policy("app") is None
+        with pytest.raises(PolicyNotFound):
+            client.delete_policy("app")
+
+    def test_session_lifecycle(self):
+        from authy_cli import Authy, AuthyError
+
+        client = Authy(passphrase="test-pass")
+        client.init_vault()
+        client.create_policy("app", ["app-*"])
+        created = client.create_session("app", 3600, label="ci")
+        assert created["token"].startswith("authy_v1.")
+        session = client.validate_token(created["token"])
+        assert session["id"] == created["session"]["id"]
+        assert session["label"] == "ci"
+        assert session["status"] == "active"
+        assert [s["id"] for s in client.list_sessions()] == [session["id"]]
+        client.revoke_session(session["id"])
+        assert client.list_sessions()[0]["status"] == "revoked"
+        with pytest.raises(AuthyError):
+            client.validate_token(created["token"])
+
+    def test_cached_client_sees_other_writes(self):
+        from authy_cli import Authy
+
+        writer = Authy(passphrase="test-pass")
+        writer.init_vault()
+        writer.store("api-key", "v1")
+        cached = Authy(passphrase="test-pass", cache_ttl=300)
+        assert cached.get("api-key") == "v1"
+        writer.rotate("api-key", "v2-longer")
+        assert cached.get("api-key") == "v2-longer"
+        cached.refresh()
+        assert cached.list() == ["api-key"]
+
     def test_no_credentials_raises(self):
         from authy_cli import Authy, AuthyError
 
@@ -119,3 +200,37 @@ class TestAuthy:
         os.environ.pop("AUTHY_KEYFILE", None)
         with pytest.raises(AuthyError):
             Authy(from_env=True)
+
+
+class TestAsyncAuthy:
+    """Tests for the asyncio client."""
+
+    def setup_method(self):
+        self._home = _isolated_home()
+
+    def test_store_and_get(self):
+        from authy_cli import AsyncAuthy
+
+        async def scenario():
+            client = AsyncAuthy(passphrase="test-pass")
+            await client.init_vault()
+            await client.store("api-key", "sk-secret-123")
+            value, missing = await asyncio.gather(
+                client.get("api-key"), client.get_or_none("nonexistent")
+            )
+            assert value == "sk-secret-123"
+            assert missing is None
+            assert await client.list() == ["api-key"]
+
+        asyncio.run(scenario())
+
+    def test_get_not_found(self):
+        from authy_cli import AsyncAuthy, SecretNotFound
+
+        async def scenario():
+            client = AsyncAuthy(passphrase="test-pass")
+            await client.init_vault()
+            with pytest.raises(SecretNotFound):
+                await client.get("nonexistent")
+
+        asyncio.run(scenario())
@@ -0,0 +1,336 @@
+//! [`AuthyClientAsync`]: [`AuthyClient`] for async code.
+//!
+//! Every vault operation is file I/O plus age decryption, so the blocking
+//! client would stall the executor thread it runs on. This wrapper moves
+//! each call onto tokio's blocking pool with
+//! [`spawn_blocking`](tokio::task::spawn_blocking) and awaits the result.
+//! It needs a tokio runtime and the `tokio` feature.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{
+    AuthyClient, GetOptions, NewSession, PolicyUpdate, SecretInfo, SessionInfo, StoreOptions,
+};
+use crate::audit::AuditEntry;
+use crate::error::{AuthyError, Result};
+use crate::policy::Policy;
+
+/// Async counterpart of [`AuthyClient`]. Cheap to clone: clones share one
+/// client. Methods take borrowed arguments and copy them for the blocking
+/// task, so the returned futures are `'static` and `Send`.
+///
+/// ```no_run
+/// # async fn demo() -> authy::error::Result<()> {
+/// use authy::api::AuthyClientAsync;
+///
+/// let client = AuthyClientAsync::with_passphrase("my-vault-passphrase").await?;
+/// let value = client.get("api-key").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AuthyClientAsync {
+    inner: Arc<AuthyClient>,
+}
+
+impl From<AuthyClient> for AuthyClientAsync {
+    fn from(client: AuthyClient) -> Self {
+        Self::new(client)
+    }
+}
+
+impl AuthyClientAsync {
+    /// Wrap a client, e.g. one configured with
+    /// [`with_home`](AuthyClient::with_home) or
+    /// [`with_actor`](AuthyClient::with_actor).
+    pub fn new(client: AuthyClient) -> Self {
+        Self {
+            inner: Arc::new(client),
+        }
+    }
+
+    /// The wrapped client, for calls that don't need to leave the current
+    /// thread or have no async counterpart.
+    pub fn blocking(&self) -> &AuthyClient {
+        &self.inner
+    }
+
+    /// See [`AuthyClient::with_passphrase`].
+    pub async fn with_passphrase(passphrase: &str) -> Result<Self> {
+        let passphrase = passphrase.to_string();
+        unblock(move || AuthyClient::with_passphrase(&passphrase))
+            .await
+            .map(Self::new)
+    }
+
+    /// See [`AuthyClient::with_keyfile`].
+    pub async fn with_keyfile(keyfile_path: &str) -> Result<Self> {
+        let keyfile_path = keyfile_path.to_string();
+        unblock(move || AuthyClient::with_keyfile(&keyfile_path))
+            .await
+            .map(Self::new)
+    }
+
+    /// See [`AuthyClient::with_token`].
+    pub async fn with_token(token: &str, keyfile_path: &str) -> Result<Self> {
+        let (token, keyfile_path) = (token.to_string(), keyfile_path.to_string());
+        unblock(move || AuthyClient::with_token(&token, &keyfile_path))
+            .await
+            .map(Self::new)
+    }
+
+    /// See [`AuthyClient::from_env`].
+    pub async fn from_env() -> Result<Self> {
+        unblock(AuthyClient::from_env).await.map(Self::new)
+    }
+
+    /// See [`AuthyClient::is_initialized`].
+    pub async fn is_initialized() -> bool {
+        unblock(|| Ok(AuthyClient::is_initialized()))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// See [`AuthyClient::is_initialized_at`].
+    pub async fn is_initialized_at(dir: impl Into<PathBuf>) -> bool {
+        let dir = dir.into();
+        unblock(move || Ok(AuthyClient::is_initialized_at(dir)))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// See [`AuthyClient::get`].
+    pub async fn get(&self, name: &str) -> Result<Option<String>> {
+        let name = name.to_string();
+        self.run(move |c| c.get(&name)).await
+    }
+
+    /// See [`AuthyClient::get_with_options`].
+    pub async fn get_with_options(
+        &self,
+        name: &str,
+        options: &GetOptions,
+    ) -> Result<Option<String>> {
+        let (name, options) = (name.to_string(), options.clone());
+        self.run(move |c| c.get_with_options(&name, &options)).await
+    }
+
+    /// See [`AuthyClient::get_or_err`].
+    pub async fn get_or_err(&self, name: &str) -> Result<String> {
+        let name = name.to_string();
+        self.run(move |c| c.get_or_err(&name)).await
+    }
+
+    /// See [`AuthyClient::get_first`].
+    pub async fn get_first(&self, names: &[&str]) -> Result<Option<String>> {
+        let names = owned(names);
+        self.run(move |c| c.get_first(&borrowed(&names))).await
+    }
+
+    /// See [`AuthyClient::get_many`].
+    pub async fn get_many(&self, names: &[&str]) -> Result<Vec<(String, Result<String>)>> {
+        let names = owned(names);
+        self.run(move |c| c.get_many(&borrowed(&names))).await
+    }
+
+    /// See [`AuthyClient::store`].
+    pub async fn store(&self, name: &str, value: &str, force: bool) -> Result<()> {
+        let (name, value) = (name.to_string(), value.to_string());
+        self.run(move |c| c.store(&name, &value, force)).await
+    }
+
+    /// See [`AuthyClient::store_with_options`].
+    pub async fn store_with_options(
+        &self,
+        name: &str,
+        value: &str,
+        options: &StoreOptions,
+    ) -> Result<()> {
+        let (name, value, options) = (name.to_string(), value.to_string(), options.clone());
+        self.run(move |c| c.store_with_options(&name, &value, &options))
+            .await
+    }
+
+    /// See [`AuthyClient::remove`].
+    pub async fn remove(&self, name: &str) -> Result<bool> {
+        let name = name.to_string();
+        self.run(move |c| c.remove(&name)).await
+    }
+
+    /// See [`AuthyClient::rotate`].
+    pub async fn rotate(&self, name: &str, new_value: &str) -> Result<u32> {
+        let (name, new_value) = (name.to_string(), new_value.to_string());
+        self.run(move |c| c.rotate(&name, &new_value)).await
+    }
+
+    /// See [`AuthyClient::list`].
+    pub async fn list(&self, scope: Option<&str>) -> Result<Vec<String>> {
+        let scope = scope.map(str::to_string);
+        self.run(move |c| c.list(scope.as_deref())).await
+    }
+
+    /// See [`AuthyClient::list_detailed`].
+    pub async fn list_detailed(&self, scope: Option<&str>) -> Result<Vec<SecretInfo>> {
+        let scope = scope.map(str::to_string);
+        self.run(move |c| c.list_detailed(scope.as_deref())).await
+    }
+
+    /// See [`AuthyClient::get_metadata`].
+    pub async fn get_metadata(&self, name: &str) -> Result<Option<SecretInfo>> {
+        let name = name.to_string();
+        self.run(move |c| c.get_metadata(&name)).await
+    }
+
+    /// See [`AuthyClient::set_tags`].
+    pub async fn set_tags(&self, name: &str, tags: &[&str]) -> Result<()> {
+        let (name, tags) = (name.to_string(), owned(tags));
+        self.run(move |c| c.set_tags(&name, &borrowed(&tags))).await
+    }
+
+    /// See [`AuthyClient::count`].
+    pub async fn count(&self, scope: Option<&str>) -> Result<usize> {
+        let scope = scope.map(str::to_string);
+        self.run(move |c| c.count(scope.as_deref())).await
+    }
+
+    /// See [`AuthyClient::init_vault`].
+    pub async fn init_vault(&self) -> Result<()> {
+        self.run(|c| c.init_vault()).await
+    }
+
+    /// See [`AuthyClient::audit_entries`].
+    pub async fn audit_entries(&self) -> Result<Vec<AuditEntry>> {
+        self.run(|c| c.audit_entries()).await
+    }
+
+    /// See [`AuthyClient::verify_audit_chain`].
+    pub async fn verify_audit_chain(&self) -> Result<(usize, bool)> {
+        self.run(|c| c.verify_audit_chain()).await
+    }
+
+    /// See [`AuthyClient::test_policy`].
+    pub async fn test_policy(&self, scope: &str, secret_name: &str) -> Result<bool> {
+        let (scope, secret_name) = (scope.to_string(), secret_name.to_string());
+        self.run(move |c| c.test_policy(&scope, &secret_name)).await
+    }
+
+    /// See [`AuthyClient::create_policy`].
+    pub async fn create_policy(
+        &self,
+        name: &str,
+        allow: Vec<String>,
+        deny: Vec<String>,
+        description: Option<&str>,
+        run_only: bool,
+    ) -> Result<()> {
+        let (name, description) = (name.to_string(), description.map(str::to_string));
+        self.run(move |c| c.create_policy(&name, allow, deny, description.as_deref(), run_only))
+            .await
+    }
+
+    /// See [`AuthyClient::list_policies`].
+    pub async fn list_policies(&self) -> Result<Vec<Policy>> {
+        self.run(|c| c.list_policies()).await
+    }
+
+    /// See [`AuthyClient::get_policy`].
+    pub async fn get_policy(&self, name: &str, effective: bool) -> Result<Option<Policy>> {
+        let name = name.to_string();
+        self.run(move |c| c.get_policy(&name, effective)).await
+    }
+
+    /// See [`AuthyClient::update_policy`].
+    pub async fn update_policy(&self, name: &str, update: &PolicyUpdate) -> Result<u32> {
+        let (name, update) = (name.to_string(), update.clone());
+        self.run(move |c| c.update_policy(&name, &update)).await
+    }
+
+    /// See [`AuthyClient::delete_policy`].
+    pub async fn delete_policy(&self, name: &str) -> Result<()> {
+        let name = name.to_string();
+        self.run(move |c| c.delete_policy(&name)).await
+    }
+
+    /// See [`AuthyClient::create_session`].
+    pub async fn create_session(
+        &self,
+        scope: &str,
+        ttl: Duration,
+        label: Option<&str>,
+        run_only: bool,
+    ) -> Result<NewSession> {
+        let (scope, label) = (scope.to_string(), label.map(str::to_string));
+        self.run(move |c| c.create_session(&scope, ttl, label.as_deref(), run_only))
+            .await
+    }
+
+    /// See [`AuthyClient::list_sessions`].
+    pub async fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
+        self.run(|c| c.list_sessions()).await
+    }
+
+    /// See [`AuthyClient::revoke_session`].
+    pub async fn revoke_session(&self, id: &str) -> Result<()> {
+        let id = id.to_string();
+        self.run(move |c| c.revoke_session(&id)).await
+    }
+
+    /// See [`AuthyClient::validate_token`].
+    pub async fn validate_token(&self, token: &str) -> Result<SessionInfo> {
+        let token = token.to_string();
+        self.run(move |c| c.validate_token(&token)).await
+    }
+
+    /// See [`AuthyClient::filter_secrets`].
+    pub async fn filter_secrets(&self, scope: &str, names: &[&str]) -> Result<Vec<String>> {
+        let (scope, names) = (scope.to_string(), owned(names));
+        self.run(move |c| c.filter_secrets(&scope, &borrowed(&names)))
+            .await
+    }
+
+    /// See [`AuthyClient::build_env_map`].
+    pub async fn build_env_map(
+        &self,
+        scope: &str,
+        uppercase: bool,
+        replace_dash: Option<char>,
+    ) -> Result<HashMap<String, String>> {
+        let scope = scope.to_string();
+        self.run(move |c| c.build_env_map(&scope, uppercase, replace_dash))
+            .await
+    }
+
+    /// Run `f` against the client on the blocking pool.
+    async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&AuthyClient) -> Result<T> + Send + 'static,
+    {
+        let client = Arc::clone(&self.inner);
+        unblock(move || f(&client)).await
+    }
+}
+
+/// Run `f` on tokio's blocking pool. A panic in `f` resumes on the caller.
+async fn unblock<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => Err(AuthyError::Other(format!("Vault task was cancelled: {}", e))),
+    }
+}
+
+fn owned(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn borrowed(names: &[String]) -> Vec<&str> {
+    names.iter().map(String::as_str).collect()
+}
@@ -0,0 +1,213 @@
+//! [`Batch`]: several changes to the vault applied as one transaction.
+//!
+//! Each [`AuthyClient`] write loads, re-encrypts and rewrites the whole
+//! vault, so storing 50 secrets one at a time does that 50 times. A batch
+//! stages the changes in memory and applies them with a single load and
+//! save, recorded as a single `batch` audit entry.
+
+use super::{AuthyClient, StoreOptions};
+use crate::audit;
+use crate::config::LimitsConfig;
+use crate::error::Result;
+use crate::session::Capability;
+
+enum Staged {
+    Store {
+        name: String,
+        value: String,
+        options: StoreOptions,
+    },
+    Rotate {
+        name: String,
+        value: String,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+impl Staged {
+    fn capability(&self) -> Capability {
+        match self {
+            Staged::Store { .. } | Staged::Remove { .. } => Capability::Write,
+            Staged::Rotate { .. } => Capability::Rotate,
+        }
+    }
+}
+
+/// One change made by [`Batch::commit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchChange {
+    /// `store`, `update` (a forced store over an existing secret), `rotate`
+    /// or `remove`.
+    pub op: &'static str,
+    pub name: String,
+    /// The new version, for `rotate`.
+    pub version: Option<u32>,
+}
+
+/// Changes staged with [`AuthyClient::batch`].
+///
+/// Nothing touches the vault until [`commit`](Self::commit). The changes
+/// then run in the order they were staged, with the same checks as the
+/// single-secret methods, and either all of them are saved or none are.
+///
+/// ```no_run
+/// # fn demo(client: &authy::api::AuthyClient) -> authy::error::Result<()> {
+/// let changes = client
+///     .batch()
+///     .store("db-host", "db.internal", false)
+///     .rotate("db-password", "n3w-pa55")
+///     .remove("old-db-url")
+///     .commit()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Batch<'a> {
+    client: &'a AuthyClient,
+    staged: Vec<Staged>,
+}
+
+impl<'a> Batch<'a> {
+    pub(super) fn new(client: &'a AuthyClient) -> Self {
+        Self {
+            client,
+            staged: Vec::new(),
+        }
+    }
+
+    /// Stage [`AuthyClient::store`].
+    pub fn store(&mut self, name: &str, value: &str, force: bool) -> &mut Self {
+        self.store_with_options(
+            name,
+            value,
+            &StoreOptions {
+                force,
+                ..StoreOptions::default()
+            },
+        )
+    }
+
+    /// Stage [`AuthyClient::store_with_options`].
+    pub fn store_with_options(
+        &mut self,
+        name: &str,
+        value: &str,
+        options: &StoreOptions,
+    ) -> &mut Self {
+        self.staged.push(Staged::Store {
+            name: name.to_string(),
+            value: value.to_string(),
+            options: options.clone(),
+        });
+        self
+    }
+
+    /// Stage [`AuthyClient::rotate`]. The secret must exist when the batch
+    /// is committed, which includes being stored earlier in the batch.
+    pub fn rotate(&mut self, name: &str, new_value: &str) -> &mut Self {
+        self.staged.push(Staged::Rotate {
+            name: name.to_string(),
+            value: new_value.to_string(),
+        });
+        self
+    }
+
+    /// Stage [`AuthyClient::remove`]. Removing a secret that doesn't exist
+    /// is not an error; it just makes no change.
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        self.staged.push(Staged::Remove {
+            name: name.to_string(),
+        });
+        self
+    }
+
+    /// Number of staged changes.
+    pub fn len(&self) -> usize {
+        self.staged.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staged.is_empty()
+    }
+
+    /// Apply the staged changes with one vault load and save, and write one
+    /// `batch` audit entry listing them. If any change fails (a scope
+    /// denial, an existing secret without `force`, a limit...), its error is
+    /// returned and the vault is left as it was. Returns the changes made,
+    /// in order.
+    pub fn commit(&mut self) -> Result<Vec<BatchChange>> {
+        let client = self.client;
+        let _home = client.bind_home();
+        for staged in &self.staged {
+            client.auth_ctx.require(staged.capability())?;
+        }
+        if self.staged.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut v = client.load()?;
+        let limits = LimitsConfig::load()?;
+        let mut changes = Vec::with_capacity(self.staged.len());
+        for staged in &self.staged {
+            match staged {
+                Staged::Store {
+                    name,
+                    value,
+                    options,
+                } => {
+                    let (op, name) = client.stage_store(&mut v, &limits, name, value, options)?;
+                    changes.push(BatchChange {
+                        op,
+                        name,
+                        version: None,
+                    });
+                }
+                Staged::Rotate { name, value } => {
+                    let version = client.stage_rotate(&mut v, &limits, name, value)?;
+                    changes.push(BatchChange {
+                        op: "rotate",
+                        name: name.clone(),
+                        version: Some(version),
+                    });
+                }
+                Staged::Remove { name } => {
+                    if client.stage_remove(&mut v, name)? {
+                        changes.push(BatchChange {
+                            op: "remove",
+                            name: name.clone(),
+                            version: None,
+                        });
+                    }
+                }
+            }
+        }
+        self.staged.clear();
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        client.save(&v)?;
+        client.audit("batch", None, "success", Some(&detail(&changes, &client.audit_key)));
+        Ok(changes)
+    }
+}
+
+/// `changes=N, ops=store:a;rotate:b@v3;...`, with names as
+/// `audit.secret_names` allows (left out entirely when omitted).
+fn detail(changes: &[BatchChange], audit_key: &[u8]) -> String {
+    let count = format!("changes={}", changes.len());
+    let names = match audit::recorded_names(changes.iter().map(|c| c.name.as_str()), audit_key) {
+        Some(names) => names,
+        None => return count,
+    };
+    let ops: Vec<String> = changes
+        .iter()
+        .zip(names)
+        .map(|(change, name)| match change.version {
+            Some(version) => format!("{}:{}@v{}", change.op, name, version),
+            None => format!("{}:{}", change.op, name),
+        })
+        .collect();
+    format!("{}, ops={}", count, ops.join(";"))
+}
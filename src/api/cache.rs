@@ -0,0 +1,81 @@
+//! The decrypted vault kept by a client built
+//! [`with_cache`](super::AuthyClient::with_cache).
+//!
+//! Every uncached call reads the vault and runs the KDF and decryption
+//! again. A long-lived client (the MCP server, a binding object held for a
+//! whole process) can instead reuse the vault it decrypted last, until the
+//! TTL runs out or the vault file changes.
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::Result;
+use crate::vault::{self, Vault};
+
+/// Secret values in a [`Vault`] are zeroized on drop, so dropping the
+/// cached copy on refresh, expiry or with the client clears them.
+pub(super) struct VaultCache {
+    ttl: Duration,
+    state: Mutex<Option<Cached>>,
+}
+
+struct Cached {
+    vault: Vault,
+    loaded_at: Instant,
+    stamp: Option<FileStamp>,
+}
+
+/// Modification time and length of the local vault file. `None` for remote
+/// storage, where only the TTL applies.
+#[derive(PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn current() -> Option<Self> {
+        let meta = fs::metadata(vault::vault_path().ok()?).ok()?;
+        Some(Self {
+            modified: meta.modified().ok()?,
+            len: meta.len(),
+        })
+    }
+}
+
+impl VaultCache {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// A copy of the cached vault if it is still fresh, else one loaded with
+    /// `load` (and cached).
+    pub(super) fn get_or_load(&self, load: impl FnOnce() -> Result<Vault>) -> Result<Vault> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let stamp = FileStamp::current();
+        if let Some(cached) = state.as_ref() {
+            if cached.loaded_at.elapsed() < self.ttl && cached.stamp == stamp {
+                return Ok(cached.vault.clone());
+            }
+        }
+        *state = None;
+
+        let vault = load()?;
+        *state = Some(Cached {
+            vault: vault.clone(),
+            loaded_at: Instant::now(),
+            stamp,
+        });
+        Ok(vault)
+    }
+
+    /// Drop the cached vault; the next call decrypts it again.
+    pub(super) fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = None;
+    }
+}
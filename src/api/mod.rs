@@ -0,0 +1,1402 @@
+//! High-level programmatic API for the Authy vault.
+//!
+//! [`AuthyClient`] provides a simple facade over the vault, handling
+//! load → operate → save → audit in every method call. With the `tokio`
+//! feature, [`AuthyClientAsync`] runs the same calls on tokio's blocking
+//! pool for async callers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::audit;
+use crate::backup;
+use crate::auth::{self, context::AuthContext};
+use crate::config::project::ProjectConfig;
+use crate::config::{Config, LimitsConfig};
+use crate::error::{AuthyError, Result};
+use crate::hooks;
+use crate::policy::{Policy, SecretOp};
+use crate::session::{self, Capability, SessionRecord};
+use crate::subprocess::{self, NamingOptions};
+use crate::vault::{self, Vault, VaultKey};
+use crate::vault::secret::{Provenance, SecretEntry, ValueEncoding};
+
+mod batch;
+mod cache;
+pub use batch::{Batch, BatchChange};
+use cache::VaultCache;
+
+#[cfg(feature = "tokio")]
+mod async_client;
+#[cfg(feature = "tokio")]
+pub use async_client::AuthyClientAsync;
+
+/// Options for [`AuthyClient::store_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct StoreOptions {
+    /// Overwrite the secret if it already exists.
+    pub force: bool,
+    /// When the secret should be rotated by (see
+    /// [`SecretMetadata::expires_at`](crate::vault::secret::SecretMetadata::expires_at)).
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Options for [`AuthyClient::get_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct GetOptions {
+    /// Return the value even if the secret is past its `expires_at`, like
+    /// `authy get --allow-expired`.
+    pub allow_expired: bool,
+}
+
+/// Changes for [`AuthyClient::update_policy`]. Fields left `None` keep
+/// their current value.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyUpdate {
+    pub allow: Option<Vec<String>>,
+    pub deny: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub run_only: Option<bool>,
+    pub count_only: Option<bool>,
+    /// Policies to inherit rules from; `Some(vec![])` clears them.
+    pub extends: Option<Vec<String>>,
+}
+
+/// A secret's metadata, as returned by [`AuthyClient::get_metadata`] and
+/// [`AuthyClient::list_detailed`]. Never includes the value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretInfo {
+    pub name: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Where the current value came from, if recorded.
+    pub provenance: Option<Provenance>,
+    /// Removing the secret needs an approver (see
+    /// [`remove`](AuthyClient::remove)).
+    pub protected: bool,
+}
+
+impl SecretInfo {
+    fn new(name: &str, entry: &SecretEntry) -> Self {
+        let meta = &entry.metadata;
+        Self {
+            name: name.to_string(),
+            version: meta.version,
+            created_at: meta.created_at,
+            modified_at: meta.modified_at,
+            tags: meta.tags.clone(),
+            description: meta.description.clone(),
+            expires_at: meta.expires_at,
+            provenance: meta.provenance.clone(),
+            protected: meta.protected,
+        }
+    }
+}
+
+/// Whether a session's token still works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    Active,
+    Expired,
+    Revoked,
+}
+
+impl SessionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionStatus::Active => "active",
+            SessionStatus::Expired => "expired",
+            SessionStatus::Revoked => "revoked",
+        }
+    }
+}
+
+/// A session, as returned by [`AuthyClient::list_sessions`] and
+/// [`AuthyClient::validate_token`]. Never includes the token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub scope: String,
+    pub label: Option<String>,
+    pub status: SessionStatus,
+    pub run_only: bool,
+    pub capabilities: Vec<Capability>,
+    /// Non-secret variables `authy run` sets alongside the secrets.
+    pub env: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub sliding: bool,
+    /// How far a sliding or refreshed session may be extended.
+    pub max_expires_at: Option<DateTime<Utc>>,
+}
+
+impl SessionInfo {
+    fn new(record: &SessionRecord, now: DateTime<Utc>) -> Self {
+        let status = if record.revoked {
+            SessionStatus::Revoked
+        } else if now > record.expires_at {
+            SessionStatus::Expired
+        } else {
+            SessionStatus::Active
+        };
+        Self {
+            id: record.id.clone(),
+            scope: record.scope.clone(),
+            label: record.label.clone(),
+            status,
+            run_only: record.run_only,
+            capabilities: record.capabilities.clone(),
+            env: record.env.clone(),
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+            revoked_at: record.revoked_at,
+            sliding: record.sliding_ttl_secs.is_some(),
+            max_expires_at: record.max_expires_at,
+        }
+    }
+}
+
+/// A session created with [`AuthyClient::create_session`]. The token is
+/// only ever returned here; the vault keeps just its HMAC.
+#[derive(Debug, Clone)]
+pub struct NewSession {
+    pub token: String,
+    pub session: SessionInfo,
+}
+
+/// High-level client for programmatic vault access.
+///
+/// Each operation loads the vault, performs the mutation, saves it back,
+/// and appends an audit entry — mirroring the CLI handler pattern. A
+/// long-lived client can skip the repeated decryption
+/// [`with_cache`](Self::with_cache).
+///
+/// A client built [`with_token`](Self::with_token) has the same limits as
+/// the CLI under `AUTHY_TOKEN`: it may only do what the token's
+/// capabilities allow (read and list unless created with `--can`), is
+/// confined to the token's scope, and cannot read values when the token or
+/// policy is run-only.
+pub struct AuthyClient {
+    key: VaultKey,
+    /// HMAC key derived from the master material, used for audit chain.
+    audit_key: Vec<u8>,
+    /// Human-readable actor label for audit entries.
+    actor: String,
+    /// Master or session-token context; decides what operations may do.
+    auth_ctx: AuthContext,
+    /// The session token, re-validated on every load so revocation and
+    /// expiry take effect in long-lived clients.
+    token: Option<String>,
+    /// Authy directory bound with [`with_home`](Self::with_home).
+    home: Option<PathBuf>,
+    /// Decrypted vault kept between calls, see [`with_cache`](Self::with_cache).
+    cache: Option<VaultCache>,
+}
+
+impl AuthyClient {
+    /// Authenticate with a passphrase.
+    pub fn with_passphrase(passphrase: &str) -> Result<Self> {
+        let key = VaultKey::Passphrase(passphrase.to_string());
+        let material = audit::key_material(&key);
+        let audit_key = audit::derive_audit_key(&material);
+        Ok(Self {
+            key,
+            audit_key,
+            actor: "api(passphrase)".to_string(),
+            auth_ctx: AuthContext::master_passphrase(),
+            token: None,
+            home: None,
+            cache: None,
+        })
+    }
+
+    /// Authenticate with an age keyfile on disk.
+    pub fn with_keyfile(keyfile_path: &str) -> Result<Self> {
+        let (identity, pubkey) = auth::read_keyfile(keyfile_path)?;
+        let key = VaultKey::Keyfile { identity, pubkey };
+        let material = audit::key_material(&key);
+        let audit_key = audit::derive_audit_key(&material);
+        Ok(Self {
+            key,
+            audit_key,
+            actor: "api(keyfile)".to_string(),
+            auth_ctx: AuthContext::master_keyfile(),
+            token: None,
+            home: None,
+            cache: None,
+        })
+    }
+
+    /// Authenticate through the SSH agent, with the vault key derived from
+    /// the agent's signature for the SSH public key at `public_key_path`.
+    pub fn with_ssh_agent(public_key_path: &str) -> Result<Self> {
+        let public_key =
+            auth::ssh_agent::SshPublicKey::read(std::path::Path::new(public_key_path))?;
+        let key = auth::ssh_agent::derive_key(&public_key)?;
+        let material = audit::key_material(&key);
+        let audit_key = audit::derive_audit_key(&material);
+        Ok(Self {
+            key,
+            audit_key,
+            actor: "api(ssh-agent)".to_string(),
+            auth_ctx: AuthContext::master_ssh_agent(),
+            token: None,
+            home: None,
+            cache: None,
+        })
+    }
+
+    /// Authenticate with a session token. The keyfile decrypts the vault;
+    /// the token decides what the client may do.
+    pub fn with_token(token: &str, keyfile_path: &str) -> Result<Self> {
+        let (identity, pubkey) = auth::read_keyfile(keyfile_path)?;
+        let key = VaultKey::Keyfile {
+            identity: identity.clone(),
+            pubkey,
+        };
+        let mut v = vault::load_vault(&key)?;
+        let auth_ctx = auth::token_context(token, &identity, &v)?;
+        auth::extend_sliding_session(&mut v, &key, &auth_ctx)?;
+
+        let material = audit::key_material(&key);
+        let audit_key = audit::derive_audit_key(&material);
+        let actor = match auth_ctx.method {
+            auth::context::AuthMethod::SessionToken { ref session_id } => {
+                format!("api(token:{})", session_id)
+            }
+            _ => "api(token)".to_string(),
+        };
+        Ok(Self {
+            key,
+            audit_key,
+            actor,
+            auth_ctx,
+            token: Some(token.to_string()),
+            home: None,
+            cache: None,
+        })
+    }
+
+    /// Authenticate from the environment: `AUTHY_KEYFILE` or `AUTHY_PASSPHRASE`,
+    /// then the keyfile named by the current project's `.authy.toml`, then
+    /// `vault.keyfile` in `authy.toml`, then the SSH agent when `authy.toml`
+    /// says `auth_method = "ssh-agent"`.
+    ///
+    /// This does **not** fall through to interactive prompts.
+    pub fn from_env() -> Result<Self> {
+        if let Ok(keyfile_path) = std::env::var("AUTHY_KEYFILE") {
+            return Self::with_keyfile(&keyfile_path);
+        }
+        if let Ok(passphrase) = std::env::var("AUTHY_PASSPHRASE") {
+            return Self::with_passphrase(&passphrase);
+        }
+        let project_keyfile = ProjectConfig::discover_from_cwd()
+            .ok()
+            .flatten()
+            .and_then(|(config, _)| config.expanded_keyfile());
+        if let Some(keyfile_path) = project_keyfile {
+            return Self::with_keyfile(&keyfile_path);
+        }
+        let config = Config::load(&vault::config_path()?)?;
+        if let Some(keyfile_path) = config.vault.expanded_keyfile() {
+            return Self::with_keyfile(&keyfile_path);
+        }
+        if config.vault.auth_method == auth::SSH_AGENT_METHOD {
+            if let Some(ssh_key) = config.vault.expanded_ssh_key() {
+                return Self::with_ssh_agent(&ssh_key);
+            }
+        }
+        Err(AuthyError::AuthFailed(
+            "No credentials found. Set AUTHY_KEYFILE or AUTHY_PASSPHRASE, or vault.keyfile in authy.toml.".into(),
+        ))
+    }
+
+    /// Override the actor label used in audit entries.
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = actor.into();
+        self
+    }
+
+    /// Bind the client to the authy directory `dir`, used instead of
+    /// `AUTHY_HOME` or `~/.authy` for everything it does (see
+    /// [`vault::bind_authy_dir`]). Several clients bound to different
+    /// directories can live in one process.
+    pub fn with_home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.home = Some(dir.into());
+        self
+    }
+
+    /// Keep the decrypted vault in memory and reuse it for up to `ttl`
+    /// instead of decrypting it on every call. The copy is dropped early
+    /// when the vault file's modification time or size changes, after this
+    /// client writes, and on [`refresh`](Self::refresh). With remote
+    /// storage only the TTL applies, so changes made elsewhere can take up
+    /// to `ttl` to show. Session tokens are still checked on every call,
+    /// against the cached vault.
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(VaultCache::new(ttl));
+        self
+    }
+
+    /// Drop the cached vault so the next call decrypts it again. Does
+    /// nothing without [`with_cache`](Self::with_cache).
+    pub fn refresh(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Check whether the vault has been initialized.
+    pub fn is_initialized() -> bool {
+        vault::is_initialized()
+    }
+
+    /// Check whether the vault in the authy directory `dir` has been
+    /// initialized, ignoring `AUTHY_HOME` and `HOME`.
+    pub fn is_initialized_at(dir: impl Into<PathBuf>) -> bool {
+        let _home = vault::bind_authy_dir(dir);
+        vault::is_initialized()
+    }
+
+    /// Where the vault is, or would be created: a file path for local
+    /// storage, or the URL of a remote backend. For setup guidance such as
+    /// "no vault at X, run authy init".
+    pub fn vault_location() -> Result<String> {
+        Ok(vault::storage::open()?.location())
+    }
+
+    /// [`vault_location`](Self::vault_location) for the authy directory `dir`.
+    pub fn vault_location_at(dir: impl Into<PathBuf>) -> Result<String> {
+        let _home = vault::bind_authy_dir(dir);
+        Self::vault_location()
+    }
+
+    /// The scope this client is confined to, if it was built from a token.
+    pub fn token_scope(&self) -> Option<&str> {
+        self.auth_ctx.scope.as_deref()
+    }
+
+    /// Retrieve a secret by name. Returns `None` if not found.
+    ///
+    /// An expired secret is refused with [`AuthyError::SecretExpired`], as
+    /// by `authy get`; see [`get_with_options`](Self::get_with_options).
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        self.get_with_options(name, &GetOptions::default())
+    }
+
+    /// Retrieve a secret by name with [`GetOptions`], e.g. to read one past
+    /// its expiry. Returns `None` if not found.
+    pub fn get_with_options(&self, name: &str, options: &GetOptions) -> Result<Option<String>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.check_readable(&v, &[name])?;
+        if !options.allow_expired {
+            self.check_not_expired(&v, name)?;
+        }
+
+        let result = v.secrets.get(name).map(|e| e.value.clone());
+        let outcome = if result.is_some() { "success" } else { "not_found" };
+
+        self.audit("get", Some(name), outcome, None);
+        self.trip_canaries(&v, [name], "get", None);
+        Ok(result)
+    }
+
+    /// Retrieve the first secret that exists among `names`, in order.
+    /// Returns `None` if none of them exist.
+    pub fn get_first(&self, names: &[&str]) -> Result<Option<String>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.check_readable(&v, &[])?;
+
+        for name in names {
+            // Like `authy get --fallback`: a denied or expired name is an
+            // error, not a miss
+            self.check_readable(&v, &[name])?;
+            self.check_not_expired(&v, name)?;
+            if let Some(entry) = v.secrets.get(*name) {
+                self.audit("get", Some(name), "success", None);
+                self.trip_canaries(&v, [*name], "get", None);
+                return Ok(Some(entry.value.clone()));
+            }
+        }
+
+        self.audit("get", names.first().copied(), "not_found", None);
+        Ok(None)
+    }
+
+    /// Retrieve several secrets with one vault load. Each name is checked
+    /// against the scope on its own, so a denied, expired or missing name
+    /// doesn't fail the rest: its entry holds the error instead. Results are
+    /// in input order.
+    pub fn get_many(&self, names: &[&str]) -> Result<Vec<(String, Result<String>)>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.check_readable(&v, &[])?;
+
+        let results = names
+            .iter()
+            .map(|name| {
+                let result = self
+                    .check_readable(&v, &[name])
+                    .and_then(|()| self.check_not_expired(&v, name))
+                    .and_then(|()| {
+                        v.secrets
+                            .get(*name)
+                            .map(|e| e.value.clone())
+                            .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))
+                    });
+                match &result {
+                    Ok(_) => {
+                        self.audit("get", Some(name), "success", Some("batch"));
+                        self.trip_canaries(&v, [*name], "get", None);
+                    }
+                    Err(AuthyError::SecretNotFound(_)) => {
+                        self.audit("get", Some(name), "not_found", Some("batch"))
+                    }
+                    // Denials are audited by check_readable and check_not_expired
+                    Err(_) => {}
+                }
+                (name.to_string(), result)
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Retrieve a secret by name, returning an error if it does not exist.
+    pub fn get_or_err(&self, name: &str) -> Result<String> {
+        self.get(name)?
+            .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))
+    }
+
+    /// Whether `name` is marked as a canary. Read caches skip canaries so
+    /// that every read of one alerts.
+    pub fn is_canary(&self, name: &str) -> Result<bool> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        Ok(v.secrets.get(name).is_some_and(|e| e.metadata.canary))
+    }
+
+    /// Check a `get` answered from a server-side read cache
+    /// ([`crate::vault::cache::ReadCache`]) as [`get`](Self::get) would:
+    /// the token, whether it was revoked, and its scope. Audits the read if
+    /// it is allowed. The check needs the vault, which
+    /// [`with_cache`](Self::with_cache) keeps decrypted.
+    pub fn check_cached_read(&self, name: &str) -> Result<()> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.check_readable(&v, &[name])?;
+        self.check_not_expired(&v, name)?;
+        self.audit("get", Some(name), "success", Some("cached"));
+        Ok(())
+    }
+
+    /// Store a secret. If `force` is false and the secret already exists,
+    /// returns [`AuthyError::SecretAlreadyExists`].
+    pub fn store(&self, name: &str, value: &str, force: bool) -> Result<()> {
+        let _home = self.bind_home();
+        self.store_with_options(
+            name,
+            value,
+            &StoreOptions {
+                force,
+                ..StoreOptions::default()
+            },
+        )
+    }
+
+    /// Store a secret with [`StoreOptions`], e.g. an expiry for rotation
+    /// planning.
+    pub fn store_with_options(&self, name: &str, value: &str, options: &StoreOptions) -> Result<()> {
+        let _home = self.bind_home();
+        self.auth_ctx.require(Capability::Write)?;
+        let mut v = self.load()?;
+        let (op, name) = self.stage_store(&mut v, &LimitsConfig::load()?, name, value, options)?;
+        self.save(&v)?;
+
+        self.audit(op, Some(&name), "success", None);
+        Ok(())
+    }
+
+    /// Remove a secret. Returns `true` if the secret existed.
+    ///
+    /// Protected secrets need a second credential and can only be removed
+    /// with `authy remove --approve-with`.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let _home = self.bind_home();
+        self.auth_ctx.require(Capability::Write)?;
+        let mut v = self.load()?;
+
+        let existed = self.stage_remove(&mut v, name)?;
+        if existed {
+            self.save(&v)?;
+            self.audit("remove", Some(name), "success", None);
+        } else {
+            self.audit("remove", Some(name), "not_found", None);
+        }
+
+        Ok(existed)
+    }
+
+    /// Rotate a secret to a new value. Returns the new version number.
+    /// The secret must already exist.
+    pub fn rotate(&self, name: &str, new_value: &str) -> Result<u32> {
+        let _home = self.bind_home();
+        self.auth_ctx.require(Capability::Rotate)?;
+        let mut v = self.load()?;
+        let version = self.stage_rotate(&mut v, &LimitsConfig::load()?, name, new_value)?;
+        self.save(&v)?;
+
+        self.audit(
+            "rotate",
+            Some(name),
+            "success",
+            Some(&format!("v{version}")),
+        );
+        Ok(version)
+    }
+
+    /// Stage several changes and apply them with one vault load and save,
+    /// all or nothing. See [`Batch`].
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// List secret names, optionally filtered by a policy scope.
+    /// A token client always lists its own scope.
+    pub fn list(&self, scope: Option<&str>) -> Result<Vec<String>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        let (names, count_only) = self.visible_names(&v, scope)?;
+        if count_only {
+            return Err(AuthyError::Other(format!(
+                "Policy '{}' only reveals how many secrets there are; use count()",
+                self.auth_ctx.scope.as_deref().unwrap_or_default()
+            )));
+        }
+
+        self.audit("list", None, "success", None);
+        Ok(names)
+    }
+
+    /// [`list`](Self::list) with each secret's metadata, sorted by name.
+    pub fn list_detailed(&self, scope: Option<&str>) -> Result<Vec<SecretInfo>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        let (mut names, count_only) = self.visible_names(&v, scope)?;
+        if count_only {
+            return Err(AuthyError::Other(format!(
+                "Policy '{}' only reveals how many secrets there are; use count()",
+                self.auth_ctx.scope.as_deref().unwrap_or_default()
+            )));
+        }
+        names.sort();
+
+        self.audit("list", None, "success", Some("detailed"));
+        Ok(names
+            .iter()
+            .filter_map(|name| v.secrets.get(name).map(|e| SecretInfo::new(name, e)))
+            .collect())
+    }
+
+    /// A secret's metadata, without its value. Returns `None` if not found.
+    /// Needs the same access as [`list`](Self::list): a token client only
+    /// sees secrets its scope can read, run-only tokens included.
+    pub fn get_metadata(&self, name: &str) -> Result<Option<SecretInfo>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.auth_ctx.require(Capability::List)?;
+        if let Some(scope) = self.auth_ctx.scope.as_deref() {
+            let policy = v.effective_policy(scope)?;
+            if policy.count_only {
+                return Err(AuthyError::Other(format!(
+                    "Policy '{}' only reveals how many secrets there are",
+                    scope
+                )));
+            }
+            let decision = policy.decide(SecretOp::Read, name)?;
+            if !decision.allowed {
+                self.audit("metadata", Some(name), "denied", Some(&format!("scope={}", scope)));
+                return Err(AuthyError::AccessDenied {
+                    secret: name.to_string(),
+                    scope: scope.to_string(),
+                    reason: decision.denial_reason(),
+                });
+            }
+        }
+
+        let info = v.secrets.get(name).map(|e| SecretInfo::new(name, e));
+        let outcome = if info.is_some() { "success" } else { "not_found" };
+        self.audit("metadata", Some(name), outcome, None);
+        Ok(info)
+    }
+
+    /// Replace a secret's tags. Tags are trimmed; empty and repeated ones
+    /// are dropped. The value, version and `modified_at` are unchanged.
+    pub fn set_tags(&self, name: &str, tags: &[&str]) -> Result<()> {
+        let _home = self.bind_home();
+        self.auth_ctx.require(Capability::Write)?;
+        let mut v = self.load()?;
+        auth::check_scope(&v, self.auth_ctx.scope.as_deref(), SecretOp::Write, name)?;
+
+        let entry = v
+            .secrets
+            .get_mut(name)
+            .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
+        let mut cleaned: Vec<String> = Vec::new();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !cleaned.iter().any(|t| t == tag) {
+                cleaned.push(tag.to_string());
+            }
+        }
+        let detail = format!("tags={}", cleaned.join(","));
+        entry.metadata.tags = cleaned;
+        v.touch();
+        self.save(&v)?;
+
+        self.audit("tags", Some(name), "success", Some(&detail));
+        Ok(())
+    }
+
+    /// Count the secrets visible under the scope, as `list` would return
+    /// them. Allowed under count-only policies, which hide the names.
+    pub fn count(&self, scope: Option<&str>) -> Result<usize> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        let (names, count_only) = self.visible_names(&v, scope)?;
+
+        let detail = count_only.then_some("count-only");
+        self.audit("list", None, "success", detail);
+        Ok(names.len())
+    }
+
+    /// The variables `authy run` would set for the scope, without their
+    /// values: `(variable, secret name)` pairs sorted by variable. Only names
+    /// are revealed, so this needs the list capability rather than read, and
+    /// works for run-only tokens.
+    pub fn env_manifest(
+        &self,
+        scope: Option<&str>,
+        naming: &NamingOptions,
+    ) -> Result<Vec<(String, String)>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        let (names, count_only) = self.visible_names(&v, scope)?;
+        if count_only {
+            return Err(AuthyError::Other(format!(
+                "Policy '{}' only reveals how many secrets there are",
+                self.auth_ctx.scope.as_deref().unwrap_or_default()
+            )));
+        }
+
+        let mut manifest: Vec<(String, String)> = names
+            .into_iter()
+            .map(|name| (subprocess::transform_name(&name, naming), name))
+            .collect();
+        manifest.sort();
+
+        self.audit(
+            "env_manifest",
+            None,
+            "success",
+            Some(&format!("count={}", manifest.len())),
+        );
+        Ok(manifest)
+    }
+
+    /// Secret names under the effective scope, and whether the token's
+    /// policy hides them (`count_only`).
+    fn visible_names(&self, v: &Vault, scope: Option<&str>) -> Result<(Vec<String>, bool)> {
+        self.auth_ctx.require(Capability::List)?;
+        let scope = self.effective_scope(scope)?;
+        match scope.as_deref() {
+            Some(scope_name) => {
+                let policy = v.effective_policy(scope_name)?;
+                let all_names: Vec<&str> = v.secrets.keys().map(String::as_str).collect();
+                let names = policy
+                    .filter_secrets(&all_names)?
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                Ok((names, policy.count_only && self.auth_ctx.scope.is_some()))
+            }
+            None => Ok((v.secrets.keys().cloned().collect(), false)),
+        }
+    }
+
+    /// Initialize a new vault. The vault must not already exist.
+    pub fn init_vault(&self) -> Result<()> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        vault::check_not_initialized()?;
+        let v = Vault::new();
+        self.save(&v)?;
+
+        // Write default config, keeping one written beforehand (e.g. to pick
+        // a storage backend)
+        let config_path = vault::config_path()?;
+        if !config_path.exists() {
+            crate::config::Config::default().save(&config_path)?;
+        }
+
+        self.audit("init", None, "success", None);
+        Ok(())
+    }
+
+    /// Write a passphrase-encrypted backup of the vault, audit log and
+    /// config to `path` (see [`backup`](crate::backup)). Master only.
+    pub fn backup(&self, path: impl AsRef<Path>, passphrase: &str) -> Result<()> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let path = path.as_ref();
+        self.load()?;
+        backup::create(path, passphrase)?;
+        self.audit("backup", None, "success", Some(&path.display().to_string()));
+        Ok(())
+    }
+
+    /// Restore a backup written by [`backup`](Self::backup). Its vault must
+    /// open with this client's credentials. An existing vault is only
+    /// replaced with `force`; the replaced files are kept with a
+    /// `.pre-restore` suffix and their paths returned.
+    pub fn restore(
+        &self,
+        path: impl AsRef<Path>,
+        passphrase: &str,
+        force: bool,
+    ) -> Result<Vec<PathBuf>> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let path = path.as_ref();
+        let snapshot = backup::read(path, passphrase)?;
+        let kept = backup::restore(&snapshot, &self.key, force)?;
+        self.refresh();
+        self.audit("restore", None, "success", Some(&path.display().to_string()));
+        Ok(kept)
+    }
+
+    /// Read all audit entries from the log.
+    pub fn audit_entries(&self) -> Result<Vec<audit::AuditEntry>> {
+        let _home = self.bind_home();
+        audit::read_entries(&vault::audit_path()?)
+    }
+
+    /// Verify the integrity of the audit chain.
+    /// Returns `(entry_count, valid)`.
+    pub fn verify_audit_chain(&self) -> Result<(usize, bool)> {
+        let _home = self.bind_home();
+        audit::verify_chain(&vault::audit_path()?, &self.audit_key)
+    }
+
+    /// Test whether a policy allows access to a secret.
+    /// Returns `true` if allowed, `false` if denied.
+    pub fn test_policy(&self, scope: &str, secret_name: &str) -> Result<bool> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.effective_scope(Some(scope))?;
+
+        let policy = v.effective_policy(scope)?;
+
+        let allowed = policy.can_read(secret_name)?;
+        let outcome = if allowed { "allowed" } else { "denied" };
+
+        self.audit(
+            "policy.test",
+            Some(secret_name),
+            outcome,
+            Some(&format!("scope={}", scope)),
+        );
+        Ok(allowed)
+    }
+
+    /// Return the subset of `names` that a policy allows, in input order.
+    /// One vault load and one audit entry for the whole batch, so callers can
+    /// pre-check many names without a `test_policy` call per name. Names
+    /// need not exist in the vault.
+    pub fn filter_secrets(&self, scope: &str, names: &[&str]) -> Result<Vec<String>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.effective_scope(Some(scope))?;
+
+        let policy = v.effective_policy(scope)?;
+
+        let allowed: Vec<String> = policy
+            .filter_secrets(names)?
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        self.audit(
+            "policy.test",
+            None,
+            "success",
+            Some(&format!(
+                "scope={}, checked={}, allowed={}",
+                scope,
+                names.len(),
+                allowed.len()
+            )),
+        );
+        Ok(allowed)
+    }
+
+    /// Create a new policy in the vault.
+    pub fn create_policy(
+        &self,
+        name: &str,
+        allow: Vec<String>,
+        deny: Vec<String>,
+        description: Option<&str>,
+        run_only: bool,
+    ) -> Result<()> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let mut v = self.load()?;
+        let name = &vault::names::policy_key(&v, name)?;
+
+        if v.policies.contains_key(name) {
+            return Err(AuthyError::PolicyAlreadyExists(name.to_string()));
+        }
+
+        let mut policy = Policy::new(name.to_string(), allow, deny);
+        policy.description = description.map(String::from);
+        policy.run_only = run_only;
+        v.policies.insert(name.to_string(), policy);
+        v.record_event("policy.create", &self.actor, Some(format!("policy={}", name)));
+        v.touch();
+        self.save(&v)?;
+
+        self.audit(
+            "policy.create",
+            None,
+            "success",
+            Some(&format!("policy={}", name)),
+        );
+        Ok(())
+    }
+
+    /// All policies, sorted by name, as stored (see
+    /// [`get_policy`](Self::get_policy) for inherited rules).
+    pub fn list_policies(&self) -> Result<Vec<Policy>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        Ok(v.policies.into_values().collect())
+    }
+
+    /// A policy by name, as stored, or `None` if there is none. With
+    /// `effective`, rules inherited through `extends` are merged in.
+    pub fn get_policy(&self, name: &str, effective: bool) -> Result<Option<Policy>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        if !v.policies.contains_key(name) {
+            return Ok(None);
+        }
+        if effective {
+            v.effective_policy(name).map(Some)
+        } else {
+            Ok(v.policies.get(name).cloned())
+        }
+    }
+
+    /// Change a policy, like `authy policy update`. The version it replaces
+    /// is kept in the policy's history; returns that revision number.
+    pub fn update_policy(&self, name: &str, update: &PolicyUpdate) -> Result<u32> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let mut v = self.load()?;
+
+        let rev = v
+            .record_policy_revision(name, &self.actor)
+            .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+        let policy = v
+            .policies
+            .get_mut(name)
+            .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+        if let Some(ref allow) = update.allow {
+            policy.allow = allow.clone();
+        }
+        if let Some(ref deny) = update.deny {
+            policy.deny = deny.clone();
+        }
+        if let Some(ref description) = update.description {
+            policy.description = Some(description.clone());
+        }
+        if let Some(run_only) = update.run_only {
+            policy.run_only = run_only;
+        }
+        if let Some(count_only) = update.count_only {
+            policy.count_only = count_only;
+        }
+        if let Some(ref extends) = update.extends {
+            policy.extends = extends.clone();
+        }
+        policy.modified_at = Utc::now();
+        v.effective_policy(name)?;
+        v.record_event("policy.update", &self.actor, Some(format!("policy={}", name)));
+        v.touch();
+        self.save(&v)?;
+
+        self.audit(
+            "policy.update",
+            None,
+            "success",
+            Some(&format!("policy={}, saved_rev={}", name, rev)),
+        );
+        Ok(rev)
+    }
+
+    /// Remove a policy and its history. Fails while other policies extend
+    /// it. Protected policies need a second credential and can only be
+    /// removed with `authy policy remove --approve-with`. Sessions scoped
+    /// to the policy stop working; the revocation hooks are run.
+    pub fn delete_policy(&self, name: &str) -> Result<()> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let mut v = self.load()?;
+
+        let protected = match v.policies.get(name) {
+            Some(policy) => policy.protected,
+            None => return Err(AuthyError::PolicyNotFound(name.to_string())),
+        };
+        let children = v.policies_extending(name);
+        if !children.is_empty() {
+            return Err(AuthyError::Other(format!(
+                "Policy '{}' is extended by {}; update those first",
+                name,
+                children.join(", ")
+            )));
+        }
+        if protected {
+            let detail = format!("policy={}, protected", name);
+            self.audit("policy.remove", None, "denied", Some(&detail));
+            return Err(AuthyError::ApprovalRequired(format!(
+                "policy '{}' is protected",
+                name
+            )));
+        }
+
+        v.policies.remove(name);
+        v.policy_history.remove(name);
+        let event = hooks::RevocationEvent::policy_removed(&self.actor, name, &v);
+        v.record_event("policy.remove", &self.actor, Some(format!("policy={}", name)));
+        v.touch();
+        self.save(&v)?;
+
+        self.audit(
+            "policy.remove",
+            None,
+            "success",
+            Some(&format!("policy={}", name)),
+        );
+        // The policy is gone either way; a failing hook can't undo that
+        let _ = hooks::on_revocation(&event);
+        Ok(())
+    }
+
+    /// Create a session token for `scope`, like `authy session create`,
+    /// with read and list capabilities. Session tokens can't create
+    /// sessions.
+    ///
+    /// Tokens are checked against the keyfile identity, so they only work
+    /// (with [`with_token`](Self::with_token) or `AUTHY_TOKEN`) on a
+    /// keyfile vault; [`validate_token`](Self::validate_token) works with
+    /// any master credential.
+    pub fn create_session(
+        &self,
+        scope: &str,
+        ttl: Duration,
+        label: Option<&str>,
+        run_only: bool,
+    ) -> Result<NewSession> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let duration = chrono::Duration::from_std(ttl)
+            .map_err(|e| AuthyError::Other(format!("Duration out of range: {e}")))?;
+        let mut v = self.load()?;
+        if !v.policies.contains_key(scope) {
+            return Err(AuthyError::PolicyNotFound(scope.to_string()));
+        }
+        LimitsConfig::load()?.check_session(&v)?;
+
+        let now = Utc::now();
+        let (token, token_hmac) = session::generate_token(&self.session_hmac_key());
+        let record = SessionRecord {
+            id: session::generate_session_id(),
+            scope: scope.to_string(),
+            token_hmac,
+            created_at: now,
+            expires_at: now + duration,
+            revoked: false,
+            label: label.map(String::from),
+            run_only,
+            env: BTreeMap::new(),
+            capabilities: session::default_capabilities(),
+            sliding_ttl_secs: None,
+            max_expires_at: None,
+            revoked_at: None,
+        };
+        let info = SessionInfo::new(&record, now);
+        v.sessions.push(record);
+        v.record_event(
+            "session.create",
+            &self.actor,
+            Some(format!("session={}, scope={}", info.id, scope)),
+        );
+        v.touch();
+        self.save(&v)?;
+
+        let can: Vec<&str> = info.capabilities.iter().map(|c| c.as_str()).collect();
+        self.audit(
+            "session.create",
+            None,
+            "success",
+            Some(&format!(
+                "session={}, scope={}, ttl={}, can={}",
+                info.id,
+                scope,
+                humantime::format_duration(ttl),
+                can.join(",")
+            )),
+        );
+        Ok(NewSession {
+            token,
+            session: info,
+        })
+    }
+
+    /// All sessions, including expired and revoked ones, oldest first.
+    pub fn list_sessions(&self) -> Result<Vec<SessionInfo>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        let now = Utc::now();
+        Ok(v.sessions.iter().map(|s| SessionInfo::new(s, now)).collect())
+    }
+
+    /// Revoke a session by ID, like `authy session revoke`, and run the
+    /// revocation hooks.
+    pub fn revoke_session(&self, id: &str) -> Result<()> {
+        let _home = self.bind_home();
+        self.require_write()?;
+        let mut v = self.load()?;
+
+        let record = v
+            .sessions
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| AuthyError::SessionNotFound(id.to_string()))?;
+        record.revoke(Utc::now());
+        let event = hooks::RevocationEvent::sessions_revoked(&self.actor, [&*record]);
+        v.record_event("session.revoke", &self.actor, Some(format!("session={}", id)));
+        v.touch();
+        self.save(&v)?;
+
+        self.audit(
+            "session.revoke",
+            None,
+            "success",
+            Some(&format!("session={}", id)),
+        );
+        // The session is revoked either way; a failing hook can't undo that
+        let _ = hooks::on_revocation(&event);
+        Ok(())
+    }
+
+    /// Check a session token minted for this vault and return its session.
+    /// Fails with [`AuthyError::InvalidToken`] for unknown or revoked tokens
+    /// and [`AuthyError::TokenExpired`] for expired ones. Validating a
+    /// token doesn't extend a sliding session.
+    pub fn validate_token(&self, token: &str) -> Result<SessionInfo> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        match session::validate_token(token, &v.sessions, &self.session_hmac_key()) {
+            Ok(record) => Ok(SessionInfo::new(record, Utc::now())),
+            Err(e) => {
+                self.audit(
+                    "session.validate",
+                    None,
+                    "denied",
+                    Some(&format!("reason={}", e.error_code())),
+                );
+                Err(e)
+            }
+        }
+    }
+
+    /// Build a map of environment variable names to secret values for a given policy scope.
+    ///
+    /// Loads the vault, filters secrets through the named policy, and transforms
+    /// secret names into environment-variable-style keys:
+    /// - `uppercase`: convert names to UPPER_CASE
+    /// - `replace_dash`: character to substitute for `-` (typically `_`)
+    ///
+    /// This is the cross-FFI entry point used by native language bindings (PyO3, napi-rs)
+    /// to implement `run()`-equivalent functionality without reimplementing policy logic.
+    ///
+    /// The values are handed to the caller, so like `authy env` this fails with
+    /// [`AuthyError::RunOnly`] for a run-only token or policy, and with
+    /// [`AuthyError::SecretExpired`] if a secret in the scope is expired.
+    pub fn build_env_map(
+        &self,
+        scope: &str,
+        uppercase: bool,
+        replace_dash: Option<char>,
+    ) -> Result<HashMap<String, String>> {
+        let _home = self.bind_home();
+        let v = self.load()?;
+        self.effective_scope(Some(scope))?;
+        self.auth_ctx.require(Capability::Read)?;
+        if self.auth_ctx.run_only {
+            return Err(AuthyError::RunOnly);
+        }
+
+        let policy = v.effective_policy(scope)?;
+        if policy.run_only {
+            return Err(AuthyError::RunOnly);
+        }
+
+        let all_names: Vec<&str> = v.secrets.keys().map(String::as_str).collect();
+        let allowed = policy.filter_secrets(&all_names)?;
+        for name in &allowed {
+            self.check_not_expired(&v, name)?;
+        }
+
+        let mut env_map = HashMap::new();
+        for name in &allowed {
+            if let Some(entry) = v.secrets.get(*name) {
+                let mut key = name.to_string();
+                if let Some(replacement) = replace_dash {
+                    key = key.replace('-', &replacement.to_string());
+                }
+                if uppercase {
+                    key = key.to_uppercase();
+                }
+                env_map.insert(key, entry.value.clone());
+            }
+        }
+
+        self.audit(
+            "build_env_map",
+            None,
+            "success",
+            Some(&format!("scope={}, count={}", scope, env_map.len())),
+        );
+        self.trip_canaries(&v, allowed, "build_env_map", Some(scope));
+        Ok(env_map)
+    }
+
+    // ── internal helpers ─────────────────────────────────────────
+
+    /// Store `value` as `name` in `v`, without saving. Returns the audit
+    /// operation (`store` or `update`) and the name it was stored under.
+    fn stage_store(
+        &self,
+        v: &mut Vault,
+        limits: &LimitsConfig,
+        name: &str,
+        value: &str,
+        options: &StoreOptions,
+    ) -> Result<(&'static str, String)> {
+        let name = vault::names::secret_key(v, name)?;
+        auth::check_scope(v, self.auth_ctx.scope.as_deref(), SecretOp::Write, &name)?;
+
+        if !options.force && v.secrets.contains_key(&name) {
+            self.audit("store", Some(&name), "denied", Some("already exists"));
+            return Err(AuthyError::SecretAlreadyExists(name));
+        }
+
+        limits.check_secret(v, &name, value)?;
+
+        let is_update = v.secrets.contains_key(&name);
+        let mut entry = SecretEntry::new(value.to_string());
+        entry.metadata.expires_at = options.expires_at;
+        entry.metadata.provenance = Some(Provenance::new("store", &self.actor).with_source("api"));
+        entry.metadata.protected = v.secrets.get(&name).is_some_and(|e| e.metadata.protected);
+        entry.metadata.canary = v.secrets.get(&name).is_some_and(|e| e.metadata.canary);
+        v.secrets.insert(name.clone(), entry);
+        v.touch();
+
+        let op = if is_update { "update" } else { "store" };
+        Ok((op, name))
+    }
+
+    /// Remove `name` from `v`, without saving. Returns whether it existed.
+    fn stage_remove(&self, v: &mut Vault, name: &str) -> Result<bool> {
+        auth::check_scope(v, self.auth_ctx.scope.as_deref(), SecretOp::Delete, name)?;
+
+        if v.secrets.get(name).is_some_and(|e| e.metadata.protected) {
+            self.audit("remove", Some(name), "denied", Some("protected"));
+            return Err(AuthyError::ApprovalRequired(format!(
+                "secret '{}' is protected",
+                name
+            )));
+        }
+
+        let existed = v.secrets.remove(name).is_some();
+        if existed {
+            v.touch();
+        }
+        Ok(existed)
+    }
+
+    /// Rotate `name` in `v` to `new_value`, without saving. Returns the new
+    /// version number.
+    fn stage_rotate(
+        &self,
+        v: &mut Vault,
+        limits: &LimitsConfig,
+        name: &str,
+        new_value: &str,
+    ) -> Result<u32> {
+        auth::check_scope(v, self.auth_ctx.scope.as_deref(), SecretOp::Rotate, name)?;
+
+        if !v.secrets.contains_key(name) {
+            return Err(AuthyError::SecretNotFound(name.to_string()));
+        }
+        limits.check_secret(v, name, new_value)?;
+
+        let entry = v.secrets.get_mut(name).unwrap();
+        entry.value = new_value.to_string();
+        entry.encoding = ValueEncoding::Text;
+        entry.metadata.bump_version();
+        entry.metadata.provenance = Some(Provenance::new("rotate", &self.actor).with_source("api"));
+        let version = entry.metadata.version;
+        v.touch();
+        Ok(version)
+    }
+
+    /// Load the vault (or take the cached copy), re-validating the session
+    /// token (and sliding its expiry) in token mode.
+    fn load(&self) -> Result<Vault> {
+        let mut v = match &self.cache {
+            Some(cache) => cache.get_or_load(|| vault::load_vault(&self.key))?,
+            None => vault::load_vault(&self.key)?,
+        };
+        if let (Some(token), VaultKey::Keyfile { identity, .. }) = (&self.token, &self.key) {
+            let auth_ctx = auth::token_context(token, identity, &v)?;
+            auth::extend_sliding_session(&mut v, &self.key, &auth_ctx)?;
+        }
+        Ok(v)
+    }
+
+    /// Save the vault, dropping the cached copy it replaces.
+    fn save(&self, v: &Vault) -> Result<()> {
+        self.refresh();
+        vault::save_vault(v, &self.key)
+    }
+
+    /// Bind this thread to the client's directory, if it has one, for as
+    /// long as the guard lives.
+    fn bind_home(&self) -> Option<vault::BoundDirGuard> {
+        self.home.clone().map(vault::bind_authy_dir)
+    }
+
+    /// The key session token HMACs are computed with, as in
+    /// `authy session create`.
+    fn session_hmac_key(&self) -> Vec<u8> {
+        vault::crypto::derive_key(&audit::key_material(&self.key), b"session-hmac", 32)
+    }
+
+    fn require_write(&self) -> Result<()> {
+        if self.auth_ctx.can_write {
+            Ok(())
+        } else {
+            Err(AuthyError::TokenReadOnly)
+        }
+    }
+
+    /// The scope an operation runs under: the token's scope if there is one
+    /// (a different explicit scope is rejected), else the requested one.
+    fn effective_scope(&self, requested: Option<&str>) -> Result<Option<String>> {
+        match (requested, self.auth_ctx.scope.as_deref()) {
+            (Some(explicit), Some(token_scope)) if explicit != token_scope => {
+                Err(AuthyError::Other(format!(
+                    "scope '{}' does not match the session token's scope '{}'",
+                    explicit, token_scope
+                )))
+            }
+            (_, Some(token_scope)) => Ok(Some(token_scope.to_string())),
+            (requested, None) => Ok(requested.map(String::from)),
+        }
+    }
+
+    /// Enforce run-only and the token's scope before handing out values.
+    /// Master clients are unrestricted, as with the CLI's `get`.
+    fn check_readable(&self, v: &Vault, names: &[&str]) -> Result<()> {
+        self.auth_ctx.require(Capability::Read)?;
+        if self.auth_ctx.run_only {
+            return Err(AuthyError::RunOnly);
+        }
+        let scope = match self.auth_ctx.scope.as_deref() {
+            Some(scope) => scope,
+            None => return Ok(()),
+        };
+        let policy = v.effective_policy(scope)?;
+        if policy.run_only {
+            return Err(AuthyError::RunOnly);
+        }
+        for name in names {
+            let decision = policy.decide(SecretOp::Read, name)?;
+            if !decision.allowed {
+                let reason = decision.denial_reason();
+                let mut detail = format!("scope={}", scope);
+                if let Some(ref reason) = reason {
+                    detail.push_str(&format!(", reason={}", reason));
+                }
+                self.audit("get", Some(name), "denied", Some(&detail));
+                return Err(AuthyError::AccessDenied {
+                    secret: name.to_string(),
+                    scope: scope.to_string(),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse a secret past its `expires_at`, auditing the denial as
+    /// `authy get` does. A missing secret passes.
+    fn check_not_expired(&self, v: &Vault, name: &str) -> Result<()> {
+        if v.secrets.get(name).is_some_and(|e| e.metadata.is_expired()) {
+            self.audit("get", Some(name), "denied", Some("expired"));
+            return Err(AuthyError::SecretExpired(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Alert on the canaries among `names` (see [`hooks::trip_canaries`]).
+    /// `scope` defaults to the token's.
+    fn trip_canaries<'a>(
+        &self,
+        v: &Vault,
+        names: impl IntoIterator<Item = &'a str>,
+        operation: &str,
+        scope: Option<&str>,
+    ) {
+        let scope = scope.or(self.auth_ctx.scope.as_deref());
+        hooks::trip_canaries(v, names, operation, &self.actor, scope, &self.audit_key);
+    }
+
+    fn audit(&self, operation: &str, secret: Option<&str>, outcome: &str, detail: Option<&str>) {
+        let _ = vault::audit_path().and_then(|path| {
+            audit::log_event(
+                &path,
+                operation,
+                secret,
+                &self.actor,
+                outcome,
+                detail,
+                &self.audit_key,
+            )
+        });
+    }
+}
@@ -0,0 +1,297 @@
+//! Rotation of the audit log into zstd-compressed archives, configured with
+//! `audit.rotate_bytes` and `audit.rotate_age`.
+//!
+//! A rotated log is written to `<stem>-<date>.log.zst` next to it, and the
+//! new log starts with a link record naming that archive and the last chain
+//! HMAC in it (the anchor). The link record is chained to the anchor like
+//! any other entry, so the chain runs unbroken across segments, and
+//! [`super::read_entries`] follows link records back through the archives.
+//! Reading stops at an archive that is missing, but verification fails
+//! there, so deleted archives can't go unnoticed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, Utc};
+
+use super::{chain_data, compute_chain_mac, lock_log, read_chain_tail, AuditEntry};
+use crate::config::{AuditConfig, ChainAlgorithm};
+use crate::error::{AuthyError, Result};
+
+/// Operation of the link record that starts a rotated log.
+pub const LINK_OPERATION: &str = "audit.rotate";
+
+/// Actor recorded on link records.
+pub const ROTATE_ACTOR: &str = "rotation";
+
+/// What a link record says about the segment before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// File name of the archive, in the same directory as the log.
+    pub archive: String,
+    /// Number of entries in the archive.
+    pub entries: usize,
+    /// Chain HMAC of the archive's last entry.
+    pub anchor: String,
+}
+
+/// Read a link record. Returns `None` for any other entry.
+pub fn parse_link(entry: &AuditEntry) -> Option<Link> {
+    if entry.operation != LINK_OPERATION {
+        return None;
+    }
+    let mut archive = None;
+    let mut entries = None;
+    let mut anchor = None;
+    for part in entry.detail.as_deref()?.split(", ") {
+        match part.split_once('=')? {
+            ("archive", value) => archive = Some(value.to_string()),
+            ("entries", value) => entries = value.parse().ok(),
+            ("anchor", value) => anchor = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(Link {
+        archive: archive?,
+        entries: entries?,
+        anchor: anchor?,
+    })
+}
+
+/// Whether the log at `audit_path` should be rotated before the next entry.
+pub fn rotation_due(audit_path: &Path, config: &AuditConfig) -> Result<bool> {
+    let size = match fs::metadata(audit_path) {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if size == 0 {
+        return Ok(false);
+    }
+    if config.rotate_bytes > 0 && size >= config.rotate_bytes {
+        return Ok(true);
+    }
+    let max_age = match config.rotate_age()? {
+        Some(age) => age,
+        None => return Ok(false),
+    };
+    // Only the first line, not the whole log
+    let mut first = None;
+    for line in BufReader::new(File::open(audit_path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            first = serde_json::from_str::<AuditEntry>(&line).ok();
+            break;
+        }
+    }
+    Ok(first.is_some_and(|entry| Utc::now() - entry.timestamp >= max_age))
+}
+
+/// Move the log at `audit_path` into a new archive and start it again with
+/// a link record. Returns the archive's path, or `None` if the log was empty.
+/// Holds the log's lock throughout, so no entry appended meanwhile is lost.
+pub fn rotate(audit_path: &Path, hmac_key: &[u8], alg: ChainAlgorithm) -> Result<Option<PathBuf>> {
+    let _lock = lock_log(audit_path)?;
+    rotate_locked(audit_path, hmac_key, alg)
+}
+
+/// [`rotate`] for a caller already holding the lock.
+pub(super) fn rotate_locked(
+    audit_path: &Path,
+    hmac_key: &[u8],
+    alg: ChainAlgorithm,
+) -> Result<Option<PathBuf>> {
+    let tail = read_chain_tail(audit_path);
+    let (anchor, count) = (tail.hmac, tail.count);
+    if count == 0 {
+        return Ok(None);
+    }
+    let dir = audit_path
+        .parent()
+        .ok_or_else(|| AuthyError::Other(format!("No directory for {}", audit_path.display())))?;
+    let name = archive_name(audit_path, Utc::now().date_naive());
+    let archive = dir.join(&name);
+
+    // The archive is complete on disk before the log is replaced, so a crash
+    // in between leaves both rather than neither
+    let content = fs::read(audit_path)?;
+    let compressed = zstd::stream::encode_all(&content[..], 0)?;
+    write_synced(&archive, &compressed)?;
+
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        operation: LINK_OPERATION.to_string(),
+        secret: None,
+        actor: ROTATE_ACTOR.to_string(),
+        outcome: "success".to_string(),
+        detail: Some(format!("archive={}, entries={}, anchor={}", name, count, anchor)),
+        chain_hmac: String::new(),
+        chain_alg: alg,
+        seq: Some(1),
+        since_checkpoint: None,
+    };
+    let chain_hmac = compute_chain_mac(alg, &chain_data(&anchor, &entry), hmac_key);
+    let link = AuditEntry { chain_hmac, ..entry };
+    let line =
+        serde_json::to_string(&link).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+
+    let mut pending = audit_path.as_os_str().to_owned();
+    pending.push(".rotating");
+    let pending = PathBuf::from(pending);
+    write_synced(&pending, format!("{}\n", line).as_bytes())?;
+    fs::rename(&pending, audit_path)?;
+    Ok(Some(archive))
+}
+
+/// Entries of the archived segments that precede a log starting with
+/// `first`, oldest first. Stops at the first archive that is missing.
+pub fn preceding_entries(audit_path: &Path, first: Option<&AuditEntry>) -> Result<Vec<AuditEntry>> {
+    let dir = match audit_path.parent() {
+        Some(dir) => dir,
+        None => return Ok(Vec::new()),
+    };
+    let mut segments: Vec<Vec<AuditEntry>> = Vec::new();
+    let mut seen = Vec::new();
+    let mut link = first.and_then(parse_link);
+
+    while let Some(Link { archive, .. }) = link {
+        let path = dir.join(&archive);
+        if seen.contains(&archive) || !path.exists() {
+            break;
+        }
+        let segment = read_archive(&path)?;
+        link = segment.first().and_then(parse_link);
+        segments.push(segment);
+        seen.push(archive);
+    }
+
+    Ok(segments.into_iter().rev().flatten().collect())
+}
+
+/// Read the entries of one archive.
+pub fn read_archive(path: &Path) -> Result<Vec<AuditEntry>> {
+    let content = zstd::stream::decode_all(File::open(path)?)
+        .map_err(|e| AuthyError::Other(format!("Cannot read {}: {}", path.display(), e)))?;
+    let content =
+        String::from_utf8(content).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(|e| AuthyError::Serialization(e.to_string())))
+        .collect()
+}
+
+/// `audit-2026-10-17.log.zst`, or `audit-2026-10-17-2.log.zst` and so on if
+/// the log was already rotated that day.
+fn archive_name(audit_path: &Path, date: NaiveDate) -> String {
+    let stem = audit_path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = audit_path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let base = format!("{}-{}", stem, date.format("%Y-%m-%d"));
+    let dir = audit_path.parent().unwrap_or(Path::new("."));
+    let mut name = format!("{}{}.zst", base, ext);
+    let mut n = 2;
+    while dir.join(&name).exists() {
+        name = format!("{}-{}{}.zst", base, n, ext);
+        n += 1;
+    }
+    name
+}
+
+fn write_synced(path: &Path, data: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{log_event, read_entries, verify_chain};
+
+    const KEY: &[u8] = b"test-audit-key";
+
+    fn write_entries(path: &Path, count: usize) {
+        for i in 0..count {
+            log_event(path, "get", Some(&format!("s{}", i)), "master", "success", None, KEY)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_chain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        write_entries(&log, 3);
+
+        let first = rotate(&log, KEY, ChainAlgorithm::default()).unwrap().unwrap();
+        write_entries(&log, 2);
+        let second = rotate(&log, KEY, ChainAlgorithm::default()).unwrap().unwrap();
+        write_entries(&log, 1);
+
+        let date = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        assert_eq!(first, dir.path().join(format!("audit-{}.log.zst", date)));
+        assert_eq!(second, dir.path().join(format!("audit-{}-2.log.zst", date)));
+        assert_eq!(read_archive(&first).unwrap().len(), 3);
+
+        // 6 entries plus the two link records
+        let entries = read_entries(&log).unwrap();
+        assert_eq!(entries.len(), 8);
+        assert_eq!(verify_chain(&log, KEY).unwrap(), (8, true));
+
+        let link = parse_link(&entries[6]).unwrap();
+        assert_eq!(link.entries, 3);
+        assert_eq!(link.anchor, entries[5].chain_hmac);
+
+        // A deleted archive is still read past, but fails verification
+        fs::remove_file(&first).unwrap();
+        assert_eq!(read_entries(&log).unwrap().len(), 5);
+        assert!(matches!(
+            verify_chain(&log, KEY),
+            Err(AuthyError::AuditArchiveMissing(name)) if first.ends_with(&name)
+        ));
+    }
+
+    #[test]
+    fn test_forged_anchor_is_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        write_entries(&log, 2);
+        rotate(&log, KEY, ChainAlgorithm::default()).unwrap().unwrap();
+
+        let content = fs::read_to_string(&log).unwrap();
+        let forged = content.replacen("anchor=", "anchor=00", 1);
+        fs::write(&log, forged).unwrap();
+        assert!(matches!(
+            verify_chain(&log, KEY),
+            Err(AuthyError::AuditChainBroken(2))
+        ));
+    }
+
+    #[test]
+    fn test_rotation_due() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        let mut config = AuditConfig::default();
+        assert!(!rotation_due(&log, &config).unwrap());
+
+        write_entries(&log, 1);
+        assert!(!rotation_due(&log, &config).unwrap());
+        config.rotate_bytes = 1;
+        assert!(rotation_due(&log, &config).unwrap());
+
+        config.rotate_bytes = 0;
+        config.rotate_age = Some("1h".to_string());
+        assert!(!rotation_due(&log, &config).unwrap());
+        config.rotate_age = Some("0s".to_string());
+        assert!(rotation_due(&log, &config).unwrap());
+    }
+}
@@ -0,0 +1,108 @@
+//! Failed-authentication log.
+//!
+//! The main audit log is keyed from the master material, so it can't record
+//! attempts that never produced a valid key. Auth failures go to a separate
+//! HMAC-chained log (`~/.authy/auth-events.log`) keyed by a random local key
+//! (`~/.authy/auth-events.key`). The local key only proves the log wasn't
+//! edited by someone without access to the authy directory — it is a
+//! visibility aid for brute-force attempts and misconfigured agents, not a
+//! substitute for the main audit chain.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use rand::RngCore;
+
+use crate::error::{AuthyError, ErrorCode, Result};
+use crate::vault;
+
+const KEY_BYTES: usize = 32;
+
+/// Path of the auth events log.
+pub fn auth_events_path() -> Result<PathBuf> {
+    Ok(vault::authy_dir()?.join("auth-events.log"))
+}
+
+/// Path of the local key that chains the auth events log.
+pub fn auth_events_key_path() -> Result<PathBuf> {
+    Ok(vault::authy_dir()?.join("auth-events.key"))
+}
+
+/// Whether an error means the presented credentials were rejected.
+pub fn is_auth_failure(err: &AuthyError) -> bool {
+    matches!(
+        err.code(),
+        ErrorCode::AuthFailed
+            | ErrorCode::DecryptionError
+            | ErrorCode::InvalidKeyfile
+            | ErrorCode::InvalidToken
+            | ErrorCode::TokenExpired
+            | ErrorCode::TokenRevoked
+    )
+}
+
+/// Load the local HMAC key, creating it (mode 0600) on first use.
+pub fn local_key() -> Result<Vec<u8>> {
+    let path = auth_events_key_path()?;
+    loop {
+        if let Ok(bytes) = fs::read(&path) {
+            if bytes.len() == KEY_BYTES {
+                return Ok(bytes);
+            }
+            return Err(AuthyError::Other(format!(
+                "Auth events key at {} is malformed",
+                path.display()
+            )));
+        }
+
+        let mut key = vec![0u8; KEY_BYTES];
+        rand::thread_rng().fill_bytes(&mut key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Created with its final mode, so the key is never readable by others
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(&key)?;
+                return Ok(key);
+            }
+            // Another process created it first: use theirs
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Record a failed authentication attempt. `method` names the credential
+/// that was tried (e.g. "passphrase", "keyfile", "token"). A passphrase
+/// prompt that timed out is recorded as `auth.prompt_timeout`.
+///
+/// Does nothing when no vault exists, so a stray command in a fresh HOME
+/// doesn't create the authy directory.
+pub fn record_failure(method: &str, err: &AuthyError) -> Result<()> {
+    if !vault::is_initialized() {
+        return Ok(());
+    }
+    let key = local_key()?;
+    let (operation, outcome) = match err {
+        AuthyError::PromptTimeout(_) => ("auth.prompt_timeout", "timeout"),
+        _ => ("auth.failure", "failure"),
+    };
+    super::log_event(
+        &auth_events_path()?,
+        operation,
+        None,
+        &format!("{}(pid={})", method, std::process::id()),
+        outcome,
+        Some(&format!("code={}, reason={}", err.code(), err)),
+        &key,
+    )
+}
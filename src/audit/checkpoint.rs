@@ -0,0 +1,273 @@
+//! Signed checkpoints in the audit log.
+//!
+//! The HMAC chain proves entries weren't edited or reordered by someone
+//! without the audit key, but whoever holds the master credential can
+//! rewrite the whole log and re-chain it. With `[audit] checkpoint_key`
+//! set, every `checkpoint_every` entries the log gets an `audit.checkpoint`
+//! entry holding an ed25519 signature over the chain HMAC before it (the
+//! head). The signing key lives outside the vault, so a rewritten log can't
+//! carry valid checkpoints, and `audit verify --checkpoints` checks them
+//! against the public key.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::RngCore;
+
+use super::{chain_start, AuditEntry};
+use crate::config::{AuditConfig, ChainAlgorithm};
+use crate::error::{AuthyError, Result};
+
+/// Operation of a checkpoint entry.
+pub const OPERATION: &str = "audit.checkpoint";
+
+/// Actor recorded on checkpoint entries.
+pub const ACTOR: &str = "checkpoint";
+
+const SECRET_PREFIX: &str = "AUTHY-CHECKPOINT-KEY-";
+const PUBLIC_PREFIX: &str = "authy-checkpoint-pub-";
+
+/// Where the public key for the signing key at `key_path` is written.
+pub fn public_key_path(key_path: &Path) -> PathBuf {
+    let mut path = key_path.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// Create a signing key at `path` (mode 0600) and its public key next to it
+/// (`<path>.pub`). Returns the public key as text.
+pub fn generate_key(path: &Path, force: bool) -> Result<String> {
+    if path.exists() && !force {
+        return Err(AuthyError::Other(format!(
+            "{} already exists (pass --force to replace it)",
+            path.display()
+        )));
+    }
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let signing = SigningKey::from_bytes(&seed);
+    let public = encode_public(&signing.verifying_key());
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}{}\n", SECRET_PREFIX, hex::encode(seed)))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    fs::write(public_key_path(path), format!("{}\n", public))?;
+    Ok(public)
+}
+
+/// Read the signing key at `path`.
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        AuthyError::Other(format!("Cannot read checkpoint key {}: {}", path.display(), e))
+    })?;
+    let seed = content
+        .trim()
+        .strip_prefix(SECRET_PREFIX)
+        .and_then(|hex_seed| hex::decode(hex_seed).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| {
+            AuthyError::Other(format!("{} is not a checkpoint signing key", path.display()))
+        })?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Parse a public key given as text, or read it from the file it names.
+pub fn parse_public_key(input: &str) -> Result<VerifyingKey> {
+    let text = match fs::read_to_string(input) {
+        Ok(content) => content,
+        Err(_) => input.to_string(),
+    };
+    text.trim()
+        .strip_prefix(PUBLIC_PREFIX)
+        .and_then(|hex_key| hex::decode(hex_key).ok())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| {
+            AuthyError::Other(format!(
+                "'{}' is not a checkpoint public key ({}<hex>) or a file holding one",
+                input, PUBLIC_PREFIX
+            ))
+        })
+}
+
+/// The public key as text (`authy-checkpoint-pub-<hex>`).
+pub fn encode_public(key: &VerifyingKey) -> String {
+    format!("{}{}", PUBLIC_PREFIX, hex::encode(key.as_bytes()))
+}
+
+/// Short identifier of a public key, recorded in each checkpoint.
+fn key_id(key: &VerifyingKey) -> String {
+    hex::encode(&key.as_bytes()[..8])
+}
+
+/// What a checkpoint signs.
+fn message(entries: usize, head: &str, timestamp: &DateTime<Utc>) -> Vec<u8> {
+    format!("authy-audit-checkpoint|{}|{}|{}", entries, head, timestamp.to_rfc3339()).into_bytes()
+}
+
+/// Whether a checkpoint is due after an entry that leaves `since` entries
+/// since the last one.
+pub fn due(config: &AuditConfig, since: usize) -> bool {
+    config.checkpoint_key.is_some() && since >= config.checkpoint_every.max(1)
+}
+
+/// A checkpoint entry signing `head`, the chain HMAC of the `entries`
+/// entries written since the previous checkpoint. `chain_hmac` is left for
+/// the caller to fill in.
+pub fn entry(key: &SigningKey, entries: usize, head: &str, alg: ChainAlgorithm) -> AuditEntry {
+    let timestamp = Utc::now();
+    let signature = key.sign(&message(entries, head, &timestamp));
+    AuditEntry {
+        timestamp,
+        operation: OPERATION.to_string(),
+        secret: None,
+        actor: ACTOR.to_string(),
+        outcome: "success".to_string(),
+        detail: Some(format!(
+            "entries={}, head={}, key={}, sig={}",
+            entries,
+            head,
+            key_id(&key.verifying_key()),
+            hex::encode(signature.to_bytes())
+        )),
+        chain_hmac: String::new(),
+        chain_alg: alg,
+        seq: None,
+        since_checkpoint: None,
+    }
+}
+
+/// The result of checking the checkpoints in a log.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointReport {
+    /// Valid checkpoints found.
+    pub checkpoints: usize,
+    /// Entries up to and including the last checkpoint.
+    pub signed: usize,
+    /// Entries after the last checkpoint, covered only by the HMAC chain.
+    pub unsigned: usize,
+}
+
+/// Check every checkpoint in `entries` against `key`: each must sign the
+/// chain HMAC of the entry before it. The chain itself is checked
+/// separately ([`super::verify_chain`]). A log with no checkpoint fails,
+/// since a rewritten log would simply leave them out.
+pub fn verify(entries: &[AuditEntry], key: &VerifyingKey) -> Result<CheckpointReport> {
+    let mut report = CheckpointReport::default();
+    let mut prev_hmac = entries.first().map(chain_start).transpose()?.unwrap_or_default();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.operation == OPERATION && entry.actor == ACTOR {
+            let invalid = |reason: &str| AuthyError::AuditCheckpointInvalid {
+                entry: i,
+                reason: reason.to_string(),
+            };
+            let detail = entry.detail.as_deref().unwrap_or_default();
+            let field = |name: &str| {
+                detail
+                    .split(", ")
+                    .find_map(|part| part.strip_prefix(name)?.strip_prefix('='))
+            };
+            let entries_since: usize = field("entries")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| invalid("malformed detail"))?;
+            let head = field("head").ok_or_else(|| invalid("malformed detail"))?;
+            if head != prev_hmac {
+                return Err(invalid("it doesn't sign the entry before it"));
+            }
+            if field("key") != Some(key_id(key).as_str()) {
+                return Err(invalid("it was signed with a different key"));
+            }
+            let signature = field("sig")
+                .and_then(|sig| hex::decode(sig).ok())
+                .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                .ok_or_else(|| invalid("malformed signature"))?;
+            key.verify(&message(entries_since, head, &entry.timestamp), &signature)
+                .map_err(|_| invalid("bad signature"))?;
+            report.checkpoints += 1;
+            report.signed = i + 1;
+        }
+        prev_hmac = entry.chain_hmac.clone();
+    }
+
+    if report.checkpoints == 0 {
+        return Err(AuthyError::Other(
+            "Audit log has no signed checkpoints".into(),
+        ));
+    }
+    report.unsigned = entries.len() - report.signed;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chained(entries: &mut Vec<AuditEntry>, mut entry: AuditEntry) {
+        let prev = entries.last().map(|e| e.chain_hmac.clone()).unwrap_or_default();
+        entry.chain_hmac = format!("{}+{}", prev.len(), entries.len());
+        entries.push(entry);
+    }
+
+    fn plain(operation: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            operation: operation.to_string(),
+            secret: None,
+            actor: "master(passphrase)".to_string(),
+            outcome: "success".to_string(),
+            detail: None,
+            chain_hmac: String::new(),
+            chain_alg: ChainAlgorithm::default(),
+            seq: None,
+            since_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut entries = Vec::new();
+        chained(&mut entries, plain("init"));
+        chained(&mut entries, plain("store"));
+        let head = entries[1].chain_hmac.clone();
+        chained(&mut entries, entry(&key, 2, &head, ChainAlgorithm::default()));
+        chained(&mut entries, plain("get"));
+
+        let report = verify(&entries, &key.verifying_key()).unwrap();
+        assert_eq!((report.checkpoints, report.signed, report.unsigned), (1, 3, 1));
+
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        assert!(verify(&entries, &other.verifying_key()).is_err());
+
+        // A forged head fails even with a valid-looking entry
+        let mut forged = entries.clone();
+        forged[1].chain_hmac = "forged".to_string();
+        assert!(verify(&forged, &key.verifying_key()).is_err());
+
+        assert!(verify(&entries[..2], &key.verifying_key()).is_err());
+    }
+
+    #[test]
+    fn test_key_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.key");
+        let public = generate_key(&path, false).unwrap();
+        assert!(generate_key(&path, false).is_err());
+
+        let signing = load_signing_key(&path).unwrap();
+        let from_text = parse_public_key(&public).unwrap();
+        let from_file = parse_public_key(public_key_path(&path).to_str().unwrap()).unwrap();
+        assert_eq!(from_text, signing.verifying_key());
+        assert_eq!(from_file, signing.verifying_key());
+        assert!(parse_public_key("nonsense").is_err());
+    }
+}
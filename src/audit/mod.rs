@@ -1,10 +1,18 @@
+pub mod archive;
+pub mod auth_events;
+pub mod checkpoint;
+pub mod query;
+pub mod sinks;
+pub mod stats;
+
 use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
+use crate::config::{AuditLevel, ChainAlgorithm, FsyncMode, SecretNameMode};
 use crate::error::{AuthyError, Result};
 use crate::types::*;
 
@@ -20,6 +28,18 @@ pub struct AuditEntry {
     pub outcome: String,
     pub detail: Option<String>,
     pub chain_hmac: String,
+    /// MAC used for `chain_hmac`. Omitted for HMAC-SHA256 entries so the
+    /// original format is unchanged.
+    #[serde(default, skip_serializing_if = "ChainAlgorithm::is_default")]
+    pub chain_alg: ChainAlgorithm,
+    /// Position in the current log file, counting from 1, so an append
+    /// only needs the last line. Missing on entries from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<usize>,
+    /// With checkpoints on, entries since the last checkpoint, this one
+    /// included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_checkpoint: Option<usize>,
 }
 
 /// Append an audit entry to the log file.
@@ -32,31 +52,44 @@ pub fn log_event(
     detail: Option<&str>,
     hmac_key: &[u8],
 ) -> Result<()> {
-    let prev_hmac = read_last_hmac(audit_path);
+    let audit_config = crate::vault::config_path()
+        .and_then(|path| crate::config::Config::load(&path))
+        .map(|c| c.audit)
+        .unwrap_or_default();
+    if !is_recorded(audit_config.level, operation, outcome) {
+        return Ok(());
+    }
+
+    if let Some(dir) = audit_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let _lock = lock_log(audit_path)?;
+    // Appending after a torn line would glue the new entry onto it
+    quarantine_torn_tail(audit_path)?;
+    if archive::rotation_due(audit_path, &audit_config)? {
+        archive::rotate_locked(audit_path, hmac_key, audit_config.chain)?;
+    }
+    let tail = read_chain_tail(audit_path);
+    let secret = secret.and_then(|name| recorded_name(name, audit_config.secret_names, hmac_key));
+    let seq = tail.count + 1;
+    let since_checkpoint = tail.since_checkpoint + 1;
+    let checkpoint_key = audit_config.expanded_checkpoint_key();
 
     let entry = AuditEntry {
         timestamp: Utc::now(),
         operation: operation.to_string(),
-        secret: secret.map(|s| s.to_string()),
+        secret,
         actor: actor.to_string(),
         outcome: outcome.to_string(),
         detail: detail.map(|s| s.to_string()),
         chain_hmac: String::new(), // Will be filled below
+        chain_alg: audit_config.chain,
+        seq: Some(seq),
+        since_checkpoint: checkpoint_key.is_some().then_some(since_checkpoint),
     };
 
     // Compute HMAC chain: HMAC(prev_hmac || serialized_entry_without_chain)
-    let chain_data = format!(
-        "{}|{}|{}|{:?}|{}|{}|{:?}",
-        prev_hmac,
-        entry.timestamp.to_rfc3339(),
-        entry.operation,
-        entry.secret,
-        entry.actor,
-        entry.outcome,
-        entry.detail,
-    );
-
-    let chain_hmac = compute_chain_hmac(&chain_data, hmac_key);
+    let chain_hmac = compute_chain_mac(entry.chain_alg, &chain_data(&tail.hmac, &entry), hmac_key);
 
     let final_entry = AuditEntry {
         chain_hmac,
@@ -66,60 +99,198 @@ pub fn log_event(
     let json_line =
         serde_json::to_string(&final_entry).map_err(|e| AuthyError::Serialization(e.to_string()))?;
 
-    if let Some(dir) = audit_path.parent() {
-        fs::create_dir_all(dir)?;
-    }
-
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(audit_path)?;
     writeln!(file, "{}", json_line)?;
 
+    let signed = match checkpoint_key {
+        Some(key_path) if checkpoint::due(&audit_config, since_checkpoint) => {
+            let signing_key = checkpoint::load_signing_key(Path::new(&key_path))?;
+            let entry = AuditEntry {
+                seq: Some(seq + 1),
+                since_checkpoint: Some(0),
+                ..checkpoint::entry(
+                    &signing_key,
+                    since_checkpoint,
+                    &final_entry.chain_hmac,
+                    audit_config.chain,
+                )
+            };
+            let chain_hmac = compute_chain_mac(
+                entry.chain_alg,
+                &chain_data(&final_entry.chain_hmac, &entry),
+                hmac_key,
+            );
+            let entry = AuditEntry { chain_hmac, ..entry };
+            let line = serde_json::to_string(&entry)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+            writeln!(file, "{}", line)?;
+            Some((entry, line))
+        }
+        _ => None,
+    };
+
+    let sync = match audit_config.fsync {
+        FsyncMode::Never => false,
+        FsyncMode::Always => true,
+        // A checkpoint is only worth having once it's on disk
+        FsyncMode::Batch => {
+            signed.is_some()
+                || audit_config.fsync_batch <= 1
+                || seq % audit_config.fsync_batch == 0
+        }
+    };
+    if sync {
+        file.sync_data()?;
+    }
+    sinks::forward(&audit_config.sinks, &final_entry, &json_line, audit_path);
+    if let Some((entry, line)) = signed {
+        sinks::forward(&audit_config.sinks, &entry, &line, audit_path);
+    }
+
     Ok(())
 }
 
-/// Read all audit entries from the log file.
+/// Operations that hand out secret values.
+pub(crate) const VALUE_READS: &[&str] = &["get", "env_export", "export", "resolve", "run", "push"];
+
+/// Operations that only read names or metadata.
+const METADATA_READS: &[&str] = &["list", "expiring"];
+
+/// Whether an event is kept at `level`. Anything that isn't a success is
+/// always kept, and operations not listed as reads count as mutations, so a
+/// new operation is never silently dropped below `all`.
+pub fn is_recorded(level: AuditLevel, operation: &str, outcome: &str) -> bool {
+    if outcome != "success" {
+        return true;
+    }
+    let value_read = VALUE_READS.contains(&operation);
+    let metadata_read = METADATA_READS.contains(&operation);
+    match level {
+        AuditLevel::All => true,
+        AuditLevel::Reads => !metadata_read,
+        AuditLevel::Writes => !value_read && !metadata_read,
+        AuditLevel::DenialsOnly => false,
+    }
+}
+
+/// Prefix of a secret name recorded as a keyed hash.
+pub const HASHED_NAME_PREFIX: &str = "sha256:";
+
+/// Keyed hash of a secret name, as recorded when `audit.secret_names = "hash"`.
+/// Keyed by the audit key so names can only be resolved with the master key.
+pub fn hash_secret_name(name: &str, hmac_key: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(hmac_key).expect("HMAC can take key of any size");
+    mac.update(b"secret-name|");
+    mac.update(name.as_bytes());
+    let digest = hex::encode(mac.finalize().into_bytes());
+    format!("{}{}", HASHED_NAME_PREFIX, &digest[..16])
+}
+
+/// How `name` is written to the log under `mode`; `None` when omitted.
+fn recorded_name(name: &str, mode: SecretNameMode, hmac_key: &[u8]) -> Option<String> {
+    match mode {
+        SecretNameMode::Plain => Some(name.to_string()),
+        SecretNameMode::Hash => Some(hash_secret_name(name, hmac_key)),
+        SecretNameMode::Omit => None,
+    }
+}
+
+/// Secret names as `audit.secret_names` allows them in an entry's detail:
+/// as-is, hashed, or `None` when names are omitted.
+pub fn recorded_names<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    hmac_key: &[u8],
+) -> Option<Vec<String>> {
+    let mode = crate::vault::config_path()
+        .and_then(|path| crate::config::Config::load(&path))
+        .map(|c| c.audit.secret_names)
+        .unwrap_or_default();
+    names
+        .into_iter()
+        .map(|name| recorded_name(name, mode, hmac_key))
+        .collect()
+}
+
+/// Where torn trailing lines are moved (`<log>.torn`).
+pub fn quarantine_path(audit_path: &Path) -> PathBuf {
+    let mut name = audit_path.as_os_str().to_owned();
+    name.push(".torn");
+    PathBuf::from(name)
+}
+
+/// An exclusive advisory lock on `<log>.lock`, released on drop.
+pub(crate) struct LogLock {
+    _file: File,
+}
+
+/// Take the lock that appends and rotation hold, so that concurrent
+/// writers neither fork the chain nor lose entries to a rotation. It is a
+/// separate file because rotation replaces the log. Only taken on Unix.
+pub(crate) fn lock_log(audit_path: &Path) -> Result<LogLock> {
+    let mut path = audit_path.as_os_str().to_owned();
+    path.push(".lock");
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(PathBuf::from(path))?;
+    #[cfg(unix)]
+    loop {
+        match rustix::fs::flock(&file, rustix::fs::FlockOperation::LockExclusive) {
+            Ok(()) => break,
+            Err(rustix::io::Errno::INTR) => continue,
+            Err(e) => return Err(std::io::Error::from(e).into()),
+        }
+    }
+    Ok(LogLock { _file: file })
+}
+
+/// Read all audit entries from the log file, preceded by those of the
+/// archives it was rotated from (see [`archive`]).
+///
+/// A final line that doesn't parse is what a crash mid-append leaves behind;
+/// it is skipped and moved to [`quarantine_path`] rather than failing the
+/// read. Unparseable lines elsewhere are still an error.
 pub fn read_entries(audit_path: &Path) -> Result<Vec<AuditEntry>> {
     if !audit_path.exists() {
         return Ok(Vec::new());
     }
 
-    let file = fs::File::open(audit_path)?;
-    let reader = BufReader::new(file);
+    let content = fs::read(audit_path)?;
+    let end = match torn_tail(&content) {
+        Some(start) => {
+            // Best effort: a read-only log can still be read. Under the
+            // lock, a line another process is still appending is complete.
+            let _ = lock_log(audit_path).and_then(|_lock| quarantine_torn_tail(audit_path));
+            start
+        }
+        None => content.len(),
+    };
+    let content = std::str::from_utf8(&content[..end])
+        .map_err(|e| AuthyError::Serialization(e.to_string()))?;
     let mut entries = Vec::new();
 
-    for line in reader.lines() {
-        let line = line?;
+    for line in content.lines() {
         if line.trim().is_empty() {
             continue;
         }
         let entry: AuditEntry =
-            serde_json::from_str(&line).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+            serde_json::from_str(line).map_err(|e| AuthyError::Serialization(e.to_string()))?;
         entries.push(entry);
     }
 
-    Ok(entries)
+    let mut all = archive::preceding_entries(audit_path, entries.first())?;
+    all.append(&mut entries);
+    Ok(all)
 }
 
 /// Verify the HMAC chain integrity of the audit log.
 pub fn verify_chain(audit_path: &Path, hmac_key: &[u8]) -> Result<(usize, bool)> {
     let entries = read_entries(audit_path)?;
-    let mut prev_hmac = String::new();
+    let mut prev_hmac = entries.first().map(chain_start).transpose()?.unwrap_or_default();
 
     for (i, entry) in entries.iter().enumerate() {
-        let chain_data = format!(
-            "{}|{}|{}|{:?}|{}|{}|{:?}",
-            prev_hmac,
-            entry.timestamp.to_rfc3339(),
-            entry.operation,
-            entry.secret,
-            entry.actor,
-            entry.outcome,
-            entry.detail,
-        );
-
-        let expected_hmac = compute_chain_hmac(&chain_data, hmac_key);
+        let expected_hmac =
+            compute_chain_mac(entry.chain_alg, &chain_data(&prev_hmac, entry), hmac_key);
         if expected_hmac != entry.chain_hmac {
             return Err(AuthyError::AuditChainBroken(i));
         }
@@ -129,29 +300,236 @@ pub fn verify_chain(audit_path: &Path, hmac_key: &[u8]) -> Result<(usize, bool)>
     Ok((entries.len(), true))
 }
 
-fn read_last_hmac(audit_path: &Path) -> String {
-    if !audit_path.exists() {
-        return String::new();
+/// Verify the HMAC chain only for entries whose timestamp falls within
+/// `[from, to]` (either bound may be open). Each entry is checked against
+/// the stored HMAC of the entry before it, so entries outside the range are
+/// not re-verified. Returns the number of entries checked.
+pub fn verify_chain_range(
+    audit_path: &Path,
+    hmac_key: &[u8],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<usize> {
+    let entries = read_entries(audit_path)?;
+    let mut checked = 0;
+
+    for (i, entry) in entries.iter().enumerate() {
+        let in_range = from.map_or(true, |f| entry.timestamp >= f)
+            && to.map_or(true, |t| entry.timestamp <= t);
+        if !in_range {
+            continue;
+        }
+
+        let prev_hmac = if i == 0 {
+            chain_start(entry)?
+        } else {
+            entries[i - 1].chain_hmac.clone()
+        };
+        let expected_hmac =
+            compute_chain_mac(entry.chain_alg, &chain_data(&prev_hmac, entry), hmac_key);
+        if expected_hmac != entry.chain_hmac {
+            return Err(AuthyError::AuditChainBroken(i));
+        }
+        checked += 1;
     }
 
-    // Read the file and get the last non-empty line
-    if let Ok(content) = fs::read_to_string(audit_path) {
-        for line in content.lines().rev() {
-            if !line.trim().is_empty() {
-                if let Ok(entry) = serde_json::from_str::<AuditEntry>(line) {
-                    return entry.chain_hmac;
-                }
+    Ok(checked)
+}
+
+/// The HMAC the first entry read is chained to: empty for a log that was
+/// never rotated. If it is a link record, [`read_entries`] couldn't find the
+/// archive it names, and the chain can't be verified.
+fn chain_start(first: &AuditEntry) -> Result<String> {
+    match archive::parse_link(first) {
+        Some(link) => Err(AuthyError::AuditArchiveMissing(link.archive)),
+        None => Ok(String::new()),
+    }
+}
+
+/// The data covered by an entry's chain HMAC: the previous HMAC plus every field.
+/// Non-default algorithms are bound in too, so an entry can't be relabelled,
+/// and so are the counters when present.
+fn chain_data(prev_hmac: &str, entry: &AuditEntry) -> String {
+    let mut data = format!(
+        "{}|{}|{}|{:?}|{}|{}|{:?}",
+        prev_hmac,
+        entry.timestamp.to_rfc3339(),
+        entry.operation,
+        entry.secret,
+        entry.actor,
+        entry.outcome,
+        entry.detail,
+    );
+    if entry.chain_alg == ChainAlgorithm::Blake3 {
+        data.push_str("|blake3");
+    }
+    if let Some(seq) = entry.seq {
+        data.push_str(&format!("|seq={}", seq));
+    }
+    if let Some(since) = entry.since_checkpoint {
+        data.push_str(&format!("|since={}", since));
+    }
+    data
+}
+
+/// The end of the log, as needed to append to it.
+#[derive(Debug, Default)]
+struct ChainTail {
+    /// The last entry's chain HMAC.
+    hmac: String,
+    /// Entries in the log.
+    count: usize,
+    /// Entries after the last checkpoint.
+    since_checkpoint: usize,
+}
+
+/// Read the end of the log at `audit_path`. Only the last line is read,
+/// unless it was written before entries carried their position; then the
+/// whole log is counted once.
+fn read_chain_tail(audit_path: &Path) -> ChainTail {
+    let last = File::open(audit_path)
+        .and_then(|mut file| last_line(&mut file))
+        .ok()
+        .flatten()
+        .and_then(|(_, line)| serde_json::from_slice::<AuditEntry>(&line).ok());
+    match last {
+        None if !audit_path.exists() => ChainTail::default(),
+        Some(AuditEntry {
+            seq: Some(count),
+            since_checkpoint,
+            operation,
+            chain_hmac,
+            ..
+        }) => ChainTail {
+            hmac: chain_hmac,
+            count,
+            since_checkpoint: if operation == checkpoint::OPERATION {
+                0
+            } else {
+                since_checkpoint.unwrap_or(count)
+            },
+        },
+        _ => count_chain_tail(audit_path),
+    }
+}
+
+/// [`read_chain_tail`] for logs without positions, reading every entry.
+fn count_chain_tail(audit_path: &Path) -> ChainTail {
+    let mut tail = ChainTail::default();
+    let content = match fs::read_to_string(audit_path) {
+        Ok(content) => content,
+        Err(_) => return tail,
+    };
+
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        if let Ok(entry) = serde_json::from_str::<AuditEntry>(line) {
+            tail.since_checkpoint = if entry.operation == checkpoint::OPERATION {
+                0
+            } else {
+                tail.since_checkpoint + 1
+            };
+            tail.hmac = entry.chain_hmac;
+            tail.count += 1;
+        }
+    }
+    tail
+}
+
+/// How much of the log is read at a time when searching back for its last
+/// line.
+const TAIL_CHUNK: u64 = 8 * 1024;
+
+/// The offset of the last non-blank line of `file` and everything from
+/// there to the end. Reads backwards from the end, so the cost doesn't grow
+/// with the log. `None` for a blank file.
+fn last_line(file: &mut File) -> std::io::Result<Option<(u64, Vec<u8>)>> {
+    let mut pos = file.metadata()?.len();
+    let mut data = Vec::new();
+    loop {
+        if let Some(end) = data.iter().rposition(|b: &u8| !b.is_ascii_whitespace()) {
+            if let Some(newline) = data[..end].iter().rposition(|&b| b == b'\n') {
+                data.drain(..=newline);
+                return Ok(Some((pos + newline as u64 + 1, data)));
             }
         }
+        if pos == 0 {
+            let found = data.iter().any(|b| !b.is_ascii_whitespace());
+            return Ok(found.then_some((0, data)));
+        }
+        let start = pos.saturating_sub(TAIL_CHUNK);
+        let mut chunk = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut data);
+        data = chunk;
+        pos = start;
     }
+}
 
-    String::new()
+/// Byte offset of the final line if it doesn't parse as an entry.
+fn torn_tail(content: &[u8]) -> Option<usize> {
+    let end = content
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    if end == 0 {
+        return None;
+    }
+    let start = content[..end]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    match serde_json::from_slice::<AuditEntry>(&content[start..end]) {
+        Ok(_) => None,
+        Err(_) => Some(start),
+    }
 }
 
-fn compute_chain_hmac(data: &str, hmac_key: &[u8]) -> String {
-    let mut mac = HmacSha256::new_from_slice(hmac_key).expect("HMAC can take key of any size");
-    mac.update(data.as_bytes());
-    hex::encode(mac.finalize().into_bytes())
+/// Move a torn final line to the quarantine file and truncate the log to
+/// the last complete entry. Returns whether anything was moved. Only the
+/// end of the log is read.
+fn quarantine_torn_tail(audit_path: &Path) -> Result<bool> {
+    let mut log = match OpenOptions::new().read(true).write(true).open(audit_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let (start, line) = match last_line(&mut log)? {
+        Some((start, line)) if serde_json::from_slice::<AuditEntry>(&line).is_err() => {
+            (start, line)
+        }
+        _ => return Ok(false),
+    };
+
+    // Keep the fragment before dropping it from the log
+    let mut quarantine = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(quarantine_path(audit_path))?;
+    let fragment = line.strip_suffix(b"\n").unwrap_or(&line);
+    quarantine.write_all(fragment)?;
+    quarantine.write_all(b"\n")?;
+    quarantine.sync_data()?;
+
+    log.set_len(start)?;
+    log.sync_data()?;
+    Ok(true)
+}
+
+fn compute_chain_mac(alg: ChainAlgorithm, data: &str, hmac_key: &[u8]) -> String {
+    match alg {
+        ChainAlgorithm::HmacSha256 => {
+            let mut mac =
+                HmacSha256::new_from_slice(hmac_key).expect("HMAC can take key of any size");
+            mac.update(data.as_bytes());
+            hex::encode(mac.finalize().into_bytes())
+        }
+        ChainAlgorithm::Blake3 => {
+            // keyed_hash wants exactly 32 bytes; derive them from the audit key
+            let key = blake3::derive_key("authy audit chain v1", hmac_key);
+            blake3::keyed_hash(&key, data.as_bytes()).to_hex().to_string()
+        }
+    }
 }
 
 /// Derive the audit HMAC key from the master key material.
@@ -163,6 +541,78 @@ pub fn derive_audit_key(master_material: &[u8]) -> Vec<u8> {
 pub fn key_material(key: &crate::vault::VaultKey) -> Vec<u8> {
     match key {
         crate::vault::VaultKey::Passphrase(p) => p.as_bytes().to_vec(),
-        crate::vault::VaultKey::Keyfile { identity, .. } => identity.as_bytes().to_vec(),
+        crate::vault::VaultKey::Keyfile { identity, .. }
+        | crate::vault::VaultKey::SshAgent { identity, .. } => identity.as_bytes().to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"test-audit-key";
+
+    fn append(path: &Path, n: usize) {
+        for i in 0..n {
+            log_event(path, "get", Some(&format!("s{}", i)), "master", "success", None, KEY)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_tail_comes_from_the_last_line() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        // Enough entries that the last line isn't in the final chunk alone
+        append(&log, 300);
+        assert!(fs::metadata(&log).unwrap().len() > TAIL_CHUNK * 2);
+
+        let tail = read_chain_tail(&log);
+        let entries = read_entries(&log).unwrap();
+        assert_eq!(tail.count, 300);
+        assert_eq!(tail.hmac, entries[299].chain_hmac);
+        assert_eq!(entries[299].seq, Some(300));
+        assert_eq!(verify_chain(&log, KEY).unwrap(), (300, true));
+    }
+
+    #[test]
+    fn test_tail_of_a_log_without_positions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        append(&log, 3);
+        // As written by versions that didn't record positions
+        let content = fs::read_to_string(&log).unwrap();
+        let legacy: String = content
+            .lines()
+            .map(|line| {
+                let mut entry: AuditEntry = serde_json::from_str(line).unwrap();
+                entry.seq = None;
+                format!("{}\n", serde_json::to_string(&entry).unwrap())
+            })
+            .collect();
+        fs::write(&log, legacy).unwrap();
+
+        assert_eq!(read_chain_tail(&log).count, 3);
+        append(&log, 1);
+        assert_eq!(read_entries(&log).unwrap()[3].seq, Some(4));
+    }
+
+    #[test]
+    fn test_torn_tail_found_from_the_end() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        append(&log, 200);
+        let intact = fs::metadata(&log).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+        file.write_all(b"{\"timestamp\":\"2026-").unwrap();
+        drop(file);
+
+        assert!(quarantine_torn_tail(&log).unwrap());
+        assert_eq!(fs::metadata(&log).unwrap().len(), intact);
+        assert!(!quarantine_torn_tail(&log).unwrap());
+        assert_eq!(
+            fs::read_to_string(quarantine_path(&log)).unwrap(),
+            "{\"timestamp\":\"2026-\n"
+        );
     }
 }
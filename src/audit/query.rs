@@ -0,0 +1,274 @@
+//! Selecting audit entries by time, operation, actor, secret and outcome.
+//! `authy audit show` builds an [`AuditQuery`] from its flags and the TUI
+//! from its `/` filter ([`AuditQuery::parse`]), so both match the same way.
+
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use globset::GlobMatcher;
+
+use super::{read_entries, AuditEntry};
+use crate::error::{AuthyError, Result};
+
+/// Criteria for audit entries. Every criterion that is set must match.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Entries at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Entries at or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Operation, exactly (case-insensitive), e.g. `get`.
+    pub operation: Option<String>,
+    /// Text the actor contains (case-insensitive): `master`, `token`,
+    /// or one session as `token(<id>)`.
+    pub actor: Option<String>,
+    /// Secret name or glob (`db-*`).
+    pub secret: Option<GlobMatcher>,
+    /// Outcome, exactly (case-insensitive), e.g. `denied`.
+    pub outcome: Option<String>,
+    /// Text found in the operation, actor, outcome or secret.
+    pub text: Option<String>,
+}
+
+impl AuditQuery {
+    /// Match secret names against `pattern`.
+    pub fn with_secret(mut self, pattern: &str) -> Result<Self> {
+        let glob = globset::Glob::new(pattern)
+            .map_err(|e| AuthyError::Other(format!("Invalid secret pattern '{}': {}", pattern, e)))?;
+        self.secret = Some(glob.compile_matcher());
+        Ok(self)
+    }
+
+    /// Parse a filter line: `key:value` terms for `since`, `until`, `op`
+    /// (or `operation`), `actor`, `secret` and `outcome`, with any other
+    /// words matched as free text. `op:get denied db` finds `get` entries
+    /// mentioning both "denied" and "db".
+    pub fn parse(filter: &str) -> Result<Self> {
+        let mut query = AuditQuery::default();
+        let mut words = Vec::new();
+        for term in filter.split_whitespace() {
+            let (key, value) = match term.split_once(':') {
+                Some((key, value)) if !value.is_empty() => (key, value),
+                _ => {
+                    words.push(term);
+                    continue;
+                }
+            };
+            match key.to_lowercase().as_str() {
+                "since" => query.since = parse_time_bound(value, false)?,
+                "until" => query.until = parse_time_bound(value, true)?,
+                "op" | "operation" => query.operation = Some(value.to_string()),
+                "actor" => query.actor = Some(value.to_string()),
+                "secret" => query = query.with_secret(value)?,
+                "outcome" => query.outcome = Some(value.to_string()),
+                _ => words.push(term),
+            }
+        }
+        if !words.is_empty() {
+            query.text = Some(words.join(" "));
+        }
+        Ok(query)
+    }
+
+    /// Whether `entry` meets every criterion.
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        if self.since.is_some_and(|since| entry.timestamp < since)
+            || self.until.is_some_and(|until| entry.timestamp > until)
+        {
+            return false;
+        }
+        if let Some(ref operation) = self.operation {
+            if !entry.operation.eq_ignore_ascii_case(operation) {
+                return false;
+            }
+        }
+        if let Some(ref actor) = self.actor {
+            if !entry.actor.to_lowercase().contains(&actor.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(ref secret) = self.secret {
+            if !entry.secret.as_deref().is_some_and(|name| secret.is_match(name)) {
+                return false;
+            }
+        }
+        if let Some(ref outcome) = self.outcome {
+            if !entry.outcome.eq_ignore_ascii_case(outcome) {
+                return false;
+            }
+        }
+        if let Some(ref text) = self.text {
+            let fields = [
+                entry.operation.as_str(),
+                entry.actor.as_str(),
+                entry.outcome.as_str(),
+                entry.secret.as_deref().unwrap_or(""),
+            ]
+            .join(" ")
+            .to_lowercase();
+            if !text
+                .to_lowercase()
+                .split_whitespace()
+                .all(|word| fields.contains(word))
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether no criterion is set.
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none()
+            && self.until.is_none()
+            && self.operation.is_none()
+            && self.actor.is_none()
+            && self.secret.is_none()
+            && self.outcome.is_none()
+            && self.text.is_none()
+    }
+}
+
+/// The entries of the log at `audit_path` (archives included) that match.
+pub fn query(audit_path: &Path, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+    let mut entries = read_entries(audit_path)?;
+    entries.retain(|entry| query.matches(entry));
+    Ok(entries)
+}
+
+/// The entries in `entries` that match, in order.
+pub fn filter<'a>(entries: &'a [AuditEntry], query: &AuditQuery) -> Vec<&'a AuditEntry> {
+    entries.iter().filter(|entry| query.matches(entry)).collect()
+}
+
+/// Parse a time bound: RFC 3339, `YYYY-MM-DD`, or a duration before now
+/// (`30m`, `24h`, `7d`). A bare date covers the whole day, so as an end
+/// bound (`end`) it means the day's last second. Empty input is no bound.
+pub fn parse_time_bound(input: &str, end: bool) -> Result<Option<DateTime<Utc>>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(ts) = DateTime::parse_from_rfc3339(input) {
+        return Ok(Some(ts.with_timezone(&Utc)));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let time = if end {
+            NaiveTime::from_hms_opt(23, 59, 59)
+        } else {
+            NaiveTime::from_hms_opt(0, 0, 0)
+        };
+        return Ok(time.map(|t| date.and_time(t).and_utc()));
+    }
+    match crate::session::parse_ttl(input) {
+        Ok(ago) => Ok(Some(Utc::now() - ago)),
+        Err(_) => Err(AuthyError::Other(format!(
+            "Invalid time '{}': use YYYY-MM-DD, RFC 3339, or a duration like 24h",
+            input
+        ))),
+    }
+}
+
+/// Header of [`to_csv`] output.
+pub const CSV_HEADER: &str = "timestamp,operation,secret,actor,outcome,detail,chain_hmac";
+
+/// Render entries as CSV with a [`CSV_HEADER`] line.
+pub fn to_csv(entries: &[&AuditEntry]) -> String {
+    let mut out = format!("{}\n", CSV_HEADER);
+    for e in entries {
+        let fields = [
+            e.timestamp.to_rfc3339(),
+            e.operation.clone(),
+            e.secret.clone().unwrap_or_default(),
+            e.actor.clone(),
+            e.outcome.clone(),
+            e.detail.clone().unwrap_or_default(),
+            e.chain_hmac.clone(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render entries as JSON Lines, one log entry per line as stored.
+pub fn to_jsonl(entries: &[&AuditEntry]) -> Result<String> {
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(
+            &serde_json::to_string(entry).map_err(|e| AuthyError::Serialization(e.to_string()))?,
+        );
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChainAlgorithm;
+
+    fn entry(operation: &str, secret: Option<&str>, actor: &str, outcome: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: "2026-03-01T12:00:00Z".parse().unwrap(),
+            operation: operation.to_string(),
+            secret: secret.map(str::to_string),
+            actor: actor.to_string(),
+            outcome: outcome.to_string(),
+            detail: None,
+            chain_hmac: String::new(),
+            chain_alg: ChainAlgorithm::default(),
+            seq: None,
+            since_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let get = entry("get", Some("db-url"), "token(abc123)", "denied");
+        let store = entry("store", Some("api-key"), "master(passphrase)", "success");
+
+        let query = AuditQuery::parse("op:GET actor:token secret:db-* outcome:denied").unwrap();
+        assert!(query.matches(&get));
+        assert!(!query.matches(&store));
+
+        let query = AuditQuery::parse("master api").unwrap();
+        assert_eq!(query.text.as_deref(), Some("master api"));
+        assert!(query.matches(&store));
+        assert!(!query.matches(&get));
+
+        let query = AuditQuery::parse("since:2026-03-02").unwrap();
+        assert!(!query.matches(&get));
+        let query = AuditQuery::parse("until:2026-03-01").unwrap();
+        assert!(query.matches(&get));
+
+        assert!(AuditQuery::parse("").unwrap().is_empty());
+        assert!(AuditQuery::parse("since:yesterday-ish").is_err());
+    }
+
+    #[test]
+    fn test_relative_bound() {
+        let bound = parse_time_bound("1h", false).unwrap().unwrap();
+        let expected = Utc::now() - chrono::Duration::hours(1);
+        assert!((bound - expected).num_seconds().abs() < 5);
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let mut e = entry("get", None, "master", "success");
+        e.detail = Some("scope=a, reason=\"x\"".to_string());
+        let csv = to_csv(&[&e]);
+        assert!(csv.starts_with(CSV_HEADER));
+        assert!(csv.contains(",\"scope=a, reason=\"\"x\"\"\","));
+    }
+}
@@ -0,0 +1,292 @@
+//! Forwarding audit entries to syslog, journald or a webhook, configured as
+//! `[[audit.sinks]]`. The local chained log stays the record of truth: an
+//! entry is forwarded after it has been written there, and a sink that
+//! can't be reached never fails the operation being audited.
+//!
+//! Webhook deliveries that fail are appended to a queue file next to the
+//! log (`audit.log.<id>.queue`) and sent, oldest first, before the next
+//! entry once [`RETRY_AFTER`] has passed since the last attempt.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::SecondsFormat;
+use sha2::{Digest, Sha256};
+
+use super::AuditEntry;
+use crate::config::AuditSink;
+use crate::error::{AuthyError, Result};
+
+/// How long a webhook that failed is left alone before delivery is tried
+/// again, so a down endpoint doesn't stall every command on its timeout.
+pub const RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Entries kept per webhook queue; the oldest are dropped first.
+pub const MAX_QUEUED: usize = 10_000;
+
+/// syslog facility `authpriv`.
+const FACILITY_AUTHPRIV: u8 = 10;
+
+/// Forward an entry already written to `audit_path` (as `line`) to every
+/// sink. Errors are dropped: see the module docs.
+pub fn forward(sinks: &[AuditSink], entry: &AuditEntry, line: &str, audit_path: &Path) {
+    for sink in sinks {
+        let _ = match sink {
+            AuditSink::Syslog { address } => send_syslog(address, &syslog_message(entry, line)),
+            AuditSink::Journald { socket } => send_datagram(socket, &journald_message(entry)),
+            AuditSink::Webhook { url, timeout_secs } => deliver_webhook(
+                url,
+                Duration::from_secs(*timeout_secs),
+                line,
+                &queue_path(audit_path, url),
+            ),
+        };
+    }
+}
+
+/// An RFC 5424 message carrying the entry's JSON. Denials and failures are
+/// sent as warnings, everything else as info.
+pub fn syslog_message(entry: &AuditEntry, line: &str) -> String {
+    let severity = if entry.outcome == "success" { 6 } else { 4 };
+    format!(
+        "<{}>1 {} - authy {} - - {}",
+        FACILITY_AUTHPRIV * 8 + severity,
+        entry.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+        std::process::id(),
+        line
+    )
+}
+
+/// A journald native-protocol datagram: one `FIELD=value` per line.
+pub fn journald_message(entry: &AuditEntry) -> String {
+    let priority = if entry.outcome == "success" { "6" } else { "4" };
+    let mut message = format!("authy: {} {}", entry.operation, entry.outcome);
+    if let Some(ref secret) = entry.secret {
+        message.push_str(&format!(" {}", secret));
+    }
+    message.push_str(&format!(" by {}", entry.actor));
+
+    let mut fields = vec![
+        ("MESSAGE", message),
+        ("PRIORITY", priority.to_string()),
+        ("SYSLOG_IDENTIFIER", "authy".to_string()),
+        ("AUTHY_OPERATION", entry.operation.clone()),
+        ("AUTHY_OUTCOME", entry.outcome.clone()),
+        ("AUTHY_ACTOR", entry.actor.clone()),
+        ("AUTHY_CHAIN_HMAC", entry.chain_hmac.clone()),
+    ];
+    if let Some(ref secret) = entry.secret {
+        fields.push(("AUTHY_SECRET", secret.clone()));
+    }
+    if let Some(ref detail) = entry.detail {
+        fields.push(("AUTHY_DETAIL", detail.clone()));
+    }
+    // The simple `FIELD=value` form can't carry newlines
+    fields
+        .into_iter()
+        .map(|(k, v)| format!("{}={}\n", k, v.replace('\n', " ")))
+        .collect()
+}
+
+/// Where entries for the webhook at `url` wait after a failed delivery.
+pub fn queue_path(audit_path: &Path, url: &str) -> PathBuf {
+    let id = hex::encode(&Sha256::digest(url.as_bytes())[..4]);
+    let mut name = audit_path.as_os_str().to_owned();
+    name.push(format!(".{}.queue", id));
+    PathBuf::from(name)
+}
+
+fn send_syslog(address: &str, message: &str) -> Result<()> {
+    match address.strip_prefix("udp://") {
+        Some(target) => {
+            use std::net::{ToSocketAddrs, UdpSocket};
+            let target = target
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| AuthyError::Other(format!("Cannot resolve {}", target)))?;
+            let local = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+            UdpSocket::bind(local)?.send_to(message.as_bytes(), target)?;
+            Ok(())
+        }
+        None => send_datagram(address, message),
+    }
+}
+
+#[cfg(unix)]
+fn send_datagram(socket: &str, message: &str) -> Result<()> {
+    std::os::unix::net::UnixDatagram::unbound()?.send_to(message.as_bytes(), socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_datagram(socket: &str, _message: &str) -> Result<()> {
+    Err(AuthyError::Other(format!(
+        "Unix socket sinks are not supported on this platform ({})",
+        socket
+    )))
+}
+
+/// Send `line` after anything already queued for this webhook, keeping
+/// whatever couldn't be delivered in the queue. The queue file starts with
+/// a `# attempted <unix seconds>` line recording the last delivery attempt.
+fn deliver_webhook(url: &str, timeout: Duration, line: &str, queue: &Path) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let contents = fs::read_to_string(queue).unwrap_or_default();
+    let mut lines = contents.lines();
+    let last_attempt = lines
+        .next()
+        .and_then(|header| header.strip_prefix("# attempted "))
+        .and_then(|secs| secs.parse::<u64>().ok());
+    let mut pending: Vec<&str> = lines.collect();
+    pending.push(line);
+
+    if last_attempt.is_some_and(|at| now.saturating_sub(at) < RETRY_AFTER.as_secs()) {
+        let mut file = fs::OpenOptions::new().append(true).open(queue)?;
+        writeln!(file, "{}", line)?;
+        return Err(AuthyError::Other(format!("Delivery to {} is backing off", url)));
+    }
+
+    let mut sent = 0;
+    for body in &pending {
+        let delivered = ureq::post(url)
+            .timeout(timeout)
+            .set("Content-Type", "application/json")
+            .send_string(body);
+        if delivered.is_err() {
+            break;
+        }
+        sent += 1;
+    }
+
+    let rest = &pending[sent..];
+    if rest.is_empty() {
+        if !contents.is_empty() {
+            fs::remove_file(queue)?;
+        }
+        return Ok(());
+    }
+    let rest = &rest[rest.len().saturating_sub(MAX_QUEUED)..];
+    let mut queued = format!("# attempted {}\n", now);
+    for body in rest {
+        queued.push_str(body);
+        queued.push('\n');
+    }
+    fs::write(queue, queued)?;
+    Err(AuthyError::Other(format!(
+        "{} audit entries queued for {}",
+        rest.len(),
+        url
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChainAlgorithm;
+    use chrono::Utc;
+
+    fn entry(outcome: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            operation: "get".to_string(),
+            secret: Some("db-url".to_string()),
+            actor: "master".to_string(),
+            outcome: outcome.to_string(),
+            detail: Some("scope=app\nsecond line".to_string()),
+            chain_hmac: "abc".to_string(),
+            chain_alg: ChainAlgorithm::default(),
+            seq: None,
+            since_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_messages() {
+        let syslog = syslog_message(&entry("denied"), "{}");
+        assert!(syslog.starts_with("<84>1 "), "{}", syslog);
+        assert!(syslog.ends_with(" - - {}"));
+        assert!(syslog_message(&entry("success"), "{}").starts_with("<86>1 "));
+
+        let journald = journald_message(&entry("success"));
+        assert!(journald.starts_with("MESSAGE=authy: get success db-url by master\n"));
+        assert!(journald.contains("AUTHY_DETAIL=scope=app second line\n"));
+        assert!(journald.contains("PRIORITY=6\n"));
+    }
+
+    #[test]
+    fn test_failed_webhook_deliveries_are_queued() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        // Nothing listens on a port that was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/audit", port);
+        let queue = queue_path(&log, &url);
+        let timeout = Duration::from_secs(1);
+
+        assert!(deliver_webhook(&url, timeout, "{\"n\":1}", &queue).is_err());
+        let attempted = fs::read_to_string(&queue).unwrap();
+        assert!(attempted.starts_with("# attempted "));
+        // Backing off: queued without another attempt
+        assert!(deliver_webhook(&url, timeout, "{\"n\":2}", &queue).is_err());
+        assert_eq!(
+            fs::read_to_string(&queue).unwrap(),
+            format!("{}{{\"n\":2}}\n", attempted)
+        );
+        assert!(attempted.ends_with("\n{\"n\":1}\n"));
+        assert_ne!(queue_path(&log, "http://other"), queue);
+    }
+
+    /// Accept `count` requests, answering 200 and sending back each body.
+    fn serve(count: usize) -> (String, std::sync::mpsc::Receiver<String>) {
+        use std::io::{BufRead, BufReader, Read};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/audit", listener.local_addr().unwrap());
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+                tx.send(String::from_utf8(body).unwrap()).unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[test]
+    fn test_queue_is_flushed_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (url, received) = serve(3);
+        let queue = queue_path(&dir.path().join("audit.log"), &url);
+        fs::write(&queue, "# attempted 0\n{\"n\":1}\n{\"n\":2}\n").unwrap();
+
+        deliver_webhook(&url, Duration::from_secs(5), "{\"n\":3}", &queue).unwrap();
+        let bodies: Vec<String> = received.iter().take(3).collect();
+        assert_eq!(bodies, ["{\"n\":1}", "{\"n\":2}", "{\"n\":3}"]);
+        assert!(!queue.exists());
+    }
+}
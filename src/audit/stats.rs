@@ -0,0 +1,265 @@
+//! Aggregates over the audit log for `authy audit stats`: what each actor
+//! did, which secrets are read most, how often things fail, and patterns
+//! worth a look, like an actor that keeps getting denied.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+
+use super::{AuditEntry, VALUE_READS};
+
+/// Outcomes that aren't failures. `alert` is a canary read that went
+/// through, reported as an anomaly instead.
+const OK_OUTCOMES: &[&str] = &["success", "allowed", "default", "alert"];
+
+/// Whether `outcome` counts against an actor's failure rate.
+pub fn is_failure(outcome: &str) -> bool {
+    !OK_OUTCOMES.contains(&outcome)
+}
+
+/// Totals for one actor.
+#[derive(Debug, Clone, Default)]
+pub struct ActorStats {
+    pub actor: String,
+    pub total: usize,
+    pub failures: usize,
+    /// Entries per operation.
+    pub operations: BTreeMap<String, usize>,
+}
+
+impl ActorStats {
+    /// Failures as a fraction of all entries, 0.0 to 1.0.
+    pub fn failure_rate(&self) -> f64 {
+        rate(self.failures, self.total)
+    }
+}
+
+/// Totals for one secret.
+#[derive(Debug, Clone)]
+pub struct SecretStats {
+    pub secret: String,
+    /// Entries that handed out (or tried to hand out) the value.
+    pub reads: usize,
+    /// Every other entry naming the secret.
+    pub other: usize,
+    pub denied: usize,
+    pub first_access: DateTime<Utc>,
+    pub last_access: DateTime<Utc>,
+}
+
+/// Something in the log that deserves attention.
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    /// `repeated_denials` or `canary_alert`.
+    pub kind: &'static str,
+    /// The actor for `repeated_denials`, the secret for `canary_alert`.
+    pub subject: String,
+    pub count: usize,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    /// Secrets involved, for `repeated_denials`.
+    pub secrets: Vec<String>,
+}
+
+/// The aggregates for a set of entries.
+#[derive(Debug, Clone, Default)]
+pub struct AuditStats {
+    pub total: usize,
+    pub failures: usize,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    /// Entries per outcome.
+    pub outcomes: BTreeMap<String, usize>,
+    /// Busiest actor first.
+    pub actors: Vec<ActorStats>,
+    /// Most read secret first.
+    pub secrets: Vec<SecretStats>,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl AuditStats {
+    /// Aggregate `entries`. An actor denied at least `denial_threshold`
+    /// times is flagged; 0 turns that check off.
+    pub fn compute<'a>(
+        entries: impl IntoIterator<Item = &'a AuditEntry>,
+        denial_threshold: usize,
+    ) -> Self {
+        let mut stats = AuditStats::default();
+        let mut actors: HashMap<&str, ActorStats> = HashMap::new();
+        let mut secrets: HashMap<&str, SecretStats> = HashMap::new();
+        let mut denials: HashMap<&str, Anomaly> = HashMap::new();
+        let mut alerts: HashMap<&str, Anomaly> = HashMap::new();
+
+        for entry in entries {
+            let ts = entry.timestamp;
+            let failed = is_failure(&entry.outcome);
+            let denied = entry.outcome == "denied";
+            stats.total += 1;
+            stats.failures += failed as usize;
+            stats.first = Some(stats.first.map_or(ts, |t| t.min(ts)));
+            stats.last = Some(stats.last.map_or(ts, |t| t.max(ts)));
+            *stats.outcomes.entry(entry.outcome.clone()).or_default() += 1;
+
+            let actor = actors.entry(&entry.actor).or_insert_with(|| ActorStats {
+                actor: entry.actor.clone(),
+                ..ActorStats::default()
+            });
+            actor.total += 1;
+            actor.failures += failed as usize;
+            *actor.operations.entry(entry.operation.clone()).or_default() += 1;
+
+            if let Some(ref name) = entry.secret {
+                let secret = secrets.entry(name).or_insert_with(|| SecretStats {
+                    secret: name.clone(),
+                    reads: 0,
+                    other: 0,
+                    denied: 0,
+                    first_access: ts,
+                    last_access: ts,
+                });
+                if VALUE_READS.contains(&entry.operation.as_str()) {
+                    secret.reads += 1;
+                } else {
+                    secret.other += 1;
+                }
+                secret.denied += denied as usize;
+                secret.first_access = secret.first_access.min(ts);
+                secret.last_access = secret.last_access.max(ts);
+            }
+
+            if denied {
+                note(&mut denials, &entry.actor, "repeated_denials", ts, entry.secret.as_deref());
+            }
+            if entry.outcome == "alert" {
+                let name = entry.secret.as_deref().unwrap_or("-");
+                note(&mut alerts, name, "canary_alert", ts, None);
+            }
+        }
+
+        stats.actors = actors.into_values().collect();
+        stats
+            .actors
+            .sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.actor.cmp(&b.actor)));
+        stats.secrets = secrets.into_values().collect();
+        stats.secrets.sort_by(|a, b| {
+            b.reads
+                .cmp(&a.reads)
+                .then_with(|| (b.reads + b.other).cmp(&(a.reads + a.other)))
+                .then_with(|| a.secret.cmp(&b.secret))
+        });
+
+        let mut anomalies: Vec<Anomaly> = denials
+            .into_values()
+            .filter(|a| denial_threshold > 0 && a.count >= denial_threshold)
+            .chain(alerts.into_values())
+            .collect();
+        anomalies.sort_by(|a, b| {
+            a.kind
+                .cmp(b.kind)
+                .then_with(|| b.count.cmp(&a.count))
+                .then_with(|| a.subject.cmp(&b.subject))
+        });
+        stats.anomalies = anomalies;
+        stats
+    }
+
+    /// Failures as a fraction of all entries, 0.0 to 1.0.
+    pub fn failure_rate(&self) -> f64 {
+        rate(self.failures, self.total)
+    }
+}
+
+/// Count one occurrence for `subject` in `anomalies`.
+fn note<'a>(
+    anomalies: &mut HashMap<&'a str, Anomaly>,
+    subject: &'a str,
+    kind: &'static str,
+    ts: DateTime<Utc>,
+    secret: Option<&str>,
+) {
+    let anomaly = anomalies.entry(subject).or_insert_with(|| Anomaly {
+        kind,
+        subject: subject.to_string(),
+        count: 0,
+        first: ts,
+        last: ts,
+        secrets: Vec::new(),
+    });
+    anomaly.count += 1;
+    anomaly.first = anomaly.first.min(ts);
+    anomaly.last = anomaly.last.max(ts);
+    if let Some(secret) = secret {
+        if !anomaly.secrets.iter().any(|s| s == secret) {
+            anomaly.secrets.push(secret.to_string());
+        }
+    }
+}
+
+fn rate(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ChainAlgorithm;
+
+    fn entry(minute: u32, operation: &str, secret: Option<&str>, actor: &str, outcome: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: format!("2026-03-01T12:{:02}:00Z", minute).parse().unwrap(),
+            operation: operation.to_string(),
+            secret: secret.map(str::to_string),
+            actor: actor.to_string(),
+            outcome: outcome.to_string(),
+            detail: None,
+            chain_hmac: String::new(),
+            chain_alg: ChainAlgorithm::default(),
+            seq: None,
+            since_checkpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_compute() {
+        let entries = vec![
+            entry(0, "store", Some("db-url"), "master(passphrase)", "success"),
+            entry(1, "get", Some("db-url"), "token(abc)", "success"),
+            entry(2, "get", Some("db-url"), "token(abc)", "success"),
+            entry(3, "get", Some("api-key"), "token(abc)", "denied"),
+            entry(4, "get", Some("ssh-key"), "token(abc)", "denied"),
+            entry(5, "get", Some("api-key"), "token(abc)", "denied"),
+            entry(6, "get", Some("canary"), "master(passphrase)", "alert"),
+        ];
+        let stats = AuditStats::compute(&entries, 3);
+
+        assert_eq!(stats.total, 7);
+        assert_eq!(stats.failures, 3);
+        assert_eq!(stats.outcomes["denied"], 3);
+
+        let token = &stats.actors[0];
+        assert_eq!(token.actor, "token(abc)");
+        assert_eq!(token.total, 5);
+        assert_eq!(token.operations["get"], 5);
+        assert!((token.failure_rate() - 0.6).abs() < 1e-9);
+
+        let db = &stats.secrets[0];
+        assert_eq!(db.secret, "db-url");
+        assert_eq!((db.reads, db.other), (2, 1));
+        assert_eq!(db.first_access, entries[0].timestamp);
+        assert_eq!(db.last_access, entries[2].timestamp);
+
+        assert_eq!(stats.anomalies.len(), 2);
+        assert_eq!(stats.anomalies[0].kind, "canary_alert");
+        assert_eq!(stats.anomalies[0].subject, "canary");
+        let denials = &stats.anomalies[1];
+        assert_eq!((denials.kind, denials.count), ("repeated_denials", 3));
+        assert_eq!(denials.secrets, vec!["api-key", "ssh-key"]);
+
+        let stats = AuditStats::compute(&entries, 4);
+        assert_eq!(stats.anomalies.len(), 1);
+    }
+}
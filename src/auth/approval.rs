@@ -0,0 +1,139 @@
+//! Second-credential approval for protected secrets and policies.
+//!
+//! Removing a protected item (or rekeying a vault that holds one) needs the
+//! master credential *and* the keyfile of a registered approver, passed with
+//! `--approve-with`. Approvers are age recipients kept in
+//! [`Vault::approvers`]; the approving keyfile must hold the matching identity
+//! and can't be the vault's own keyfile.
+
+use rand::RngCore;
+
+use crate::error::{AuthyError, Result};
+use crate::vault::{crypto, plugin, Vault, VaultKey};
+
+/// Check that `recipient` is an age recipient that can be registered as an
+/// approver: not malformed, and not the credential the vault is unlocked with.
+pub fn validate_approver(recipient: &str, master: &VaultKey) -> Result<()> {
+    let valid = recipient.parse::<age::x25519::Recipient>().is_ok()
+        || plugin::recipient_plugin(recipient).is_some();
+    if !valid {
+        return Err(AuthyError::InvalidKeyfile(format!(
+            "'{}' is not an age recipient",
+            recipient
+        )));
+    }
+    if master_recipient(master) == Some(recipient) {
+        return Err(AuthyError::Other(
+            "The vault's own keyfile can't approve its changes; use a separate recovery keyfile"
+                .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Require an approval when `reason` is set (something protected is
+/// affected). Returns the approving recipient, or `None` if no approval was
+/// needed.
+pub fn require(
+    vault: &Vault,
+    master: &VaultKey,
+    reason: Option<String>,
+    approve_with: Option<&str>,
+) -> Result<Option<String>> {
+    let reason = match reason {
+        Some(reason) => reason,
+        None => return Ok(None),
+    };
+    match approve_with {
+        Some(keyfile) => approve(vault, master, keyfile).map(Some),
+        None => Err(AuthyError::ApprovalRequired(reason)),
+    }
+}
+
+/// Verify that `keyfile` belongs to one of the vault's approvers. Returns its
+/// recipient.
+pub fn approve(vault: &Vault, master: &VaultKey, keyfile: &str) -> Result<String> {
+    let (identity, pubkey) = super::read_keyfile(keyfile)?;
+    if master_recipient(master) == Some(pubkey.as_str()) {
+        return Err(AuthyError::AuthFailed(
+            "The approval must come from a second credential, not the vault's own keyfile".into(),
+        ));
+    }
+    if !vault.approvers.contains(&pubkey) {
+        return Err(AuthyError::AuthFailed(format!(
+            "{} is not a registered approver (see `authy approver list`)",
+            keyfile
+        )));
+    }
+
+    // Plugin identities take their recipient from a comment, so prove the
+    // identity can actually open something sealed to it.
+    let mut challenge = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut challenge);
+    let sealed = crypto::encrypt_with_keyfile(&challenge, &pubkey)?;
+    let opened = crypto::decrypt_with_keyfile(&sealed, &identity)
+        .map_err(|_| AuthyError::AuthFailed(format!("{} does not match its recipient", keyfile)))?;
+    if opened != challenge {
+        return Err(AuthyError::AuthFailed(format!(
+            "{} does not match its recipient",
+            keyfile
+        )));
+    }
+    Ok(pubkey)
+}
+
+/// Describes the protected secrets and policies in the vault, if there are
+/// any. Rekeying such a vault needs an approval.
+pub fn protected_items(vault: &Vault) -> Option<String> {
+    let secrets = vault.secrets.values().filter(|e| e.metadata.protected).count();
+    let policies = vault.policies.values().filter(|p| p.protected).count();
+    if secrets + policies == 0 {
+        return None;
+    }
+    Some(format!(
+        "the vault holds {} protected secret(s) and {} protected policy(ies)",
+        secrets, policies
+    ))
+}
+
+fn master_recipient(master: &VaultKey) -> Option<&str> {
+    match master {
+        VaultKey::Passphrase(_) => None,
+        VaultKey::Keyfile { pubkey, .. } | VaultKey::SshAgent { pubkey, .. } => Some(pubkey),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_keyfile(dir: &std::path::Path, name: &str) -> (String, String) {
+        let (identity, pubkey) = crypto::generate_keypair();
+        let path = dir.join(name);
+        std::fs::write(&path, identity).unwrap();
+        (path.display().to_string(), pubkey)
+    }
+
+    #[test]
+    fn test_approval_needs_registered_second_keyfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let (master_path, _) = write_keyfile(dir.path(), "master.key");
+        let (identity, pubkey) = crate::auth::read_keyfile(&master_path).unwrap();
+        let master = VaultKey::Keyfile { identity, pubkey: pubkey.clone() };
+        let (approver_path, approver) = write_keyfile(dir.path(), "approver.key");
+        let (stranger_path, _) = write_keyfile(dir.path(), "stranger.key");
+
+        let mut vault = Vault::new();
+        vault.approvers.push(approver.clone());
+        vault.approvers.push(pubkey);
+
+        assert!(require(&vault, &master, None, None).unwrap().is_none());
+        assert!(matches!(
+            require(&vault, &master, Some("protected".into()), None),
+            Err(AuthyError::ApprovalRequired(_))
+        ));
+        assert_eq!(approve(&vault, &master, &approver_path).unwrap(), approver);
+        assert!(approve(&vault, &master, &stranger_path).is_err());
+        assert!(approve(&vault, &master, &master_path).is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::{AuthyError, Result};
+use crate::session::Capability;
+
 /// The resolved authentication context after verifying credentials.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -10,12 +13,15 @@ pub struct AuthContext {
     pub can_write: bool,
     /// When true, secrets can only be injected via `run` — `get`, `env`, `export` are blocked.
     pub run_only: bool,
+    /// What a session token may do. Master credentials may do everything.
+    pub capabilities: Vec<Capability>,
 }
 
 #[derive(Debug, Clone)]
 pub enum AuthMethod {
     Passphrase,
     Keyfile,
+    SshAgent,
     SessionToken { session_id: String },
 }
 
@@ -26,6 +32,7 @@ impl AuthContext {
             scope: None,
             can_write: true,
             run_only: false,
+            capabilities: Capability::ALL.to_vec(),
         }
     }
 
@@ -35,15 +42,41 @@ impl AuthContext {
             scope: None,
             can_write: true,
             run_only: false,
+            capabilities: Capability::ALL.to_vec(),
         }
     }
 
-    pub fn from_token(session_id: String, scope: String, run_only: bool) -> Self {
+    pub fn master_ssh_agent() -> Self {
+        Self {
+            method: AuthMethod::SshAgent,
+            scope: None,
+            can_write: true,
+            run_only: false,
+            capabilities: Capability::ALL.to_vec(),
+        }
+    }
+
+    pub fn from_token(
+        session_id: String,
+        scope: String,
+        run_only: bool,
+        capabilities: Vec<Capability>,
+    ) -> Self {
         Self {
             method: AuthMethod::SessionToken { session_id },
             scope: Some(scope),
             can_write: false,
             run_only,
+            capabilities,
+        }
+    }
+
+    /// Fail unless this context holds `capability`.
+    pub fn require(&self, capability: Capability) -> Result<()> {
+        if self.capabilities.contains(&capability) {
+            Ok(())
+        } else {
+            Err(AuthyError::MissingCapability(capability))
         }
     }
 
@@ -51,6 +84,7 @@ impl AuthContext {
         match &self.method {
             AuthMethod::Passphrase => "master(passphrase)".to_string(),
             AuthMethod::Keyfile => "master(keyfile)".to_string(),
+            AuthMethod::SshAgent => "master(ssh-agent)".to_string(),
             AuthMethod::SessionToken { session_id } => format!("token({})", session_id),
         }
     }
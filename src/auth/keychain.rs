@@ -0,0 +1,115 @@
+//! The master credential kept in the OS keychain (macOS Keychain, Windows
+//! Credential Manager, or the Secret Service on Linux), so shells don't need
+//! `AUTHY_PASSPHRASE`. Enabled with `[auth] keyring = true`; see
+//! [`resolve_auth`](super::resolve_auth).
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{AuthyError, Result};
+use crate::vault::{self, VaultKey};
+
+/// Service name the entries are filed under.
+const SERVICE: &str = "authy";
+
+/// What the keychain entry holds.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+enum Stored {
+    Passphrase { passphrase: String },
+    Keyfile { identity: String, pubkey: String },
+}
+
+/// One entry per authy directory, so vaults under different homes (or
+/// `HOME` overrides) don't share a credential.
+fn entry() -> Result<keyring::Entry> {
+    let account = vault::authy_dir()?.display().to_string();
+    keyring::Entry::new(SERVICE, &account).map_err(keychain_error)
+}
+
+/// Save `key` in the keychain, replacing any earlier credential.
+pub fn store(key: &VaultKey) -> Result<()> {
+    entry()?.set_password(&encode(key)?).map_err(keychain_error)
+}
+
+/// The stored credential, or `None` if nothing has been saved.
+pub fn load() -> Result<Option<VaultKey>> {
+    match entry()?.get_password() {
+        Ok(secret) => decode(&Zeroizing::new(secret)).map(Some),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
+
+/// Remove the stored credential. Returns false if there was none.
+pub fn delete() -> Result<bool> {
+    match entry()?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
+
+fn encode(key: &VaultKey) -> Result<Zeroizing<String>> {
+    let stored = match key {
+        VaultKey::Passphrase(passphrase) => Stored::Passphrase {
+            passphrase: passphrase.clone(),
+        },
+        VaultKey::Keyfile { identity, pubkey } => Stored::Keyfile {
+            identity: identity.clone(),
+            pubkey: pubkey.clone(),
+        },
+        VaultKey::SshAgent { .. } => {
+            return Err(AuthyError::Other(
+                "The vault is unlocked through the SSH agent; there is no credential to keep in the keychain".into(),
+            ))
+        }
+    };
+    serde_json::to_string(&stored)
+        .map(Zeroizing::new)
+        .map_err(|e| AuthyError::Serialization(e.to_string()))
+}
+
+fn decode(secret: &str) -> Result<VaultKey> {
+    let stored: Stored = serde_json::from_str(secret)
+        .map_err(|_| AuthyError::AuthFailed("Keychain entry is not an authy credential".into()))?;
+    Ok(match stored {
+        Stored::Passphrase { passphrase } => VaultKey::Passphrase(passphrase),
+        Stored::Keyfile { identity, pubkey } => VaultKey::Keyfile { identity, pubkey },
+    })
+}
+
+fn keychain_error(e: keyring::Error) -> AuthyError {
+    AuthyError::AuthFailed(format!("OS keychain: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = VaultKey::Keyfile {
+            identity: "AGE-SECRET-KEY-1XYZ".into(),
+            pubkey: "age1xyz".into(),
+        };
+        match decode(&encode(&key).unwrap()).unwrap() {
+            VaultKey::Keyfile { identity, pubkey } => {
+                assert_eq!(identity, "AGE-SECRET-KEY-1XYZ");
+                assert_eq!(pubkey, "age1xyz");
+            }
+            _ => panic!("expected a keyfile credential"),
+        }
+
+        let key = VaultKey::Passphrase("hunter2".into());
+        assert!(matches!(
+            decode(&encode(&key).unwrap()).unwrap(),
+            VaultKey::Passphrase(p) if p == "hunter2"
+        ));
+    }
+
+    #[test]
+    fn test_foreign_entry_rejected() {
+        assert!(decode("hunter2").is_err());
+    }
+}
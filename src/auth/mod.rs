@@ -1,11 +1,17 @@
+pub mod approval;
 pub mod context;
+#[cfg(feature = "keyring")]
+pub mod keychain;
+pub mod ssh_agent;
 
 use std::env;
 use std::fs;
 use std::io::IsTerminal;
+use std::time::Duration;
 
 use crate::error::{AuthyError, Result};
-use crate::session;
+use crate::policy::SecretOp;
+use crate::session::{self, Capability};
 use crate::vault::{self, VaultKey};
 use context::AuthContext;
 
@@ -13,6 +19,10 @@ const AUTHY_PASSPHRASE_ENV: &str = "AUTHY_PASSPHRASE";
 const AUTHY_KEYFILE_ENV: &str = "AUTHY_KEYFILE";
 const AUTHY_TOKEN_ENV: &str = "AUTHY_TOKEN";
 const AUTHY_NON_INTERACTIVE_ENV: &str = "AUTHY_NON_INTERACTIVE";
+const AUTHY_PROMPT_TIMEOUT_ENV: &str = "AUTHY_PROMPT_TIMEOUT";
+
+/// `[vault] auth_method` for vaults unlocked through the SSH agent.
+pub const SSH_AGENT_METHOD: &str = "ssh-agent";
 
 /// Check if we are in non-interactive mode.
 /// Returns true if stdin is not a TTY or AUTHY_NON_INTERACTIVE=1 is set.
@@ -26,11 +36,59 @@ pub fn is_non_interactive() -> bool {
     !std::io::stdin().is_terminal()
 }
 
+/// How long the passphrase prompt waits before giving up:
+/// `AUTHY_PROMPT_TIMEOUT`, else `[auth] prompt_timeout_secs`. `None` (or 0)
+/// waits forever. Guards CI jobs where TTY detection misfires.
+pub fn prompt_timeout() -> Result<Option<Duration>> {
+    let secs = match env::var(AUTHY_PROMPT_TIMEOUT_ENV) {
+        Ok(raw) => Some(parse_prompt_timeout(&raw)?),
+        Err(_) => vault::config_path()
+            .and_then(|path| crate::config::Config::load(&path))
+            .ok()
+            .and_then(|c| c.auth.prompt_timeout_secs),
+    };
+    Ok(secs.filter(|&s| s > 0).map(Duration::from_secs))
+}
+
+/// Seconds, or a duration like `30s` or `2m` (rounded up to a second).
+fn parse_prompt_timeout(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    raw.parse()
+        .or_else(|_| {
+            humantime::parse_duration(raw)
+                .map(|d| d.as_secs() + u64::from(d.subsec_nanos() > 0))
+        })
+        .map_err(|_| {
+            AuthyError::Other(format!(
+                "Invalid {}: '{}' (use seconds, or a duration like 30s)",
+                AUTHY_PROMPT_TIMEOUT_ENV, raw
+            ))
+        })
+}
+
+/// Name the credential `resolve_auth` would try, for logging failed attempts.
+pub fn credential_method() -> &'static str {
+    if env::var(AUTHY_TOKEN_ENV).is_ok() {
+        "token"
+    } else if env::var(AUTHY_KEYFILE_ENV).is_ok() {
+        "keyfile"
+    } else if configured_ssh_key().is_some() {
+        "ssh-agent"
+    } else if keyring_enabled() {
+        "keyring"
+    } else {
+        "passphrase"
+    }
+}
+
 /// Resolve authentication. Tries in order:
 /// 1. AUTHY_TOKEN env var (session token, requires AUTHY_KEYFILE for vault decryption)
 /// 2. AUTHY_KEYFILE env var (master keyfile)
 /// 3. AUTHY_PASSPHRASE env var (master passphrase)
-/// 4. Interactive passphrase prompt (only if TTY is available)
+/// 4. The SSH agent, when `[vault] auth_method = "ssh-agent"`
+/// 5. The OS keychain, when `[auth] keyring = true`
+/// 6. Interactive passphrase prompt (only if TTY is available), failing
+///    with [`AuthyError::PromptTimeout`] after [`prompt_timeout`]
 pub fn resolve_auth(require_write: bool) -> Result<(VaultKey, AuthContext)> {
     // Check for token-based auth first
     if let Ok(token) = env::var(AUTHY_TOKEN_ENV) {
@@ -51,15 +109,9 @@ pub fn resolve_auth(require_write: bool) -> Result<(VaultKey, AuthContext)> {
         };
 
         // Load the vault to validate the token
-        let vault = vault::load_vault(&vault_key)?;
-        let hmac_key = vault::crypto::derive_key(identity.as_bytes(), b"session-hmac", 32);
-        let session_record = session::validate_token(&token, &vault.sessions, &hmac_key)?;
-
-        let auth_ctx = AuthContext::from_token(
-            session_record.id.clone(),
-            session_record.scope.clone(),
-            session_record.run_only,
-        );
+        let mut vault = vault::load_vault(&vault_key)?;
+        let auth_ctx = token_context(&token, &identity, &vault)?;
+        extend_sliding_session(&mut vault, &vault_key, &auth_ctx)?;
 
         return Ok((vault_key, auth_ctx));
     }
@@ -79,6 +131,18 @@ pub fn resolve_auth(require_write: bool) -> Result<(VaultKey, AuthContext)> {
         return Ok((vault_key, auth_ctx));
     }
 
+    // SSH key chosen with `authy init --ssh-key`
+    if let Some(ssh_key) = configured_ssh_key() {
+        let public_key = ssh_agent::SshPublicKey::read(std::path::Path::new(&ssh_key))?;
+        let vault_key = ssh_agent::derive_key(&public_key)?;
+        return Ok((vault_key, AuthContext::master_ssh_agent()));
+    }
+
+    // Credential saved with `authy config set auth.keyring true`
+    if let Some(found) = keychain_auth()? {
+        return Ok(found);
+    }
+
     // Non-interactive mode: fail immediately without prompting
     if is_non_interactive() {
         return Err(AuthyError::AuthFailed(
@@ -89,15 +153,192 @@ pub fn resolve_auth(require_write: bool) -> Result<(VaultKey, AuthContext)> {
     interactive_passphrase_prompt()
 }
 
+/// Where [`resolve_auth`] would take credentials from, without reading or
+/// checking them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialSource {
+    /// `AUTHY_TOKEN`, with the keyfile from `AUTHY_KEYFILE` (required).
+    Token { keyfile: Option<String> },
+    /// `AUTHY_KEYFILE`.
+    Keyfile(String),
+    /// `AUTHY_PASSPHRASE`.
+    Passphrase,
+    /// The SSH public key from `[vault] ssh_key`.
+    SshAgent(String),
+    /// The credential saved in the OS keychain.
+    Keyring,
+    /// The interactive passphrase prompt.
+    Prompt,
+    /// Nothing: non-interactive with no credentials.
+    None,
+}
+
+impl CredentialSource {
+    /// Short name: `token`, `keyfile`, `passphrase`, `ssh-agent`,
+    /// `keyring`, `prompt` or `none`.
+    pub fn method(&self) -> &'static str {
+        match self {
+            CredentialSource::Token { .. } => "token",
+            CredentialSource::Keyfile(_) => "keyfile",
+            CredentialSource::Passphrase => "passphrase",
+            CredentialSource::SshAgent(_) => SSH_AGENT_METHOD,
+            CredentialSource::Keyring => "keyring",
+            CredentialSource::Prompt => "prompt",
+            CredentialSource::None => "none",
+        }
+    }
+}
+
+/// The credential source [`resolve_auth`] would use, checked in the same
+/// order.
+pub fn credential_source() -> CredentialSource {
+    if env::var(AUTHY_TOKEN_ENV).is_ok() {
+        return CredentialSource::Token {
+            keyfile: env::var(AUTHY_KEYFILE_ENV).ok(),
+        };
+    }
+    if let Ok(keyfile_path) = env::var(AUTHY_KEYFILE_ENV) {
+        return CredentialSource::Keyfile(keyfile_path);
+    }
+    if env::var(AUTHY_PASSPHRASE_ENV).is_ok() {
+        return CredentialSource::Passphrase;
+    }
+    if let Some(ssh_key) = configured_ssh_key() {
+        return CredentialSource::SshAgent(ssh_key);
+    }
+    if keyring_enabled() {
+        return CredentialSource::Keyring;
+    }
+    if is_non_interactive() {
+        CredentialSource::None
+    } else {
+        CredentialSource::Prompt
+    }
+}
+
+/// Resolve authentication for an operation a session token needs
+/// `capability` for. Master credentials may do anything.
+pub fn resolve_auth_for(capability: Capability) -> Result<(VaultKey, AuthContext)> {
+    let (key, auth_ctx) = resolve_auth(false)?;
+    auth_ctx.require(capability)?;
+    Ok((key, auth_ctx))
+}
+
+/// Fail unless the policy for `scope` allows `op` on `name`. Pass the
+/// token's scope, or an explicit `--scope`; with neither (master
+/// credentials, no scope) nothing is checked.
+pub fn check_scope(vault: &vault::Vault, scope: Option<&str>, op: SecretOp, name: &str) -> Result<()> {
+    let scope = match scope {
+        Some(scope) => scope,
+        None => return Ok(()),
+    };
+    let decision = vault.effective_policy(scope)?.decide(op, name)?;
+    if decision.allowed {
+        Ok(())
+    } else {
+        Err(AuthyError::AccessDenied {
+            secret: name.to_string(),
+            scope: scope.to_string(),
+            reason: decision.denial_reason(),
+        })
+    }
+}
+
+/// The SSH public key to unlock the vault with, when `[vault] auth_method`
+/// is "ssh-agent".
+fn configured_ssh_key() -> Option<String> {
+    let config = vault::config_path()
+        .and_then(|path| crate::config::Config::load(&path))
+        .ok()?;
+    if config.vault.auth_method == SSH_AGENT_METHOD {
+        config.vault.expanded_ssh_key()
+    } else {
+        None
+    }
+}
+
+/// Whether `[auth] keyring` is on. An unreadable config counts as off.
+fn keyring_enabled() -> bool {
+    cfg!(feature = "keyring")
+        && vault::config_path()
+            .and_then(|path| crate::config::Config::load(&path))
+            .map(|c| c.auth.keyring)
+            .unwrap_or(false)
+}
+
+#[cfg(feature = "keyring")]
+fn keychain_auth() -> Result<Option<(VaultKey, AuthContext)>> {
+    if !keyring_enabled() {
+        return Ok(None);
+    }
+    Ok(keychain::load()?.map(|key| {
+        let auth_ctx = match key {
+            VaultKey::Passphrase(_) => AuthContext::master_passphrase(),
+            VaultKey::Keyfile { .. } => AuthContext::master_keyfile(),
+            VaultKey::SshAgent { .. } => AuthContext::master_ssh_agent(),
+        };
+        (key, auth_ctx)
+    }))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keychain_auth() -> Result<Option<(VaultKey, AuthContext)>> {
+    Ok(None)
+}
+
 #[cfg(feature = "cli")]
 fn interactive_passphrase_prompt() -> Result<(VaultKey, AuthContext)> {
-    let passphrase = dialoguer::Password::new()
-        .with_prompt("Enter vault passphrase")
-        .interact()
-        .map_err(|e| AuthyError::AuthFailed(format!("Failed to read passphrase: {e}")))?;
+    let passphrase = match prompt_timeout()? {
+        Some(timeout) => timed_passphrase("Enter vault passphrase", timeout)?,
+        None => dialoguer::Password::new()
+            .with_prompt("Enter vault passphrase")
+            .interact()
+            .map_err(|e| AuthyError::AuthFailed(format!("Failed to read passphrase: {e}")))?,
+    };
     Ok((VaultKey::Passphrase(passphrase), AuthContext::master_passphrase()))
 }
 
+/// Read a passphrase without echo, giving up once `timeout` has passed.
+/// dialoguer blocks in a read that can't be interrupted, so this reads key
+/// events in raw mode, which is restored however the prompt ends.
+#[cfg(feature = "cli")]
+fn timed_passphrase(prompt: &str, timeout: Duration) -> Result<String> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use crossterm::terminal;
+    use std::time::Instant;
+
+    let deadline = Instant::now() + timeout;
+    eprint!("{}: ", prompt);
+    terminal::enable_raw_mode()?;
+    let read = (|| -> Result<Option<String>> {
+        let mut passphrase = String::new();
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() || !event::poll(left)? {
+                return Ok(None);
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Enter => return Ok(Some(passphrase)),
+                KeyCode::Backspace => {
+                    passphrase.pop();
+                }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Err(AuthyError::AuthFailed("Passphrase prompt interrupted".into()));
+                }
+                KeyCode::Char(c) => passphrase.push(c),
+                _ => {}
+            }
+        }
+    })();
+    let _ = terminal::disable_raw_mode();
+    eprintln!();
+    read?.ok_or(AuthyError::PromptTimeout(timeout.as_secs()))
+}
+
 #[cfg(not(feature = "cli"))]
 fn interactive_passphrase_prompt() -> Result<(VaultKey, AuthContext)> {
     Err(AuthyError::AuthFailed(
@@ -105,6 +346,43 @@ fn interactive_passphrase_prompt() -> Result<(VaultKey, AuthContext)> {
     ))
 }
 
+/// Validate a session token against the vault's sessions and return its
+/// scoped context. `identity` is the keyfile's age identity,
+/// which keys the session HMACs.
+pub fn token_context(token: &str, identity: &str, vault: &vault::Vault) -> Result<AuthContext> {
+    let hmac_key = vault::crypto::derive_key(identity.as_bytes(), b"session-hmac", 32);
+    let session_record = session::validate_token(token, &vault.sessions, &hmac_key)?;
+
+    Ok(AuthContext::from_token(
+        session_record.id.clone(),
+        session_record.scope.clone(),
+        session_record.run_only,
+        session_record.capabilities.clone(),
+    ))
+}
+
+/// After a successful token validation, push back the session's expiry if
+/// it is a sliding session, saving the vault when it moves.
+pub fn extend_sliding_session(
+    vault: &mut vault::Vault,
+    key: &VaultKey,
+    auth_ctx: &AuthContext,
+) -> Result<()> {
+    let session_id = match &auth_ctx.method {
+        context::AuthMethod::SessionToken { session_id } => session_id,
+        _ => return Ok(()),
+    };
+    let moved = vault
+        .sessions
+        .iter_mut()
+        .find(|s| &s.id == session_id)
+        .is_some_and(|s| s.slide(chrono::Utc::now()));
+    if moved {
+        vault::save_vault(vault, key)?;
+    }
+    Ok(())
+}
+
 /// Resolve auth specifically for init (no vault exists yet, just get the key).
 pub fn resolve_auth_for_init(
     passphrase: Option<String>,
@@ -162,11 +440,69 @@ fn interactive_init_passphrase() -> Result<VaultKey> {
     ))
 }
 
+/// Key for `init --ssh-key`: the identity the SSH agent derives for the
+/// public key at `path`.
+pub fn resolve_ssh_key_for_init(path: &str) -> Result<VaultKey> {
+    let public_key = ssh_agent::SshPublicKey::read(std::path::Path::new(path))?;
+    let key = ssh_agent::derive_key(&public_key)?;
+    eprintln!("Vault key derived through the SSH agent from {}", path);
+    Ok(key)
+}
+
+/// Key for `init --piv`: the plugin identity at `path`, enrolling a new
+/// hardware key with `age-plugin-<plugin> --generate` if the file doesn't
+/// exist yet. An existing file may hold any plugin identity (e.g. one from
+/// `age-plugin-tpm --generate`).
+pub fn resolve_plugin_identity_for_init(path: &str, plugin: &str) -> Result<VaultKey> {
+    if !std::path::Path::new(path).exists() {
+        let (contents, recipient) = vault::plugin::generate(plugin)?;
+        fs::write(path, &contents)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+        let pubkey_path = format!("{}.pub", path);
+        fs::write(&pubkey_path, &recipient)?;
+
+        eprintln!("Enrolled {} identity: {}", plugin, path);
+        eprintln!("Recipient: {}", pubkey_path);
+    }
+
+    let (identity, pubkey) = read_keyfile(path)?;
+    if vault::plugin::identity_plugin(&identity).is_none() {
+        return Err(AuthyError::InvalidKeyfile(format!(
+            "{} is not an age plugin identity",
+            path
+        )));
+    }
+    Ok(VaultKey::Keyfile { identity, pubkey })
+}
+
 /// Read an age keyfile from disk. Returns (identity_string, public_key_string).
+///
+/// Plugin identities (`AGE-PLUGIN-...`) can't derive their recipient, so it
+/// comes from a `# Recipient:` comment in the file or from `<path>.pub`.
 pub fn read_keyfile(path: &str) -> Result<(String, String)> {
-    let content = fs::read_to_string(path)
+    let content = crate::timings::time("keyfile read", || fs::read_to_string(path))
         .map_err(|e| AuthyError::InvalidKeyfile(format!("Cannot read {}: {}", path, e)))?;
 
+    if let Some(identity) = vault::plugin::identity_line(&content) {
+        let pubkey = vault::plugin::recipient_comment(&content)
+            .or_else(|| {
+                fs::read_to_string(format!("{}.pub", path))
+                    .ok()
+                    .map(|s| s.trim().to_string())
+            })
+            .ok_or_else(|| {
+                AuthyError::InvalidKeyfile(format!(
+                    "No recipient for the plugin identity in {}: add a '# Recipient: age1...' line or {}.pub",
+                    path, path
+                ))
+            })?;
+        return Ok((identity.to_string(), pubkey));
+    }
+
     let identity: age::x25519::Identity = content
         .trim()
         .parse()
@@ -175,3 +511,17 @@ pub fn read_keyfile(path: &str) -> Result<(String, String)> {
     let pubkey = identity.to_public().to_string();
     Ok((content.trim().to_string(), pubkey))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prompt_timeout() {
+        assert_eq!(parse_prompt_timeout("30").unwrap(), 30);
+        assert_eq!(parse_prompt_timeout(" 2m ").unwrap(), 120);
+        assert_eq!(parse_prompt_timeout("1500ms").unwrap(), 2);
+        assert_eq!(parse_prompt_timeout("0").unwrap(), 0);
+        assert!(parse_prompt_timeout("soon").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Vault keys held by an SSH agent.
+//!
+//! An agent never reveals a private key and can only sign, so the vault is
+//! not encrypted to the SSH key itself. Instead the agent signs a fixed
+//! challenge and the vault's age identity is derived from that signature.
+//! Ed25519 and RSA (PKCS#1 v1.5) signatures are deterministic, so the same
+//! key always yields the same identity; ECDSA keys are refused because
+//! their signatures are not.
+
+use std::path::Path;
+
+use secrecy::ExposeSecret;
+
+use crate::error::{AuthyError, Result};
+use crate::vault::{crypto, VaultKey};
+
+/// Socket of the running agent, set by `ssh-agent`.
+const SSH_AUTH_SOCK_ENV: &str = "SSH_AUTH_SOCK";
+
+/// Signed by the agent; the signature seeds the vault identity.
+const CHALLENGE: &[u8] = b"authy vault key v1";
+
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+/// Request an rsa-sha2-256 signature rather than legacy SHA-1.
+const SSH_AGENT_RSA_SHA2_256: u32 = 2;
+
+/// An SSH public key, as found in `id_ed25519.pub`.
+pub struct SshPublicKey {
+    pub key_type: String,
+    blob: Vec<u8>,
+}
+
+impl SshPublicKey {
+    /// Read an OpenSSH public key file (`ssh-ed25519 AAAA... comment`).
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AuthyError::InvalidKeyfile(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&content)
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let mut fields = content.split_whitespace();
+        let (key_type, encoded) = match (fields.next(), fields.next()) {
+            (Some(key_type), Some(encoded)) => (key_type, encoded),
+            _ => {
+                return Err(AuthyError::InvalidKeyfile(
+                    "expected an SSH public key like `ssh-ed25519 AAAA...`".into(),
+                ))
+            }
+        };
+        match key_type {
+            "ssh-ed25519" | "ssh-rsa" => {}
+            other => {
+                return Err(AuthyError::InvalidKeyfile(format!(
+                    "{} keys can't derive a vault key (use ssh-ed25519 or ssh-rsa)",
+                    other
+                )))
+            }
+        }
+        let blob = STANDARD
+            .decode(encoded)
+            .map_err(|e| AuthyError::InvalidKeyfile(format!("bad SSH public key: {}", e)))?;
+        Ok(Self {
+            key_type: key_type.to_string(),
+            blob,
+        })
+    }
+}
+
+/// Derive the vault key for `public_key` by having the agent sign the
+/// challenge.
+pub fn derive_key(public_key: &SshPublicKey) -> Result<VaultKey> {
+    let flags = if public_key.key_type == "ssh-rsa" {
+        SSH_AGENT_RSA_SHA2_256
+    } else {
+        0
+    };
+    let signature = zeroize::Zeroizing::new(sign(&public_key.blob, CHALLENGE, flags)?);
+    let seed = zeroize::Zeroizing::new(crypto::derive_key(&signature, b"ssh-agent-vault-key", 32));
+    let identity = crypto::identity_from_seed(&seed)?;
+    let pubkey = identity.to_public().to_string();
+    Ok(VaultKey::SshAgent {
+        identity: identity.to_string().expose_secret().clone(),
+        pubkey,
+    })
+}
+
+#[cfg(unix)]
+fn sign(key_blob: &[u8], data: &[u8], flags: u32) -> Result<Vec<u8>> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    let socket = std::env::var_os(SSH_AUTH_SOCK_ENV).ok_or_else(|| {
+        AuthyError::AuthFailed(format!("No SSH agent: {} is not set", SSH_AUTH_SOCK_ENV))
+    })?;
+    let mut stream = UnixStream::connect(&socket)
+        .map_err(|e| AuthyError::AuthFailed(format!("Can't reach the SSH agent: {}", e)))?;
+
+    let mut body = vec![SSH_AGENTC_SIGN_REQUEST];
+    put_string(&mut body, key_blob);
+    put_string(&mut body, data);
+    body.extend_from_slice(&flags.to_be_bytes());
+    let mut request = (body.len() as u32).to_be_bytes().to_vec();
+    request.extend_from_slice(&body);
+    stream.write_all(&request)?;
+
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let mut response = vec![0u8; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut response)?;
+
+    match response.split_first() {
+        Some((&SSH_AGENT_SIGN_RESPONSE, rest)) => match get_string(rest) {
+            Some(signature) => Ok(signature.to_vec()),
+            None => Err(AuthyError::AuthFailed(
+                "Malformed signature from the SSH agent".into(),
+            )),
+        },
+        _ => Err(AuthyError::AuthFailed(
+            "The SSH agent refused to sign (is the key loaded? try `ssh-add`)".into(),
+        )),
+    }
+}
+
+#[cfg(not(unix))]
+fn sign(_key_blob: &[u8], _data: &[u8], _flags: u32) -> Result<Vec<u8>> {
+    Err(AuthyError::AuthFailed(
+        "SSH agent authentication is only supported on Unix".into(),
+    ))
+}
+
+#[cfg(unix)]
+fn put_string(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(unix)]
+fn get_string(buf: &[u8]) -> Option<&[u8]> {
+    let len = u32::from_be_bytes(buf.get(..4)?.try_into().ok()?) as usize;
+    buf.get(4..4 + len)
+}
@@ -0,0 +1,180 @@
+//! Portable backups: the vault, audit log and config packed into one
+//! passphrase-encrypted file that `authy backup restore` (or
+//! [`AuthyClient::restore`](crate::api::AuthyClient::restore)) can put back,
+//! on this machine or another.
+//!
+//! The vault is copied as stored, still encrypted with its own key, so a
+//! restore needs both the backup passphrase and the vault's credentials.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{AuthyError, Result};
+use crate::vault::{self, crypto, storage, VaultKey};
+
+/// Bumped when the snapshot layout changes incompatibly.
+const FORMAT_VERSION: u32 = 1;
+
+/// Suffix given to files a restore replaces.
+pub const PRE_RESTORE_SUFFIX: &str = "pre-restore";
+
+/// What a backup file holds, once decrypted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: u32,
+    pub created_at: DateTime<Utc>,
+    /// The vault as stored, base64-encoded.
+    vault: String,
+    pub audit_log: Option<String>,
+    pub config: Option<String>,
+}
+
+impl Snapshot {
+    /// The vault ciphertext.
+    pub fn vault_bytes(&self) -> Result<Vec<u8>> {
+        STANDARD
+            .decode(&self.vault)
+            .map_err(|e| AuthyError::Other(format!("Invalid backup: {}", e)))
+    }
+
+    /// Number of entries in the backed-up audit log.
+    pub fn audit_entries(&self) -> usize {
+        self.audit_log
+            .as_deref()
+            .map(|log| log.lines().filter(|l| !l.trim().is_empty()).count())
+            .unwrap_or(0)
+    }
+}
+
+/// Read the current vault, audit log and config into a snapshot.
+pub fn snapshot() -> Result<Snapshot> {
+    let vault = storage::open()?.read()?;
+    Ok(Snapshot {
+        format: FORMAT_VERSION,
+        created_at: Utc::now(),
+        vault: STANDARD.encode(vault),
+        audit_log: read_optional(&vault::audit_path()?)?,
+        config: read_optional(&vault::config_path()?)?,
+    })
+}
+
+/// Snapshot the vault and write it to `path`, encrypted with `passphrase`.
+pub fn create(path: &Path, passphrase: &str) -> Result<Snapshot> {
+    if passphrase.is_empty() {
+        return Err(AuthyError::Other("Backup passphrase must not be empty".into()));
+    }
+    let snapshot = snapshot()?;
+    let plaintext = zeroize::Zeroizing::new(
+        serde_json::to_vec(&snapshot).map_err(|e| AuthyError::Serialization(e.to_string()))?,
+    );
+    let ciphertext = crypto::encrypt_with_passphrase(&plaintext, passphrase)?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, ciphertext)?;
+    Ok(snapshot)
+}
+
+/// Decrypt a backup file.
+pub fn read(path: &Path, passphrase: &str) -> Result<Snapshot> {
+    let ciphertext = fs::read(path)?;
+    let plaintext = zeroize::Zeroizing::new(crypto::decrypt_with_passphrase(
+        &ciphertext,
+        passphrase,
+    )?);
+    let snapshot: Snapshot = serde_json::from_slice(&plaintext)
+        .map_err(|e| AuthyError::Other(format!("Invalid backup: {}", e)))?;
+    if snapshot.format > FORMAT_VERSION {
+        return Err(AuthyError::Other(format!(
+            "Backup format {} is newer than this version of authy supports ({})",
+            snapshot.format, FORMAT_VERSION
+        )));
+    }
+    Ok(snapshot)
+}
+
+/// Put a snapshot back in place.
+///
+/// The snapshot's vault must open with `key`, so a restore can't leave
+/// behind a vault nobody can read. Its config is restored too, and decides
+/// which storage backend the vault is written to.
+///
+/// An existing vault or audit log is a conflict: without `force` nothing is
+/// written and `VaultAlreadyExists` is returned. With `force`, every file
+/// that gets replaced is first kept next to it with a `.pre-restore` suffix.
+/// Returns the paths of those copies.
+pub fn restore(snapshot: &Snapshot, key: &VaultKey, force: bool) -> Result<Vec<PathBuf>> {
+    let vault_bytes = snapshot.vault_bytes()?;
+    vault::decrypt_vault(&vault_bytes, key)?;
+
+    let config = match &snapshot.config {
+        Some(content) => Config::parse(content)?,
+        None => Config::load(&vault::config_path()?)?,
+    };
+    let target = storage::from_config(&config.storage)?;
+
+    let audit_path = vault::audit_path()?;
+    let vault_exists = target.exists()?;
+    if !force && (vault_exists || audit_path.exists()) {
+        let location = if vault_exists {
+            target.location()
+        } else {
+            audit_path.display().to_string()
+        };
+        return Err(AuthyError::VaultAlreadyExists(location));
+    }
+
+    let mut kept = Vec::new();
+    if vault_exists {
+        let path = pre_restore_path(&vault::vault_path()?);
+        write_file(&path, &target.read()?)?;
+        kept.push(path);
+    }
+    for path in [&audit_path, &vault::config_path()?] {
+        if path.exists() {
+            let copy = pre_restore_path(path);
+            fs::copy(path, &copy)?;
+            kept.push(copy);
+        }
+    }
+
+    if let Some(content) = &snapshot.config {
+        write_file(&vault::config_path()?, content.as_bytes())?;
+    }
+    match &snapshot.audit_log {
+        Some(log) => write_file(&audit_path, log.as_bytes())?,
+        None if audit_path.exists() => fs::remove_file(&audit_path)?,
+        None => {}
+    }
+    target.write(&vault_bytes)?;
+
+    Ok(kept)
+}
+
+fn pre_restore_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(PRE_RESTORE_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    if path.exists() {
+        Ok(Some(fs::read_to_string(path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
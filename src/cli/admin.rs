@@ -2,11 +2,11 @@ use authy::auth;
 use authy::error::{AuthyError, Result};
 use crate::tui;
 
-pub fn run(keyfile: Option<String>) -> Result<()> {
+pub fn run(keyfile: Option<String>, colors: bool) -> Result<()> {
     if auth::is_non_interactive() {
         return Err(AuthyError::Other(
             "authy admin requires an interactive terminal.".into(),
         ));
     }
-    tui::run(keyfile)
+    tui::run(keyfile, colors)
 }
@@ -0,0 +1,266 @@
+//! `authy agent --stdio` — line-based protocol for editor plugins.
+//!
+//! Each input line is a JSON object `{"id": .., "op": "..", "name": ".."}` and
+//! produces exactly one JSON response line. Supported ops: `hello`, `get`,
+//! `list`, `test`. Credentials and scope come from the environment and
+//! `.authy.toml`, so editor settings never need to carry a keyfile path.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use authy::audit;
+use authy::auth::{self, context::AuthContext};
+use authy::config::project::ProjectConfig;
+use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::policy::SecretOp;
+use authy::session::Capability;
+use authy::vault::{self, cache::ReadCache, VaultKey};
+
+#[derive(Deserialize)]
+struct AgentRequest {
+    #[serde(default)]
+    id: Value,
+    op: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AgentResponse {
+    id: Value,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AgentError>,
+}
+
+#[derive(Serialize)]
+struct AgentError {
+    code: String,
+    message: String,
+}
+
+impl AgentResponse {
+    fn ok(id: Value, result: Value) -> Self {
+        Self {
+            id,
+            ok: true,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    fn err(id: Value, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            id,
+            ok: false,
+            result: None,
+            error: Some(AgentError {
+                code: code.to_string(),
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+struct AgentSession {
+    key: VaultKey,
+    auth_ctx: AuthContext,
+    scope: String,
+    audit_key: Vec<u8>,
+    cache: Option<ReadCache>,
+}
+
+pub fn run(
+    stdio: bool,
+    scope_arg: Option<&str>,
+    dir: Option<&str>,
+    cache_ttl: Option<&str>,
+) -> Result<()> {
+    if !stdio {
+        return Err(AuthyError::Other("authy agent requires --stdio".into()));
+    }
+    let cache_ttl = super::common::parse_cache_ttl(cache_ttl)?;
+
+    let project = match dir {
+        Some(d) => ProjectConfig::discover(Path::new(d))?,
+        None => ProjectConfig::discover_from_cwd()?,
+    };
+    let project_config = project.as_ref().map(|(c, _)| c);
+
+    // If project has keyfile and AUTHY_KEYFILE not set, set it
+    if std::env::var("AUTHY_KEYFILE").is_err() {
+        if let Some(kf) = project_config.and_then(|c| c.expanded_keyfile()) {
+            std::env::set_var("AUTHY_KEYFILE", &kf);
+        }
+    }
+
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Read)?;
+
+    let scope = scope_arg
+        .map(|s| s.to_string())
+        .or_else(|| auth_ctx.scope.clone())
+        .or_else(|| project_config.map(|c| c.scope.clone()))
+        .ok_or_else(|| {
+            AuthyError::Other("No --scope provided and no .authy.toml found.".to_string())
+        })?;
+
+    // Fail at startup rather than on the first request
+    let vault_data = vault::load_vault(&key)?;
+    if !vault_data.policies.contains_key(&scope) {
+        return Err(AuthyError::PolicyNotFound(scope));
+    }
+
+    let material = audit::key_material(&key);
+    let session = AgentSession {
+        audit_key: audit::derive_audit_key(&material),
+        key,
+        auth_ctx,
+        scope,
+        cache: cache_ttl.map(ReadCache::new),
+    };
+
+    let stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<AgentRequest>(line) {
+            Ok(req) => session.handle(req),
+            Err(e) => AgentResponse::err(Value::Null, "parse_error", e.to_string()),
+        };
+        let json = serde_json::to_string(&response)
+            .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+        writeln!(stdout, "{}", json)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+impl AgentSession {
+    fn handle(&self, req: AgentRequest) -> AgentResponse {
+        let id = req.id.clone();
+        let result = match req.op.as_str() {
+            "hello" => Ok(self.hello()),
+            "get" => match req.name.as_deref() {
+                Some(name) => self.get(name),
+                None => return AgentResponse::err(id, "invalid_params", "Missing 'name'"),
+            },
+            "list" => self.list(),
+            "test" => match req.name.as_deref() {
+                Some(name) => self.test(name),
+                None => return AgentResponse::err(id, "invalid_params", "Missing 'name'"),
+            },
+            other => {
+                return AgentResponse::err(id, "unknown_op", format!("Unknown op: {}", other))
+            }
+        };
+
+        match result {
+            Ok(value) => AgentResponse::ok(id, value),
+            Err(e) => AgentResponse::err(id, e.error_code(), e.to_string()),
+        }
+    }
+
+    fn hello(&self) -> Value {
+        serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "scope": self.scope,
+            "run_only": self.auth_ctx.run_only,
+        })
+    }
+
+    fn get(&self, name: &str) -> Result<Value> {
+        // Only allowed reads are cached, and the scope is fixed for the
+        // session, so a hit needs no policy check.
+        if let Some(value) = self.cache.as_ref().and_then(|c| c.get(name)) {
+            self.audit("get", Some(name), "success", None);
+            return Ok(serde_json::json!({ "name": name, "value": *value }));
+        }
+
+        let vault_data = vault::load_vault(&self.key)?;
+        let policy = vault_data.effective_policy(&self.scope)?;
+
+        if self.auth_ctx.run_only || policy.run_only {
+            return Err(AuthyError::RunOnly);
+        }
+
+        let decision = policy.decide(SecretOp::Read, name)?;
+        if !decision.allowed {
+            let reason = decision.denial_reason();
+            self.audit("get", Some(name), "denied", reason.as_deref());
+            return Err(AuthyError::AccessDenied {
+                secret: name.to_string(),
+                scope: self.scope.clone(),
+                reason,
+            });
+        }
+
+        let entry = vault_data
+            .secrets
+            .get(name)
+            .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
+
+        // A canary is never cached, so every read of it alerts
+        if let Some(cache) = self.cache.as_ref().filter(|_| !entry.metadata.canary) {
+            cache.insert(name, &entry.value);
+        }
+        self.audit("get", Some(name), "success", None);
+        hooks::trip_canaries(
+            &vault_data,
+            [name],
+            "get",
+            &self.auth_ctx.actor_name(),
+            Some(&self.scope),
+            &self.audit_key,
+        );
+        Ok(serde_json::json!({ "name": name, "value": entry.value }))
+    }
+
+    fn list(&self) -> Result<Value> {
+        let vault_data = vault::load_vault(&self.key)?;
+        let policy = vault_data.effective_policy(&self.scope)?;
+
+        let names: Vec<&str> = vault_data.secrets.keys().map(|s| s.as_str()).collect();
+        let allowed = policy.filter_secrets(&names)?;
+
+        self.audit("list", None, "success", None);
+        Ok(serde_json::json!({ "names": allowed }))
+    }
+
+    fn test(&self, name: &str) -> Result<Value> {
+        let vault_data = vault::load_vault(&self.key)?;
+        let policy = vault_data.effective_policy(&self.scope)?;
+
+        let allowed = policy.can_read(name)?;
+        Ok(serde_json::json!({ "name": name, "allowed": allowed }))
+    }
+
+    fn audit(&self, operation: &str, secret: Option<&str>, outcome: &str, reason: Option<&str>) {
+        let mut detail = format!("scope={}, via=agent", self.scope);
+        if let Some(reason) = reason {
+            detail.push_str(&format!(", reason={}", reason));
+        }
+        let _ = vault::audit_path().and_then(|path| {
+            audit::log_event(
+                &path,
+                operation,
+                secret,
+                &self.auth_ctx.actor_name(),
+                outcome,
+                Some(&detail),
+                &self.audit_key,
+            )
+        });
+    }
+}
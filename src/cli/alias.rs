@@ -1,5 +1,23 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
 use authy::config::project::ProjectConfig;
 use authy::error::{AuthyError, Result};
+use authy::vault;
+
+/// Aliases generated by `--from-project`, recorded so `--cleanup` removes
+/// exactly what was created even after `.authy.toml` changes.
+#[derive(Debug, Serialize, Deserialize)]
+struct AliasState {
+    project_dir: String,
+    shell: String,
+    scope: String,
+    aliases: Vec<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
 
 pub fn run(
     scope: Option<&str>,
@@ -48,7 +66,7 @@ pub fn run(
 }
 
 fn run_from_project(shell: &str) -> Result<()> {
-    let (config, _dir) = ProjectConfig::discover_from_cwd()?
+    let (config, dir) = ProjectConfig::discover_from_cwd()?
         .ok_or_else(|| AuthyError::Other("No .authy.toml found".to_string()))?;
 
     if config.aliases.is_empty() {
@@ -68,19 +86,46 @@ fn run_from_project(shell: &str) -> Result<()> {
         print_alias(shell, tool, &run_flags, tool);
     }
 
+    // Tracking is best-effort: the aliases above are already printed for eval
+    let state = AliasState {
+        project_dir: dir.display().to_string(),
+        shell: shell.to_string(),
+        scope: config.scope.clone(),
+        aliases: config.aliases.clone(),
+        created_at: chrono::Utc::now(),
+    };
+    if let Err(e) = write_state(&dir, &state) {
+        eprintln!("authy: could not record aliases: {}", e);
+    }
+
     Ok(())
 }
 
 fn run_cleanup(shell: &str) -> Result<()> {
-    // Read AUTHY_PROJECT_DIR to find the project config to clean up
-    let project_dir = std::env::var("AUTHY_PROJECT_DIR")
-        .map_err(|_| AuthyError::Other("AUTHY_PROJECT_DIR not set — nothing to clean up.".to_string()))?;
+    // AUTHY_PROJECT_DIR is set by the shell hook; fall back to discovery
+    let project_dir = match std::env::var("AUTHY_PROJECT_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => ProjectConfig::discover_from_cwd()?
+            .map(|(_, dir)| dir)
+            .ok_or_else(|| {
+                AuthyError::Other("AUTHY_PROJECT_DIR not set — nothing to clean up.".to_string())
+            })?,
+    };
 
-    let config_path = std::path::PathBuf::from(&project_dir).join(".authy.toml");
+    // Prefer the recorded aliases: they're what was actually created
+    if let Some(state) = read_state(&project_dir)? {
+        for tool in &state.aliases {
+            print_unalias(shell, tool);
+        }
+        fs::remove_file(state_path(&project_dir)?)?;
+        return Ok(());
+    }
+
+    let config_path = project_dir.join(".authy.toml");
     if !config_path.is_file() {
         return Err(AuthyError::Other(format!(
             "No .authy.toml in {}",
-            project_dir
+            project_dir.display()
         )));
     }
 
@@ -93,6 +138,77 @@ fn run_cleanup(shell: &str) -> Result<()> {
     Ok(())
 }
 
+/// Print the aliases recorded for every project.
+pub fn run_list(json: bool) -> Result<()> {
+    let dir = state_dir()?;
+    let mut states = Vec::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                let content = fs::read_to_string(&path)?;
+                let state: AliasState = serde_json::from_str(&content)
+                    .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+                states.push(state);
+            }
+        }
+    }
+    states.sort_by(|a, b| a.project_dir.cmp(&b.project_dir));
+
+    if json {
+        let out = serde_json::to_string_pretty(&states)
+            .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    if states.is_empty() {
+        eprintln!("No aliases are active.");
+        return Ok(());
+    }
+    for state in &states {
+        println!(
+            "{} ({}, scope {}): {}",
+            state.project_dir,
+            state.shell,
+            state.scope,
+            state.aliases.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn state_dir() -> Result<PathBuf> {
+    Ok(vault::authy_dir()?.join("aliases"))
+}
+
+/// One state file per project, named by a hash of the canonical project
+/// directory (the hook's $PWD may go through symlinks).
+fn state_path(project_dir: &Path) -> Result<PathBuf> {
+    let canonical = fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_path_buf());
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    Ok(state_dir()?.join(format!("{}.json", &hex::encode(digest)[..16])))
+}
+
+fn read_state(project_dir: &Path) -> Result<Option<AliasState>> {
+    let path = state_path(project_dir)?;
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)?;
+    let state = serde_json::from_str(&content)
+        .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+    Ok(Some(state))
+}
+
+fn write_state(project_dir: &Path, state: &AliasState) -> Result<()> {
+    fs::create_dir_all(state_dir()?)?;
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+    fs::write(state_path(project_dir)?, content)?;
+    Ok(())
+}
+
 fn build_run_flags(scope: &str, uppercase: bool, replace_dash: Option<char>, prefix: Option<&str>) -> String {
     let mut flags = format!("--scope {}", shell_quote(scope));
     if uppercase {
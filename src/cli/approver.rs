@@ -0,0 +1,125 @@
+use std::fs;
+
+use crate::cli::ApproverCommands;
+use authy::audit;
+use authy::auth::{self, approval};
+use authy::error::{AuthyError, Result};
+use authy::vault;
+
+pub fn run(cmd: &ApproverCommands) -> Result<()> {
+    match cmd {
+        ApproverCommands::Add { recipient } => add(recipient),
+        ApproverCommands::List => list(),
+        ApproverCommands::Remove {
+            recipient,
+            approve_with,
+        } => remove(recipient, approve_with.as_deref()),
+    }
+}
+
+fn add(arg: &str) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    let recipient = read_recipient(arg)?;
+    approval::validate_approver(&recipient, &key)?;
+    if vault.approvers.contains(&recipient) {
+        eprintln!("{} is already an approver.", recipient);
+        return Ok(());
+    }
+
+    vault.approvers.push(recipient.clone());
+    let detail = format!("approver={}", recipient);
+    vault.record_event("approver.add", &auth_ctx.actor_name(), Some(detail.clone()));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let audit_key = audit::derive_audit_key(&audit::key_material(&key));
+    audit::log_event(
+        &vault::audit_path()?,
+        "approver.add",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!("Added approver {}.", recipient);
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let (key, _) = auth::resolve_auth(true)?;
+    let vault = vault::load_vault(&key)?;
+
+    if vault.approvers.is_empty() {
+        eprintln!("No approvers registered.");
+    }
+    for recipient in &vault.approvers {
+        println!("{}", recipient);
+    }
+    Ok(())
+}
+
+fn remove(recipient: &str, approve_with: Option<&str>) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    if !vault.approvers.iter().any(|r| r == recipient) {
+        return Err(AuthyError::Other(format!("{} is not an approver", recipient)));
+    }
+    if vault.approvers.len() == 1 && approval::protected_items(&vault).is_some() {
+        return Err(AuthyError::Other(
+            "Can't remove the last approver while secrets or policies are protected".to_string(),
+        ));
+    }
+    super::common::require_approval(
+        &vault,
+        &key,
+        &auth_ctx,
+        "approver.remove",
+        None,
+        Some(format!("removing approver {}", recipient)),
+        approve_with,
+    )?;
+
+    vault.approvers.retain(|r| r != recipient);
+    let detail = format!("approver={}", recipient);
+    vault.record_event("approver.remove", &auth_ctx.actor_name(), Some(detail.clone()));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let audit_key = audit::derive_audit_key(&audit::key_material(&key));
+    audit::log_event(
+        &vault::audit_path()?,
+        "approver.remove",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!("Removed approver {}.", recipient);
+    Ok(())
+}
+
+/// An approver given as a recipient (`age1...`), a `.pub` file holding one,
+/// or a keyfile to derive it from.
+fn read_recipient(arg: &str) -> Result<String> {
+    if arg.starts_with("age1") {
+        return Ok(arg.to_string());
+    }
+    let content = fs::read_to_string(arg)
+        .map_err(|e| AuthyError::InvalidKeyfile(format!("Cannot read {}: {}", arg, e)))?;
+    if content.contains("AGE-SECRET-KEY-") || content.contains("AGE-PLUGIN-") {
+        return auth::read_keyfile(arg).map(|(_, pubkey)| pubkey);
+    }
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .ok_or_else(|| AuthyError::InvalidKeyfile(format!("No recipient in {}", arg)))
+}
@@ -1,20 +1,261 @@
 use authy::audit as audit_mod;
+use authy::audit::checkpoint;
+use authy::audit::query::{self, AuditQuery};
+use authy::audit::stats::AuditStats;
 use authy::auth;
-use crate::cli::json_output::{AuditEntryItem, AuditShowResponse};
-use crate::cli::AuditCommands;
-use authy::error::Result;
-use authy::vault;
+use crate::cli::json_output::{
+    AuditActorStats, AuditAnomaly, AuditEntryItem, AuditSecretStats, AuditShowResponse,
+    AuditStatsResponse,
+};
+use crate::cli::table::{Column, Table};
+use crate::cli::{AuditCommands, AuditFormat};
+use authy::config::Config;
+use authy::error::{AuthyError, Result};
+use authy::vault::{self, VaultKey};
+use chrono::{DateTime, Duration, FixedOffset, Local, Utc};
+use ed25519_dalek::VerifyingKey;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 pub fn run(cmd: &AuditCommands, json: bool) -> Result<()> {
     match cmd {
-        AuditCommands::Show { count } => show(*count, json),
-        AuditCommands::Verify => verify(),
+        AuditCommands::Show {
+            count,
+            local,
+            relative,
+            tz,
+            auth,
+            resolve_names,
+            columns,
+            since,
+            until,
+            operation,
+            actor,
+            secret,
+            outcome,
+            format,
+        } => {
+            let display = TimeDisplay::from_args(*local, *relative, tz.as_deref())?;
+            let path = if *auth {
+                audit_mod::auth_events::auth_events_path()?
+            } else {
+                vault::audit_path()?
+            };
+            let mut filter = AuditQuery {
+                since: query::parse_time_bound(since.as_deref().unwrap_or_default(), false)?,
+                until: query::parse_time_bound(until.as_deref().unwrap_or_default(), true)?,
+                operation: operation.clone(),
+                actor: actor.clone(),
+                outcome: outcome.clone(),
+                ..AuditQuery::default()
+            };
+            if let Some(secret) = secret {
+                filter = filter.with_secret(secret)?;
+            }
+            show(
+                &path,
+                &filter,
+                *count,
+                &display,
+                *resolve_names,
+                columns,
+                *format,
+                json,
+            )
+        }
+        AuditCommands::Verify {
+            auth,
+            offline,
+            key_material,
+            log,
+            vault,
+            checkpoints,
+            checkpoint_pubkey,
+        } => {
+            let checkpoint_key = if *checkpoints {
+                Some(checkpoint_public_key(checkpoint_pubkey.as_deref(), *offline)?)
+            } else {
+                None
+            };
+            if *auth {
+                verify_auth_events()
+            } else if *offline {
+                verify_offline(
+                    key_material.as_deref().unwrap_or_default(),
+                    log.as_deref(),
+                    vault.as_deref(),
+                    checkpoint_key.as_ref(),
+                )
+            } else {
+                verify(checkpoint_key.as_ref())
+            }
+        }
+        AuditCommands::CheckpointKey { path, force } => {
+            let path = Path::new(path);
+            let public = checkpoint::generate_key(path, *force)?;
+            println!("{}", public);
+            eprintln!(
+                "Checkpoint signing key written to {} (public key in {}).",
+                path.display(),
+                checkpoint::public_key_path(path).display()
+            );
+            eprintln!(
+                "Enable it with: authy config set audit.checkpoint_key {}",
+                path.display()
+            );
+            Ok(())
+        }
         AuditCommands::Export => export(),
+        AuditCommands::Stats {
+            since,
+            until,
+            top,
+            denials,
+            resolve_names,
+        } => {
+            let window = AuditQuery {
+                since: query::parse_time_bound(since.as_deref().unwrap_or_default(), false)?,
+                until: query::parse_time_bound(until.as_deref().unwrap_or_default(), true)?,
+                ..AuditQuery::default()
+            };
+            stats(&window, *top, *denials, *resolve_names, json)
+        }
+    }
+}
+
+/// How `audit show` renders timestamps in text mode. JSON is always RFC3339 UTC.
+enum TimeDisplay {
+    Utc,
+    Local,
+    Offset(FixedOffset),
+    Relative,
+}
+
+impl TimeDisplay {
+    fn from_args(local: bool, relative: bool, tz: Option<&str>) -> Result<Self> {
+        if relative {
+            return Ok(TimeDisplay::Relative);
+        }
+        if let Some(tz) = tz {
+            return parse_offset(tz).map(TimeDisplay::Offset);
+        }
+        if local {
+            return Ok(TimeDisplay::Local);
+        }
+        Ok(TimeDisplay::Utc)
+    }
+
+    fn render(&self, ts: &DateTime<Utc>) -> String {
+        const FMT: &str = "%Y-%m-%d %H:%M:%S";
+        const FMT_TZ: &str = "%Y-%m-%d %H:%M:%S %:z";
+        match self {
+            TimeDisplay::Utc => ts.format(FMT).to_string(),
+            TimeDisplay::Local => ts.with_timezone(&Local).format(FMT_TZ).to_string(),
+            TimeDisplay::Offset(offset) => ts.with_timezone(offset).format(FMT_TZ).to_string(),
+            TimeDisplay::Relative => format!("{:>19}", format_relative(Utc::now() - *ts)),
+        }
+    }
+}
+
+/// Parse "UTC", "Z", "+05:30", "-0800", or "+9" into a fixed offset.
+fn parse_offset(tz: &str) -> Result<FixedOffset> {
+    let invalid = || {
+        AuthyError::Other(format!(
+            "Invalid --tz '{}'. Use UTC or an offset like +05:30 or -0800.",
+            tz
+        ))
+    };
+
+    let trimmed = tz.trim();
+    if trimmed.eq_ignore_ascii_case("utc") || trimmed == "Z" {
+        return Ok(FixedOffset::east_opt(0).unwrap());
+    }
+
+    let (sign, rest) = match trimmed.chars().next() {
+        Some('+') => (1, &trimmed[1..]),
+        Some('-') => (-1, &trimmed[1..]),
+        _ => return Err(invalid()),
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if digits.is_empty() || digits.len() > 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let (hours, minutes) = if digits.len() <= 2 {
+        (digits.parse::<i32>().map_err(|_| invalid())?, 0)
+    } else {
+        let split = digits.len() - 2;
+        (
+            digits[..split].parse::<i32>().map_err(|_| invalid())?,
+            digits[split..].parse::<i32>().map_err(|_| invalid())?,
+        )
+    };
+    if hours > 14 || minutes > 59 {
+        return Err(invalid());
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// Format a duration as a compact "ago" string: "42s ago", "5m ago", "3h ago", "2d ago".
+fn format_relative(delta: Duration) -> String {
+    let secs = delta.num_seconds();
+    if secs < 0 {
+        return "in the future".to_string();
+    }
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 86_400 {
+        format!("{}h ago", secs / 3600)
+    } else {
+        format!("{}d ago", secs / 86_400)
     }
 }
 
-fn show(count: usize, json: bool) -> Result<()> {
-    let entries = audit_mod::read_entries(&vault::audit_path())?;
+const SHOW_COLUMNS: [Column; 6] = [
+    Column::new("time", true, false),
+    Column::new("operation", true, false),
+    Column::new("outcome", true, false),
+    Column::new("actor", true, true),
+    Column::new("secret", true, true),
+    Column::new("detail", true, true),
+];
+
+#[allow(clippy::too_many_arguments)]
+fn show(
+    path: &Path,
+    filter: &AuditQuery,
+    count: usize,
+    time_display: &TimeDisplay,
+    resolve_names: bool,
+    columns: &[String],
+    format: Option<AuditFormat>,
+    json: bool,
+) -> Result<()> {
+    let mut table = Table::new(&SHOW_COLUMNS, columns)?;
+    let mut entries = audit_mod::read_entries(path)?;
+    // Names are resolved first so --secret can use the real name
+    if resolve_names {
+        resolve_hashed_names(&mut entries)?;
+    }
+    entries.retain(|entry| filter.matches(entry));
+
+    let display = if count == 0 {
+        &entries[..]
+    } else {
+        let start = entries.len().saturating_sub(count);
+        &entries[start..]
+    };
+    if let Some(format) = format {
+        let display: Vec<&audit_mod::AuditEntry> = display.iter().collect();
+        match format {
+            AuditFormat::Csv => print!("{}", query::to_csv(&display)),
+            AuditFormat::Jsonl => print!("{}", query::to_jsonl(&display)?),
+        }
+        return Ok(());
+    }
 
     if entries.is_empty() {
         if json {
@@ -28,19 +269,14 @@ fn show(count: usize, json: bool) -> Result<()> {
                 serde_json::to_string(&response)
                     .map_err(|e| authy::error::AuthyError::Serialization(e.to_string()))?
             );
-        } else {
+        } else if filter.is_empty() {
             eprintln!("No audit log entries.");
+        } else {
+            eprintln!("No audit log entries match.");
         }
         return Ok(());
     }
 
-    let display = if count == 0 {
-        &entries[..]
-    } else {
-        let start = entries.len().saturating_sub(count);
-        &entries[start..]
-    };
-
     if json {
         let items: Vec<AuditEntryItem> = display
             .iter()
@@ -65,18 +301,16 @@ fn show(count: usize, json: bool) -> Result<()> {
         );
     } else {
         for entry in display {
-            let secret_str = entry.secret.as_deref().unwrap_or("-");
-            let detail_str = entry.detail.as_deref().unwrap_or("");
-            println!(
-                "{} | {:<16} | {:<12} | {:<24} | {} {}",
-                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
-                entry.operation,
-                entry.outcome,
-                entry.actor,
-                secret_str,
-                detail_str,
-            );
+            table.push(vec![
+                time_display.render(&entry.timestamp),
+                entry.operation.clone(),
+                entry.outcome.clone(),
+                entry.actor.clone(),
+                entry.secret.clone().unwrap_or_else(|| "-".to_string()),
+                entry.detail.clone().unwrap_or_else(|| "-".to_string()),
+            ]);
         }
+        table.print();
 
         eprintln!("\n({} entries shown of {} total)", display.len(), entries.len());
     }
@@ -84,15 +318,42 @@ fn show(count: usize, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn verify() -> Result<()> {
+/// Replace `sha256:` secret names with the vault secret they hash to.
+/// Hashes of secrets that no longer exist are left as-is.
+fn resolve_hashed_names(entries: &mut [audit_mod::AuditEntry]) -> Result<()> {
+    let (key, _) = auth::resolve_auth(true)?;
+    let vault = vault::load_vault(&key)?;
+    let audit_key = audit_mod::derive_audit_key(&audit_mod::key_material(&key));
+
+    let names: HashMap<String, &str> = vault
+        .secrets
+        .keys()
+        .map(|name| (audit_mod::hash_secret_name(name, &audit_key), name.as_str()))
+        .collect();
+
+    for entry in entries.iter_mut() {
+        if let Some(secret) = &entry.secret {
+            if let Some(name) = names.get(secret) {
+                entry.secret = Some(name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn verify(checkpoint_key: Option<&VerifyingKey>) -> Result<()> {
     let (key, _) = auth::resolve_auth(false)?;
     let material = audit_mod::key_material(&key);
     let audit_key = audit_mod::derive_audit_key(&material);
+    let path = vault::audit_path()?;
 
-    match audit_mod::verify_chain(&vault::audit_path(), &audit_key) {
+    match audit_mod::verify_chain(&path, &audit_key) {
         Ok((count, true)) => {
             println!("Audit log integrity verified. {} entries, chain intact.", count);
-            Ok(())
+            match checkpoint_key {
+                Some(public) => verify_checkpoints(&path, public),
+                None => Ok(()),
+            }
         }
         Ok(_) => {
             println!("Audit log verification returned unexpected result.");
@@ -105,10 +366,289 @@ fn verify() -> Result<()> {
     }
 }
 
+/// Verify escrowed copies of the audit log and/or vault with nothing but
+/// the key material: no config, no authy directory, no credentials lookup.
+fn verify_offline(
+    key_material: &str,
+    log: Option<&str>,
+    vault_file: Option<&str>,
+    checkpoint_key: Option<&VerifyingKey>,
+) -> Result<()> {
+    if log.is_none() && vault_file.is_none() {
+        return Err(AuthyError::Other(
+            "--offline needs --log and/or --vault to verify".into(),
+        ));
+    }
+    let key = read_key_material(key_material)?;
+
+    if let Some(log) = log {
+        let audit_key = audit_mod::derive_audit_key(&audit_mod::key_material(&key));
+        match audit_mod::verify_chain(Path::new(log), &audit_key) {
+            Ok((count, _)) => {
+                println!("Audit log {} verified. {} entries, chain intact.", log, count)
+            }
+            Err(e) => {
+                eprintln!("INTEGRITY FAILURE: {}: {}", log, e);
+                return Err(e);
+            }
+        }
+        if let Some(public) = checkpoint_key {
+            verify_checkpoints(Path::new(log), public)?;
+        }
+    }
+
+    if let Some(vault_file) = vault_file {
+        let ciphertext = fs::read(vault_file)
+            .map_err(|e| AuthyError::Other(format!("Cannot read {}: {}", vault_file, e)))?;
+        match vault::open_snapshot(&ciphertext, &key) {
+            Ok(snapshot) => println!(
+                "Vault {} verified. {} secrets, {} policies, {} sessions, last modified {}.",
+                vault_file,
+                snapshot.secrets.len(),
+                snapshot.policies.len(),
+                snapshot.sessions.len(),
+                snapshot.modified_at.to_rfc3339()
+            ),
+            Err(e) => {
+                eprintln!("INTEGRITY FAILURE: {}: {}", vault_file, e);
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The public key to check checkpoints against: the one given, or the
+/// configured key's `.pub` file (not offline, which reads no config).
+fn checkpoint_public_key(given: Option<&str>, offline: bool) -> Result<VerifyingKey> {
+    if let Some(given) = given {
+        return checkpoint::parse_public_key(given);
+    }
+    let configured = if offline {
+        None
+    } else {
+        Config::load(&vault::config_path()?)?
+            .audit
+            .expanded_checkpoint_key()
+    };
+    match configured {
+        Some(key_path) => {
+            let public = checkpoint::public_key_path(Path::new(&key_path));
+            checkpoint::parse_public_key(&public.to_string_lossy())
+        }
+        None => Err(AuthyError::Other(
+            "--checkpoints needs --checkpoint-pubkey (no [audit] checkpoint_key is configured)"
+                .into(),
+        )),
+    }
+}
+
+fn verify_checkpoints(path: &Path, public: &VerifyingKey) -> Result<()> {
+    let entries = audit_mod::read_entries(path)?;
+    match checkpoint::verify(&entries, public) {
+        Ok(report) => {
+            println!(
+                "Checkpoints verified. {} signed checkpoints cover {} entries; {} since the last.",
+                report.checkpoints, report.signed, report.unsigned
+            );
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("INTEGRITY FAILURE: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// A key material file is either an age keyfile or holds the passphrase.
+fn read_key_material(path: &str) -> Result<VaultKey> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AuthyError::Other(format!("Cannot read {}: {}", path, e)))?;
+    if content.contains("AGE-SECRET-KEY-") || content.contains("AGE-PLUGIN-") {
+        let (identity, pubkey) = auth::read_keyfile(path)?;
+        Ok(VaultKey::Keyfile { identity, pubkey })
+    } else {
+        Ok(VaultKey::Passphrase(
+            content.trim_end_matches(['\r', '\n']).to_string(),
+        ))
+    }
+}
+
+fn verify_auth_events() -> Result<()> {
+    let path = audit_mod::auth_events::auth_events_path()?;
+    if !path.exists() {
+        println!("No auth events recorded.");
+        return Ok(());
+    }
+    let key = audit_mod::auth_events::local_key()?;
+    match audit_mod::verify_chain(&path, &key) {
+        Ok((count, _)) => {
+            println!("Auth events log verified. {} entries, chain intact.", count);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("INTEGRITY FAILURE: {}", e);
+            Err(e)
+        }
+    }
+}
+
 fn export() -> Result<()> {
-    let entries = audit_mod::read_entries(&vault::audit_path())?;
+    let entries = audit_mod::read_entries(&vault::audit_path()?)?;
     let json = serde_json::to_string_pretty(&entries)
         .map_err(|e| authy::error::AuthyError::Serialization(e.to_string()))?;
     println!("{}", json);
     Ok(())
 }
+
+/// Aggregate the log (within `window`) per actor and per secret, and list
+/// anomalies.
+fn stats(
+    window: &AuditQuery,
+    top: usize,
+    denial_threshold: usize,
+    resolve_names: bool,
+    json: bool,
+) -> Result<()> {
+    let mut entries = audit_mod::read_entries(&vault::audit_path()?)?;
+    if resolve_names {
+        resolve_hashed_names(&mut entries)?;
+    }
+    let report = AuditStats::compute(query::filter(&entries, window), denial_threshold);
+    let secrets = if top == 0 {
+        &report.secrets[..]
+    } else {
+        &report.secrets[..top.min(report.secrets.len())]
+    };
+
+    if json {
+        let response = AuditStatsResponse {
+            total: report.total,
+            failures: report.failures,
+            failure_rate: report.failure_rate(),
+            first: report.first.map(|t| t.to_rfc3339()),
+            last: report.last.map(|t| t.to_rfc3339()),
+            outcomes: report.outcomes.clone(),
+            actors: report
+                .actors
+                .iter()
+                .map(|a| AuditActorStats {
+                    actor: a.actor.clone(),
+                    total: a.total,
+                    failures: a.failures,
+                    failure_rate: a.failure_rate(),
+                    operations: a.operations.clone(),
+                })
+                .collect(),
+            secrets: secrets
+                .iter()
+                .map(|s| AuditSecretStats {
+                    secret: s.secret.clone(),
+                    reads: s.reads,
+                    other: s.other,
+                    denied: s.denied,
+                    first_access: s.first_access.to_rfc3339(),
+                    last_access: s.last_access.to_rfc3339(),
+                })
+                .collect(),
+            anomalies: report
+                .anomalies
+                .iter()
+                .map(|a| AuditAnomaly {
+                    kind: a.kind.to_string(),
+                    subject: a.subject.clone(),
+                    count: a.count,
+                    first: a.first.to_rfc3339(),
+                    last: a.last.to_rfc3339(),
+                    secrets: a.secrets.clone(),
+                })
+                .collect(),
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+        return Ok(());
+    }
+
+    let (Some(first), Some(last)) = (report.first, report.last) else {
+        eprintln!("No audit log entries.");
+        return Ok(());
+    };
+    let display = TimeDisplay::Utc;
+    println!(
+        "Entries: {} ({} to {}), {} failed ({})",
+        report.total,
+        display.render(&first),
+        display.render(&last),
+        report.failures,
+        percent(report.failure_rate())
+    );
+    let outcomes: Vec<String> = report
+        .outcomes
+        .iter()
+        .map(|(outcome, n)| format!("{} {}", outcome, n))
+        .collect();
+    println!("Outcomes: {}", outcomes.join(", "));
+
+    println!("\nActors ({}):", report.actors.len());
+    for actor in &report.actors {
+        let operations: Vec<String> = actor
+            .operations
+            .iter()
+            .map(|(op, n)| format!("{} {}", op, n))
+            .collect();
+        println!(
+            "  {}: {} entries, {} failed ({}) [{}]",
+            actor.actor,
+            actor.total,
+            actor.failures,
+            percent(actor.failure_rate()),
+            operations.join(", ")
+        );
+    }
+
+    println!("\nSecrets ({} of {}):", secrets.len(), report.secrets.len());
+    for secret in secrets {
+        println!(
+            "  {}: {} reads, {} other, {} denied, first {}, last {}",
+            secret.secret,
+            secret.reads,
+            secret.other,
+            secret.denied,
+            display.render(&secret.first_access),
+            display.render(&secret.last_access)
+        );
+    }
+
+    println!("\nAnomalies ({}):", report.anomalies.len());
+    for anomaly in &report.anomalies {
+        match anomaly.kind {
+            "repeated_denials" => println!(
+                "  ! {} denied {} times ({} to {}) on {}",
+                anomaly.subject,
+                anomaly.count,
+                display.render(&anomaly.first),
+                display.render(&anomaly.last),
+                if anomaly.secrets.is_empty() {
+                    "-".to_string()
+                } else {
+                    anomaly.secrets.join(", ")
+                }
+            ),
+            _ => println!(
+                "  ! canary {} tripped {} times ({} to {})",
+                anomaly.subject,
+                anomaly.count,
+                display.render(&anomaly.first),
+                display.render(&anomaly.last)
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate * 100.0)
+}
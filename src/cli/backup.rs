@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use authy::audit;
+use authy::auth;
+use authy::backup::{self, Snapshot};
+use authy::error::{AuthyError, Result};
+use authy::vault::{self, VaultKey};
+
+use crate::cli::json_output::BackupResponse;
+use crate::cli::BackupCommands;
+
+/// Backup passphrase for scripts; prompted for when unset.
+const BACKUP_PASSPHRASE_ENV: &str = "AUTHY_BACKUP_PASSPHRASE";
+
+pub fn run(cmd: &BackupCommands, json: bool) -> Result<()> {
+    match cmd {
+        BackupCommands::Create { path } => create(path, json),
+        BackupCommands::Restore { path, force } => restore(path, *force, json),
+    }
+}
+
+fn create(path: &str, json: bool) -> Result<()> {
+    // Master only: a backup carries the whole vault and audit log
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    vault::load_vault(&key)?;
+
+    let passphrase = backup_passphrase(true)?;
+    let snapshot = backup::create(Path::new(path), &passphrase)?;
+
+    log(&key, "backup", &auth_ctx.actor_name(), path)?;
+
+    if json {
+        print_json(path, &snapshot, Vec::new())?;
+    } else {
+        eprintln!(
+            "Backed up vault, {} audit entries{} to {}",
+            snapshot.audit_entries(),
+            if snapshot.config.is_some() { " and config" } else { "" },
+            path
+        );
+    }
+    Ok(())
+}
+
+fn restore(path: &str, force: bool, json: bool) -> Result<()> {
+    // The restored vault must open with these credentials, so resolve them
+    // before asking for the backup passphrase
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+
+    let passphrase = backup_passphrase(false)?;
+    let snapshot = backup::read(Path::new(path), &passphrase)?;
+    let kept = match backup::restore(&snapshot, &key, force) {
+        Ok(kept) => kept,
+        Err(AuthyError::VaultAlreadyExists(location)) => {
+            return Err(AuthyError::Other(format!(
+                "{} already exists. Use --force to replace it (the current files are kept as *.{}).",
+                location,
+                backup::PRE_RESTORE_SUFFIX
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+
+    log(&key, "restore", &auth_ctx.actor_name(), path)?;
+
+    if json {
+        print_json(
+            path,
+            &snapshot,
+            kept.iter().map(|p| p.display().to_string()).collect(),
+        )?;
+    } else {
+        eprintln!(
+            "Restored backup from {} ({} audit entries{})",
+            snapshot.created_at.format("%Y-%m-%d %H:%M UTC"),
+            snapshot.audit_entries(),
+            if snapshot.config.is_some() { ", config" } else { "" }
+        );
+        for p in &kept {
+            eprintln!("Kept previous file: {}", p.display());
+        }
+    }
+    Ok(())
+}
+
+/// The backup passphrase from `AUTHY_BACKUP_PASSPHRASE`, else a prompt
+/// (confirmed when creating a backup).
+fn backup_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(BACKUP_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    if auth::is_non_interactive() {
+        return Err(AuthyError::AuthFailed(format!(
+            "No backup passphrase provided. Set {}.",
+            BACKUP_PASSPHRASE_ENV
+        )));
+    }
+    let mut prompt = dialoguer::Password::new().with_prompt("Backup passphrase");
+    if confirm {
+        prompt = prompt.with_confirmation("Confirm backup passphrase", "Passphrases don't match");
+    }
+    prompt
+        .interact()
+        .map_err(|e| AuthyError::AuthFailed(format!("Failed to read passphrase: {}", e)))
+}
+
+fn log(key: &VaultKey, operation: &str, actor: &str, path: &str) -> Result<()> {
+    let material = audit::key_material(key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        operation,
+        None,
+        actor,
+        "success",
+        Some(path),
+        &audit_key,
+    )
+}
+
+fn print_json(path: &str, snapshot: &Snapshot, kept: Vec<String>) -> Result<()> {
+    let response = BackupResponse {
+        path: path.to_string(),
+        created: snapshot.created_at.to_rfc3339(),
+        audit_entries: snapshot.audit_entries(),
+        config: snapshot.config.is_some(),
+        kept,
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&response).map_err(|e| AuthyError::Serialization(e.to_string()))?
+    );
+    Ok(())
+}
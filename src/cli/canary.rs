@@ -0,0 +1,51 @@
+use authy::audit;
+use authy::auth;
+use authy::error::{AuthyError, Result};
+use authy::vault;
+
+pub fn run(name: Option<&str>, off: bool, list: bool) -> Result<()> {
+    // Only the master key may see or change which secrets are planted
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    if list {
+        for (name, entry) in &vault.secrets {
+            if entry.metadata.canary {
+                println!("{}", name);
+            }
+        }
+        return Ok(());
+    }
+
+    let name = name.unwrap_or_default();
+    let entry = vault
+        .secrets
+        .get_mut(name)
+        .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
+    if entry.metadata.canary != off {
+        let state = if off { "not a canary" } else { "already a canary" };
+        eprintln!("Secret '{}' is {}.", name, state);
+        return Ok(());
+    }
+    entry.metadata.canary = !off;
+
+    let op = if off { "canary_off" } else { "canary_on" };
+    vault.record_event(op, &auth_ctx.actor_name(), Some(format!("secret={}", name)));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let audit_key = audit::derive_audit_key(&audit::key_material(&key));
+    audit::log_event(
+        &vault::audit_path()?,
+        op,
+        Some(name),
+        &auth_ctx.actor_name(),
+        "success",
+        None,
+        &audit_key,
+    )?;
+
+    let state = if off { "no longer a canary" } else { "now a canary" };
+    eprintln!("Secret '{}' is {}.", name, state);
+    Ok(())
+}
@@ -2,7 +2,8 @@ use std::collections::HashMap;
 
 use authy::error::{AuthyError, Result};
 use authy::auth::context::AuthContext;
-use authy::vault::Vault;
+use authy::config::project::ProjectConfig;
+use authy::vault::{self, Vault, VaultKey};
 
 /// Resolve secrets accessible under a given scope (policy name).
 /// Returns a HashMap of secret_name -> secret_value for all allowed secrets.
@@ -20,10 +21,13 @@ pub fn resolve_scoped_secrets(
         return Ok(HashMap::new());
     };
 
-    let policy = vault
-        .policies
-        .get(&effective_scope)
-        .ok_or_else(|| AuthyError::PolicyNotFound(effective_scope.clone()))?;
+    let policy = vault.effective_policy(&effective_scope)?;
+    if let Some(reason) = policy.window_denial() {
+        eprintln!(
+            "Warning: policy '{}' grants no secrets right now ({}).",
+            effective_scope, reason
+        );
+    }
 
     let names: Vec<&str> = vault.secrets.keys().map(|s| s.as_str()).collect();
     let allowed = policy.filter_secrets(&names)?;
@@ -37,3 +41,136 @@ pub fn resolve_scoped_secrets(
 
     Ok(secrets)
 }
+
+/// Pick the scope for a command that injects secrets. A session token pins
+/// its own scope, so it is used when no `--scope` is given (ahead of the
+/// project's scope), and a different explicit `--scope` is rejected rather
+/// than silently resolving against another policy.
+pub fn effective_scope(
+    scope_arg: Option<&str>,
+    project_scope: Option<&str>,
+    auth_ctx: &AuthContext,
+) -> Result<String> {
+    match (scope_arg, auth_ctx.scope.as_deref()) {
+        (Some(explicit), Some(token_scope)) if explicit != token_scope => {
+            Err(AuthyError::Other(format!(
+                "--scope '{}' does not match the session token's scope '{}'",
+                explicit, token_scope
+            )))
+        }
+        (Some(explicit), _) => Ok(explicit.to_string()),
+        (None, Some(token_scope)) => Ok(token_scope.to_string()),
+        (None, None) => project_scope.map(|s| s.to_string()).ok_or_else(|| {
+            AuthyError::Other(
+                "No --scope provided, no session token, and no .authy.toml found.".to_string(),
+            )
+        }),
+    }
+}
+
+/// Pick the scope for a command that lists secret names or changes a
+/// secret. A session token always works under its own scope, with or
+/// without `--scope`; a different explicit `--scope` is rejected. Master
+/// credentials are unrestricted unless a scope is given.
+pub fn listing_scope(scope_arg: Option<&str>, auth_ctx: &AuthContext) -> Result<Option<String>> {
+    match (scope_arg, auth_ctx.scope.as_deref()) {
+        (Some(explicit), Some(token_scope)) if explicit != token_scope => {
+            Err(AuthyError::Other(format!(
+                "--scope '{}' does not match the session token's scope '{}'",
+                explicit, token_scope
+            )))
+        }
+        (_, Some(token_scope)) => Ok(Some(token_scope.to_string())),
+        (explicit, None) => Ok(explicit.map(|s| s.to_string())),
+    }
+}
+
+/// The project config from `.authy.toml`, if it sets `enforce_prefix`.
+/// Discovery errors are surfaced so a broken config can't silently skip
+/// the namespace.
+pub fn prefix_project() -> Result<Option<ProjectConfig>> {
+    Ok(ProjectConfig::discover_from_cwd()?
+        .map(|(config, _)| config)
+        .filter(|config| config.enforce_prefix.is_some()))
+}
+
+/// Parse a `--cache-ttl` value ("30s", "5m"). A zero TTL disables the cache.
+pub fn parse_cache_ttl(ttl: Option<&str>) -> Result<Option<std::time::Duration>> {
+    match ttl {
+        Some(s) => {
+            let ttl = humantime::parse_duration(s)
+                .map_err(|e| AuthyError::Other(format!("Invalid --cache-ttl: {e}")))?;
+            Ok((!ttl.is_zero()).then_some(ttl))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Write a file readable only by the current user. A new file is created
+/// with mode 0600, so the contents are never readable by others; an
+/// existing one is narrowed to 0600 before it is written.
+pub fn write_private_file(path: &std::path::Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    Ok(())
+}
+
+/// Run the configured revocation hooks, warning (not failing) if they don't
+/// succeed: the revocation itself is already saved.
+pub fn notify_revocation(event: &authy::hooks::RevocationEvent) {
+    if let Err(e) = authy::hooks::on_revocation(event) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+/// Enforce the approval rule for `op`: when `reason` says something protected
+/// is affected, check the `--approve-with` keyfile and record the outcome as
+/// an `approve` audit event. Returns the approving recipient, if any.
+pub fn require_approval(
+    vault: &Vault,
+    key: &VaultKey,
+    auth_ctx: &AuthContext,
+    op: &str,
+    secret: Option<&str>,
+    reason: Option<String>,
+    approve_with: Option<&str>,
+) -> Result<Option<String>> {
+    let reason = match reason {
+        Some(reason) => reason,
+        None => return Ok(None),
+    };
+    let detail = format!("op={} ({})", op, reason);
+    let result = authy::auth::approval::require(vault, key, Some(reason), approve_with);
+    let (outcome, detail) = match &result {
+        Ok(approver) => (
+            "success",
+            format!("{} approver={}", detail, approver.as_deref().unwrap_or("-")),
+        ),
+        Err(e) => ("denied", format!("{}: {}", detail, e)),
+    };
+    let audit_key = authy::audit::derive_audit_key(&authy::audit::key_material(key));
+    authy::audit::log_event(
+        &vault::audit_path()?,
+        "approve",
+        secret,
+        &auth_ctx.actor_name(),
+        outcome,
+        Some(&detail),
+        &audit_key,
+    )?;
+    result
+}
@@ -1,18 +1,86 @@
+use std::fs;
+
 use crate::cli::ConfigCommands;
 use authy::config::Config;
-use authy::error::Result;
+use authy::error::{AuthyError, Result};
 use authy::vault;
 
 pub fn run(cmd: &ConfigCommands) -> Result<()> {
     match cmd {
         ConfigCommands::Show => show(),
+        ConfigCommands::Set { key, value } => set(key, value),
     }
 }
 
 fn show() -> Result<()> {
-    let config = Config::load(&vault::config_path())?;
+    let config = Config::load(&vault::config_path()?)?;
     let toml_str = toml::to_string_pretty(&config)
-        .map_err(|e| authy::error::AuthyError::Other(format!("Config serialize error: {}", e)))?;
+        .map_err(|e| AuthyError::Other(format!("Config serialize error: {}", e)))?;
     println!("{}", toml_str);
     Ok(())
 }
+
+fn set(key: &str, value: &str) -> Result<()> {
+    let path = vault::config_path()?;
+    let previous = if path.exists() {
+        Some(fs::read_to_string(&path)?)
+    } else {
+        None
+    };
+    let config = Config::set(&path, key, value)?;
+
+    // Turning the keychain on or off also saves or removes the credential;
+    // if that fails, the config change is undone
+    if key == "auth.keyring" {
+        if let Err(e) = sync_keychain(config.auth.keyring) {
+            match previous {
+                Some(content) => fs::write(&path, content)?,
+                None => fs::remove_file(&path)?,
+            }
+            return Err(e);
+        }
+    }
+
+    eprintln!("Set {} = {} in {}", key, value, path.display());
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+fn sync_keychain(enabled: bool) -> Result<()> {
+    use authy::audit;
+    use authy::auth::{self, keychain};
+
+    // Master only, and checked against the vault before it is saved
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    vault::load_vault(&key)?;
+
+    let detail = if enabled {
+        keychain::store(&key)?;
+        eprintln!("Saved the master credential in the OS keychain.");
+        "credential saved"
+    } else {
+        if keychain::delete()? {
+            eprintln!("Removed the master credential from the OS keychain.");
+        }
+        "credential removed"
+    };
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        "keyring",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(detail),
+        &audit_key,
+    )
+}
+
+#[cfg(not(feature = "keyring"))]
+fn sync_keychain(_enabled: bool) -> Result<()> {
+    Err(AuthyError::Other(
+        "This build of authy has no OS keychain support (the `keyring` feature).".into(),
+    ))
+}
@@ -3,6 +3,8 @@ use authy::auth;
 use crate::cli::common;
 use authy::config::project::ProjectConfig;
 use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::session::Capability;
 use authy::subprocess::{transform_name, NamingOptions};
 use authy::vault;
 
@@ -37,7 +39,7 @@ pub fn run(
         }
     }
 
-    let (key, auth_ctx) = auth::resolve_auth(false)?;
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Read)?;
     let vault = vault::load_vault(&key)?;
 
     // Token-level run_only enforcement
@@ -46,7 +48,7 @@ pub fn run(
     }
 
     // Policy-level run_only enforcement
-    if let Some(policy) = vault.policies.get(&scope) {
+    if let Ok(policy) = vault.effective_policy(&scope) {
         if policy.run_only {
             return Err(AuthyError::RunOnly);
         }
@@ -95,9 +97,16 @@ pub fn run(
                     .map_err(|e| AuthyError::Serialization(e.to_string()))?
             );
         }
+        "json-nested" => {
+            println!(
+                "{}",
+                serde_json::to_string(&nest(&pairs)?)
+                    .map_err(|e| AuthyError::Serialization(e.to_string()))?
+            );
+        }
         other => {
             return Err(AuthyError::Other(format!(
-                "Unknown format '{}'. Use 'shell', 'dotenv', or 'json'.",
+                "Unknown format '{}'. Use 'shell', 'dotenv', 'json', or 'json-nested'.",
                 other
             )));
         }
@@ -107,7 +116,7 @@ pub fn run(
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "env_export",
         None,
         &auth_ctx.actor_name(),
@@ -120,10 +129,56 @@ pub fn run(
         )),
         &audit_key,
     )?;
+    hooks::trip_canaries(
+        &vault,
+        secrets.keys().map(|s| s.as_str()),
+        "env_export",
+        &auth_ctx.actor_name(),
+        Some(&scope),
+        &audit_key,
+    );
 
     Ok(())
 }
 
+/// Build a nested JSON object by splitting each name on `-` and `/`, so
+/// `db-host` and `db-port` become `{"db": {"host": .., "port": ..}}`.
+/// Keys are sorted. A name that is both a value and a parent (`db` alongside
+/// `db-host`) is an error rather than silently dropping one of them.
+fn nest(pairs: &[(String, String)]) -> Result<serde_json::Value> {
+    use serde_json::{Map, Value};
+
+    let mut root = Map::new();
+    for (name, value) in pairs {
+        let segments: Vec<&str> = name.split(['-', '/']).filter(|s| !s.is_empty()).collect();
+        let (leaf, parents) = match segments.split_last() {
+            Some(split) => split,
+            None => continue,
+        };
+
+        let conflict = || {
+            AuthyError::Other(format!(
+                "Cannot nest '{}': a parent path is also a secret value",
+                name
+            ))
+        };
+
+        let mut node = &mut root;
+        for segment in parents {
+            node = node
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .ok_or_else(conflict)?;
+        }
+        if node.contains_key(*leaf) {
+            return Err(conflict());
+        }
+        node.insert(leaf.to_string(), Value::String(value.clone()));
+    }
+    Ok(Value::Object(root))
+}
+
 /// Shell-escape a value for single-quoted POSIX shell strings.
 /// Replaces `'` with `'\''`.
 fn shell_escape(value: &str) -> String {
@@ -0,0 +1,96 @@
+use chrono::Utc;
+
+use authy::audit;
+use authy::auth;
+use crate::cli::json_output::{ExpiringItem, ExpiringResponse};
+use authy::error::{AuthyError, Result};
+use authy::session::{self, Capability};
+use authy::vault;
+
+/// List secrets whose `expires_at` has passed or falls within `within`,
+/// soonest first, for rotation planning. Values are never read.
+pub fn run(within: &str, scope: Option<&str>, json: bool) -> Result<()> {
+    let horizon = Utc::now() + session::parse_ttl(within)?;
+
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::List)?;
+    let vault = vault::load_vault(&key)?;
+
+    let effective_scope = super::common::listing_scope(scope, &auth_ctx)?;
+
+    let names: Vec<&str> = vault.secrets.keys().map(|s| s.as_str()).collect();
+    let mut count_only = false;
+    let visible = if let Some(ref scope_name) = effective_scope {
+        let policy = vault.effective_policy(scope_name)?;
+        count_only = policy.count_only && auth_ctx.scope.is_some();
+        policy.filter_secrets(&names)?
+    } else {
+        names
+    };
+
+    let mut due: Vec<(&str, chrono::DateTime<Utc>, bool)> = visible
+        .iter()
+        .filter_map(|name| {
+            let metadata = &vault.secrets.get(*name)?.metadata;
+            let at = metadata.expires_at?;
+            (at <= horizon).then_some((*name, at, metadata.is_expired()))
+        })
+        .collect();
+    due.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+
+    if json {
+        let items = due
+            .iter()
+            .map(|(name, at, expired)| ExpiringItem {
+                name: name.to_string(),
+                expires: at.to_rfc3339(),
+                expired: *expired,
+            })
+            .collect();
+        let response = ExpiringResponse {
+            secrets: (!count_only).then_some(items),
+            count: due.len(),
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+    } else if due.is_empty() {
+        eprintln!("No secrets expire within {}.", within);
+    } else if count_only {
+        println!("{}", due.len());
+        eprintln!("Secret names are hidden by this token's policy.");
+    } else {
+        let width = due.iter().map(|(n, _, _)| n.len()).max().unwrap_or(0);
+        for (name, at, expired) in &due {
+            println!(
+                "{:<width$}  {}{}",
+                name,
+                at.format("%Y-%m-%d %H:%M UTC"),
+                if *expired { "  (expired)" } else { "" }
+            );
+        }
+    }
+
+    // Audit log
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    let mut detail = format!("within={}", within);
+    if let Some(ref s) = effective_scope {
+        detail.push_str(&format!(", scope={}", s));
+    }
+    if count_only {
+        detail.push_str(", count-only");
+    }
+    audit::log_event(
+        &vault::audit_path()?,
+        "expiring",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    Ok(())
+}
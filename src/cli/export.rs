@@ -1,28 +1,68 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
 use serde::Serialize;
+use zeroize::Zeroizing;
 
 use authy::audit;
 use authy::auth;
 use crate::cli::common;
 use authy::config::project::ProjectConfig;
 use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::session::Capability;
+use authy::policy::build_globset;
 use authy::subprocess::{transform_name, NamingOptions};
-use authy::vault;
+use authy::vault::{self, crypto, secret::{Provenance, SecretEntry}, Vault};
+
+/// Comment placed before a binary secret in `--format env` output.
+pub const BINARY_MARKER: &str = "# authy:binary";
 
 #[derive(Serialize)]
 struct ExportJsonEntry {
     name: String,
     value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+    version: u32,
+    created: String,
+    modified: String,
+}
+
+/// One secret in `--format metadata-only` output. Never carries the value.
+#[derive(Serialize)]
+struct ExportMetadataEntry<'a> {
+    name: &'a str,
     version: u32,
     created: String,
     modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    binary: bool,
+    protected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<&'a Provenance>,
+    /// Policies whose patterns allow reading this secret.
+    policies: Vec<&'a str>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     format: &str,
     scope_arg: Option<&str>,
     uppercase_arg: bool,
     replace_dash_arg: Option<char>,
     prefix_arg: Option<String>,
+    tag: Option<&str>,
+    name_match: Option<&str>,
+    secrets_dir: Option<&str>,
+    age_recipients: &[String],
 ) -> Result<()> {
     // Merge CLI args with project config (scope remains optional for export)
     let project = ProjectConfig::discover_from_cwd().ok().flatten();
@@ -44,20 +84,29 @@ pub fn run(
         }
     }
 
+    // Metadata-only output carries no values, so it needs only `list` and
+    // is allowed under run-only restrictions
+    let with_values = format != "metadata-only";
+
     // Without scope: require master auth (reject tokens)
-    let require_write = scope.is_none();
-    let (key, auth_ctx) = auth::resolve_auth(require_write)?;
+    let (key, auth_ctx) = if scope.is_none() {
+        auth::resolve_auth(true)?
+    } else if with_values {
+        auth::resolve_auth_for(Capability::Read)?
+    } else {
+        auth::resolve_auth_for(Capability::List)?
+    };
     let vault_data = vault::load_vault(&key)?;
 
     // Token-level run_only enforcement
-    if auth_ctx.run_only {
+    if with_values && auth_ctx.run_only {
         return Err(AuthyError::RunOnly);
     }
 
     // Policy-level run_only enforcement
     if let Some(ref scope_name) = scope {
-        if let Some(policy) = vault_data.policies.get(scope_name) {
-            if policy.run_only {
+        if let Ok(policy) = vault_data.effective_policy(scope_name) {
+            if with_values && policy.run_only {
                 return Err(AuthyError::RunOnly);
             }
         }
@@ -69,77 +118,102 @@ pub fn run(
         prefix,
     };
 
+    // Select secrets: scope-filtered, or everything under master auth
+    let mut selected: Vec<(&str, &SecretEntry)> = if let Some(ref scope) = scope {
+        let secrets = common::resolve_scoped_secrets(&vault_data, scope, &auth_ctx)?;
+        vault_data
+            .secrets
+            .iter()
+            .filter(|(name, _)| secrets.contains_key(name.as_str()))
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect()
+    } else {
+        vault_data
+            .secrets
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect()
+    };
+
+    // Ad-hoc filters applied on top of the scope
+    if let Some(tag) = tag {
+        selected.retain(|(_, entry)| entry.metadata.tags.iter().any(|t| t == tag));
+    }
+    if let Some(pattern) = name_match {
+        let matcher = build_globset(&[pattern.to_string()])?;
+        selected.retain(|(name, _)| matcher.is_match(name));
+    }
+    // Every format lists secrets in vault name order (byte order), whatever
+    // naming options are set, so one changed secret is a one-entry diff
+    selected.sort_by(|a, b| a.0.cmp(b.0));
+
     match format {
         "env" => {
-            if let Some(ref scope) = scope {
-                let secrets = common::resolve_scoped_secrets(&vault_data, scope, &auth_ctx)?;
-                let mut pairs: Vec<(String, String)> = secrets
-                    .iter()
-                    .map(|(name, value)| (transform_name(name, &naming), value.clone()))
-                    .collect();
-                pairs.sort_by(|a, b| a.0.cmp(&b.0));
-
-                for (key, value) in &pairs {
-                    println!("{}={}", key, dotenv_quote(value));
-                }
-            } else {
-                // Export all secrets (master auth required)
-                let mut pairs: Vec<(String, &str)> = vault_data
-                    .secrets
-                    .iter()
-                    .map(|(name, entry)| (transform_name(name, &naming), entry.value.as_str()))
-                    .collect();
-                pairs.sort_by(|a, b| a.0.cmp(&b.0));
-
-                for (key, value) in &pairs {
-                    println!("{}={}", key, dotenv_quote(value));
-                }
-            }
+            print!("{}", *render_dotenv(&exported_names(&selected, &naming)?));
         }
         "json" => {
-            if let Some(ref scope) = scope {
-                let secrets = common::resolve_scoped_secrets(&vault_data, scope, &auth_ctx)?;
-                let mut entries: Vec<ExportJsonEntry> = secrets
-                    .keys()
-                    .filter_map(|name| {
-                        vault_data.secrets.get(name).map(|entry| ExportJsonEntry {
-                            name: transform_name(name, &naming),
-                            value: entry.value.clone(),
-                            version: entry.metadata.version,
-                            created: entry.metadata.created_at.to_rfc3339(),
-                            modified: entry.metadata.modified_at.to_rfc3339(),
-                        })
-                    })
-                    .collect();
-                entries.sort_by(|a, b| a.name.cmp(&b.name));
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&entries)
-                        .map_err(|e| AuthyError::Serialization(e.to_string()))?
-                );
-            } else {
-                let mut entries: Vec<ExportJsonEntry> = vault_data
-                    .secrets
-                    .iter()
-                    .map(|(name, entry)| ExportJsonEntry {
-                        name: transform_name(name, &naming),
-                        value: entry.value.clone(),
-                        version: entry.metadata.version,
-                        created: entry.metadata.created_at.to_rfc3339(),
-                        modified: entry.metadata.modified_at.to_rfc3339(),
-                    })
-                    .collect();
-                entries.sort_by(|a, b| a.name.cmp(&b.name));
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&entries)
-                        .map_err(|e| AuthyError::Serialization(e.to_string()))?
-                );
+            let entries: Vec<ExportJsonEntry> = exported_names(&selected, &naming)?
+                .into_iter()
+                .map(|(name, entry)| ExportJsonEntry {
+                    name,
+                    value: entry.value.clone(),
+                    encoding: entry.is_binary().then_some("base64"),
+                    version: entry.metadata.version,
+                    created: entry.metadata.created_at.to_rfc3339(),
+                    modified: entry.metadata.modified_at.to_rfc3339(),
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&entries)
+                    .map_err(|e| AuthyError::Serialization(e.to_string()))?
+            );
+        }
+        "metadata-only" => {
+            let entries = metadata_entries(&vault_data, &selected)?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&entries)
+                    .map_err(|e| AuthyError::Serialization(e.to_string()))?
+            );
+        }
+        "compose" => {
+            let dir = secrets_dir.ok_or_else(|| {
+                AuthyError::Other("--format compose requires --secrets-dir <DIR>".into())
+            })?;
+            write_compose_secrets(&selected, &naming, Path::new(dir))?;
+        }
+        "sops" => {
+            if age_recipients.is_empty() {
+                return Err(AuthyError::Other(
+                    "--format sops requires at least one --age-recipient".into(),
+                ));
             }
+            print!(
+                "{}",
+                encrypt_sops(&exported_names(&selected, &naming)?, age_recipients)?
+            );
+        }
+        "encrypted" => {
+            if age_recipients.is_empty() {
+                return Err(AuthyError::Other(
+                    "--format encrypted requires at least one --recipient".into(),
+                ));
+            }
+            // A dotenv bundle, so `authy import --from encrypted` reuses the
+            // dotenv parser (binary markers included)
+            let bundle = render_dotenv(&exported_names(&selected, &naming)?);
+            let armored = crypto::encrypt_to_recipients(bundle.as_bytes(), age_recipients)?;
+            std::io::stdout().write_all(&armored)?;
+            eprintln!(
+                "Encrypted {} secret(s) to {} recipient(s).",
+                selected.len(),
+                age_recipients.len()
+            );
         }
         other => {
             return Err(AuthyError::Other(format!(
-                "Unknown format '{}'. Use 'env' or 'json'.",
+                "Unknown format '{}'. Use 'env', 'json', 'metadata-only', 'compose', 'sops', or 'encrypted'.",
                 other
             )));
         }
@@ -148,12 +222,18 @@ pub fn run(
     // Audit log
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
-    let detail = match scope {
+    let mut detail = match scope {
         Some(ref s) => format!("format={}, scope={}", format, s),
         None => format!("format={}, scope=all", format),
     };
+    if let Some(tag) = tag {
+        detail.push_str(&format!(", tag={}", tag));
+    }
+    if let Some(pattern) = name_match {
+        detail.push_str(&format!(", match={}", pattern));
+    }
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "export",
         None,
         &auth_ctx.actor_name(),
@@ -161,10 +241,219 @@ pub fn run(
         Some(&detail),
         &audit_key,
     )?;
+    if with_values {
+        hooks::trip_canaries(
+            &vault_data,
+            selected.iter().map(|(name, _)| *name),
+            "export",
+            &auth_ctx.actor_name(),
+            scope.as_deref(),
+            &audit_key,
+        );
+    }
+
+    Ok(())
+}
+
+/// Pair the selected secrets with the names they are exported under,
+/// keeping their order. Two secrets exported under the same name (`db-url`
+/// and `db_url` with `--replace-dash _`) are refused rather than written as
+/// a duplicate key whose winner depends on the reader.
+fn exported_names<'a>(
+    selected: &[(&str, &'a SecretEntry)],
+    naming: &NamingOptions,
+) -> Result<Vec<(String, &'a SecretEntry)>> {
+    let mut seen: BTreeMap<String, &str> = BTreeMap::new();
+    let mut pairs = Vec::with_capacity(selected.len());
+    for &(name, entry) in selected {
+        let key = transform_name(name, naming);
+        if let Some(other) = seen.insert(key.clone(), name) {
+            return Err(AuthyError::Other(format!(
+                "Secrets '{}' and '{}' would both be exported as '{}'",
+                other, name, key
+            )));
+        }
+        pairs.push((key, entry));
+    }
+    Ok(pairs)
+}
+
+/// Describe the selected secrets without their values, sorted by name, each
+/// with the policies that can read it.
+fn metadata_entries<'a>(
+    vault: &'a Vault,
+    selected: &[(&'a str, &'a SecretEntry)],
+) -> Result<Vec<ExportMetadataEntry<'a>>> {
+    let names: Vec<&str> = selected.iter().map(|(name, _)| *name).collect();
+    let mut assignments: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for policy_name in vault.policies.keys() {
+        for name in vault.effective_policy(policy_name)?.filter_secrets(&names)? {
+            assignments.entry(name).or_default().push(policy_name.as_str());
+        }
+    }
 
+    let mut entries: Vec<ExportMetadataEntry> = selected
+        .iter()
+        .map(|(name, entry)| {
+            let meta = &entry.metadata;
+            ExportMetadataEntry {
+                name,
+                version: meta.version,
+                created: meta.created_at.to_rfc3339(),
+                modified: meta.modified_at.to_rfc3339(),
+                expires: meta.expires_at.map(|t| t.to_rfc3339()),
+                tags: &meta.tags,
+                description: meta.description.as_deref(),
+                binary: entry.is_binary(),
+                protected: meta.protected,
+                provenance: meta.provenance.as_ref(),
+                policies: assignments.remove(name).unwrap_or_default(),
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(b.name));
+    Ok(entries)
+}
+
+#[derive(Serialize)]
+struct ComposeSecrets {
+    secrets: BTreeMap<String, ComposeSecretFile>,
+}
+
+#[derive(Serialize)]
+struct ComposeSecretFile {
+    file: String,
+}
+
+/// The env file written next to the Compose secret files.
+const COMPOSE_ENV_FILE: &str = "secrets.env";
+
+/// Write Docker Compose file-based secrets.
+///
+/// Each secret value goes to `<dir>/<name>`, and `<dir>/secrets.env` maps
+/// `<ENV_NAME>_FILE` to the in-container path `/run/secrets/<name>` (the
+/// `*_FILE` convention used by official images), so no secret may be named
+/// `secrets.env`. The `secrets:` block to paste
+/// into `compose.yaml` is printed to stdout.
+fn write_compose_secrets(
+    selected: &[(&str, &SecretEntry)],
+    naming: &NamingOptions,
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let env_names = exported_names(selected, naming)?;
+
+    let mut env_file = String::new();
+    let mut block = ComposeSecrets {
+        secrets: BTreeMap::new(),
+    };
+
+    // Check every name before writing anything
+    for (name, _) in selected {
+        if name.contains(['/', '\\']) || name.starts_with('.') || *name == COMPOSE_ENV_FILE {
+            return Err(AuthyError::Other(format!(
+                "Secret name '{}' cannot be used as a Compose secret file name",
+                name
+            )));
+        }
+    }
+
+    for ((name, entry), (env_name, _)) in selected.iter().zip(&env_names) {
+        let path = dir.join(name);
+        common::write_private_file(&path, &entry.bytes()?)?;
+
+        env_file.push_str(&format!("{}_FILE=/run/secrets/{}\n", env_name, name));
+        block.secrets.insert(
+            name.to_string(),
+            ComposeSecretFile {
+                file: path.display().to_string(),
+            },
+        );
+    }
+
+    let env_path = dir.join(COMPOSE_ENV_FILE);
+    common::write_private_file(&env_path, env_file.as_bytes())?;
+
+    print!(
+        "{}",
+        serde_yaml::to_string(&block).map_err(|e| AuthyError::Serialization(e.to_string()))?
+    );
+    eprintln!(
+        "Wrote {} secret file(s) and {} for use as env_file.",
+        selected.len(),
+        env_path.display()
+    );
     Ok(())
 }
 
+/// Encrypt the selected secrets into a SOPS YAML document for the given age
+/// recipients. The plaintext is piped to `sops` on stdin and never touches disk.
+fn encrypt_sops(exported: &[(String, &SecretEntry)], recipients: &[String]) -> Result<String> {
+    match Command::new("sops").arg("--version").output() {
+        Ok(output) if output.status.success() => {}
+        _ => {
+            return Err(AuthyError::Other(
+                "SOPS CLI not found. Install from https://github.com/getsops/sops".into(),
+            ))
+        }
+    }
+
+    // A Mapping keeps insertion order, so keys follow the vault names
+    let data: serde_yaml::Mapping = exported
+        .iter()
+        .map(|(name, entry)| (name.as_str().into(), entry.value.as_str().into()))
+        .collect();
+    let plaintext =
+        serde_yaml::to_string(&data).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+
+    let mut child = Command::new("sops")
+        .args([
+            "--encrypt",
+            "--input-type",
+            "yaml",
+            "--output-type",
+            "yaml",
+            "--age",
+            &recipients.join(","),
+            "/dev/stdin",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AuthyError::Other(format!("Failed to run sops: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(plaintext.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AuthyError::Other(format!(
+            "SOPS encryption failed: {}",
+            stderr.trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|e| AuthyError::Other(format!("SOPS output is not valid UTF-8: {}", e)))
+}
+
+/// Render exported secrets as dotenv lines, in the order given.
+fn render_dotenv(exported: &[(String, &SecretEntry)]) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    for (key, entry) in exported {
+        // Lets `authy import` restore the value as binary
+        if entry.is_binary() {
+            out.push_str(BINARY_MARKER);
+            out.push('\n');
+        }
+        out.push_str(&format!("{}={}\n", key, dotenv_quote(&entry.value)));
+    }
+    out
+}
+
 /// Quote a value for dotenv format.
 fn dotenv_quote(value: &str) -> String {
     if value.is_empty() {
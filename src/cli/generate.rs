@@ -0,0 +1,72 @@
+use authy::audit;
+use authy::auth;
+use authy::config::LimitsConfig;
+use authy::error::{AuthyError, Result};
+use authy::policy::generation;
+use authy::vault::{self, names, secret::{Provenance, SecretEntry}};
+
+pub fn run(name: &str, scope: Option<&str>, length: usize, force: bool) -> Result<()> {
+    let project = super::common::prefix_project()?;
+    let name = &match project {
+        Some(ref p) => p.namespaced(name),
+        None => name.to_string(),
+    };
+
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+    let name = &names::secret_key(&vault, name)?;
+
+    if vault.secrets.contains_key(name) && !force {
+        return Err(AuthyError::SecretAlreadyExists(name.to_string()));
+    }
+
+    let rules = match scope {
+        Some(scope) => vault.effective_policy(scope)?
+            .generation
+            .clone(),
+        None => None,
+    };
+    let value = generation::generate(length, rules.as_ref())?;
+
+    LimitsConfig::load()?.check_secret(&vault, name, &value)?;
+
+    let is_update = vault.secrets.contains_key(name);
+    let chars = value.len();
+    let mut entry = SecretEntry::new(value);
+    let mut provenance = Provenance::new("generate", &auth_ctx.actor_name());
+    if let Some(scope) = scope {
+        provenance = provenance.with_source(format!("policy {}", scope));
+    }
+    entry.metadata.provenance = Some(provenance);
+    entry.metadata.protected = vault.secrets.get(name).is_some_and(|e| e.metadata.protected);
+    entry.metadata.canary = vault.secrets.get(name).is_some_and(|e| e.metadata.canary);
+    vault.secrets.insert(name.to_string(), entry);
+    vault.touch();
+
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    let op = if is_update { "update" } else { "store" };
+    let detail = match scope {
+        Some(scope) => format!("generated scope={}", scope),
+        None => "generated".to_string(),
+    };
+    audit::log_event(
+        &vault::audit_path()?,
+        op,
+        Some(name),
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!(
+        "Secret '{}' {} with a generated {}-character value.",
+        name,
+        if is_update { "updated" } else { "stored" },
+        chars
+    );
+    Ok(())
+}
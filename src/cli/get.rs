@@ -1,12 +1,31 @@
+use std::io::{self, Write};
+use std::path::Path;
+
 use authy::audit;
 use authy::auth;
-use crate::cli::json_output::GetResponse;
+use crate::cli::json_output::{GetDefaultResponse, GetResponse};
 use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::policy::SecretOp;
+use authy::session::Capability;
 use authy::vault;
 
-pub fn run(name: &str, scope: Option<&str>, json: bool) -> Result<()> {
-    let (key, auth_ctx) = auth::resolve_auth(false)?;
+/// Print the first secret that exists among `name` and its `fallbacks`,
+/// or `default` if none does. A name the scope denies is an error, not a
+/// miss, so a policy mistake can't silently fall through to another key.
+pub fn run(
+    name: &str,
+    fallbacks: &[String],
+    default: Option<&str>,
+    scope: Option<&str>,
+    out: Option<&str>,
+    allow_expired: bool,
+    json: bool,
+) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Read)?;
     let vault = vault::load_vault(&key)?;
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
 
     // Token-level run_only enforcement
     if auth_ctx.run_only {
@@ -19,50 +38,136 @@ pub fn run(name: &str, scope: Option<&str>, json: bool) -> Result<()> {
         .or_else(|| auth_ctx.scope.clone());
 
     // If a scope is active, enforce policy
-    if let Some(ref scope_name) = effective_scope {
-        let policy = vault
-            .policies
-            .get(scope_name)
-            .ok_or_else(|| AuthyError::PolicyNotFound(scope_name.clone()))?;
-
-        // Policy-level run_only enforcement
-        if policy.run_only {
-            return Err(AuthyError::RunOnly);
+    let policy = match effective_scope {
+        Some(ref scope_name) => {
+            let policy = vault.effective_policy(scope_name)?;
+
+            // Policy-level run_only enforcement
+            if policy.run_only {
+                return Err(AuthyError::RunOnly);
+            }
+            Some(policy)
         }
+        None => None,
+    };
+
+    let candidates = std::iter::once(name).chain(fallbacks.iter().map(|s| s.as_str()));
+    for candidate in candidates {
+        if let (Some(policy), Some(scope_name)) = (policy.as_ref(), effective_scope.as_ref()) {
+            let decision = policy.decide(SecretOp::Read, candidate)?;
+            if !decision.allowed {
+                // Audit the denial with the rule that caused it
+                let reason = decision.denial_reason();
+                let mut detail = format!("scope={}", scope_name);
+                if let Some(ref reason) = reason {
+                    detail.push_str(&format!(", reason={}", reason));
+                }
+                audit::log_event(
+                    &vault::audit_path()?,
+                    "get",
+                    Some(candidate),
+                    &auth_ctx.actor_name(),
+                    "denied",
+                    Some(&detail),
+                    &audit_key,
+                )?;
+
+                return Err(AuthyError::AccessDenied {
+                    secret: candidate.to_string(),
+                    scope: scope_name.clone(),
+                    reason,
+                });
+            }
+        }
+
+        let entry = match vault.secrets.get(candidate) {
+            Some(entry) => entry,
+            None => continue,
+        };
 
-        if !policy.can_read(name)? {
-            // Audit the denial
-            let material = audit::key_material(&key);
-            let audit_key = audit::derive_audit_key(&material);
+        // An expired secret is refused, not skipped, so a fallback can't
+        // quietly stand in for a credential that needs rotating
+        if entry.metadata.is_expired() && !allow_expired {
             audit::log_event(
-                &vault::audit_path(),
+                &vault::audit_path()?,
                 "get",
-                Some(name),
+                Some(candidate),
                 &auth_ctx.actor_name(),
                 "denied",
-                Some(&format!("scope={}", scope_name)),
+                Some("expired"),
                 &audit_key,
             )?;
+            return Err(AuthyError::SecretExpired(candidate.to_string()));
+        }
+
+        if let Some(path) = out {
+            let bytes = entry.bytes()?;
+            super::common::write_private_file(Path::new(path), &bytes)?;
+            eprintln!("Wrote {} bytes to {}", bytes.len(), path);
+        } else if json {
+            let response = GetResponse {
+                name: candidate.to_string(),
+                value: entry.value.clone(),
+                encoding: entry.is_binary().then_some("base64"),
+                version: entry.metadata.version,
+                created: entry.metadata.created_at.to_rfc3339(),
+                modified: entry.metadata.modified_at.to_rfc3339(),
+                expires: entry.metadata.expires_at.map(|at| at.to_rfc3339()),
+                provenance: entry.metadata.provenance.clone(),
+            };
+            println!(
+                "{}",
+                serde_json::to_string(&response)
+                    .map_err(|e| authy::error::AuthyError::Serialization(e.to_string()))?
+            );
+        } else if entry.is_binary() {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&entry.bytes()?)?;
+            stdout.flush()?;
+        } else {
+            print!("{}", entry.value);
+        }
 
-            return Err(AuthyError::AccessDenied {
-                secret: name.to_string(),
-                scope: scope_name.clone(),
-            });
+        // Audit log
+        let mut details = Vec::new();
+        if let Some(ref s) = effective_scope {
+            details.push(format!("scope={}", s));
+        }
+        if candidate != name {
+            details.push(format!("fallback_for={}", name));
         }
+        let detail = (!details.is_empty()).then(|| details.join(", "));
+        audit::log_event(
+            &vault::audit_path()?,
+            "get",
+            Some(candidate),
+            &auth_ctx.actor_name(),
+            "success",
+            detail.as_deref(),
+            &audit_key,
+        )?;
+        hooks::trip_canaries(
+            &vault,
+            [candidate],
+            "get",
+            &auth_ctx.actor_name(),
+            effective_scope.as_deref(),
+            &audit_key,
+        );
+
+        return Ok(());
     }
 
-    let entry = vault
-        .secrets
-        .get(name)
-        .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
+    let default = default.ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
 
-    if json {
-        let response = GetResponse {
+    if let Some(path) = out {
+        super::common::write_private_file(Path::new(path), default.as_bytes())?;
+        eprintln!("Wrote {} bytes to {}", default.len(), path);
+    } else if json {
+        let response = GetDefaultResponse {
             name: name.to_string(),
-            value: entry.value.clone(),
-            version: entry.metadata.version,
-            created: entry.metadata.created_at.to_rfc3339(),
-            modified: entry.metadata.modified_at.to_rfc3339(),
+            value: default.to_string(),
+            default: true,
         };
         println!(
             "{}",
@@ -70,20 +175,16 @@ pub fn run(name: &str, scope: Option<&str>, json: bool) -> Result<()> {
                 .map_err(|e| authy::error::AuthyError::Serialization(e.to_string()))?
         );
     } else {
-        print!("{}", entry.value);
+        print!("{}", default);
     }
 
-    // Audit log
-    let material = audit::key_material(&key);
-    let audit_key = audit::derive_audit_key(&material);
-    let detail = effective_scope.as_deref().map(|s| format!("scope={}", s));
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "get",
         Some(name),
         &auth_ctx.actor_name(),
-        "success",
-        detail.as_deref(),
+        "default",
+        None,
         &audit_key,
     )?;
 
@@ -1,10 +1,10 @@
 use authy::error::{AuthyError, Result};
 
-pub fn run(shell: &str) -> Result<()> {
+pub fn run(shell: &str, cached: bool) -> Result<()> {
     let output = match shell {
-        "bash" => generate_bash(),
-        "zsh" => generate_zsh(),
-        "fish" => generate_fish(),
+        "bash" => fill(generate_bash(), cached, POSIX_FIND_CONFIG, POSIX_CACHED_FIND_CONFIG, POSIX_GUARD),
+        "zsh" => fill(generate_zsh(), cached, POSIX_FIND_CONFIG, POSIX_CACHED_FIND_CONFIG, POSIX_GUARD),
+        "fish" => fill(generate_fish(), cached, FISH_FIND_CONFIG, FISH_CACHED_FIND_CONFIG, FISH_GUARD),
         other => {
             return Err(AuthyError::Other(format!(
                 "Unsupported shell '{}'. Use bash, zsh, or fish.",
@@ -16,10 +16,23 @@ pub fn run(shell: &str) -> Result<()> {
     Ok(())
 }
 
-fn generate_bash() -> String {
-    r#"# authy shell hook — eval "$(authy hook bash)"
+/// Substitute the discovery function and, in cached mode, the unchanged-$PWD
+/// guard and `--cached` lookups into a hook template.
+fn fill(template: String, cached: bool, find: &str, cached_find: &str, guard: &str) -> String {
+    if cached {
+        template
+            .replace("@FIND_CONFIG@", cached_find)
+            .replace("@GUARD@\n", guard)
+            .replace("@CACHED@", " --cached")
+    } else {
+        template
+            .replace("@FIND_CONFIG@", find)
+            .replace("@GUARD@\n", "")
+            .replace("@CACHED@", "")
+    }
+}
 
-_authy_find_config() {
+const POSIX_FIND_CONFIG: &str = r#"_authy_find_config() {
   local dir="$1"
   while [ "$dir" != "/" ]; do
     if [ -f "$dir/.authy.toml" ]; then
@@ -33,9 +46,50 @@ _authy_find_config() {
     return 0
   fi
   return 1
-}
+}"#;
+
+// Discovery results are cached in ~/.authy/cache, keyed on directory mtimes.
+const POSIX_CACHED_FIND_CONFIG: &str = r#"_authy_find_config() {
+  authy project-info --cached --field dir --dir "$1" 2>/dev/null
+}"#;
+
+const POSIX_GUARD: &str = r#"  [ "$PWD" = "${_AUTHY_LAST_PWD:-}" ] && return 0
+  _AUTHY_LAST_PWD="$PWD"
+"#;
+
+const FISH_FIND_CONFIG: &str = r#"function _authy_find_config
+    set -l dir $argv[1]
+    while test "$dir" != "/"
+        if test -f "$dir/.authy.toml"
+            echo $dir
+            return 0
+        end
+        set dir (dirname $dir)
+    end
+    if test -f "/.authy.toml"
+        echo "/"
+        return 0
+    end
+    return 1
+end"#;
+
+const FISH_CACHED_FIND_CONFIG: &str = r#"function _authy_find_config
+    authy project-info --cached --field dir --dir $argv[1] 2>/dev/null
+end"#;
+
+const FISH_GUARD: &str = r#"    if test "$PWD" = "$_AUTHY_LAST_PWD"
+        return 0
+    end
+    set -g _AUTHY_LAST_PWD $PWD
+"#;
+
+fn generate_bash() -> String {
+    r#"# authy shell hook — eval "$(authy hook bash)"
+
+@FIND_CONFIG@
 
 _authy_hook() {
+@GUARD@
   local project_dir
   project_dir="$(_authy_find_config "$PWD")"
 
@@ -53,7 +107,7 @@ _authy_hook() {
 
       # Set keyfile if configured
       local keyfile
-      keyfile="$(authy project-info --field keyfile --dir "$project_dir" 2>/dev/null)"
+      keyfile="$(authy project-info@CACHED@ --field keyfile --dir "$project_dir" 2>/dev/null)"
       if [ -n "$keyfile" ]; then
         export AUTHY_KEYFILE="$keyfile"
       fi
@@ -86,23 +140,10 @@ _authy_hook
 fn generate_zsh() -> String {
     r#"# authy shell hook — eval "$(authy hook zsh)"
 
-_authy_find_config() {
-  local dir="$1"
-  while [ "$dir" != "/" ]; do
-    if [ -f "$dir/.authy.toml" ]; then
-      echo "$dir"
-      return 0
-    fi
-    dir="$(dirname "$dir")"
-  done
-  if [ -f "/.authy.toml" ]; then
-    echo "/"
-    return 0
-  fi
-  return 1
-}
+@FIND_CONFIG@
 
 _authy_hook() {
+@GUARD@
   local project_dir
   project_dir="$(_authy_find_config "$PWD")"
 
@@ -117,7 +158,7 @@ _authy_hook() {
       export AUTHY_PROJECT_DIR="$project_dir"
 
       local keyfile
-      keyfile="$(authy project-info --field keyfile --dir "$project_dir" 2>/dev/null)"
+      keyfile="$(authy project-info@CACHED@ --field keyfile --dir "$project_dir" 2>/dev/null)"
       if [ -n "$keyfile" ]; then
         export AUTHY_KEYFILE="$keyfile"
       fi
@@ -147,23 +188,10 @@ _authy_hook
 fn generate_fish() -> String {
     r#"# authy shell hook — authy hook fish | source
 
-function _authy_find_config
-    set -l dir $argv[1]
-    while test "$dir" != "/"
-        if test -f "$dir/.authy.toml"
-            echo $dir
-            return 0
-        end
-        set dir (dirname $dir)
-    end
-    if test -f "/.authy.toml"
-        echo "/"
-        return 0
-    end
-    return 1
-end
+@FIND_CONFIG@
 
 function _authy_hook --on-variable PWD
+@GUARD@
     set -l project_dir (_authy_find_config $PWD)
 
     if test -n "$project_dir"
@@ -177,7 +205,7 @@ function _authy_hook --on-variable PWD
 
             set -gx AUTHY_PROJECT_DIR $project_dir
 
-            set -l keyfile (authy project-info --field keyfile --dir $project_dir 2>/dev/null)
+            set -l keyfile (authy project-info@CACHED@ --field keyfile --dir $project_dir 2>/dev/null)
             if test -n "$keyfile"
                 set -gx AUTHY_KEYFILE $keyfile
             end
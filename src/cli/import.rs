@@ -1,16 +1,24 @@
-use std::io::{self, BufRead};
+use std::collections::HashSet;
+use std::io::{self, BufRead, IsTerminal, Read};
+
+use clap::ValueEnum;
+use console::style;
+use zeroize::Zeroizing;
 
 use authy::audit;
 use authy::auth;
+use authy::config::LimitsConfig;
 use authy::error::{AuthyError, Result};
-use authy::vault;
-use authy::vault::secret::SecretEntry;
+use authy::vault::{self, crypto, names};
+use authy::vault::secret::{decode_base64, Provenance, SecretEntry, ValueEncoding};
 
 use super::import_sources::hcvault::HcVaultAdapter;
 use super::import_sources::onepassword::OnePasswordAdapter;
 use super::import_sources::pass::PassAdapter;
+use super::import_sources::procenv::EnvAdapter;
 use super::import_sources::sops::SopsAdapter;
 use super::import_sources::ImportAdapter;
+use super::export::BINARY_MARKER;
 use super::ImportSource;
 
 #[allow(clippy::too_many_arguments)]
@@ -21,64 +29,123 @@ pub fn run(
     tag: Option<&str>,
     path: Option<&str>,
     mount: &str,
+    filter: Option<&str>,
+    all: bool,
+    pid: Option<u32>,
+    identity: Option<&str>,
     keep_names: bool,
     prefix: Option<&str>,
     force: bool,
     dry_run: bool,
+    yes: bool,
 ) -> Result<()> {
-    let parsed = fetch_secrets(file, from, op_vault, tag, path, mount)?;
+    let (parsed, binary) = fetch_secrets(file, from, op_vault, tag, path, mount, filter, all, pid, identity)?;
 
     if parsed.is_empty() {
         eprintln!("No secrets found in input.");
         return Ok(());
     }
 
+    let project = super::common::prefix_project()?;
+
     let (key, auth_ctx) = auth::resolve_auth(!dry_run)?;
     let mut vault_data = vault::load_vault(&key)?;
 
-    let mut imported = 0usize;
-    let mut skipped = 0usize;
-
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
+    let limits = LimitsConfig::load()?;
+    let provenance = provenance(from, file, op_vault, path, mount, pid, &auth_ctx.actor_name());
 
+    // Reject bad names before anything is written or audited
+    let mut plan = Vec::with_capacity(parsed.len());
     for (raw_name, value) in &parsed {
-        let name = transform_name(raw_name, keep_names, prefix);
-
-        let exists = vault_data.secrets.contains_key(&name);
-
-        if exists && !force {
-            eprintln!("Skipping '{}' (already exists, use --force to overwrite)", name);
-            skipped += 1;
-            continue;
+        let mut name = transform_name(raw_name, keep_names, prefix);
+        if let Some(ref p) = project {
+            name = p.namespaced(&name);
         }
+        let name = names::secret_key(&vault_data, &name)?;
+        let is_binary = binary.contains(raw_name);
+        if is_binary {
+            // Reject a corrupt payload before anything is written
+            decode_base64(value)?;
+        }
+        let action = match vault_data.secrets.get(&name) {
+            None => Action::Create,
+            Some(_) if !force => Action::Skip,
+            Some(existing) => Action::Overwrite {
+                old_len: existing.value.len(),
+            },
+        };
+        if action != Action::Skip {
+            limits.check_secret(&vault_data, &name, value)?;
+        }
+        plan.push((name, value, action, is_binary));
+    }
+    // The whole import fits under max_secrets, or none of it is done
+    let creates: HashSet<&str> = plan
+        .iter()
+        .filter(|(_, _, a, _)| *a == Action::Create)
+        .map(|(name, _, _, _)| name.as_str())
+        .collect();
+    limits.check_new_secrets(&vault_data, creates.len())?;
+
+    let to_write = plan.iter().filter(|(_, _, a, _)| *a != Action::Skip).count();
+    let skipped = plan.len() - to_write;
+
+    if dry_run {
+        print_preview(&plan, "[dry-run] ");
+        eprintln!("{} secret(s) imported, {} skipped. (dry run)", to_write, skipped);
+        return Ok(());
+    }
 
-        if dry_run {
-            let action = if exists { "overwrite" } else { "create" };
-            println!("[dry-run] {} {} = {}",
-                action,
-                name,
-                if value.len() > 20 { format!("{}...", &value[..20]) } else { value.clone() }
-            );
-            imported += 1;
-            continue;
+    // Large --force imports are easy to get wrong; show what will change first
+    if !yes && to_write > 0 && !auth::is_non_interactive() && io::stderr().is_terminal() {
+        print_preview(&plan, "");
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!("Import {} secret(s)?", to_write))
+            .default(false)
+            .interact()
+            .map_err(|e| AuthyError::Other(format!("Failed to read confirmation: {}", e)))?;
+        if !confirmed {
+            eprintln!("Import cancelled.");
+            return Ok(());
         }
+    }
 
+    let mut imported = 0usize;
+    for (name, value, action, is_binary) in plan {
+        let exists = match action {
+            Action::Skip => {
+                eprintln!("Skipping '{}' (already exists, use --force to overwrite)", name);
+                continue;
+            }
+            Action::Create => false,
+            Action::Overwrite { .. } => true,
+        };
+
+        let encoding = if is_binary {
+            ValueEncoding::Base64
+        } else {
+            ValueEncoding::Text
+        };
         if exists {
             // Force overwrite: bump version
             if let Some(entry) = vault_data.secrets.get_mut(&name) {
                 entry.value = value.clone();
+                entry.encoding = encoding;
                 entry.metadata.bump_version();
+                entry.metadata.provenance = Some(provenance.clone());
             }
         } else {
-            vault_data
-                .secrets
-                .insert(name.clone(), SecretEntry::new(value.clone()));
+            let mut entry = SecretEntry::new(value.clone());
+            entry.encoding = encoding;
+            entry.metadata.provenance = Some(provenance.clone());
+            vault_data.secrets.insert(name.clone(), entry);
         }
 
         // Audit each imported secret
         audit::log_event(
-            &vault::audit_path(),
+            &vault::audit_path()?,
             "import",
             Some(&name),
             &auth_ctx.actor_name(),
@@ -90,22 +157,102 @@ pub fn run(
         imported += 1;
     }
 
-    if !dry_run && imported > 0 {
+    if imported > 0 {
         vault_data.touch();
         vault::save_vault(&vault_data, &key)?;
     }
 
-    eprintln!(
-        "{} secret(s) imported, {} skipped.{}",
-        imported,
-        skipped,
-        if dry_run { " (dry run)" } else { "" }
-    );
+    eprintln!("{} secret(s) imported, {} skipped.", imported, skipped);
 
     Ok(())
 }
 
+/// What an import will do with one secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Create,
+    Overwrite { old_len: usize },
+    Skip,
+}
+
+/// Print one diff-style line per secret. Values are shown only as lengths.
+fn print_preview(plan: &[(String, &String, Action, bool)], tag: &str) {
+    let width = plan.iter().map(|(n, _, _, _)| n.len()).max().unwrap_or(0);
+    for (name, value, action, _) in plan {
+        let line = match action {
+            Action::Create => style(format!("+ {:<width$}  new ({} bytes)", name, value.len()))
+                .green()
+                .to_string(),
+            Action::Overwrite { old_len } => style(format!(
+                "~ {:<width$}  overwrite ({} → {} bytes)",
+                name,
+                old_len,
+                value.len()
+            ))
+            .yellow()
+            .to_string(),
+            Action::Skip => style(format!(
+                "= {:<width$}  skipped (exists, use --force to overwrite)",
+                name
+            ))
+            .dim()
+            .to_string(),
+        };
+        println!("{}{}", tag, line);
+    }
+}
+
+/// Provenance shared by every secret in one import: the adapter, and the
+/// file or path it read from.
+fn provenance(
+    from: Option<&ImportSource>,
+    file: Option<&str>,
+    op_vault: Option<&str>,
+    path: Option<&str>,
+    mount: &str,
+    pid: Option<u32>,
+    actor: &str,
+) -> Provenance {
+    let source = from.unwrap_or(&ImportSource::Dotenv);
+    let mut provenance = Provenance::new("import", actor).with_source(
+        source
+            .to_possible_value()
+            .expect("no skipped variants")
+            .get_name(),
+    );
+    let location = match source {
+        ImportSource::OnePassword => match (file, op_vault) {
+            (Some(f), _) => Some(
+                std::fs::canonicalize(f)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|_| f.to_string()),
+            ),
+            (None, v) => v.map(|v| format!("vault {}", v)),
+        },
+        ImportSource::Pass => path.map(String::from),
+        ImportSource::Vault => path.map(|p| format!("{}/{}", mount, p)),
+        ImportSource::Env => pid.map(|pid| format!("pid {}", pid)),
+        ImportSource::Dotenv | ImportSource::Sops | ImportSource::Encrypted => {
+            file.map(|f| match f {
+                "-" => "stdin".to_string(),
+                f => std::fs::canonicalize(f)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|_| f.to_string()),
+            })
+        }
+    };
+    if let Some(location) = location {
+        provenance = provenance.with_location(location);
+    }
+    provenance
+}
+
+/// Fetched `(name, value)` pairs, and the names whose values are
+/// base64-encoded binary.
+type Fetched = (Vec<(String, String)>, HashSet<String>);
+
 /// Fetch secrets from the appropriate source.
+#[allow(clippy::too_many_arguments)]
 fn fetch_secrets(
     file: Option<&str>,
     from: Option<&ImportSource>,
@@ -113,12 +260,17 @@ fn fetch_secrets(
     tag: Option<&str>,
     path: Option<&str>,
     mount: &str,
-) -> Result<Vec<(String, String)>> {
-    match from {
+    filter: Option<&str>,
+    all: bool,
+    pid: Option<u32>,
+    identity: Option<&str>,
+) -> Result<Fetched> {
+    let fetched = match from {
         Some(ImportSource::OnePassword) => {
             let adapter = OnePasswordAdapter {
                 vault: op_vault.map(String::from),
                 tag: tag.map(String::from),
+                export: file.map(String::from),
             };
             adapter.fetch()
         }
@@ -153,6 +305,23 @@ fn fetch_secrets(
             };
             adapter.fetch()
         }
+        Some(ImportSource::Env) => {
+            let adapter = EnvAdapter {
+                pid,
+                filter: filter.map(String::from),
+                all,
+            };
+            adapter.fetch()
+        }
+        Some(ImportSource::Encrypted) => {
+            let f = file.ok_or_else(|| {
+                AuthyError::Other(
+                    "Encrypted import requires a file argument (e.g., authy import --from encrypted bundle.age)"
+                        .into(),
+                )
+            })?;
+            return read_encrypted(f, identity);
+        }
         Some(ImportSource::Dotenv) | None => {
             // Existing .env import behavior
             let f = file.ok_or_else(|| {
@@ -160,13 +329,14 @@ fn fetch_secrets(
                     "Import requires a file argument (e.g., authy import .env)".into(),
                 )
             })?;
-            read_dotenv(f)
+            return read_dotenv(f);
         }
-    }
+    };
+    Ok((fetched?, HashSet::new()))
 }
 
 /// Read and parse a dotenv file (or stdin with "-").
-fn read_dotenv(file: &str) -> Result<Vec<(String, String)>> {
+fn read_dotenv(file: &str) -> Result<Fetched> {
     let content = if file == "-" {
         let mut buf = String::new();
         let stdin = io::stdin();
@@ -183,6 +353,33 @@ fn read_dotenv(file: &str) -> Result<Vec<(String, String)>> {
     parse_dotenv(&content)
 }
 
+/// Decrypt a bundle from `authy export --format encrypted` (or stdin with
+/// "-") and parse the dotenv inside.
+fn read_encrypted(file: &str, identity: Option<&str>) -> Result<Fetched> {
+    let identity_path = match identity {
+        Some(path) => path.to_string(),
+        None => std::env::var("AUTHY_KEYFILE").map_err(|_| {
+            AuthyError::Other(
+                "Encrypted import requires --identity <keyfile> (or AUTHY_KEYFILE)".into(),
+            )
+        })?,
+    };
+    let (identity, _) = auth::read_keyfile(&identity_path)?;
+
+    let ciphertext = if file == "-" {
+        let mut buf = Vec::new();
+        io::stdin().lock().read_to_end(&mut buf)?;
+        buf
+    } else {
+        std::fs::read(file)?
+    };
+
+    let plaintext = Zeroizing::new(crypto::decrypt_from_recipients(&ciphertext, &identity)?);
+    let content = std::str::from_utf8(&plaintext)
+        .map_err(|_| AuthyError::Other("Encrypted bundle is not a dotenv file".into()))?;
+    parse_dotenv(content)
+}
+
 /// Transform a raw secret name using the shared pipeline.
 fn transform_name(raw_name: &str, keep_names: bool, prefix: Option<&str>) -> String {
     if keep_names {
@@ -208,13 +405,21 @@ fn to_lower_kebab(name: &str) -> String {
         .replace(['_', '/', ' ', '.'], "-")
 }
 
-/// Parse a dotenv-format string into (key, value) pairs.
-fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>> {
+/// Parse a dotenv-format string into (key, value) pairs, plus the keys
+/// that `authy export` marked as binary.
+fn parse_dotenv(content: &str) -> Result<Fetched> {
     let mut result = Vec::new();
+    let mut binary = HashSet::new();
+    let mut next_is_binary = false;
 
     for line in content.lines() {
         let trimmed = line.trim();
 
+        if trimmed == BINARY_MARKER {
+            next_is_binary = true;
+            continue;
+        }
+
         // Skip empty lines and comments
         if trimmed.is_empty() || trimmed.starts_with('#') {
             continue;
@@ -239,10 +444,13 @@ fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>> {
         }
 
         let value = parse_dotenv_value(&raw_value);
+        if std::mem::take(&mut next_is_binary) {
+            binary.insert(key.clone());
+        }
         result.push((key, value));
     }
 
-    Ok(result)
+    Ok((result, binary))
 }
 
 /// Parse a dotenv value, handling quoted and unquoted forms.
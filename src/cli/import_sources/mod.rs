@@ -1,6 +1,7 @@
 pub mod hcvault;
 pub mod onepassword;
 pub mod pass;
+pub mod procenv;
 pub mod sops;
 
 use authy::error::Result;
@@ -1,3 +1,4 @@
+use std::io::Read;
 use std::process::Command;
 
 use authy::error::{AuthyError, Result};
@@ -7,10 +8,16 @@ use super::ImportAdapter;
 pub struct OnePasswordAdapter {
     pub vault: Option<String>,
     pub tag: Option<String>,
+    /// A `.1pux` export to read instead of asking the `op` CLI.
+    pub export: Option<String>,
 }
 
 impl ImportAdapter for OnePasswordAdapter {
     fn fetch(&self) -> Result<Vec<(String, String)>> {
+        if let Some(ref path) = self.export {
+            return read_export(path, &self.vault, &self.tag);
+        }
+
         // Check that `op` CLI is installed
         check_op_installed()?;
 
@@ -129,3 +136,85 @@ fn get_item_password(id: &str) -> Result<String> {
 
     Ok(field["value"].as_str().unwrap_or_default().to_string())
 }
+
+/// Read a `.1pux` export: a zip archive whose `export.data` holds every
+/// account, vault and item as JSON. Archived and deleted items are skipped.
+fn read_export(
+    path: &str,
+    vault: &Option<String>,
+    tag: &Option<String>,
+) -> Result<Vec<(String, String)>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| AuthyError::Other(format!("Failed to open {}: {}", path, e)))?;
+    let not_1pux = |e: zip::result::ZipError| {
+        AuthyError::Other(format!("{} is not a 1Password .1pux export: {}", path, e))
+    };
+    let mut archive = zip::ZipArchive::new(file).map_err(not_1pux)?;
+    let mut data = String::new();
+    archive
+        .by_name("export.data")
+        .map_err(not_1pux)?
+        .read_to_string(&mut data)?;
+    let export: serde_json::Value = serde_json::from_str(&data)
+        .map_err(|e| AuthyError::Other(format!("Failed to parse {}: {}", path, e)))?;
+
+    let mut secrets = Vec::new();
+    let accounts = export["accounts"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for export_vault in accounts.iter().flat_map(|a| json_array(&a["vaults"])) {
+        let vault_name = export_vault["attrs"]["name"].as_str().unwrap_or_default();
+        if vault.as_deref().is_some_and(|v| v != vault_name) {
+            continue;
+        }
+        for item in json_array(&export_vault["items"]) {
+            if item["state"].as_str().is_some_and(|s| s != "active") {
+                continue;
+            }
+            let title = item["overview"]["title"].as_str().unwrap_or_default();
+            if title.is_empty() {
+                continue;
+            }
+            if let Some(t) = tag {
+                let tags = json_array(&item["overview"]["tags"]);
+                if !tags.iter().any(|v| v.as_str() == Some(t)) {
+                    continue;
+                }
+            }
+            match export_item_secret(&item["details"]) {
+                Some(value) if !value.is_empty() => secrets.push((title.to_string(), value)),
+                _ => eprintln!("Warning: skipping '{}': No password or credential field found", title),
+            }
+        }
+    }
+
+    Ok(secrets)
+}
+
+/// The password of an exported item, or its `credential` field (API
+/// credentials and similar), like `get_item_password` asks `op` for.
+fn export_item_secret(details: &serde_json::Value) -> Option<String> {
+    let login_password = json_array(&details["loginFields"])
+        .iter()
+        .find(|f| f["designation"].as_str() == Some("password"))
+        .and_then(|f| f["value"].as_str());
+    if let Some(value) = login_password.or_else(|| details["password"].as_str()) {
+        return Some(value.to_string());
+    }
+
+    let field = json_array(&details["sections"])
+        .iter()
+        .flat_map(|s| json_array(&s["fields"]))
+        .find(|f| {
+            ["password", "credential"]
+                .iter()
+                .any(|label| f["id"].as_str() == Some(label) || f["title"].as_str() == Some(label))
+        })?;
+    let value = &field["value"];
+    value["concealed"]
+        .as_str()
+        .or_else(|| value["string"].as_str())
+        .map(String::from)
+}
+
+fn json_array(value: &serde_json::Value) -> &[serde_json::Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
@@ -60,7 +60,9 @@ impl ImportAdapter for PassAdapter {
     }
 }
 
-fn resolve_store_dir(explicit_path: &Option<String>) -> Result<PathBuf> {
+/// The password-store directory: `explicit_path`, else `$PASSWORD_STORE_DIR`,
+/// else `~/.password-store`.
+pub fn resolve_store_dir(explicit_path: &Option<String>) -> Result<PathBuf> {
     if let Some(p) = explicit_path {
         return Ok(PathBuf::from(p));
     }
@@ -76,7 +78,7 @@ fn resolve_store_dir(explicit_path: &Option<String>) -> Result<PathBuf> {
     Ok(home.join(".password-store"))
 }
 
-fn check_gpg_installed() -> Result<()> {
+pub fn check_gpg_installed() -> Result<()> {
     match Command::new("gpg").arg("--version").output() {
         Ok(output) if output.status.success() => Ok(()),
         _ => Err(AuthyError::Other(
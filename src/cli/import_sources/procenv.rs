@@ -0,0 +1,74 @@
+use std::fs;
+
+use authy::error::{AuthyError, Result};
+use authy::policy::build_globset;
+
+use super::ImportAdapter;
+
+/// Snapshot environment variables from this process or another process
+/// (via `/proc/<pid>/environ`, which requires permission to read it).
+pub struct EnvAdapter {
+    pub pid: Option<u32>,
+    pub filter: Option<String>,
+    /// Import everything when there is no filter. Without it, a missing
+    /// filter is an error rather than a copy of PATH, HOME and the rest.
+    pub all: bool,
+}
+
+impl ImportAdapter for EnvAdapter {
+    fn fetch(&self) -> Result<Vec<(String, String)>> {
+        if self.filter.is_none() && !self.all {
+            return Err(AuthyError::Other(
+                "Importing from env requires --filter <glob> (e.g. --filter \"STRIPE_*\"), or --all for every variable"
+                    .into(),
+            ));
+        }
+
+        let vars = match self.pid {
+            Some(pid) => read_proc_environ(pid)?,
+            None => std::env::vars().collect(),
+        };
+
+        let matcher = match &self.filter {
+            Some(pattern) => Some(build_globset(std::slice::from_ref(pattern))?),
+            None => None,
+        };
+
+        let mut secrets: Vec<(String, String)> = vars
+            .into_iter()
+            // Never capture authy's own credentials, nor the shell's `$_`
+            .filter(|(k, _)| !k.starts_with("AUTHY_") && k != "_")
+            .filter(|(k, _)| matcher.as_ref().map_or(true, |m| m.is_match(k)))
+            .collect();
+        secrets.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(secrets)
+    }
+}
+
+fn read_proc_environ(pid: u32) -> Result<Vec<(String, String)>> {
+    let path = format!("/proc/{}/environ", pid);
+    let raw = fs::read(&path).map_err(|e| {
+        AuthyError::Other(format!(
+            "Cannot read environment of process {} ({}): {}",
+            pid, path, e
+        ))
+    })?;
+
+    Ok(parse_environ(&raw))
+}
+
+/// Parse a NUL-separated `KEY=value` block as found in `/proc/<pid>/environ`.
+fn parse_environ(raw: &[u8]) -> Vec<(String, String)> {
+    raw.split(|b| *b == 0)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
@@ -4,28 +4,46 @@ use authy::config::Config;
 use authy::error::{AuthyError, Result};
 use authy::vault::{self, Vault};
 
-pub fn run(passphrase: Option<String>, generate_keyfile: Option<String>) -> Result<()> {
-    if vault::is_initialized() {
-        return Err(AuthyError::VaultAlreadyExists(
-            vault::vault_path().display().to_string(),
-        ));
-    }
+use crate::cli::json_output::{InitPlanFile, InitPlanResponse};
+
+/// age plugin used by `--piv`.
+const PIV_PLUGIN: &str = "yubikey";
+
+pub fn run(
+    passphrase: Option<String>,
+    generate_keyfile: Option<String>,
+    piv: Option<&str>,
+    ssh_key: Option<&str>,
+) -> Result<()> {
+    vault::check_not_initialized()?;
 
-    let key = auth::resolve_auth_for_init(passphrase, generate_keyfile)?;
+    let key = match (piv, ssh_key) {
+        (Some(path), _) => auth::resolve_plugin_identity_for_init(path, PIV_PLUGIN)?,
+        (None, Some(path)) => auth::resolve_ssh_key_for_init(path)?,
+        (None, None) => auth::resolve_auth_for_init(passphrase, generate_keyfile)?,
+    };
 
     // Create empty vault
     let vault = Vault::new();
     vault::save_vault(&vault, &key)?;
 
-    // Write default config
-    let config = Config::default();
-    config.save(&vault::config_path())?;
+    // Write default config, keeping one written beforehand (e.g. to pick
+    // a storage backend)
+    let config_path = vault::config_path()?;
+    if !config_path.exists() {
+        Config::default().save(&config_path)?;
+    }
+    // Later commands find the SSH key here
+    if let Some(path) = ssh_key {
+        Config::set(&config_path, "vault.auth_method", auth::SSH_AGENT_METHOD)?;
+        Config::set(&config_path, "vault.ssh_key", path)?;
+    }
 
     // Log the init event
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "init",
         None,
         "master",
@@ -34,6 +52,109 @@ pub fn run(passphrase: Option<String>, generate_keyfile: Option<String>) -> Resu
         &audit_key,
     )?;
 
-    eprintln!("Vault initialized at {}", vault::authy_dir().display());
+    eprintln!("Vault initialized at {}", vault::authy_dir()?.display());
+    Ok(())
+}
+
+/// Report what `init` would create, without writing anything.
+pub fn dry_run(
+    has_passphrase: bool,
+    generate_keyfile: Option<&str>,
+    piv: Option<&str>,
+    ssh_key: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    vault::check_not_initialized()?;
+
+    let auth_mode = match (generate_keyfile, piv, ssh_key) {
+        (Some(_), _, _) => "keyfile",
+        (None, Some(_), _) => "plugin (age-plugin-yubikey)",
+        (None, None, Some(_)) => "ssh-agent",
+        (None, None, None) if has_passphrase => "passphrase",
+        (None, None, None) => "passphrase (interactive prompt)",
+    };
+
+    let file = |path: String, kind: &str, description: &str, mode: Option<&str>| InitPlanFile {
+        path,
+        kind: kind.to_string(),
+        description: description.to_string(),
+        mode: mode.map(|m| m.to_string()),
+    };
+
+    let mut files = Vec::new();
+    let dir = vault::authy_dir()?;
+    if !dir.exists() {
+        files.push(file(dir.display().to_string(), "directory", "authy directory", None));
+    }
+    if let Some(path) = generate_keyfile {
+        let description = if std::path::Path::new(path).exists() {
+            "age identity (private key), replacing the existing file"
+        } else {
+            "age identity (private key)"
+        };
+        files.push(file(path.to_string(), "file", description, Some("0600")));
+        files.push(file(format!("{}.pub", path), "file", "age recipient (public key)", None));
+    }
+    if let Some(path) = piv.filter(|p| !std::path::Path::new(p).exists()) {
+        files.push(file(
+            path.to_string(),
+            "file",
+            "age plugin identity (references the YubiKey)",
+            Some("0600"),
+        ));
+        files.push(file(format!("{}.pub", path), "file", "age recipient (public key)", None));
+    }
+    files.push(file(
+        vault::storage::open()?.location(),
+        "file",
+        "encrypted vault",
+        None,
+    ));
+    if !vault::config_path()?.exists() {
+        files.push(file(
+            vault::config_path()?.display().to_string(),
+            "file",
+            "configuration",
+            None,
+        ));
+    }
+    files.push(file(
+        vault::audit_path()?.display().to_string(),
+        "file",
+        "audit log (one \"init\" entry)",
+        None,
+    ));
+
+    let mut planned = Config::default();
+    if let Some(path) = ssh_key {
+        planned.vault.auth_method = auth::SSH_AGENT_METHOD.to_string();
+        planned.vault.ssh_key = Some(path.to_string());
+    }
+    let config = toml::to_string_pretty(&planned)
+        .map_err(|e| AuthyError::Other(format!("Config serialize error: {}", e)))?;
+
+    if json {
+        let response = InitPlanResponse {
+            dry_run: true,
+            auth_mode: auth_mode.to_string(),
+            files,
+            config,
+        };
+        let out = serde_json::to_string_pretty(&response)
+            .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+        println!("{}", out);
+        return Ok(());
+    }
+
+    println!("Auth mode: {}", auth_mode);
+    println!("Would create:");
+    for f in &files {
+        let mode = f.mode.as_deref().map(|m| format!(" (mode {})", m)).unwrap_or_default();
+        println!("  {}  {}{}", f.path, f.description, mode);
+    }
+    println!();
+    println!("{}:", vault::config_path()?.display());
+    print!("{}", config);
+    eprintln!("Dry run: nothing was written.");
     Ok(())
 }
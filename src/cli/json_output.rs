@@ -1,19 +1,44 @@
+use std::collections::BTreeMap;
+
+use authy::policy::generation::GenerationRules;
+use authy::session::Capability;
+use authy::vault::secret::Provenance;
 use serde::Serialize;
 
 /// JSON response for `authy get --json`.
 #[derive(Serialize)]
 pub struct GetResponse {
     pub name: String,
+    /// Base64 when `encoding` is set.
     pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<&'static str>,
     pub version: u32,
     pub created: String,
     pub modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    /// Where the value came from; absent for secrets stored before
+    /// provenance was recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// JSON response for `authy get --json` when `--default` was used.
+#[derive(Serialize)]
+pub struct GetDefaultResponse {
+    pub name: String,
+    pub value: String,
+    pub default: bool,
 }
 
 /// JSON response for `authy list --json`.
 #[derive(Serialize)]
 pub struct ListResponse {
-    pub secrets: Vec<SecretListItem>,
+    /// Left out when the token's policy only reveals the count.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<Vec<SecretListItem>>,
+    pub count: usize,
 }
 
 #[derive(Serialize)]
@@ -22,6 +47,38 @@ pub struct SecretListItem {
     pub version: u32,
     pub created: String,
     pub modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    pub expired: bool,
+}
+
+/// JSON response for `authy expiring --json`.
+#[derive(Serialize)]
+pub struct ExpiringResponse {
+    /// Left out when the token's policy only reveals the count.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<Vec<ExpiringItem>>,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct ExpiringItem {
+    pub name: String,
+    pub expires: String,
+    pub expired: bool,
+}
+
+/// JSON response for `authy backup create|restore --json`.
+#[derive(Serialize)]
+pub struct BackupResponse {
+    pub path: String,
+    /// When the backup was taken.
+    pub created: String,
+    pub audit_entries: usize,
+    pub config: bool,
+    /// Replaced files kept with a `.pre-restore` suffix (restore only).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub kept: Vec<String>,
 }
 
 /// JSON response for `authy policy show --json`.
@@ -33,6 +90,21 @@ pub struct PolicyShowResponse {
     pub allow: Vec<String>,
     pub deny: Vec<String>,
     pub run_only: bool,
+    pub count_only: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extends: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow_write: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow_rotate: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow_delete: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_hours: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_days: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<GenerationRules>,
     pub created: String,
     pub modified: String,
 }
@@ -52,12 +124,78 @@ pub struct PolicyListItem {
     pub deny_count: usize,
 }
 
+/// JSON response for `authy policy history --json`.
+#[derive(Serialize)]
+pub struct PolicyHistoryResponse {
+    pub policy: String,
+    pub revisions: Vec<PolicyRevisionItem>,
+}
+
+#[derive(Serialize)]
+pub struct PolicyRevisionItem {
+    pub rev: u32,
+    pub replaced: String,
+    pub actor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub run_only: bool,
+    pub count_only: bool,
+}
+
 /// JSON response for `authy policy test --json`.
 #[derive(Serialize)]
 pub struct PolicyTestResponse {
     pub scope: String,
     pub secret: String,
     pub allowed: bool,
+    /// `allow`, `deny`, `window` or `default`.
+    pub rule_kind: &'static str,
+    /// The pattern that matched, or the missed window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+    /// Why access is denied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// JSON response for `authy policy test --stdin/--all --json`.
+#[derive(Serialize)]
+pub struct PolicyBulkTestResponse {
+    pub scope: String,
+    pub results: Vec<PolicyBulkTestItem>,
+    pub allowed: usize,
+    pub denied: usize,
+}
+
+#[derive(Serialize)]
+pub struct PolicyBulkTestItem {
+    pub secret: String,
+    pub allowed: bool,
+}
+
+/// JSON response for `authy policy coverage --json`.
+#[derive(Serialize)]
+pub struct PolicyCoverageResponse {
+    pub policy: String,
+    pub allowed: Vec<String>,
+    pub denied: Vec<PolicyCoverageDenied>,
+    pub dead_rules: Vec<PolicyDeadRule>,
+}
+
+#[derive(Serialize)]
+pub struct PolicyCoverageDenied {
+    pub secret: String,
+    /// The deny pattern that matched; absent when no allow pattern matches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deny_pattern: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PolicyDeadRule {
+    pub list: String,
+    pub pattern: String,
 }
 
 /// JSON response for `authy session create --json`.
@@ -68,6 +206,12 @@ pub struct SessionCreateResponse {
     pub scope: String,
     pub run_only: bool,
     pub expires: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    pub capabilities: Vec<Capability>,
+    pub sliding: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expires: Option<String>,
 }
 
 /// JSON response for `authy session list --json`.
@@ -86,6 +230,12 @@ pub struct SessionListItem {
     pub label: Option<String>,
     pub created: String,
     pub expires: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    pub capabilities: Vec<Capability>,
+    pub sliding: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_expires: Option<String>,
 }
 
 /// JSON response for `authy audit show --json`.
@@ -107,3 +257,111 @@ pub struct AuditEntryItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
 }
+
+/// JSON response for `authy audit stats --json`.
+#[derive(Serialize)]
+pub struct AuditStatsResponse {
+    pub total: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<String>,
+    pub outcomes: BTreeMap<String, usize>,
+    pub actors: Vec<AuditActorStats>,
+    pub secrets: Vec<AuditSecretStats>,
+    pub anomalies: Vec<AuditAnomaly>,
+}
+
+#[derive(Serialize)]
+pub struct AuditActorStats {
+    pub actor: String,
+    pub total: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    pub operations: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+pub struct AuditSecretStats {
+    pub secret: String,
+    pub reads: usize,
+    pub other: usize,
+    pub denied: usize,
+    pub first_access: String,
+    pub last_access: String,
+}
+
+#[derive(Serialize)]
+pub struct AuditAnomaly {
+    pub kind: String,
+    pub subject: String,
+    pub count: usize,
+    pub first: String,
+    pub last: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+}
+
+/// JSON response for `authy lint-values --json`.
+#[derive(Serialize)]
+pub struct LintValuesResponse {
+    pub checked: usize,
+    pub findings: Vec<LintFinding>,
+}
+
+#[derive(Serialize)]
+pub struct LintFinding {
+    pub secret: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected: Option<String>,
+    pub message: String,
+}
+
+/// JSON response for `authy status --json`.
+#[derive(Serialize)]
+pub struct StatusResponse {
+    pub vault_path: String,
+    pub created: String,
+    pub modified: String,
+    pub secrets: usize,
+    pub policies: usize,
+    pub active_sessions: usize,
+    pub last_rekey: Option<VaultEventItem>,
+    pub last_policy_change: Option<VaultEventItem>,
+    pub last_session_change: Option<VaultEventItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<VaultEventItem>>,
+}
+
+#[derive(Serialize)]
+pub struct VaultEventItem {
+    pub timestamp: String,
+    pub kind: String,
+    pub actor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// JSON response for `authy init --dry-run --json`.
+#[derive(Serialize)]
+pub struct InitPlanResponse {
+    pub dry_run: bool,
+    pub auth_mode: String,
+    pub files: Vec<InitPlanFile>,
+    pub config: String,
+}
+
+#[derive(Serialize)]
+pub struct InitPlanFile {
+    pub path: String,
+    pub kind: String,
+    pub description: String,
+    /// Explicit permission bits, if set; otherwise the process umask applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
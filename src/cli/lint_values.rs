@@ -0,0 +1,72 @@
+use authy::audit;
+use authy::auth;
+use crate::cli::json_output::{LintFinding, LintValuesResponse};
+use authy::error::{AuthyError, Result};
+use authy::vault::{self, lint};
+use authy::vault::secret::ValueEncoding;
+
+/// Check every stored value against the credential format its name
+/// suggests. Values are read but never printed.
+pub fn run(strict: bool, json: bool) -> Result<()> {
+    // Reads every value, so a scoped token can't run it
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let vault = vault::load_vault(&key)?;
+
+    let text: Vec<(&String, &String)> = vault
+        .secrets
+        .iter()
+        .filter(|(_, entry)| entry.encoding == ValueEncoding::Text)
+        .map(|(name, entry)| (name, &entry.value))
+        .collect();
+    let findings: Vec<lint::Finding> = text
+        .iter()
+        .filter_map(|(name, value)| lint::lint(name, value))
+        .collect();
+
+    if json {
+        let response = LintValuesResponse {
+            checked: text.len(),
+            findings: findings
+                .iter()
+                .map(|f| LintFinding {
+                    secret: f.name.clone(),
+                    kind: f.kind.to_string(),
+                    expected: f.expected.map(|e| e.as_str().to_string()),
+                    detected: f.detected.map(|d| d.as_str().to_string()),
+                    message: f.message.clone(),
+                })
+                .collect(),
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+    } else if findings.is_empty() {
+        eprintln!("Checked {} values, nothing flagged.", text.len());
+    } else {
+        for finding in &findings {
+            println!("{}: {}: {}", finding.name, finding.kind, finding.message);
+        }
+        eprintln!("Checked {} values, {} flagged.", text.len(), findings.len());
+    }
+
+    let audit_key = audit::derive_audit_key(&audit::key_material(&key));
+    audit::log_event(
+        &vault::audit_path()?,
+        "lint_values",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&format!("checked={}, findings={}", text.len(), findings.len())),
+        &audit_key,
+    )?;
+
+    if strict && !findings.is_empty() {
+        return Err(AuthyError::Other(format!(
+            "{} value(s) flagged by lint-values",
+            findings.len()
+        )));
+    }
+    Ok(())
+}
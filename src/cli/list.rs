@@ -1,30 +1,54 @@
 use authy::audit;
 use authy::auth;
 use crate::cli::json_output::{ListResponse, SecretListItem};
+use crate::cli::table::{self, Column, Table};
 use authy::error::{AuthyError, Result};
+use authy::session::Capability;
 use authy::vault;
 
-pub fn run(scope: Option<&str>, json: bool) -> Result<()> {
-    let (key, auth_ctx) = auth::resolve_auth(false)?;
+const COLUMNS: [Column; 5] = [
+    Column::new("name", true, true),
+    Column::new("version", false, false),
+    Column::new("created", false, false),
+    Column::new("modified", false, false),
+    Column::new("expires", false, false),
+];
+
+pub fn run(scope: Option<&str>, columns: &[String], json: bool) -> Result<()> {
+    let mut table = Table::new(&COLUMNS, columns)?;
+
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::List)?;
     let vault = vault::load_vault(&key)?;
 
-    let effective_scope = scope
-        .map(|s| s.to_string())
-        .or_else(|| auth_ctx.scope.clone());
+    let effective_scope = super::common::listing_scope(scope, &auth_ctx)?;
 
     let names: Vec<&str> = vault.secrets.keys().map(|s| s.as_str()).collect();
 
+    let mut count_only = false;
     let filtered = if let Some(ref scope_name) = effective_scope {
-        let policy = vault
-            .policies
-            .get(scope_name)
-            .ok_or_else(|| AuthyError::PolicyNotFound(scope_name.clone()))?;
+        let policy = vault.effective_policy(scope_name)?;
+        count_only = policy.count_only && auth_ctx.scope.is_some();
         policy.filter_secrets(&names)?
     } else {
         names
     };
 
-    if json {
+    if count_only {
+        if json {
+            let response = ListResponse {
+                secrets: None,
+                count: filtered.len(),
+            };
+            println!(
+                "{}",
+                serde_json::to_string(&response)
+                    .map_err(|e| AuthyError::Serialization(e.to_string()))?
+            );
+        } else {
+            println!("{}", filtered.len());
+            eprintln!("Secret names are hidden by this token's policy.");
+        }
+    } else if json {
         let secrets: Vec<SecretListItem> = filtered
             .iter()
             .filter_map(|name| {
@@ -33,10 +57,15 @@ pub fn run(scope: Option<&str>, json: bool) -> Result<()> {
                     version: entry.metadata.version,
                     created: entry.metadata.created_at.to_rfc3339(),
                     modified: entry.metadata.modified_at.to_rfc3339(),
+                    expires: entry.metadata.expires_at.map(|at| at.to_rfc3339()),
+                    expired: entry.metadata.is_expired(),
                 })
             })
             .collect();
-        let response = ListResponse { secrets };
+        let response = ListResponse {
+            count: secrets.len(),
+            secrets: Some(secrets),
+        };
         println!(
             "{}",
             serde_json::to_string(&response)
@@ -44,16 +73,51 @@ pub fn run(scope: Option<&str>, json: bool) -> Result<()> {
         );
     } else {
         for name in &filtered {
-            println!("{}", name);
+            if let Some(entry) = vault.secrets.get(*name) {
+                let metadata = &entry.metadata;
+                table.push(vec![
+                    name.to_string(),
+                    metadata.version.to_string(),
+                    table::timestamp(&metadata.created_at),
+                    table::timestamp(&metadata.modified_at),
+                    match metadata.expires_at {
+                        Some(at) if metadata.is_expired() => {
+                            format!("{} (expired)", table::timestamp(&at))
+                        }
+                        Some(at) => table::timestamp(&at),
+                        None => "-".to_string(),
+                    },
+                ]);
+            }
+        }
+        table.print();
+        // Names stay bare on stdout for scripts; the flag goes to stderr
+        let expired: Vec<&str> = filtered
+            .iter()
+            .copied()
+            .filter(|name| vault.secrets.get(*name).is_some_and(|e| e.metadata.is_expired()))
+            .collect();
+        if !expired.is_empty() {
+            eprintln!(
+                "Expired ({}): {}. See `authy expiring`.",
+                expired.len(),
+                expired.join(", ")
+            );
         }
     }
 
     // Audit log
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
-    let detail = effective_scope.as_deref().map(|s| format!("scope={}", s));
+    let detail = effective_scope.as_deref().map(|s| {
+        if count_only {
+            format!("scope={}, count-only", s)
+        } else {
+            format!("scope={}", s)
+        }
+    });
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "list",
         None,
         &auth_ctx.actor_name(),
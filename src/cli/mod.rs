@@ -1,19 +1,29 @@
 pub mod admin;
+pub mod agent;
 pub mod alias;
+pub mod approver;
 pub mod audit;
+pub mod backup;
+pub mod canary;
 pub mod common;
 pub mod config;
 pub mod env;
+pub mod expiring;
 pub mod export;
+pub mod generate;
 pub mod get;
 pub mod hook;
 pub mod import;
 pub mod import_sources;
 pub mod init;
 pub mod json_output;
+pub mod lint_values;
 pub mod list;
 pub mod policy;
 pub mod project_info;
+pub mod protect;
+pub mod push;
+pub mod push_targets;
 pub mod rekey;
 pub mod remove;
 pub mod resolve;
@@ -21,8 +31,14 @@ pub mod rotate;
 pub mod run;
 pub mod serve;
 pub mod session;
+pub mod status;
 pub mod store;
+pub mod table;
 
+use authy::policy::generation::{CharClass, GenerationRules};
+use authy::policy::window::{DaySet, HourWindow};
+use authy::policy::Policy;
+use authy::session::Capability;
 use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
@@ -32,6 +48,14 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Print a per-phase timing breakdown to stderr
+    #[arg(long, global = true)]
+    pub timings: bool,
+
+    /// When to use colors in output and the admin TUI (auto honors NO_COLOR)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -43,9 +67,20 @@ pub enum Commands {
         /// Generate a keyfile at this path instead of using a passphrase
         #[arg(long)]
         generate_keyfile: Option<String>,
+        /// Use a YubiKey (via age-plugin-yubikey) as the vault key, enrolling one
+        /// and writing its identity to this path if the file doesn't exist
+        #[arg(long, value_name = "PATH", conflicts_with = "generate_keyfile")]
+        piv: Option<String>,
+        /// Unlock the vault through the SSH agent with this public key
+        /// (ssh-ed25519 or ssh-rsa), instead of a passphrase or keyfile
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["generate_keyfile", "piv"])]
+        ssh_key: Option<String>,
         /// Set vault passphrase non-interactively
         #[arg(long, env = "AUTHY_PASSPHRASE")]
         passphrase: Option<String>,
+        /// Report what would be created without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Store a secret (reads value from stdin)
@@ -55,6 +90,33 @@ pub enum Commands {
         /// Overwrite if exists
         #[arg(long)]
         force: bool,
+        /// Store this file's bytes as a binary secret instead of reading stdin
+        #[arg(long)]
+        file: Option<String>,
+        /// Mark the secret as due for rotation after this long (e.g. 90d)
+        #[arg(long)]
+        expires: Option<String>,
+        /// Check the write patterns of this policy first
+        #[arg(long)]
+        scope: Option<String>,
+        /// Store an empty, placeholder or unexpanded-variable value anyway
+        #[arg(long)]
+        allow_weak: bool,
+    },
+
+    /// Store a randomly generated secret (the value is not printed)
+    Generate {
+        /// Secret name
+        name: String,
+        /// Apply this policy's generation rules
+        #[arg(long)]
+        scope: Option<String>,
+        /// Length of the value (raised to the policy's minimum)
+        #[arg(long, default_value_t = authy::policy::generation::DEFAULT_LENGTH)]
+        length: usize,
+        /// Overwrite if exists
+        #[arg(long)]
+        force: bool,
     },
 
     /// Get a secret value
@@ -64,6 +126,18 @@ pub enum Commands {
         /// Scope to enforce policy against
         #[arg(long)]
         scope: Option<String>,
+        /// Secret to try if the previous name doesn't exist (repeatable, tried in order)
+        #[arg(long)]
+        fallback: Vec<String>,
+        /// Value to print if no name in the chain exists
+        #[arg(long)]
+        default: Option<String>,
+        /// Write the value's raw bytes to this file (mode 0600) instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+        /// Return the value even if the secret has expired
+        #[arg(long)]
+        allow_expired: bool,
     },
 
     /// List secret names
@@ -71,18 +145,43 @@ pub enum Commands {
         /// Scope to filter by policy
         #[arg(long)]
         scope: Option<String>,
+        /// Columns to show, comma-separated (e.g. name,version,expires)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+    },
+
+    /// List secrets that have expired or expire soon
+    Expiring {
+        /// Include secrets expiring within this long (e.g. 14d)
+        #[arg(long, default_value = "14d")]
+        within: String,
+        /// Scope to filter by policy
+        #[arg(long)]
+        scope: Option<String>,
     },
 
     /// Remove a secret
     Remove {
         /// Secret name
         name: String,
+        /// Check the delete patterns of this policy first
+        #[arg(long)]
+        scope: Option<String>,
+        /// Approver keyfile, required when the secret is protected
+        #[arg(long)]
+        approve_with: Option<String>,
     },
 
     /// Rotate a secret (reads new value from stdin)
     Rotate {
         /// Secret name
         name: String,
+        /// Check the rotate patterns of this policy first
+        #[arg(long)]
+        scope: Option<String>,
+        /// Store an empty, placeholder or unexpanded-variable value anyway
+        #[arg(long)]
+        allow_weak: bool,
     },
 
     /// Manage access policies
@@ -111,6 +210,10 @@ pub enum Commands {
         /// Prefix for env var names
         #[arg(long)]
         prefix: Option<String>,
+        /// After the command exits, report its wall time, exit code and the
+        /// secrets injected: to stderr (default), the audit log, or both
+        #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "stderr")]
+        summary: Option<RunSummary>,
         /// Command and arguments to run
         #[arg(last = true, required = true)]
         command: Vec<String>,
@@ -130,7 +233,7 @@ pub enum Commands {
         /// Prefix for env var names
         #[arg(long)]
         prefix: Option<String>,
-        /// Output format: shell, dotenv, json
+        /// Output format: shell, dotenv, json, json-nested
         #[arg(long, default_value = "shell")]
         format: String,
         /// Omit 'export' keyword in shell format
@@ -140,7 +243,7 @@ pub enum Commands {
 
     /// Import secrets from a .env file or external source
     Import {
-        /// Source file (.env, SOPS encrypted file). Not required for 1password, pass, or vault.
+        /// Source file (.env, SOPS, encrypted bundle, or 1Password .1pux export). Not required for 1password, pass, vault, or env.
         file: Option<String>,
         /// External source type
         #[arg(long, value_enum)]
@@ -157,6 +260,18 @@ pub enum Commands {
         /// HashiCorp Vault mount point (default: "secret")
         #[arg(long, default_value = "secret")]
         mount: String,
+        /// Glob filter on variable names (--from env, e.g. "STRIPE_*")
+        #[arg(long)]
+        filter: Option<String>,
+        /// Import every variable, PATH and HOME included (--from env, instead of --filter)
+        #[arg(long, conflicts_with = "filter")]
+        all: bool,
+        /// Read the environment of this process instead of our own (--from env, Linux)
+        #[arg(long)]
+        pid: Option<u32>,
+        /// age identity file to decrypt with (--from encrypted; default: AUTHY_KEYFILE)
+        #[arg(long)]
+        identity: Option<String>,
         /// Keep original names (don't transform to lower-kebab-case)
         #[arg(long)]
         keep_names: bool,
@@ -169,11 +284,14 @@ pub enum Commands {
         /// Preview changes without storing
         #[arg(long)]
         dry_run: bool,
+        /// Don't ask for confirmation before importing (interactive terminals only)
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 
-    /// Export secrets as .env or JSON
+    /// Export secrets as .env, JSON, Docker Compose secrets, SOPS, or an age-encrypted bundle
     Export {
-        /// Output format: env, json
+        /// Output format: env, json, metadata-only, compose, sops, encrypted
         #[arg(long, default_value = "env")]
         format: String,
         /// Scope (policy name) to filter secrets
@@ -188,6 +306,43 @@ pub enum Commands {
         /// Prefix for env var names
         #[arg(long)]
         prefix: Option<String>,
+        /// Only export secrets carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only export secrets whose names match this glob pattern (e.g. "db-*")
+        #[arg(long = "match")]
+        name_match: Option<String>,
+        /// Directory for per-secret files (--format compose)
+        #[arg(long)]
+        secrets_dir: Option<String>,
+        /// age recipient public key (--format sops or encrypted, repeatable)
+        #[arg(long, visible_alias = "recipient")]
+        age_recipient: Vec<String>,
+    },
+
+    /// Push secrets to an external store (HashiCorp Vault KV v2)
+    Push {
+        /// Target store type
+        #[arg(long, value_enum)]
+        to: PushTarget,
+        /// HashiCorp Vault mount point (default: "secret")
+        #[arg(long, default_value = "secret")]
+        mount: String,
+        /// Secret path within the mount (e.g. "myapp"), or the password-store directory (--to pass)
+        #[arg(long)]
+        path: Option<String>,
+        /// GPG key to encrypt to (--to pass, repeatable; default: the store's .gpg-id)
+        #[arg(long = "gpg-id")]
+        gpg_id: Vec<String>,
+        /// Scope (policy name) to filter secrets
+        #[arg(long)]
+        scope: Option<String>,
+        /// Only push secrets whose names match this glob pattern
+        #[arg(long = "match")]
+        name_match: Option<String>,
+        /// Preview which secrets would be pushed
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// View and verify audit logs
@@ -202,14 +357,24 @@ pub enum Commands {
         command: ConfigCommands,
     },
 
+    /// Show a vault summary: counts, last rekey, last policy and session changes
+    Status {
+        /// Also list the structural events recorded in the vault
+        #[arg(long)]
+        events: bool,
+    },
+
     /// Show project config from .authy.toml
     ProjectInfo {
-        /// Show a specific field (scope, keyfile, vault, uppercase, replace-dash, prefix, dir, aliases)
+        /// Show a specific field (scope, keyfile, vault, uppercase, replace-dash, prefix, enforce-prefix, dir, aliases)
         #[arg(long)]
         field: Option<String>,
         /// Start directory for .authy.toml discovery
         #[arg(long)]
         dir: Option<String>,
+        /// Reuse cached discovery results while directory mtimes are unchanged
+        #[arg(long)]
+        cached: bool,
     },
 
     /// Generate shell aliases for tools
@@ -222,9 +387,13 @@ pub enum Commands {
         /// Read scope, naming, and aliases from .authy.toml
         #[arg(long)]
         from_project: bool,
-        /// Output unalias commands for the project in AUTHY_PROJECT_DIR
+        /// Output unalias commands for the aliases generated earlier for the
+        /// project in AUTHY_PROJECT_DIR (or the current directory)
         #[arg(long)]
         cleanup: bool,
+        /// List aliases currently generated for each project
+        #[arg(long, conflicts_with_all = ["cleanup", "from_project"])]
+        list: bool,
         /// Tool names to alias
         #[arg(trailing_var_arg = true)]
         tools: Vec<String>,
@@ -234,6 +403,9 @@ pub enum Commands {
     Hook {
         /// Shell to generate hook for (bash, zsh, fish)
         shell: String,
+        /// Use cached project discovery and skip work when the directory is unchanged
+        #[arg(long)]
+        cached: bool,
     },
 
     /// Resolve <authy:key-name> placeholders in a file
@@ -259,6 +431,56 @@ pub enum Commands {
         /// Re-encrypt with an existing keyfile
         #[arg(long)]
         new_keyfile: Option<String>,
+        /// Approver keyfile, required when the vault holds protected items
+        #[arg(long)]
+        approve_with: Option<String>,
+    },
+
+    /// Mark a secret or policy as protected, so removing it needs an approver
+    Protect {
+        /// Secret name (or policy name with --policy)
+        name: String,
+        /// Protect the policy called NAME instead of a secret
+        #[arg(long)]
+        policy: bool,
+        /// Lift the protection (needs --approve-with)
+        #[arg(long)]
+        off: bool,
+        /// Approver keyfile, required with --off
+        #[arg(long)]
+        approve_with: Option<String>,
+    },
+
+    /// Mark a secret as a canary (honeytoken): any read of its value raises an alert
+    Canary {
+        /// Secret name
+        #[arg(required_unless_present = "list")]
+        name: Option<String>,
+        /// Clear the canary mark
+        #[arg(long)]
+        off: bool,
+        /// List the secrets marked as canaries
+        #[arg(long, conflicts_with_all = ["name", "off"])]
+        list: bool,
+    },
+
+    /// Check stored values against known credential formats and their names (master key only)
+    LintValues {
+        /// Exit with an error if anything is flagged
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Manage the keyfiles that approve changes to protected items
+    Approver {
+        #[command(subcommand)]
+        command: ApproverCommands,
+    },
+
+    /// Back up the vault, audit log and config to a passphrase-encrypted file, or restore one
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommands,
     },
 
     /// Start a server (MCP, etc.)
@@ -266,6 +488,25 @@ pub enum Commands {
         /// Run as MCP server (JSON-RPC over stdio)
         #[arg(long)]
         mcp: bool,
+        /// Cache secret reads in memory for this long (e.g. "30s"); off by default
+        #[arg(long)]
+        cache_ttl: Option<String>,
+    },
+
+    /// Serve scoped secrets to editor plugins over a line-based stdio protocol
+    Agent {
+        /// Speak the JSON-lines protocol on stdin/stdout
+        #[arg(long)]
+        stdio: bool,
+        /// Scope for secret access (optional if .authy.toml exists or a token is used)
+        #[arg(long)]
+        scope: Option<String>,
+        /// Start directory for .authy.toml discovery (default: cwd)
+        #[arg(long)]
+        dir: Option<String>,
+        /// Cache secret reads in memory for this long (e.g. "30s"); off by default
+        #[arg(long)]
+        cache_ttl: Option<String>,
     },
 
     /// Launch admin TUI (interactive vault management)
@@ -282,8 +523,8 @@ pub enum PolicyCommands {
     Create {
         /// Policy / scope name
         name: String,
-        /// Allow glob patterns
-        #[arg(long, required = true, num_args = 1..)]
+        /// Allow glob patterns (default: "<enforce_prefix>*" from .authy.toml)
+        #[arg(long, num_args = 1..)]
         allow: Vec<String>,
         /// Deny glob patterns
         #[arg(long, num_args = 1..)]
@@ -294,10 +535,25 @@ pub enum PolicyCommands {
         /// Restrict to run-only mode (secrets can only be injected via `authy run`)
         #[arg(long)]
         run_only: bool,
+        /// Show session tokens only the number of secrets in scope, not their names
+        #[arg(long)]
+        count_only: bool,
+        /// Policies whose rules this one inherits
+        #[arg(long, num_args = 1..)]
+        extends: Vec<String>,
+        #[command(flatten)]
+        operations: OperationArgs,
+        #[command(flatten)]
+        window: WindowArgs,
+        #[command(flatten)]
+        generation: GenerationArgs,
     },
     /// Show a policy
     Show {
         name: String,
+        /// Show the rules in effect, including those inherited through --extends
+        #[arg(long)]
+        effective: bool,
     },
     /// Update an existing policy
     Update {
@@ -314,20 +570,74 @@ pub enum PolicyCommands {
         /// Enable run-only mode (secrets can only be injected via `authy run`)
         #[arg(long)]
         run_only: Option<bool>,
+        /// Show session tokens only the number of secrets in scope, not their names
+        #[arg(long)]
+        count_only: Option<bool>,
+        /// New parent policies (replaces existing)
+        #[arg(long, num_args = 1..)]
+        extends: Option<Vec<String>>,
+        /// Stop inheriting from other policies
+        #[arg(long, conflicts_with = "extends")]
+        clear_extends: bool,
+        #[command(flatten)]
+        operations: OperationArgs,
+        #[command(flatten)]
+        window: WindowArgs,
+        /// Remove the policy's valid hours and days
+        #[arg(long, conflicts_with_all = ["valid_hours", "valid_days"])]
+        clear_window: bool,
+        #[command(flatten)]
+        generation: GenerationArgs,
+        /// Remove the policy's generation rules
+        #[arg(long, conflicts_with_all = ["gen_min_length", "gen_require", "gen_forbid"])]
+        clear_gen: bool,
+    },
+    /// Show the previous versions of a policy
+    History {
+        name: String,
+    },
+    /// Restore a previous version of a policy
+    Rollback {
+        name: String,
+        /// Revision number, from `authy policy history`
+        #[arg(long)]
+        to: u32,
     },
     /// List all policies
-    List,
+    List {
+        /// Columns to show, comma-separated (e.g. name,run_only)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+    },
     /// Remove a policy
     Remove {
         name: String,
+        /// Approver keyfile, required when the policy is protected
+        #[arg(long)]
+        approve_with: Option<String>,
     },
-    /// Test a policy against a secret name
+    /// Test a policy against a secret name (or many with --stdin / --all)
+    #[command(group(clap::ArgGroup::new("target").required(true).args(["name", "stdin", "all"])))]
     Test {
         /// Policy name
         #[arg(long)]
         scope: String,
         /// Secret name to test
+        name: Option<String>,
+        /// Read newline-separated secret names from stdin
+        #[arg(long)]
+        stdin: bool,
+        /// Test every secret currently in the vault
+        #[arg(long)]
+        all: bool,
+    },
+    /// Report which secrets a policy allows and denies, and which of its patterns match nothing
+    Coverage {
+        /// Policy name
         name: String,
+        /// Exit with an error if any pattern matches nothing
+        #[arg(long)]
+        strict: bool,
     },
 }
 
@@ -347,13 +657,73 @@ pub enum SessionCommands {
         /// Restrict to run-only mode (secrets can only be injected via `authy run`)
         #[arg(long)]
         run_only: bool,
+        /// Non-secret env var for `authy run` to set with this token (KEY=VALUE, repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// What the token may do, comma-separated (read, write, rotate, list; default read,list)
+        #[arg(long, value_delimiter = ',')]
+        can: Vec<Capability>,
+        /// Extend the expiry by the TTL each time the token is used
+        #[arg(long)]
+        sliding: bool,
+        /// Hard limit on the session's lifetime, however it is extended (e.g. "24h")
+        #[arg(long, default_value_if("sliding", "true", Some("24h")))]
+        max_lifetime: Option<String>,
     },
     /// List active sessions
-    List,
-    /// Revoke a session by ID
+    List {
+        /// Columns to show, comma-separated (e.g. id,scope,env)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+    },
+    /// Revoke a session by ID, or every active session matching filters
+    #[command(group(
+        clap::ArgGroup::new("target")
+            .required(true)
+            .multiple(true)
+            .args(["id", "label", "scope", "older_than"])
+    ))]
     Revoke {
         /// Session ID to revoke
+        #[arg(conflicts_with_all = ["label", "scope", "older_than"])]
+        id: Option<String>,
+        /// Revoke active sessions with this label
+        #[arg(long)]
+        label: Option<String>,
+        /// Revoke active sessions for this scope (policy name)
+        #[arg(long)]
+        scope: Option<String>,
+        /// Revoke active sessions created more than this long ago (e.g. "7d", "12h")
+        #[arg(long)]
+        older_than: Option<String>,
+    },
+    /// Extend a session's expiry
+    Refresh {
+        /// Session ID to refresh
+        id: String,
+        /// New time to live from now (default: the session's TTL)
+        #[arg(long)]
+        ttl: Option<String>,
+    },
+    /// Remove sessions that expired or were revoked longer ago than the retention window
+    Prune {
+        /// Retention window (default: `[sessions] retention` in authy.toml, 30d)
+        #[arg(long)]
+        older_than: Option<String>,
+        /// Only report how many sessions would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Set or clear a session's label
+    Label {
+        /// Session ID to label
         id: String,
+        /// New label (e.g. "ci-runner-3")
+        #[arg(required_unless_present = "clear", conflicts_with = "clear")]
+        label: Option<String>,
+        /// Remove the label
+        #[arg(long)]
+        clear: bool,
     },
     /// Revoke all sessions
     RevokeAll,
@@ -366,24 +736,270 @@ pub enum AuditCommands {
         /// Number of entries to show (0 = all)
         #[arg(long, short, default_value = "20")]
         count: usize,
+        /// Render timestamps in the local time zone
+        #[arg(long)]
+        local: bool,
+        /// Render timestamps relative to now (e.g. "5m ago")
+        #[arg(long)]
+        relative: bool,
+        /// Render timestamps at a fixed UTC offset (e.g. "+05:30", "-0800", "UTC")
+        #[arg(long, conflicts_with = "local")]
+        tz: Option<String>,
+        /// Show the failed-authentication (security) log instead of the audit log
+        #[arg(long, visible_alias = "security")]
+        auth: bool,
+        /// Map hashed secret names back to names (requires master credentials)
+        #[arg(long, conflicts_with = "auth")]
+        resolve_names: bool,
+        /// Columns to show, comma-separated (e.g. time,operation,secret)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// Only entries at or after this time (YYYY-MM-DD, RFC 3339, or a duration ago like 24h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries at or before this time (same forms as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Only this operation (e.g. get, store, policy.update)
+        #[arg(long)]
+        operation: Option<String>,
+        /// Only actors containing this text (e.g. master, token, token(<id>))
+        #[arg(long)]
+        actor: Option<String>,
+        /// Only this secret, or secrets matching a glob (e.g. "db-*")
+        #[arg(long)]
+        secret: Option<String>,
+        /// Only this outcome (e.g. success, denied, alert)
+        #[arg(long)]
+        outcome: Option<String>,
+        /// Print raw entries as CSV or JSON Lines instead of a table
+        #[arg(long, value_enum, conflicts_with = "columns")]
+        format: Option<AuditFormat>,
     },
     /// Verify audit log integrity
-    Verify,
+    Verify {
+        /// Verify the failed-authentication (security) log (uses its local key, no credentials needed)
+        #[arg(long, visible_alias = "security", conflicts_with = "offline")]
+        auth: bool,
+        /// Verify copies of the audit log and vault without ~/.authy (needs --key-material)
+        #[arg(long, requires = "key_material")]
+        offline: bool,
+        /// File holding the vault's age keyfile identity or passphrase
+        #[arg(long, requires = "offline")]
+        key_material: Option<String>,
+        /// Audit log copy to verify (with --offline)
+        #[arg(long, requires = "offline")]
+        log: Option<String>,
+        /// Vault snapshot (vault.age) to verify (with --offline)
+        #[arg(long, requires = "offline")]
+        vault: Option<String>,
+        /// Also check the signed checkpoints in the log
+        #[arg(long, conflicts_with = "auth")]
+        checkpoints: bool,
+        /// Checkpoint public key, or a file holding it (default: the
+        /// configured checkpoint_key's .pub file)
+        #[arg(long, requires = "checkpoints")]
+        checkpoint_pubkey: Option<String>,
+    },
+    /// Create an ed25519 key for signing audit checkpoints (and <path>.pub)
+    CheckpointKey {
+        /// Where to write the signing key; keep it outside ~/.authy
+        path: String,
+        /// Replace an existing key
+        #[arg(long)]
+        force: bool,
+    },
     /// Export audit log as JSON array
     Export,
+    /// Summarize the log: activity per actor and secret, failure rates, anomalies
+    Stats {
+        /// Only entries at or after this time (YYYY-MM-DD, RFC 3339, or a duration ago like 24h)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries at or before this time (same forms as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Number of secrets to list (0 = all)
+        #[arg(long, default_value = "10")]
+        top: usize,
+        /// Flag actors denied at least this many times (0 = never)
+        #[arg(long, default_value = "3")]
+        denials: usize,
+        /// Map hashed secret names back to names (requires master credentials)
+        #[arg(long)]
+        resolve_names: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// Write an encrypted backup (passphrase from AUTHY_BACKUP_PASSPHRASE or a prompt)
+    Create {
+        /// Backup file to write
+        path: String,
+    },
+    /// Restore a backup; the vault in it must open with your current credentials
+    Restore {
+        /// Backup file to read
+        path: String,
+        /// Replace an existing vault (the replaced files are kept as *.pre-restore)
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ApproverCommands {
+    /// Register an approver: an age recipient, or a .pub or keyfile to read it from
+    Add {
+        recipient: String,
+    },
+    /// List registered approvers
+    List,
+    /// Unregister an approver (needs another approver's, or its own, keyfile)
+    Remove {
+        recipient: String,
+        /// Approver keyfile authorising the removal
+        #[arg(long)]
+        approve_with: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Show current configuration
     Show,
+    /// Set a config key in authy.toml, e.g. `authy config set auth.keyring true`
+    Set {
+        /// Dotted key: section.key
+        key: String,
+        /// New value (read as TOML, else as a plain string)
+        value: String,
+    },
+}
+
+/// Rules for values generated in a policy's scope (`authy generate --scope`).
+#[derive(clap::Args, Debug)]
+pub struct GenerationArgs {
+    /// Minimum length of generated values
+    #[arg(long)]
+    pub gen_min_length: Option<usize>,
+    /// Character classes generated values must contain (replaces existing)
+    #[arg(long, value_enum, num_args = 1..)]
+    pub gen_require: Option<Vec<CharClass>>,
+    /// Substrings generated values must not contain (replaces existing)
+    #[arg(long, num_args = 1..)]
+    pub gen_forbid: Option<Vec<String>>,
+}
+
+impl GenerationArgs {
+    /// Apply the given flags on top of `rules`; `None` if nothing is left.
+    pub fn apply(&self, rules: Option<GenerationRules>) -> Option<GenerationRules> {
+        let mut rules = rules.unwrap_or_default();
+        if let Some(min_length) = self.gen_min_length {
+            rules.min_length = min_length;
+        }
+        if let Some(ref require) = self.gen_require {
+            rules.require = require.clone();
+        }
+        if let Some(ref forbid) = self.gen_forbid {
+            rules.forbid = forbid.clone();
+        }
+        (!rules.is_empty()).then_some(rules)
+    }
+}
+
+/// Which secrets a policy lets tokens (or `--scope`) store, rotate and
+/// remove. Without them, those operations follow the allow patterns.
+#[derive(clap::Args, Debug)]
+pub struct OperationArgs {
+    /// Patterns of secrets that may be stored (replaces existing; none = use --allow)
+    #[arg(long, num_args = 0..)]
+    pub allow_write: Option<Vec<String>>,
+    /// Patterns of secrets that may be rotated (replaces existing; none = use --allow)
+    #[arg(long, num_args = 0..)]
+    pub allow_rotate: Option<Vec<String>>,
+    /// Patterns of secrets that may be removed (replaces existing; none = use --allow)
+    #[arg(long, num_args = 0..)]
+    pub allow_delete: Option<Vec<String>>,
+}
+
+impl OperationArgs {
+    /// Set the pattern lists given on the command line.
+    pub fn apply(&self, policy: &mut Policy) {
+        if let Some(ref patterns) = self.allow_write {
+            policy.allow_write = patterns.clone();
+        }
+        if let Some(ref patterns) = self.allow_rotate {
+            policy.allow_rotate = patterns.clone();
+        }
+        if let Some(ref patterns) = self.allow_delete {
+            policy.allow_delete = patterns.clone();
+        }
+    }
+}
+
+/// When a policy grants access, in local time.
+#[derive(clap::Args, Debug)]
+pub struct WindowArgs {
+    /// Only grant access between these times each day (e.g. 09:00-17:00)
+    #[arg(long, value_parser = HourWindow::parse)]
+    pub valid_hours: Option<HourWindow>,
+    /// Only grant access on these days (e.g. mon-fri, or sat,sun)
+    #[arg(long, value_parser = DaySet::parse)]
+    pub valid_days: Option<DaySet>,
+}
+
+impl WindowArgs {
+    /// Set the window fields given on the command line.
+    pub fn apply(&self, policy: &mut Policy) {
+        if self.valid_hours.is_some() {
+            policy.valid_hours = self.valid_hours;
+        }
+        if self.valid_days.is_some() {
+            policy.valid_days = self.valid_days;
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color terminals, unless NO_COLOR is set
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Turn colored CLI output on or off, and return whether the TUI should
+    /// use colors. `auto` leaves stdout and stderr to terminal detection
+    /// (and `CLICOLOR`), and turns everything off when `NO_COLOR` is set to
+    /// a non-empty value.
+    pub fn apply(self) -> bool {
+        let enabled = match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+                    false
+                } else {
+                    return true;
+                }
+            }
+        };
+        console::set_colors_enabled(enabled);
+        console::set_colors_enabled_stderr(enabled);
+        enabled
+    }
 }
 
 #[derive(ValueEnum, Clone, Debug)]
 pub enum ImportSource {
     /// .env file (explicit)
     Dotenv,
-    /// 1Password via `op` CLI
+    /// 1Password via `op` CLI, or a .1pux export file
     #[value(name = "1password")]
     OnePassword,
     /// pass (password-store) via GPG
@@ -392,4 +1008,34 @@ pub enum ImportSource {
     Sops,
     /// HashiCorp Vault KV
     Vault,
+    /// Environment of this process or a running process (/proc/<pid>/environ)
+    Env,
+    /// age-encrypted bundle from `authy export --format encrypted`
+    Encrypted,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunSummary {
+    /// Print one line to stderr
+    Stderr,
+    /// Record a `run_exit` audit entry
+    Audit,
+    /// Both
+    Both,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON entry per line, as stored in the log
+    Jsonl,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum PushTarget {
+    /// HashiCorp Vault KV v2 (check-and-set writes)
+    Vault,
+    /// pass (password-store), one GPG-encrypted file per secret
+    Pass,
 }
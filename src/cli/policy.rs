@@ -1,11 +1,17 @@
 use authy::audit;
 use authy::auth;
+use std::io::{self, BufRead};
+
 use crate::cli::json_output::{
-    PolicyListItem, PolicyListResponse, PolicyShowResponse, PolicyTestResponse,
+    PolicyBulkTestItem, PolicyBulkTestResponse, PolicyCoverageDenied, PolicyCoverageResponse,
+    PolicyDeadRule, PolicyHistoryResponse, PolicyListItem, PolicyListResponse,
+    PolicyRevisionItem, PolicyShowResponse, PolicyTestResponse,
 };
-use crate::cli::PolicyCommands;
+use crate::cli::table::{Column, Table};
+use crate::cli::{GenerationArgs, OperationArgs, PolicyCommands, WindowArgs};
 use authy::error::{AuthyError, Result};
-use authy::policy::Policy;
+use authy::hooks::RevocationEvent;
+use authy::policy::{Policy, SecretOp};
 use authy::vault;
 
 pub fn run(cmd: &PolicyCommands, json: bool) -> Result<()> {
@@ -16,41 +22,125 @@ pub fn run(cmd: &PolicyCommands, json: bool) -> Result<()> {
             deny,
             description,
             run_only,
-        } => create(name, allow, deny, description.as_deref(), *run_only),
-        PolicyCommands::Show { name } => show(name, json),
+            count_only,
+            extends,
+            operations,
+            window,
+            generation,
+        } => create(
+            name,
+            allow,
+            deny,
+            description.as_deref(),
+            *run_only,
+            *count_only,
+            extends,
+            operations,
+            window,
+            generation,
+        ),
+        PolicyCommands::Show { name, effective } => show(name, *effective, json),
         PolicyCommands::Update {
             name,
             allow,
             deny,
             description,
             run_only,
-        } => update(name, allow.as_deref(), deny.as_deref(), description.as_deref(), *run_only),
-        PolicyCommands::List => list(json),
-        PolicyCommands::Remove { name } => remove(name),
-        PolicyCommands::Test { scope, name } => test(scope, name, json),
+            count_only,
+            extends,
+            clear_extends,
+            operations,
+            window,
+            clear_window,
+            generation,
+            clear_gen,
+        } => update(
+            name,
+            allow.as_deref(),
+            deny.as_deref(),
+            description.as_deref(),
+            *run_only,
+            *count_only,
+            if *clear_extends { Some(&[]) } else { extends.as_deref() },
+            operations,
+            window,
+            *clear_window,
+            generation,
+            *clear_gen,
+        ),
+        PolicyCommands::History { name } => history(name, json),
+        PolicyCommands::Rollback { name, to } => rollback(name, *to),
+        PolicyCommands::List { columns } => list(columns, json),
+        PolicyCommands::Remove { name, approve_with } => remove(name, approve_with.as_deref()),
+        PolicyCommands::Test {
+            scope,
+            name,
+            stdin,
+            all: _,
+        } => match name {
+            Some(name) => test(scope, name, json),
+            None => test_many(scope, *stdin, json),
+        },
+        PolicyCommands::Coverage { name, strict } => coverage(name, *strict, json),
     }
 }
 
-fn create(name: &str, allow: &[String], deny: &[String], description: Option<&str>, run_only: bool) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+fn create(
+    name: &str,
+    allow: &[String],
+    deny: &[String],
+    description: Option<&str>,
+    run_only: bool,
+    count_only: bool,
+    extends: &[String],
+    operations: &OperationArgs,
+    window: &WindowArgs,
+    generation: &GenerationArgs,
+) -> Result<()> {
     let (key, auth_ctx) = auth::resolve_auth(true)?;
     let mut vault = vault::load_vault(&key)?;
+    let name = &vault::names::policy_key(&vault, name)?;
 
     if vault.policies.contains_key(name) {
         return Err(AuthyError::PolicyAlreadyExists(name.to_string()));
     }
 
-    let mut policy = Policy::new(name.to_string(), allow.to_vec(), deny.to_vec());
+    let allow = if allow.is_empty() && !extends.is_empty() {
+        Vec::new()
+    } else if allow.is_empty() {
+        let prefix = super::common::prefix_project()?.and_then(|p| p.enforce_prefix);
+        match prefix {
+            Some(p) => vec![format!("{}*", p)],
+            None => {
+                return Err(AuthyError::Other(
+                    "--allow is required (or set enforce_prefix in .authy.toml)".into(),
+                ))
+            }
+        }
+    } else {
+        allow.to_vec()
+    };
+
+    let mut policy = Policy::new(name.to_string(), allow, deny.to_vec());
     policy.description = description.map(|s| s.to_string());
     policy.run_only = run_only;
+    policy.count_only = count_only;
+    policy.extends = extends.to_vec();
+    operations.apply(&mut policy);
+    window.apply(&mut policy);
+    policy.generation = generation.apply(None);
 
     vault.policies.insert(name.to_string(), policy);
+    vault.effective_policy(name)?;
+    vault.record_event("policy.create", &auth_ctx.actor_name(), Some(format!("policy={}", name)));
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "policy.create",
         None,
         &auth_ctx.actor_name(),
@@ -63,14 +153,19 @@ fn create(name: &str, allow: &[String], deny: &[String], description: Option<&st
     Ok(())
 }
 
-fn show(name: &str, json: bool) -> Result<()> {
+fn show(name: &str, effective: bool, json: bool) -> Result<()> {
     let (key, _) = auth::resolve_auth(false)?;
     let vault = vault::load_vault(&key)?;
 
-    let policy = vault
-        .policies
-        .get(name)
-        .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+    let policy = &if effective {
+        vault.effective_policy(name)?
+    } else {
+        vault
+            .policies
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?
+    };
 
     if json {
         let response = PolicyShowResponse {
@@ -79,6 +174,14 @@ fn show(name: &str, json: bool) -> Result<()> {
             allow: policy.allow.clone(),
             deny: policy.deny.clone(),
             run_only: policy.run_only,
+            count_only: policy.count_only,
+            extends: policy.extends.clone(),
+            allow_write: policy.allow_write.clone(),
+            allow_rotate: policy.allow_rotate.clone(),
+            allow_delete: policy.allow_delete.clone(),
+            valid_hours: policy.valid_hours.map(|h| h.to_string()),
+            valid_days: policy.valid_days.map(|d| d.to_string()),
+            generation: policy.generation.clone(),
             created: policy.created_at.to_rfc3339(),
             modified: policy.modified_at.to_rfc3339(),
         };
@@ -95,6 +198,19 @@ fn show(name: &str, json: bool) -> Result<()> {
         if policy.run_only {
             println!("Mode: run-only (secrets can only be injected via `authy run`)");
         }
+        if policy.count_only {
+            println!("Listing: count only (tokens don't see secret names)");
+        }
+        if !policy.extends.is_empty() {
+            let note = if effective { " (inherited rules included below)" } else { "" };
+            println!("Extends: {}{}", policy.extends.join(", "), note);
+        }
+        if let Some(hours) = policy.valid_hours {
+            println!("Valid hours: {} (local time)", hours);
+        }
+        if let Some(days) = policy.valid_days {
+            println!("Valid days: {}", days);
+        }
         println!("Allow patterns:");
         for p in &policy.allow {
             println!("  + {}", p);
@@ -107,6 +223,28 @@ fn show(name: &str, json: bool) -> Result<()> {
                 println!("  - {}", p);
             }
         }
+        for op in [SecretOp::Write, SecretOp::Rotate, SecretOp::Delete] {
+            let patterns = policy.op_patterns(op);
+            if !patterns.is_empty() {
+                println!("Allow {} patterns:", op.as_str());
+                for p in patterns {
+                    println!("  + {}", p);
+                }
+            }
+        }
+        if let Some(ref rules) = policy.generation {
+            println!("Generation rules:");
+            if rules.min_length > 0 {
+                println!("  min length: {}", rules.min_length);
+            }
+            if !rules.require.is_empty() {
+                let classes: Vec<&str> = rules.require.iter().map(|c| c.as_str()).collect();
+                println!("  require: {}", classes.join(", "));
+            }
+            if !rules.forbid.is_empty() {
+                println!("  forbid: {}", rules.forbid.join(" "));
+            }
+        }
         println!("Created: {}", policy.created_at);
         println!("Modified: {}", policy.modified_at);
     }
@@ -114,16 +252,27 @@ fn show(name: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update(
     name: &str,
     allow: Option<&[String]>,
     deny: Option<&[String]>,
     description: Option<&str>,
     run_only: Option<bool>,
+    count_only: Option<bool>,
+    extends: Option<&[String]>,
+    operations: &OperationArgs,
+    window: &WindowArgs,
+    clear_window: bool,
+    generation: &GenerationArgs,
+    clear_gen: bool,
 ) -> Result<()> {
     let (key, auth_ctx) = auth::resolve_auth(true)?;
     let mut vault = vault::load_vault(&key)?;
 
+    let rev = vault
+        .record_policy_revision(name, &auth_ctx.actor_name())
+        .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
     let policy = vault
         .policies
         .get_mut(name)
@@ -141,27 +290,177 @@ fn update(
     if let Some(run_only) = run_only {
         policy.run_only = run_only;
     }
+    if let Some(count_only) = count_only {
+        policy.count_only = count_only;
+    }
+    if let Some(extends) = extends {
+        policy.extends = extends.to_vec();
+    }
+    if clear_window {
+        policy.valid_hours = None;
+        policy.valid_days = None;
+    }
+    operations.apply(policy);
+    window.apply(policy);
+    policy.generation = if clear_gen {
+        None
+    } else {
+        generation.apply(policy.generation.take())
+    };
     policy.modified_at = chrono::Utc::now();
+    vault.effective_policy(name)?;
+    vault.record_event("policy.update", &auth_ctx.actor_name(), Some(format!("policy={}", name)));
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "policy.update",
         None,
         &auth_ctx.actor_name(),
         "success",
-        Some(&format!("policy={}", name)),
+        Some(&format!("policy={}, saved_rev={}", name, rev)),
         &audit_key,
     )?;
 
-    eprintln!("Policy '{}' updated.", name);
+    eprintln!("Policy '{}' updated (previous version saved as revision {}).", name, rev);
     Ok(())
 }
 
-fn list(json: bool) -> Result<()> {
+fn history(name: &str, json: bool) -> Result<()> {
+    let (key, _) = auth::resolve_auth(false)?;
+    let vault = vault::load_vault(&key)?;
+
+    let current = vault
+        .policies
+        .get(name)
+        .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+    let revisions = vault
+        .policy_history
+        .get(name)
+        .map(|h| h.as_slice())
+        .unwrap_or_default();
+
+    if json {
+        let response = PolicyHistoryResponse {
+            policy: name.to_string(),
+            revisions: revisions
+                .iter()
+                .map(|r| PolicyRevisionItem {
+                    rev: r.rev,
+                    replaced: r.replaced_at.to_rfc3339(),
+                    actor: r.actor.clone(),
+                    description: r.policy.description.clone(),
+                    allow: r.policy.allow.clone(),
+                    deny: r.policy.deny.clone(),
+                    run_only: r.policy.run_only,
+                    count_only: r.policy.count_only,
+                })
+                .collect(),
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+        return Ok(());
+    }
+
+    if revisions.is_empty() {
+        eprintln!("Policy '{}' has no previous versions.", name);
+    }
+    for r in revisions {
+        println!(
+            "rev {}  replaced {} by {}",
+            r.rev,
+            r.replaced_at.format("%Y-%m-%d %H:%M:%S"),
+            r.actor
+        );
+        print_rules(&r.policy);
+    }
+    println!("current  modified {}", current.modified_at.format("%Y-%m-%d %H:%M:%S"));
+    print_rules(current);
+    Ok(())
+}
+
+/// One-line-per-field summary of a policy's rules for `policy history`.
+fn print_rules(policy: &Policy) {
+    println!("  allow: {}", policy.allow.join(" "));
+    println!(
+        "  deny:  {}",
+        if policy.deny.is_empty() { "-".to_string() } else { policy.deny.join(" ") }
+    );
+    if policy.run_only {
+        println!("  run-only");
+    }
+    if policy.count_only {
+        println!("  count-only");
+    }
+}
+
+fn rollback(name: &str, to: u32) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    if !vault.policies.contains_key(name) {
+        return Err(AuthyError::PolicyNotFound(name.to_string()));
+    }
+    let target = vault
+        .policy_history
+        .get(name)
+        .and_then(|h| h.iter().find(|r| r.rev == to))
+        .map(|r| r.policy.clone())
+        .ok_or_else(|| {
+            AuthyError::Other(format!(
+                "Policy '{}' has no revision {} (see `authy policy history {}`)",
+                name, to, name
+            ))
+        })?;
+
+    // The version being replaced becomes a revision too, so a rollback can be undone
+    let saved = vault.record_policy_revision(name, &auth_ctx.actor_name());
+    if let Some(policy) = vault.policies.get_mut(name) {
+        policy.restore(&target);
+    }
+    vault.effective_policy(name)?;
+    let detail = format!("policy={}, rev={}", name, to);
+    vault.record_event("policy.rollback", &auth_ctx.actor_name(), Some(detail.clone()));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        "policy.rollback",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!(
+        "Policy '{}' rolled back to revision {} (previous version saved as revision {}).",
+        name,
+        to,
+        saved.unwrap_or_default()
+    );
+    Ok(())
+}
+
+const LIST_COLUMNS: [Column; 5] = [
+    Column::new("name", true, true),
+    Column::new("allow", true, false),
+    Column::new("deny", true, false),
+    Column::new("run_only", false, false),
+    Column::new("description", true, true),
+];
+
+fn list(columns: &[String], json: bool) -> Result<()> {
+    let mut table = Table::new(&LIST_COLUMNS, columns)?;
     let (key, _) = auth::resolve_auth(false)?;
     let vault = vault::load_vault(&key)?;
 
@@ -189,38 +488,58 @@ fn list(json: bool) -> Result<()> {
         }
 
         for (name, policy) in &vault.policies {
-            let desc = policy
-                .description
-                .as_deref()
-                .unwrap_or("(no description)");
-            println!(
-                "{:<20} allow:{} deny:{} — {}",
-                name,
-                policy.allow.len(),
-                policy.deny.len(),
-                desc
-            );
+            table.push(vec![
+                name.clone(),
+                policy.allow.len().to_string(),
+                policy.deny.len().to_string(),
+                policy.run_only.to_string(),
+                policy.description.clone().unwrap_or_else(|| "-".to_string()),
+            ]);
         }
+        table.print();
     }
 
     Ok(())
 }
 
-fn remove(name: &str) -> Result<()> {
+fn remove(name: &str, approve_with: Option<&str>) -> Result<()> {
     let (key, auth_ctx) = auth::resolve_auth(true)?;
     let mut vault = vault::load_vault(&key)?;
 
-    if vault.policies.remove(name).is_none() {
-        return Err(AuthyError::PolicyNotFound(name.to_string()));
+    let protected = match vault.policies.get(name) {
+        Some(policy) => policy.protected,
+        None => return Err(AuthyError::PolicyNotFound(name.to_string())),
+    };
+    let children = vault.policies_extending(name);
+    if !children.is_empty() {
+        return Err(AuthyError::Other(format!(
+            "Policy '{}' is extended by {}; update those first",
+            name,
+            children.join(", ")
+        )));
     }
+    let reason = protected.then(|| format!("policy '{}' is protected", name));
+    super::common::require_approval(
+        &vault,
+        &key,
+        &auth_ctx,
+        "policy.remove",
+        None,
+        reason,
+        approve_with,
+    )?;
+    vault.policies.remove(name);
+    vault.policy_history.remove(name);
+    let event = RevocationEvent::policy_removed(&auth_ctx.actor_name(), name, &vault);
 
+    vault.record_event("policy.remove", &auth_ctx.actor_name(), Some(format!("policy={}", name)));
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "policy.remove",
         None,
         &auth_ctx.actor_name(),
@@ -230,6 +549,7 @@ fn remove(name: &str) -> Result<()> {
     )?;
 
     eprintln!("Policy '{}' removed.", name);
+    super::common::notify_revocation(&event);
     Ok(())
 }
 
@@ -237,29 +557,159 @@ fn test(scope: &str, secret_name: &str, json: bool) -> Result<()> {
     let (key, _) = auth::resolve_auth(false)?;
     let vault = vault::load_vault(&key)?;
 
-    let policy = vault
-        .policies
-        .get(scope)
-        .ok_or_else(|| AuthyError::PolicyNotFound(scope.to_string()))?;
+    let policy = vault.effective_policy(scope)?;
 
-    let allowed = policy.can_read(secret_name)?;
+    let decision = policy.decide(SecretOp::Read, secret_name)?;
+    let reason = decision.denial_reason();
 
     if json {
         let response = PolicyTestResponse {
             scope: scope.to_string(),
             secret: secret_name.to_string(),
+            allowed: decision.allowed,
+            rule_kind: decision.rule_kind.as_str(),
+            matched_rule: decision.matched_rule,
+            reason,
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+    } else if let Some(reason) = reason {
+        println!("DENIED: '{}' cannot read '{}' ({})", scope, secret_name, reason);
+    } else {
+        println!(
+            "ALLOWED: '{}' can read '{}' (allow rule '{}')",
+            scope,
+            secret_name,
+            decision.matched_rule.unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// Test a policy against many names: from stdin, or every secret in the vault.
+fn test_many(scope: &str, from_stdin: bool, json: bool) -> Result<()> {
+    let (key, _) = auth::resolve_auth(false)?;
+    let vault = vault::load_vault(&key)?;
+
+    let policy = vault.effective_policy(scope)?;
+
+    let names: Vec<String> = if from_stdin {
+        let mut names = Vec::new();
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            let name = line.trim();
+            if !name.is_empty() {
+                names.push(name.to_string());
+            }
+        }
+        names
+    } else {
+        vault.secrets.keys().cloned().collect()
+    };
+
+    let refs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+    let results = policy.evaluate_all(&refs)?;
+    let allowed = results.iter().filter(|(_, ok)| *ok).count();
+    let denied = results.len() - allowed;
+
+    if json {
+        let response = PolicyBulkTestResponse {
+            scope: scope.to_string(),
+            results: results
+                .iter()
+                .map(|(name, ok)| PolicyBulkTestItem {
+                    secret: name.to_string(),
+                    allowed: *ok,
+                })
+                .collect(),
+            allowed,
+            denied,
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+    } else {
+        for (name, ok) in &results {
+            println!("{:<8} {}", if *ok { "ALLOWED" } else { "DENIED" }, name);
+        }
+        eprintln!(
+            "\n'{}': {} allowed, {} denied ({} tested)",
+            scope,
             allowed,
+            denied,
+            results.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Evaluate a policy against every secret in the vault and list the
+/// patterns that match nothing.
+fn coverage(name: &str, strict: bool, json: bool) -> Result<()> {
+    let (key, _) = auth::resolve_auth(false)?;
+    let vault = vault::load_vault(&key)?;
+
+    let policy = vault.effective_policy(name)?;
+    let names: Vec<&str> = vault.secrets.keys().map(|s| s.as_str()).collect();
+    let report = policy.coverage(&names)?;
+
+    if json {
+        let response = PolicyCoverageResponse {
+            policy: name.to_string(),
+            allowed: report.allowed.clone(),
+            denied: report
+                .denied
+                .iter()
+                .map(|(secret, pattern)| PolicyCoverageDenied {
+                    secret: secret.clone(),
+                    deny_pattern: pattern.clone(),
+                })
+                .collect(),
+            dead_rules: report
+                .dead
+                .iter()
+                .map(|(list, pattern)| PolicyDeadRule {
+                    list: list.to_string(),
+                    pattern: pattern.clone(),
+                })
+                .collect(),
         };
         println!(
             "{}",
             serde_json::to_string(&response)
                 .map_err(|e| AuthyError::Serialization(e.to_string()))?
         );
-    } else if allowed {
-        println!("ALLOWED: '{}' can read '{}'", scope, secret_name);
     } else {
-        println!("DENIED: '{}' cannot read '{}'", scope, secret_name);
+        println!("Allowed ({}):", report.allowed.len());
+        for secret in &report.allowed {
+            println!("  + {}", secret);
+        }
+        println!("Denied ({}):", report.denied.len());
+        for (secret, pattern) in &report.denied {
+            match pattern {
+                Some(pattern) => println!("  - {} (deny {})", secret, pattern),
+                None => println!("  - {} (no allow match)", secret),
+            }
+        }
+        println!("Dead rules ({}):", report.dead.len());
+        for (list, pattern) in &report.dead {
+            println!("  {} {}", list, pattern);
+        }
     }
 
+    if strict && !report.dead.is_empty() {
+        return Err(AuthyError::Other(format!(
+            "Policy '{}' has {} pattern(s) that match no secret",
+            name,
+            report.dead.len()
+        )));
+    }
     Ok(())
 }
@@ -1,8 +1,11 @@
 use serde::Serialize;
 use std::path::PathBuf;
 
+use authy::auth::{self, CredentialSource};
 use authy::config::project::ProjectConfig;
+use authy::config::{Config, StorageBackend};
 use authy::error::{AuthyError, Result};
+use authy::vault::{self, storage};
 
 #[derive(Serialize)]
 struct ProjectInfoJson {
@@ -16,18 +19,127 @@ struct ProjectInfoJson {
     replace_dash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enforce_prefix: Option<String>,
     aliases: Vec<String>,
     dir: String,
+    effective: EffectiveSettings,
+}
+
+/// What `authy run` in this project would actually use, after merging
+/// `.authy.toml`, `authy.toml` and the environment (before any `run`
+/// flags).
+#[derive(Serialize)]
+struct EffectiveSettings {
+    /// `None` when a session token pins its own scope instead.
+    scope: Option<String>,
+    /// `project` or `token`.
+    scope_source: &'static str,
+    uppercase: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replace_dash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<String>,
+    auth: EffectiveAuth,
+    authy_dir: String,
+    /// `AUTHY_HOME` or `default`.
+    authy_dir_source: &'static str,
+    config: String,
+    storage: StorageBackend,
+    /// Vault file path, or URL for remote storage. Absent when the backend
+    /// can't be set up (e.g. missing S3 credentials).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vault: Option<String>,
+    non_interactive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt_timeout_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct EffectiveAuth {
+    /// See [`CredentialSource::method`].
+    method: &'static str,
+    /// Where the credential (for a token, its keyfile) is set: `env`,
+    /// `project` or `config`. Absent for `prompt` and `none`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyfile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ssh_key: Option<String>,
+}
+
+impl EffectiveSettings {
+    fn resolve(project: &ProjectConfig) -> Result<Self> {
+        let auth = EffectiveAuth::resolve(project);
+        let token = auth.method == "token";
+        let config_path = vault::config_path()?;
+        let config = Config::load(&config_path)?;
+        let home_set = std::env::var_os(vault::AUTHY_HOME_ENV).is_some_and(|d| !d.is_empty());
+        Ok(Self {
+            scope: (!token).then(|| project.scope.clone()),
+            scope_source: if token { "token" } else { "project" },
+            uppercase: project.uppercase,
+            replace_dash: project.replace_dash.clone(),
+            prefix: project.prefix.clone(),
+            auth,
+            authy_dir: vault::authy_dir()?.display().to_string(),
+            authy_dir_source: if home_set { "AUTHY_HOME" } else { "default" },
+            config: config_path.display().to_string(),
+            storage: config.storage.backend,
+            vault: storage::from_config(&config.storage).ok().map(|s| s.location()),
+            non_interactive: auth::is_non_interactive(),
+            prompt_timeout_secs: auth::prompt_timeout()?.map(|t| t.as_secs()),
+        })
+    }
 }
 
-pub fn run(field: Option<&str>, dir: Option<&str>, json: bool) -> Result<()> {
+impl EffectiveAuth {
+    /// `run` exports the project's keyfile as `AUTHY_KEYFILE` when that is
+    /// unset, so it stands in for the env var here.
+    fn resolve(project: &ProjectConfig) -> Self {
+        let project_keyfile = project
+            .expanded_keyfile()
+            .filter(|_| std::env::var("AUTHY_KEYFILE").is_err());
+        let source = match (auth::credential_source(), project_keyfile.clone()) {
+            (CredentialSource::Token { keyfile: None }, Some(kf)) => {
+                CredentialSource::Token { keyfile: Some(kf) }
+            }
+            (source @ CredentialSource::Token { .. }, _) => source,
+            (_, Some(kf)) => CredentialSource::Keyfile(kf),
+            (source, None) => source,
+        };
+        let keyfile_source = if project_keyfile.is_some() { "project" } else { "env" };
+        let (source_name, keyfile, ssh_key) = match &source {
+            CredentialSource::Token { keyfile } => (Some(keyfile_source), keyfile.clone(), None),
+            CredentialSource::Keyfile(kf) => (Some(keyfile_source), Some(kf.clone()), None),
+            CredentialSource::Passphrase => (Some("env"), None, None),
+            CredentialSource::SshAgent(key) => (Some("config"), None, Some(key.clone())),
+            CredentialSource::Keyring => (Some("config"), None, None),
+            CredentialSource::Prompt | CredentialSource::None => (None, None, None),
+        };
+        Self {
+            method: source.method(),
+            source: source_name,
+            keyfile,
+            ssh_key,
+        }
+    }
+}
+
+pub fn run(field: Option<&str>, dir: Option<&str>, cached: bool, json: bool) -> Result<()> {
     let start_dir = match dir {
         Some(d) => PathBuf::from(d),
         None => std::env::current_dir()
             .map_err(|e| AuthyError::Other(format!("Cannot determine cwd: {}", e)))?,
     };
 
-    let (config, project_dir) = ProjectConfig::discover(&start_dir)?
+    let discovered = if cached {
+        ProjectConfig::discover_cached(&start_dir, &vault::authy_dir()?.join("cache/project-discovery.json"))?
+    } else {
+        ProjectConfig::discover(&start_dir)?
+    };
+    let (config, project_dir) = discovered
         .ok_or_else(|| AuthyError::Other("No .authy.toml found".to_string()))?;
 
     if json && field.is_none() {
@@ -38,8 +150,10 @@ pub fn run(field: Option<&str>, dir: Option<&str>, json: bool) -> Result<()> {
             uppercase: config.uppercase,
             replace_dash: config.replace_dash.clone(),
             prefix: config.prefix.clone(),
+            enforce_prefix: config.enforce_prefix.clone(),
             aliases: config.aliases.clone(),
             dir: project_dir.to_string_lossy().to_string(),
+            effective: EffectiveSettings::resolve(&config)?,
         };
         println!(
             "{}",
@@ -72,6 +186,11 @@ pub fn run(field: Option<&str>, dir: Option<&str>, json: bool) -> Result<()> {
                 println!("{}", p);
             }
         }
+        Some("enforce-prefix") => {
+            if let Some(ref p) = config.enforce_prefix {
+                println!("{}", p);
+            }
+        }
         Some("dir") => println!("{}", project_dir.display()),
         Some("aliases") => {
             for alias in &config.aliases {
@@ -80,7 +199,7 @@ pub fn run(field: Option<&str>, dir: Option<&str>, json: bool) -> Result<()> {
         }
         Some(other) => {
             return Err(AuthyError::Other(format!(
-                "Unknown field '{}'. Valid fields: scope, keyfile, vault, uppercase, replace-dash, prefix, dir, aliases",
+                "Unknown field '{}'. Valid fields: scope, keyfile, vault, uppercase, replace-dash, prefix, enforce-prefix, dir, aliases",
                 other
             )));
         }
@@ -100,6 +219,9 @@ pub fn run(field: Option<&str>, dir: Option<&str>, json: bool) -> Result<()> {
             if let Some(ref p) = config.prefix {
                 println!("prefix: {}", p);
             }
+            if let Some(ref p) = config.enforce_prefix {
+                println!("enforce-prefix: {}", p);
+            }
             if !config.aliases.is_empty() {
                 println!("aliases: {}", config.aliases.join(", "));
             }
@@ -0,0 +1,76 @@
+use authy::audit;
+use authy::auth;
+use authy::error::{AuthyError, Result};
+use authy::vault;
+
+pub fn run(name: &str, policy: bool, off: bool, approve_with: Option<&str>) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    if !off && vault.approvers.is_empty() {
+        return Err(AuthyError::Other(
+            "No approvers are registered; add one with `authy approver add` first".to_string(),
+        ));
+    }
+
+    let (kind, protected) = if policy {
+        let p = vault
+            .policies
+            .get(name)
+            .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+        ("policy", p.protected)
+    } else {
+        let entry = vault
+            .secrets
+            .get(name)
+            .ok_or_else(|| AuthyError::SecretNotFound(name.to_string()))?;
+        ("secret", entry.metadata.protected)
+    };
+    let state = if off { "unprotected" } else { "protected" };
+    if protected != off {
+        eprintln!("The {} '{}' is already {}.", kind, name, state);
+        return Ok(());
+    }
+
+    let op = if off { "unprotect" } else { "protect" };
+    let secret = (!policy).then_some(name);
+
+    // Lifting protection is as sensitive as the removal it would allow
+    if off {
+        super::common::require_approval(
+            &vault,
+            &key,
+            &auth_ctx,
+            op,
+            secret,
+            Some(format!("{} '{}' is protected", kind, name)),
+            approve_with,
+        )?;
+    }
+
+    if let Some(p) = vault.policies.get_mut(name).filter(|_| policy) {
+        p.protected = !off;
+        p.modified_at = chrono::Utc::now();
+    } else if let Some(entry) = vault.secrets.get_mut(name).filter(|_| !policy) {
+        entry.metadata.protected = !off;
+    }
+
+    let detail = format!("{}={}", kind, name);
+    vault.record_event(op, &auth_ctx.actor_name(), Some(detail.clone()));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let audit_key = audit::derive_audit_key(&audit::key_material(&key));
+    audit::log_event(
+        &vault::audit_path()?,
+        op,
+        secret,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!("The {} '{}' is now {}.", kind, name, state);
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use authy::audit;
+use authy::auth;
+use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::policy::build_globset;
+use authy::vault;
+
+use super::push_targets::hcvault::HcVaultPushAdapter;
+use super::push_targets::pass::PassPushAdapter;
+use super::push_targets::PushAdapter;
+use super::PushTarget;
+
+pub fn run(
+    to: &PushTarget,
+    mount: &str,
+    path: Option<&str>,
+    gpg_ids: &[String],
+    scope: Option<&str>,
+    name_match: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    if matches!(to, PushTarget::Vault) && path.is_none() {
+        return Err(AuthyError::Other(
+            "HashiCorp Vault push requires --path (e.g., authy push --to vault --path myapp)".into(),
+        ));
+    }
+
+    // Pushing copies values out of the vault: master auth only
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let vault_data = vault::load_vault(&key)?;
+
+    let names: Vec<&str> = vault_data.secrets.keys().map(|s| s.as_str()).collect();
+    let mut selected = if let Some(scope_name) = scope {
+        let policy = vault_data.effective_policy(scope_name)?;
+        policy.filter_secrets(&names)?
+    } else {
+        names
+    };
+    if let Some(pattern) = name_match {
+        let matcher = build_globset(&[pattern.to_string()])?;
+        selected.retain(|name| matcher.is_match(name));
+    }
+
+    if selected.is_empty() {
+        eprintln!("No secrets selected; nothing to push.");
+        return Ok(());
+    }
+
+    if dry_run {
+        for name in &selected {
+            println!("[dry-run] push {}", name);
+        }
+        eprintln!("{} secret(s) would be pushed. (dry run)", selected.len());
+        return Ok(());
+    }
+
+    let pairs: Vec<(String, String)> = selected
+        .iter()
+        .filter_map(|name| {
+            vault_data
+                .secrets
+                .get(*name)
+                .map(|entry| (name.to_string(), entry.value.clone()))
+        })
+        .collect();
+
+    let (target_name, result) = match to {
+        PushTarget::Vault => {
+            let adapter = HcVaultPushAdapter {
+                path: path.unwrap_or_default().to_string(),
+                mount: mount.to_string(),
+            };
+            ("vault", adapter.push(&pairs))
+        }
+        PushTarget::Pass => {
+            let adapter = PassPushAdapter {
+                store_path: path.map(String::from),
+                gpg_ids: gpg_ids.to_vec(),
+            };
+            ("pass", adapter.push(&pairs))
+        }
+    };
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    let (outcome, detail) = match &result {
+        Ok(summary) => ("success", format!("to={}, count={}, {}", target_name, pairs.len(), summary)),
+        Err(e) => ("failed", format!("to={}, error={}", target_name, e)),
+    };
+    audit::log_event(
+        &vault::audit_path()?,
+        "push",
+        None,
+        &auth_ctx.actor_name(),
+        outcome,
+        Some(&detail),
+        &audit_key,
+    )?;
+    if result.is_ok() {
+        hooks::trip_canaries(
+            &vault_data,
+            pairs.iter().map(|(name, _)| name.as_str()),
+            "push",
+            &auth_ctx.actor_name(),
+            scope,
+            &audit_key,
+        );
+    }
+
+    let summary = result?;
+    eprintln!("{} secret(s) pushed to {} ({}).", pairs.len(), target_name, summary);
+    Ok(())
+}
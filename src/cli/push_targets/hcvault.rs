@@ -0,0 +1,141 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use authy::error::{AuthyError, Result};
+
+use super::PushAdapter;
+
+/// Push to a HashiCorp Vault KV v2 mount using check-and-set writes.
+pub struct HcVaultPushAdapter {
+    pub path: String,
+    pub mount: String,
+}
+
+impl PushAdapter for HcVaultPushAdapter {
+    fn push(&self, secrets: &[(String, String)]) -> Result<String> {
+        check_vault_installed()?;
+
+        // CAS against the version we observed, so a concurrent writer
+        // makes the push fail instead of being silently overwritten.
+        let current = current_version(&self.path, &self.mount)?;
+        let new_version = write_kv_secret(&self.path, &self.mount, current, secrets)?;
+
+        Ok(format!(
+            "mount={}, path={}, version={}",
+            self.mount, self.path, new_version
+        ))
+    }
+}
+
+fn check_vault_installed() -> Result<()> {
+    match Command::new("vault").arg("version").output() {
+        Ok(output) if output.status.success() => Ok(()),
+        _ => Err(AuthyError::Other(
+            "HashiCorp Vault CLI not found. Install from https://www.vaultproject.io/downloads"
+                .into(),
+        )),
+    }
+}
+
+/// Read the current KV v2 version at `path`. Returns 0 if nothing exists yet.
+fn current_version(path: &str, mount: &str) -> Result<u64> {
+    let output = Command::new("vault")
+        .args([
+            "kv",
+            "metadata",
+            "get",
+            "-format=json",
+            &format!("-mount={}", mount),
+            path,
+        ])
+        .output()
+        .map_err(|e| AuthyError::Other(format!("Failed to run `vault kv metadata get`: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No value found") || stderr.contains("Not Found") {
+            return Ok(0);
+        }
+        return Err(vault_error("vault kv metadata get", &stderr));
+    }
+
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AuthyError::Other(format!("Failed to parse vault output: {}", e)))?;
+
+    response
+        .get("data")
+        .and_then(|d| d.get("current_version"))
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| {
+            AuthyError::Other(format!(
+                "Mount '{}' does not look like a KV v2 engine (no version metadata)",
+                mount
+            ))
+        })
+}
+
+/// Write all pairs as a single KV v2 version. Values are passed on stdin,
+/// never as command-line arguments.
+fn write_kv_secret(
+    path: &str,
+    mount: &str,
+    cas: u64,
+    secrets: &[(String, String)],
+) -> Result<u64> {
+    let data: serde_json::Map<String, serde_json::Value> = secrets
+        .iter()
+        .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+        .collect();
+    let payload = serde_json::to_vec(&data)
+        .map_err(|e| AuthyError::Serialization(e.to_string()))?;
+
+    let mut child = Command::new("vault")
+        .args([
+            "kv",
+            "put",
+            "-format=json",
+            &format!("-mount={}", mount),
+            &format!("-cas={}", cas),
+            path,
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AuthyError::Other(format!("Failed to run `vault kv put`: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&payload)?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("check-and-set") {
+            return Err(AuthyError::Other(format!(
+                "Check-and-set failed: '{}' changed since version {}. Re-run to push again.",
+                path, cas
+            )));
+        }
+        return Err(vault_error("vault kv put", &stderr));
+    }
+
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| AuthyError::Other(format!("Failed to parse vault output: {}", e)))?;
+    Ok(response
+        .get("data")
+        .and_then(|d| d.get("version"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(cas + 1))
+}
+
+fn vault_error(command: &str, stderr: &str) -> AuthyError {
+    if stderr.contains("permission denied")
+        || stderr.contains("missing client token")
+        || stderr.contains("VAULT_TOKEN")
+    {
+        return AuthyError::Other("Not authenticated. Run `vault login` or set VAULT_TOKEN.".into());
+    }
+    AuthyError::Other(format!("{} failed: {}", command, stderr.trim()))
+}
@@ -0,0 +1,11 @@
+pub mod hcvault;
+pub mod pass;
+
+use authy::error::Result;
+
+/// Trait for external secret store push targets.
+/// Each adapter receives the selected (name, value) pairs and writes them
+/// to the external store, returning a short description of the result.
+pub trait PushAdapter {
+    fn push(&self, secrets: &[(String, String)]) -> Result<String>;
+}
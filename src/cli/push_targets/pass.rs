@@ -0,0 +1,128 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use authy::error::{AuthyError, Result};
+
+use super::PushAdapter;
+use crate::cli::import_sources::pass::{check_gpg_installed, resolve_store_dir};
+
+/// Push into a password-store: each secret becomes `<store>/<a>/<b>.gpg`
+/// for the name `a-b`, the layout `import --from pass` reads back.
+pub struct PassPushAdapter {
+    pub store_path: Option<String>,
+    /// GPG recipients. When empty, each secret goes to the recipients in the
+    /// nearest `.gpg-id`, as `pass insert` does.
+    pub gpg_ids: Vec<String>,
+}
+
+impl PushAdapter for PassPushAdapter {
+    fn push(&self, secrets: &[(String, String)]) -> Result<String> {
+        check_gpg_installed()?;
+        let store_dir = resolve_store_dir(&self.store_path)?;
+
+        // A new store gets a .gpg-id, so `pass` itself can use it
+        if !self.gpg_ids.is_empty() && !store_dir.join(".gpg-id").exists() {
+            fs::create_dir_all(&store_dir)?;
+            fs::write(store_dir.join(".gpg-id"), format!("{}\n", self.gpg_ids.join("\n")))?;
+        }
+
+        // Work out every file and its recipients before writing any
+        let mut plan = Vec::with_capacity(secrets.len());
+        for (name, value) in secrets {
+            let file = entry_path(&store_dir, name);
+            let recipients = if self.gpg_ids.is_empty() {
+                nearest_gpg_ids(&store_dir, &file)?.ok_or_else(|| {
+                    AuthyError::Other(format!(
+                        "No .gpg-id in {} for '{}'. Pass --gpg-id <key-id>.",
+                        store_dir.display(),
+                        name
+                    ))
+                })?
+            } else {
+                self.gpg_ids.clone()
+            };
+            plan.push((file, recipients, value));
+        }
+
+        for (file, recipients, value) in &plan {
+            if let Some(dir) = file.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            encrypt_to(file, recipients, value)?;
+        }
+
+        Ok(format!("store={}, files={}", store_dir.display(), plan.len()))
+    }
+}
+
+/// `db-primary-password` (or `db/primary/password`) ->
+/// `<store>/db/primary/password.gpg`
+fn entry_path(store_dir: &Path, name: &str) -> PathBuf {
+    let segments: Vec<&str> = name
+        .split(['-', '/'])
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .collect();
+    let mut path = store_dir.to_path_buf();
+    if let Some((last, dirs)) = segments.split_last() {
+        path.extend(dirs);
+        path.push(format!("{}.gpg", last));
+    }
+    path
+}
+
+/// The recipients in the `.gpg-id` closest to `file`, looking up through
+/// its directories to the store root.
+fn nearest_gpg_ids(store_dir: &Path, file: &Path) -> Result<Option<Vec<String>>> {
+    for dir in file.ancestors().skip(1) {
+        let gpg_id = dir.join(".gpg-id");
+        if gpg_id.is_file() {
+            let ids: Vec<String> = fs::read_to_string(&gpg_id)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from)
+                .collect();
+            return Ok((!ids.is_empty()).then_some(ids));
+        }
+        if dir == store_dir {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+/// Encrypt `value` (plus the trailing newline `pass` writes) to `file`.
+/// The value goes through stdin, never the command line.
+fn encrypt_to(file: &Path, recipients: &[String], value: &str) -> Result<()> {
+    let mut cmd = Command::new("gpg");
+    cmd.args(["--quiet", "--yes", "--batch", "--encrypt", "--trust-model", "always"]);
+    for recipient in recipients {
+        cmd.args(["--recipient", recipient]);
+    }
+    let mut child = cmd
+        .arg("--output")
+        .arg(file)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AuthyError::Other(format!("Failed to run gpg: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(value.as_bytes())?;
+        stdin.write_all(b"\n")?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AuthyError::Other(format!(
+            "GPG encryption of {} failed: {}",
+            file.display(),
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
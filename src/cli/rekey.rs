@@ -9,6 +9,7 @@ pub fn run(
     generate_keyfile: Option<&str>,
     to_passphrase: bool,
     new_keyfile: Option<&str>,
+    approve_with: Option<&str>,
 ) -> Result<()> {
     // Validate mutual exclusivity
     let flag_count =
@@ -22,7 +23,16 @@ pub fn run(
 
     // Auth with old credentials (require write access — no tokens)
     let (old_key, auth_ctx) = auth::resolve_auth(true)?;
-    let vault = vault::load_vault(&old_key)?;
+    let mut vault = vault::load_vault(&old_key)?;
+    super::common::require_approval(
+        &vault,
+        &old_key,
+        &auth_ctx,
+        "rekey",
+        None,
+        auth::approval::protected_items(&vault),
+        approve_with,
+    )?;
 
     // Determine new key
     let new_key = if let Some(keyfile_path) = generate_keyfile {
@@ -61,14 +71,32 @@ pub fn run(
         vault::VaultKey::Passphrase(passphrase)
     };
 
+    // An approver can't become the vault's own key, or a single keyfile
+    // would be enough to remove protected items
+    if let vault::VaultKey::Keyfile { pubkey, .. } = &new_key {
+        if vault.approvers.contains(pubkey) {
+            return Err(AuthyError::Other(
+                "That keyfile is a registered approver; remove it with `authy approver remove` first"
+                    .to_string(),
+            ));
+        }
+    }
+
     // Save vault with new key
+    let method = match new_key {
+        vault::VaultKey::Passphrase(_) => "passphrase",
+        vault::VaultKey::Keyfile { .. } => "keyfile",
+        vault::VaultKey::SshAgent { .. } => "ssh-agent",
+    };
+    vault.record_event("rekey", &auth_ctx.actor_name(), Some(format!("to={}", method)));
+    vault.touch();
     vault::save_vault(&vault, &new_key)?;
 
     // Audit log with NEW key material (so the chain continues with new key)
     let material = audit::key_material(&new_key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "rekey",
         None,
         &auth_ctx.actor_name(),
@@ -77,6 +105,21 @@ pub fn run(
         &audit_key,
     )?;
 
+    // Stop unlocking through the SSH agent once the vault no longer uses it
+    if matches!(old_key, vault::VaultKey::SshAgent { .. }) {
+        authy::config::Config::set(&vault::config_path()?, "vault.auth_method", method)?;
+    }
+
+    // Keep a keychain credential in step with the vault
+    #[cfg(feature = "keyring")]
+    if authy::config::Config::load(&vault::config_path()?)?
+        .auth
+        .keyring
+    {
+        auth::keychain::store(&new_key)?;
+        eprintln!("Updated the master credential in the OS keychain.");
+    }
+
     eprintln!("Vault re-encrypted successfully.");
     eprintln!("Warning: all existing session tokens are now invalidated.");
 
@@ -1,15 +1,31 @@
 use authy::audit;
 use authy::auth;
 use authy::error::{AuthyError, Result};
+use authy::policy::SecretOp;
+use authy::session::Capability;
 use authy::vault;
 
-pub fn run(name: &str) -> Result<()> {
-    let (key, auth_ctx) = auth::resolve_auth(true)?;
+pub fn run(name: &str, scope: Option<&str>, approve_with: Option<&str>) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Write)?;
     let mut vault = vault::load_vault(&key)?;
+    let scope = super::common::listing_scope(scope, &auth_ctx)?;
+    auth::check_scope(&vault, scope.as_deref(), SecretOp::Delete, name)?;
 
-    if vault.secrets.remove(name).is_none() {
-        return Err(AuthyError::SecretNotFound(name.to_string()));
-    }
+    let protected = match vault.secrets.get(name) {
+        Some(entry) => entry.metadata.protected,
+        None => return Err(AuthyError::SecretNotFound(name.to_string())),
+    };
+    let reason = protected.then(|| format!("secret '{}' is protected", name));
+    super::common::require_approval(
+        &vault,
+        &key,
+        &auth_ctx,
+        "remove",
+        Some(name),
+        reason,
+        approve_with,
+    )?;
+    vault.secrets.remove(name);
 
     vault.touch();
     vault::save_vault(&vault, &key)?;
@@ -18,7 +34,7 @@ pub fn run(name: &str) -> Result<()> {
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "remove",
         Some(name),
         &auth_ctx.actor_name(),
@@ -5,6 +5,9 @@ use authy::auth;
 use crate::cli::common;
 use authy::config::project::ProjectConfig;
 use authy::error::{AuthyError, Result};
+use authy::hooks;
+use authy::policy::SecretOp;
+use authy::session::Capability;
 use authy::vault;
 
 pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<()> {
@@ -23,7 +26,7 @@ pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<
         }
     }
 
-    let (key, auth_ctx) = auth::resolve_auth(false)?;
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Read)?;
     let vault = vault::load_vault(&key)?;
 
     // Do NOT check auth_ctx.run_only — resolve is a safe command (like run)
@@ -44,6 +47,7 @@ pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<
     let mut result = String::with_capacity(content.len());
     let mut rest = content.as_str();
     let mut keys_resolved = 0u32;
+    let mut resolved_names = Vec::new();
 
     while let Some(start) = rest.find("<authy:") {
         result.push_str(&rest[..start]);
@@ -60,19 +64,25 @@ pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<
                 continue;
             }
 
-            let value = secrets.get(key_name).ok_or_else(|| {
-                if vault.secrets.contains_key(key_name) {
-                    AuthyError::AccessDenied {
+            let value = match secrets.get(key_name) {
+                Some(value) => value,
+                None if vault.secrets.contains_key(key_name) => {
+                    let reason = vault
+                        .effective_policy(&scope)?
+                        .decide(SecretOp::Read, key_name)?
+                        .denial_reason();
+                    return Err(AuthyError::AccessDenied {
                         secret: key_name.to_string(),
                         scope: scope.clone(),
-                    }
-                } else {
-                    AuthyError::SecretNotFound(key_name.to_string())
+                        reason,
+                    });
                 }
-            })?;
+                None => return Err(AuthyError::SecretNotFound(key_name.to_string())),
+            };
 
             result.push_str(value);
             keys_resolved += 1;
+            resolved_names.push(key_name);
             rest = &after_prefix[end + 1..];
         } else {
             // No closing '>', pass through rest
@@ -95,7 +105,7 @@ pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "resolve",
         None,
         &auth_ctx.actor_name(),
@@ -106,6 +116,14 @@ pub fn run(file: &str, output: Option<&str>, scope_arg: Option<&str>) -> Result<
         )),
         &audit_key,
     )?;
+    hooks::trip_canaries(
+        &vault,
+        resolved_names,
+        "resolve",
+        &auth_ctx.actor_name(),
+        Some(&scope),
+        &audit_key,
+    );
 
     Ok(())
 }
@@ -2,12 +2,17 @@ use std::io::{self, Read};
 
 use authy::audit;
 use authy::auth;
+use authy::config::LimitsConfig;
 use authy::error::{AuthyError, Result};
-use authy::vault;
+use authy::policy::SecretOp;
+use authy::session::Capability;
+use authy::vault::{self, secret::{Provenance, ValueEncoding}, values};
 
-pub fn run(name: &str) -> Result<()> {
-    let (key, auth_ctx) = auth::resolve_auth(true)?;
+pub fn run(name: &str, scope: Option<&str>, allow_weak: bool) -> Result<()> {
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Rotate)?;
     let mut vault = vault::load_vault(&key)?;
+    let scope = super::common::listing_scope(scope, &auth_ctx)?;
+    auth::check_scope(&vault, scope.as_deref(), SecretOp::Rotate, name)?;
 
     if !vault.secrets.contains_key(name) {
         return Err(AuthyError::SecretNotFound(name.to_string()));
@@ -19,10 +24,17 @@ pub fn run(name: &str) -> Result<()> {
         .read_to_string(&mut value)
         .map_err(|e| AuthyError::Other(format!("Failed to read from stdin: {}", e)))?;
     let value = value.trim_end_matches('\n').to_string();
+    if !allow_weak {
+        values::check(name, &value)?;
+    }
+
+    LimitsConfig::load()?.check_secret(&vault, name, &value)?;
 
     let entry = vault.secrets.get_mut(name).unwrap();
     entry.value = value;
+    entry.encoding = ValueEncoding::Text;
     entry.metadata.bump_version();
+    entry.metadata.provenance = Some(Provenance::new("rotate", &auth_ctx.actor_name()));
     let version = entry.metadata.version;
 
     vault.touch();
@@ -33,7 +45,7 @@ pub fn run(name: &str) -> Result<()> {
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "rotate",
         Some(name),
         &auth_ctx.actor_name(),
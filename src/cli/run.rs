@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
 use authy::audit;
-use authy::auth;
-use crate::cli::common;
+use authy::auth::{self, context::AuthMethod};
+use crate::cli::{common, RunSummary};
 use authy::config::project::ProjectConfig;
-use authy::error::{AuthyError, Result};
+use authy::error::Result;
+use authy::hooks;
+use authy::session::Capability;
 use authy::subprocess::{self, NamingOptions};
 use authy::vault;
 
@@ -11,19 +16,13 @@ pub fn run(
     uppercase_arg: bool,
     replace_dash_arg: Option<char>,
     prefix_arg: Option<String>,
+    summary: Option<RunSummary>,
     command: &[String],
 ) -> Result<()> {
     // Merge CLI args with project config
     let project = ProjectConfig::discover_from_cwd().ok().flatten();
     let project_config = project.as_ref().map(|(c, _)| c);
 
-    let scope = scope_arg
-        .map(|s| s.to_string())
-        .or_else(|| project_config.map(|c| c.scope.clone()))
-        .ok_or_else(|| {
-            AuthyError::Other("No --scope provided and no .authy.toml found.".to_string())
-        })?;
-
     let uppercase = uppercase_arg || project_config.is_some_and(|c| c.uppercase);
     let replace_dash =
         replace_dash_arg.or_else(|| project_config.and_then(|c| c.replace_dash_char()));
@@ -36,11 +35,27 @@ pub fn run(
         }
     }
 
-    let (key, auth_ctx) = auth::resolve_auth(false)?;
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Read)?;
+    let scope = common::effective_scope(
+        scope_arg,
+        project_config.map(|c| c.scope.as_str()),
+        &auth_ctx,
+    )?;
     let vault = vault::load_vault(&key)?;
 
     let secrets = common::resolve_scoped_secrets(&vault, &scope, &auth_ctx)?;
 
+    // A token's session can carry fixed, non-secret env overrides
+    let session_env = match auth_ctx.method {
+        AuthMethod::SessionToken { ref session_id } => vault
+            .sessions
+            .iter()
+            .find(|s| &s.id == session_id)
+            .map(|s| s.env.clone())
+            .unwrap_or_default(),
+        _ => BTreeMap::new(),
+    };
+
     let naming = NamingOptions {
         uppercase,
         replace_dash,
@@ -51,20 +66,78 @@ pub fn run(
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "run",
         None,
         &auth_ctx.actor_name(),
         "success",
         Some(&format!(
-            "scope={}, secrets={}, cmd={}",
+            "scope={}, secrets={}{}, cmd={}",
             scope,
             secrets.len(),
+            if session_env.is_empty() {
+                String::new()
+            } else {
+                format!(", env={}", session_env.len())
+            },
             command.first().map(|s| s.as_str()).unwrap_or("?")
         )),
         &audit_key,
     )?;
+    hooks::trip_canaries(
+        &vault,
+        secrets.keys().map(|s| s.as_str()),
+        "run",
+        &auth_ctx.actor_name(),
+        Some(&scope),
+        &audit_key,
+    );
+
+    // The child's runtime isn't ours to report; stop the clock at spawn
+    authy::timings::report();
+    let started = Instant::now();
+    let exit_code = subprocess::run_with_secrets(command, &secrets, &naming, &session_env)?;
 
-    let exit_code = subprocess::run_with_secrets(command, &secrets, &naming)?;
+    if let Some(summary) = summary {
+        let mut names: Vec<&str> = secrets.keys().map(|s| s.as_str()).collect();
+        names.sort_unstable();
+        let program = command.first().map(|s| s.as_str()).unwrap_or("?");
+        let wall_ms = started.elapsed().as_millis();
+        let detail = |names: Option<Vec<String>>| {
+            let mut detail = format!(
+                "cmd={}, exit={}, wall_ms={}, secrets={}",
+                program,
+                exit_code,
+                wall_ms,
+                secrets.len()
+            );
+            if let Some(names) = names.filter(|n| !n.is_empty()) {
+                detail.push_str(&format!(", names={}", names.join(",")));
+            }
+            detail
+        };
+        if matches!(summary, RunSummary::Stderr | RunSummary::Both) {
+            let plain = names.iter().map(|n| n.to_string()).collect();
+            eprintln!("authy run: {}", detail(Some(plain)));
+        }
+        if matches!(summary, RunSummary::Audit | RunSummary::Both) {
+            // The command has already run; a failed write mustn't change its exit code
+            let recorded = audit::recorded_names(names.iter().copied(), &audit_key);
+            let logged = vault::audit_path().and_then(|path| {
+                audit::log_event(
+                    &path,
+                    "run_exit",
+                    None,
+                    &auth_ctx.actor_name(),
+                    "success",
+                    Some(&detail(recorded)),
+                    &audit_key,
+                )
+            });
+            if let Err(e) = logged {
+                eprintln!("authy run: could not record summary: {}", e);
+            }
+        }
+    }
     std::process::exit(exit_code);
 }
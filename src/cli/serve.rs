@@ -4,14 +4,22 @@ use authy::api::AuthyClient;
 use authy::error::{AuthyError, Result};
 use authy::mcp::McpServer;
 
-pub fn run(mcp: bool) -> Result<()> {
+pub fn run(mcp: bool, cache_ttl: Option<&str>) -> Result<()> {
     if !mcp {
         eprintln!("authy serve requires --mcp");
         return Err(AuthyError::Other("authy serve requires --mcp".into()));
     }
 
-    let client = AuthyClient::from_env().ok();
-    let server = McpServer::new(client);
+    let cache_ttl = super::common::parse_cache_ttl(cache_ttl)?;
+    let mut client = AuthyClient::from_env().ok();
+    if let Some(ttl) = cache_ttl {
+        // Tools other than get (list, env...) reuse the decrypted vault too
+        client = client.map(|c| c.with_cache(ttl));
+    }
+    let mut server = McpServer::new(client);
+    if let Some(ttl) = cache_ttl {
+        server = server.with_cache_ttl(ttl);
+    }
 
     let stdin = io::stdin().lock();
     let stdout = io::stdout().lock();
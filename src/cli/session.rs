@@ -1,25 +1,75 @@
+use std::collections::BTreeMap;
+
 use authy::audit;
 use authy::auth;
+use authy::config::{LimitsConfig, SessionsConfig};
 use crate::cli::json_output::{
     SessionCreateResponse, SessionListItem, SessionListResponse,
 };
-use crate::cli::SessionCommands;
+use crate::cli::table::{self, Column, Table};
+use crate::cli::{common, SessionCommands};
 use authy::error::{AuthyError, Result};
-use authy::session::{self, SessionRecord};
+use authy::hooks::RevocationEvent;
+use authy::session::{self, Capability, SessionRecord};
 use authy::vault;
 
 pub fn run(cmd: &SessionCommands, json: bool) -> Result<()> {
     match cmd {
-        SessionCommands::Create { scope, ttl, label, run_only } => {
-            create(scope, ttl, label.as_deref(), *run_only, json)
-        }
-        SessionCommands::List => list(json),
-        SessionCommands::Revoke { id } => revoke(id),
+        SessionCommands::Create {
+            scope,
+            ttl,
+            label,
+            run_only,
+            env,
+            can,
+            sliding,
+            max_lifetime,
+        } => create(
+            scope,
+            ttl,
+            label.as_deref(),
+            *run_only,
+            env,
+            can,
+            *sliding,
+            max_lifetime.as_deref(),
+            json,
+        ),
+        SessionCommands::List { columns } => list(columns, json),
+        SessionCommands::Revoke { id, label, scope, older_than } => match id {
+            Some(id) => revoke(id),
+            None => revoke_matching(label.as_deref(), scope.as_deref(), older_than.as_deref()),
+        },
+        SessionCommands::Refresh { id, ttl } => refresh(id, ttl.as_deref()),
+        SessionCommands::Prune { older_than, dry_run } => prune(older_than.as_deref(), *dry_run),
+        SessionCommands::Label { id, label, clear: _ } => set_label(id, label.as_deref()),
         SessionCommands::RevokeAll => revoke_all(),
     }
 }
 
-fn create(scope: &str, ttl: &str, label: Option<&str>, run_only: bool, json: bool) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+fn create(
+    scope: &str,
+    ttl: &str,
+    label: Option<&str>,
+    run_only: bool,
+    env: &[String],
+    can: &[Capability],
+    sliding: bool,
+    max_lifetime: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let capabilities = if can.is_empty() {
+        session::default_capabilities()
+    } else {
+        Capability::ALL.into_iter().filter(|c| can.contains(c)).collect()
+    };
+    let can_list = join_capabilities(&capabilities);
+    let env = env
+        .iter()
+        .map(|s| session::parse_env_override(s))
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
     let (key, auth_ctx) = auth::resolve_auth(true)?;
     let mut vault = vault::load_vault(&key)?;
 
@@ -29,6 +79,14 @@ fn create(scope: &str, ttl: &str, label: Option<&str>, run_only: bool, json: boo
     }
 
     let duration = session::parse_ttl(ttl)?;
+    let max_lifetime = max_lifetime.map(session::parse_ttl).transpose()?;
+    if max_lifetime.is_some_and(|max| max < duration) {
+        return Err(AuthyError::Other(format!(
+            "--max-lifetime is shorter than --ttl {}",
+            ttl
+        )));
+    }
+    LimitsConfig::load()?.check_session(&vault)?;
     let now = chrono::Utc::now();
     let expires_at = now + duration;
 
@@ -48,21 +106,34 @@ fn create(scope: &str, ttl: &str, label: Option<&str>, run_only: bool, json: boo
         revoked: false,
         label: label.map(|s| s.to_string()),
         run_only,
+        env: env.clone(),
+        capabilities: capabilities.clone(),
+        sliding_ttl_secs: sliding.then(|| duration.num_seconds()),
+        max_expires_at: max_lifetime.map(|max| now + max),
+        revoked_at: None,
     };
 
     vault.sessions.push(record);
+    vault.record_event(
+        "session.create",
+        &auth_ctx.actor_name(),
+        Some(format!("session={}, scope={}", session_id, scope)),
+    );
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     // Audit log
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "session.create",
         None,
         &auth_ctx.actor_name(),
         "success",
-        Some(&format!("session={}, scope={}, ttl={}", session_id, scope, ttl)),
+        Some(&format!(
+            "session={}, scope={}, ttl={}, can={}",
+            session_id, scope, ttl, can_list
+        )),
         &audit_key,
     )?;
 
@@ -73,6 +144,10 @@ fn create(scope: &str, ttl: &str, label: Option<&str>, run_only: bool, json: boo
             scope: scope.to_string(),
             run_only,
             expires: expires_at.to_rfc3339(),
+            env,
+            capabilities,
+            sliding,
+            max_expires: max_lifetime.map(|max| (now + max).to_rfc3339()),
         };
         println!(
             "{}",
@@ -83,12 +158,41 @@ fn create(scope: &str, ttl: &str, label: Option<&str>, run_only: bool, json: boo
         // Print the token to stdout (the only time it's ever shown)
         println!("{}", token);
         let mode = if run_only { ", mode=run-only" } else { "" };
-        eprintln!("Session '{}' created (scope={}, expires={}{})", session_id, scope, expires_at, mode);
+        let sliding = match max_lifetime {
+            Some(max) if sliding => format!(", sliding until {}", now + max),
+            _ => String::new(),
+        };
+        let env_keys = if env.is_empty() {
+            String::new()
+        } else {
+            format!(", env={}", env.keys().cloned().collect::<Vec<_>>().join(","))
+        };
+        eprintln!(
+            "Session '{}' created (scope={}, expires={}{}, can={}{}{})",
+            session_id, scope, expires_at, sliding, can_list, mode, env_keys
+        );
     }
     Ok(())
 }
 
-fn list(json: bool) -> Result<()> {
+fn join_capabilities(capabilities: &[Capability]) -> String {
+    capabilities.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(",")
+}
+
+const LIST_COLUMNS: [Column; 9] = [
+    Column::new("id", true, false),
+    Column::new("scope", true, true),
+    Column::new("status", true, false),
+    Column::new("label", true, true),
+    Column::new("created", false, false),
+    Column::new("expires", true, false),
+    Column::new("mode", true, false),
+    Column::new("can", true, false),
+    Column::new("env", false, true),
+];
+
+fn list(columns: &[String], json: bool) -> Result<()> {
+    let mut table = Table::new(&LIST_COLUMNS, columns)?;
     let (key, _) = auth::resolve_auth(false)?;
     let vault = vault::load_vault(&key)?;
 
@@ -114,6 +218,10 @@ fn list(json: bool) -> Result<()> {
                     label: s.label.clone(),
                     created: s.created_at.to_rfc3339(),
                     expires: s.expires_at.to_rfc3339(),
+                    env: s.env.clone(),
+                    capabilities: s.capabilities.clone(),
+                    sliding: s.sliding_ttl_secs.is_some(),
+                    max_expires: s.max_expires_at.map(|at| at.to_rfc3339()),
                 }
             })
             .collect();
@@ -138,13 +246,24 @@ fn list(json: bool) -> Result<()> {
                 "active".to_string()
             };
 
-            let label = session.label.as_deref().unwrap_or("-");
-            let mode = if session.run_only { " run-only" } else { "" };
-            println!(
-                "{:<16} scope={:<16} status={:<8} label={} expires={}{}",
-                session.id, session.scope, status, label, session.expires_at, mode
-            );
+            let env = if session.env.is_empty() {
+                "-".to_string()
+            } else {
+                session.env.keys().cloned().collect::<Vec<_>>().join(",")
+            };
+            table.push(vec![
+                session.id.clone(),
+                session.scope.clone(),
+                status,
+                session.label.clone().unwrap_or_else(|| "-".to_string()),
+                table::timestamp(&session.created_at),
+                table::timestamp(&session.expires_at),
+                if session.run_only { "run-only" } else { "full" }.to_string(),
+                join_capabilities(&session.capabilities),
+                env,
+            ]);
         }
+        table.print();
     }
 
     Ok(())
@@ -160,14 +279,16 @@ fn revoke(id: &str) -> Result<()> {
         .find(|s| s.id == id)
         .ok_or_else(|| AuthyError::SessionNotFound(id.to_string()))?;
 
-    session.revoked = true;
+    session.revoke(chrono::Utc::now());
+    let event = RevocationEvent::sessions_revoked(&auth_ctx.actor_name(), [&*session]);
+    vault.record_event("session.revoke", &auth_ctx.actor_name(), Some(format!("session={}", id)));
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "session.revoke",
         None,
         &auth_ctx.actor_name(),
@@ -177,27 +298,241 @@ fn revoke(id: &str) -> Result<()> {
     )?;
 
     eprintln!("Session '{}' revoked.", id);
+    common::notify_revocation(&event);
+    Ok(())
+}
+
+/// Revoke every active session matching all of the given filters.
+fn revoke_matching(label: Option<&str>, scope: Option<&str>, older_than: Option<&str>) -> Result<()> {
+    if label.is_none() && scope.is_none() && older_than.is_none() {
+        return Err(AuthyError::Other(
+            "Provide a session ID, --label, --scope, or --older-than".into(),
+        ));
+    }
+    let now = chrono::Utc::now();
+    let cutoff = match older_than {
+        Some(age) => Some(now - session::parse_ttl(age)?),
+        None => None,
+    };
+
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    let revoked: Vec<SessionRecord> = vault
+        .sessions
+        .iter_mut()
+        .filter(|s| {
+            !s.revoked
+                && label.map_or(true, |l| s.label.as_deref() == Some(l))
+                && scope.map_or(true, |sc| s.scope == sc)
+                && cutoff.map_or(true, |c| s.created_at < c)
+        })
+        .map(|s| {
+            s.revoke(now);
+            s.clone()
+        })
+        .collect();
+
+    let filters = [
+        label.map(|l| format!("label={}", l)),
+        scope.map(|s| format!("scope={}", s)),
+        older_than.map(|a| format!("older_than={}", a)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    if revoked.is_empty() {
+        eprintln!("No active sessions match ({}).", filters);
+        return Ok(());
+    }
+
+    for s in &revoked {
+        vault.record_event(
+            "session.revoke",
+            &auth_ctx.actor_name(),
+            Some(format!("session={}, {}", s.id, filters)),
+        );
+    }
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    for s in &revoked {
+        audit::log_event(
+            &vault::audit_path()?,
+            "session.revoke",
+            None,
+            &auth_ctx.actor_name(),
+            "success",
+            Some(&format!("session={}, {}", s.id, filters)),
+            &audit_key,
+        )?;
+    }
+
+    for s in &revoked {
+        eprintln!(
+            "  {:<16} scope={:<16} label={} created={}",
+            s.id,
+            s.scope,
+            s.label.as_deref().unwrap_or("-"),
+            s.created_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    eprintln!("{} session(s) revoked ({}).", revoked.len(), filters);
+    common::notify_revocation(&RevocationEvent::sessions_revoked(
+        &auth_ctx.actor_name(),
+        &revoked,
+    ));
+    Ok(())
+}
+
+fn refresh(id: &str, ttl: Option<&str>) -> Result<()> {
+    let ttl = ttl.map(session::parse_ttl).transpose()?;
+
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    let session = vault
+        .sessions
+        .iter_mut()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AuthyError::SessionNotFound(id.to_string()))?;
+    let ttl = ttl.unwrap_or_else(|| session.default_ttl());
+    session.refresh(ttl, chrono::Utc::now())?;
+    let expires_at = session.expires_at;
+
+    vault.record_event(
+        "session.refresh",
+        &auth_ctx.actor_name(),
+        Some(format!("session={}", id)),
+    );
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        "session.refresh",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&format!("session={}, expires={}", id, expires_at.to_rfc3339())),
+        &audit_key,
+    )?;
+
+    eprintln!("Session '{}' now expires {}.", id, expires_at);
+    Ok(())
+}
+
+fn prune(older_than: Option<&str>, dry_run: bool) -> Result<()> {
+    let retention = match older_than {
+        Some(age) => age.to_string(),
+        None => SessionsConfig::load()?.retention,
+    };
+    let window = session::parse_ttl(&retention)?;
+
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+    let now = chrono::Utc::now();
+
+    if dry_run {
+        let count = vault.prunable_sessions(window, now);
+        eprintln!("{} session(s) ended more than {} ago would be pruned.", count, retention);
+        return Ok(());
+    }
+
+    let count = vault.prune_sessions(window, now);
+    if count == 0 {
+        eprintln!("No sessions ended more than {} ago.", retention);
+        return Ok(());
+    }
+
+    let detail = format!("count={}, retention={}", count, retention);
+    vault.record_event("session.prune", &auth_ctx.actor_name(), Some(detail.clone()));
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        "session.prune",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&detail),
+        &audit_key,
+    )?;
+
+    eprintln!("Pruned {} session(s) ended more than {} ago.", count, retention);
+    Ok(())
+}
+
+fn set_label(id: &str, label: Option<&str>) -> Result<()> {
+    if label.is_some_and(|l| l.trim().is_empty()) {
+        return Err(AuthyError::Other("Label cannot be empty; use --clear to remove it".into()));
+    }
+
+    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let mut vault = vault::load_vault(&key)?;
+
+    let session = vault
+        .sessions
+        .iter_mut()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AuthyError::SessionNotFound(id.to_string()))?;
+
+    session.label = label.map(|s| s.to_string());
+    vault.touch();
+    vault::save_vault(&vault, &key)?;
+
+    let material = audit::key_material(&key);
+    let audit_key = audit::derive_audit_key(&material);
+    audit::log_event(
+        &vault::audit_path()?,
+        "session.label",
+        None,
+        &auth_ctx.actor_name(),
+        "success",
+        Some(&format!("session={}, label={}", id, label.unwrap_or("-"))),
+        &audit_key,
+    )?;
+
+    match label {
+        Some(l) => eprintln!("Session '{}' labeled '{}'.", id, l),
+        None => eprintln!("Session '{}' label cleared.", id),
+    }
     Ok(())
 }
 
 fn revoke_all() -> Result<()> {
     let (key, auth_ctx) = auth::resolve_auth(true)?;
     let mut vault = vault::load_vault(&key)?;
+    let now = chrono::Utc::now();
 
-    let count = vault
+    let revoked: Vec<SessionRecord> = vault
         .sessions
         .iter_mut()
         .filter(|s| !s.revoked)
-        .map(|s| s.revoked = true)
-        .count();
+        .map(|s| {
+            s.revoke(now);
+            s.clone()
+        })
+        .collect();
+    let count = revoked.len();
 
+    vault.record_event("session.revoke_all", &auth_ctx.actor_name(), Some(format!("count={}", count)));
     vault.touch();
     vault::save_vault(&vault, &key)?;
 
     let material = audit::key_material(&key);
     let audit_key = audit::derive_audit_key(&material);
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         "session.revoke_all",
         None,
         &auth_ctx.actor_name(),
@@ -207,5 +542,11 @@ fn revoke_all() -> Result<()> {
     )?;
 
     eprintln!("{} session(s) revoked.", count);
+    if count > 0 {
+        common::notify_revocation(&RevocationEvent::sessions_revoked(
+            &auth_ctx.actor_name(),
+            &revoked,
+        ));
+    }
     Ok(())
 }
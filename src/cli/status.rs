@@ -0,0 +1,89 @@
+use authy::auth;
+use authy::error::{AuthyError, Result};
+use authy::vault::{self, Vault, VaultEvent};
+
+use crate::cli::json_output::{StatusResponse, VaultEventItem};
+
+/// Summarize the vault from its own contents. History comes from the
+/// events recorded inside the vault, so it survives audit log rotation.
+pub fn run(show_events: bool, json: bool) -> Result<()> {
+    let (key, _) = auth::resolve_auth(false)?;
+    let vault = vault::load_vault(&key)?;
+    let location = vault::storage::open()?.location();
+
+    let now = chrono::Utc::now();
+    let active_sessions = vault
+        .sessions
+        .iter()
+        .filter(|s| !s.revoked && s.expires_at > now)
+        .count();
+
+    if json {
+        let response = StatusResponse {
+            vault_path: location.clone(),
+            created: vault.created_at.to_rfc3339(),
+            modified: vault.modified_at.to_rfc3339(),
+            secrets: vault.secrets.len(),
+            policies: vault.policies.len(),
+            active_sessions,
+            last_rekey: vault.last_event("rekey").map(event_item),
+            last_policy_change: vault.last_event("policy").map(event_item),
+            last_session_change: vault.last_event("session").map(event_item),
+            events: show_events.then(|| vault.events.iter().map(event_item).collect()),
+        };
+        println!(
+            "{}",
+            serde_json::to_string(&response)
+                .map_err(|e| AuthyError::Serialization(e.to_string()))?
+        );
+        return Ok(());
+    }
+
+    println!("Vault:               {}", location);
+    println!("Created:             {}", vault.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    println!("Modified:            {}", vault.modified_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    println!("Secrets:             {}", vault.secrets.len());
+    println!("Policies:            {}", vault.policies.len());
+    println!("Active sessions:     {}", active_sessions);
+    println!("Last rekey:          {}", describe(&vault, "rekey"));
+    println!("Last policy change:  {}", describe(&vault, "policy"));
+    println!("Last session change: {}", describe(&vault, "session"));
+
+    if show_events {
+        println!();
+        if vault.events.is_empty() {
+            println!("No vault events recorded.");
+        }
+        for event in &vault.events {
+            println!(
+                "{}  {:<20} {:<24} {}",
+                event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                event.kind,
+                event.actor,
+                event.detail.as_deref().unwrap_or("")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn describe(vault: &Vault, kind: &str) -> String {
+    match vault.last_event(kind) {
+        Some(e) => format!(
+            "{} ({}{})",
+            e.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+            e.kind,
+            e.detail.as_deref().map(|d| format!(", {}", d)).unwrap_or_default()
+        ),
+        None => "never".to_string(),
+    }
+}
+
+fn event_item(event: &VaultEvent) -> VaultEventItem {
+    VaultEventItem {
+        timestamp: event.timestamp.to_rfc3339(),
+        kind: event.kind.clone(),
+        actor: event.actor.clone(),
+        detail: event.detail.clone(),
+    }
+}
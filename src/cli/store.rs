@@ -1,29 +1,87 @@
+use std::fs;
 use std::io::{self, Read};
 
+use chrono::Utc;
+
 use authy::audit;
 use authy::auth;
+use authy::config::LimitsConfig;
 use authy::error::{AuthyError, Result};
-use authy::vault::{self, secret::SecretEntry};
+use authy::policy::SecretOp;
+use authy::session::{self, Capability};
+use authy::vault::{self, names, secret::{Provenance, SecretEntry}, values};
+
+pub fn run(
+    name: &str,
+    force: bool,
+    file: Option<&str>,
+    expires: Option<&str>,
+    scope: Option<&str>,
+    allow_weak: bool,
+) -> Result<()> {
+    let expires_at = expires
+        .map(|ttl| session::parse_ttl(ttl).map(|ttl| Utc::now() + ttl))
+        .transpose()?;
+
+    let project = super::common::prefix_project()?;
+    let name = &match project {
+        Some(ref p) => p.namespaced(name),
+        None => name.to_string(),
+    };
 
-pub fn run(name: &str, force: bool) -> Result<()> {
-    let (key, auth_ctx) = auth::resolve_auth(true)?;
+    let (key, auth_ctx) = auth::resolve_auth_for(Capability::Write)?;
     let mut vault = vault::load_vault(&key)?;
+    let name = &names::secret_key(&vault, name)?;
+    let scope = super::common::listing_scope(scope, &auth_ctx)?;
+    auth::check_scope(&vault, scope.as_deref(), SecretOp::Write, name)?;
 
     if vault.secrets.contains_key(name) && !force {
         return Err(AuthyError::SecretAlreadyExists(name.to_string()));
     }
 
-    // Read secret value from stdin
-    let mut value = String::new();
-    io::stdin()
-        .read_to_string(&mut value)
-        .map_err(|e| AuthyError::Other(format!("Failed to read from stdin: {}", e)))?;
+    let mut entry = match file {
+        // Files are stored byte-for-byte, without newline trimming
+        Some(path) => {
+            let bytes = fs::read(path)
+                .map_err(|e| AuthyError::Other(format!("Failed to read {}: {}", path, e)))?;
+            if bytes.is_empty() && !allow_weak {
+                return Err(AuthyError::WeakValue {
+                    name: name.to_string(),
+                    reason: format!("{} is empty", path),
+                });
+            }
+            SecretEntry::new_binary(&bytes)
+        }
+        None => {
+            // Read secret value from stdin
+            let mut value = String::new();
+            io::stdin()
+                .read_to_string(&mut value)
+                .map_err(|e| AuthyError::Other(format!("Failed to read from stdin: {}", e)))?;
+
+            // Trim trailing newline (common when piping echo)
+            let value = value.trim_end_matches('\n');
+            if !allow_weak {
+                values::check(name, value)?;
+            }
+            SecretEntry::new(value.to_string())
+        }
+    };
+
+    entry.metadata.expires_at = expires_at;
+    let mut provenance = Provenance::new("store", &auth_ctx.actor_name());
+    if let Some(path) = file {
+        provenance = provenance.with_location(path);
+    }
+    entry.metadata.provenance = Some(provenance);
+    // Overwriting a value doesn't lift its protection or canary mark
+    entry.metadata.protected = vault.secrets.get(name).is_some_and(|e| e.metadata.protected);
+    entry.metadata.canary = vault.secrets.get(name).is_some_and(|e| e.metadata.canary);
 
-    // Trim trailing newline (common when piping echo)
-    let value = value.trim_end_matches('\n').to_string();
+    LimitsConfig::load()?.check_secret(&vault, name, &entry.value)?;
 
     let is_update = vault.secrets.contains_key(name);
-    vault.secrets.insert(name.to_string(), SecretEntry::new(value));
+    vault.secrets.insert(name.to_string(), entry);
     vault.touch();
 
     vault::save_vault(&vault, &key)?;
@@ -33,19 +91,23 @@ pub fn run(name: &str, force: bool) -> Result<()> {
     let audit_key = audit::derive_audit_key(&material);
     let op = if is_update { "update" } else { "store" };
     audit::log_event(
-        &vault::audit_path(),
+        &vault::audit_path()?,
         op,
         Some(name),
         &auth_ctx.actor_name(),
         "success",
-        None,
+        expires_at.map(|at| format!("expires={}", at.to_rfc3339())).as_deref(),
         &audit_key,
     )?;
 
+    let expiry = expires_at
+        .map(|at| format!(" (expires {})", at.format("%Y-%m-%d %H:%M UTC")))
+        .unwrap_or_default();
     eprintln!(
-        "Secret '{}' {}.",
+        "Secret '{}' {}{}.",
         name,
-        if is_update { "updated" } else { "stored" }
+        if is_update { "updated" } else { "stored" },
+        expiry
     );
     Ok(())
 }
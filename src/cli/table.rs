@@ -0,0 +1,210 @@
+//! Plain-text tables for the listing commands (`list`, `policy list`,
+//! `session list`, `audit show`).
+//!
+//! On a terminal, tables get a header row and are fitted to the terminal
+//! width by truncating the columns marked `shrink`. Piped output has no
+//! header and no truncation, so scripts see one full record per line.
+
+use std::io::IsTerminal;
+
+use chrono::{DateTime, Utc};
+
+use authy::error::{AuthyError, Result};
+
+/// Columns are never truncated below this many characters.
+const MIN_SHRINK_WIDTH: usize = 8;
+
+const GAP: &str = "  ";
+
+/// A column a listing command can show.
+pub struct Column {
+    /// Name accepted by `--columns`, and the header (uppercased).
+    pub key: &'static str,
+    /// Shown when `--columns` isn't given.
+    pub default: bool,
+    /// May be truncated to fit the terminal width.
+    pub shrink: bool,
+}
+
+impl Column {
+    pub const fn new(key: &'static str, default: bool, shrink: bool) -> Self {
+        Self { key, default, shrink }
+    }
+}
+
+pub struct Table<'a> {
+    columns: &'a [Column],
+    selected: Vec<usize>,
+    rows: Vec<Vec<String>>,
+}
+
+impl<'a> Table<'a> {
+    /// A table showing `requested` columns in the given order, or the
+    /// default ones if none were requested.
+    pub fn new(columns: &'a [Column], requested: &[String]) -> Result<Self> {
+        let selected = if requested.is_empty() {
+            (0..columns.len()).filter(|&i| columns[i].default).collect()
+        } else {
+            requested
+                .iter()
+                .map(|key| {
+                    let key = key.trim().to_ascii_lowercase();
+                    columns.iter().position(|c| c.key == key).ok_or_else(|| {
+                        AuthyError::Other(format!(
+                            "Unknown column '{}'. Available: {}",
+                            key,
+                            columns.iter().map(|c| c.key).collect::<Vec<_>>().join(", ")
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        Ok(Self {
+            columns,
+            selected,
+            rows: Vec::new(),
+        })
+    }
+
+    /// Add a row, with one cell per column in the order the columns were
+    /// defined (not the selected order).
+    pub fn push(&mut self, row: Vec<String>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Print to stdout, with a header and fitted to the width when stdout
+    /// is a terminal.
+    pub fn print(&self) {
+        let stdout = std::io::stdout();
+        let width = if stdout.is_terminal() {
+            console::Term::stdout()
+                .size_checked()
+                .map(|(_, cols)| cols as usize)
+        } else {
+            None
+        };
+        print!("{}", self.render(stdout.is_terminal(), width));
+    }
+
+    fn render(&self, header: bool, width: Option<usize>) -> String {
+        let mut widths: Vec<usize> = self
+            .selected
+            .iter()
+            .map(|&i| {
+                let cells = self.rows.iter().map(|r| console::measure_text_width(&r[i]));
+                let header_width = if header { self.columns[i].key.len() } else { 0 };
+                cells.fold(header_width, usize::max)
+            })
+            .collect();
+
+        if let Some(width) = width {
+            fit(&mut widths, &self.shrinkable(), width);
+        }
+
+        let mut out = String::new();
+        if header {
+            let titles: Vec<String> = self
+                .selected
+                .iter()
+                .map(|&i| self.columns[i].key.to_ascii_uppercase())
+                .collect();
+            out.push_str(&format_line(&titles, &widths));
+        }
+        for row in &self.rows {
+            let cells: Vec<String> = self.selected.iter().map(|&i| row[i].clone()).collect();
+            out.push_str(&format_line(&cells, &widths));
+        }
+        out
+    }
+
+    fn shrinkable(&self) -> Vec<bool> {
+        self.selected.iter().map(|&i| self.columns[i].shrink).collect()
+    }
+}
+
+/// Narrow the widest shrinkable columns, one character at a time, until
+/// the line fits in `width` or nothing more can give.
+fn fit(widths: &mut [usize], shrinkable: &[bool], width: usize) {
+    let gaps = GAP.len() * widths.len().saturating_sub(1);
+    while widths.iter().sum::<usize>() + gaps > width {
+        let widest = (0..widths.len())
+            .filter(|&i| shrinkable[i] && widths[i] > MIN_SHRINK_WIDTH)
+            .max_by_key(|&i| widths[i]);
+        match widest {
+            Some(i) => widths[i] -= 1,
+            None => break,
+        }
+    }
+}
+
+fn format_line(cells: &[String], widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (i, (cell, &width)) in cells.iter().zip(widths).enumerate() {
+        if i > 0 {
+            line.push_str(GAP);
+        }
+        if console::measure_text_width(cell) > width {
+            line.push_str(&console::truncate_str(cell, width, "…"));
+        } else {
+            line.push_str(&console::pad_str(cell, width, console::Alignment::Left, None));
+        }
+    }
+    let mut line = line.trim_end().to_string();
+    line.push('\n');
+    line
+}
+
+/// Timestamp as shown in table cells.
+pub fn timestamp(at: &DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [Column; 3] = [
+        Column::new("name", true, true),
+        Column::new("version", true, false),
+        Column::new("note", false, true),
+    ];
+
+    fn table(requested: &[&str]) -> Table<'static> {
+        let requested: Vec<String> = requested.iter().map(|s| s.to_string()).collect();
+        let mut table = Table::new(&COLUMNS, &requested).unwrap();
+        table.push(vec!["a-rather-long-secret-name".into(), "3".into(), "x".into()]);
+        table.push(vec!["short".into(), "12".into(), "".into()]);
+        table
+    }
+
+    #[test]
+    fn default_columns_without_header() {
+        assert_eq!(
+            table(&[]).render(false, None),
+            "a-rather-long-secret-name  3\nshort                      12\n"
+        );
+    }
+
+    #[test]
+    fn requested_columns_in_order_with_header() {
+        assert_eq!(
+            table(&["version", "name"]).render(true, None),
+            "VERSION  NAME\n3        a-rather-long-secret-name\n12       short\n"
+        );
+    }
+
+    #[test]
+    fn shrinks_to_width() {
+        assert_eq!(
+            table(&[]).render(true, Some(20)),
+            "NAME         VERSION\na-rather-l…  3\nshort        12\n"
+        );
+    }
+
+    #[test]
+    fn unknown_column_is_an_error() {
+        let err = Table::new(&COLUMNS, &["size".to_string()]).err().unwrap();
+        assert!(err.to_string().contains("Available: name, version, note"));
+    }
+}
@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
-use crate::error::Result;
+use crate::error::{AuthyError, Result};
+use crate::vault::Vault;
 
 /// Configuration file format (~/.authy/authy.toml).
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -13,15 +14,54 @@ pub struct Config {
     pub vault: VaultConfig,
     #[serde(default)]
     pub audit: AuditConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub cipher: CipherConfig,
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+}
+
+/// Where credentials come from when none are given in the environment.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuthConfig {
+    /// Fall back to the master credential saved in the OS keychain
+    #[serde(default)]
+    pub keyring: bool,
+    /// Give up on the passphrase prompt after this many seconds (0 or
+    /// unset waits forever). `AUTHY_PROMPT_TIMEOUT` overrides it.
+    #[serde(default)]
+    pub prompt_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultConfig {
-    /// Default auth method: "passphrase" or "keyfile"
+    /// Default auth method: "passphrase", "keyfile" or "ssh-agent"
     #[serde(default = "default_auth_method")]
     pub auth_method: String,
     /// Path to the keyfile (if auth_method is "keyfile")
     pub keyfile: Option<String>,
+    /// SSH public key whose agent signature derives the vault key
+    /// (if auth_method is "ssh-agent")
+    pub ssh_key: Option<String>,
+}
+
+impl VaultConfig {
+    /// The configured keyfile with `~` expanded.
+    pub fn expanded_keyfile(&self) -> Option<String> {
+        self.keyfile.as_deref().map(project::expand_tilde)
+    }
+
+    /// The configured SSH public key with `~` expanded.
+    pub fn expanded_ssh_key(&self) -> Option<String> {
+        self.ssh_key.as_deref().map(project::expand_tilde)
+    }
 }
 
 impl Default for VaultConfig {
@@ -29,6 +69,7 @@ impl Default for VaultConfig {
         Self {
             auth_method: default_auth_method(),
             keyfile: None,
+            ssh_key: None,
         }
     }
 }
@@ -37,12 +78,506 @@ impl Default for VaultConfig {
 pub struct AuditConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// How secret names are recorded in audit entries: "plain", "hash", or "omit"
+    #[serde(default)]
+    pub secret_names: SecretNameMode,
+    /// MAC used for new chain entries: "hmac-sha256" or "blake3"
+    #[serde(default)]
+    pub chain: ChainAlgorithm,
+    /// When entries are flushed to disk: "never", "always", or "batch"
+    #[serde(default)]
+    pub fsync: FsyncMode,
+    /// With `fsync = "batch"`, sync once every this many entries
+    #[serde(default = "default_fsync_batch")]
+    pub fsync_batch: usize,
+    /// Which events are recorded: "all", "reads", "writes", or "denials-only"
+    #[serde(default)]
+    pub level: AuditLevel,
+    /// Where recorded entries are forwarded besides the local log
+    /// (`[[audit.sinks]]`, see [`crate::audit::sinks`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<AuditSink>,
+    /// Rotate the log into a compressed archive once it reaches this many
+    /// bytes; 0 disables size-based rotation
+    #[serde(default)]
+    pub rotate_bytes: u64,
+    /// Rotate the log once its first entry is this old, e.g. "30d"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_age: Option<String>,
+    /// Ed25519 key that signs periodic checkpoints into the log; keep it
+    /// outside `~/.authy` (see [`crate::audit::checkpoint`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_key: Option<String>,
+    /// With `checkpoint_key` set, sign a checkpoint every this many entries
+    #[serde(default = "default_checkpoint_every")]
+    pub checkpoint_every: usize,
 }
 
 impl Default for AuditConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            secret_names: SecretNameMode::default(),
+            chain: ChainAlgorithm::default(),
+            fsync: FsyncMode::default(),
+            fsync_batch: default_fsync_batch(),
+            level: AuditLevel::default(),
+            sinks: Vec::new(),
+            rotate_bytes: 0,
+            rotate_age: None,
+            checkpoint_key: None,
+            checkpoint_every: default_checkpoint_every(),
+        }
+    }
+}
+
+impl AuditConfig {
+    /// The parsed `rotate_age`, if set.
+    pub fn rotate_age(&self) -> Result<Option<chrono::Duration>> {
+        self.rotate_age
+            .as_deref()
+            .map(|age| {
+                crate::session::parse_ttl(age).map_err(|e| {
+                    AuthyError::Other(format!("Invalid [audit] rotate_age '{}': {}", age, e))
+                })
+            })
+            .transpose()
     }
+
+    /// The configured checkpoint key with `~` expanded.
+    pub fn expanded_checkpoint_key(&self) -> Option<String> {
+        self.checkpoint_key.as_deref().map(project::expand_tilde)
+    }
+}
+
+/// A destination audit entries are forwarded to, as `type = "..."` in an
+/// `[[audit.sinks]]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AuditSink {
+    /// RFC 5424 messages to a Unix datagram socket (`/dev/log`) or to
+    /// `udp://host:port`.
+    Syslog {
+        #[serde(default = "default_syslog_address")]
+        address: String,
+    },
+    /// The systemd journal's native protocol.
+    Journald {
+        #[serde(default = "default_journald_socket")]
+        socket: String,
+    },
+    /// One JSON `POST` per entry; failed deliveries are queued and retried.
+    Webhook {
+        url: String,
+        #[serde(default = "default_sink_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+/// How secret names appear in audit entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretNameMode {
+    /// Record the name as-is.
+    #[default]
+    Plain,
+    /// Record a keyed hash (`sha256:<hex>`), resolvable with the master key.
+    Hash,
+    /// Leave the secret field empty.
+    Omit,
+}
+
+/// How much the audit log records. Each level keeps everything the next
+/// one does; denials and other failed outcomes are always recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditLevel {
+    /// Every event, including listings.
+    #[default]
+    All,
+    /// Mutations and reads of secret values (`get`, `run`, `export`, ...).
+    Reads,
+    /// Mutations only: stores, rotations, policy and session changes.
+    Writes,
+    /// Only denied or failed operations.
+    DenialsOnly,
+}
+
+/// MAC used to chain audit entries. Each entry records which one it was
+/// written with, so switching algorithms keeps older entries verifiable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChainAlgorithm {
+    /// HMAC-SHA256 (the original format).
+    #[default]
+    HmacSha256,
+    /// Keyed BLAKE3, cheaper per entry for high-volume logging.
+    Blake3,
+}
+
+impl ChainAlgorithm {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// When audit writes are synced to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FsyncMode {
+    /// Leave flushing to the OS.
+    #[default]
+    Never,
+    /// Sync after every entry.
+    Always,
+    /// Sync every `fsync_batch` entries.
+    Batch,
+}
+
+/// Where the encrypted vault is kept. Decryption always happens locally;
+/// remote backends only ever see ciphertext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// "local", "s3", or "http"
+    #[serde(default)]
+    pub backend: StorageBackend,
+    /// HTTP(S) URL of the vault object, or the S3 endpoint (defaults to AWS)
+    pub url: Option<String>,
+    /// S3 bucket name
+    pub bucket: Option<String>,
+    /// S3 object key
+    #[serde(default = "default_storage_key")]
+    pub key: String,
+    /// S3 region
+    #[serde(default = "default_storage_region")]
+    pub region: String,
+    /// Environment variable holding a bearer token for the HTTP backend
+    pub token_env: Option<String>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::default(),
+            url: None,
+            bucket: None,
+            key: default_storage_key(),
+            region: default_storage_region(),
+            token_env: None,
+        }
+    }
+}
+
+/// Storage backend for the encrypted vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// `~/.authy/vault.age` on the local filesystem.
+    #[default]
+    Local,
+    /// An object in S3 or an S3-compatible store.
+    S3,
+    /// A generic HTTP(S) endpoint supporting GET, HEAD, and PUT.
+    Http,
+}
+
+/// How the vault is encrypted at rest (see [`crate::vault::cipher`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherConfig {
+    /// "age" or "aws-kms"
+    #[serde(default)]
+    pub backend: CipherBackend,
+    /// KMS key ID, ARN or alias (aws-kms)
+    pub key_id: Option<String>,
+    /// KMS region (aws-kms)
+    #[serde(default = "default_storage_region")]
+    pub region: String,
+    /// KMS endpoint, for VPC endpoints or local emulators (defaults to AWS)
+    pub url: Option<String>,
+}
+
+impl Default for CipherConfig {
+    fn default() -> Self {
+        Self {
+            backend: CipherBackend::default(),
+            key_id: None,
+            region: default_storage_region(),
+            url: None,
+        }
+    }
+}
+
+/// Encryption backend for the vault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum CipherBackend {
+    /// age, with the vault passphrase or keyfile.
+    #[default]
+    Age,
+    /// age, then sealed again under a data key from AWS KMS.
+    AwsKms,
+}
+
+/// Commands and webhooks run when access is cut off (see [`crate::hooks`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Shell command run after a session is revoked or a policy is removed
+    pub on_revoke: Option<String>,
+    /// URL that receives the revocation event as a JSON POST
+    pub on_revoke_webhook: Option<String>,
+    /// Shell command run when a canary secret's value is read
+    #[serde(default)]
+    pub on_canary: Option<String>,
+    /// URL that receives the canary event as a JSON POST
+    #[serde(default)]
+    pub on_canary_webhook: Option<String>,
+    /// Seconds before a hook is abandoned
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_revoke: None,
+            on_revoke_webhook: None,
+            on_canary: None,
+            on_canary_webhook: None,
+            timeout_secs: default_hook_timeout_secs(),
+        }
+    }
+}
+
+/// How long ended sessions stay in the vault (see [`Vault::prune_sessions`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// How long expired or revoked sessions are kept, e.g. "30d"
+    #[serde(default = "default_session_retention")]
+    pub retention: String,
+    /// Prune ended sessions past the retention window whenever the vault is saved
+    #[serde(default = "default_true")]
+    pub auto_prune: bool,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            retention: default_session_retention(),
+            auto_prune: true,
+        }
+    }
+}
+
+impl SessionsConfig {
+    /// Load the session settings from the default config path.
+    pub fn load() -> Result<Self> {
+        Ok(Config::load(&crate::vault::config_path()?)?.sessions)
+    }
+
+    /// The parsed retention window.
+    pub fn retention(&self) -> Result<chrono::Duration> {
+        crate::session::parse_ttl(&self.retention).map_err(|e| {
+            AuthyError::Other(format!("Invalid [sessions] retention '{}': {}", self.retention, e))
+        })
+    }
+}
+
+/// Size and count limits enforced when secrets or sessions are written.
+/// A limit of 0 disables that check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitsConfig {
+    /// Maximum size of a single secret value, in bytes.
+    #[serde(default = "default_max_secret_bytes")]
+    pub max_secret_bytes: usize,
+    /// Maximum number of secrets in the vault.
+    #[serde(default = "default_max_secrets")]
+    pub max_secrets: usize,
+    /// Maximum number of active (unrevoked, unexpired) sessions.
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_secret_bytes: default_max_secret_bytes(),
+            max_secrets: default_max_secrets(),
+            max_sessions: default_max_sessions(),
+        }
+    }
+}
+
+impl LimitsConfig {
+    /// Load the limits from the default config path.
+    pub fn load() -> Result<Self> {
+        Ok(Config::load(&crate::vault::config_path()?)?.limits)
+    }
+
+    /// Check that storing `value` under `name` stays within the limits.
+    pub fn check_secret(&self, vault: &Vault, name: &str, value: &str) -> Result<()> {
+        if self.max_secret_bytes > 0 && value.len() > self.max_secret_bytes {
+            return Err(AuthyError::LimitExceeded(format!(
+                "secret '{}' is {} bytes (max_secret_bytes = {})",
+                name,
+                value.len(),
+                self.max_secret_bytes
+            )));
+        }
+        if self.max_secrets > 0
+            && !vault.secrets.contains_key(name)
+            && vault.secrets.len() >= self.max_secrets
+        {
+            return Err(AuthyError::LimitExceeded(format!(
+                "vault already holds {} secrets (max_secrets = {})",
+                vault.secrets.len(),
+                self.max_secrets
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that adding `count` new secrets at once stays within the limits.
+    pub fn check_new_secrets(&self, vault: &Vault, count: usize) -> Result<()> {
+        if self.max_secrets > 0 && vault.secrets.len() + count > self.max_secrets {
+            return Err(AuthyError::LimitExceeded(format!(
+                "adding {} secrets to the {} in the vault would exceed max_secrets = {}",
+                count,
+                vault.secrets.len(),
+                self.max_secrets
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that one more session stays within the limits.
+    pub fn check_session(&self, vault: &Vault) -> Result<()> {
+        if self.max_sessions == 0 {
+            return Ok(());
+        }
+        let now = chrono::Utc::now();
+        let active = vault
+            .sessions
+            .iter()
+            .filter(|s| !s.revoked && s.expires_at > now)
+            .count();
+        if active >= self.max_sessions {
+            return Err(AuthyError::LimitExceeded(format!(
+                "{} active sessions (max_sessions = {})",
+                active, self.max_sessions
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn default_max_secret_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_max_secrets() -> usize {
+    10_000
+}
+
+fn default_max_sessions() -> usize {
+    1_000
+}
+
+fn default_session_retention() -> String {
+    "30d".into()
+}
+
+/// Apply `AUTHY__SECTION__KEY=value` variables to a parsed config table.
+/// Values are read as TOML (`true`, `32`, `"x"`) and fall back to a plain
+/// string, so `AUTHY__VAULT__AUTH_METHOD=keyfile` needs no quoting.
+fn apply_env_overrides(
+    table: &mut toml::Table,
+    vars: impl Iterator<Item = (String, String)>,
+) -> Result<()> {
+    let sections = match toml::Value::try_from(Config::default()) {
+        Ok(toml::Value::Table(t)) => t,
+        _ => toml::Table::new(),
+    };
+
+    for (var, raw) in vars {
+        let path = match var.strip_prefix(ENV_OVERRIDE_PREFIX) {
+            Some(path) => path.to_lowercase(),
+            None => continue,
+        };
+        let (section, key) = match path.split_once("__") {
+            Some((section, key)) if !key.is_empty() && !key.contains("__") => (section, key),
+            _ => {
+                return Err(AuthyError::Other(format!(
+                    "{} must name a section and key, like AUTHY__VAULT__AUTH_METHOD",
+                    var
+                )))
+            }
+        };
+        if !sections.contains_key(section) {
+            return Err(AuthyError::Other(format!(
+                "{} overrides unknown config section [{}]",
+                var, section
+            )));
+        }
+
+        set_value(table, section, key, parse_value(&raw))?;
+    }
+    Ok(())
+}
+
+/// Read a raw value as TOML, falling back to a plain string.
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn set_value(table: &mut toml::Table, section: &str, key: &str, value: toml::Value) -> Result<()> {
+    match table
+        .entry(section.to_string())
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+    {
+        toml::Value::Table(t) => {
+            t.insert(key.to_string(), value);
+            Ok(())
+        }
+        _ => Err(AuthyError::Other(format!(
+            "Invalid config: [{}] is not a table",
+            section
+        ))),
+    }
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    10
+}
+
+fn default_storage_key() -> String {
+    "vault.age".to_string()
+}
+
+fn default_storage_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_syslog_address() -> String {
+    "/dev/log".to_string()
+}
+
+fn default_journald_socket() -> String {
+    "/run/systemd/journal/socket".to_string()
+}
+
+fn default_sink_timeout_secs() -> u64 {
+    5
+}
+
+fn default_fsync_batch() -> usize {
+    32
+}
+
+fn default_checkpoint_every() -> usize {
+    100
 }
 
 fn default_auth_method() -> String {
@@ -53,22 +588,86 @@ fn default_true() -> bool {
     true
 }
 
+/// Prefix for environment variables that override config keys, e.g.
+/// `AUTHY__VAULT__AUTH_METHOD=keyfile` sets `[vault] auth_method`.
+pub const ENV_OVERRIDE_PREFIX: &str = "AUTHY__";
+
 impl Config {
-    /// Load config from a path. Returns default config if file doesn't exist.
+    /// Load config from a path, then apply `AUTHY__SECTION__KEY` environment
+    /// overrides. Returns the default config (plus overrides) if the file
+    /// doesn't exist.
     pub fn load(path: &Path) -> Result<Self> {
-        if !path.exists() {
-            return Ok(Self::default());
+        if path.exists() {
+            Self::parse(&fs::read_to_string(path)?)
+        } else {
+            Self::parse("")
+        }
+    }
+
+    /// Parse config file contents, then apply environment overrides as
+    /// [`load`](Self::load) does.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut table = toml::from_str::<toml::Table>(content)
+            .map_err(|e| AuthyError::Other(format!("Invalid config: {}", e)))?;
+        apply_env_overrides(&mut table, std::env::vars())?;
+        let config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| AuthyError::Other(format!("Invalid config: {}", e)))?;
+        Ok(config)
+    }
+
+    /// Set `section.key` (e.g. `auth.keyring`) in the config file at `path`
+    /// and return the resulting config. The value is read like an
+    /// environment override; the file is only written if the result is a
+    /// valid config. Environment overrides are not written to the file.
+    pub fn set(path: &Path, name: &str, raw: &str) -> Result<Self> {
+        let content = if path.exists() {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+        let mut table = toml::from_str::<toml::Table>(&content)
+            .map_err(|e| AuthyError::Other(format!("Invalid config: {}", e)))?;
+
+        let (section, key) = match name.split_once('.') {
+            Some((section, key))
+                if !section.is_empty() && !key.is_empty() && !key.contains('.') =>
+            {
+                (section, key)
+            }
+            _ => {
+                return Err(AuthyError::Other(format!(
+                    "'{}' must name a section and key, like auth.keyring",
+                    name
+                )))
+            }
+        };
+        let known = match toml::Value::try_from(Config::default()) {
+            Ok(toml::Value::Table(t)) => t.contains_key(section),
+            _ => false,
+        };
+        if !known {
+            return Err(AuthyError::Other(format!(
+                "Unknown config section [{}]",
+                section
+            )));
+        }
+        set_value(&mut table, section, key, parse_value(raw))?;
+
+        let updated = toml::to_string_pretty(&table)
+            .map_err(|e| AuthyError::Other(format!("Config serialize error: {}", e)))?;
+        let config = Self::parse(&updated)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
         }
-        let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| crate::error::AuthyError::Other(format!("Invalid config: {}", e)))?;
+        fs::write(path, updated)?;
         Ok(config)
     }
 
     /// Save config to a path.
     pub fn save(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self)
-            .map_err(|e| crate::error::AuthyError::Other(format!("Config serialize error: {}", e)))?;
+            .map_err(|e| AuthyError::Other(format!("Config serialize error: {}", e)))?;
         if let Some(dir) = path.parent() {
             fs::create_dir_all(dir)?;
         }
@@ -1,5 +1,7 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 
 use crate::error::{AuthyError, Result};
 
@@ -13,6 +15,7 @@ use crate::error::{AuthyError, Result};
 /// uppercase = true
 /// replace_dash = "_"
 /// aliases = ["claude", "aider"]
+/// enforce_prefix = "myapp-"
 /// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProjectConfigFile {
@@ -37,6 +40,9 @@ pub struct ProjectConfig {
     /// Tool names to alias (e.g. ["claude", "aider"])
     #[serde(default)]
     pub aliases: Vec<String>,
+    /// Namespace applied to secrets stored or imported from this project,
+    /// and the default allow pattern for new policies
+    pub enforce_prefix: Option<String>,
 }
 
 const CONFIG_FILENAME: &str = ".authy.toml";
@@ -61,6 +67,12 @@ impl ProjectConfig {
             }
         }
 
+        if config.enforce_prefix.as_deref() == Some("") {
+            return Err(AuthyError::Other(
+                "enforce_prefix must not be empty in .authy.toml".to_string(),
+            ));
+        }
+
         // Validate scope is not empty
         if config.scope.is_empty() {
             return Err(AuthyError::Other(
@@ -88,6 +100,52 @@ impl ProjectConfig {
         Ok(None)
     }
 
+    /// Like [`discover`](Self::discover), but reuses an earlier result from the
+    /// cache file at `cache_path` while the mtimes of every directory walked
+    /// are unchanged (creating or removing `.authy.toml` bumps its directory's
+    /// mtime). The config itself is always re-read. Cache errors are ignored.
+    pub fn discover_cached(start_dir: &Path, cache_path: &Path) -> Result<Option<(Self, PathBuf)>> {
+        let mut cache = DiscoveryCache::read(cache_path);
+        let key = start_dir.to_string_lossy().to_string();
+
+        if let Some(entry) = cache.entries.get(&key) {
+            if entry.is_fresh() {
+                return match entry.project_dir {
+                    Some(ref dir) => {
+                        let dir = PathBuf::from(dir);
+                        Ok(Some((Self::load(&dir.join(CONFIG_FILENAME))?, dir)))
+                    }
+                    None => Ok(None),
+                };
+            }
+        }
+
+        let found = Self::discover(start_dir)?;
+
+        // Record the mtime of each directory walked, up to the one holding the config
+        let mut dirs = Vec::new();
+        let mut dir = start_dir.to_path_buf();
+        loop {
+            dirs.push((dir.to_string_lossy().to_string(), dir_mtime(&dir)));
+            if found.as_ref().is_some_and(|(_, d)| *d == dir) || !dir.pop() {
+                break;
+            }
+        }
+        if cache.entries.len() >= DISCOVERY_CACHE_MAX {
+            cache.entries.clear();
+        }
+        cache.entries.insert(
+            key,
+            DiscoveryEntry {
+                project_dir: found.as_ref().map(|(_, d)| d.to_string_lossy().to_string()),
+                dirs,
+            },
+        );
+        cache.write(cache_path);
+
+        Ok(found)
+    }
+
     /// Convenience: discover from current working directory.
     pub fn discover_from_cwd() -> Result<Option<(Self, PathBuf)>> {
         let cwd = std::env::current_dir()
@@ -95,6 +153,14 @@ impl ProjectConfig {
         Self::discover(&cwd)
     }
 
+    /// Apply `enforce_prefix` to a secret name, unless it already carries it.
+    pub fn namespaced(&self, name: &str) -> String {
+        match self.enforce_prefix.as_deref() {
+            Some(p) if !name.starts_with(p) => format!("{}{}", p, name),
+            _ => name.to_string(),
+        }
+    }
+
     /// Get replace_dash as a char.
     pub fn replace_dash_char(&self) -> Option<char> {
         self.replace_dash.as_ref().and_then(|s| s.chars().next())
@@ -111,8 +177,54 @@ impl ProjectConfig {
     }
 }
 
+/// Upper bound on cached start directories before the cache is reset.
+const DISCOVERY_CACHE_MAX: usize = 512;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DiscoveryCache {
+    entries: BTreeMap<String, DiscoveryEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DiscoveryEntry {
+    project_dir: Option<String>,
+    /// Each directory walked with its mtime (nanoseconds since the epoch).
+    dirs: Vec<(String, Option<u64>)>,
+}
+
+impl DiscoveryCache {
+    fn read(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, path: &Path) {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(content) = serde_json::to_string(self) {
+            let _ = std::fs::write(path, content);
+        }
+    }
+}
+
+impl DiscoveryEntry {
+    fn is_fresh(&self) -> bool {
+        self.dirs
+            .iter()
+            .all(|(dir, mtime)| dir_mtime(Path::new(dir)) == *mtime)
+    }
+}
+
+fn dir_mtime(dir: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(dir).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
 /// Expand leading `~` to the user's home directory.
-fn expand_tilde(path: &str) -> String {
+pub(crate) fn expand_tilde(path: &str) -> String {
     if path.starts_with("~/") || path == "~" {
         if let Some(home) = dirs::home_dir() {
             return path.replacen('~', &home.to_string_lossy(), 1);
@@ -266,4 +378,46 @@ scope = ""
         // Absolute path unchanged
         assert_eq!(expand_tilde("/absolute/path"), "/absolute/path");
     }
+
+    #[test]
+    fn test_enforce_prefix_namespacing() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join(".authy.toml");
+        fs::write(
+            &config_path,
+            "[authy]\nscope = \"myapp\"\nenforce_prefix = \"myapp-\"\n",
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(&config_path).unwrap();
+        assert_eq!(config.namespaced("db-url"), "myapp-db-url");
+        assert_eq!(config.namespaced("myapp-db-url"), "myapp-db-url");
+
+        fs::write(&config_path, "[authy]\nscope = \"myapp\"\nenforce_prefix = \"\"\n").unwrap();
+        assert!(ProjectConfig::load(&config_path).is_err());
+    }
+
+    #[test]
+    fn test_discover_cached_sees_new_config() {
+        let root = TempDir::new().unwrap();
+        let cache = root.path().join("cache.json");
+        let project = root.path().join("project");
+        let sub = project.join("src");
+        fs::create_dir_all(&sub).unwrap();
+
+        assert!(ProjectConfig::discover_cached(&sub, &cache).unwrap().is_none());
+        assert!(cache.exists());
+        // Cached miss
+        assert!(ProjectConfig::discover_cached(&sub, &cache).unwrap().is_none());
+
+        fs::write(project.join(".authy.toml"), "[authy]\nscope = \"cached\"\n").unwrap();
+        let (config, dir) = ProjectConfig::discover_cached(&sub, &cache).unwrap().unwrap();
+        assert_eq!(config.scope, "cached");
+        assert_eq!(dir, project);
+
+        // Cached hit still re-reads the config
+        fs::write(project.join(".authy.toml"), "[authy]\nscope = \"edited\"\n").unwrap();
+        let (config, _) = ProjectConfig::discover_cached(&sub, &cache).unwrap().unwrap();
+        assert_eq!(config.scope, "edited");
+    }
 }
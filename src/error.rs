@@ -1,6 +1,8 @@
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::session::Capability;
+
 #[derive(Error, Debug)]
 pub enum AuthyError {
     #[error("Vault not initialized. Run `authy init` first.")]
@@ -9,24 +11,41 @@ pub enum AuthyError {
     #[error("Vault already initialized at {0}")]
     VaultAlreadyExists(String),
 
+    #[error("Vault at {0} changed since it was read; retry the command")]
+    VaultConflict(String),
+
     #[error("Secret not found: {0}")]
     SecretNotFound(String),
 
     #[error("Secret already exists: {0} (use --force to overwrite)")]
     SecretAlreadyExists(String),
 
+    #[error("Secret expired: {0} (rotate it, or pass --allow-expired)")]
+    SecretExpired(String),
+
     #[error("Policy not found: {0}")]
     PolicyNotFound(String),
 
     #[error("Policy already exists: {0}")]
     PolicyAlreadyExists(String),
 
-    #[error("Access denied: secret '{secret}' not allowed by scope '{scope}'")]
-    AccessDenied { secret: String, scope: String },
+    #[error(
+        "Access denied: secret '{secret}' not allowed by scope '{scope}'{}",
+        .reason.as_ref().map(|r| format!(" ({})", r)).unwrap_or_default()
+    )]
+    AccessDenied {
+        secret: String,
+        scope: String,
+        /// The rule that denied it (see [`crate::policy::Decision::denial_reason`]).
+        reason: Option<String>,
+    },
 
     #[error("Authentication failed: {0}")]
     AuthFailed(String),
 
+    #[error("No passphrase entered within {0}s. Set AUTHY_KEYFILE, AUTHY_PASSPHRASE or AUTHY_TOKEN to run without a prompt.")]
+    PromptTimeout(u64),
+
     #[error("Invalid session token")]
     InvalidToken,
 
@@ -34,7 +53,6 @@ pub enum AuthyError {
     TokenExpired,
 
     #[error("Session token revoked")]
-    #[allow(dead_code)]
     TokenRevoked,
 
     #[error("Session not found: {0}")]
@@ -43,6 +61,9 @@ pub enum AuthyError {
     #[error("Write operations require master key authentication (tokens are read-only)")]
     TokenReadOnly,
 
+    #[error("Session token lacks the '{0}' capability (tokens are read-only unless created with `--can {0}`)")]
+    MissingCapability(Capability),
+
     #[error("Run-only mode: secret values cannot be read directly. Use `authy run` to inject secrets into a subprocess.")]
     RunOnly,
 
@@ -58,9 +79,36 @@ pub enum AuthyError {
     #[error("Audit chain integrity violation at entry {0}")]
     AuditChainBroken(usize),
 
+    #[error("Audit checkpoint at entry {entry} is invalid: {reason}")]
+    AuditCheckpointInvalid { entry: usize, reason: String },
+
+    #[error("Audit archive {0} is missing, so the entries before it can't be verified")]
+    AuditArchiveMissing(String),
+
+    #[error("Vault corrupted: {reason}{}", backup_hint(.backup))]
+    VaultCorrupted {
+        reason: String,
+        backup: Option<String>,
+    },
+
+    #[error("Limit exceeded: {0}. Raise it under [limits] in authy.toml.")]
+    LimitExceeded(String),
+
+    #[error("Approval required: {0} (pass --approve-with <keyfile>)")]
+    ApprovalRequired(String),
+
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+
+    #[error("Refusing to store '{name}': {reason} (pass --allow-weak to store it anyway)")]
+    WeakValue { name: String, reason: String },
+
     #[error("Invalid keyfile: {0}")]
     InvalidKeyfile(String),
 
+    #[error("Could not determine the authy directory: HOME is not set. Set HOME or AUTHY_HOME.")]
+    HomeNotFound,
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -68,60 +116,196 @@ pub enum AuthyError {
     Other(String),
 }
 
-impl AuthyError {
-    /// Return a typed exit code for this error category.
+fn backup_hint(backup: &Option<String>) -> String {
+    match backup {
+        Some(path) => format!(". Latest backup: {}", path),
+        None => ". No backup is available.".into(),
+    }
+}
+
+/// Stable, machine-readable error category.
+///
+/// Each variant maps to a fixed string code (as emitted in `--json` errors and
+/// MCP results) and a process exit code. Several [`AuthyError`] variants share
+/// a code — e.g. missing secrets, policies, and sessions are all `not_found`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    VaultNotInitialized,
+    AlreadyExists,
+    NotFound,
+    AccessDenied,
+    AuthFailed,
+    InvalidToken,
+    TokenExpired,
+    TokenRevoked,
+    TokenReadOnly,
+    RunOnly,
+    EncryptionError,
+    DecryptionError,
+    SerializationError,
+    AuditChainBroken,
+    VaultCorrupted,
+    VaultConflict,
+    LimitExceeded,
+    InvalidName,
+    InvalidKeyfile,
+    IoError,
+    Error,
+}
+
+impl ErrorCode {
+    /// Every error code, in exit-code order. Useful for generating tables in bindings.
+    pub const ALL: [ErrorCode; 21] = [
+        ErrorCode::EncryptionError,
+        ErrorCode::SerializationError,
+        ErrorCode::AuditChainBroken,
+        ErrorCode::VaultCorrupted,
+        ErrorCode::VaultConflict,
+        ErrorCode::LimitExceeded,
+        ErrorCode::InvalidName,
+        ErrorCode::IoError,
+        ErrorCode::Error,
+        ErrorCode::AuthFailed,
+        ErrorCode::DecryptionError,
+        ErrorCode::InvalidKeyfile,
+        ErrorCode::NotFound,
+        ErrorCode::AccessDenied,
+        ErrorCode::TokenReadOnly,
+        ErrorCode::RunOnly,
+        ErrorCode::AlreadyExists,
+        ErrorCode::InvalidToken,
+        ErrorCode::TokenExpired,
+        ErrorCode::TokenRevoked,
+        ErrorCode::VaultNotInitialized,
+    ];
+
+    /// The stable string identifier for this code.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::VaultNotInitialized => "vault_not_initialized",
+            ErrorCode::AlreadyExists => "already_exists",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::AccessDenied => "access_denied",
+            ErrorCode::AuthFailed => "auth_failed",
+            ErrorCode::InvalidToken => "invalid_token",
+            ErrorCode::TokenExpired => "token_expired",
+            ErrorCode::TokenRevoked => "token_revoked",
+            ErrorCode::TokenReadOnly => "token_read_only",
+            ErrorCode::RunOnly => "run_only",
+            ErrorCode::EncryptionError => "encryption_error",
+            ErrorCode::DecryptionError => "decryption_error",
+            ErrorCode::SerializationError => "serialization_error",
+            ErrorCode::AuditChainBroken => "audit_chain_broken",
+            ErrorCode::VaultCorrupted => "vault_corrupted",
+            ErrorCode::VaultConflict => "vault_conflict",
+            ErrorCode::LimitExceeded => "limit_exceeded",
+            ErrorCode::InvalidName => "invalid_name",
+            ErrorCode::InvalidKeyfile => "invalid_keyfile",
+            ErrorCode::IoError => "io_error",
+            ErrorCode::Error => "error",
+        }
+    }
+
+    /// The process exit code for this category.
     pub fn exit_code(&self) -> i32 {
         match self {
-            AuthyError::VaultNotInitialized => 7,
-            AuthyError::VaultAlreadyExists(_) => 5,
-            AuthyError::SecretNotFound(_) => 3,
-            AuthyError::SecretAlreadyExists(_) => 5,
-            AuthyError::PolicyNotFound(_) => 3,
-            AuthyError::PolicyAlreadyExists(_) => 5,
-            AuthyError::AccessDenied { .. } => 4,
-            AuthyError::AuthFailed(_) => 2,
-            AuthyError::InvalidToken => 6,
-            AuthyError::TokenExpired => 6,
-            AuthyError::TokenRevoked => 6,
-            AuthyError::SessionNotFound(_) => 3,
-            AuthyError::TokenReadOnly => 4,
-            AuthyError::RunOnly => 4,
-            AuthyError::Encryption(_) => 1,
-            AuthyError::Decryption(_) => 2,
-            AuthyError::Serialization(_) => 1,
-            AuthyError::AuditChainBroken(_) => 1,
-            AuthyError::InvalidKeyfile(_) => 2,
-            AuthyError::Io(_) => 1,
-            AuthyError::Other(_) => 1,
+            ErrorCode::VaultNotInitialized => 7,
+            ErrorCode::AlreadyExists => 5,
+            ErrorCode::NotFound => 3,
+            ErrorCode::AccessDenied => 4,
+            ErrorCode::AuthFailed => 2,
+            ErrorCode::InvalidToken => 6,
+            ErrorCode::TokenExpired => 6,
+            ErrorCode::TokenRevoked => 6,
+            ErrorCode::TokenReadOnly => 4,
+            ErrorCode::RunOnly => 4,
+            ErrorCode::EncryptionError => 1,
+            ErrorCode::DecryptionError => 2,
+            ErrorCode::SerializationError => 1,
+            ErrorCode::AuditChainBroken => 1,
+            ErrorCode::VaultCorrupted => 1,
+            ErrorCode::VaultConflict => 1,
+            ErrorCode::LimitExceeded => 1,
+            ErrorCode::InvalidName => 1,
+            ErrorCode::InvalidKeyfile => 2,
+            ErrorCode::IoError => 1,
+            ErrorCode::Error => 1,
         }
     }
 
-    /// Return a string error code identifier.
-    pub fn error_code(&self) -> &'static str {
+    /// Look up a code by its string identifier.
+    pub fn from_code_str(code: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.as_str() == code)
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ErrorCode {
+    type Err = AuthyError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_code_str(s)
+            .ok_or_else(|| AuthyError::Other(format!("Unknown error code: {}", s)))
+    }
+}
+
+impl AuthyError {
+    /// Return the typed error category.
+    pub fn code(&self) -> ErrorCode {
         match self {
-            AuthyError::VaultNotInitialized => "vault_not_initialized",
-            AuthyError::VaultAlreadyExists(_) => "already_exists",
-            AuthyError::SecretNotFound(_) => "not_found",
-            AuthyError::SecretAlreadyExists(_) => "already_exists",
-            AuthyError::PolicyNotFound(_) => "not_found",
-            AuthyError::PolicyAlreadyExists(_) => "already_exists",
-            AuthyError::AccessDenied { .. } => "access_denied",
-            AuthyError::AuthFailed(_) => "auth_failed",
-            AuthyError::InvalidToken => "invalid_token",
-            AuthyError::TokenExpired => "token_expired",
-            AuthyError::TokenRevoked => "token_revoked",
-            AuthyError::SessionNotFound(_) => "not_found",
-            AuthyError::TokenReadOnly => "token_read_only",
-            AuthyError::RunOnly => "run_only",
-            AuthyError::Encryption(_) => "encryption_error",
-            AuthyError::Decryption(_) => "decryption_error",
-            AuthyError::Serialization(_) => "serialization_error",
-            AuthyError::AuditChainBroken(_) => "audit_chain_broken",
-            AuthyError::InvalidKeyfile(_) => "invalid_keyfile",
-            AuthyError::Io(_) => "io_error",
-            AuthyError::Other(_) => "error",
+            AuthyError::VaultNotInitialized => ErrorCode::VaultNotInitialized,
+            AuthyError::VaultAlreadyExists(_) => ErrorCode::AlreadyExists,
+            AuthyError::VaultConflict(_) => ErrorCode::VaultConflict,
+            AuthyError::SecretNotFound(_) => ErrorCode::NotFound,
+            AuthyError::SecretAlreadyExists(_) => ErrorCode::AlreadyExists,
+            AuthyError::SecretExpired(_) => ErrorCode::AccessDenied,
+            AuthyError::PolicyNotFound(_) => ErrorCode::NotFound,
+            AuthyError::PolicyAlreadyExists(_) => ErrorCode::AlreadyExists,
+            AuthyError::AccessDenied { .. } => ErrorCode::AccessDenied,
+            AuthyError::AuthFailed(_) => ErrorCode::AuthFailed,
+            AuthyError::PromptTimeout(_) => ErrorCode::AuthFailed,
+            AuthyError::InvalidToken => ErrorCode::InvalidToken,
+            AuthyError::TokenExpired => ErrorCode::TokenExpired,
+            AuthyError::TokenRevoked => ErrorCode::TokenRevoked,
+            AuthyError::SessionNotFound(_) => ErrorCode::NotFound,
+            AuthyError::TokenReadOnly => ErrorCode::TokenReadOnly,
+            AuthyError::MissingCapability(capability) => match capability {
+                Capability::Write | Capability::Rotate => ErrorCode::TokenReadOnly,
+                Capability::Read | Capability::List => ErrorCode::AccessDenied,
+            },
+            AuthyError::RunOnly => ErrorCode::RunOnly,
+            AuthyError::Encryption(_) => ErrorCode::EncryptionError,
+            AuthyError::Decryption(_) => ErrorCode::DecryptionError,
+            AuthyError::Serialization(_) => ErrorCode::SerializationError,
+            AuthyError::AuditChainBroken(_) => ErrorCode::AuditChainBroken,
+            AuthyError::AuditCheckpointInvalid { .. } => ErrorCode::AuditChainBroken,
+            AuthyError::AuditArchiveMissing(_) => ErrorCode::AuditChainBroken,
+            AuthyError::VaultCorrupted { .. } => ErrorCode::VaultCorrupted,
+            AuthyError::LimitExceeded(_) => ErrorCode::LimitExceeded,
+            AuthyError::ApprovalRequired(_) => ErrorCode::AccessDenied,
+            AuthyError::InvalidName(_) => ErrorCode::InvalidName,
+            AuthyError::WeakValue { .. } => ErrorCode::Error,
+            AuthyError::InvalidKeyfile(_) => ErrorCode::InvalidKeyfile,
+            AuthyError::HomeNotFound => ErrorCode::Error,
+            AuthyError::Io(_) => ErrorCode::IoError,
+            AuthyError::Other(_) => ErrorCode::Error,
         }
     }
+
+    /// Return a typed exit code for this error category.
+    pub fn exit_code(&self) -> i32 {
+        self.code().exit_code()
+    }
+
+    /// Return a string error code identifier.
+    pub fn error_code(&self) -> &'static str {
+        self.code().as_str()
+    }
 }
 
 /// JSON error response for --json mode.
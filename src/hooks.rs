@@ -0,0 +1,258 @@
+//! Revocation hooks: a command and/or webhook run after a session is revoked
+//! or a policy is removed, so external systems (e.g. an agent container that
+//! holds the token) can react right away instead of on their next failed call.
+//!
+//! Canary hooks run the same way when the value of a secret marked as a
+//! canary (a planted honeytoken) is handed out.
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::audit;
+use crate::config::{Config, HooksConfig};
+use crate::error::{AuthyError, Result};
+use crate::session::SessionRecord;
+use crate::vault::{self, Vault};
+
+/// What was cut off, as passed to the hook command (via `AUTHY_*` env vars)
+/// and posted to the webhook (as JSON). Never contains tokens or secrets.
+#[derive(Debug, Clone, Serialize)]
+pub struct RevocationEvent {
+    /// `session.revoke` or `policy.remove`
+    pub event: String,
+    pub actor: String,
+    /// Session ids that lost access.
+    pub sessions: Vec<String>,
+    /// Scopes (policy names) affected.
+    pub scopes: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl RevocationEvent {
+    /// Sessions that were just revoked.
+    pub fn sessions_revoked<'a>(
+        actor: &str,
+        sessions: impl IntoIterator<Item = &'a SessionRecord>,
+    ) -> Self {
+        let mut ids = Vec::new();
+        let mut scopes = Vec::new();
+        for s in sessions {
+            ids.push(s.id.clone());
+            if !scopes.contains(&s.scope) {
+                scopes.push(s.scope.clone());
+            }
+        }
+        Self {
+            event: "session.revoke".to_string(),
+            actor: actor.to_string(),
+            sessions: ids,
+            scopes,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// A removed policy, with the still-active sessions that were scoped to it.
+    pub fn policy_removed(actor: &str, policy: &str, vault: &Vault) -> Self {
+        let now = Utc::now();
+        Self {
+            event: "policy.remove".to_string(),
+            actor: actor.to_string(),
+            sessions: vault
+                .sessions
+                .iter()
+                .filter(|s| s.scope == policy && !s.revoked && s.expires_at > now)
+                .map(|s| s.id.clone())
+                .collect(),
+            scopes: vec![policy.to_string()],
+            timestamp: now,
+        }
+    }
+}
+
+/// A canary secret's value was handed out, as passed to the `on_canary`
+/// command and posted to `on_canary_webhook`. Never contains the value.
+#[derive(Debug, Clone, Serialize)]
+pub struct CanaryEvent {
+    /// Always `secret.canary`
+    pub event: String,
+    pub secret: String,
+    /// What handed the value out: `get`, `run`, `env_export`, `export`, ...
+    pub operation: String,
+    pub actor: String,
+    /// Policy the read went through, if any.
+    pub scope: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Alert on every canary among `names`, whose values `operation` just
+/// handed out: an audit entry with outcome `alert` (kept at every audit
+/// level) and the `on_canary` hooks. Nothing is returned or printed, so the
+/// reader isn't told they tripped a canary; a hook failure is noted in the
+/// audit entry instead.
+pub fn trip_canaries<'a>(
+    vault: &Vault,
+    names: impl IntoIterator<Item = &'a str>,
+    operation: &str,
+    actor: &str,
+    scope: Option<&str>,
+    audit_key: &[u8],
+) {
+    let tripped: Vec<&str> = names
+        .into_iter()
+        .filter(|name| vault.secrets.get(*name).is_some_and(|e| e.metadata.canary))
+        .collect();
+    if tripped.is_empty() {
+        return;
+    }
+    let config = vault::config_path()
+        .and_then(|path| Config::load(&path))
+        .map(|c| c.hooks)
+        .unwrap_or_default();
+
+    for name in tripped {
+        let event = CanaryEvent {
+            event: "secret.canary".to_string(),
+            secret: name.to_string(),
+            operation: operation.to_string(),
+            actor: actor.to_string(),
+            scope: scope.map(String::from),
+            timestamp: Utc::now(),
+        };
+        let mut detail = format!("op={}", operation);
+        if let Some(scope) = scope {
+            detail.push_str(&format!(", scope={}", scope));
+        }
+        if let Err(e) = run_canary_hooks(&config, &event) {
+            detail.push_str(&format!(", hook_error={}", e));
+        }
+        let _ = vault::audit_path().and_then(|path| {
+            audit::log_event(&path, "canary", Some(name), actor, "alert", Some(&detail), audit_key)
+        });
+    }
+}
+
+/// Run the hooks configured in `[hooks]`, if any. The revocation itself has
+/// already been saved; an error here only means the hook didn't succeed.
+pub fn on_revocation(event: &RevocationEvent) -> Result<()> {
+    let config = Config::load(&vault::config_path()?)?.hooks;
+    run_hooks(&config, event)
+}
+
+/// Run the command and webhook from `config`, reporting every failure.
+pub fn run_hooks(config: &HooksConfig, event: &RevocationEvent) -> Result<()> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let mut failures = Vec::new();
+
+    if let Some(ref command) = config.on_revoke {
+        let env = [
+            ("AUTHY_EVENT", event.event.clone()),
+            ("AUTHY_ACTOR", event.actor.clone()),
+            ("AUTHY_SESSIONS", event.sessions.join(" ")),
+            ("AUTHY_SCOPES", event.scopes.join(" ")),
+        ];
+        if let Err(e) = run_command(command, &env, timeout) {
+            failures.push(format!("on_revoke: {}", e));
+        }
+    }
+    if let Some(ref url) = config.on_revoke_webhook {
+        if let Err(e) = post_webhook(url, event, timeout) {
+            failures.push(format!("on_revoke_webhook: {}", e));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(AuthyError::Other(format!(
+            "Revocation hook failed ({})",
+            failures.join("; ")
+        )))
+    }
+}
+
+/// Run the `on_canary` command and webhook from `config`, reporting every
+/// failure.
+pub fn run_canary_hooks(config: &HooksConfig, event: &CanaryEvent) -> Result<()> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let mut failures = Vec::new();
+
+    if let Some(ref command) = config.on_canary {
+        let env = [
+            ("AUTHY_EVENT", event.event.clone()),
+            ("AUTHY_SECRET", event.secret.clone()),
+            ("AUTHY_OPERATION", event.operation.clone()),
+            ("AUTHY_ACTOR", event.actor.clone()),
+            ("AUTHY_SCOPES", event.scope.clone().unwrap_or_default()),
+        ];
+        if let Err(e) = run_command(command, &env, timeout) {
+            failures.push(format!("on_canary: {}", e));
+        }
+    }
+    if let Some(ref url) = config.on_canary_webhook {
+        if let Err(e) = post_webhook(url, event, timeout) {
+            failures.push(format!("on_canary_webhook: {}", e));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(AuthyError::Other(format!(
+            "Canary hook failed ({})",
+            failures.join("; ")
+        )))
+    }
+}
+
+fn run_command(command: &str, env: &[(&str, String)], timeout: Duration) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", command]);
+        c
+    };
+    let mut child = cmd
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| AuthyError::Other(format!("failed to start: {}", e)))?;
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(AuthyError::Other(format!("exited with {}", status)))
+            };
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AuthyError::Other(format!(
+                "timed out after {}s",
+                timeout.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn post_webhook(url: &str, event: &impl Serialize, timeout: Duration) -> Result<()> {
+    let body =
+        serde_json::to_string(event).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+    ureq::post(url)
+        .timeout(timeout)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map_err(|e| AuthyError::Other(e.to_string()))?;
+    Ok(())
+}
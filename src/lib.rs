@@ -18,11 +18,16 @@
 pub mod api;
 pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod config;
 pub mod error;
+pub mod hooks;
 pub mod mcp;
 pub mod policy;
 pub mod session;
 pub mod subprocess;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod timings;
 pub mod types;
 pub mod vault;
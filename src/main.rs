@@ -1,29 +1,101 @@
 mod cli;
 mod tui;
 
-use authy::error;
+use authy::{audit, auth, error};
 use clap::Parser;
 use cli::{Cli, Commands};
 
 fn main() {
     let cli = Cli::parse();
     let json = cli.json;
+    if cli.timings {
+        authy::timings::enable();
+    }
+    let colors = cli.color.apply();
 
     let result = match &cli.command {
         Commands::Init {
             generate_keyfile,
+            piv,
+            ssh_key,
             passphrase,
-        } => cli::init::run(passphrase.clone(), generate_keyfile.clone()),
+            dry_run,
+        } => {
+            if *dry_run {
+                cli::init::dry_run(
+                    passphrase.is_some(),
+                    generate_keyfile.as_deref(),
+                    piv.as_deref(),
+                    ssh_key.as_deref(),
+                    json,
+                )
+            } else {
+                cli::init::run(
+                    passphrase.clone(),
+                    generate_keyfile.clone(),
+                    piv.as_deref(),
+                    ssh_key.as_deref(),
+                )
+            }
+        }
 
-        Commands::Store { name, force } => cli::store::run(name, *force),
+        Commands::Store {
+            name,
+            force,
+            file,
+            expires,
+            scope,
+            allow_weak,
+        } => cli::store::run(
+            name,
+            *force,
+            file.as_deref(),
+            expires.as_deref(),
+            scope.as_deref(),
+            *allow_weak,
+        ),
 
-        Commands::Get { name, scope } => cli::get::run(name, scope.as_deref(), json),
+        Commands::Generate {
+            name,
+            scope,
+            length,
+            force,
+        } => cli::generate::run(name, scope.as_deref(), *length, *force),
 
-        Commands::List { scope } => cli::list::run(scope.as_deref(), json),
+        Commands::Get {
+            name,
+            scope,
+            fallback,
+            default,
+            out,
+            allow_expired,
+        } => cli::get::run(
+            name,
+            fallback,
+            default.as_deref(),
+            scope.as_deref(),
+            out.as_deref(),
+            *allow_expired,
+            json,
+        ),
+
+        Commands::List { scope, columns } => cli::list::run(scope.as_deref(), columns, json),
+
+        Commands::Expiring { within, scope } => {
+            cli::expiring::run(within, scope.as_deref(), json)
+        }
 
-        Commands::Remove { name } => cli::remove::run(name),
+        Commands::Remove {
+            name,
+            scope,
+            approve_with,
+        } => cli::remove::run(name, scope.as_deref(), approve_with.as_deref()),
 
-        Commands::Rotate { name } => cli::rotate::run(name),
+        Commands::Rotate {
+            name,
+            scope,
+            allow_weak,
+        } => cli::rotate::run(name, scope.as_deref(), *allow_weak),
 
         Commands::Policy { command } => cli::policy::run(command, json),
 
@@ -34,8 +106,16 @@ fn main() {
             uppercase,
             replace_dash,
             prefix,
+            summary,
             command,
-        } => cli::run::run(scope.as_deref(), *uppercase, *replace_dash, prefix.clone(), command),
+        } => cli::run::run(
+            scope.as_deref(),
+            *uppercase,
+            *replace_dash,
+            prefix.clone(),
+            *summary,
+            command,
+        ),
 
         Commands::Env {
             scope,
@@ -53,10 +133,15 @@ fn main() {
             tag,
             path,
             mount,
+            filter,
+            all,
+            pid,
+            identity,
             keep_names,
             prefix,
             force,
             dry_run,
+            yes,
         } => cli::import::run(
             file.as_deref(),
             from.as_ref(),
@@ -64,10 +149,15 @@ fn main() {
             tag.as_deref(),
             path.as_deref(),
             mount,
+            filter.as_deref(),
+            *all,
+            *pid,
+            identity.as_deref(),
             *keep_names,
             prefix.as_deref(),
             *force,
             *dry_run,
+            *yes,
         ),
 
         Commands::Export {
@@ -76,14 +166,48 @@ fn main() {
             uppercase,
             replace_dash,
             prefix,
-        } => cli::export::run(format, scope.as_deref(), *uppercase, *replace_dash, prefix.clone()),
+            tag,
+            name_match,
+            secrets_dir,
+            age_recipient,
+        } => cli::export::run(
+            format,
+            scope.as_deref(),
+            *uppercase,
+            *replace_dash,
+            prefix.clone(),
+            tag.as_deref(),
+            name_match.as_deref(),
+            secrets_dir.as_deref(),
+            age_recipient,
+        ),
+
+        Commands::Push {
+            to,
+            mount,
+            path,
+            gpg_id,
+            scope,
+            name_match,
+            dry_run,
+        } => cli::push::run(
+            to,
+            mount,
+            path.as_deref(),
+            gpg_id,
+            scope.as_deref(),
+            name_match.as_deref(),
+            *dry_run,
+        ),
 
         Commands::Audit { command } => cli::audit::run(command, json),
 
         Commands::Config { command } => cli::config::run(command),
 
-        Commands::ProjectInfo { field, dir } => {
-            cli::project_info::run(field.as_deref(), dir.as_deref(), json)
+        Commands::Status { events } => cli::status::run(*events, json),
+
+        Commands::ProjectInfo { field, dir, cached } => {
+            cli::project_info::run(field.as_deref(), dir.as_deref(), *cached, json)
         }
 
         Commands::Alias {
@@ -91,10 +215,17 @@ fn main() {
             shell,
             from_project,
             cleanup,
+            list,
             tools,
-        } => cli::alias::run(scope.as_deref(), shell, *from_project, *cleanup, tools),
+        } => {
+            if *list {
+                cli::alias::run_list(json)
+            } else {
+                cli::alias::run(scope.as_deref(), shell, *from_project, *cleanup, tools)
+            }
+        }
 
-        Commands::Hook { shell } => cli::hook::run(shell),
+        Commands::Hook { shell, cached } => cli::hook::run(shell, *cached),
 
         Commands::Resolve {
             file,
@@ -106,18 +237,50 @@ fn main() {
             generate_keyfile,
             to_passphrase,
             new_keyfile,
+            approve_with,
         } => cli::rekey::run(
             generate_keyfile.as_deref(),
             *to_passphrase,
             new_keyfile.as_deref(),
+            approve_with.as_deref(),
         ),
 
-        Commands::Serve { mcp } => cli::serve::run(*mcp),
+        Commands::Protect {
+            name,
+            policy,
+            off,
+            approve_with,
+        } => cli::protect::run(name, *policy, *off, approve_with.as_deref()),
+
+        Commands::Canary { name, off, list } => cli::canary::run(name.as_deref(), *off, *list),
+
+        Commands::LintValues { strict } => cli::lint_values::run(*strict, json),
 
-        Commands::Admin { keyfile } => cli::admin::run(keyfile.clone()),
+        Commands::Approver { command } => cli::approver::run(command),
+
+        Commands::Serve { mcp, cache_ttl } => cli::serve::run(*mcp, cache_ttl.as_deref()),
+
+        Commands::Agent {
+            stdio,
+            scope,
+            dir,
+            cache_ttl,
+        } => {
+            cli::agent::run(*stdio, scope.as_deref(), dir.as_deref(), cache_ttl.as_deref())
+        }
+
+        Commands::Backup { command } => cli::backup::run(command, json),
+
+        Commands::Admin { keyfile } => cli::admin::run(keyfile.clone(), colors),
     };
 
+    authy::timings::report();
+
     if let Err(e) = result {
+        // Rejected credentials can't reach the master-keyed audit log
+        if audit::auth_events::is_auth_failure(&e) {
+            let _ = audit::auth_events::record_failure(auth::credential_method(), &e);
+        }
         if json {
             let json_err = error::JsonError::from_error(&e);
             eprintln!(
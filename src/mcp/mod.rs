@@ -6,11 +6,15 @@
 pub mod tools;
 
 use std::io::{BufRead, Write};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::api::AuthyClient;
+use crate::vault::cache::ReadCache;
 
 // ── JSON-RPC 2.0 types ──────────────────────────────────────────
 
@@ -67,61 +71,247 @@ impl JsonRpcResponse {
     }
 }
 
+// ── Limits ───────────────────────────────────────────────────────
+
+/// Resource limits applied by [`McpServer::run`].
+#[derive(Debug, Clone)]
+pub struct McpLimits {
+    /// Maximum size of a single request line in bytes. Longer lines are
+    /// discarded without being buffered and answered with `-32600`.
+    pub max_line_bytes: usize,
+    /// Maximum number of requests accepted in one JSON-RPC batch.
+    pub max_batch_len: usize,
+    /// Maximum wall-clock time for a single read-only `tools/call`. `None`
+    /// disables it. A timed-out call keeps running in the background, so
+    /// tools that change the vault are never timed out: their answer would
+    /// say nothing about whether the change was made.
+    pub request_timeout: Option<Duration>,
+}
+
+impl Default for McpLimits {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: 1024 * 1024,
+            max_batch_len: 64,
+            request_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// Outcome of reading one bounded line from the input.
+enum LineRead {
+    Line(Vec<u8>),
+    TooLong,
+    Eof,
+}
+
+/// Read up to and including the next `\n`, never buffering more than `max`
+/// bytes. The remainder of an oversized line is consumed and dropped.
+fn read_bounded_line<R: BufRead>(reader: &mut R, max: usize) -> std::io::Result<LineRead> {
+    let mut line = Vec::new();
+    let mut too_long = false;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(if too_long {
+                LineRead::TooLong
+            } else if line.is_empty() {
+                LineRead::Eof
+            } else {
+                LineRead::Line(line)
+            });
+        }
+
+        let (chunk, done) = match available.iter().position(|b| *b == b'\n') {
+            Some(i) => (&available[..i], i + 1),
+            None => (available, available.len()),
+        };
+        let found_newline = done > chunk.len();
+
+        if !too_long {
+            if line.len() + chunk.len() > max {
+                too_long = true;
+                line = Vec::new();
+            } else {
+                line.extend_from_slice(chunk);
+            }
+        }
+        reader.consume(done);
+
+        if found_newline {
+            return Ok(if too_long {
+                LineRead::TooLong
+            } else {
+                LineRead::Line(line)
+            });
+        }
+    }
+}
+
 // ── MCP Server ───────────────────────────────────────────────────
 
 /// MCP server that dispatches JSON-RPC requests to the AuthyClient API.
 pub struct McpServer {
-    client: Option<AuthyClient>,
+    client: Option<Arc<AuthyClient>>,
+    limits: McpLimits,
+    cache: Option<Arc<ReadCache>>,
 }
 
 impl McpServer {
     pub fn new(client: Option<AuthyClient>) -> Self {
-        Self { client }
+        Self {
+            client: client.map(Arc::new),
+            limits: McpLimits::default(),
+            cache: None,
+        }
+    }
+
+    /// Override the default resource limits.
+    pub fn with_limits(mut self, limits: McpLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Serve `get_secret` from an in-memory cache for `ttl` after each read.
+    /// Writes through this server clear the cache; changes made by other
+    /// processes (and token revocations) show up once entries expire.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache = Some(Arc::new(ReadCache::new(ttl)));
+        self
     }
 
     /// Run the server read loop on the given reader/writer pair.
     ///
     /// Reads line-delimited JSON-RPC from `reader`, dispatches, and writes
     /// responses to `writer`. Returns when the reader reaches EOF.
-    pub fn run<R: BufRead, W: Write>(&self, reader: R, mut writer: W) -> std::io::Result<()> {
-        for line in reader.lines() {
-            let line = line?;
+    pub fn run<R: BufRead, W: Write>(&self, mut reader: R, mut writer: W) -> std::io::Result<()> {
+        loop {
+            let raw = match read_bounded_line(&mut reader, self.limits.max_line_bytes)? {
+                LineRead::Eof => break,
+                LineRead::TooLong => {
+                    let resp = JsonRpcResponse::error(
+                        None,
+                        -32600,
+                        format!(
+                            "Request exceeds maximum size of {} bytes",
+                            self.limits.max_line_bytes
+                        ),
+                    );
+                    Self::write_json(&mut writer, &resp)?;
+                    continue;
+                }
+                LineRead::Line(raw) => raw,
+            };
+
+            let line = String::from_utf8_lossy(&raw);
             let line = line.trim();
             if line.is_empty() {
                 continue;
             }
 
-            let request: JsonRpcRequest = match serde_json::from_str(line) {
-                Ok(r) => r,
+            let message: Value = match serde_json::from_str(line) {
+                Ok(v) => v,
                 Err(e) => {
                     let resp =
                         JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e));
-                    Self::write_response(&mut writer, &resp)?;
+                    Self::write_json(&mut writer, &resp)?;
                     continue;
                 }
             };
 
-            // Notifications (no id) produce no response
-            let is_notification = request.id.is_none();
-            let response = self.dispatch(&request);
-
-            if !is_notification {
-                if let Some(resp) = response {
-                    Self::write_response(&mut writer, &resp)?;
+            match message {
+                Value::Array(batch) => {
+                    if let Some(responses) = self.handle_batch(batch) {
+                        Self::write_json(&mut writer, &responses)?;
+                    }
+                }
+                single => {
+                    if let Some(resp) = self.handle_message(single) {
+                        Self::write_json(&mut writer, &resp)?;
+                    }
                 }
             }
         }
         Ok(())
     }
 
-    fn write_response<W: Write>(writer: &mut W, resp: &JsonRpcResponse) -> std::io::Result<()> {
-        let json = serde_json::to_string(resp)
+    /// Handle a JSON-RPC batch. Returns `None` when every entry was a
+    /// notification (the spec requires no output in that case).
+    fn handle_batch(&self, batch: Vec<Value>) -> Option<Value> {
+        if batch.is_empty() {
+            let resp = JsonRpcResponse::error(None, -32600, "Invalid Request: empty batch");
+            return serde_json::to_value(resp).ok();
+        }
+        if batch.len() > self.limits.max_batch_len {
+            let resp = JsonRpcResponse::error(
+                None,
+                -32600,
+                format!(
+                    "Invalid Request: batch exceeds {} entries",
+                    self.limits.max_batch_len
+                ),
+            );
+            return serde_json::to_value(resp).ok();
+        }
+
+        let responses: Vec<Value> = batch
+            .into_iter()
+            .filter_map(|msg| self.handle_message(msg))
+            .filter_map(|resp| serde_json::to_value(resp).ok())
+            .collect();
+
+        if responses.is_empty() {
+            None
+        } else {
+            Some(Value::Array(responses))
+        }
+    }
+
+    /// Validate and dispatch a single JSON-RPC message.
+    ///
+    /// Returns `None` for notifications. Exposed so request handling can be
+    /// tested without going through a reader/writer pair.
+    pub fn handle_message(&self, message: Value) -> Option<JsonRpcResponse> {
+        let id = message.get("id").cloned();
+        let request: JsonRpcRequest = match serde_json::from_value(message) {
+            Ok(r) => r,
+            Err(e) => {
+                return Some(JsonRpcResponse::error(
+                    id,
+                    -32600,
+                    format!("Invalid Request: {}", e),
+                ));
+            }
+        };
+
+        // Notifications (no id) produce no response
+        let is_notification = request.id.is_none();
+        let response = self.dispatch(&request);
+
+        if is_notification {
+            None
+        } else {
+            response
+        }
+    }
+
+    fn write_json<W: Write, T: Serialize>(writer: &mut W, value: &T) -> std::io::Result<()> {
+        let json = serde_json::to_string(value)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
         writeln!(writer, "{}", json)?;
         writer.flush()
     }
 
-    fn dispatch(&self, req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    /// Dispatch a parsed request to its method handler.
+    pub fn dispatch(&self, req: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+        if !(req.params.is_object() || req.params.is_null()) {
+            return Some(JsonRpcResponse::error(
+                req.id.clone(),
+                -32602,
+                "Invalid params: expected an object",
+            ));
+        }
+
         match req.method.as_str() {
             "initialize" => Some(self.handle_initialize(req)),
             "notifications/initialized" => None,
@@ -161,28 +351,65 @@ impl McpServer {
     }
 
     fn handle_tools_call(&self, req: &JsonRpcRequest) -> JsonRpcResponse {
-        let tool_name = req
-            .params
-            .get("name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let arguments = req
-            .params
-            .get("arguments")
-            .cloned()
-            .unwrap_or(Value::Object(Default::default()));
+        let tool_name = match req.params.get("name").and_then(|v| v.as_str()) {
+            Some(name) => name.to_string(),
+            None => {
+                return JsonRpcResponse::error(
+                    req.id.clone(),
+                    -32602,
+                    "Invalid params: 'name' must be a string",
+                );
+            }
+        };
+        let arguments = match req.params.get("arguments") {
+            None | Some(Value::Null) => Value::Object(Default::default()),
+            Some(v @ Value::Object(_)) => v.clone(),
+            Some(_) => {
+                return JsonRpcResponse::error(
+                    req.id.clone(),
+                    -32602,
+                    "Invalid params: 'arguments' must be an object",
+                );
+            }
+        };
 
         let client = match &self.client {
-            Some(c) => c,
+            Some(c) => Arc::clone(c),
             None => {
                 let result = tools::error_result(
+                    "auth_failed",
                     "No credentials configured. Set AUTHY_KEYFILE or AUTHY_PASSPHRASE.",
                 );
                 return JsonRpcResponse::success(req.id.clone(), result);
             }
         };
 
-        let result = tools::dispatch(client, tool_name, &arguments);
-        JsonRpcResponse::success(req.id.clone(), result)
+        let timeout = match self.limits.request_timeout {
+            Some(t) if !tools::is_mutating(&tool_name) => t,
+            _ => {
+                let result =
+                    tools::dispatch_cached(&client, self.cache.as_deref(), &tool_name, &arguments);
+                return JsonRpcResponse::success(req.id.clone(), result);
+            }
+        };
+
+        // Run the tool on a worker thread so a stuck call (e.g. a slow
+        // filesystem) can't wedge the read loop. On timeout the worker is
+        // left to finish; it can only read.
+        let (tx, rx) = mpsc::channel();
+        let cache = self.cache.clone();
+        thread::spawn(move || {
+            let result = tools::dispatch_cached(&client, cache.as_deref(), &tool_name, &arguments);
+            let _ = tx.send(result);
+        });
+
+        match rx.recv_timeout(timeout) {
+            Ok(result) => JsonRpcResponse::success(req.id.clone(), result),
+            Err(_) => JsonRpcResponse::error(
+                req.id.clone(),
+                -32603,
+                format!("Request timed out after {}s", timeout.as_secs()),
+            ),
+        }
     }
 }
@@ -2,7 +2,10 @@
 
 use serde_json::Value;
 
-use crate::api::AuthyClient;
+use crate::api::{AuthyClient, PolicyUpdate};
+use crate::error::AuthyError;
+use crate::subprocess::NamingOptions;
+use crate::vault::cache::ReadCache;
 
 /// Return JSON Schema definitions for all MCP tools.
 pub fn tool_definitions() -> Vec<Value> {
@@ -18,6 +21,21 @@ pub fn tool_definitions() -> Vec<Value> {
                 "required": ["name"]
             }
         }),
+        serde_json::json!({
+            "name": "get_secrets",
+            "description": "Retrieve several secret values in one call. Each name is checked against the scope on its own; denied or missing names are reported under \"errors\" without failing the rest",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "names": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Secret names"
+                    }
+                },
+                "required": ["names"]
+            }
+        }),
         serde_json::json!({
             "name": "list_secrets",
             "description": "List all secret names, optionally filtered by a policy scope",
@@ -28,6 +46,29 @@ pub fn tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        serde_json::json!({
+            "name": "describe_secrets",
+            "description": "List secrets with their metadata (version, created/modified times, tags, description, expiry, provenance), optionally filtered by a policy scope. Values are never included",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "scope": { "type": "string", "description": "Policy scope to filter by (optional)" }
+                }
+            }
+        }),
+        serde_json::json!({
+            "name": "build_env",
+            "description": "Show the environment variables `authy run` would set for a scope, mapped to their secret names. Values are never included",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "scope": { "type": "string", "description": "Policy scope (optional with a session token)" },
+                    "uppercase": { "type": "boolean", "description": "Uppercase variable names (default: false)" },
+                    "replace_dash": { "type": "string", "description": "Character to replace '-' with, e.g. \"_\" (optional)" },
+                    "prefix": { "type": "string", "description": "Prefix for variable names (optional)" }
+                }
+            }
+        }),
         serde_json::json!({
             "name": "store_secret",
             "description": "Store a secret in the vault",
@@ -52,6 +93,18 @@ pub fn tool_definitions() -> Vec<Value> {
                 "required": ["name"]
             }
         }),
+        serde_json::json!({
+            "name": "set_tags",
+            "description": "Replace a secret's tags. The value and version are unchanged",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Secret name" },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "New tags; [] clears them" }
+                },
+                "required": ["name", "tags"]
+            }
+        }),
         serde_json::json!({
             "name": "test_policy",
             "description": "Test whether a policy allows access to a secret name",
@@ -64,29 +117,179 @@ pub fn tool_definitions() -> Vec<Value> {
                 "required": ["scope", "secret_name"]
             }
         }),
+        serde_json::json!({
+            "name": "list_policies",
+            "description": "List all policies with their allow/deny rules, as stored",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        serde_json::json!({
+            "name": "get_policy",
+            "description": "Show one policy. With effective, rules inherited through extends are merged in",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Policy name" },
+                    "effective": { "type": "boolean", "description": "Merge in inherited rules (default: false)" }
+                },
+                "required": ["name"]
+            }
+        }),
+        serde_json::json!({
+            "name": "create_policy",
+            "description": "Create a policy from allow/deny glob patterns",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Policy name" },
+                    "allow": { "type": "array", "items": { "type": "string" }, "description": "Allow patterns" },
+                    "deny": { "type": "array", "items": { "type": "string" }, "description": "Deny patterns (optional)" },
+                    "description": { "type": "string", "description": "Description (optional)" },
+                    "run_only": { "type": "boolean", "description": "Only allow injection via run (default: false)" }
+                },
+                "required": ["name", "allow"]
+            }
+        }),
+        serde_json::json!({
+            "name": "update_policy",
+            "description": "Change a policy. Omitted fields keep their value; the replaced version is kept in the policy's history",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Policy name" },
+                    "allow": { "type": "array", "items": { "type": "string" }, "description": "New allow patterns" },
+                    "deny": { "type": "array", "items": { "type": "string" }, "description": "New deny patterns" },
+                    "description": { "type": "string", "description": "New description" },
+                    "run_only": { "type": "boolean", "description": "Only allow injection via run" },
+                    "count_only": { "type": "boolean", "description": "Only allow counting matching secrets" },
+                    "extends": { "type": "array", "items": { "type": "string" }, "description": "Policies to inherit from; [] clears" }
+                },
+                "required": ["name"]
+            }
+        }),
+        serde_json::json!({
+            "name": "delete_policy",
+            "description": "Remove a policy and its history. Fails while other policies extend it or if it is protected",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Policy name" }
+                },
+                "required": ["name"]
+            }
+        }),
     ]
 }
 
+/// Whether a tool changes the vault.
+pub fn is_mutating(tool_name: &str) -> bool {
+    matches!(
+        tool_name,
+        "store_secret"
+            | "remove_secret"
+            | "set_tags"
+            | "create_policy"
+            | "update_policy"
+            | "delete_policy"
+    )
+}
+
 /// Dispatch a tool call to the appropriate handler.
 pub fn dispatch(client: &AuthyClient, tool_name: &str, args: &Value) -> Value {
+    dispatch_cached(client, None, tool_name, args)
+}
+
+/// Dispatch a tool call, serving `get_secret` from `cache` when it holds a
+/// fresh value. Successful writes clear the cache.
+pub fn dispatch_cached(
+    client: &AuthyClient,
+    cache: Option<&ReadCache>,
+    tool_name: &str,
+    args: &Value,
+) -> Value {
     match tool_name {
-        "get_secret" => handle_get_secret(client, args),
+        "get_secret" => handle_get_secret(client, cache, args),
+        "get_secrets" => handle_get_secrets(client, args),
         "list_secrets" => handle_list_secrets(client, args),
-        "store_secret" => handle_store_secret(client, args),
-        "remove_secret" => handle_remove_secret(client, args),
+        "describe_secrets" => handle_describe_secrets(client, args),
+        "build_env" => handle_build_env(client, args),
+        "store_secret" => invalidating(cache, handle_store_secret(client, args)),
+        "remove_secret" => invalidating(cache, handle_remove_secret(client, args)),
+        "set_tags" => handle_set_tags(client, args),
         "test_policy" => handle_test_policy(client, args),
-        _ => error_result(&format!("Unknown tool: {}", tool_name)),
+        "list_policies" => handle_list_policies(client),
+        "get_policy" => handle_get_policy(client, args),
+        "create_policy" => invalidating(cache, handle_create_policy(client, args)),
+        "update_policy" => invalidating(cache, handle_update_policy(client, args)),
+        "delete_policy" => invalidating(cache, handle_delete_policy(client, args)),
+        _ => error_result("unknown_tool", &format!("Unknown tool: {}", tool_name)),
+    }
+}
+
+/// Clear the cache after a write that succeeded.
+fn invalidating(cache: Option<&ReadCache>, result: Value) -> Value {
+    if let Some(cache) = cache {
+        if result.get("isError").is_none() {
+            cache.clear();
+        }
     }
+    result
 }
 
 /// Build an MCP error result with `isError: true`.
-pub fn error_result(msg: &str) -> Value {
+///
+/// Alongside the human-readable text block, `structuredContent.error.code`
+/// carries a stable identifier so agent frameworks can branch on it.
+pub fn error_result(code: &str, msg: &str) -> Value {
     serde_json::json!({
         "content": [{ "type": "text", "text": msg }],
+        "structuredContent": {
+            "error": { "code": code, "message": msg }
+        },
         "isError": true
     })
 }
 
+/// Build an MCP error result from an [`AuthyError`], including its typed
+/// error code, exit code, and the secret/policy name it refers to.
+pub fn authy_error_result(e: &AuthyError) -> Value {
+    let msg = e.to_string();
+    serde_json::json!({
+        "content": [{ "type": "text", "text": msg }],
+        "structuredContent": { "error": error_detail(e) },
+        "isError": true
+    })
+}
+
+/// The `structuredContent.error` object for an [`AuthyError`].
+fn error_detail(e: &AuthyError) -> Value {
+    let mut error = serde_json::json!({
+        "code": e.error_code(),
+        "message": e.to_string(),
+        "exit_code": e.exit_code(),
+    });
+    match e {
+        AuthyError::SecretNotFound(name) | AuthyError::SecretAlreadyExists(name) => {
+            error["secret"] = Value::String(name.clone());
+        }
+        AuthyError::PolicyNotFound(name) | AuthyError::PolicyAlreadyExists(name) => {
+            error["policy"] = Value::String(name.clone());
+        }
+        AuthyError::AccessDenied {
+            secret,
+            scope,
+            reason,
+        } => {
+            error["secret"] = Value::String(secret.clone());
+            error["policy"] = Value::String(scope.clone());
+            if let Some(reason) = reason {
+                error["reason"] = Value::String(reason.clone());
+            }
+        }
+        _ => {}
+    }
+    error
+}
+
 /// Build an MCP success result.
 fn text_result(text: &str) -> Value {
     serde_json::json!({
@@ -94,15 +297,70 @@ fn text_result(text: &str) -> Value {
     })
 }
 
-fn handle_get_secret(client: &AuthyClient, args: &Value) -> Value {
+/// Build an MCP success result carrying `body` both as JSON text and as
+/// `structuredContent`.
+fn json_result(body: Value) -> Value {
+    serde_json::json!({
+        "content": [{ "type": "text", "text": body.to_string() }],
+        "structuredContent": body
+    })
+}
+
+fn handle_get_secret(client: &AuthyClient, cache: Option<&ReadCache>, args: &Value) -> Value {
     let name = match args.get("name").and_then(|v| v.as_str()) {
         Some(n) => n,
-        None => return error_result("Missing required parameter: name"),
+        None => return error_result("invalid_params", "Missing required parameter: name"),
     };
 
+    if let Some((cache, value)) = cache.and_then(|c| c.get(name).map(|v| (c, v))) {
+        // A revoked token or a narrowed scope applies to cached values too
+        return match client.check_cached_read(name) {
+            Ok(()) => text_result(&value),
+            Err(e) => {
+                cache.invalidate(name);
+                authy_error_result(&e)
+            }
+        };
+    }
+
     match client.get_or_err(name) {
-        Ok(value) => text_result(&value),
-        Err(e) => error_result(&e.to_string()),
+        Ok(value) => {
+            // A canary is never cached, so every read of it alerts
+            if let Some(cache) = cache.filter(|_| !client.is_canary(name).unwrap_or(true)) {
+                cache.insert(name, &value);
+            }
+            text_result(&value)
+        }
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_get_secrets(client: &AuthyClient, args: &Value) -> Value {
+    let names: Vec<&str> = match args.get("names").and_then(|v| v.as_array()) {
+        Some(names) => match names.iter().map(|n| n.as_str()).collect::<Option<_>>() {
+            Some(names) => names,
+            None => return error_result("invalid_params", "names must be an array of strings"),
+        },
+        None => return error_result("invalid_params", "Missing required parameter: names"),
+    };
+
+    match client.get_many(&names) {
+        Ok(results) => {
+            let mut secrets = serde_json::Map::new();
+            let mut errors = serde_json::Map::new();
+            for (name, result) in results {
+                match result {
+                    Ok(value) => {
+                        secrets.insert(name, Value::String(value));
+                    }
+                    Err(e) => {
+                        errors.insert(name, error_detail(&e));
+                    }
+                }
+            }
+            json_result(serde_json::json!({ "secrets": secrets, "errors": errors }))
+        }
+        Err(e) => authy_error_result(&e),
     }
 }
 
@@ -114,48 +372,98 @@ fn handle_list_secrets(client: &AuthyClient, args: &Value) -> Value {
             let json = serde_json::to_string(&names).unwrap_or_else(|_| "[]".to_string());
             text_result(&json)
         }
-        Err(e) => error_result(&e.to_string()),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_describe_secrets(client: &AuthyClient, args: &Value) -> Value {
+    let scope = args.get("scope").and_then(|v| v.as_str());
+
+    match client.list_detailed(scope) {
+        Ok(secrets) => json_result(serde_json::json!({ "secrets": secrets })),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_build_env(client: &AuthyClient, args: &Value) -> Value {
+    let scope = args.get("scope").and_then(|v| v.as_str());
+    let naming = NamingOptions {
+        uppercase: args.get("uppercase").and_then(|v| v.as_bool()).unwrap_or(false),
+        replace_dash: args
+            .get("replace_dash")
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.chars().next()),
+        prefix: args.get("prefix").and_then(|v| v.as_str()).map(String::from),
+    };
+
+    match client.env_manifest(scope, &naming) {
+        Ok(manifest) => {
+            let variables: Vec<Value> = manifest
+                .into_iter()
+                .map(|(name, secret)| serde_json::json!({ "name": name, "secret": secret }))
+                .collect();
+            json_result(serde_json::json!({ "variables": variables }))
+        }
+        Err(e) => authy_error_result(&e),
     }
 }
 
 fn handle_store_secret(client: &AuthyClient, args: &Value) -> Value {
     let name = match args.get("name").and_then(|v| v.as_str()) {
         Some(n) => n,
-        None => return error_result("Missing required parameter: name"),
+        None => return error_result("invalid_params", "Missing required parameter: name"),
     };
     let value = match args.get("value").and_then(|v| v.as_str()) {
         Some(v) => v,
-        None => return error_result("Missing required parameter: value"),
+        None => return error_result("invalid_params", "Missing required parameter: value"),
     };
     let force = args.get("force").and_then(|v| v.as_bool()).unwrap_or(false);
 
     match client.store(name, value, force) {
         Ok(()) => text_result(&format!("Stored secret '{}'", name)),
-        Err(e) => error_result(&e.to_string()),
+        Err(e) => authy_error_result(&e),
     }
 }
 
 fn handle_remove_secret(client: &AuthyClient, args: &Value) -> Value {
     let name = match args.get("name").and_then(|v| v.as_str()) {
         Some(n) => n,
-        None => return error_result("Missing required parameter: name"),
+        None => return error_result("invalid_params", "Missing required parameter: name"),
     };
 
     match client.remove(name) {
         Ok(true) => text_result(&format!("Removed secret '{}'", name)),
         Ok(false) => text_result(&format!("Secret '{}' not found", name)),
-        Err(e) => error_result(&e.to_string()),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_set_tags(client: &AuthyClient, args: &Value) -> Value {
+    let name = match args.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return error_result("invalid_params", "Missing required parameter: name"),
+    };
+    let tags = match string_list(args, "tags") {
+        Ok(Some(tags)) => tags,
+        Ok(None) => return error_result("invalid_params", "Missing required parameter: tags"),
+        Err(e) => return e,
+    };
+    let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+
+    match client.set_tags(name, &tags) {
+        Ok(()) => text_result(&format!("Set tags on '{}'", name)),
+        Err(e) => authy_error_result(&e),
     }
 }
 
 fn handle_test_policy(client: &AuthyClient, args: &Value) -> Value {
     let scope = match args.get("scope").and_then(|v| v.as_str()) {
         Some(s) => s,
-        None => return error_result("Missing required parameter: scope"),
+        None => return error_result("invalid_params", "Missing required parameter: scope"),
     };
     let secret_name = match args.get("secret_name").and_then(|v| v.as_str()) {
         Some(n) => n,
-        None => return error_result("Missing required parameter: secret_name"),
+        None => return error_result("invalid_params", "Missing required parameter: secret_name"),
     };
 
     match client.test_policy(scope, secret_name) {
@@ -167,6 +475,112 @@ fn handle_test_policy(client: &AuthyClient, args: &Value) -> Value {
             "denied: scope '{}' cannot read '{}'",
             scope, secret_name
         )),
-        Err(e) => error_result(&e.to_string()),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+/// An optional array-of-strings argument; `Err` holds the error result.
+fn string_list(args: &Value, key: &str) -> Result<Option<Vec<String>>, Value> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| v.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()
+            .map(Some)
+            .ok_or_else(|| {
+                error_result("invalid_params", &format!("{} must be an array of strings", key))
+            }),
+        Some(_) => Err(error_result(
+            "invalid_params",
+            &format!("{} must be an array of strings", key),
+        )),
+    }
+}
+
+fn handle_list_policies(client: &AuthyClient) -> Value {
+    match client.list_policies() {
+        Ok(policies) => json_result(serde_json::json!({ "policies": policies })),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_get_policy(client: &AuthyClient, args: &Value) -> Value {
+    let name = match args.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return error_result("invalid_params", "Missing required parameter: name"),
+    };
+    let effective = args.get("effective").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match client.get_policy(name, effective) {
+        Ok(Some(policy)) => json_result(serde_json::json!({ "policy": policy })),
+        Ok(None) => authy_error_result(&AuthyError::PolicyNotFound(name.to_string())),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_create_policy(client: &AuthyClient, args: &Value) -> Value {
+    let name = match args.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return error_result("invalid_params", "Missing required parameter: name"),
+    };
+    let allow = match string_list(args, "allow") {
+        Ok(Some(allow)) => allow,
+        Ok(None) => return error_result("invalid_params", "Missing required parameter: allow"),
+        Err(e) => return e,
+    };
+    let deny = match string_list(args, "deny") {
+        Ok(deny) => deny.unwrap_or_default(),
+        Err(e) => return e,
+    };
+    let description = args.get("description").and_then(|v| v.as_str());
+    let run_only = args.get("run_only").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match client.create_policy(name, allow, deny, description, run_only) {
+        Ok(()) => text_result(&format!("Created policy '{}'", name)),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_update_policy(client: &AuthyClient, args: &Value) -> Value {
+    let name = match args.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return error_result("invalid_params", "Missing required parameter: name"),
+    };
+    let mut update = PolicyUpdate {
+        description: args.get("description").and_then(|v| v.as_str()).map(String::from),
+        run_only: args.get("run_only").and_then(|v| v.as_bool()),
+        count_only: args.get("count_only").and_then(|v| v.as_bool()),
+        ..Default::default()
+    };
+    for (key, field) in [
+        ("allow", &mut update.allow),
+        ("deny", &mut update.deny),
+        ("extends", &mut update.extends),
+    ] {
+        match string_list(args, key) {
+            Ok(list) => *field = list,
+            Err(e) => return e,
+        }
+    }
+
+    match client.update_policy(name, &update) {
+        Ok(rev) => text_result(&format!(
+            "Updated policy '{}' (previous version saved as revision {})",
+            name, rev
+        )),
+        Err(e) => authy_error_result(&e),
+    }
+}
+
+fn handle_delete_policy(client: &AuthyClient, args: &Value) -> Value {
+    let name = match args.get("name").and_then(|v| v.as_str()) {
+        Some(n) => n,
+        None => return error_result("invalid_params", "Missing required parameter: name"),
+    };
+
+    match client.delete_policy(name) {
+        Ok(()) => text_result(&format!("Removed policy '{}'", name)),
+        Err(e) => authy_error_result(&e),
     }
 }
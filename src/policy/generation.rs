@@ -0,0 +1,153 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AuthyError, Result};
+
+/// Length of generated values when neither the caller nor the policy asks
+/// for more.
+pub const DEFAULT_LENGTH: usize = 32;
+
+/// Give up after this many candidates fail the rules.
+const MAX_ATTEMPTS: usize = 1000;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGIT: &str = "0123456789";
+const SYMBOL: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+
+/// Constraints a scope's downstream system puts on generated values, so
+/// `authy generate --scope` produces something that system will accept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationRules {
+    /// Minimum length; raises shorter requested lengths.
+    #[serde(default)]
+    pub min_length: usize,
+    /// Character classes that must each appear at least once.
+    #[serde(default)]
+    pub require: Vec<CharClass>,
+    /// Substrings that must not appear. Single characters are dropped from
+    /// the alphabet entirely.
+    #[serde(default)]
+    pub forbid: Vec<String>,
+}
+
+/// A class of characters a generated value can be required to contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    fn chars(self) -> &'static str {
+        match self {
+            CharClass::Lower => LOWER,
+            CharClass::Upper => UPPER,
+            CharClass::Digit => DIGIT,
+            CharClass::Symbol => SYMBOL,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CharClass::Lower => "lower",
+            CharClass::Upper => "upper",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+}
+
+impl GenerationRules {
+    /// True when no constraint is set.
+    pub fn is_empty(&self) -> bool {
+        self.min_length == 0 && self.require.is_empty() && self.forbid.is_empty()
+    }
+
+    /// Check a value against the rules, naming the first one it breaks.
+    pub fn check(&self, value: &str) -> Result<()> {
+        if value.chars().count() < self.min_length {
+            return Err(violation(format!("shorter than min_length {}", self.min_length)));
+        }
+        for class in &self.require {
+            if !value.chars().any(|c| class.chars().contains(c)) {
+                return Err(violation(format!("no {} character", class.as_str())));
+            }
+        }
+        for pattern in &self.forbid {
+            if !pattern.is_empty() && value.contains(pattern.as_str()) {
+                return Err(violation(format!("contains forbidden '{}'", pattern)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn violation(reason: String) -> AuthyError {
+    AuthyError::Other(format!("Generated value rule violated: {}", reason))
+}
+
+/// Generate a random value of at least `length` characters satisfying
+/// `rules`. Without required classes the alphabet is letters and digits.
+pub fn generate(length: usize, rules: Option<&GenerationRules>) -> Result<String> {
+    let default_rules = GenerationRules::default();
+    let rules = rules.unwrap_or(&default_rules);
+    let length = length.max(rules.min_length);
+
+    let forbidden_chars: Vec<char> = rules
+        .forbid
+        .iter()
+        .filter(|p| p.chars().count() == 1)
+        .filter_map(|p| p.chars().next())
+        .collect();
+    let classes: Vec<CharClass> = if rules.require.is_empty() {
+        vec![CharClass::Lower, CharClass::Upper, CharClass::Digit]
+    } else {
+        rules.require.clone()
+    };
+
+    let mut alphabet: Vec<char> = Vec::new();
+    for class in &classes {
+        let usable: Vec<char> = class
+            .chars()
+            .chars()
+            .filter(|c| !forbidden_chars.contains(c))
+            .collect();
+        if usable.is_empty() && rules.require.contains(class) {
+            return Err(AuthyError::Other(format!(
+                "Generation rules forbid every {} character",
+                class.as_str()
+            )));
+        }
+        for c in usable {
+            if !alphabet.contains(&c) {
+                alphabet.push(c);
+            }
+        }
+    }
+    if length == 0 || alphabet.is_empty() || length < rules.require.len() {
+        return Err(AuthyError::Other(format!(
+            "Cannot generate a {}-character value with {} required classes",
+            length,
+            rules.require.len()
+        )));
+    }
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..MAX_ATTEMPTS {
+        let candidate: String = (0..length)
+            .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+            .collect();
+        if rules.check(&candidate).is_ok() {
+            return Ok(candidate);
+        }
+    }
+    Err(AuthyError::Other(
+        "Could not generate a value satisfying the generation rules; relax them or raise the length"
+            .into(),
+    ))
+}
@@ -1,7 +1,14 @@
+pub mod generation;
+pub mod window;
+
+use std::collections::BTreeMap;
+
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AuthyError, Result};
+use generation::GenerationRules;
+use window::{DaySet, HourWindow};
 
 /// A policy defines which secrets a scope can access.
 /// Deny patterns override allow patterns. Default is deny.
@@ -16,6 +23,103 @@ pub struct Policy {
     /// When true, secrets can only be injected via `run` — `get`, `env`, `export` are blocked.
     #[serde(default)]
     pub run_only: bool,
+    /// Constraints for values generated in this scope (`authy generate --scope`).
+    #[serde(default)]
+    pub generation: Option<GenerationRules>,
+    /// When true, session tokens under this policy see how many secrets
+    /// they can access, but not their names (`list`, `expiring`).
+    #[serde(default)]
+    pub count_only: bool,
+    /// When true, `policy remove` needs an approver's keyfile (`--approve-with`).
+    #[serde(default)]
+    pub protected: bool,
+    /// Policies whose rules this one inherits (see [`resolve`]).
+    #[serde(default)]
+    pub extends: Vec<String>,
+    /// Local time of day outside which the policy grants nothing.
+    #[serde(default)]
+    pub valid_hours: Option<HourWindow>,
+    /// Weekdays outside which the policy grants nothing.
+    #[serde(default)]
+    pub valid_days: Option<DaySet>,
+    /// Secrets that may be stored under this policy. Empty means the same
+    /// as `allow`; deny patterns apply to every operation.
+    #[serde(default)]
+    pub allow_write: Vec<String>,
+    /// Secrets that may be rotated. Empty means the same as `allow`.
+    #[serde(default)]
+    pub allow_rotate: Vec<String>,
+    /// Secrets that may be removed. Empty means the same as `allow`.
+    #[serde(default)]
+    pub allow_delete: Vec<String>,
+}
+
+/// An operation on a secret, which a policy can grant separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretOp {
+    Read,
+    Write,
+    Rotate,
+    Delete,
+}
+
+impl SecretOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecretOp::Read => "read",
+            SecretOp::Write => "write",
+            SecretOp::Rotate => "rotate",
+            SecretOp::Delete => "delete",
+        }
+    }
+}
+
+/// The kind of rule that decided an access check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// An allow pattern (or the operation's own allow pattern) matched.
+    Allow,
+    /// A deny pattern matched.
+    Deny,
+    /// The check fell outside the policy's valid hours or days.
+    Window,
+    /// Nothing matched, so the default deny applied.
+    Default,
+}
+
+impl RuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleKind::Allow => "allow",
+            RuleKind::Deny => "deny",
+            RuleKind::Window => "window",
+            RuleKind::Default => "default",
+        }
+    }
+}
+
+/// The outcome of an access check and the rule behind it (see
+/// [`Policy::decide`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    /// The pattern that matched, or the window that was missed (e.g.
+    /// `outside valid hours 09:00-17:00`). `None` for a default deny.
+    pub matched_rule: Option<String>,
+    pub rule_kind: RuleKind,
+}
+
+impl Decision {
+    /// Why access was denied, for error messages and audit details. `None`
+    /// when it was allowed.
+    pub fn denial_reason(&self) -> Option<String> {
+        match (self.rule_kind, self.matched_rule.as_deref()) {
+            (RuleKind::Allow, _) => None,
+            (RuleKind::Deny, Some(pattern)) => Some(format!("deny rule '{}'", pattern)),
+            (RuleKind::Window, Some(window)) => Some(window.to_string()),
+            _ => Some("no allow rule matches".to_string()),
+        }
+    }
 }
 
 impl Policy {
@@ -29,34 +133,277 @@ impl Policy {
             created_at: now,
             modified_at: now,
             run_only: false,
+            generation: None,
+            count_only: false,
+            protected: false,
+            extends: Vec::new(),
+            valid_hours: None,
+            valid_days: None,
+            allow_write: Vec::new(),
+            allow_rotate: Vec::new(),
+            allow_delete: Vec::new(),
         }
     }
 
-    /// Check if a secret name is allowed by this policy.
-    /// Deny overrides allow. Default deny.
+    /// Why the policy grants nothing right now, if the current local time
+    /// is outside its valid hours or days.
+    pub fn window_denial(&self) -> Option<String> {
+        self.window_denial_at(&chrono::Local::now())
+    }
+
+    /// Like [`window_denial`](Self::window_denial), at a given time.
+    pub fn window_denial_at<T: chrono::Datelike + chrono::Timelike>(&self, now: &T) -> Option<String> {
+        window::denial_reason(self.valid_hours.as_ref(), self.valid_days.as_ref(), now)
+    }
+
+    /// Check if a secret name is allowed by this policy's own patterns.
+    /// Deny overrides allow. Default deny. Nothing is allowed outside the
+    /// policy's valid hours and days (see [`window_denial`](Self::window_denial)).
+    /// For a policy that extends others, call this on the result of [`resolve`].
     pub fn can_read(&self, secret_name: &str) -> Result<bool> {
-        let deny_set = build_globset(&self.deny)?;
-        if deny_set.is_match(secret_name) {
-            return Ok(false);
+        Ok(self.decide(SecretOp::Read, secret_name)?.allowed)
+    }
+
+    /// Check if a secret may be stored under this policy.
+    pub fn can_write(&self, secret_name: &str) -> Result<bool> {
+        self.allows(SecretOp::Write, secret_name)
+    }
+
+    /// Check if a secret may be rotated under this policy.
+    pub fn can_rotate(&self, secret_name: &str) -> Result<bool> {
+        self.allows(SecretOp::Rotate, secret_name)
+    }
+
+    /// Check if a secret may be removed under this policy.
+    pub fn can_delete(&self, secret_name: &str) -> Result<bool> {
+        self.allows(SecretOp::Delete, secret_name)
+    }
+
+    /// Check `op` on a secret: like [`can_read`](Self::can_read), but against
+    /// the operation's own allow patterns when the policy sets them.
+    pub fn allows(&self, op: SecretOp, secret_name: &str) -> Result<bool> {
+        Ok(self.decide(op, secret_name)?.allowed)
+    }
+
+    /// Check `op` on a secret and report which rule decided it: the
+    /// policy's window first, then the first matching deny pattern, then
+    /// the first matching allow pattern for `op`, else the default deny.
+    pub fn decide(&self, op: SecretOp, secret_name: &str) -> Result<Decision> {
+        let decision = |allowed, rule_kind, matched_rule| Decision {
+            allowed,
+            matched_rule,
+            rule_kind,
+        };
+        if let Some(reason) = self.window_denial() {
+            return Ok(decision(false, RuleKind::Window, Some(reason)));
+        }
+        if let Some(&i) = build_globset(&self.deny)?.matches(secret_name).first() {
+            return Ok(decision(false, RuleKind::Deny, Some(self.deny[i].clone())));
         }
+        let patterns = match self.op_patterns(op) {
+            [] => &self.allow,
+            patterns => patterns,
+        };
+        Ok(match build_globset(patterns)?.matches(secret_name).first() {
+            Some(&i) => decision(true, RuleKind::Allow, Some(patterns[i].clone())),
+            None => decision(false, RuleKind::Default, None),
+        })
+    }
 
+    /// The allow patterns set for `op` (empty when it falls back to `allow`).
+    pub fn op_patterns(&self, op: SecretOp) -> &[String] {
+        match op {
+            SecretOp::Read => &self.allow,
+            SecretOp::Write => &self.allow_write,
+            SecretOp::Rotate => &self.allow_rotate,
+            SecretOp::Delete => &self.allow_delete,
+        }
+    }
+
+    /// Evaluate every name in a list, returning each with its allow/deny result.
+    /// The glob sets are compiled once, so this is cheap for large lists.
+    pub fn evaluate_all<'a>(&self, names: &[&'a str]) -> Result<Vec<(&'a str, bool)>> {
+        let deny_set = build_globset(&self.deny)?;
         let allow_set = build_globset(&self.allow)?;
-        Ok(allow_set.is_match(secret_name))
+        let in_window = self.window_denial().is_none();
+        Ok(names
+            .iter()
+            .map(|name| {
+                let allowed = in_window && !deny_set.is_match(name) && allow_set.is_match(name);
+                (*name, allowed)
+            })
+            .collect())
     }
 
     /// Return all secret names from a list that this policy allows.
+    /// Like [`evaluate_all`](Self::evaluate_all), the glob sets are compiled once.
     pub fn filter_secrets<'a>(&self, names: &[&'a str]) -> Result<Vec<&'a str>> {
-        let mut allowed = Vec::new();
+        Ok(self
+            .evaluate_all(names)?
+            .into_iter()
+            .filter_map(|(name, allowed)| allowed.then_some(name))
+            .collect())
+    }
+}
+
+/// How a policy's rules apply to a set of secret names (see
+/// [`Policy::coverage`]).
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Names the policy lets its scope read.
+    pub allowed: Vec<String>,
+    /// Names it denies, each with the deny pattern that matched, or `None`
+    /// when no allow pattern matches.
+    pub denied: Vec<(String, Option<String>)>,
+    /// Patterns that match none of the names, with the list they're in
+    /// (`allow`, `deny`, `allow_write`, `allow_rotate`, `allow_delete`).
+    pub dead: Vec<(&'static str, String)>,
+}
+
+impl Policy {
+    /// Evaluate the policy against `names` for a coverage report. Valid
+    /// hours and days are ignored, so the result doesn't depend on when it
+    /// runs.
+    pub fn coverage(&self, names: &[&str]) -> Result<Coverage> {
+        let mut coverage = Coverage::default();
+        let allow_set = build_globset(&self.allow)?;
+        let deny_sets = self
+            .deny
+            .iter()
+            .map(|p| build_globset(std::slice::from_ref(p)))
+            .collect::<Result<Vec<_>>>()?;
         for name in names {
-            if self.can_read(name)? {
-                allowed.push(*name);
+            match deny_sets.iter().position(|set| set.is_match(name)) {
+                Some(i) => coverage.denied.push((name.to_string(), Some(self.deny[i].clone()))),
+                None if allow_set.is_match(name) => coverage.allowed.push(name.to_string()),
+                None => coverage.denied.push((name.to_string(), None)),
+            }
+        }
+
+        for (list, patterns) in [
+            ("allow", &self.allow),
+            ("deny", &self.deny),
+            ("allow_write", &self.allow_write),
+            ("allow_rotate", &self.allow_rotate),
+            ("allow_delete", &self.allow_delete),
+        ] {
+            for pattern in patterns {
+                let set = build_globset(std::slice::from_ref(pattern))?;
+                if !names.iter().any(|name| set.is_match(name)) {
+                    coverage.dead.push((list, pattern.clone()));
+                }
             }
         }
-        Ok(allowed)
+        Ok(coverage)
+    }
+}
+
+/// A previous version of a policy, kept in [`crate::vault::Vault::policy_history`]
+/// so `authy policy rollback` can restore it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRevision {
+    /// Increases by one per change and is never reused, even after the
+    /// oldest revisions are dropped.
+    pub rev: u32,
+    /// When this version was replaced.
+    pub replaced_at: chrono::DateTime<chrono::Utc>,
+    /// Who replaced it.
+    pub actor: String,
+    pub policy: Policy,
+}
+
+impl Policy {
+    /// Take the rule fields of `revision`, keeping this policy's name,
+    /// creation time and protection.
+    pub fn restore(&mut self, revision: &Policy) {
+        self.description = revision.description.clone();
+        self.allow = revision.allow.clone();
+        self.deny = revision.deny.clone();
+        self.run_only = revision.run_only;
+        self.generation = revision.generation.clone();
+        self.count_only = revision.count_only;
+        self.extends = revision.extends.clone();
+        self.valid_hours = revision.valid_hours;
+        self.valid_days = revision.valid_days;
+        self.allow_write = revision.allow_write.clone();
+        self.allow_rotate = revision.allow_rotate.clone();
+        self.allow_delete = revision.allow_delete.clone();
+        self.modified_at = chrono::Utc::now();
+    }
+}
+
+/// The effective policy `name`: its own rules plus those of every policy
+/// it extends, directly or through its parents. Allow and deny patterns,
+/// including the per-operation ones, are merged (deny still wins), and
+/// run-only and count-only carry over from any ancestor. Valid hours and
+/// days are the policy's own, else those of the first ancestor that sets
+/// them. Fails on a missing parent or an inheritance cycle.
+pub fn resolve(name: &str, policies: &BTreeMap<String, Policy>) -> Result<Policy> {
+    let policy = policies
+        .get(name)
+        .ok_or_else(|| AuthyError::PolicyNotFound(name.to_string()))?;
+    let mut effective = policy.clone();
+    inherit(policy, policies, &mut effective, &mut vec![name])?;
+    Ok(effective)
+}
+
+fn inherit<'a>(
+    policy: &'a Policy,
+    policies: &'a BTreeMap<String, Policy>,
+    effective: &mut Policy,
+    path: &mut Vec<&'a str>,
+) -> Result<()> {
+    for parent_name in &policy.extends {
+        if path.contains(&parent_name.as_str()) {
+            return Err(AuthyError::Other(format!(
+                "Policy inheritance cycle: {} -> {}",
+                path.join(" -> "),
+                parent_name
+            )));
+        }
+        let parent = policies
+            .get(parent_name)
+            .ok_or_else(|| AuthyError::PolicyNotFound(parent_name.clone()))?;
+
+        for pattern in &parent.allow {
+            if !effective.allow.contains(pattern) {
+                effective.allow.push(pattern.clone());
+            }
+        }
+        for pattern in &parent.deny {
+            if !effective.deny.contains(pattern) {
+                effective.deny.push(pattern.clone());
+            }
+        }
+        for (patterns, inherited) in [
+            (&mut effective.allow_write, &parent.allow_write),
+            (&mut effective.allow_rotate, &parent.allow_rotate),
+            (&mut effective.allow_delete, &parent.allow_delete),
+        ] {
+            for pattern in inherited {
+                if !patterns.contains(pattern) {
+                    patterns.push(pattern.clone());
+                }
+            }
+        }
+        effective.run_only |= parent.run_only;
+        effective.count_only |= parent.count_only;
+        if effective.valid_hours.is_none() {
+            effective.valid_hours = parent.valid_hours;
+        }
+        if effective.valid_days.is_none() {
+            effective.valid_days = parent.valid_days;
+        }
+
+        path.push(parent_name);
+        inherit(parent, policies, effective, path)?;
+        path.pop();
     }
+    Ok(())
 }
 
-fn build_globset(patterns: &[String]) -> Result<GlobSet> {
+/// Compile a list of glob patterns into a single matcher.
+pub fn build_globset(patterns: &[String]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
     for pattern in patterns {
         let glob = Glob::new(pattern)
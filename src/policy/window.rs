@@ -0,0 +1,219 @@
+//! Time-of-day and weekday constraints on when a policy grants access.
+//!
+//! Windows are evaluated in the machine's local time zone.
+
+use std::fmt;
+
+use chrono::{Datelike, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AuthyError, Result};
+
+/// A daily window such as `09:00-17:00`, as minutes since midnight. The
+/// start is inclusive and the end exclusive; an end before the start wraps
+/// past midnight (`22:00-06:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HourWindow {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl HourWindow {
+    /// Parse `HH:MM-HH:MM` (or `H-H`). `24:00` is allowed as an end.
+    pub fn parse(s: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            AuthyError::Other(format!(
+                "Invalid hours '{}': {} (expected HH:MM-HH:MM, e.g. 09:00-17:00)",
+                s, reason
+            ))
+        };
+        let (start, end) = s.split_once('-').ok_or_else(|| invalid("missing '-'"))?;
+        let start = parse_minute(start.trim()).ok_or_else(|| invalid("bad start time"))?;
+        let end = parse_minute(end.trim()).ok_or_else(|| invalid("bad end time"))?;
+        if start >= 24 * 60 {
+            return Err(invalid("start must be before 24:00"));
+        }
+        if start == end {
+            return Err(invalid("start and end are the same"));
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Whether the time of day falls inside the window.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let minute = (time.hour() * 60 + time.minute()) as u16;
+        if self.start < self.end {
+            self.start <= minute && minute < self.end
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+impl fmt::Display for HourWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start / 60,
+            self.start % 60,
+            self.end / 60,
+            self.end % 60
+        )
+    }
+}
+
+fn parse_minute(s: &str) -> Option<u16> {
+    let (hours, minutes) = match s.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h, m),
+        Some(_) => return None,
+        None => (s, "00"),
+    };
+    if hours.is_empty() || hours.len() > 2 {
+        return None;
+    }
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    if minutes >= 60 || hours > 24 || (hours == 24 && minutes > 0) {
+        return None;
+    }
+    Some(hours * 60 + minutes)
+}
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// A set of weekdays, one bit per day from Monday (bit 0) to Sunday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaySet(pub u8);
+
+impl DaySet {
+    /// Parse a comma-separated list of days and ranges, e.g. `mon-fri` or
+    /// `mon,wed,sat-sun`. Ranges may wrap (`fri-mon`).
+    pub fn parse(s: &str) -> Result<Self> {
+        let invalid = || {
+            AuthyError::Other(format!(
+                "Invalid days '{}' (expected names like mon-fri or mon,wed,sat)",
+                s
+            ))
+        };
+        let mut bits = 0u8;
+        for part in s.split(',').map(str::trim) {
+            let (first, last) = match part.split_once('-') {
+                Some((a, b)) => (day_index(a).ok_or_else(invalid)?, day_index(b).ok_or_else(invalid)?),
+                None => {
+                    let day = day_index(part).ok_or_else(invalid)?;
+                    (day, day)
+                }
+            };
+            let mut day = first;
+            loop {
+                bits |= 1 << day;
+                if day == last {
+                    break;
+                }
+                day = (day + 1) % 7;
+            }
+        }
+        Ok(Self(bits))
+    }
+
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0 & (1 << day.num_days_from_monday()) != 0
+    }
+}
+
+impl fmt::Display for DaySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days: Vec<&str> = (0..7)
+            .filter(|d| self.0 & (1 << d) != 0)
+            .map(|d| DAY_NAMES[d])
+            .collect();
+        f.write_str(&days.join(","))
+    }
+}
+
+fn day_index(name: &str) -> Option<usize> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.len() < 3 {
+        return None;
+    }
+    DAY_NAMES
+        .iter()
+        .position(|d| name.starts_with(d) && weekday_full_name(d).starts_with(&name))
+}
+
+fn weekday_full_name(abbrev: &str) -> &'static str {
+    match abbrev {
+        "mon" => "monday",
+        "tue" => "tuesday",
+        "wed" => "wednesday",
+        "thu" => "thursday",
+        "fri" => "friday",
+        "sat" => "saturday",
+        _ => "sunday",
+    }
+}
+
+/// Why a policy with these constraints grants nothing at `now`, or `None`
+/// when `now` is inside both.
+pub fn denial_reason<T: Datelike + Timelike>(
+    hours: Option<&HourWindow>,
+    days: Option<&DaySet>,
+    now: &T,
+) -> Option<String> {
+    if let Some(days) = days {
+        if !days.contains(now.weekday()) {
+            return Some(format!("outside valid days {}", days));
+        }
+    }
+    if let Some(hours) = hours {
+        let time = NaiveTime::from_hms_opt(now.hour(), now.minute(), 0)?;
+        if !hours.contains(time) {
+            return Some(format!("outside valid hours {}", hours));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_hour_window() {
+        let business = HourWindow::parse("09:00-17:30").unwrap();
+        assert!(business.contains(at(9, 0)));
+        assert!(business.contains(at(17, 29)));
+        assert!(!business.contains(at(17, 30)));
+        assert!(!business.contains(at(3, 0)));
+        assert_eq!(business.to_string(), "09:00-17:30");
+
+        let night = HourWindow::parse("22-6").unwrap();
+        assert!(night.contains(at(23, 0)));
+        assert!(night.contains(at(5, 59)));
+        assert!(!night.contains(at(12, 0)));
+
+        assert!(HourWindow::parse("0-24").unwrap().contains(at(23, 59)));
+        for bad in ["9", "25-26", "09:60-10", "10-10", "ab-cd", "24-1"] {
+            assert!(HourWindow::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_day_set() {
+        let weekdays = DaySet::parse("mon-fri").unwrap();
+        assert!(weekdays.contains(Weekday::Wed));
+        assert!(!weekdays.contains(Weekday::Sun));
+        assert_eq!(weekdays.to_string(), "mon,tue,wed,thu,fri");
+
+        let wrap = DaySet::parse("Friday-mon, wed").unwrap();
+        assert_eq!(wrap.to_string(), "mon,wed,fri,sat,sun");
+
+        assert!(DaySet::parse("funday").is_err());
+        assert!(DaySet::parse("mo").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use hmac::{Hmac, Mac};
 use rand::RngCore;
@@ -26,6 +28,135 @@ pub struct SessionRecord {
     /// When true, this token can only use `run` and `list` — not `get`, `env`, or `export`.
     #[serde(default)]
     pub run_only: bool,
+    /// Fixed, non-secret environment variables (e.g. `ENVIRONMENT=staging`)
+    /// that `authy run` sets alongside the secrets when using this token.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// What the token may do. Sessions created before `--can` existed can
+    /// read and list.
+    #[serde(default = "default_capabilities")]
+    pub capabilities: Vec<Capability>,
+    /// For sliding sessions (`--sliding`), the TTL in seconds that each
+    /// successful use extends `expires_at` to.
+    #[serde(default)]
+    pub sliding_ttl_secs: Option<i64>,
+    /// Hard limit for `expires_at`: sliding and `session refresh` never
+    /// extend a session past it.
+    #[serde(default)]
+    pub max_expires_at: Option<DateTime<Utc>>,
+    /// When the session was revoked. Unset for sessions revoked before
+    /// this was recorded.
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl SessionRecord {
+    /// Extend a sliding session to `now` plus its TTL, capped at its maximum
+    /// lifetime. To avoid a vault write on every use, the expiry only moves
+    /// once at least half the TTL has passed. Returns whether it moved.
+    pub fn slide(&mut self, now: DateTime<Utc>) -> bool {
+        let ttl = match self.sliding_ttl_secs {
+            Some(secs) => chrono::Duration::seconds(secs),
+            None => return false,
+        };
+        let target = self.capped(now + ttl);
+        if target - self.expires_at < ttl / 2 {
+            return false;
+        }
+        self.expires_at = target;
+        true
+    }
+
+    /// Revoke the session as of `now`.
+    pub fn revoke(&mut self, now: DateTime<Utc>) {
+        self.revoked = true;
+        self.revoked_at = Some(now);
+    }
+
+    /// When the session stopped being usable, or `None` while it still is.
+    /// Sessions revoked without a recorded time count from their expiry.
+    pub fn ended_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.revoked {
+            Some(self.revoked_at.unwrap_or(self.expires_at))
+        } else {
+            (now >= self.expires_at).then_some(self.expires_at)
+        }
+    }
+
+    /// Move the expiry to `now + ttl`, capped at the maximum lifetime.
+    pub fn refresh(&mut self, ttl: chrono::Duration, now: DateTime<Utc>) -> Result<()> {
+        if self.revoked {
+            return Err(AuthyError::TokenRevoked);
+        }
+        if self.max_expires_at.is_some_and(|max| now >= max) {
+            return Err(AuthyError::Other(format!(
+                "Session '{}' has reached its maximum lifetime",
+                self.id
+            )));
+        }
+        self.expires_at = self.capped(now + ttl);
+        Ok(())
+    }
+
+    /// The TTL a refresh without `--ttl` uses: the sliding TTL, or the
+    /// session's original lifetime.
+    pub fn default_ttl(&self) -> chrono::Duration {
+        match self.sliding_ttl_secs {
+            Some(secs) => chrono::Duration::seconds(secs),
+            None => self.expires_at - self.created_at,
+        }
+    }
+
+    fn capped(&self, expires_at: DateTime<Utc>) -> DateTime<Utc> {
+        match self.max_expires_at {
+            Some(max) => expires_at.min(max),
+            None => expires_at,
+        }
+    }
+}
+
+/// Something a session token may do, granted with `authy session create --can`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Capability {
+    /// Read values (`get`, `env`, `export`, `run`).
+    Read,
+    /// Store and remove secrets in the token's scope.
+    Write,
+    /// Rotate existing secrets in the token's scope.
+    Rotate,
+    /// List secret names in the token's scope.
+    List,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::Read,
+        Capability::Write,
+        Capability::Rotate,
+        Capability::List,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capability::Read => "read",
+            Capability::Write => "write",
+            Capability::Rotate => "rotate",
+            Capability::List => "list",
+        }
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a token gets without `--can`: read and list, as tokens always could.
+pub fn default_capabilities() -> Vec<Capability> {
+    vec![Capability::Read, Capability::List]
 }
 
 /// Generate a session token and its HMAC.
@@ -85,7 +216,6 @@ pub fn validate_token<'a>(
 }
 
 /// Parse a duration string like "1h", "30m", "7d".
-#[cfg(feature = "cli")]
 pub fn parse_ttl(s: &str) -> Result<chrono::Duration> {
     let duration: std::time::Duration =
         humantime::parse_duration(s).map_err(|e| AuthyError::Other(format!("Invalid TTL: {e}")))?;
@@ -93,9 +223,86 @@ pub fn parse_ttl(s: &str) -> Result<chrono::Duration> {
         .map_err(|e| AuthyError::Other(format!("Duration out of range: {e}")))
 }
 
+/// Parse a `KEY=VALUE` environment override for a session. Keys follow the
+/// same character set as injected secret names (letters, digits, `_`, `-`, `.`).
+pub fn parse_env_override(s: &str) -> Result<(String, String)> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| AuthyError::Other(format!("Invalid env override '{}': expected KEY=VALUE", s)))?;
+    let valid_key = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid_key {
+        return Err(AuthyError::Other(format!(
+            "Invalid env override name '{}': use letters, digits, '_', '-' and '.', not starting with a digit",
+            key
+        )));
+    }
+    if value.contains('\0') {
+        return Err(AuthyError::Other(format!(
+            "Invalid env override '{}': value contains a NUL byte",
+            key
+        )));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 /// Generate a short unique session ID.
 pub fn generate_session_id() -> String {
     let mut bytes = [0u8; 8];
     rand::thread_rng().fill_bytes(&mut bytes);
     hex::encode(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ttl_secs: i64, max_lifetime_secs: i64) -> SessionRecord {
+        let now = Utc::now();
+        SessionRecord {
+            id: "s1".into(),
+            scope: "deploy".into(),
+            token_hmac: vec![],
+            created_at: now,
+            expires_at: now + chrono::Duration::seconds(ttl_secs),
+            revoked: false,
+            label: None,
+            run_only: false,
+            env: BTreeMap::new(),
+            capabilities: default_capabilities(),
+            sliding_ttl_secs: Some(ttl_secs),
+            max_expires_at: Some(now + chrono::Duration::seconds(max_lifetime_secs)),
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_slide_waits_for_half_the_ttl_and_stops_at_max() {
+        let mut s = record(3600, 5400);
+        let start = s.created_at;
+
+        // Fresh session: not worth a vault write yet
+        assert!(!s.slide(start + chrono::Duration::minutes(10)));
+
+        // Past half the TTL: extended, but capped at the max lifetime
+        assert!(s.slide(start + chrono::Duration::minutes(40)));
+        assert_eq!(s.expires_at, start + chrono::Duration::minutes(90));
+        assert!(!s.slide(start + chrono::Duration::minutes(80)));
+    }
+
+    #[test]
+    fn test_refresh_respects_max_and_revocation() {
+        let mut s = record(3600, 7200);
+        let start = s.created_at;
+        s.refresh(chrono::Duration::hours(5), start).unwrap();
+        assert_eq!(s.expires_at, start + chrono::Duration::hours(2));
+
+        assert!(s.refresh(s.default_ttl(), start + chrono::Duration::hours(2)).is_err());
+
+        s.revoked = true;
+        assert!(matches!(
+            s.refresh(s.default_ttl(), start),
+            Err(AuthyError::TokenRevoked)
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::process::Command;
 
 use crate::error::{AuthyError, Result};
@@ -31,11 +31,13 @@ pub fn transform_name(name: &str, opts: &NamingOptions) -> String {
 }
 
 /// Run a subprocess with the given secrets injected as environment variables.
-/// Returns the exit code of the subprocess.
+/// `extra_env` (a session's fixed overrides) is set first, so a secret with
+/// the same variable name wins. Returns the exit code of the subprocess.
 pub fn run_with_secrets(
     command: &[String],
     secrets: &HashMap<String, String>,
     naming: &NamingOptions,
+    extra_env: &BTreeMap<String, String>,
 ) -> Result<i32> {
     if command.is_empty() {
         return Err(AuthyError::Other("No command specified".into()));
@@ -48,6 +50,7 @@ pub fn run_with_secrets(
 
     let status = Command::new(&command[0])
         .args(&command[1..])
+        .envs(extra_env)
         .envs(&env_vars)
         .env_remove("AUTHY_PASSPHRASE")
         .env_remove("AUTHY_TOKEN")
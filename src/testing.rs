@@ -0,0 +1,152 @@
+//! Throwaway vaults for testing code that uses authy.
+//!
+//! Enabled with the `testing` feature. A [`TestVault`] lives in its own
+//! temp directory, removed on drop, and hands out [`AuthyClient`]s bound to
+//! that directory with [`AuthyClient::with_home`] — no `HOME` or
+//! `AUTHY_HOME` mutation, so tests using it can run in parallel.
+//!
+//! Vaults are encrypted to a generated keyfile, so opening one is cheap.
+//! [`TestVault::with_passphrase`] makes one that goes through the
+//! passphrase KDF instead, for testing that path.
+//!
+//! ```
+//! use authy::testing::TestVault;
+//!
+//! let vault = TestVault::new()?;
+//! vault.seed_secret("db-url", "postgres://localhost/test")?;
+//! vault.seed_policy("app", &["db-*"], &[])?;
+//!
+//! let client = vault.client()?;
+//! assert_eq!(client.get("db-url")?.as_deref(), Some("postgres://localhost/test"));
+//! assert!(client.test_policy("app", "db-url")?);
+//! # Ok::<(), authy::error::AuthyError>(())
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::api::AuthyClient;
+use crate::error::Result;
+use crate::vault::crypto;
+
+/// Passphrase a [`TestVault::with_passphrase`] vault is initialized with.
+pub const TEST_PASSPHRASE: &str = "authy-test-passphrase";
+
+/// An initialized vault in a temp directory, deleted when dropped.
+pub struct TestVault {
+    dir: TempDir,
+    /// `None` for a passphrase vault.
+    keyfile: Option<PathBuf>,
+}
+
+impl TestVault {
+    /// Create a temp directory and initialize an empty vault in it,
+    /// encrypted to a keyfile generated next to it.
+    pub fn new() -> Result<Self> {
+        let dir = TempDir::new()?;
+        let keyfile = dir.path().join("test.key");
+        let (secret_key, _) = crypto::generate_keypair();
+        fs::write(&keyfile, secret_key)?;
+        Self::init(dir, Some(keyfile))
+    }
+
+    /// Like [`new`](Self::new), but encrypted with [`TEST_PASSPHRASE`].
+    /// Every client pays for the passphrase KDF on each vault access.
+    pub fn with_passphrase() -> Result<Self> {
+        Self::init(TempDir::new()?, None)
+    }
+
+    fn init(dir: TempDir, keyfile: Option<PathBuf>) -> Result<Self> {
+        let vault = Self { dir, keyfile };
+        vault.client()?.init_vault()?;
+        Ok(vault)
+    }
+
+    /// The authy directory holding `vault.age`, `audit.log` and
+    /// `authy.toml`. Point the CLI at it with `AUTHY_HOME`.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The keyfile the vault is encrypted to, for `AUTHY_KEYFILE`. `None`
+    /// for a [`with_passphrase`](Self::with_passphrase) vault.
+    pub fn keyfile(&self) -> Option<&Path> {
+        self.keyfile.as_deref()
+    }
+
+    /// A master client bound to this vault.
+    pub fn client(&self) -> Result<AuthyClient> {
+        let client = match &self.keyfile {
+            Some(keyfile) => AuthyClient::with_keyfile(&keyfile.to_string_lossy())?,
+            None => AuthyClient::with_passphrase(TEST_PASSPHRASE)?,
+        };
+        Ok(client.with_actor("test").with_home(self.path()))
+    }
+
+    /// Store a secret, overwriting any existing value.
+    pub fn seed_secret(&self, name: &str, value: &str) -> Result<()> {
+        self.client()?.store(name, value, true)
+    }
+
+    /// Store several secrets at once (see [`seed_secret`](Self::seed_secret)).
+    pub fn seed_secrets(&self, secrets: &[(&str, &str)]) -> Result<()> {
+        let client = self.client()?;
+        for (name, value) in secrets {
+            client.store(name, value, true)?;
+        }
+        Ok(())
+    }
+
+    /// Create a policy with the given allow and deny patterns.
+    pub fn seed_policy(&self, name: &str, allow: &[&str], deny: &[&str]) -> Result<()> {
+        self.client()?.create_policy(
+            name,
+            allow.iter().map(|p| p.to_string()).collect(),
+            deny.iter().map(|p| p.to_string()).collect(),
+            None,
+            false,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vaults_are_isolated() {
+        let first = TestVault::new().unwrap();
+        let second = TestVault::new().unwrap();
+        first.seed_secrets(&[("a", "1"), ("b", "2")]).unwrap();
+        second.seed_secret("a", "other").unwrap();
+
+        assert_eq!(first.client().unwrap().get("a").unwrap().as_deref(), Some("1"));
+        assert_eq!(second.client().unwrap().get("a").unwrap().as_deref(), Some("other"));
+        assert_eq!(second.client().unwrap().list(None).unwrap(), vec!["a"]);
+        assert!(first.path().join("vault.age").exists());
+    }
+
+    #[test]
+    fn test_seed_policy() {
+        let vault = TestVault::new().unwrap();
+        vault.seed_secrets(&[("db-url", "x"), ("db-admin", "y")]).unwrap();
+        vault.seed_policy("app", &["db-*"], &["db-admin"]).unwrap();
+
+        let client = vault.client().unwrap();
+        assert!(client.test_policy("app", "db-url").unwrap());
+        assert!(!client.test_policy("app", "db-admin").unwrap());
+    }
+
+    #[test]
+    fn test_passphrase_vault() {
+        let vault = TestVault::with_passphrase().unwrap();
+        assert!(vault.keyfile().is_none());
+        vault.seed_secret("a", "1").unwrap();
+        let client = AuthyClient::with_passphrase(TEST_PASSPHRASE)
+            .unwrap()
+            .with_home(vault.path());
+        assert_eq!(client.get("a").unwrap().as_deref(), Some("1"));
+    }
+}
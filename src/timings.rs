@@ -0,0 +1,70 @@
+//! Opt-in phase timings behind the global `--timings` flag.
+//!
+//! The vault and auth code record how long each phase takes (keyfile read,
+//! KDF, decrypt, ...). Recording is a no-op until [`enable`] is called, so
+//! library users pay only an atomic load per phase.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static START: OnceLock<Instant> = OnceLock::new();
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Start recording. The total is measured from this call.
+pub fn enable() {
+    let _ = START.set(Instant::now());
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether timings are being recorded.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add `elapsed` to `phase`.
+pub fn record(phase: &'static str, elapsed: Duration) {
+    if !is_enabled() {
+        return;
+    }
+    if let Ok(mut phases) = PHASES.lock() {
+        match phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+    }
+}
+
+/// Run `f`, recording its duration under `phase`.
+pub fn time<T>(phase: &'static str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    record(phase, started.elapsed());
+    result
+}
+
+/// Phases recorded so far, in first-seen order, followed by `operation`
+/// (everything not covered by a phase) and `total`.
+pub fn breakdown() -> Vec<(&'static str, Duration)> {
+    let total = START.get().map(|s| s.elapsed()).unwrap_or_default();
+    let mut phases = PHASES.lock().map(|p| p.clone()).unwrap_or_default();
+    let covered: Duration = phases.iter().map(|(_, d)| *d).sum();
+    phases.push(("operation", total.saturating_sub(covered)));
+    phases.push(("total", total));
+    phases
+}
+
+/// Print the breakdown to stderr, if timings are enabled.
+pub fn report() {
+    if !is_enabled() {
+        return;
+    }
+    eprintln!("timings:");
+    for (phase, elapsed) in breakdown() {
+        eprintln!("  {:<14} {:>10.2} ms", phase, elapsed.as_secs_f64() * 1000.0);
+    }
+}
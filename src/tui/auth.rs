@@ -93,7 +93,9 @@ pub fn draw(frame: &mut Frame, app: &TuiApp) {
     let inner = block.inner(form);
     frame.render_widget(block, form);
 
-    let vault_path = vault::vault_path().display().to_string();
+    let vault_path = vault::vault_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
 
     let mut y = inner.y;
 
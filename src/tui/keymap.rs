@@ -0,0 +1,281 @@
+//! Key bindings for the main screen — one table of every action, shared by
+//! the `:` / Ctrl+P command palette and the context-sensitive `?` help overlay.
+
+use crossterm::event::KeyCode;
+
+use super::{PopupKind, Section};
+
+/// A main-screen action. Running it from the palette dispatches `code` as if
+/// the key had been pressed in `section`.
+#[derive(Debug)]
+pub struct Action {
+    pub keys: &'static str,
+    pub label: &'static str,
+    /// Section the action belongs to (None = available everywhere).
+    pub section: Option<Section>,
+    pub code: KeyCode,
+    pub requires_write: bool,
+    pub requires_reveal: bool,
+}
+
+const fn action(
+    keys: &'static str,
+    label: &'static str,
+    section: Option<Section>,
+    code: KeyCode,
+) -> Action {
+    Action {
+        keys,
+        label,
+        section,
+        code,
+        requires_write: false,
+        requires_reveal: false,
+    }
+}
+
+const fn write_action(
+    keys: &'static str,
+    label: &'static str,
+    section: Section,
+    code: KeyCode,
+) -> Action {
+    Action {
+        requires_write: true,
+        ..action(keys, label, Some(section), code)
+    }
+}
+
+/// Every action reachable from the main screen.
+pub const ACTIONS: &[Action] = &[
+    action("1", "Go to Secrets", None, KeyCode::Char('1')),
+    action("2", "Go to Policies", None, KeyCode::Char('2')),
+    action("3", "Go to Sessions", None, KeyCode::Char('3')),
+    action("4", "Go to Audit", None, KeyCode::Char('4')),
+    write_action("s", "Store new secret", Section::Secrets, KeyCode::Char('s')),
+    Action {
+        requires_reveal: true,
+        ..action("Enter", "Reveal secret", Some(Section::Secrets), KeyCode::Enter)
+    },
+    write_action("r", "Rotate secret", Section::Secrets, KeyCode::Char('r')),
+    write_action("d", "Delete secret", Section::Secrets, KeyCode::Char('d')),
+    write_action("c", "Create policy", Section::Policies, KeyCode::Char('c')),
+    write_action("e", "Edit policy", Section::Policies, KeyCode::Char('e')),
+    write_action("d", "Delete policy", Section::Policies, KeyCode::Char('d')),
+    action("t", "Test policy", Some(Section::Policies), KeyCode::Char('t')),
+    write_action("c", "Create session", Section::Sessions, KeyCode::Char('c')),
+    write_action("r", "Revoke session", Section::Sessions, KeyCode::Char('r')),
+    write_action("R", "Revoke all sessions", Section::Sessions, KeyCode::Char('R')),
+    action("/", "Filter log", Some(Section::Audit), KeyCode::Char('/')),
+    action("v", "Verify chain", Some(Section::Audit), KeyCode::Char('v')),
+    action("V", "Verify time range", Some(Section::Audit), KeyCode::Char('V')),
+    action("x", "Export filtered entries", Some(Section::Audit), KeyCode::Char('x')),
+    action("?", "Show help", None, KeyCode::Char('?')),
+    action("q", "Quit", None, KeyCode::Char('q')),
+];
+
+/// Navigation keys listed at the top of the main-screen help.
+const NAVIGATION: &[(&str, &str)] = &[
+    ("Tab/1-4", "Switch section"),
+    ("j/k ↑/↓", "Navigate list"),
+    (": Ctrl+P", "Command palette"),
+    ("F1", "Help (also inside popups)"),
+];
+
+impl Action {
+    /// Whether the action is permitted for the current auth context.
+    pub fn allowed(&self, can_write: bool, can_reveal: bool) -> bool {
+        (!self.requires_write || can_write) && (!self.requires_reveal || can_reveal)
+    }
+
+    /// Text shown (and searched) in the palette, e.g. "Secrets: Rotate secret".
+    pub fn title(&self) -> String {
+        match self.section {
+            Some(section) => format!("{}: {}", section.label(), self.label),
+            None => self.label.to_string(),
+        }
+    }
+}
+
+/// Score how well `query` fuzzy-matches `text`: every query character must
+/// appear in order. Consecutive runs and word starts score higher, gaps lower.
+/// Returns None if `query` is not a subsequence of `text`.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut prev: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let pos = (next..text.len()).find(|&i| text[i] == qc)?;
+        score += 1;
+        if prev.is_some_and(|p| p + 1 == pos) {
+            score += 3;
+        }
+        if pos == 0 || !text[pos - 1].is_alphanumeric() {
+            score += 2;
+        }
+        score -= (pos - next).min(5) as i32;
+        prev = Some(pos);
+        next = pos + 1;
+    }
+
+    Some(score)
+}
+
+/// Palette entries matching `query`, best match first (table order on ties).
+pub fn palette_matches(query: &str, can_write: bool, can_reveal: bool) -> Vec<&'static Action> {
+    let mut scored: Vec<(i32, usize, &Action)> = ACTIONS
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.allowed(can_write, can_reveal))
+        .filter_map(|(i, a)| fuzzy_score(query, &a.title()).map(|s| (s, i, a)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, a)| a).collect()
+}
+
+/// Help lines for the main screen: navigation, the active section's actions,
+/// then global actions.
+pub fn section_help(section: Section, can_write: bool, can_reveal: bool) -> Vec<(&'static str, &'static str)> {
+    let mut lines: Vec<(&str, &str)> = NAVIGATION.to_vec();
+    lines.push(("", ""));
+    lines.push(("", section.label()));
+    lines.extend(
+        ACTIONS
+            .iter()
+            .filter(|a| a.section == Some(section) && a.allowed(can_write, can_reveal))
+            .map(|a| (a.keys, a.label)),
+    );
+    lines.push(("", ""));
+    lines.extend(
+        ACTIONS
+            .iter()
+            .filter(|a| a.section.is_none() && !a.keys.starts_with(char::is_numeric))
+            .map(|a| (a.keys, a.label)),
+    );
+    lines
+}
+
+/// Help lines for the keys accepted by a popup, with a title naming it.
+pub fn popup_help(popup: &PopupKind) -> (&'static str, Vec<(&'static str, &'static str)>) {
+    match popup {
+        PopupKind::RevealSecret { .. } => (
+            "Reveal secret",
+            vec![
+                ("Ctrl+R", "Toggle mask"),
+                ("Ctrl+Y", "Copy to clipboard"),
+                ("j/k", "Scroll"),
+                ("p", "Raw/pretty JSON"),
+                ("Esc/q", "Close"),
+            ],
+        ),
+        PopupKind::StoreForm { .. } | PopupKind::PolicyForm { .. } | PopupKind::RotateForm { .. } => (
+            "Form",
+            vec![
+                ("Tab", "Next field"),
+                ("Shift+Tab", "Previous field"),
+                ("Enter", "Save"),
+                ("Ctrl+R", "Toggle mask"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        PopupKind::PolicyTest { .. } => (
+            "Test policy",
+            vec![
+                ("Enter", "Test the typed name"),
+                ("Tab", "Toggle all-secrets view"),
+                ("j/k", "Scroll results"),
+                ("Esc", "Close"),
+            ],
+        ),
+        PopupKind::SessionForm { .. } => (
+            "Create session",
+            vec![
+                ("Tab", "Next field"),
+                ("←/→", "Change scope"),
+                ("Space", "Toggle run-only"),
+                ("Enter", "Create"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        PopupKind::AuditVerifyRange { .. } => (
+            "Verify time range",
+            vec![
+                ("Tab", "Next field"),
+                ("Enter", "Verify"),
+                ("From/To", "YYYY-MM-DD, RFC 3339 or 24h ago, blank = open"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        PopupKind::AuditFilter { .. } => (
+            "Audit filter",
+            vec![
+                ("words", "Match operation, actor, outcome or secret"),
+                ("op:get", "Operation (also outcome:denied)"),
+                ("actor:token", "Actor containing the text"),
+                ("secret:db-*", "Secret name or glob"),
+                ("since:24h", "Also until:, YYYY-MM-DD or RFC 3339"),
+                ("Enter", "Apply"),
+                ("Esc", "Cancel"),
+            ],
+        ),
+        PopupKind::AuditExport { .. } => (
+            "Audit",
+            vec![("Enter", "Apply"), ("Esc", "Cancel")],
+        ),
+        PopupKind::CommandPalette { .. } => (
+            "Command palette",
+            vec![
+                ("type", "Fuzzy search"),
+                ("↑/↓", "Select"),
+                ("Enter", "Run action"),
+                ("Esc", "Close"),
+            ],
+        ),
+        PopupKind::ShowToken { .. } => (
+            "Session token",
+            vec![("Ctrl+Y", "Copy to clipboard"), ("any key", "Close")],
+        ),
+        PopupKind::ConfirmDelete { .. }
+        | PopupKind::ConfirmDeletePolicy { .. }
+        | PopupKind::ConfirmRevokeSession { .. }
+        | PopupKind::ConfirmRevokeAllSessions => (
+            "Confirm",
+            vec![("y", "Yes"), ("any key", "No")],
+        ),
+        PopupKind::AuditVerifyResult { .. } | PopupKind::StatusMessage { .. } | PopupKind::Help { .. } => (
+            "Message",
+            vec![("any key", "Close")],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_requires_subsequence() {
+        assert!(fuzzy_score("rot", "Secrets: Rotate secret").is_some());
+        assert!(fuzzy_score("rsec", "Secrets: Rotate secret").is_some());
+        assert!(fuzzy_score("xyz", "Secrets: Rotate secret").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts() {
+        let direct = fuzzy_score("vc", "verify chain").unwrap();
+        let scattered = fuzzy_score("vc", "revoke secret").unwrap();
+        assert!(direct > scattered);
+    }
+
+    #[test]
+    fn palette_hides_write_actions_without_write() {
+        let all = palette_matches("", true, true);
+        let read_only = palette_matches("", false, false);
+        assert!(all.iter().any(|a| a.label == "Store new secret"));
+        assert!(!read_only.iter().any(|a| a.requires_write || a.requires_reveal));
+        assert_eq!(palette_matches("rotate", true, true)[0].label, "Rotate secret");
+    }
+}
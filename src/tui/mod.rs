@@ -1,4 +1,6 @@
 mod auth;
+mod keymap;
+mod preview;
 mod widgets;
 
 use std::io;
@@ -12,10 +14,12 @@ use ratatui::widgets::{Block, Borders, Paragraph};
 
 use authy::audit;
 use authy::auth::context::{AuthContext, AuthMethod};
+use authy::config::{LimitsConfig, SessionsConfig};
 use authy::error::{AuthyError, Result};
+use authy::hooks::{self, RevocationEvent};
 use authy::policy::Policy;
 use authy::session;
-use authy::vault::{self, secret::SecretEntry, Vault, VaultKey};
+use authy::vault::{self, secret::{Provenance, SecretEntry, ValueEncoding}, Vault, VaultKey};
 
 /// Which sidebar section is active.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,7 +31,7 @@ pub enum Section {
 }
 
 impl Section {
-    pub fn label(&self) -> &str {
+    pub fn label(&self) -> &'static str {
         match self {
             Section::Secrets => "Secrets",
             Section::Policies => "Policies",
@@ -73,6 +77,10 @@ pub enum PopupKind {
         value: String,
         masked: bool,
         auto_close_at: Instant,
+        scroll: u16,
+        pretty: bool, // pretty-print JSON values
+        /// Where the value came from (its provenance).
+        origin: String,
     },
     /// Store a new secret form.
     StoreForm {
@@ -112,18 +120,22 @@ pub enum PopupKind {
     ConfirmDeletePolicy {
         name: String,
     },
-    /// Test a policy against a secret name.
+    /// Test a policy against a secret name, or against every secret at once.
     PolicyTest {
         scope: String,
         name_input: widgets::TextInput,
         result: Option<String>,
+        bulk: Option<Vec<(String, bool)>>, // Some = bulk mode, (name, allowed)
+        scroll: usize,
     },
     /// Create a session token form.
     SessionForm {
         scope_index: usize, // index into policies list
         policy_names: Vec<String>,
         ttl_input: widgets::TextInput,
-        focused_field: usize, // 0=scope, 1=ttl
+        label_input: widgets::TextInput,
+        run_only: bool,
+        focused_field: usize, // 0=scope, 1=ttl, 2=label, 3=run-only
         error: Option<String>,
     },
     /// Show a newly created session token (one-time display).
@@ -147,10 +159,43 @@ pub enum PopupKind {
     AuditFilter {
         filter_input: widgets::TextInput,
     },
-    /// Help overlay.
-    Help,
-    /// Vault changed externally — prompt to reload.
-    VaultChanged,
+    /// Export the filtered audit entries to a file (.csv or .json).
+    AuditExport {
+        path_input: widgets::TextInput,
+        error: Option<String>,
+    },
+    /// Verify the audit chain over a time range.
+    AuditVerifyRange {
+        from_input: widgets::TextInput,
+        to_input: widgets::TextInput,
+        focused_field: usize, // 0=from, 1=to
+        error: Option<String>,
+    },
+    /// Help overlay for the current section, or for the popup it was opened over.
+    Help {
+        title: String,
+        lines: Vec<(&'static str, &'static str)>,
+        previous: Option<Box<PopupKind>>,
+    },
+    /// Command palette: fuzzy-search and run any main-screen action.
+    CommandPalette {
+        query_input: widgets::TextInput,
+        matches: Vec<&'static keymap::Action>,
+        selected: usize,
+    },
+}
+
+impl PopupKind {
+    /// Whether this popup holds unsaved input that will be written to the vault.
+    pub fn is_form(&self) -> bool {
+        matches!(
+            self,
+            PopupKind::StoreForm { .. }
+                | PopupKind::RotateForm { .. }
+                | PopupKind::PolicyForm { .. }
+                | PopupKind::SessionForm { .. }
+        )
+    }
 }
 
 /// Top-level screen state.
@@ -194,10 +239,21 @@ pub struct TuiApp {
     // Audit entries cache (loaded on demand)
     pub audit_entries: Vec<audit::AuditEntry>,
     pub audit_filter: String,
+    /// `audit_filter` parsed; what the Audit section shows and exports.
+    pub audit_query: audit::query::AuditQuery,
     pub audit_scroll: usize,
 
     // Vault change detection
     pub last_vault_mtime: Option<SystemTime>,
+    // The vault was reloaded while a form was open: show the conflict banner
+    pub external_change: bool,
+
+    // Session token from AUTHY_TOKEN (narrows auth to the token's scope)
+    pub token: Option<String>,
+    pub session: Option<session::SessionRecord>,
+
+    // False under `--color never` or NO_COLOR: draw in monochrome
+    pub colors: bool,
 }
 
 impl TuiApp {
@@ -217,8 +273,13 @@ impl TuiApp {
             popup: None,
             audit_entries: Vec::new(),
             audit_filter: String::new(),
+            audit_query: audit::query::AuditQuery::default(),
             audit_scroll: 0,
             last_vault_mtime: None,
+            external_change: false,
+            token: None,
+            session: None,
+            colors: true,
         }
     }
 
@@ -242,11 +303,24 @@ impl TuiApp {
         self.cursor[self.section_idx()] = pos;
     }
 
-    /// Save the vault to disk and record the new mtime.
+    /// Save the vault to disk and record the new mtime. Sessions past the
+    /// retention window are pruned here rather than in `vault::save_vault`,
+    /// so the in-memory vault matches what was written.
     pub fn save_vault(&mut self) -> Result<()> {
-        if let (Some(v), Some(k)) = (&self.vault, &self.key) {
+        let config = SessionsConfig::load()?;
+        let mut pruned = 0;
+        if let (Some(v), Some(k)) = (&mut self.vault, &self.key) {
+            pruned = vault::auto_prune_sessions(v, &config)?;
             vault::save_vault(v, k)?;
         }
+        if pruned > 0 {
+            let _ = self.log_audit(
+                "session.prune",
+                None,
+                "success",
+                Some(&format!("count={}, retention={}", pruned, config.retention)),
+            );
+        }
         self.record_vault_mtime();
         Ok(())
     }
@@ -277,7 +351,7 @@ impl TuiApp {
     ) -> Result<()> {
         if let Some(audit_key) = self.audit_key() {
             audit::log_event(
-                &vault::audit_path(),
+                &vault::audit_path()?,
                 operation,
                 secret,
                 &self.actor_name(),
@@ -290,13 +364,85 @@ impl TuiApp {
     }
 
     /// Try to authenticate with the current auth input or keyfile.
+    /// When a session token is set, it must also validate against the vault.
+    /// Failed attempts are recorded in the auth events log.
     pub fn try_auth(&mut self) -> Result<()> {
-        auth::try_authenticate(self)
+        let method = if self.keyfile.is_some() { "keyfile" } else { "passphrase" };
+        if let Err(e) = auth::try_authenticate(self) {
+            record_auth_failure(method, &e);
+            return Err(e);
+        }
+        if let Err(e) = self.apply_session_token() {
+            self.key = None;
+            self.auth_ctx = None;
+            self.vault = None;
+            record_auth_failure("token", &e);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Validate the session token (if any) against the loaded vault and
+    /// narrow the auth context to the token's scope and permissions.
+    pub fn apply_session_token(&mut self) -> Result<()> {
+        let token = match &self.token {
+            Some(t) => t.clone(),
+            None => return Ok(()),
+        };
+        let hmac_key = self
+            .session_hmac_key()
+            .ok_or_else(|| AuthyError::AuthFailed("No vault key available".into()))?;
+        let record = match &self.vault {
+            Some(v) => session::validate_token(&token, &v.sessions, &hmac_key)?.clone(),
+            None => return Err(AuthyError::AuthFailed("No vault loaded".into())),
+        };
+        self.auth_ctx = Some(AuthContext::from_token(
+            record.id.clone(),
+            record.scope.clone(),
+            record.run_only,
+            record.capabilities.clone(),
+        ));
+        self.session = Some(record);
+        Ok(())
+    }
+
+    /// Whether the current auth context may modify the vault.
+    pub fn can_write(&self) -> bool {
+        self.auth_ctx.as_ref().map(|ctx| ctx.can_write).unwrap_or(false)
+    }
+
+    /// Whether the current auth context may reveal secret values.
+    pub fn can_reveal(&self) -> bool {
+        self.auth_ctx.as_ref().map(|ctx| !ctx.run_only).unwrap_or(false)
+    }
+
+    /// Secrets visible to the current auth context, in vault order.
+    /// With a session token, only secrets allowed by the token's scope are listed.
+    pub fn visible_secrets(&self) -> Vec<(&String, &SecretEntry)> {
+        let vault = match &self.vault {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+        let scope = match self.auth_ctx.as_ref().and_then(|ctx| ctx.scope.as_deref()) {
+            Some(s) => s,
+            None => return vault.secrets.iter().collect(),
+        };
+        let policy = match vault.effective_policy(scope) {
+            Ok(p) => p,
+            Err(_) => return Vec::new(),
+        };
+        let names: Vec<&str> = vault.secrets.keys().map(|s| s.as_str()).collect();
+        let allowed = policy.filter_secrets(&names).unwrap_or_default();
+        vault
+            .secrets
+            .iter()
+            .filter(|(name, _)| allowed.binary_search(&name.as_str()).is_ok())
+            .collect()
     }
 
     /// Load audit entries from disk.
     pub fn load_audit_entries(&mut self) {
-        match audit::read_entries(&vault::audit_path()) {
+        match vault::audit_path().and_then(|path| audit::read_entries(&path)) {
             Ok(entries) => self.audit_entries = entries,
             Err(_) => self.audit_entries = Vec::new(),
         }
@@ -304,20 +450,7 @@ impl TuiApp {
 
     /// Get the filtered audit entries.
     pub fn filtered_audit_entries(&self) -> Vec<&audit::AuditEntry> {
-        if self.audit_filter.is_empty() {
-            self.audit_entries.iter().collect()
-        } else {
-            let filter = self.audit_filter.to_lowercase();
-            self.audit_entries
-                .iter()
-                .filter(|e| {
-                    e.operation.to_lowercase().contains(&filter)
-                        || e.actor.to_lowercase().contains(&filter)
-                        || e.outcome.to_lowercase().contains(&filter)
-                        || e.secret.as_deref().unwrap_or("").to_lowercase().contains(&filter)
-                })
-                .collect()
-        }
+        audit::query::filter(&self.audit_entries, &self.audit_query)
     }
 
     /// Derive session HMAC key from vault key.
@@ -330,16 +463,67 @@ impl TuiApp {
 
     /// Record the current vault file mtime for change detection.
     pub fn record_vault_mtime(&mut self) {
-        self.last_vault_mtime = std::fs::metadata(vault::vault_path())
-            .and_then(|m| m.modified())
-            .ok();
+        self.last_vault_mtime = vault::vault_path()
+            .ok()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .and_then(|m| m.modified().ok());
+    }
+
+    /// Reload the vault after another process changed it, keeping the
+    /// selection on the same item where it still exists. A token revoked in
+    /// the meantime sends the user back to the auth screen.
+    pub fn reload_vault(&mut self) -> Result<()> {
+        let fresh = match &self.key {
+            Some(key) => vault::load_vault(key)?,
+            None => return Ok(()),
+        };
+        let selected = self.selected_item();
+        self.vault = Some(fresh);
+        self.record_vault_mtime();
+
+        if let Err(e) = self.apply_session_token() {
+            self.key = None;
+            self.auth_ctx = None;
+            self.vault = None;
+            self.session = None;
+            self.popup = None;
+            self.auth_error = Some(format!("{}", e));
+            self.screen = Screen::Auth;
+            return Ok(());
+        }
+
+        let pos = selected
+            .and_then(|item| self.section_items().iter().position(|i| *i == item))
+            .unwrap_or_else(|| self.cursor_pos().min(list_len(self).saturating_sub(1)));
+        self.set_cursor_pos(pos);
+        Ok(())
+    }
+
+    /// Keys of the items listed in the current section (empty for Audit).
+    fn section_items(&self) -> Vec<String> {
+        let vault = match &self.vault {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+        match self.section {
+            Section::Secrets => self.visible_secrets().into_iter().map(|(n, _)| n.clone()).collect(),
+            Section::Policies => vault.policies.keys().cloned().collect(),
+            Section::Sessions => vault.sessions.iter().map(|s| s.id.clone()).collect(),
+            Section::Audit => Vec::new(),
+        }
+    }
+
+    /// The key of the item under the cursor.
+    fn selected_item(&self) -> Option<String> {
+        self.section_items().into_iter().nth(self.cursor_pos())
     }
 
     /// Check whether the vault file was modified since we last recorded mtime.
     pub fn vault_changed_externally(&self) -> bool {
-        let current = std::fs::metadata(vault::vault_path())
-            .and_then(|m| m.modified())
-            .ok();
+        let current = vault::vault_path()
+            .ok()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .and_then(|m| m.modified().ok());
         match (self.last_vault_mtime, current) {
             (Some(last), Some(now)) => now > last,
             _ => false,
@@ -347,8 +531,32 @@ impl TuiApp {
     }
 }
 
+/// Best-effort record of a rejected credential in the auth events log.
+fn record_auth_failure(method: &str, err: &AuthyError) {
+    if audit::auth_events::is_auth_failure(err) {
+        let _ = audit::auth_events::record_failure(method, err);
+    }
+}
+
 /// Copy data to the system clipboard via OSC 52 escape sequence.
 /// Writes to `/dev/tty` to bypass ratatui's alternate screen buffer.
+/// Status popup after a revocation, after running the `[hooks]` for `event`.
+/// A failing hook turns it into an error, but the revocation already stands.
+fn revocation_status(message: String, event: Option<RevocationEvent>) -> PopupKind {
+    match event.map(|e| hooks::on_revocation(&e)) {
+        Some(Err(e)) => PopupKind::StatusMessage {
+            message: format!("{} {}", message, e),
+            is_error: true,
+            auto_close_at: Instant::now() + Duration::from_secs(4),
+        },
+        _ => PopupKind::StatusMessage {
+            message,
+            is_error: false,
+            auto_close_at: Instant::now() + Duration::from_secs(2),
+        },
+    }
+}
+
 fn copy_to_clipboard(data: &str) -> bool {
     use base64::Engine;
     let encoded = base64::engine::general_purpose::STANDARD.encode(data);
@@ -362,13 +570,15 @@ fn copy_to_clipboard(data: &str) -> bool {
 }
 
 /// Main entry point — set up terminal, run event loop, restore terminal.
-pub fn run(keyfile: Option<String>) -> Result<()> {
+pub fn run(keyfile: Option<String>, colors: bool) -> Result<()> {
     // Check vault exists
     if !vault::is_initialized() {
         return Err(AuthyError::VaultNotInitialized);
     }
 
     let mut app = TuiApp::new(keyfile.clone());
+    app.token = std::env::var("AUTHY_TOKEN").ok().filter(|t| !t.is_empty());
+    app.colors = colors;
 
     // If keyfile provided, try to auth immediately (skip auth screen)
     if keyfile.is_some() {
@@ -448,10 +658,9 @@ fn event_loop(
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
 
-            // Check for external vault changes (main screen, no popup)
-            if app.screen == Screen::Main && app.popup.is_none() && app.vault_changed_externally()
-            {
-                app.popup = Some(PopupKind::VaultChanged);
+            // Pick up external vault changes so the next save doesn't clobber them
+            if app.screen == Screen::Main && app.vault_changed_externally() {
+                sync_external_changes(app, true);
             }
         }
 
@@ -465,6 +674,9 @@ fn event_loop(
         if should_close {
             app.popup = None;
         }
+        if !app.popup.as_ref().is_some_and(PopupKind::is_form) {
+            app.external_change = false;
+        }
 
         if app.should_quit {
             break;
@@ -474,8 +686,43 @@ fn event_loop(
     Ok(())
 }
 
+/// Reload a vault that another process (e.g. `authy store` in another
+/// terminal) has saved. An open form stays up, with a banner saying it will
+/// be saved against the reloaded vault; otherwise, with `announce`, a status
+/// message says the view was refreshed.
+fn sync_external_changes(app: &mut TuiApp, announce: bool) {
+    let editing = app.popup.as_ref().is_some_and(PopupKind::is_form);
+    match app.reload_vault() {
+        Ok(()) if editing => app.external_change = true,
+        Ok(()) if announce && app.popup.is_none() && app.screen == Screen::Main => {
+            app.popup = Some(PopupKind::StatusMessage {
+                message: "Vault changed on disk; reloaded.".into(),
+                is_error: false,
+                auto_close_at: Instant::now() + Duration::from_secs(2),
+            });
+        }
+        Ok(()) => {}
+        Err(e) if !editing && app.popup.is_none() => {
+            app.popup = Some(PopupKind::StatusMessage {
+                message: format!("Reload failed: {}", e),
+                is_error: true,
+                auto_close_at: Instant::now() + Duration::from_secs(3),
+            });
+        }
+        Err(_) => {}
+    }
+}
+
 /// Handle key input on the main dashboard screen.
 fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
+    // Apply the key to the latest vault, not a copy that is about to be stale
+    if app.vault_changed_externally() {
+        sync_external_changes(app, false);
+        if app.screen != Screen::Main {
+            return;
+        }
+    }
+
     // If a popup is active, handle popup input first
     if app.popup.is_some() {
         handle_popup_input(app, key);
@@ -537,13 +784,11 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             app.audit_scroll = app.audit_scroll.saturating_sub(20);
         }
         // Reveal secret on Enter (Secrets section)
-        KeyCode::Enter => {
-            if app.section == Section::Secrets {
-                open_reveal_popup(app);
-            }
+        KeyCode::Enter if app.section == Section::Secrets && app.can_reveal() => {
+            open_reveal_popup(app);
         }
         // Store new secret
-        KeyCode::Char('s') if app.section == Section::Secrets => {
+        KeyCode::Char('s') if app.section == Section::Secrets && app.can_write() => {
             app.popup = Some(PopupKind::StoreForm {
                 name_input: widgets::TextInput::new(false),
                 value_input: widgets::TextInput::new(true),
@@ -553,29 +798,25 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             });
         }
         // Rotate secret
-        KeyCode::Char('r') if app.section == Section::Secrets => {
-            if let Some(vault) = &app.vault {
-                if let Some((name, _)) = vault.secrets.iter().nth(app.cursor_pos()) {
-                    app.popup = Some(PopupKind::RotateForm {
-                        name: name.clone(),
-                        value_input: widgets::TextInput::new(true),
-                        error: None,
-                    });
-                }
+        KeyCode::Char('r') if app.section == Section::Secrets && app.can_write() => {
+            let selected = app.visible_secrets().get(app.cursor_pos()).map(|(name, _)| (*name).clone());
+            if let Some(name) = selected {
+                app.popup = Some(PopupKind::RotateForm {
+                    name,
+                    value_input: widgets::TextInput::new(true),
+                    error: None,
+                });
             }
         }
         // Delete secret
-        KeyCode::Char('d') if app.section == Section::Secrets => {
-            if let Some(vault) = &app.vault {
-                if let Some((name, _)) = vault.secrets.iter().nth(app.cursor_pos()) {
-                    app.popup = Some(PopupKind::ConfirmDelete {
-                        name: name.clone(),
-                    });
-                }
+        KeyCode::Char('d') if app.section == Section::Secrets && app.can_write() => {
+            let selected = app.visible_secrets().get(app.cursor_pos()).map(|(name, _)| (*name).clone());
+            if let Some(name) = selected {
+                app.popup = Some(PopupKind::ConfirmDelete { name });
             }
         }
         // Create policy
-        KeyCode::Char('c') if app.section == Section::Policies => {
+        KeyCode::Char('c') if app.section == Section::Policies && app.can_write() => {
             app.popup = Some(PopupKind::PolicyForm {
                 name_input: widgets::TextInput::new(false),
                 desc_input: widgets::TextInput::new(false),
@@ -587,21 +828,17 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             });
         }
         // Edit policy
-        KeyCode::Char('e') if app.section == Section::Policies => {
+        KeyCode::Char('e') if app.section == Section::Policies && app.can_write() => {
             if let Some(vault) = &app.vault {
                 if let Some((name, policy)) = vault.policies.iter().nth(app.cursor_pos()) {
                     let mut name_input = widgets::TextInput::new(false);
-                    name_input.value = name.clone();
-                    name_input.cursor_pos = name.len();
+                    name_input.set_value(name.clone());
                     let mut desc_input = widgets::TextInput::new(false);
-                    desc_input.value = policy.description.clone().unwrap_or_default();
-                    desc_input.cursor_pos = desc_input.value.len();
+                    desc_input.set_value(policy.description.clone().unwrap_or_default());
                     let mut allow_input = widgets::TextInput::new(false);
-                    allow_input.value = policy.allow.join(", ");
-                    allow_input.cursor_pos = allow_input.value.len();
+                    allow_input.set_value(policy.allow.join(", "));
                     let mut deny_input = widgets::TextInput::new(false);
-                    deny_input.value = policy.deny.join(", ");
-                    deny_input.cursor_pos = deny_input.value.len();
+                    deny_input.set_value(policy.deny.join(", "));
                     app.popup = Some(PopupKind::PolicyForm {
                         name_input,
                         desc_input,
@@ -615,7 +852,7 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             }
         }
         // Delete policy
-        KeyCode::Char('d') if app.section == Section::Policies => {
+        KeyCode::Char('d') if app.section == Section::Policies && app.can_write() => {
             if let Some(vault) = &app.vault {
                 if let Some((name, _)) = vault.policies.iter().nth(app.cursor_pos()) {
                     app.popup = Some(PopupKind::ConfirmDeletePolicy {
@@ -632,12 +869,14 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
                         scope: name.clone(),
                         name_input: widgets::TextInput::new(false),
                         result: None,
+                        bulk: None,
+                        scroll: 0,
                     });
                 }
             }
         }
         // Create session
-        KeyCode::Char('c') if app.section == Section::Sessions => {
+        KeyCode::Char('c') if app.section == Section::Sessions && app.can_write() => {
             if let Some(vault) = &app.vault {
                 let policy_names: Vec<String> = vault.policies.keys().cloned().collect();
                 if policy_names.is_empty() {
@@ -648,12 +887,13 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
                     });
                 } else {
                     let mut ttl_input = widgets::TextInput::new(false);
-                    ttl_input.value = "1h".to_string();
-                    ttl_input.cursor_pos = 2;
+                    ttl_input.set_value("1h");
                     app.popup = Some(PopupKind::SessionForm {
                         scope_index: 0,
                         policy_names,
                         ttl_input,
+                        label_input: widgets::TextInput::new(false),
+                        run_only: false,
                         focused_field: 0,
                         error: None,
                     });
@@ -661,7 +901,7 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             }
         }
         // Revoke session
-        KeyCode::Char('r') if app.section == Section::Sessions => {
+        KeyCode::Char('r') if app.section == Section::Sessions && app.can_write() => {
             if let Some(vault) = &app.vault {
                 if let Some(s) = vault.sessions.get(app.cursor_pos()) {
                     if !s.revoked {
@@ -673,14 +913,14 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
             }
         }
         // Revoke all sessions
-        KeyCode::Char('R') if app.section == Section::Sessions => {
+        KeyCode::Char('R') if app.section == Section::Sessions && app.can_write() => {
             app.popup = Some(PopupKind::ConfirmRevokeAllSessions);
         }
         // Audit: verify chain
         KeyCode::Char('v') if app.section == Section::Audit => {
             app.load_audit_entries();
             if let Some(audit_key) = app.audit_key() {
-                match audit::verify_chain(&vault::audit_path(), &audit_key) {
+                match vault::audit_path().and_then(|path| audit::verify_chain(&path, &audit_key)) {
                     Ok((count, _)) => {
                         app.popup = Some(PopupKind::AuditVerifyResult {
                             message: format!("Chain valid ({} entries)", count),
@@ -696,39 +936,101 @@ fn handle_main_input(app: &mut TuiApp, key: event::KeyEvent) {
                 }
             }
         }
+        // Audit: verify chain over a time range
+        KeyCode::Char('V') if app.section == Section::Audit => {
+            app.popup = Some(PopupKind::AuditVerifyRange {
+                from_input: widgets::TextInput::new(false),
+                to_input: widgets::TextInput::new(false),
+                focused_field: 0,
+                error: None,
+            });
+        }
+        // Audit: export filtered entries
+        KeyCode::Char('x') if app.section == Section::Audit => {
+            let mut path_input = widgets::TextInput::new(false);
+            path_input.set_value("audit-export.json");
+            app.popup = Some(PopupKind::AuditExport { path_input, error: None });
+        }
         // Audit: filter
         KeyCode::Char('/') if app.section == Section::Audit => {
             let mut filter_input = widgets::TextInput::new(false);
-            filter_input.value = app.audit_filter.clone();
-            filter_input.cursor_pos = filter_input.value.len();
+            filter_input.set_value(app.audit_filter.clone());
             app.popup = Some(PopupKind::AuditFilter { filter_input });
         }
         // Help overlay
-        KeyCode::Char('?') => {
-            app.popup = Some(PopupKind::Help);
+        KeyCode::Char('?') | KeyCode::F(1) => {
+            app.popup = Some(PopupKind::Help {
+                title: format!("Key Bindings: {}", app.section.label()),
+                lines: keymap::section_help(app.section, app.can_write(), app.can_reveal()),
+                previous: None,
+            });
         }
+        // Command palette
+        KeyCode::Char(':') => open_command_palette(app),
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => open_command_palette(app),
         _ => {}
     }
 }
 
+/// Open the command palette with every permitted action listed.
+fn open_command_palette(app: &mut TuiApp) {
+    app.popup = Some(PopupKind::CommandPalette {
+        query_input: widgets::TextInput::new(false),
+        matches: keymap::palette_matches("", app.can_write(), app.can_reveal()),
+        selected: 0,
+    });
+}
+
+/// Run a palette action by switching to its section and dispatching its key.
+fn run_palette_action(app: &mut TuiApp, action: &keymap::Action) {
+    if let Some(section) = action.section {
+        if app.section != section {
+            app.section = section;
+            if section == Section::Audit {
+                app.load_audit_entries();
+            }
+        }
+    }
+    handle_main_input(app, event::KeyEvent::new(action.code, KeyModifiers::NONE));
+}
+
 /// Open the reveal-secret popup for the currently selected secret.
 fn open_reveal_popup(app: &mut TuiApp) {
-    let vault = match &app.vault {
-        Some(v) => v,
-        None => return,
-    };
-
     let pos = app.cursor_pos();
-    if let Some((name, entry)) = vault.secrets.iter().nth(pos) {
+    let selected = app
+        .visible_secrets()
+        .get(pos)
+        .map(|(name, entry)| {
+            let origin = match entry.metadata.provenance {
+                Some(ref p) => p.to_string(),
+                None => "unknown (stored before provenance was recorded)".to_string(),
+            };
+            ((*name).clone(), entry.value.clone(), origin)
+        });
+    if let Some((name, value, origin)) = selected {
         app.popup = Some(PopupKind::RevealSecret {
-            name: name.clone(),
-            value: entry.value.clone(),
+            name,
+            value,
             masked: true,
             auto_close_at: Instant::now() + Duration::from_secs(30),
+            scroll: 0,
+            pretty: true,
+            origin,
         });
     }
 }
 
+/// Evaluate a policy against every secret in the vault, in vault order.
+fn evaluate_policy_bulk(app: &TuiApp, scope: &str) -> std::result::Result<Vec<(String, bool)>, String> {
+    let vault = app.vault.as_ref().ok_or_else(|| "No vault".to_string())?;
+    let policy = vault.effective_policy(scope).map_err(|e| format!("Error: {}", e))?;
+    let names: Vec<&str> = vault.secrets.keys().map(|k| k.as_str()).collect();
+    policy
+        .evaluate_all(&names)
+        .map(|results| results.into_iter().map(|(n, ok)| (n.to_string(), ok)).collect())
+        .map_err(|e| format!("Error: {}", e))
+}
+
 /// Handle key input when a popup is active.
 fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
     // Take ownership of the popup temporarily
@@ -737,8 +1039,19 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
         None => return,
     };
 
+    // F1 shows help for the active popup, returning to it afterwards
+    if key.code == KeyCode::F(1) && !matches!(popup, PopupKind::Help { .. }) {
+        let (title, lines) = keymap::popup_help(&popup);
+        app.popup = Some(PopupKind::Help {
+            title: format!("Key Bindings: {}", title),
+            lines,
+            previous: Some(Box::new(popup)),
+        });
+        return;
+    }
+
     match popup {
-        PopupKind::RevealSecret { mut masked, name, value, auto_close_at } => {
+        PopupKind::RevealSecret { mut masked, name, value, auto_close_at, mut scroll, mut pretty, origin } => {
             match key.code {
                 KeyCode::Esc | KeyCode::Char('q') => {
                     // Close popup (already taken)
@@ -755,8 +1068,26 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                     }
                     if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('r') {
                         masked = !masked;
+                        scroll = 0;
+                    }
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => scroll = scroll.saturating_add(1),
+                        KeyCode::Char('k') | KeyCode::Up => scroll = scroll.saturating_sub(1),
+                        KeyCode::PageDown => scroll = scroll.saturating_add(10),
+                        KeyCode::PageUp => scroll = scroll.saturating_sub(10),
+                        KeyCode::Home => scroll = 0,
+                        KeyCode::Char('p') => {
+                            pretty = !pretty;
+                            scroll = 0;
+                        }
+                        _ => {}
+                    }
+                    if !masked {
+                        let kind = preview::ValueKind::detect(&value);
+                        let lines = preview::render_value(&value, kind, pretty).lines().count();
+                        scroll = scroll.min(lines.saturating_sub(1) as u16);
                     }
-                    app.popup = Some(PopupKind::RevealSecret { name, value, masked, auto_close_at });
+                    app.popup = Some(PopupKind::RevealSecret { name, value, masked, auto_close_at, scroll, pretty, origin });
                 }
             }
         }
@@ -793,6 +1124,18 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         });
                         return;
                     }
+                    let name = match app.vault.as_ref().map(|v| vault::names::secret_key(v, &name)) {
+                        Some(Err(e)) => {
+                            app.popup = Some(PopupKind::StoreForm {
+                                name_input, value_input, tags_input, focused_field,
+                                error: Some(e.to_string()),
+                            });
+                            return;
+                        }
+                        Some(Ok(normalized)) => normalized,
+                        None => name,
+                    };
+                    let provenance = Provenance::new("store", &app.actor_name()).with_source("tui");
 
                     if let Some(ref mut vault) = app.vault {
                         if vault.secrets.contains_key(&name) {
@@ -802,11 +1145,19 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                             });
                             return;
                         }
+                        if let Err(e) = LimitsConfig::load().and_then(|l| l.check_secret(vault, &name, &value)) {
+                            app.popup = Some(PopupKind::StoreForm {
+                                name_input, value_input, tags_input, focused_field,
+                                error: Some(e.to_string()),
+                            });
+                            return;
+                        }
 
                         let mut entry = SecretEntry::new(value);
                         if !tags_str.is_empty() {
                             entry.metadata.tags = tags_str.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
                         }
+                        entry.metadata.provenance = Some(provenance);
                         vault.secrets.insert(name.clone(), entry);
                         vault.touch();
                     }
@@ -854,11 +1205,21 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         });
                         return;
                     }
+                    let provenance = Provenance::new("rotate", &app.actor_name()).with_source("tui");
 
                     if let Some(ref mut vault) = app.vault {
+                        if let Err(e) = LimitsConfig::load().and_then(|l| l.check_secret(vault, &name, &new_value)) {
+                            app.popup = Some(PopupKind::RotateForm {
+                                name, value_input,
+                                error: Some(e.to_string()),
+                            });
+                            return;
+                        }
                         if let Some(entry) = vault.secrets.get_mut(&name) {
                             entry.value = new_value;
+                            entry.encoding = ValueEncoding::Text;
                             entry.metadata.bump_version();
+                            entry.metadata.provenance = Some(provenance);
                             vault.touch();
                         }
                     }
@@ -889,6 +1250,17 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
         PopupKind::ConfirmDelete { name } => {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    let protected = app.vault.as_ref()
+                        .and_then(|v| v.secrets.get(&name))
+                        .is_some_and(|e| e.metadata.protected);
+                    if protected {
+                        app.popup = Some(PopupKind::StatusMessage {
+                            message: format!("Secret '{}' is protected: use `authy remove --approve-with`", name),
+                            is_error: true,
+                            auto_close_at: Instant::now() + Duration::from_secs(3),
+                        });
+                        return;
+                    }
                     if let Some(ref mut vault) = app.vault {
                         vault.secrets.remove(&name);
                         vault.touch();
@@ -906,7 +1278,7 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                     let _ = app.log_audit("remove", Some(&name), "success", None);
 
                     // Adjust cursor if it was at the end
-                    let len = app.vault.as_ref().map(|v| v.secrets.len()).unwrap_or(0);
+                    let len = app.visible_secrets().len();
                     if app.cursor_pos() >= len && len > 0 {
                         app.set_cursor_pos(len - 1);
                     }
@@ -955,12 +1327,25 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         });
                         return;
                     }
+                    let name = match app.vault.as_ref().map(|v| vault::names::policy_key(v, &name)) {
+                        Some(Err(e)) => {
+                            app.popup = Some(PopupKind::PolicyForm {
+                                name_input, desc_input, allow_input, deny_input, focused_field,
+                                error: Some(e.to_string()), editing,
+                            });
+                            return;
+                        }
+                        Some(Ok(normalized)) => normalized,
+                        None => name,
+                    };
 
+                    let actor = app.actor_name();
                     let allow: Vec<String> = allow_str.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
                     let deny: Vec<String> = deny_str.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
 
                     if let Some(ref mut vault) = app.vault {
                         if editing {
+                            vault.record_policy_revision(&name, &actor);
                             if let Some(policy) = vault.policies.get_mut(&name) {
                                 policy.allow = allow;
                                 policy.deny = deny;
@@ -979,6 +1364,8 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                             policy.description = if desc.is_empty() { None } else { Some(desc) };
                             vault.policies.insert(name.clone(), policy);
                         }
+                        let kind = if editing { "policy.update" } else { "policy.create" };
+                        vault.record_event(kind, &actor, Some(format!("policy={}", name)));
                         vault.touch();
                     }
 
@@ -1016,8 +1403,35 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
         PopupKind::ConfirmDeletePolicy { name } => {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    let protected = app.vault.as_ref()
+                        .and_then(|v| v.policies.get(&name))
+                        .is_some_and(|p| p.protected);
+                    if protected {
+                        app.popup = Some(PopupKind::StatusMessage {
+                            message: format!("Policy '{}' is protected: use `authy policy remove --approve-with`", name),
+                            is_error: true,
+                            auto_close_at: Instant::now() + Duration::from_secs(3),
+                        });
+                        return;
+                    }
+                    let children = app.vault.as_ref()
+                        .map(|v| v.policies_extending(&name).join(", "))
+                        .unwrap_or_default();
+                    if !children.is_empty() {
+                        app.popup = Some(PopupKind::StatusMessage {
+                            message: format!("Policy '{}' is extended by {}; update those first", name, children),
+                            is_error: true,
+                            auto_close_at: Instant::now() + Duration::from_secs(3),
+                        });
+                        return;
+                    }
+                    let actor = app.actor_name();
+                    let mut event = None;
                     if let Some(ref mut vault) = app.vault {
                         vault.policies.remove(&name);
+                        vault.policy_history.remove(&name);
+                        event = Some(RevocationEvent::policy_removed(&actor, &name, vault));
+                        vault.record_event("policy.remove", &actor, Some(format!("policy={}", name)));
                         vault.touch();
                     }
 
@@ -1037,41 +1451,69 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         app.set_cursor_pos(len - 1);
                     }
 
-                    app.popup = Some(PopupKind::StatusMessage {
-                        message: format!("Policy '{}' deleted.", name),
-                        is_error: false,
-                        auto_close_at: Instant::now() + Duration::from_secs(2),
-                    });
+                    app.popup = Some(revocation_status(format!("Policy '{}' deleted.", name), event));
                 }
                 _ => {
                     // Cancel
                 }
             }
         }
+        PopupKind::PolicyTest { scope, name_input, bulk: Some(results), mut scroll, .. } => {
+            match key.code {
+                KeyCode::Esc => {
+                    // Close
+                }
+                KeyCode::Tab => {
+                    // Back to single-name mode
+                    app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: None, bulk: None, scroll: 0 });
+                }
+                _ => {
+                    let max = results.len().saturating_sub(1);
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => scroll = (scroll + 1).min(max),
+                        KeyCode::Char('k') | KeyCode::Up => scroll = scroll.saturating_sub(1),
+                        KeyCode::PageDown => scroll = (scroll + 10).min(max),
+                        KeyCode::PageUp => scroll = scroll.saturating_sub(10),
+                        KeyCode::Home => scroll = 0,
+                        KeyCode::End => scroll = max,
+                        _ => {}
+                    }
+                    app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: None, bulk: Some(results), scroll });
+                }
+            }
+        }
         PopupKind::PolicyTest { scope, mut name_input, .. } => {
             match key.code {
                 KeyCode::Esc => {
                     // Close
                 }
+                KeyCode::Tab => {
+                    // Evaluate the policy against every secret in the vault
+                    match evaluate_policy_bulk(app, &scope) {
+                        Ok(results) => {
+                            app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: None, bulk: Some(results), scroll: 0 });
+                        }
+                        Err(msg) => {
+                            app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: Some(msg), bulk: None, scroll: 0 });
+                        }
+                    }
+                }
                 KeyCode::Enter => {
                     let secret_name = name_input.value.trim().to_string();
                     if secret_name.is_empty() {
                         app.popup = Some(PopupKind::PolicyTest {
                             scope, name_input,
                             result: Some("Enter a secret name to test".into()),
+                            bulk: None, scroll: 0,
                         });
                         return;
                     }
 
                     let result = if let Some(vault) = &app.vault {
-                        if let Some(policy) = vault.policies.get(&scope) {
-                            match policy.can_read(&secret_name) {
-                                Ok(true) => format!("ALLOWED: '{}' can read '{}'", scope, secret_name),
-                                Ok(false) => format!("DENIED: '{}' cannot read '{}'", scope, secret_name),
-                                Err(e) => format!("Error: {}", e),
-                            }
-                        } else {
-                            "Policy not found".into()
+                        match vault.effective_policy(&scope).and_then(|p| p.can_read(&secret_name)) {
+                            Ok(true) => format!("ALLOWED: '{}' can read '{}'", scope, secret_name),
+                            Ok(false) => format!("DENIED: '{}' cannot read '{}'", scope, secret_name),
+                            Err(e) => format!("Error: {}", e),
                         }
                     } else {
                         "No vault".into()
@@ -1080,43 +1522,49 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                     app.popup = Some(PopupKind::PolicyTest {
                         scope, name_input,
                         result: Some(result),
+                        bulk: None, scroll: 0,
                     });
                 }
                 _ => {
                     name_input.handle_input(key);
-                    app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: None });
+                    app.popup = Some(PopupKind::PolicyTest { scope, name_input, result: None, bulk: None, scroll: 0 });
                 }
             }
         }
-        PopupKind::SessionForm { mut scope_index, policy_names, mut ttl_input, mut focused_field, .. } => {
+        PopupKind::SessionForm { mut scope_index, policy_names, mut ttl_input, mut label_input, mut run_only, mut focused_field, .. } => {
             match key.code {
                 KeyCode::Esc => {
                     // Cancel
                 }
                 KeyCode::Tab => {
-                    focused_field = (focused_field + 1) % 2;
-                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, focused_field, error: None });
+                    focused_field = (focused_field + 1) % 4;
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
                 }
                 KeyCode::BackTab => {
-                    focused_field = if focused_field == 0 { 1 } else { 0 };
-                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, focused_field, error: None });
+                    focused_field = if focused_field == 0 { 3 } else { focused_field - 1 };
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
                 }
                 // Arrow keys to cycle scope when scope field is focused
                 KeyCode::Up | KeyCode::Left if focused_field == 0 => {
                     scope_index = if scope_index == 0 { policy_names.len().saturating_sub(1) } else { scope_index - 1 };
-                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, focused_field, error: None });
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
                 }
                 KeyCode::Down | KeyCode::Right if focused_field == 0 => {
                     scope_index = (scope_index + 1) % policy_names.len().max(1);
-                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, focused_field, error: None });
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
+                }
+                KeyCode::Char(' ') if focused_field == 3 => {
+                    run_only = !run_only;
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
                 }
                 KeyCode::Enter => {
                     let scope = policy_names.get(scope_index).cloned().unwrap_or_default();
                     let ttl_str = ttl_input.value.trim().to_string();
+                    let label = Some(label_input.value.trim().to_string()).filter(|l| !l.is_empty());
 
                     if scope.is_empty() {
                         app.popup = Some(PopupKind::SessionForm {
-                            scope_index, policy_names, ttl_input, focused_field,
+                            scope_index, policy_names, ttl_input, label_input, run_only, focused_field,
                             error: Some("No scope selected".into()),
                         });
                         return;
@@ -1126,13 +1574,25 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         Ok(d) => d,
                         Err(e) => {
                             app.popup = Some(PopupKind::SessionForm {
-                                scope_index, policy_names, ttl_input, focused_field,
+                                scope_index, policy_names, ttl_input, label_input, run_only, focused_field,
                                 error: Some(format!("Invalid TTL: {}", e)),
                             });
                             return;
                         }
                     };
 
+                    let limit_check = match app.vault {
+                        Some(ref vault) => LimitsConfig::load().and_then(|l| l.check_session(vault)),
+                        None => Ok(()),
+                    };
+                    if let Err(e) = limit_check {
+                        app.popup = Some(PopupKind::SessionForm {
+                            scope_index, policy_names, ttl_input, label_input, run_only, focused_field,
+                            error: Some(e.to_string()),
+                        });
+                        return;
+                    }
+
                     let hmac_key = match app.session_hmac_key() {
                         Some(k) => k,
                         None => {
@@ -1157,12 +1617,23 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                         created_at: now,
                         expires_at,
                         revoked: false,
-                        label: None,
-                        run_only: false,
+                        label,
+                        run_only,
+                        env: Default::default(),
+                        capabilities: session::default_capabilities(),
+                        sliding_ttl_secs: None,
+                        max_expires_at: None,
+                        revoked_at: None,
                     };
 
+                    let actor = app.actor_name();
                     if let Some(ref mut vault) = app.vault {
                         vault.sessions.push(record);
+                        vault.record_event(
+                            "session.create",
+                            &actor,
+                            Some(format!("session={}, scope={}", session_id, scope)),
+                        );
                         vault.touch();
                     }
 
@@ -1187,10 +1658,16 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                     });
                 }
                 _ => {
-                    if focused_field == 1 {
-                        ttl_input.handle_input(key);
+                    match focused_field {
+                        1 => {
+                            ttl_input.handle_input(key);
+                        }
+                        2 => {
+                            label_input.handle_input(key);
+                        }
+                        _ => {}
                     }
-                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, focused_field, error: None });
+                    app.popup = Some(PopupKind::SessionForm { scope_index, policy_names, ttl_input, label_input, run_only, focused_field, error: None });
                 }
             }
         }
@@ -1208,10 +1685,14 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
         PopupKind::ConfirmRevokeSession { session_id } => {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    let actor = app.actor_name();
+                    let mut event = None;
                     if let Some(ref mut vault) = app.vault {
                         if let Some(s) = vault.sessions.iter_mut().find(|s| s.id == session_id) {
-                            s.revoked = true;
+                            s.revoke(chrono::Utc::now());
+                            event = Some(RevocationEvent::sessions_revoked(&actor, [&*s]));
                         }
+                        vault.record_event("session.revoke", &actor, Some(format!("session={}", session_id)));
                         vault.touch();
                     }
 
@@ -1226,11 +1707,7 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
 
                     let _ = app.log_audit("session.revoke", None, "success", Some(&format!("session={}", session_id)));
 
-                    app.popup = Some(PopupKind::StatusMessage {
-                        message: format!("Session '{}' revoked.", session_id),
-                        is_error: false,
-                        auto_close_at: Instant::now() + Duration::from_secs(2),
-                    });
+                    app.popup = Some(revocation_status(format!("Session '{}' revoked.", session_id), event));
                 }
                 _ => {
                     // Cancel
@@ -1241,13 +1718,21 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
                     let mut count = 0;
+                    let actor = app.actor_name();
+                    let mut event = None;
                     if let Some(ref mut vault) = app.vault {
+                        let mut revoked = Vec::new();
                         for s in vault.sessions.iter_mut() {
                             if !s.revoked {
-                                s.revoked = true;
+                                s.revoke(chrono::Utc::now());
                                 count += 1;
+                                revoked.push(s.clone());
                             }
                         }
+                        if !revoked.is_empty() {
+                            event = Some(RevocationEvent::sessions_revoked(&actor, &revoked));
+                        }
+                        vault.record_event("session.revoke_all", &actor, Some(format!("count={}", count)));
                         vault.touch();
                     }
 
@@ -1262,11 +1747,7 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
 
                     let _ = app.log_audit("session.revoke_all", None, "success", Some(&format!("count={}", count)));
 
-                    app.popup = Some(PopupKind::StatusMessage {
-                        message: format!("{} session(s) revoked.", count),
-                        is_error: false,
-                        auto_close_at: Instant::now() + Duration::from_secs(2),
-                    });
+                    app.popup = Some(revocation_status(format!("{} session(s) revoked.", count), event));
                 }
                 _ => {
                     // Cancel
@@ -1282,8 +1763,21 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                     // Cancel, keep old filter
                 }
                 KeyCode::Enter => {
-                    app.audit_filter = filter_input.value.trim().to_string();
-                    app.audit_scroll = 0;
+                    let filter = filter_input.value.trim().to_string();
+                    match audit::query::AuditQuery::parse(&filter) {
+                        Ok(query) => {
+                            app.audit_filter = filter;
+                            app.audit_query = query;
+                            app.audit_scroll = 0;
+                        }
+                        Err(e) => {
+                            app.popup = Some(PopupKind::StatusMessage {
+                                message: e.to_string(),
+                                is_error: true,
+                                auto_close_at: Instant::now() + Duration::from_secs(3),
+                            });
+                        }
+                    }
                 }
                 _ => {
                     filter_input.handle_input(key);
@@ -1291,37 +1785,127 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
                 }
             }
         }
-        PopupKind::Help => {
-            // Any key closes help
+        PopupKind::AuditExport { mut path_input, .. } => {
+            match key.code {
+                KeyCode::Esc => {
+                    // Cancel
+                }
+                KeyCode::Enter => {
+                    let path = path_input.value.trim().to_string();
+                    if path.is_empty() {
+                        app.popup = Some(PopupKind::AuditExport {
+                            path_input,
+                            error: Some("Path cannot be empty".into()),
+                        });
+                        return;
+                    }
+                    match export_audit_entries(&app.filtered_audit_entries(), &path) {
+                        Ok(count) => {
+                            app.popup = Some(PopupKind::StatusMessage {
+                                message: format!("Exported {} entries to {}", count, path),
+                                is_error: false,
+                                auto_close_at: Instant::now() + Duration::from_secs(3),
+                            });
+                        }
+                        Err(e) => {
+                            app.popup = Some(PopupKind::AuditExport {
+                                path_input,
+                                error: Some(format!("Export failed: {}", e)),
+                            });
+                        }
+                    }
+                }
+                _ => {
+                    path_input.handle_input(key);
+                    app.popup = Some(PopupKind::AuditExport { path_input, error: None });
+                }
+            }
         }
-        PopupKind::VaultChanged => {
+        PopupKind::AuditVerifyRange { mut from_input, mut to_input, mut focused_field, .. } => {
             match key.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    // Reload vault from disk
-                    if let Some(ref key) = app.key {
-                        match vault::load_vault(key) {
-                            Ok(v) => {
-                                app.vault = Some(v);
-                                app.record_vault_mtime();
-                                app.popup = Some(PopupKind::StatusMessage {
-                                    message: "Vault reloaded.".into(),
-                                    is_error: false,
-                                    auto_close_at: Instant::now() + Duration::from_secs(2),
-                                });
-                            }
-                            Err(e) => {
-                                app.popup = Some(PopupKind::StatusMessage {
-                                    message: format!("Reload failed: {}", e),
-                                    is_error: true,
-                                    auto_close_at: Instant::now() + Duration::from_secs(3),
-                                });
-                            }
+                KeyCode::Esc => {
+                    // Cancel
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    focused_field = 1 - focused_field;
+                    app.popup = Some(PopupKind::AuditVerifyRange { from_input, to_input, focused_field, error: None });
+                }
+                KeyCode::Enter => {
+                    let from = match audit::query::parse_time_bound(&from_input.value, false) {
+                        Ok(t) => t,
+                        Err(e) => {
+                            app.popup = Some(PopupKind::AuditVerifyRange { from_input, to_input, focused_field, error: Some(e.to_string()) });
+                            return;
+                        }
+                    };
+                    let to = match audit::query::parse_time_bound(&to_input.value, true) {
+                        Ok(t) => t,
+                        Err(e) => {
+                            app.popup = Some(PopupKind::AuditVerifyRange { from_input, to_input, focused_field, error: Some(e.to_string()) });
+                            return;
+                        }
+                    };
+                    if let (Some(f), Some(t)) = (from, to) {
+                        if f > t {
+                            app.popup = Some(PopupKind::AuditVerifyRange {
+                                from_input, to_input, focused_field,
+                                error: Some("'From' must not be after 'To'".into()),
+                            });
+                            return;
                         }
                     }
+
+                    let audit_key = match app.audit_key() {
+                        Some(k) => k,
+                        None => return,
+                    };
+                    app.popup = Some(match vault::audit_path().and_then(|path| audit::verify_chain_range(&path, &audit_key, from, to)) {
+                        Ok(count) => PopupKind::AuditVerifyResult {
+                            message: format!("Range valid ({} entries checked)", count),
+                            is_ok: true,
+                        },
+                        Err(e) => PopupKind::AuditVerifyResult {
+                            message: format!("{}", e),
+                            is_ok: false,
+                        },
+                    });
+                }
+                _ => {
+                    match focused_field {
+                        0 => { from_input.handle_input(key); }
+                        _ => { to_input.handle_input(key); }
+                    }
+                    app.popup = Some(PopupKind::AuditVerifyRange { from_input, to_input, focused_field, error: None });
+                }
+            }
+        }
+        PopupKind::Help { previous, .. } => {
+            // Any key closes help, returning to the popup it was opened over
+            app.popup = previous.map(|p| *p);
+        }
+        PopupKind::CommandPalette { mut query_input, mut matches, mut selected } => {
+            match key.code {
+                KeyCode::Esc => {
+                    // Close
+                }
+                KeyCode::Enter => {
+                    if let Some(action) = matches.get(selected) {
+                        run_palette_action(app, action);
+                    }
+                }
+                KeyCode::Up => {
+                    selected = selected.saturating_sub(1);
+                    app.popup = Some(PopupKind::CommandPalette { query_input, matches, selected });
+                }
+                KeyCode::Down => {
+                    selected = (selected + 1).min(matches.len().saturating_sub(1));
+                    app.popup = Some(PopupKind::CommandPalette { query_input, matches, selected });
                 }
                 _ => {
-                    // Dismiss and update mtime to suppress repeated prompts
-                    app.record_vault_mtime();
+                    query_input.handle_input(key);
+                    matches = keymap::palette_matches(&query_input.value, app.can_write(), app.can_reveal());
+                    selected = 0;
+                    app.popup = Some(PopupKind::CommandPalette { query_input, matches, selected });
                 }
             }
         }
@@ -1331,6 +1915,17 @@ fn handle_popup_input(app: &mut TuiApp, key: event::KeyEvent) {
     }
 }
 
+/// Write audit entries to `path`, as CSV if it ends in `.csv`, JSON otherwise.
+fn export_audit_entries(entries: &[&audit::AuditEntry], path: &str) -> Result<usize> {
+    let content = if path.to_lowercase().ends_with(".csv") {
+        audit::query::to_csv(entries)
+    } else {
+        serde_json::to_string_pretty(entries).map_err(|e| AuthyError::Serialization(e.to_string()))?
+    };
+    std::fs::write(path, content)?;
+    Ok(entries.len())
+}
+
 /// Get the number of items in the current section list.
 fn list_len(app: &TuiApp) -> usize {
     let vault = match &app.vault {
@@ -1338,7 +1933,7 @@ fn list_len(app: &TuiApp) -> usize {
         None => return 0,
     };
     match app.section {
-        Section::Secrets => vault.secrets.len(),
+        Section::Secrets => app.visible_secrets().len(),
         Section::Policies => vault.policies.len(),
         Section::Sessions => vault.sessions.len(),
         Section::Audit => app.filtered_audit_entries().len(),
@@ -1355,8 +1950,36 @@ fn draw(frame: &mut Frame, app: &TuiApp) {
             if let Some(ref popup) = app.popup {
                 draw_popup(frame, popup);
             }
+            if app.external_change {
+                draw_conflict_banner(frame);
+            }
         }
     }
+    if !app.colors {
+        monochrome(frame.buffer_mut());
+    }
+}
+
+/// Warn, above an open form, that the vault was reloaded underneath it.
+fn draw_conflict_banner(frame: &mut Frame) {
+    let area = frame.area();
+    let banner = Paragraph::new(Span::styled(
+        " Vault changed on disk while this form was open. It was reloaded; saving applies your edit to the new version. ",
+        Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+    ));
+    frame.render_widget(banner, Rect { x: area.x, y: area.y, width: area.width, height: 1 });
+}
+
+/// Strip colors from a drawn frame. Highlighted cells (selection, cursor)
+/// are shown reversed so they stay visible.
+fn monochrome(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.set_fg(Color::Reset);
+        cell.set_bg(Color::Reset);
+    }
 }
 
 /// Draw a popup overlay.
@@ -1367,21 +1990,38 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             value,
             masked,
             auto_close_at,
+            scroll,
+            pretty,
+            origin,
         } => {
+            let kind = preview::ValueKind::detect(value);
             let display_value = if *masked {
-                "\u{2022}".repeat(value.len().min(40))
+                preview::masked_summary(value, kind)
             } else {
-                value.clone()
+                preview::render_value(value, kind, *pretty)
             };
+            let display_value = format!("{}\n\nOrigin: {}", display_value, origin);
+            // Clamp scrolling so the last line stays in view
+            let max_scroll = display_value.lines().count().saturating_sub(1) as u16;
 
             let remaining = auto_close_at
                 .checked_duration_since(Instant::now())
                 .unwrap_or_default();
 
-            let title = name.to_string();
+            let title = if kind == preview::ValueKind::Plain {
+                name.to_string()
+            } else {
+                format!("{} ({})", name, kind.label())
+            };
+            let extra = match (kind, *masked) {
+                (preview::ValueKind::Json, false) => "  [j/k] scroll  [p] raw/pretty",
+                (preview::ValueKind::Plain, _) | (_, true) => "",
+                _ => "  [j/k] scroll",
+            };
             let footer = format!(
-                "[Esc] close  [Ctrl+R] {}  [Ctrl+Y] copy  auto-close: {}s",
+                "[Esc] close  [Ctrl+R] {}  [Ctrl+Y] copy{}  auto-close: {}s",
                 if *masked { "reveal" } else { "mask" },
+                extra,
                 remaining.as_secs()
             );
 
@@ -1389,6 +2029,7 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
                 title: &title,
                 content: &display_value,
                 footer: &footer,
+                scroll: (*scroll).min(max_scroll),
             };
             p.render(frame);
         }
@@ -1524,10 +2165,59 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             };
             dialog.render(frame);
         }
+        PopupKind::PolicyTest {
+            scope,
+            bulk: Some(results),
+            scroll,
+            ..
+        } => {
+            let height = 20.min(frame.area().height.saturating_sub(2));
+            let area = widgets::centered_rect(60, height, frame.area());
+            frame.render_widget(ratatui::widgets::Clear, area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Test policy: {} (all secrets) ", scope))
+                .border_style(Style::default().fg(Color::Cyan));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+
+            let w = inner.width.saturating_sub(2);
+            let x = inner.x + 1;
+            let allowed = results.iter().filter(|(_, ok)| *ok).count();
+
+            let counts = Line::from(vec![
+                Span::styled(format!("{} allowed", allowed), Style::default().fg(Color::Green)),
+                Span::raw("  "),
+                Span::styled(format!("{} denied", results.len() - allowed), Style::default().fg(Color::Yellow)),
+                Span::styled(format!("  ({} secrets)", results.len()), Style::default().fg(Color::DarkGray)),
+            ]);
+            frame.render_widget(Paragraph::new(counts), Rect { x, y: inner.y, width: w, height: 1 });
+
+            let list_height = inner.height.saturating_sub(3);
+            if results.is_empty() {
+                let p = Paragraph::new(Span::styled("No secrets in vault", Style::default().fg(Color::DarkGray)));
+                frame.render_widget(p, Rect { x, y: inner.y + 2, width: w, height: 1 });
+            }
+            for (i, (name, ok)) in results.iter().skip(*scroll).take(list_height as usize).enumerate() {
+                let (label, color) = if *ok { ("ALLOWED", Color::Green) } else { ("DENIED", Color::Yellow) };
+                let line = Line::from(vec![
+                    Span::styled(format!("{:<8} ", label), Style::default().fg(color)),
+                    Span::raw(name.as_str()),
+                ]);
+                frame.render_widget(Paragraph::new(line), Rect { x, y: inner.y + 2 + i as u16, width: w, height: 1 });
+            }
+
+            let hint = Paragraph::new(Span::styled(
+                "[j/k] scroll  [Tab] single name  [Esc] close",
+                Style::default().fg(Color::DarkGray),
+            ));
+            frame.render_widget(hint, Rect { x, y: inner.y + inner.height.saturating_sub(1), width: w, height: 1 });
+        }
         PopupKind::PolicyTest {
             scope,
             name_input,
             result,
+            ..
         } => {
             let area = widgets::centered_rect(60, 9, frame.area());
             frame.render_widget(ratatui::widgets::Clear, area);
@@ -1559,7 +2249,7 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             }
 
             let hint = Paragraph::new(Span::styled(
-                "[Enter] test  [Esc] close",
+                "[Enter] test  [Tab] test all secrets  [Esc] close",
                 Style::default().fg(Color::DarkGray),
             ));
             frame.render_widget(hint, Rect { x, y, width: w, height: 1 });
@@ -1568,10 +2258,12 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             scope_index,
             policy_names,
             ttl_input,
+            label_input,
+            run_only,
             focused_field,
             error,
         } => {
-            let area = widgets::centered_rect(60, 10, frame.area());
+            let area = widgets::centered_rect(60, 12, frame.area());
             frame.render_widget(ratatui::widgets::Clear, area);
             let block = Block::default()
                 .borders(Borders::ALL)
@@ -1597,6 +2289,19 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             y += 1;
 
             widgets::render_input(frame, Rect { x, y, width: w, height: 1 }, ttl_input, "TTL  ", *focused_field == 1);
+            y += 1;
+
+            widgets::render_input(frame, Rect { x, y, width: w, height: 1 }, label_input, "Label", *focused_field == 2);
+            y += 1;
+
+            let run_only_style = if *focused_field == 3 {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let run_only_text = format!("[{}] Run-only (values only via `authy run`)", if *run_only { "x" } else { " " });
+            let p = Paragraph::new(Span::styled(run_only_text, run_only_style));
+            frame.render_widget(p, Rect { x, y, width: w, height: 1 });
             y += 2;
 
             if let Some(err) = error {
@@ -1606,7 +2311,7 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             }
 
             let hint = Paragraph::new(Span::styled(
-                "[Tab] next  [</>] change scope  [Enter] create  [Esc] cancel",
+                "[Tab] next  [</>] scope  [Space] toggle  [Enter] create  [Esc] cancel",
                 Style::default().fg(Color::DarkGray),
             ));
             frame.render_widget(hint, Rect { x, y, width: w, height: 1 });
@@ -1625,6 +2330,7 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
                 title: &title,
                 content: token,
                 footer: &footer,
+                scroll: 0,
             };
             p.render(frame);
         }
@@ -1670,59 +2376,128 @@ fn draw_popup(frame: &mut Frame, popup: &PopupKind) {
             widgets::render_input(frame, Rect { x, y: inner.y, width: w, height: 1 }, filter_input, "Filter", true);
 
             let hint = Paragraph::new(Span::styled(
-                "[Enter] apply  [Esc] cancel",
+                "op: actor: secret: outcome: since: until:  [Enter] apply",
                 Style::default().fg(Color::DarkGray),
             ));
             frame.render_widget(hint, Rect { x, y: inner.y + 2, width: w, height: 1 });
         }
-        PopupKind::Help => {
-            let help_text = "\
-Tab/1-4    Switch section
-j/k ↑/↓    Navigate list
-Enter      Select / reveal
-
-Secrets:
-  s        Store new secret
-  r        Rotate secret
-  d        Delete secret
-
-Policies:
-  c        Create policy
-  e        Edit policy
-  d        Delete policy
-  t        Test policy
-
-Sessions:
-  c        Create session
-  r        Revoke session
-  R        Revoke all
-
-Audit:
-  /        Filter log
-  v        Verify chain
-
-Ctrl+R     Toggle mask
-Ctrl+Y     Copy to clipboard
-Esc/q      Close / quit
-?          This help";
-
-            let area = widgets::centered_rect(50, 30.min(frame.area().height.saturating_sub(2)), frame.area());
+        PopupKind::AuditExport { path_input, error } => {
+            let area = widgets::centered_rect(60, 8, frame.area());
             frame.render_widget(ratatui::widgets::Clear, area);
             let block = Block::default()
                 .borders(Borders::ALL)
-                .title(" Key Bindings ")
+                .title(" Export filtered audit entries ")
                 .border_style(Style::default().fg(Color::Cyan));
             let inner = block.inner(area);
             frame.render_widget(block, area);
-            let p = Paragraph::new(help_text);
+
+            let mut y = inner.y;
+            let x = inner.x + 1;
+            let w = inner.width.saturating_sub(2);
+            widgets::render_input(frame, Rect { x, y, width: w, height: 1 }, path_input, "File", true);
+            y += 2;
+
+            if let Some(err) = error {
+                let p = Paragraph::new(Span::styled(err.as_str(), Style::default().fg(Color::Red)));
+                frame.render_widget(p, Rect { x, y, width: w, height: 1 });
+                y += 1;
+            }
+
+            let hint = Paragraph::new(Span::styled(
+                "[Enter] export (.csv or .json)  [Esc] cancel",
+                Style::default().fg(Color::DarkGray),
+            ));
+            frame.render_widget(hint, Rect { x, y, width: w, height: 1 });
+        }
+        PopupKind::AuditVerifyRange { from_input, to_input, focused_field, error } => {
+            let area = widgets::centered_rect(60, 9, frame.area());
+            frame.render_widget(ratatui::widgets::Clear, area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(" Verify chain over range ")
+                .border_style(Style::default().fg(Color::Cyan));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+
+            let mut y = inner.y;
+            let x = inner.x + 1;
+            let w = inner.width.saturating_sub(2);
+            widgets::render_input(frame, Rect { x, y, width: w, height: 1 }, from_input, "From", *focused_field == 0);
+            y += 1;
+            widgets::render_input(frame, Rect { x, y, width: w, height: 1 }, to_input, "To  ", *focused_field == 1);
+            y += 2;
+
+            if let Some(err) = error {
+                let p = Paragraph::new(Span::styled(err.as_str(), Style::default().fg(Color::Red)));
+                frame.render_widget(p, Rect { x, y, width: w, height: 1 });
+                y += 1;
+            }
+
+            let hint = Paragraph::new(Span::styled(
+                "[Tab] next  [Enter] verify  [Esc] cancel  (YYYY-MM-DD or RFC 3339, blank = open)",
+                Style::default().fg(Color::DarkGray),
+            ));
+            frame.render_widget(hint, Rect { x, y, width: w, height: 1 });
+        }
+        PopupKind::Help { title, lines, .. } => {
+            let text: Vec<Line> = lines
+                .iter()
+                .map(|(keys, label)| {
+                    if keys.is_empty() {
+                        Line::from(Span::styled(*label, Style::default().add_modifier(Modifier::BOLD)))
+                    } else {
+                        Line::from(format!("{:<11}{}", keys, label))
+                    }
+                })
+                .collect();
+
+            let height = (lines.len() as u16 + 2).min(frame.area().height.saturating_sub(2));
+            let area = widgets::centered_rect(50, height, frame.area());
+            frame.render_widget(ratatui::widgets::Clear, area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", title))
+                .border_style(Style::default().fg(Color::Cyan));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+            let p = Paragraph::new(text);
             frame.render_widget(p, inner);
         }
-        PopupKind::VaultChanged => {
-            let dialog = widgets::ConfirmDialog {
-                title: "Vault changed",
-                message: "The vault was modified externally. Reload?",
+        PopupKind::CommandPalette { query_input, matches, selected } => {
+            let height = 16.min(frame.area().height.saturating_sub(2));
+            let area = widgets::centered_rect(60, height, frame.area());
+            frame.render_widget(ratatui::widgets::Clear, area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(" Command palette ")
+                .border_style(Style::default().fg(Color::Cyan));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+
+            let x = inner.x + 1;
+            let w = inner.width.saturating_sub(2);
+            widgets::render_input(frame, Rect { x, y: inner.y, width: w, height: 1 }, query_input, ">", true);
+
+            let list_area = Rect {
+                x: inner.x,
+                y: inner.y + 2,
+                width: inner.width,
+                height: inner.height.saturating_sub(2),
             };
-            dialog.render(frame);
+            if matches.is_empty() {
+                let p = Paragraph::new(Span::styled(" No matching actions", Style::default().fg(Color::DarkGray)));
+                frame.render_widget(p, list_area);
+            } else {
+                // Keep the selection in view
+                let visible = list_area.height as usize;
+                let offset = (selected + 1).saturating_sub(visible);
+                let items: Vec<String> = matches
+                    .iter()
+                    .skip(offset)
+                    .map(|a| format!(" {:<40} {}", a.title(), a.keys))
+                    .collect();
+                draw_list(frame, list_area, &items, selected - offset);
+            }
         }
         PopupKind::StatusMessage {
             message,
@@ -1844,9 +2619,9 @@ fn draw_section_content(frame: &mut Frame, area: Rect, app: &TuiApp) {
                 height: inner.height.saturating_sub(1),
             };
 
-            let items: Vec<String> = vault
-                .secrets
-                .iter()
+            let items: Vec<String> = app
+                .visible_secrets()
+                .into_iter()
                 .map(|(name, entry)| {
                     format!(
                         " {:<20} {:<12} {:<12} {:<5} {}",
@@ -1894,8 +2669,12 @@ fn draw_section_content(frame: &mut Frame, area: Rect, app: &TuiApp) {
                         format!("{}m left", remaining.num_minutes())
                     };
                     format!(
-                        " {:<16} {:<16} {}",
-                        s.id, s.scope, status
+                        " {:<16} {:<16} {:<16} {}{}",
+                        s.id,
+                        s.scope,
+                        s.label.as_deref().unwrap_or("-"),
+                        status,
+                        if s.run_only { " (run-only)" } else { "" }
                     )
                 })
                 .collect();
@@ -1976,13 +2755,16 @@ fn draw_list(frame: &mut Frame, area: Rect, items: &[String], selected: usize) {
 
 /// Draw the status bar at the bottom.
 fn draw_status_bar(frame: &mut Frame, area: Rect, app: &TuiApp) {
-    let vault_path = vault::vault_path().display().to_string();
+    let vault_path = vault::vault_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
     let auth_method = app
         .auth_ctx
         .as_ref()
         .map(|ctx| match &ctx.method {
             AuthMethod::Passphrase => "passphrase",
             AuthMethod::Keyfile => "keyfile",
+            AuthMethod::SshAgent => "ssh-agent",
             AuthMethod::SessionToken { .. } => "token",
         })
         .unwrap_or("none");
@@ -1993,20 +2775,45 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, app: &TuiApp) {
         .map(|v| v.modified_at.format("%Y-%m-%d %H:%M").to_string())
         .unwrap_or_default();
 
-    // Hint bar with section-specific keys
-    let hints = match app.section {
-        Section::Secrets => "[s]tore [Enter]reveal [r]otate [d]elete [q]uit",
-        Section::Policies => "[c]reate [e]dit [d]elete [t]est [q]uit",
-        Section::Sessions => "[c]reate [r]evoke [R]evoke all [q]uit",
-        Section::Audit => "[v]erify [/]filter [q]uit",
+    // Hint bar with section-specific keys (write actions hidden for tokens)
+    let hints = match (app.section, app.can_write()) {
+        (Section::Secrets, true) => "[s]tore [Enter]reveal [r]otate [d]elete [q]uit",
+        (Section::Secrets, false) if app.can_reveal() => "[Enter]reveal [q]uit",
+        (Section::Secrets, false) => "[q]uit",
+        (Section::Policies, true) => "[c]reate [e]dit [d]elete [t]est [q]uit",
+        (Section::Policies, false) => "[t]est [q]uit",
+        (Section::Sessions, true) => "[c]reate [r]evoke [R]evoke all [q]uit",
+        (Section::Sessions, false) => "[q]uit",
+        (Section::Audit, _) => "[v]erify [V]erify range [/]filter e[x]port [q]uit",
     };
 
+    // Session token details: id, scope, run-only flag, remaining TTL
+    let session_info = app
+        .session
+        .as_ref()
+        .map(|s| {
+            let remaining = s.expires_at - chrono::Utc::now();
+            let ttl = if remaining.num_seconds() <= 0 {
+                "expired".to_string()
+            } else {
+                format!("{}m left", remaining.num_minutes())
+            };
+            format!(
+                "  session: {}  scope: {}{}  ttl: {}",
+                s.id,
+                s.scope,
+                if s.run_only { " (run-only)" } else { "" },
+                ttl
+            )
+        })
+        .unwrap_or_default();
+
     let top = Paragraph::new(Span::styled(
         format!(" {}  ", hints),
         Style::default().fg(Color::DarkGray),
     ));
     let bottom = Paragraph::new(Span::styled(
-        format!(" vault: {}  auth: {}  modified: {}", vault_path, auth_method, modified),
+        format!(" vault: {}  auth: {}{}  modified: {}", vault_path, auth_method, session_info, modified),
         Style::default().fg(Color::DarkGray),
     ));
 
@@ -0,0 +1,62 @@
+//! Secret value preview — detects structured values (JSON, PEM, multi-line)
+//! so the reveal popup can render them wrapped and scrollable.
+
+/// The detected shape of a secret value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Plain,
+    MultiLine,
+    Json,
+    Pem,
+}
+
+impl ValueKind {
+    /// Detect the kind of a value. PEM wins over JSON, JSON over plain multi-line.
+    pub fn detect(value: &str) -> Self {
+        let trimmed = value.trim();
+        if trimmed.starts_with("-----BEGIN ") && trimmed.contains("-----END ") {
+            ValueKind::Pem
+        } else if (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+        {
+            ValueKind::Json
+        } else if trimmed.contains('\n') {
+            ValueKind::MultiLine
+        } else {
+            ValueKind::Plain
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            ValueKind::Plain => "text",
+            ValueKind::MultiLine => "multi-line",
+            ValueKind::Json => "JSON",
+            ValueKind::Pem => "PEM",
+        }
+    }
+}
+
+/// Text to show for a revealed value. JSON is pretty-printed when `pretty` is set.
+pub fn render_value(value: &str, kind: ValueKind, pretty: bool) -> String {
+    if kind == ValueKind::Json && pretty {
+        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(value.trim()) {
+            if let Ok(s) = serde_json::to_string_pretty(&parsed) {
+                return s;
+            }
+        }
+    }
+    value.to_string()
+}
+
+/// Text to show while masked: bullets, plus the line count and kind for
+/// anything other than a single-line plain value.
+pub fn masked_summary(value: &str, kind: ValueKind) -> String {
+    let bullets = "\u{2022}".repeat(value.chars().count().min(40));
+    if kind == ValueKind::Plain {
+        bullets
+    } else {
+        let lines = value.trim_end().lines().count();
+        format!("{}\n({} {}, {})", bullets, lines, if lines == 1 { "line" } else { "lines" }, kind.label())
+    }
+}
@@ -1,8 +1,15 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// A text input widget that supports masked (password) mode.
+///
+/// Editing works on grapheme clusters, so an accented letter, a CJK
+/// character or an emoji sequence is one step for the cursor, one
+/// Backspace and one mask bullet. `cursor_pos` is a byte offset into
+/// `value` and always sits on a cluster boundary.
 #[derive(Debug, Clone)]
 pub struct TextInput {
     pub value: String,
@@ -24,6 +31,12 @@ impl TextInput {
         self.cursor_pos = 0;
     }
 
+    /// Replace the value, with the cursor at the end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor_pos = self.value.len();
+    }
+
     /// Handle a key event. Returns true if the event was consumed.
     pub fn handle_input(&mut self, key: KeyEvent) -> bool {
         // Ctrl+R toggles mask
@@ -35,51 +48,28 @@ impl TextInput {
         match key.code {
             KeyCode::Char(c) => {
                 self.value.insert(self.cursor_pos, c);
-                self.cursor_pos += c.len_utf8();
+                // A combining mark typed before existing text joins the
+                // cluster after it; keep the cursor on a boundary
+                self.cursor_pos = self.next_boundary(self.cursor_pos);
                 true
             }
             KeyCode::Backspace => {
-                if self.cursor_pos > 0 {
-                    // Find the previous char boundary
-                    let prev = self.value[..self.cursor_pos]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                    self.value.drain(prev..self.cursor_pos);
-                    self.cursor_pos = prev;
-                }
+                let prev = self.prev_boundary(self.cursor_pos);
+                self.value.drain(prev..self.cursor_pos);
+                self.cursor_pos = prev;
                 true
             }
             KeyCode::Delete => {
-                if self.cursor_pos < self.value.len() {
-                    let next = self.value[self.cursor_pos..]
-                        .char_indices()
-                        .nth(1)
-                        .map(|(i, _)| self.cursor_pos + i)
-                        .unwrap_or(self.value.len());
-                    self.value.drain(self.cursor_pos..next);
-                }
+                let next = self.next_boundary(self.cursor_pos);
+                self.value.drain(self.cursor_pos..next);
                 true
             }
             KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos = self.value[..self.cursor_pos]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                }
+                self.cursor_pos = self.prev_boundary(self.cursor_pos);
                 true
             }
             KeyCode::Right => {
-                if self.cursor_pos < self.value.len() {
-                    self.cursor_pos = self.value[self.cursor_pos..]
-                        .char_indices()
-                        .nth(1)
-                        .map(|(i, _)| self.cursor_pos + i)
-                        .unwrap_or(self.value.len());
-                }
+                self.cursor_pos = self.next_boundary(self.cursor_pos);
                 true
             }
             KeyCode::Home => {
@@ -94,23 +84,72 @@ impl TextInput {
         }
     }
 
-    /// Get display text (masked or plain).
+    /// Start of the cluster before byte offset `pos`.
+    fn prev_boundary(&self, pos: usize) -> usize {
+        self.value
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .take_while(|&i| i < pos)
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// End of the cluster at or after byte offset `pos`.
+    fn next_boundary(&self, pos: usize) -> usize {
+        self.value
+            .grapheme_indices(true)
+            .map(|(i, g)| i + g.len())
+            .find(|&end| end > pos)
+            .unwrap_or(self.value.len())
+    }
+
+    /// Get display text (masked or plain). Masked text has one bullet per
+    /// cluster.
     pub fn display_text(&self) -> String {
         if self.masked {
-            "\u{2022}".repeat(self.value.chars().count())
+            "\u{2022}".repeat(self.value.graphemes(true).count())
         } else {
             self.value.clone()
         }
     }
 
-    /// Get the display cursor position (in characters, for masked mode).
+    /// The terminal column of the cursor within the display text. Wide
+    /// characters (CJK, most emoji) take two columns.
     pub fn display_cursor(&self) -> usize {
+        let before = &self.value[..self.cursor_pos];
         if self.masked {
-            self.value[..self.cursor_pos].chars().count()
+            before.graphemes(true).count()
         } else {
-            self.cursor_pos
+            before.width()
         }
     }
+
+    /// The part of the display text that fits in `width` columns with the
+    /// cursor in view, and the cursor's column within it. Scrolls by whole
+    /// clusters, so a wide character is never cut in half.
+    pub fn visible(&self, width: usize) -> (String, usize) {
+        let display = self.display_text();
+        let clusters: Vec<(&str, usize)> =
+            display.graphemes(true).map(|g| (g, g.width())).collect();
+        let mut cursor = self.display_cursor();
+
+        // Drop clusters from the left until the cursor column fits
+        let mut skip = 0;
+        while cursor >= width.max(1) && skip < clusters.len() {
+            cursor -= clusters[skip].1;
+            skip += 1;
+        }
+        let mut text = String::new();
+        let mut used = 0;
+        for (g, w) in &clusters[skip..] {
+            if used + w > width {
+                break;
+            }
+            text.push_str(g);
+            used += w;
+        }
+        (text, cursor)
+    }
 }
 
 /// Render a text input field with an optional label.
@@ -121,8 +160,10 @@ pub fn render_input(
     label: &str,
     focused: bool,
 ) {
-    let display = input.display_text();
-    let text = format!("{}: {}", label, display);
+    let prefix = format!("{}: ", label);
+    let field = (area.width as usize).saturating_sub(prefix.width());
+    let (display, cursor) = input.visible(field);
+    let text = format!("{}{}", prefix, display);
     let style = if focused {
         Style::default().fg(Color::Yellow)
     } else {
@@ -133,7 +174,7 @@ pub fn render_input(
 
     // Place cursor
     if focused {
-        let cursor_x = area.x + label.len() as u16 + 2 + input.display_cursor() as u16;
+        let cursor_x = area.x + (prefix.width() + cursor) as u16;
         let cursor_y = area.y;
         if cursor_x < area.x + area.width {
             frame.set_cursor_position(Position::new(cursor_x, cursor_y));
@@ -164,16 +205,18 @@ impl<'a> ConfirmDialog<'a> {
     }
 }
 
-/// A popup overlay for showing content.
+/// A popup overlay for showing content. Long lines wrap; `scroll` is the
+/// number of wrapped lines skipped from the top.
 pub struct Popup<'a> {
     pub title: &'a str,
     pub content: &'a str,
     pub footer: &'a str,
+    pub scroll: u16,
 }
 
 impl<'a> Popup<'a> {
     pub fn render(&self, frame: &mut Frame) {
-        let height = (self.content.lines().count() as u16 + 4).min(frame.area().height - 2);
+        let height = (self.content.lines().count() as u16 + 4).min(frame.area().height.saturating_sub(2));
         let area = centered_rect(60, height, frame.area());
         frame.render_widget(Clear, area);
         let block = Block::default()
@@ -196,7 +239,9 @@ impl<'a> Popup<'a> {
             height: 1,
         };
 
-        let paragraph = Paragraph::new(self.content);
+        let paragraph = Paragraph::new(self.content)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
         frame.render_widget(paragraph, content_area);
 
         let footer = Paragraph::new(Span::styled(
@@ -220,3 +265,67 @@ pub fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
         height: height.min(area.height),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(s: &str, masked: bool) -> TextInput {
+        let mut input = TextInput::new(masked);
+        for c in s.chars() {
+            input.handle_input(KeyEvent::from(KeyCode::Char(c)));
+        }
+        input
+    }
+
+    fn press(input: &mut TextInput, code: KeyCode) {
+        input.handle_input(KeyEvent::from(code));
+    }
+
+    #[test]
+    fn test_cjk_cursor_columns() {
+        let mut input = typed("密码", false);
+        assert_eq!(input.cursor_pos, "密码".len());
+        assert_eq!(input.display_cursor(), 4);
+        press(&mut input, KeyCode::Left);
+        assert_eq!(input.display_cursor(), 2);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value, "码");
+        assert_eq!(input.display_cursor(), 0);
+    }
+
+    #[test]
+    fn test_emoji_and_combining_marks_are_one_step() {
+        // Family emoji (ZWJ sequence) and "e" + combining acute
+        let mut input = typed("a👨‍👩‍👧e\u{301}", false);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value, "a👨‍👩‍👧");
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value, "a");
+
+        let mut input = typed("x👍y", false);
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Right);
+        press(&mut input, KeyCode::Delete);
+        assert_eq!(input.value, "xy");
+    }
+
+    #[test]
+    fn test_masking_counts_graphemes() {
+        let input = typed("pä\u{308}ss密🔑", true);
+        assert_eq!(input.display_text(), "\u{2022}".repeat(6));
+        assert_eq!(input.display_cursor(), 6);
+    }
+
+    #[test]
+    fn test_visible_scrolls_whole_clusters() {
+        let input = typed("一二三四五", false);
+        let (text, cursor) = input.visible(5);
+        assert_eq!(text, "四五");
+        assert_eq!(cursor, 4);
+
+        let mut input = typed("abcdef", false);
+        press(&mut input, KeyCode::Home);
+        assert_eq!(input.visible(3), ("abc".to_string(), 0));
+    }
+}
@@ -0,0 +1,71 @@
+//! Read-through cache of secret values for long-running servers
+//! (`authy serve --mcp`, `authy agent --stdio`), so repeated `get`s skip the
+//! KDF and the vault read until the entry's TTL runs out.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use zeroize::Zeroizing;
+
+/// Secret values keyed by name, each valid for `ttl` after it was read.
+/// Values are zeroized when evicted or when the cache is dropped.
+pub struct ReadCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedValue>>,
+}
+
+struct CachedValue {
+    value: Zeroizing<String>,
+    read_at: Instant,
+}
+
+impl ReadCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// The cached value of `name`, if it was read less than `ttl` ago.
+    pub fn get(&self, name: &str) -> Option<Zeroizing<String>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(name) {
+            Some(cached) if cached.read_at.elapsed() < self.ttl => Some(cached.value.clone()),
+            Some(_) => {
+                entries.remove(name);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remember a value just read from the vault.
+    pub fn insert(&self, name: &str, value: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(
+            name.to_string(),
+            CachedValue {
+                value: Zeroizing::new(value.to_string()),
+                read_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drop one entry, e.g. after the secret was written or removed.
+    pub fn invalidate(&self, name: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(name);
+    }
+
+    /// Drop every entry.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clear();
+    }
+}
@@ -0,0 +1,324 @@
+//! How the vault is encrypted at rest. `load_vault` / `save_vault` hand the
+//! sealed vault body to the backend selected by `[cipher]` in the config
+//! file; storage backends only ever see what it returns.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::storage::{sigv4_authorization, AwsCredentials};
+use super::{crypto, VaultKey};
+use crate::config::{CipherBackend, CipherConfig, Config};
+use crate::error::{AuthyError, Result};
+
+/// Encrypts and decrypts the vault with the master credential.
+pub trait VaultCipher {
+    /// Short name for messages (`age`, `aws-kms`).
+    fn name(&self) -> &'static str;
+
+    fn encrypt(&self, plaintext: &[u8], key: &VaultKey) -> Result<Vec<u8>>;
+
+    fn decrypt(&self, ciphertext: &[u8], key: &VaultKey) -> Result<Vec<u8>>;
+}
+
+/// Open the cipher backend selected by `[cipher]` in the config file.
+pub fn open() -> Result<Box<dyn VaultCipher>> {
+    let config = Config::load(&super::config_path()?)?;
+    from_config(&config.cipher)
+}
+
+/// Build a cipher backend from its configuration.
+pub fn from_config(config: &CipherConfig) -> Result<Box<dyn VaultCipher>> {
+    match config.backend {
+        CipherBackend::Age => Ok(Box::new(AgeCipher)),
+        CipherBackend::AwsKms => {
+            let key_id = config.key_id.as_deref().ok_or_else(|| {
+                AuthyError::Other("[cipher] backend \"aws-kms\" requires key_id".into())
+            })?;
+            let endpoint = match &config.url {
+                Some(url) => url.trim_end_matches('/').to_string(),
+                None => format!("https://kms.{}.amazonaws.com", config.region),
+            };
+            Ok(Box::new(EnvelopeCipher {
+                keys: Box::new(AwsKms {
+                    endpoint,
+                    region: config.region.clone(),
+                    key_id: key_id.to_string(),
+                    credentials: AwsCredentials::from_env()?,
+                }),
+            }))
+        }
+    }
+}
+
+/// age encryption to the vault passphrase or keyfile (the default).
+pub struct AgeCipher;
+
+impl VaultCipher for AgeCipher {
+    fn name(&self) -> &'static str {
+        "age"
+    }
+
+    fn encrypt(&self, plaintext: &[u8], key: &VaultKey) -> Result<Vec<u8>> {
+        match key {
+            VaultKey::Passphrase(pass) => crypto::encrypt_with_passphrase(plaintext, pass),
+            VaultKey::Keyfile { pubkey, .. } | VaultKey::SshAgent { pubkey, .. } => {
+                crypto::encrypt_with_keyfile(plaintext, pubkey)
+            }
+        }
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], key: &VaultKey) -> Result<Vec<u8>> {
+        if ciphertext.starts_with(ENVELOPE_MAGIC) {
+            return Err(AuthyError::Decryption(
+                "the vault is sealed with a KMS data key; set [cipher] backend = \"aws-kms\"".into(),
+            ));
+        }
+        match key {
+            VaultKey::Passphrase(pass) => crypto::decrypt_with_passphrase(ciphertext, pass),
+            VaultKey::Keyfile { identity, .. } | VaultKey::SshAgent { identity, .. } => {
+                crypto::decrypt_with_keyfile(ciphertext, identity)
+            }
+        }
+    }
+}
+
+/// A key management service that issues and unwraps data keys. The key
+/// that wraps them never leaves the service.
+pub trait KeyService {
+    fn name(&self) -> &'static str;
+
+    /// A fresh 32-byte data key, as (plaintext, wrapped).
+    fn generate_data_key(&self) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)>;
+
+    /// Unwrap a data key issued by [`generate_data_key`](Self::generate_data_key).
+    fn decrypt_data_key(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>>;
+}
+
+/// First line of an envelope-encrypted vault.
+const ENVELOPE_MAGIC: &[u8] = b"authy-envelope-v1\n";
+
+/// Envelope encryption over age: the age-encrypted vault is sealed again to
+/// an identity derived from a fresh data key on every save, and only the
+/// wrapped data key is stored next to it. Opening the vault takes both the
+/// master credential and permission to unwrap through the key service.
+///
+/// Layout: `authy-envelope-v1\n<base64 wrapped key>\n<age ciphertext>`.
+pub struct EnvelopeCipher {
+    keys: Box<dyn KeyService>,
+}
+
+impl EnvelopeCipher {
+    pub fn new(keys: Box<dyn KeyService>) -> Self {
+        Self { keys }
+    }
+}
+
+impl VaultCipher for EnvelopeCipher {
+    fn name(&self) -> &'static str {
+        self.keys.name()
+    }
+
+    fn encrypt(&self, plaintext: &[u8], key: &VaultKey) -> Result<Vec<u8>> {
+        let inner = AgeCipher.encrypt(plaintext, key)?;
+        let (data_key, wrapped) = self.keys.generate_data_key()?;
+        let identity = data_key_identity(&data_key)?;
+        let outer = crypto::encrypt_with_keyfile(&inner, &identity.to_public().to_string())?;
+
+        let mut sealed = ENVELOPE_MAGIC.to_vec();
+        sealed.extend_from_slice(STANDARD.encode(wrapped).as_bytes());
+        sealed.push(b'\n');
+        sealed.extend_from_slice(&outer);
+        Ok(sealed)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], key: &VaultKey) -> Result<Vec<u8>> {
+        // A vault saved before the switch is still plain age; the next save
+        // seals it
+        let rest = match ciphertext.strip_prefix(ENVELOPE_MAGIC) {
+            Some(rest) => rest,
+            None => return AgeCipher.decrypt(ciphertext, key),
+        };
+        let (wrapped, outer) = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|i| (&rest[..i], &rest[i + 1..]))
+            .ok_or_else(|| AuthyError::Decryption("truncated vault envelope".into()))?;
+        let wrapped = STANDARD
+            .decode(wrapped)
+            .map_err(|e| AuthyError::Decryption(format!("bad wrapped data key: {}", e)))?;
+
+        let data_key = self.keys.decrypt_data_key(&wrapped)?;
+        let identity = data_key_identity(&data_key)?;
+        let inner = crypto::decrypt_with_keyfile(outer, identity.to_string().expose_secret())?;
+        AgeCipher.decrypt(&inner, key)
+    }
+}
+
+fn data_key_identity(data_key: &[u8]) -> Result<age::x25519::Identity> {
+    if data_key.len() != 32 {
+        return Err(AuthyError::Encryption(format!(
+            "expected a 32-byte data key, got {} bytes",
+            data_key.len()
+        )));
+    }
+    crypto::identity_from_seed(data_key)
+}
+
+/// AWS KMS, called over its JSON API and signed with SigV4.
+struct AwsKms {
+    endpoint: String,
+    region: String,
+    key_id: String,
+    credentials: AwsCredentials,
+}
+
+impl AwsKms {
+    fn call(&self, action: &str, body: serde_json::Value) -> Result<serde_json::Value> {
+        let payload = body.to_string();
+        let host = self
+            .endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, rest)| rest)
+            .to_string();
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(payload.as_bytes()));
+
+        let mut headers = vec![
+            ("content-type".to_string(), "application/x-amz-json-1.1".to_string()),
+            ("host".to_string(), host),
+            ("x-amz-date".to_string(), amz_date.clone()),
+            ("x-amz-target".to_string(), format!("TrentService.{}", action)),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let authorization = sigv4_authorization(
+            &self.credentials,
+            &self.region,
+            "kms",
+            "POST",
+            "/",
+            &headers,
+            &payload_hash,
+            &amz_date,
+        );
+
+        let mut request = ureq::post(&format!("{}/", self.endpoint));
+        for (name, value) in headers.iter().filter(|(n, _)| n != "host") {
+            request = request.set(name, value);
+        }
+        let response = request
+            .set("Authorization", &authorization)
+            .send_string(&payload)
+            .map_err(|e| kms_error(action, e))?
+            .into_string()
+            .map_err(|e| kms_error(action, e))?;
+        serde_json::from_str(&response).map_err(|e| kms_error(action, e))
+    }
+
+    /// Binds wrapped data keys to authy vaults (and shows up in CloudTrail).
+    fn encryption_context() -> serde_json::Value {
+        serde_json::json!({ "purpose": "authy-vault" })
+    }
+}
+
+impl KeyService for AwsKms {
+    fn name(&self) -> &'static str {
+        "aws-kms"
+    }
+
+    fn generate_data_key(&self) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+        let response = self.call(
+            "GenerateDataKey",
+            serde_json::json!({
+                "KeyId": self.key_id,
+                "NumberOfBytes": 32,
+                "EncryptionContext": Self::encryption_context(),
+            }),
+        )?;
+        let plaintext = Zeroizing::new(base64_field(&response, "Plaintext")?);
+        let wrapped = base64_field(&response, "CiphertextBlob")?;
+        Ok((plaintext, wrapped))
+    }
+
+    fn decrypt_data_key(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let response = self.call(
+            "Decrypt",
+            serde_json::json!({
+                "KeyId": self.key_id,
+                "CiphertextBlob": STANDARD.encode(wrapped),
+                "EncryptionContext": Self::encryption_context(),
+            }),
+        )?;
+        base64_field(&response, "Plaintext").map(Zeroizing::new)
+    }
+}
+
+fn base64_field(response: &serde_json::Value, field: &str) -> Result<Vec<u8>> {
+    response
+        .get(field)
+        .and_then(|v| v.as_str())
+        .and_then(|v| STANDARD.decode(v).ok())
+        .ok_or_else(|| AuthyError::Other(format!("AWS KMS response has no {}", field)))
+}
+
+fn kms_error(action: &str, e: impl std::fmt::Display) -> AuthyError {
+    AuthyError::Other(format!("AWS KMS {}: {}", action, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wraps data keys by XOR with a fixed pad.
+    struct FakeKms;
+
+    impl KeyService for FakeKms {
+        fn name(&self) -> &'static str {
+            "fake-kms"
+        }
+
+        fn generate_data_key(&self) -> Result<(Zeroizing<Vec<u8>>, Vec<u8>)> {
+            let key: Vec<u8> = (0..32).collect();
+            let wrapped = key.iter().map(|b| b ^ 0x5a).collect();
+            Ok((Zeroizing::new(key), wrapped))
+        }
+
+        fn decrypt_data_key(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+            Ok(Zeroizing::new(wrapped.iter().map(|b| b ^ 0x5a).collect()))
+        }
+    }
+
+    fn keyfile_key() -> VaultKey {
+        let (identity, pubkey) = crypto::generate_keypair();
+        VaultKey::Keyfile { identity, pubkey }
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let key = keyfile_key();
+        let cipher = EnvelopeCipher::new(Box::new(FakeKms));
+
+        let sealed = cipher.encrypt(b"vault body", &key).unwrap();
+        assert!(sealed.starts_with(ENVELOPE_MAGIC));
+        assert_eq!(cipher.decrypt(&sealed, &key).unwrap(), b"vault body");
+
+        // Plain age can't open it, and says why
+        let err = AgeCipher.decrypt(&sealed, &key).unwrap_err();
+        assert!(err.to_string().contains("aws-kms"));
+
+        // The master credential is still required
+        assert!(cipher.decrypt(&sealed, &keyfile_key()).is_err());
+    }
+
+    #[test]
+    fn test_envelope_reads_plain_age_vault() {
+        let key = keyfile_key();
+        let plain = AgeCipher.encrypt(b"vault body", &key).unwrap();
+        let cipher = EnvelopeCipher::new(Box::new(FakeKms));
+        assert_eq!(cipher.decrypt(&plain, &key).unwrap(), b"vault body");
+    }
+}
@@ -1,10 +1,14 @@
 use std::io::{Read, Write};
 
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
 use age::secrecy::ExposeSecret;
+use bech32::ToBase32;
 use hkdf::Hkdf;
 use sha2::Sha256;
 
+use super::plugin::{PluginIdentity, PluginRecipient};
 use crate::error::{AuthyError, Result};
+use crate::timings;
 
 /// Encrypt data using a passphrase via age.
 pub fn encrypt_with_passphrase(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
@@ -13,15 +17,14 @@ pub fn encrypt_with_passphrase(plaintext: &[u8], passphrase: &str) -> Result<Vec
     );
 
     let mut encrypted = vec![];
-    let mut writer = encryptor
-        .wrap_output(&mut encrypted)
-        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
-    writer
-        .write_all(plaintext)
-        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
-    writer
-        .finish()
+    // Wrapping the file key runs the KDF (scrypt, for passphrases)
+    let mut writer = timings::time("kdf", || encryptor.wrap_output(&mut encrypted))
         .map_err(|e| AuthyError::Encryption(e.to_string()))?;
+    timings::time("encrypt", || {
+        writer.write_all(plaintext)?;
+        writer.finish()
+    })
+    .map_err(|e| AuthyError::Encryption(e.to_string()))?;
 
     Ok(encrypted)
 }
@@ -36,49 +39,110 @@ pub fn decrypt_with_passphrase(ciphertext: &[u8], passphrase: &str) -> Result<Ve
     };
 
     let mut decrypted = vec![];
-    let mut reader = decryptor
-        .decrypt(
-            &age::secrecy::Secret::new(passphrase.to_string()),
-            None,
-        )
-        .map_err(|e| AuthyError::Decryption(e.to_string()))?;
-    reader
-        .read_to_end(&mut decrypted)
+    let mut reader = timings::time("kdf", || {
+        decryptor.decrypt(&age::secrecy::Secret::new(passphrase.to_string()), None)
+    })
+    .map_err(|e| AuthyError::Decryption(e.to_string()))?;
+    timings::time("decrypt", || reader.read_to_end(&mut decrypted))
         .map_err(|e| AuthyError::Decryption(e.to_string()))?;
 
     Ok(decrypted)
 }
 
-/// Encrypt data using an age identity (keyfile).
+/// Encrypt data using an age identity (keyfile). `pubkey` may be a plugin
+/// recipient (`age1yubikey1...`), wrapped by the plugin binary.
 pub fn encrypt_with_keyfile(plaintext: &[u8], pubkey: &str) -> Result<Vec<u8>> {
-    let recipient: age::x25519::Recipient = pubkey
-        .parse()
-        .map_err(|e: &str| AuthyError::Encryption(e.to_string()))?;
+    let recipient: Box<dyn age::Recipient + Send> = match PluginRecipient::new(pubkey) {
+        Some(recipient) => Box::new(recipient),
+        None => Box::new(
+            pubkey
+                .parse::<age::x25519::Recipient>()
+                .map_err(|e: &str| AuthyError::Encryption(e.to_string()))?,
+        ),
+    };
+
+    let encryptor =
+        age::Encryptor::with_recipients(vec![recipient]).expect("recipients not empty");
+
+    let mut encrypted = vec![];
+    // Wrapping the file key runs the KDF (scrypt, for passphrases)
+    let mut writer = timings::time("kdf", || encryptor.wrap_output(&mut encrypted))
+        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
+    timings::time("encrypt", || {
+        writer.write_all(plaintext)?;
+        writer.finish()
+    })
+    .map_err(|e| AuthyError::Encryption(e.to_string()))?;
+
+    Ok(encrypted)
+}
+
+/// Decrypt data using an age identity (keyfile). A plugin identity
+/// (`AGE-PLUGIN-...`) is unwrapped by the plugin binary.
+pub fn decrypt_with_keyfile(ciphertext: &[u8], identity_str: &str) -> Result<Vec<u8>> {
+    let identity: Box<dyn age::Identity> = match PluginIdentity::new(identity_str) {
+        Some(identity) => Box::new(identity),
+        None => Box::new(
+            identity_str
+                .parse::<age::x25519::Identity>()
+                .map_err(|e: &str| AuthyError::InvalidKeyfile(e.to_string()))?,
+        ),
+    };
+
+    let decryptor = match age::Decryptor::new(ciphertext)
+        .map_err(|e| AuthyError::Decryption(e.to_string()))?
+    {
+        age::Decryptor::Recipients(d) => d,
+        _ => return Err(AuthyError::Decryption("Expected recipients-encrypted data".into())),
+    };
+
+    let mut decrypted = vec![];
+    let mut reader = timings::time("kdf", || {
+        decryptor.decrypt(std::iter::once(identity.as_ref()))
+    })
+    .map_err(|e| AuthyError::Decryption(e.to_string()))?;
+    timings::time("decrypt", || reader.read_to_end(&mut decrypted))
+        .map_err(|e| AuthyError::Decryption(e.to_string()))?;
+
+    Ok(decrypted)
+}
 
-    let encryptor = age::Encryptor::with_recipients(vec![Box::new(recipient)])
-        .expect("recipients not empty");
+/// Encrypt data to one or more age recipients (`age1...`), ASCII-armored so
+/// it can be pasted or mailed. Used for export bundles, not the vault.
+pub fn encrypt_to_recipients(plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>> {
+    let mut parsed: Vec<Box<dyn age::Recipient + Send>> = Vec::with_capacity(recipients.len());
+    for r in recipients {
+        let recipient: age::x25519::Recipient = r.trim().parse().map_err(|e: &str| {
+            AuthyError::Encryption(format!("Invalid age recipient '{}': {}", r, e))
+        })?;
+        parsed.push(Box::new(recipient));
+    }
+    let encryptor = age::Encryptor::with_recipients(parsed)
+        .ok_or_else(|| AuthyError::Encryption("At least one age recipient is required".into()))?;
 
     let mut encrypted = vec![];
+    let armored = ArmoredWriter::wrap_output(&mut encrypted, Format::AsciiArmor)
+        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
     let mut writer = encryptor
-        .wrap_output(&mut encrypted)
+        .wrap_output(armored)
         .map_err(|e| AuthyError::Encryption(e.to_string()))?;
     writer
         .write_all(plaintext)
-        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
-    writer
-        .finish()
+        .and_then(|_| writer.finish())
+        .and_then(|armored| armored.finish())
         .map_err(|e| AuthyError::Encryption(e.to_string()))?;
 
     Ok(encrypted)
 }
 
-/// Decrypt data using an age identity (keyfile).
-pub fn decrypt_with_keyfile(ciphertext: &[u8], identity_str: &str) -> Result<Vec<u8>> {
+/// Decrypt data produced by [`encrypt_to_recipients`] (armored or binary)
+/// with an age identity.
+pub fn decrypt_from_recipients(ciphertext: &[u8], identity_str: &str) -> Result<Vec<u8>> {
     let identity: age::x25519::Identity = identity_str
         .parse()
         .map_err(|e: &str| AuthyError::InvalidKeyfile(e.to_string()))?;
 
-    let decryptor = match age::Decryptor::new(ciphertext)
+    let decryptor = match age::Decryptor::new(ArmoredReader::new(ciphertext))
         .map_err(|e| AuthyError::Decryption(e.to_string()))?
     {
         age::Decryptor::Recipients(d) => d,
@@ -86,10 +150,9 @@ pub fn decrypt_with_keyfile(ciphertext: &[u8], identity_str: &str) -> Result<Vec
     };
 
     let mut decrypted = vec![];
-    let mut reader = decryptor
+    decryptor
         .decrypt(std::iter::once(&identity as &dyn age::Identity))
-        .map_err(|e| AuthyError::Decryption(e.to_string()))?;
-    reader
+        .map_err(|e| AuthyError::Decryption(e.to_string()))?
         .read_to_end(&mut decrypted)
         .map_err(|e| AuthyError::Decryption(e.to_string()))?;
 
@@ -112,3 +175,14 @@ pub fn generate_keypair() -> (String, String) {
     let public_key = identity.to_public().to_string();
     (secret_key.expose_secret().clone(), public_key)
 }
+
+/// An age x25519 identity with `seed` (32 bytes) as its secret scalar, for
+/// keys that are derived rather than generated.
+pub fn identity_from_seed(seed: &[u8]) -> Result<age::x25519::Identity> {
+    let encoded = bech32::encode("age-secret-key-", seed.to_base32(), bech32::Variant::Bech32)
+        .map_err(|e| AuthyError::Encryption(e.to_string()))?;
+    encoded
+        .to_uppercase()
+        .parse()
+        .map_err(|e: &str| AuthyError::Encryption(e.to_string()))
+}
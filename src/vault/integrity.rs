@@ -0,0 +1,109 @@
+//! Integrity framing and schema validation for the decrypted vault.
+//!
+//! The age layer already authenticates the ciphertext, but a vault written by a
+//! buggy build (or a plaintext mangled before encryption) decrypts cleanly and
+//! then fails somewhere inside msgpack. The sealed format prefixes the
+//! serialized vault with a keyed checksum so that case is reported as
+//! corruption rather than a generic deserialization error.
+//!
+//! Layout: `MAGIC (8 bytes) || HMAC-SHA256(body) (32 bytes) || body (msgpack)`.
+//! Vaults written before the framing existed are plain msgpack and still load.
+
+use std::collections::HashSet;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::Vault;
+use crate::policy::build_globset;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const MAGIC: &[u8; 8] = b"AUTHYVI1";
+const MAC_LEN: usize = 32;
+
+/// Highest vault format version this build understands.
+pub const SUPPORTED_VERSION: u32 = 1;
+
+/// Derive the integrity key from the master key material.
+pub fn derive_integrity_key(master_material: &[u8]) -> Vec<u8> {
+    super::crypto::derive_key(master_material, b"vault-integrity", 32)
+}
+
+/// Frame a serialized vault body with its keyed checksum.
+pub fn seal(body: &[u8], integrity_key: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(MAGIC.len() + MAC_LEN + body.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&checksum(body, integrity_key));
+    out.extend_from_slice(body);
+    out
+}
+
+/// Strip and check the framing, returning the serialized vault body.
+/// Unframed (legacy) plaintext is returned as-is.
+pub fn open<'a>(plaintext: &'a [u8], integrity_key: &[u8]) -> std::result::Result<&'a [u8], String> {
+    if !plaintext.starts_with(MAGIC) {
+        return Ok(plaintext);
+    }
+    if plaintext.len() < MAGIC.len() + MAC_LEN {
+        return Err("truncated integrity header".into());
+    }
+    let (mac, body) = plaintext[MAGIC.len()..].split_at(MAC_LEN);
+    let mut verifier =
+        HmacSha256::new_from_slice(integrity_key).expect("HMAC can take key of any size");
+    verifier.update(body);
+    verifier
+        .verify_slice(mac)
+        .map_err(|_| "integrity checksum mismatch".to_string())?;
+    Ok(body)
+}
+
+fn checksum(body: &[u8], integrity_key: &[u8]) -> Vec<u8> {
+    let mut mac =
+        HmacSha256::new_from_slice(integrity_key).expect("HMAC can take key of any size");
+    mac.update(body);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Check structural invariants that serde can't express.
+pub fn validate(vault: &Vault) -> std::result::Result<(), String> {
+    if vault.version == 0 || vault.version > SUPPORTED_VERSION {
+        return Err(format!(
+            "unsupported vault version {} (this build reads up to {})",
+            vault.version, SUPPORTED_VERSION
+        ));
+    }
+
+    for (name, entry) in &vault.secrets {
+        if name.is_empty() {
+            return Err("secret with an empty name".into());
+        }
+        if entry.metadata.version == 0 {
+            return Err(format!("secret '{}' has version 0", name));
+        }
+    }
+
+    for (key, policy) in &vault.policies {
+        if key != &policy.name {
+            return Err(format!(
+                "policy stored under '{}' is named '{}'",
+                key, policy.name
+            ));
+        }
+        build_globset(&policy.allow)
+            .and_then(|_| build_globset(&policy.deny))
+            .map_err(|e| format!("policy '{}' has an invalid pattern: {}", key, e))?;
+    }
+
+    let mut ids = HashSet::new();
+    for session in &vault.sessions {
+        if session.id.is_empty() {
+            return Err("session with an empty id".into());
+        }
+        if !ids.insert(session.id.as_str()) {
+            return Err(format!("duplicate session id '{}'", session.id));
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,19 @@
+pub mod cache;
+pub mod cipher;
 pub mod crypto;
+pub mod integrity;
+pub mod lint;
+pub mod names;
+pub mod plugin;
 pub mod secret;
+pub mod storage;
+pub mod values;
 
-use std::fs;
-
+use crate::config::{Config, SessionsConfig};
 use crate::error::{AuthyError, Result};
-use crate::policy::Policy;
+use crate::policy::{Policy, PolicyRevision};
 use crate::session::SessionRecord;
+use crate::timings;
 use crate::types::*;
 use crate::vault::secret::SecretEntry;
 
@@ -18,6 +26,33 @@ pub struct Vault {
     pub secrets: BTreeMap<String, SecretEntry>,
     pub policies: BTreeMap<String, Policy>,
     pub sessions: Vec<SessionRecord>,
+    /// Structural changes (policies, sessions, rekeys), oldest first.
+    #[serde(default)]
+    pub events: Vec<VaultEvent>,
+    /// Age recipients whose keyfiles can approve changes to protected
+    /// secrets and policies (`authy approver add`).
+    #[serde(default)]
+    pub approvers: Vec<String>,
+    /// Previous versions of each policy, oldest first (`authy policy history`).
+    #[serde(default)]
+    pub policy_history: BTreeMap<String, Vec<PolicyRevision>>,
+}
+
+/// Upper bound on [`Vault::events`]; the oldest are dropped first.
+pub const MAX_EVENTS: usize = 1000;
+
+/// Upper bound on the revisions kept per policy; the oldest are dropped first.
+pub const MAX_POLICY_REVISIONS: usize = 20;
+
+/// A structural change recorded inside the vault, so history such as the
+/// last rekey survives audit log rotation. Never holds secret values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEvent {
+    pub timestamp: DateTime<Utc>,
+    /// The audit operation name: `policy.create`, `session.revoke`, `rekey`, ...
+    pub kind: String,
+    pub actor: String,
+    pub detail: Option<String>,
 }
 
 impl Default for Vault {
@@ -37,6 +72,9 @@ impl Vault {
             secrets: BTreeMap::new(),
             policies: BTreeMap::new(),
             sessions: Vec::new(),
+            events: Vec::new(),
+            approvers: Vec::new(),
+            policy_history: BTreeMap::new(),
         }
     }
 
@@ -44,6 +82,78 @@ impl Vault {
     pub fn touch(&mut self) {
         self.modified_at = Utc::now();
     }
+
+    /// Append a structural event, dropping the oldest beyond [`MAX_EVENTS`].
+    pub fn record_event(&mut self, kind: &str, actor: &str, detail: Option<String>) {
+        self.events.push(VaultEvent {
+            timestamp: Utc::now(),
+            kind: kind.to_string(),
+            actor: actor.to_string(),
+            detail,
+        });
+        if self.events.len() > MAX_EVENTS {
+            let excess = self.events.len() - MAX_EVENTS;
+            self.events.drain(..excess);
+        }
+    }
+
+    /// Policy `name` with the rules of the policies it extends folded in
+    /// (see [`crate::policy::resolve`]). Use this, not `policies`, when
+    /// checking access.
+    pub fn effective_policy(&self, name: &str) -> Result<Policy> {
+        crate::policy::resolve(name, &self.policies)
+    }
+
+    /// Names of the policies that extend `name` directly.
+    pub fn policies_extending(&self, name: &str) -> Vec<&str> {
+        self.policies
+            .values()
+            .filter(|p| p.extends.iter().any(|parent| parent == name))
+            .map(|p| p.name.as_str())
+            .collect()
+    }
+
+    /// Save the current version of policy `name` to its history before it
+    /// changes, dropping the oldest beyond [`MAX_POLICY_REVISIONS`].
+    /// Returns the new revision number, or `None` if there is no such policy.
+    pub fn record_policy_revision(&mut self, name: &str, actor: &str) -> Option<u32> {
+        let policy = self.policies.get(name)?.clone();
+        let history = self.policy_history.entry(name.to_string()).or_default();
+        let rev = history.last().map_or(1, |r| r.rev + 1);
+        history.push(PolicyRevision {
+            rev,
+            replaced_at: Utc::now(),
+            actor: actor.to_string(),
+            policy,
+        });
+        if history.len() > MAX_POLICY_REVISIONS {
+            let excess = history.len() - MAX_POLICY_REVISIONS;
+            history.drain(..excess);
+        }
+        Some(rev)
+    }
+
+    /// Drop sessions that ended (expired or were revoked) at least
+    /// `retention` ago. Returns how many were removed.
+    pub fn prune_sessions(&mut self, retention: chrono::Duration, now: DateTime<Utc>) -> usize {
+        let before = self.sessions.len();
+        self.sessions.retain(|s| !prunable(s, retention, now));
+        before - self.sessions.len()
+    }
+
+    /// How many sessions [`prune_sessions`](Self::prune_sessions) would drop.
+    pub fn prunable_sessions(&self, retention: chrono::Duration, now: DateTime<Utc>) -> usize {
+        self.sessions.iter().filter(|s| prunable(s, retention, now)).count()
+    }
+
+    /// The most recent event of `kind`, or of any `kind.*` sub-kind
+    /// (`"policy"` matches `policy.create` and `policy.remove`).
+    pub fn last_event(&self, kind: &str) -> Option<&VaultEvent> {
+        self.events.iter().rev().find(|e| {
+            e.kind == kind
+                || (e.kind.starts_with(kind) && e.kind[kind.len()..].starts_with('.'))
+        })
+    }
 }
 
 /// Encryption mode for the vault.
@@ -51,74 +161,218 @@ impl Vault {
 pub enum VaultKey {
     Passphrase(String),
     Keyfile { identity: String, pubkey: String },
+    /// An age identity derived through the SSH agent (see [`crate::auth::ssh_agent`]).
+    SshAgent { identity: String, pubkey: String },
+}
+
+/// Overrides the authy directory, for service accounts and containers
+/// without a usable `HOME`.
+pub const AUTHY_HOME_ENV: &str = "AUTHY_HOME";
+
+thread_local! {
+    static BOUND_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Binds the authy directory for the current thread until dropped; see
+/// [`bind_authy_dir`].
+#[must_use = "the directory is only bound while the guard is alive"]
+pub struct BoundDirGuard {
+    previous: Option<PathBuf>,
+}
+
+impl Drop for BoundDirGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        BOUND_DIR.with(|dir| *dir.borrow_mut() = previous);
+    }
+}
+
+/// Use `dir` as the authy directory on this thread while the returned
+/// guard lives, ahead of `AUTHY_HOME` and `HOME`. `AUTHY_CONFIG` is ignored
+/// too, so the config is the one in `dir`. This is how an
+/// [`AuthyClient`](crate::api::AuthyClient) bound
+/// [`with_home`](crate::api::AuthyClient::with_home) reaches its vault
+/// without touching the process environment.
+pub fn bind_authy_dir(dir: impl Into<PathBuf>) -> BoundDirGuard {
+    let previous = BOUND_DIR.with(|bound| bound.borrow_mut().replace(dir.into()));
+    BoundDirGuard { previous }
+}
+
+fn bound_authy_dir() -> Option<PathBuf> {
+    BOUND_DIR.with(|dir| dir.borrow().clone())
+}
+
+/// Get the authy directory: the one bound with [`bind_authy_dir`], else
+/// `AUTHY_HOME` if set, else `~/.authy`.
+/// Fails with [`AuthyError::HomeNotFound`] when none is available.
+pub fn authy_dir() -> Result<PathBuf> {
+    if let Some(dir) = bound_authy_dir() {
+        return Ok(dir);
+    }
+    match std::env::var_os(AUTHY_HOME_ENV) {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => dirs::home_dir()
+            .filter(|home| !home.as_os_str().is_empty())
+            .map(|home| home.join(".authy"))
+            .ok_or(AuthyError::HomeNotFound),
+    }
 }
 
-/// Get the default authy directory path (~/.authy).
-pub fn authy_dir() -> PathBuf {
-    dirs::home_dir()
-        .expect("Could not determine home directory")
-        .join(".authy")
+/// Get the vault file path used by the local storage backend.
+pub fn vault_path() -> Result<PathBuf> {
+    Ok(authy_dir()?.join("vault.age"))
 }
 
-/// Get the vault file path.
-pub fn vault_path() -> PathBuf {
-    authy_dir().join("vault.age")
+/// Get the path of the copy of the previous vault kept by `save_vault`.
+pub fn backup_path() -> Result<PathBuf> {
+    Ok(authy_dir()?.join("vault.age.bak"))
 }
 
-/// Get the config file path.
-pub fn config_path() -> PathBuf {
-    authy_dir().join("authy.toml")
+/// Get the config file path: `AUTHY_CONFIG` if set, else `authy.toml` in
+/// the authy directory. A directory bound with [`bind_authy_dir`] always
+/// uses its own `authy.toml`.
+pub fn config_path() -> Result<PathBuf> {
+    if let Some(dir) = bound_authy_dir() {
+        return Ok(dir.join("authy.toml"));
+    }
+    match std::env::var_os("AUTHY_CONFIG") {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Ok(authy_dir()?.join("authy.toml")),
+    }
 }
 
 /// Get the audit log path.
-pub fn audit_path() -> PathBuf {
-    authy_dir().join("audit.log")
+pub fn audit_path() -> Result<PathBuf> {
+    Ok(authy_dir()?.join("audit.log"))
 }
 
-/// Check if the vault is initialized.
+/// Check if the vault is initialized. A storage backend that can't be
+/// reached counts as not initialized; use [`storage::open`] to see why.
 pub fn is_initialized() -> bool {
-    vault_path().exists()
+    storage::open()
+        .and_then(|s| s.exists())
+        .unwrap_or(false)
 }
 
-/// Load and decrypt the vault from disk.
-pub fn load_vault(key: &VaultKey) -> Result<Vault> {
-    let path = vault_path();
-    if !path.exists() {
-        return Err(AuthyError::VaultNotInitialized);
+/// Fail with `VaultAlreadyExists` if the configured storage already holds
+/// a vault. Unlike [`is_initialized`], an unreachable backend is an error.
+pub fn check_not_initialized() -> Result<()> {
+    let storage = storage::open()?;
+    if storage.exists()? {
+        return Err(AuthyError::VaultAlreadyExists(storage.location()));
     }
+    Ok(())
+}
 
-    let ciphertext = fs::read(&path)?;
-    let plaintext = match key {
-        VaultKey::Passphrase(pass) => crypto::decrypt_with_passphrase(&ciphertext, pass)?,
-        VaultKey::Keyfile { identity, .. } => {
-            crypto::decrypt_with_keyfile(&ciphertext, identity)?
-        }
-    };
+/// Load and decrypt the vault from the configured storage backend.
+pub fn load_vault(key: &VaultKey) -> Result<Vault> {
+    let storage = storage::open()?;
+    let ciphertext = timings::time("vault read", || storage.read())?;
+    decrypt_vault(&ciphertext, key)
+}
 
-    let vault: Vault =
-        rmp_serde::from_slice(&plaintext).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+/// Decrypt and check vault bytes as stored by a storage backend.
+pub fn decrypt_vault(ciphertext: &[u8], key: &VaultKey) -> Result<Vault> {
+    let plaintext = cipher::open()?.decrypt(ciphertext, key)?;
+    timings::time("deserialize", || unseal(&plaintext, key).map_err(corrupted))
+}
 
+/// Decrypt and check an age-encrypted copy of `vault.age` without reading
+/// the config or the authy directory, so an escrowed snapshot can be
+/// verified on another machine.
+pub fn open_snapshot(ciphertext: &[u8], key: &VaultKey) -> Result<Vault> {
+    use cipher::VaultCipher;
+    let plaintext = cipher::AgeCipher.decrypt(ciphertext, key)?;
+    unseal(&plaintext, key).map_err(|reason| AuthyError::VaultCorrupted {
+        reason,
+        backup: None,
+    })
+}
+
+/// Check the integrity seal on a decrypted vault and deserialize it.
+fn unseal(plaintext: &[u8], key: &VaultKey) -> std::result::Result<Vault, String> {
+    let integrity_key = integrity::derive_integrity_key(&crate::audit::key_material(key));
+    let body = integrity::open(plaintext, &integrity_key)?;
+    let vault: Vault = rmp_serde::from_slice(body).map_err(|e| e.to_string())?;
+    integrity::validate(&vault)?;
     Ok(vault)
 }
 
-/// Encrypt and save the vault to disk with atomic rename.
+/// Build a `VaultCorrupted` error, pointing at the backup if there is one.
+fn corrupted(reason: String) -> AuthyError {
+    AuthyError::VaultCorrupted {
+        reason,
+        backup: backup_path()
+            .ok()
+            .filter(|backup| backup.exists())
+            .map(|backup| backup.display().to_string()),
+    }
+}
+
+fn prunable(session: &SessionRecord, retention: chrono::Duration, now: DateTime<Utc>) -> bool {
+    session.ended_at(now).is_some_and(|ended| now - ended >= retention)
+}
+
+/// Actor recorded for sessions pruned while saving the vault.
+pub const AUTO_PRUNE_ACTOR: &str = "auto-prune";
+
+/// Drop sessions past the `[sessions]` retention window if `auto_prune` is
+/// on, recording a `session.prune` event. Returns how many were dropped.
+pub fn auto_prune_sessions(vault: &mut Vault, config: &SessionsConfig) -> Result<usize> {
+    if !config.auto_prune {
+        return Ok(0);
+    }
+    let count = vault.prune_sessions(config.retention()?, Utc::now());
+    if count > 0 {
+        vault.record_event(
+            "session.prune",
+            AUTO_PRUNE_ACTOR,
+            Some(format!("count={}, retention={}", count, config.retention)),
+        );
+    }
+    Ok(count)
+}
+
+/// Encrypt and save the vault to the configured storage backend.
+///
+/// Sessions past the `[sessions]` retention window are left out of the saved
+/// copy (see [`auto_prune_sessions`]), with an audit entry for the purge.
 pub fn save_vault(vault: &Vault, key: &VaultKey) -> Result<()> {
-    let path = vault_path();
-    let dir = path.parent().unwrap();
-    fs::create_dir_all(dir)?;
+    let config = Config::load(&config_path()?)?;
+    let storage = storage::from_config(&config.storage)?;
 
-    let plaintext =
-        rmp_serde::to_vec(vault).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+    let mut pruned = None;
+    if config.sessions.auto_prune
+        && vault.prunable_sessions(config.sessions.retention()?, Utc::now()) > 0
+    {
+        let mut copy = vault.clone();
+        let count = auto_prune_sessions(&mut copy, &config.sessions)?;
+        pruned = Some((copy, count));
+    }
+    let vault = pruned.as_ref().map_or(vault, |(copy, _)| copy);
+
+    let plaintext = timings::time("serialize", || {
+        let body =
+            rmp_serde::to_vec(vault).map_err(|e| AuthyError::Serialization(e.to_string()))?;
+        let integrity_key = integrity::derive_integrity_key(&crate::audit::key_material(key));
+        Ok::<_, AuthyError>(integrity::seal(&body, &integrity_key))
+    })?;
 
-    let ciphertext = match key {
-        VaultKey::Passphrase(pass) => crypto::encrypt_with_passphrase(&plaintext, pass)?,
-        VaultKey::Keyfile { pubkey, .. } => crypto::encrypt_with_keyfile(&plaintext, pubkey)?,
-    };
+    let ciphertext = cipher::from_config(&config.cipher)?.encrypt(&plaintext, key)?;
 
-    // Atomic write: write to temp file, then rename
-    let tmp_path = path.with_extension("age.tmp");
-    fs::write(&tmp_path, &ciphertext)?;
-    fs::rename(&tmp_path, &path)?;
+    timings::time("write", || storage.write(&ciphertext))?;
 
+    if let Some((_, count)) = pruned {
+        let audit_key = crate::audit::derive_audit_key(&crate::audit::key_material(key));
+        crate::audit::log_event(
+            &audit_path()?,
+            "session.prune",
+            None,
+            AUTO_PRUNE_ACTOR,
+            "success",
+            Some(&format!("count={}, retention={}", count, config.sessions.retention)),
+            &audit_key,
+        )?;
+    }
     Ok(())
 }
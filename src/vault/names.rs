@@ -0,0 +1,71 @@
+//! Validation and normalization of secret and policy names.
+//!
+//! Names are compared byte-for-byte, so the same visible name typed on macOS
+//! (often NFD) and Linux (NFC) would otherwise be two different secrets. New
+//! names are normalized to NFC and restricted to characters that are safe in
+//! globs, environment variable names, and shell arguments. Names already in a
+//! vault are left alone so existing entries stay reachable.
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::Vault;
+use crate::error::{AuthyError, Result};
+
+/// Maximum length of a name, in bytes after normalization.
+pub const MAX_NAME_LEN: usize = 256;
+
+/// Normalize and validate a new secret name.
+pub fn secret_name(name: &str) -> Result<String> {
+    normalize("secret", name)
+}
+
+/// Normalize and validate a new policy name.
+pub fn policy_name(name: &str) -> Result<String> {
+    normalize("policy", name)
+}
+
+/// The key to store a secret under. An existing entry keeps its exact name
+/// (even one that predates validation); anything else is normalized.
+pub fn secret_key(vault: &Vault, name: &str) -> Result<String> {
+    if vault.secrets.contains_key(name) {
+        return Ok(name.to_string());
+    }
+    secret_name(name)
+}
+
+/// The key to create or update a policy under; see [`secret_key`].
+pub fn policy_key(vault: &Vault, name: &str) -> Result<String> {
+    if vault.policies.contains_key(name) {
+        return Ok(name.to_string());
+    }
+    policy_name(name)
+}
+
+fn normalize(kind: &str, name: &str) -> Result<String> {
+    let normalized: String = name.nfc().collect();
+    let invalid = |reason: String| {
+        AuthyError::InvalidName(format!("{} name '{}' {}", kind, name.escape_debug(), reason))
+    };
+
+    let first = match normalized.chars().next() {
+        Some(c) => c,
+        None => return Err(AuthyError::InvalidName(format!("{} name is empty", kind))),
+    };
+    if normalized.len() > MAX_NAME_LEN {
+        return Err(invalid(format!("is longer than {} bytes", MAX_NAME_LEN)));
+    }
+    if !(first.is_alphanumeric() || first == '_') {
+        return Err(invalid("must start with a letter, digit, or '_'".into()));
+    }
+    if let Some(bad) = normalized.chars().find(|c| !is_name_char(*c)) {
+        return Err(invalid(format!(
+            "contains '{}' (allowed: letters, digits, '-', '_', '.', '/')",
+            bad.escape_debug()
+        )));
+    }
+    Ok(normalized)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/')
+}
@@ -0,0 +1,369 @@
+//! age plugin identities (`AGE-PLUGIN-YUBIKEY-1...`, `AGE-PLUGIN-TPM-1...`)
+//! for hardware-backed vault keys.
+//!
+//! The identity file only references a key held by the device, so the vault
+//! master key never sits on disk in plaintext. Wrapping and unwrapping the
+//! file key is done by the `age-plugin-<name>` binary on `PATH`, spoken to
+//! over the age plugin protocol (`recipient-v1` / `identity-v1`).
+
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use age::{DecryptError, EncryptError};
+use age_core::format::{FileKey, Stanza};
+use age_core::plugin::{Connection, IDENTITY_V1, RECIPIENT_V1};
+use age_core::secrecy::ExposeSecret;
+use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine};
+
+use crate::error::{AuthyError, Result};
+
+const IDENTITY_PREFIX: &str = "AGE-PLUGIN-";
+const RECIPIENT_PREFIX: &str = "age1";
+
+/// The plugin name (e.g. `yubikey`) of an `AGE-PLUGIN-<NAME>-1...` identity.
+pub fn identity_plugin(identity: &str) -> Option<String> {
+    let hrp = identity.get(..identity.rfind('1')?)?;
+    let name = hrp.strip_prefix(IDENTITY_PREFIX)?.trim_end_matches('-');
+    valid_name(name).then(|| name.to_ascii_lowercase())
+}
+
+/// The plugin name of an `age1<name>1...` recipient. Plain x25519
+/// recipients (`age1...` with no name) return `None`.
+pub fn recipient_plugin(recipient: &str) -> Option<String> {
+    let hrp = recipient.get(..recipient.rfind('1')?)?;
+    let name = hrp.strip_prefix(RECIPIENT_PREFIX)?;
+    valid_name(name).then(|| name.to_string())
+}
+
+/// Plugin names become binary names, so keep them to a safe alphabet.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.' | b'_'))
+}
+
+fn binary_name(plugin: &str) -> String {
+    format!("age-plugin-{}", plugin)
+}
+
+/// Find `age-plugin-<name>` on `PATH`.
+fn find_binary(plugin: &str) -> Option<PathBuf> {
+    let name = binary_name(plugin);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+}
+
+fn missing(plugin: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} not found on PATH", binary_name(plugin)),
+    )
+}
+
+fn plugin_error(plugin: &str, message: &[u8]) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!("{}: {}", binary_name(plugin), String::from_utf8_lossy(message)),
+    )
+}
+
+/// A plugin recipient, as found in the keyfile's `.pub`.
+pub struct PluginRecipient {
+    plugin: String,
+    recipient: String,
+}
+
+impl PluginRecipient {
+    pub fn new(recipient: &str) -> Option<Self> {
+        Some(Self {
+            plugin: recipient_plugin(recipient)?,
+            recipient: recipient.to_string(),
+        })
+    }
+}
+
+impl age::Recipient for PluginRecipient {
+    fn wrap_file_key(&self, file_key: &FileKey) -> std::result::Result<Vec<Stanza>, EncryptError> {
+        let binary = find_binary(&self.plugin).ok_or_else(|| missing(&self.plugin))?;
+        let mut conn = Connection::open(&binary, RECIPIENT_V1)?;
+
+        conn.unidir_send(|mut phase| {
+            phase.send("add-recipient", &[&self.recipient], &[])?;
+            phase.send("wrap-file-key", &[], file_key.expose_secret())
+        })?;
+
+        let mut stanzas = Vec::new();
+        let mut error = None;
+        conn.bidir_receive(
+            &["msg", "confirm", "request-public", "request-secret", "recipient-stanza", "error"],
+            |mut command, reply| match command.tag.as_str() {
+                // `recipient-stanza <file index> <tag> <args...>`; we only
+                // ever wrap one file key
+                "recipient-stanza" if command.args.len() >= 2 && command.args[0] == "0" => {
+                    command.args.remove(0);
+                    command.tag = command.args.remove(0);
+                    stanzas.push(command);
+                    reply.ok(None)
+                }
+                "recipient-stanza" => {
+                    error = Some(plugin_error(&self.plugin, b"malformed recipient-stanza"));
+                    reply.ok(None)
+                }
+                "error" => {
+                    error = Some(plugin_error(&self.plugin, &command.body));
+                    reply.ok(None)
+                }
+                _ => interact(command, reply),
+            },
+        )?;
+
+        match error {
+            Some(e) => Err(e.into()),
+            None if stanzas.is_empty() => {
+                Err(plugin_error(&self.plugin, b"returned no recipient stanza").into())
+            }
+            None => Ok(stanzas),
+        }
+    }
+}
+
+/// A plugin identity, as read from the keyfile.
+pub struct PluginIdentity {
+    plugin: String,
+    identity: String,
+}
+
+impl PluginIdentity {
+    pub fn new(identity: &str) -> Option<Self> {
+        Some(Self {
+            plugin: identity_plugin(identity)?,
+            identity: identity.to_string(),
+        })
+    }
+}
+
+impl age::Identity for PluginIdentity {
+    fn unwrap_stanza(&self, stanza: &Stanza) -> Option<std::result::Result<FileKey, DecryptError>> {
+        self.unwrap_stanzas(std::slice::from_ref(stanza))
+    }
+
+    fn unwrap_stanzas(
+        &self,
+        stanzas: &[Stanza],
+    ) -> Option<std::result::Result<FileKey, DecryptError>> {
+        let binary = match find_binary(&self.plugin) {
+            Some(binary) => binary,
+            None => return Some(Err(missing(&self.plugin).into())),
+        };
+        let mut conn = match Connection::open(&binary, IDENTITY_V1) {
+            Ok(conn) => conn,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        if let Err(e) = conn.unidir_send(|mut phase| {
+            phase.send("add-identity", &[&self.identity], &[])?;
+            for stanza in stanzas {
+                phase.send_stanza("recipient-stanza", &["0"], stanza)?;
+            }
+            Ok(())
+        }) {
+            return Some(Err(e.into()));
+        }
+
+        let mut file_key = None;
+        let mut error = None;
+        if let Err(e) = conn.bidir_receive(
+            &["msg", "confirm", "request-public", "request-secret", "file-key", "error"],
+            |command, reply| match command.tag.as_str() {
+                "file-key" => {
+                    file_key = Some(
+                        <[u8; 16]>::try_from(&command.body[..])
+                            .map(FileKey::from)
+                            .map_err(|_| DecryptError::DecryptionFailed),
+                    );
+                    reply.ok(None)
+                }
+                "error" => {
+                    error = Some(plugin_error(&self.plugin, &command.body));
+                    reply.ok(None)
+                }
+                _ => interact(command, reply),
+            },
+        ) {
+            return Some(Err(e.into()));
+        }
+
+        match (file_key, error) {
+            (Some(file_key), _) => Some(file_key),
+            (None, Some(e)) => Some(Err(e.into())),
+            // None of the stanzas were for this identity
+            (None, None) => None,
+        }
+    }
+}
+
+type Reply<'a> = age_core::plugin::Reply<
+    'a,
+    age_core::io::DebugReader<std::process::ChildStdout>,
+    age_core::io::DebugWriter<std::process::ChildStdin>,
+>;
+
+/// Handle the plugin's requests to talk to the user: messages (e.g. "touch
+/// your YubiKey"), PIN prompts and confirmations. Prompts need the `cli`
+/// feature and a terminal; otherwise the request fails.
+fn interact(command: Stanza, reply: Reply<'_>) -> age_core::plugin::Response {
+    let message = String::from_utf8_lossy(&command.body).to_string();
+    match command.tag.as_str() {
+        "msg" => {
+            eprintln!("{}", message);
+            reply.ok(None)
+        }
+        "request-secret" => match prompt::secret(&message) {
+            Some(secret) => reply.ok(Some(secret.as_bytes())),
+            None => reply.fail(),
+        },
+        "request-public" => match prompt::public(&message) {
+            Some(value) => reply.ok(Some(value.as_bytes())),
+            None => reply.fail(),
+        },
+        "confirm" => {
+            let yes = command
+                .args
+                .first()
+                .and_then(|s| BASE64_STANDARD_NO_PAD.decode(s).ok())
+                .map(|s| String::from_utf8_lossy(&s).to_string())
+                .unwrap_or_else(|| "yes".to_string());
+            match prompt::confirm(&message, &yes) {
+                Some(answer) => reply.ok_with_metadata(&[if answer { "yes" } else { "no" }], None),
+                None => reply.fail(),
+            }
+        }
+        _ => reply.fail(),
+    }
+}
+
+#[cfg(feature = "cli")]
+mod prompt {
+    use crate::auth::is_non_interactive;
+
+    pub fn secret(message: &str) -> Option<String> {
+        if is_non_interactive() {
+            return None;
+        }
+        dialoguer::Password::new().with_prompt(message).interact().ok()
+    }
+
+    pub fn public(message: &str) -> Option<String> {
+        if is_non_interactive() {
+            return None;
+        }
+        dialoguer::Input::new().with_prompt(message).interact_text().ok()
+    }
+
+    pub fn confirm(message: &str, yes: &str) -> Option<bool> {
+        if is_non_interactive() {
+            return None;
+        }
+        dialoguer::Confirm::new()
+            .with_prompt(format!("{} ({})", message, yes))
+            .interact()
+            .ok()
+    }
+}
+
+#[cfg(not(feature = "cli"))]
+mod prompt {
+    pub fn secret(_message: &str) -> Option<String> {
+        None
+    }
+
+    pub fn public(_message: &str) -> Option<String> {
+        None
+    }
+
+    pub fn confirm(_message: &str, _yes: &str) -> Option<bool> {
+        None
+    }
+}
+
+/// Enroll a new hardware key with `age-plugin-<plugin> --generate`, which
+/// may ask for a PIN or a touch on the terminal. Returns the identity file
+/// contents (as printed by the plugin) and the recipient.
+pub fn generate(plugin: &str) -> Result<(String, String)> {
+    let binary = find_binary(plugin).ok_or_else(|| {
+        AuthyError::InvalidKeyfile(format!("{} not found on PATH", binary_name(plugin)))
+    })?;
+    let output = Command::new(&binary)
+        .arg("--generate")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| AuthyError::Other(format!("Failed to run {}: {}", binary_name(plugin), e)))?;
+    if !output.status.success() {
+        return Err(AuthyError::Other(format!(
+            "{} --generate failed ({})",
+            binary_name(plugin),
+            output.status
+        )));
+    }
+    let contents = String::from_utf8_lossy(&output.stdout).to_string();
+    let recipient = recipient_comment(&contents).ok_or_else(|| {
+        AuthyError::InvalidKeyfile(format!(
+            "{} did not print a recipient",
+            binary_name(plugin)
+        ))
+    })?;
+    Ok((contents, recipient))
+}
+
+/// Pull the plugin identity line out of an identity file, which may also
+/// hold `#` comments.
+pub fn identity_line(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .find(|line| identity_plugin(line).is_some())
+}
+
+/// The recipient from a `# Recipient: age1...` comment, as written by
+/// `age-plugin-yubikey` and `age-plugin-tpm`.
+pub fn recipient_comment(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let comment = line.trim().strip_prefix('#')?.trim();
+        let (label, value) = comment.split_once(':')?;
+        (label.trim().eq_ignore_ascii_case("recipient") && recipient_plugin(value.trim()).is_some())
+            .then(|| value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_names() {
+        assert_eq!(
+            identity_plugin("AGE-PLUGIN-YUBIKEY-1QQQQQ").as_deref(),
+            Some("yubikey")
+        );
+        assert_eq!(recipient_plugin("age1yubikey1qqqqq").as_deref(), Some("yubikey"));
+        assert_eq!(recipient_plugin("age1tpm1qqqqq").as_deref(), Some("tpm"));
+        // Plain x25519 keys aren't plugin keys
+        assert_eq!(recipient_plugin("age1qqqqqqqq"), None);
+        assert_eq!(identity_plugin("AGE-SECRET-KEY-1QQQQ"), None);
+        assert_eq!(identity_plugin("AGE-PLUGIN-../../BIN-1QQ"), None);
+    }
+
+    #[test]
+    fn identity_file_parsing() {
+        let contents = "#       Serial: 1, Slot: 1\n\
+                        #    Recipient: age1yubikey1qqqqq\n\
+                        AGE-PLUGIN-YUBIKEY-1QQQQQ\n";
+        assert_eq!(identity_line(contents), Some("AGE-PLUGIN-YUBIKEY-1QQQQQ"));
+        assert_eq!(recipient_comment(contents).as_deref(), Some("age1yubikey1qqqqq"));
+    }
+}
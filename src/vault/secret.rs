@@ -1,5 +1,8 @@
+use base64::Engine;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::error::{AuthyError, Result};
 use crate::types::*;
-use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// A single secret entry in the vault.
 #[derive(Debug, Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
@@ -9,6 +12,22 @@ pub struct SecretEntry {
     /// Metadata about this secret.
     #[zeroize(skip)]
     pub metadata: SecretMetadata,
+    /// How `value` is encoded. Binary secrets are kept as base64 text, which
+    /// is also what `run` and `env` inject.
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub encoding: ValueEncoding,
+}
+
+/// Encoding of a secret's stored value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueEncoding {
+    /// UTF-8 text, stored as-is.
+    #[default]
+    Text,
+    /// Arbitrary bytes (certificates, keystores), stored as standard base64.
+    Base64,
 }
 
 /// Metadata associated with a secret (non-sensitive).
@@ -21,6 +40,76 @@ pub struct SecretMetadata {
     pub tags: Vec<String>,
     #[serde(default)]
     pub description: Option<String>,
+    /// When the secret should have been rotated by. Expired secrets are
+    /// refused by `authy get` unless `--allow-expired` is given.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Where the current value came from. `None` for values written before
+    /// provenance was recorded.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+    /// Removing a protected secret, or rekeying a vault that holds one,
+    /// needs an approver's keyfile (`--approve-with`).
+    #[serde(default)]
+    pub protected: bool,
+    /// A planted honeytoken: any read of the value raises an alert
+    /// ([`crate::hooks::trip_canaries`]).
+    #[serde(default)]
+    pub canary: bool,
+}
+
+/// How a secret's current value got into the vault. Recorded whenever the
+/// value is written, so audits can answer "where did this credential come
+/// from" without digging through the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// `store`, `import`, `generate` or `rotate`.
+    pub method: String,
+    /// Import adapter (`dotenv`, `1password`, `vault`, ...), the policy a
+    /// value was generated under, or the client that wrote it (`api`, `tui`).
+    #[serde(default)]
+    pub source: Option<String>,
+    /// File or path the value was read from (an imported `.env`, a Vault KV
+    /// path, `store --file`).
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Who wrote the value.
+    pub actor: String,
+}
+
+impl Provenance {
+    pub fn new(method: &str, actor: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            source: None,
+            location: None,
+            actor: actor.to_string(),
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+}
+
+impl std::fmt::Display for Provenance {
+    /// e.g. `import from dotenv (.env.prod) by master`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.method)?;
+        if let Some(ref source) = self.source {
+            write!(f, " from {}", source)?;
+        }
+        if let Some(ref location) = self.location {
+            write!(f, " ({})", location)?;
+        }
+        write!(f, " by {}", self.actor)
+    }
 }
 
 impl Default for SecretMetadata {
@@ -38,12 +127,24 @@ impl SecretMetadata {
             version: 1,
             tags: Vec::new(),
             description: None,
+            expires_at: None,
+            provenance: None,
+            protected: false,
+            canary: false,
         }
     }
 
+    /// Record a new value. The old value's expiry no longer applies, so
+    /// `expires_at` is cleared.
     pub fn bump_version(&mut self) {
         self.version += 1;
         self.modified_at = Utc::now();
+        self.expires_at = None;
+    }
+
+    /// True once `expires_at` has passed.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
     }
 }
 
@@ -52,6 +153,36 @@ impl SecretEntry {
         Self {
             value,
             metadata: SecretMetadata::new(),
+            encoding: ValueEncoding::Text,
+        }
+    }
+
+    /// A binary secret holding `bytes`.
+    pub fn new_binary(bytes: &[u8]) -> Self {
+        Self {
+            value: base64::engine::general_purpose::STANDARD.encode(bytes),
+            metadata: SecretMetadata::new(),
+            encoding: ValueEncoding::Base64,
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.encoding == ValueEncoding::Base64
+    }
+
+    /// The raw bytes of the value: decoded for binary secrets, UTF-8 otherwise.
+    pub fn bytes(&self) -> Result<Zeroizing<Vec<u8>>> {
+        match self.encoding {
+            ValueEncoding::Text => Ok(Zeroizing::new(self.value.as_bytes().to_vec())),
+            ValueEncoding::Base64 => decode_base64(&self.value),
         }
     }
 }
+
+/// Decode a base64 binary value, as stored or as exported.
+pub fn decode_base64(value: &str) -> Result<Zeroizing<Vec<u8>>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .map(Zeroizing::new)
+        .map_err(|e| AuthyError::Other(format!("Invalid base64 secret value: {}", e)))
+}
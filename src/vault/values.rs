@@ -0,0 +1,81 @@
+//! Checks on values about to be stored, for the mistakes that are easy to
+//! make when piping into `store`: an empty variable, a template placeholder,
+//! a variable name that was never expanded, or a default like `changeme`.
+//! None of these is ever a real secret, so they are refused unless the
+//! caller says otherwise (`--allow-weak`).
+
+use crate::error::{AuthyError, Result};
+
+/// Values that are stand-ins rather than secrets, compared case-insensitively
+/// after trimming.
+pub const PLACEHOLDERS: &[&str] = &[
+    "changeme",
+    "change-me",
+    "change_me",
+    "changeit",
+    "placeholder",
+    "replaceme",
+    "replace-me",
+    "todo",
+    "tbd",
+    "xxx",
+    "null",
+    "undefined",
+    "none",
+    "your-api-key",
+    "your_api_key",
+    "<secret>",
+    "<password>",
+];
+
+/// Why `value` looks like a mistake, or `None` if it doesn't.
+pub fn weakness(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Some(if value.is_empty() {
+            "the value is empty".to_string()
+        } else {
+            "the value is only whitespace".to_string()
+        });
+    }
+    if value.contains("<authy:") {
+        return Some("the value contains an unresolved <authy:...> placeholder".to_string());
+    }
+    if is_unexpanded_variable(trimmed) {
+        return Some(format!("'{}' looks like a variable that was never expanded", trimmed));
+    }
+    let lower = trimmed.to_lowercase();
+    if PLACEHOLDERS.contains(&lower.as_str()) {
+        return Some(format!("'{}' is a placeholder, not a secret", trimmed));
+    }
+    None
+}
+
+/// Refuse a weak value for `name` (see [`weakness`]).
+pub fn check(name: &str, value: &str) -> Result<()> {
+    match weakness(value) {
+        Some(reason) => Err(AuthyError::WeakValue {
+            name: name.to_string(),
+            reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `$NAME` or `${NAME}`, as left behind by single quotes or a missing `export`.
+fn is_unexpanded_variable(value: &str) -> bool {
+    let name = match value.strip_prefix("${") {
+        Some(rest) => match rest.strip_suffix('}') {
+            Some(name) => name,
+            None => return false,
+        },
+        None => match value.strip_prefix('$') {
+            Some(name) => name,
+            None => return false,
+        },
+    };
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -43,6 +43,54 @@ fn test_api_init_twice_fails() {
     });
 }
 
+// ── authy home ──────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_authy_home() {
+    with_isolated_home(|home| {
+        let state = home.path().join("service-state");
+        std::env::set_var("AUTHY_HOME", &state);
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        std::env::remove_var("AUTHY_HOME");
+
+        assert!(state.join("vault.age").exists());
+        assert!(!home.path().join(".authy").exists());
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_vault_location() {
+    with_isolated_home(|home| {
+        let expected = home.path().join(".authy/vault.age");
+        assert_eq!(
+            authy::api::AuthyClient::vault_location().unwrap(),
+            expected.display().to_string()
+        );
+
+        let other = home.path().join("other");
+        assert_eq!(
+            authy::api::AuthyClient::vault_location_at(&other).unwrap(),
+            other.join("vault.age").display().to_string()
+        );
+        let client = authy::api::AuthyClient::with_passphrase("test-pass")
+            .unwrap()
+            .with_home(&other);
+        client.init_vault().unwrap();
+        assert!(authy::api::AuthyClient::is_initialized_at(&other));
+        assert!(!authy::api::AuthyClient::is_initialized());
+    });
+}
+
+#[test]
+fn test_api_home_not_found_error() {
+    let err = authy::error::AuthyError::HomeNotFound;
+    assert_eq!(err.code(), authy::error::ErrorCode::Error);
+    assert!(err.to_string().contains("AUTHY_HOME"));
+}
+
 // ── store / get ──────────────────────────────────────────────────────
 
 #[test]
@@ -112,6 +160,31 @@ fn test_api_store_force_overwrite() {
     });
 }
 
+#[test]
+#[serial]
+fn test_api_store_normalizes_and_validates_names() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+
+        // "café" typed as e + combining acute (NFD) is stored as NFC
+        client.store("cafe\u{301}-key", "v1", false).unwrap();
+        assert_eq!(client.get("caf\u{e9}-key").unwrap(), Some("v1".to_string()));
+        let err = client.store("caf\u{e9}-key", "v2", false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        for bad in ["", "has space", "glob*", "-flag", "a=b", &"x".repeat(257)] {
+            let err = client.store(bad, "v", false).unwrap_err();
+            assert_eq!(err.error_code(), "invalid_name", "{:?}", bad);
+        }
+
+        let err = client
+            .create_policy("bad policy", vec!["*".into()], vec![], None, false)
+            .unwrap_err();
+        assert_eq!(err.error_code(), "invalid_name");
+    });
+}
+
 // ── remove ───────────────────────────────────────────────────────────
 
 #[test]
@@ -138,6 +211,108 @@ fn test_api_remove_nonexistent_returns_false() {
     });
 }
 
+#[test]
+#[serial]
+fn test_api_store_with_expiry() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::days(90);
+        let options = authy::api::StoreOptions {
+            expires_at: Some(expires_at),
+            ..Default::default()
+        };
+        client.store_with_options("cert", "pem", &options).unwrap();
+
+        let err = client.store_with_options("cert", "pem2", &options).unwrap_err();
+        assert!(matches!(err, authy::error::AuthyError::SecretAlreadyExists(_)));
+
+        let key = authy::vault::VaultKey::Passphrase("test-pass".into());
+        let vault = authy::vault::load_vault(&key).unwrap();
+        let metadata = &vault.secrets["cert"].metadata;
+        assert_eq!(metadata.expires_at, Some(expires_at));
+        assert!(!metadata.is_expired());
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_expired_secret_is_refused() {
+    use authy::error::AuthyError;
+
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let client = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        let options = authy::api::StoreOptions {
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        client.store_with_options("old-cert", "pem", &options).unwrap();
+        client.store("fresh", "ok", false).unwrap();
+        client
+            .create_policy("all", vec!["*".into()], vec![], None, false)
+            .unwrap();
+
+        assert!(matches!(client.get("old-cert"), Err(AuthyError::SecretExpired(_))));
+        assert!(matches!(
+            client.get_first(&["old-cert", "fresh"]),
+            Err(AuthyError::SecretExpired(_))
+        ));
+        let many = client.get_many(&["old-cert", "fresh"]).unwrap();
+        assert!(matches!(many[0].1, Err(AuthyError::SecretExpired(_))));
+        assert_eq!(many[1].1.as_deref().unwrap(), "ok");
+        assert!(matches!(
+            client.build_env_map("all", true, Some('_')),
+            Err(AuthyError::SecretExpired(_))
+        ));
+
+        // Opting in reads it anyway
+        let allow = authy::api::GetOptions { allow_expired: true };
+        assert_eq!(
+            client.get_with_options("old-cert", &allow).unwrap().as_deref(),
+            Some("pem")
+        );
+
+        let denied = client
+            .audit_entries()
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.outcome == "denied" && e.detail.as_deref() == Some("expired"))
+            .count();
+        assert_eq!(denied, 4);
+    });
+}
+
+// ── backup ───────────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_backup_and_restore() {
+    with_isolated_home(|home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("db-url", "postgres://prod", false).unwrap();
+
+        let path = home.path().join("vault.backup");
+        client.backup(&path, "backup-pass").unwrap();
+
+        client.store("db-url", "changed", true).unwrap();
+
+        let err = client.restore(&path, "backup-pass", false).unwrap_err();
+        assert!(matches!(err, authy::error::AuthyError::VaultAlreadyExists(_)));
+        assert!(client.restore(&path, "wrong", true).is_err());
+
+        let other = authy::api::AuthyClient::with_passphrase("other-pass").unwrap();
+        assert!(other.restore(&path, "backup-pass", true).is_err());
+
+        let kept = client.restore(&path, "backup-pass", true).unwrap();
+        assert!(kept.iter().any(|p| p.ends_with("vault.age.pre-restore")));
+        assert_eq!(client.get("db-url").unwrap().as_deref(), Some("postgres://prod"));
+        assert!(client.verify_audit_chain().unwrap().1);
+    });
+}
+
 // ── rotate ───────────────────────────────────────────────────────────
 
 #[test]
@@ -280,6 +455,28 @@ fn test_api_from_env_no_credentials_fails() {
     });
 }
 
+#[test]
+#[serial]
+fn test_api_from_env_configured_keyfile() {
+    with_isolated_home(|home| {
+        std::env::remove_var("AUTHY_PASSPHRASE");
+        std::env::remove_var("AUTHY_KEYFILE");
+        init_keyfile_vault(home);
+
+        // init writes a config without a keyfile
+        assert!(authy::api::AuthyClient::from_env().is_err());
+
+        std::fs::write(
+            home.path().join(".authy/authy.toml"),
+            "[vault]\nauth_method = \"keyfile\"\nkeyfile = \"~/master.key\"\n",
+        )
+        .unwrap();
+        let client = authy::api::AuthyClient::from_env().unwrap();
+        client.store("from-config", "v", false).unwrap();
+        assert_eq!(client.get("from-config").unwrap(), Some("v".to_string()));
+    });
+}
+
 // ── test_policy ──────────────────────────────────────────────────────
 
 #[test]
@@ -312,6 +509,33 @@ fn test_api_test_policy_denied() {
     });
 }
 
+#[test]
+#[serial]
+fn test_api_filter_secrets() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client
+            .create_policy(
+                "app",
+                vec!["app-*".into()],
+                vec!["app-admin-*".into()],
+                None,
+                false,
+            )
+            .unwrap();
+
+        // Names are checked against the policy only; they need not exist
+        let allowed = client
+            .filter_secrets("app", &["app-db", "other", "app-admin-key", "app-api"])
+            .unwrap();
+        assert_eq!(allowed, vec!["app-db", "app-api"]);
+
+        let err = client.filter_secrets("nope", &["app-db"]).unwrap_err();
+        assert_eq!(err.error_code(), "not_found");
+    });
+}
+
 #[test]
 #[serial]
 fn test_api_test_policy_not_found() {
@@ -368,6 +592,82 @@ fn test_api_create_policy_duplicate_fails() {
 
 // ── build_env_map ───────────────────────────────────────────────
 
+#[test]
+#[serial]
+fn test_api_policy_crud() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client
+            .create_policy("base", vec!["shared-*".into()], vec![], None, false)
+            .unwrap();
+        client
+            .create_policy("app", vec!["app-*".into()], vec![], Some("App"), false)
+            .unwrap();
+
+        let names: Vec<String> = client
+            .list_policies()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["app", "base"]);
+
+        let rev = client
+            .update_policy(
+                "app",
+                &authy::api::PolicyUpdate {
+                    deny: Some(vec!["app-admin*".into()]),
+                    extends: Some(vec!["base".into()]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(rev, 1);
+        let stored = client.get_policy("app", false).unwrap().unwrap();
+        assert_eq!(stored.allow, vec!["app-*"]);
+        assert_eq!(stored.deny, vec!["app-admin*"]);
+        assert_eq!(stored.description.as_deref(), Some("App"));
+        assert!(client.test_policy("app", "shared-db").unwrap());
+        assert!(!client.test_policy("app", "app-admin-key").unwrap());
+        assert!(client.get_policy("missing", true).unwrap().is_none());
+
+        // A policy that others extend can't be removed
+        assert!(client.delete_policy("base").is_err());
+        client.delete_policy("app").unwrap();
+        client.delete_policy("base").unwrap();
+        assert!(client.list_policies().unwrap().is_empty());
+        assert!(matches!(
+            client.delete_policy("app").unwrap_err(),
+            authy::error::AuthyError::PolicyNotFound(_)
+        ));
+        assert!(matches!(
+            client
+                .update_policy("app", &authy::api::PolicyUpdate::default())
+                .unwrap_err(),
+            authy::error::AuthyError::PolicyNotFound(_)
+        ));
+
+        let ops: Vec<String> = client
+            .audit_entries()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.operation)
+            .filter(|op| op.starts_with("policy.") && op != "policy.test")
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                "policy.create",
+                "policy.create",
+                "policy.update",
+                "policy.remove",
+                "policy.remove"
+            ]
+        );
+    });
+}
+
 #[test]
 #[serial]
 fn test_api_build_env_map() {
@@ -426,6 +726,163 @@ fn test_api_build_env_map_policy_not_found() {
     });
 }
 
+#[test]
+#[serial]
+fn test_api_get_first() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("shared", "s", false).unwrap();
+
+        assert_eq!(client.get_first(&["env-specific", "shared"]).unwrap().as_deref(), Some("s"));
+        assert_eq!(client.get_first(&["missing", "also-missing"]).unwrap(), None);
+    });
+}
+
+// ── with_token ───────────────────────────────────────────────────────
+
+/// Write a fresh keyfile, init a vault with it, and return its path.
+fn init_keyfile_vault(home: &TempDir) -> String {
+    use secrecy::ExposeSecret;
+
+    let identity = age::x25519::Identity::generate();
+    let path = home.path().join("master.key");
+    std::fs::write(&path, identity.to_string().expose_secret()).unwrap();
+    let path = path.to_str().unwrap().to_string();
+    authy::api::AuthyClient::with_keyfile(&path)
+        .unwrap()
+        .init_vault()
+        .unwrap();
+    path
+}
+
+/// Add a session for `scope` directly to the vault and return its token.
+fn add_session(keyfile: &str, scope: &str, run_only: bool) -> String {
+    let (identity, pubkey) = authy::auth::read_keyfile(keyfile).unwrap();
+    let key = authy::vault::VaultKey::Keyfile {
+        identity: identity.clone(),
+        pubkey,
+    };
+    let mut v = authy::vault::load_vault(&key).unwrap();
+    let hmac_key = authy::vault::crypto::derive_key(identity.as_bytes(), b"session-hmac", 32);
+    let (token, token_hmac) = authy::session::generate_token(&hmac_key);
+    let now = chrono::Utc::now();
+    v.sessions.push(authy::session::SessionRecord {
+        id: authy::session::generate_session_id(),
+        scope: scope.to_string(),
+        token_hmac,
+        created_at: now,
+        expires_at: now + chrono::Duration::hours(1),
+        revoked: false,
+        label: None,
+        run_only,
+        env: Default::default(),
+        capabilities: authy::session::default_capabilities(),
+        sliding_ttl_secs: None,
+        max_expires_at: None,
+        revoked_at: None,
+    });
+    authy::vault::save_vault(&v, &key).unwrap();
+    token
+}
+
+#[test]
+#[serial]
+fn test_api_with_token_enforces_scope_and_writes() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master.store("other", "x", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        let token = add_session(&keyfile, "app", false);
+
+        let client = authy::api::AuthyClient::with_token(&token, &keyfile).unwrap();
+        assert_eq!(client.token_scope(), Some("app"));
+        assert_eq!(client.get("app-db").unwrap(), Some("postgres://".into()));
+        assert_eq!(client.get("other").unwrap_err().error_code(), "access_denied");
+        assert_eq!(client.list(None).unwrap(), vec!["app-db"]);
+        assert!(client.build_env_map("app", true, Some('_')).is_ok());
+        assert!(client.build_env_map("admin", true, Some('_')).is_err());
+        assert_eq!(
+            client.store("app-new", "v", false).unwrap_err().error_code(),
+            "token_read_only"
+        );
+        assert_eq!(
+            client.remove("app-db").unwrap_err().error_code(),
+            "token_read_only"
+        );
+
+        // Revocation takes effect for an existing client
+        let (identity, pubkey) = authy::auth::read_keyfile(&keyfile).unwrap();
+        let key = authy::vault::VaultKey::Keyfile { identity, pubkey };
+        let mut v = authy::vault::load_vault(&key).unwrap();
+        v.sessions[0].revoked = true;
+        authy::vault::save_vault(&v, &key).unwrap();
+        assert!(client.get("app-db").is_err());
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_count_only_policy() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master.store("app-cache", "redis://", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        let (identity, pubkey) = authy::auth::read_keyfile(&keyfile).unwrap();
+        let key = authy::vault::VaultKey::Keyfile { identity, pubkey };
+        let mut v = authy::vault::load_vault(&key).unwrap();
+        v.policies.get_mut("app").unwrap().count_only = true;
+        authy::vault::save_vault(&v, &key).unwrap();
+
+        let token = add_session(&keyfile, "app", false);
+        let client = authy::api::AuthyClient::with_token(&token, &keyfile).unwrap();
+        assert_eq!(client.count(None).unwrap(), 2);
+        assert!(client.list(None).is_err());
+        // The policy doesn't hide names from the master key
+        assert_eq!(master.list(Some("app")).unwrap().len(), 2);
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_run_only_blocks_values() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        master
+            .create_policy("ci", vec!["app-*".into()], vec![], None, true)
+            .unwrap();
+
+        // Run-only token: names are visible, values are not
+        let token = add_session(&keyfile, "app", true);
+        let client = authy::api::AuthyClient::with_token(&token, &keyfile).unwrap();
+        assert_eq!(client.get("app-db").unwrap_err().error_code(), "run_only");
+        assert_eq!(
+            client.build_env_map("app", true, None).unwrap_err().error_code(),
+            "run_only"
+        );
+        assert_eq!(client.list(None).unwrap(), vec!["app-db"]);
+
+        // A run-only policy blocks build_env_map even for the master key
+        assert_eq!(
+            master.build_env_map("ci", true, None).unwrap_err().error_code(),
+            "run_only"
+        );
+    });
+}
+
 // ── wrong passphrase ─────────────────────────────────────────────────
 
 #[test]
@@ -440,3 +897,534 @@ fn test_api_wrong_passphrase_fails() {
         assert!(wrong.get("key").is_err());
     });
 }
+
+#[test]
+fn test_policy_filter_secrets_matches_can_read() {
+    let policy = authy::policy::Policy::new(
+        "p".into(),
+        vec!["db-*".into(), "api-*".into()],
+        vec!["*-prod".into()],
+    );
+    let names: Vec<String> = (0..1500)
+        .map(|i| match i % 3 {
+            0 => format!("db-{}", i),
+            1 => format!("api-{}-prod", i),
+            _ => format!("other-{}", i),
+        })
+        .collect();
+    let refs: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+
+    let allowed = policy.filter_secrets(&refs).unwrap();
+    assert_eq!(allowed.len(), 500);
+    for name in &refs {
+        assert_eq!(allowed.contains(name), policy.can_read(name).unwrap());
+    }
+}
+
+#[test]
+fn test_policy_decide_reports_matched_rule() {
+    use authy::policy::{Policy, RuleKind, SecretOp};
+
+    let mut policy = Policy::new(
+        "p".into(),
+        vec!["db-*".into(), "api-*".into()],
+        vec!["*-prod".into(), "db-admin".into()],
+    );
+    policy.allow_write = vec!["db-cache-*".into()];
+
+    let decision = policy.decide(SecretOp::Read, "api-key").unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.rule_kind, RuleKind::Allow);
+    assert_eq!(decision.matched_rule.as_deref(), Some("api-*"));
+    assert_eq!(decision.denial_reason(), None);
+
+    // The first matching deny pattern is reported
+    let decision = policy.decide(SecretOp::Read, "db-admin").unwrap();
+    assert_eq!(decision.rule_kind, RuleKind::Deny);
+    assert_eq!(decision.denial_reason().as_deref(), Some("deny rule 'db-admin'"));
+
+    let decision = policy.decide(SecretOp::Read, "ssh-key").unwrap();
+    assert_eq!(decision.rule_kind, RuleKind::Default);
+    assert_eq!(decision.denial_reason().as_deref(), Some("no allow rule matches"));
+
+    // Per-operation patterns replace `allow` for that operation
+    let decision = policy.decide(SecretOp::Write, "db-cache-1").unwrap();
+    assert_eq!(decision.matched_rule.as_deref(), Some("db-cache-*"));
+    assert!(!policy.decide(SecretOp::Write, "db-url").unwrap().allowed);
+
+    let err = authy::error::AuthyError::AccessDenied {
+        secret: "db-admin".into(),
+        scope: "p".into(),
+        reason: Some("deny rule 'db-admin'".into()),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Access denied: secret 'db-admin' not allowed by scope 'p' (deny rule 'db-admin')"
+    );
+}
+
+// ── vault integrity ──────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_corrupted_vault_reports_backup() {
+    use authy::error::{AuthyError, ErrorCode};
+    use authy::vault::crypto;
+
+    with_isolated_home(|home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("key", "val", false).unwrap();
+
+        // Flip a byte in the sealed body and re-encrypt, so age still accepts it
+        let vault_path = home.path().join(".authy/vault.age");
+        let ciphertext = std::fs::read(&vault_path).unwrap();
+        let mut plaintext = crypto::decrypt_with_passphrase(&ciphertext, "test-pass").unwrap();
+        let last = plaintext.len() - 1;
+        plaintext[last] ^= 0xff;
+        let tampered = crypto::encrypt_with_passphrase(&plaintext, "test-pass").unwrap();
+        std::fs::write(&vault_path, tampered).unwrap();
+
+        let err = client.get("key").unwrap_err();
+        assert_eq!(err.code(), ErrorCode::VaultCorrupted);
+        match err {
+            AuthyError::VaultCorrupted { backup, .. } => {
+                assert!(backup.unwrap().ends_with("vault.age.bak"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // The backup holds the vault as it was before the last save
+        std::fs::copy(home.path().join(".authy/vault.age.bak"), &vault_path).unwrap();
+        assert_eq!(client.get("key").unwrap(), None);
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_legacy_unsealed_vault_loads() {
+    use authy::vault::{self, crypto, Vault};
+
+    with_isolated_home(|_home| {
+        let mut legacy = Vault::new();
+        legacy.secrets.insert(
+            "key".into(),
+            authy::vault::secret::SecretEntry::new("val".into()),
+        );
+        let plaintext = rmp_serde::to_vec(&legacy).unwrap();
+        std::fs::create_dir_all(vault::authy_dir().unwrap()).unwrap();
+        std::fs::write(
+            vault::vault_path().unwrap(),
+            crypto::encrypt_with_passphrase(&plaintext, "test-pass").unwrap(),
+        )
+        .unwrap();
+
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        assert_eq!(client.get("key").unwrap().as_deref(), Some("val"));
+    });
+}
+
+#[test]
+fn test_vault_schema_validation() {
+    use authy::vault::{integrity, Vault};
+
+    let mut vault = Vault::new();
+    assert!(integrity::validate(&vault).is_ok());
+
+    vault.version = integrity::SUPPORTED_VERSION + 1;
+    assert!(integrity::validate(&vault).unwrap_err().contains("unsupported vault version"));
+
+    let mut vault = Vault::new();
+    let policy = authy::policy::Policy::new("a".into(), vec!["*".into()], vec![]);
+    vault.policies.insert("b".into(), policy);
+    assert!(integrity::validate(&vault).unwrap_err().contains("named 'a'"));
+}
+
+// ── error codes ──────────────────────────────────────────────────────
+
+#[test]
+fn test_error_code_registry_roundtrip() {
+    use authy::error::ErrorCode;
+
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::from_code_str(code.as_str()), Some(code));
+        assert_eq!(code.as_str().parse::<ErrorCode>().unwrap(), code);
+    }
+    assert!(ErrorCode::from_code_str("bogus").is_none());
+}
+
+#[test]
+fn test_error_code_matches_error() {
+    use authy::error::{AuthyError, ErrorCode};
+
+    let err = AuthyError::SecretNotFound("x".into());
+    assert_eq!(err.code(), ErrorCode::NotFound);
+    assert_eq!(err.error_code(), "not_found");
+    assert_eq!(err.exit_code(), 3);
+
+    let err = AuthyError::RunOnly;
+    assert_eq!(err.code(), ErrorCode::RunOnly);
+    assert_eq!(err.exit_code(), ErrorCode::RunOnly.exit_code());
+}
+
+// ── audit ────────────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_verify_audit_chain_range() {
+    with_isolated_home(|home| {
+        let path = home.path().join("audit.log");
+        let key = authy::audit::derive_audit_key(b"test-pass");
+        for op in ["store", "get", "remove"] {
+            authy::audit::log_event(&path, op, Some("k"), "tester", "success", None, &key).unwrap();
+        }
+
+        assert_eq!(authy::audit::verify_chain_range(&path, &key, None, None).unwrap(), 3);
+        let future = chrono::Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(authy::audit::verify_chain_range(&path, &key, Some(future), None).unwrap(), 0);
+
+        // Tamper with the last entry: a range covering it fails
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replacen("\"remove\"", "\"rotate\"", 1)).unwrap();
+        assert!(authy::audit::verify_chain_range(&path, &key, None, None).is_err());
+    });
+}
+
+// ── bound home ──────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_with_home_binds_client_to_directory() {
+    with_isolated_home(|home| {
+        let first = home.path().join("first");
+        let second = home.path().join("second");
+        let a = authy::api::AuthyClient::with_passphrase("test-pass")
+            .unwrap()
+            .with_home(&first);
+        let b = authy::api::AuthyClient::with_passphrase("test-pass")
+            .unwrap()
+            .with_home(&second);
+        a.init_vault().unwrap();
+        b.init_vault().unwrap();
+        a.store("shared", "from-a", false).unwrap();
+        b.store("shared", "from-b", false).unwrap();
+
+        assert_eq!(a.get("shared").unwrap().as_deref(), Some("from-a"));
+        assert_eq!(b.get("shared").unwrap().as_deref(), Some("from-b"));
+        assert!(first.join("authy.toml").exists());
+        assert!(second.join("audit.log").exists());
+        assert!(!home.path().join(".authy").exists());
+        assert!(!authy::api::AuthyClient::is_initialized());
+    });
+}
+
+// ── batch ────────────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_batch_commits_once() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("old-url", "x", false).unwrap();
+        let before = client.audit_entries().unwrap().len();
+
+        let changes = client
+            .batch()
+            .store("db-host", "db.internal", false)
+            .store("db-pass", "first", false)
+            .rotate("db-pass", "second")
+            .remove("old-url")
+            .remove("never-existed")
+            .commit()
+            .unwrap();
+
+        let ops: Vec<(&str, &str, Option<u32>)> = changes
+            .iter()
+            .map(|c| (c.op, c.name.as_str(), c.version))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                ("store", "db-host", None),
+                ("store", "db-pass", None),
+                ("rotate", "db-pass", Some(2)),
+                ("remove", "old-url", None),
+            ]
+        );
+        assert_eq!(client.get("db-pass").unwrap().as_deref(), Some("second"));
+        assert_eq!(client.get("old-url").unwrap(), None);
+
+        let entries = client.audit_entries().unwrap();
+        let batch = &entries[before];
+        assert_eq!(batch.operation, "batch");
+        assert_eq!(
+            batch.detail.as_deref(),
+            Some("changes=4, ops=store:db-host;store:db-pass;rotate:db-pass@v2;remove:old-url")
+        );
+        assert_eq!(entries[before + 1].operation, "get");
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_batch_is_all_or_nothing() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("taken", "v1", false).unwrap();
+
+        let err = client
+            .batch()
+            .store("fresh", "v1", false)
+            .store("taken", "v2", false)
+            .commit()
+            .unwrap_err();
+        assert!(matches!(err, authy::error::AuthyError::SecretAlreadyExists(_)));
+        assert_eq!(client.get("fresh").unwrap(), None);
+        assert_eq!(client.get("taken").unwrap().as_deref(), Some("v1"));
+
+        let err = client.batch().rotate("missing", "v").commit().unwrap_err();
+        assert!(matches!(err, authy::error::AuthyError::SecretNotFound(_)));
+        assert!(client.batch().commit().unwrap().is_empty());
+    });
+}
+
+// ── metadata ─────────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_metadata_and_tags() {
+    with_isolated_home(|_home| {
+        let client = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("db-url", "postgres://", false).unwrap();
+        client.store("api-key", "sk-1", false).unwrap();
+        client.rotate("api-key", "sk-2").unwrap();
+
+        client
+            .set_tags("api-key", &["prod", " billing ", "", "prod"])
+            .unwrap();
+        let info = client.get_metadata("api-key").unwrap().unwrap();
+        assert_eq!(info.name, "api-key");
+        assert_eq!(info.version, 2);
+        assert_eq!(info.tags, vec!["prod", "billing"]);
+        assert_eq!(info.provenance.unwrap().method, "rotate");
+        assert!(client.get_metadata("missing").unwrap().is_none());
+
+        let listed = client.list_detailed(None).unwrap();
+        let names: Vec<&str> = listed.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["api-key", "db-url"]);
+        assert!(listed[1].tags.is_empty());
+
+        assert_eq!(
+            client.set_tags("missing", &["x"]).unwrap_err().error_code(),
+            "not_found"
+        );
+        let last = client.audit_entries().unwrap().pop().unwrap();
+        assert_eq!(last.operation, "list");
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_metadata_respects_token_scope() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master.store("other", "x", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+
+        // Metadata is name-level, so run-only tokens may see it
+        let token = add_session(&keyfile, "app", true);
+        let client = authy::api::AuthyClient::with_token(&token, &keyfile).unwrap();
+        let listed = client.list_detailed(None).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "app-db");
+        assert!(client.get_metadata("app-db").unwrap().is_some());
+        assert_eq!(
+            client.get_metadata("other").unwrap_err().error_code(),
+            "access_denied"
+        );
+        assert_eq!(
+            client.set_tags("app-db", &["x"]).unwrap_err().error_code(),
+            "token_read_only"
+        );
+    });
+}
+
+// ── vault cache ──────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_api_cached_client_sees_changes() {
+    with_isolated_home(|_home| {
+        let writer = authy::api::AuthyClient::with_passphrase("test-pass").unwrap();
+        writer.init_vault().unwrap();
+        writer.store("api-key", "v1", false).unwrap();
+
+        let cached = authy::api::AuthyClient::with_passphrase("test-pass")
+            .unwrap()
+            .with_cache(std::time::Duration::from_secs(300));
+        assert_eq!(cached.get("api-key").unwrap().as_deref(), Some("v1"));
+
+        // Another client rewrites the vault file
+        writer.rotate("api-key", "v2-longer").unwrap();
+        assert_eq!(cached.get("api-key").unwrap().as_deref(), Some("v2-longer"));
+
+        // The cached client's own writes
+        cached.store("db-url", "postgres://x", false).unwrap();
+        assert_eq!(cached.get("db-url").unwrap().as_deref(), Some("postgres://x"));
+        cached.batch().remove("db-url").commit().unwrap();
+        assert_eq!(cached.get("db-url").unwrap(), None);
+
+        cached.refresh();
+        assert_eq!(cached.list(None).unwrap(), vec!["api-key".to_string()]);
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_cached_client_still_checks_token() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        let token = add_session(&keyfile, "app", false);
+
+        let client = authy::api::AuthyClient::with_token(&token, &keyfile)
+            .unwrap()
+            .with_cache(std::time::Duration::from_secs(300));
+        assert_eq!(client.get("app-db").unwrap(), Some("postgres://".into()));
+
+        let (identity, pubkey) = authy::auth::read_keyfile(&keyfile).unwrap();
+        let key = authy::vault::VaultKey::Keyfile { identity, pubkey };
+        let mut v = authy::vault::load_vault(&key).unwrap();
+        v.sessions[0].revoked = true;
+        authy::vault::save_vault(&v, &key).unwrap();
+        assert!(client.get("app-db").is_err());
+    });
+}
+
+#[test]
+#[serial]
+fn test_api_session_lifecycle() {
+    with_isolated_home(|home| {
+        let keyfile = init_keyfile_vault(home);
+        let master = authy::api::AuthyClient::with_keyfile(&keyfile).unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        assert!(matches!(
+            master
+                .create_session("nope", std::time::Duration::from_secs(60), None, false)
+                .unwrap_err(),
+            authy::error::AuthyError::PolicyNotFound(_)
+        ));
+
+        let created = master
+            .create_session("app", std::time::Duration::from_secs(3600), Some("ci"), false)
+            .unwrap();
+        assert!(created.token.starts_with("authy_v1."));
+        assert_eq!(created.session.scope, "app");
+        assert_eq!(created.session.label.as_deref(), Some("ci"));
+        assert_eq!(created.session.status, authy::api::SessionStatus::Active);
+
+        // The minted token works for a token client and validates
+        let client = authy::api::AuthyClient::with_token(&created.token, &keyfile).unwrap();
+        assert_eq!(client.get("app-db").unwrap(), Some("postgres://".into()));
+        assert!(matches!(
+            client
+                .create_session("app", std::time::Duration::from_secs(60), None, false)
+                .unwrap_err(),
+            authy::error::AuthyError::TokenReadOnly
+        ));
+        let validated = master.validate_token(&created.token).unwrap();
+        assert_eq!(validated.id, created.session.id);
+        assert!(matches!(
+            master.validate_token("authy_v1.bogus").unwrap_err(),
+            authy::error::AuthyError::InvalidToken
+        ));
+
+        let sessions = master.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, created.session.id);
+        assert!(sessions[0].revoked_at.is_none());
+
+        master.revoke_session(&created.session.id).unwrap();
+        assert!(master.validate_token(&created.token).is_err());
+        assert!(client.get("app-db").is_err());
+        let sessions = master.list_sessions().unwrap();
+        assert_eq!(sessions[0].status, authy::api::SessionStatus::Revoked);
+        assert!(sessions[0].revoked_at.is_some());
+        assert!(matches!(
+            master.revoke_session("missing").unwrap_err(),
+            authy::error::AuthyError::SessionNotFound(_)
+        ));
+
+        let ops: Vec<String> = master
+            .audit_entries()
+            .unwrap()
+            .into_iter()
+            .map(|e| e.operation)
+            .filter(|op| op.starts_with("session."))
+            .collect();
+        assert_eq!(
+            ops,
+            vec![
+                "session.create",
+                "session.validate",
+                "session.revoke",
+                "session.validate"
+            ]
+        );
+    });
+}
+
+// ── async client ─────────────────────────────────────────────────────
+
+#[cfg(feature = "tokio")]
+#[test]
+#[serial]
+fn test_api_async_client() {
+    with_isolated_home(|home| {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let client = authy::api::AuthyClient::with_passphrase("test-pass")
+                .unwrap()
+                .with_home(home.path().join(".authy"));
+            let client = authy::api::AuthyClientAsync::new(client);
+            client.init_vault().await.unwrap();
+            assert!(authy::api::AuthyClientAsync::is_initialized_at(home.path().join(".authy")).await);
+
+            client.store("db-url", "postgres://localhost", false).await.unwrap();
+            let tasks: Vec<_> = ["db-url", "missing"]
+                .into_iter()
+                .map(|name| {
+                    let client = client.clone();
+                    async move { client.get(name).await }
+                })
+                .collect();
+            let mut values = Vec::new();
+            for task in tasks {
+                values.push(task.await.unwrap());
+            }
+            assert_eq!(values, vec![Some("postgres://localhost".to_string()), None]);
+
+            assert_eq!(client.rotate("db-url", "postgres://db").await.unwrap(), 2);
+            assert_eq!(client.list(None).await.unwrap(), vec!["db-url"]);
+            assert!(client.remove("db-url").await.unwrap());
+            assert_eq!(client.count(None).await.unwrap(), 0);
+            assert!(client.verify_audit_chain().await.unwrap().1);
+        });
+    });
+}
@@ -0,0 +1,122 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env("AUTHY_PASSPHRASE", "testpass");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd.current_dir(home.path());
+    cmd
+}
+
+fn setup(home: &TempDir) {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path())
+        .env_remove("AUTHY_PASSPHRASE")
+        .env_remove("AUTHY_KEYFILE")
+        .env_remove("AUTHY_TOKEN")
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .success();
+
+    for (name, val) in [("db-host", "localhost"), ("api-key", "sk-123")] {
+        authy_cmd(home)
+            .args(["store", name])
+            .write_stdin(val)
+            .assert()
+            .success();
+    }
+
+    authy_cmd(home)
+        .args(["policy", "create", "editor", "--allow", "db-*"])
+        .assert()
+        .success();
+}
+
+fn responses(stdout: &[u8]) -> Vec<serde_json::Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_agent_requires_stdio_flag() {
+    let home = TempDir::new().unwrap();
+    authy_cmd(&home)
+        .arg("agent")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--stdio"));
+}
+
+#[test]
+fn test_agent_get_list_test() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    let input = [
+        r#"{"id":1,"op":"hello"}"#,
+        r#"{"id":2,"op":"get","name":"db-host"}"#,
+        r#"{"id":3,"op":"get","name":"api-key"}"#,
+        r#"{"id":4,"op":"list"}"#,
+        r#"{"id":5,"op":"test","name":"api-key"}"#,
+        r#"not json"#,
+    ]
+    .join("\n");
+
+    let output = authy_cmd(&home)
+        .args(["agent", "--stdio", "--scope", "editor"])
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let resp = responses(&output.stdout);
+    assert_eq!(resp.len(), 6);
+    assert_eq!(resp[0]["result"]["scope"], "editor");
+    assert_eq!(resp[1]["result"]["value"], "localhost");
+    assert_eq!(resp[2]["ok"], false);
+    assert_eq!(resp[2]["error"]["code"], "access_denied");
+    assert_eq!(resp[3]["result"]["names"], serde_json::json!(["db-host"]));
+    assert_eq!(resp[4]["result"]["allowed"], false);
+    assert_eq!(resp[5]["error"]["code"], "parse_error");
+}
+
+#[test]
+fn test_agent_scope_from_project_config() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy.toml"),
+        "[authy]\nscope = \"editor\"\n",
+    )
+    .unwrap();
+
+    let output = authy_cmd(&home)
+        .args(["agent", "--stdio"])
+        .write_stdin(r#"{"id":"a","op":"list"}"#)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let resp = responses(&output.stdout);
+    assert_eq!(resp[0]["id"], "a");
+    assert_eq!(resp[0]["result"]["names"], serde_json::json!(["db-host"]));
+}
+
+#[test]
+fn test_agent_unknown_scope_fails_at_startup() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["agent", "--stdio", "--scope", "nope"])
+        .write_stdin("")
+        .assert()
+        .failure()
+        .code(3);
+}
@@ -173,3 +173,51 @@ aliases = ["claude"]
         .success()
         .stdout(predicate::str::contains("functions --erase claude"));
 }
+
+#[test]
+fn test_alias_cleanup_uses_recorded_state() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let config_path = project.path().join(".authy.toml");
+
+    fs::write(
+        &config_path,
+        "[authy]\nscope = \"test\"\naliases = [\"claude\", \"aider\"]\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .current_dir(project.path())
+        .args(["alias", "--from-project"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["alias", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("scope test"))
+        .stdout(predicate::str::contains("claude, aider"));
+
+    // Rename a tool after the aliases were generated
+    fs::write(
+        &config_path,
+        "[authy]\nscope = \"test\"\naliases = [\"claude-code\"]\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["alias", "--cleanup"])
+        .env("AUTHY_PROJECT_DIR", project.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unalias claude 2>/dev/null"))
+        .stdout(predicate::str::contains("unalias aider 2>/dev/null"))
+        .stdout(predicate::str::contains("claude-code").not());
+
+    authy_cmd(&home)
+        .args(["alias", "--list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No aliases are active"));
+}
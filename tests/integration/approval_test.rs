@@ -0,0 +1,174 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd
+}
+
+/// A keyfile vault holding `db-url` and a `deploy` policy, with a separate
+/// approver keyfile registered. Returns (master keyfile, approver keyfile).
+fn setup(home: &TempDir) -> (String, String) {
+    let keyfile = home.path().join("master.key").to_str().unwrap().to_string();
+    authy_cmd(home)
+        .args(["init", "--generate-keyfile", &keyfile])
+        .assert()
+        .success();
+
+    // Any other age keyfile can approve; borrow one from a throwaway vault
+    let other = TempDir::new().unwrap();
+    let approver = home.path().join("approver.key").to_str().unwrap().to_string();
+    authy_cmd(&other)
+        .args(["init", "--generate-keyfile", &approver])
+        .assert()
+        .success();
+
+    authy_cmd(home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["store", "db-url"])
+        .write_stdin("postgres://prod")
+        .assert()
+        .success();
+    authy_cmd(home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["policy", "create", "deploy", "--allow", "*"])
+        .assert()
+        .success();
+    authy_cmd(home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["approver", "add", &format!("{}.pub", approver)])
+        .assert()
+        .success();
+
+    (keyfile, approver)
+}
+
+#[test]
+fn test_protected_secret_needs_approval_to_remove() {
+    let home = TempDir::new().unwrap();
+    let (keyfile, approver) = setup(&home);
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["protect", "db-url"])
+        .assert()
+        .success();
+
+    // Overwriting keeps the flag
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["store", "db-url", "--force"])
+        .write_stdin("postgres://prod2")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["remove", "db-url"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("Approval required"));
+
+    // The master keyfile can't approve its own change
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["remove", "db-url", "--approve-with", &keyfile])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("second credential"));
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["remove", "db-url", "--approve-with", &approver])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("approve"))
+        .stdout(predicate::str::contains("denied"));
+}
+
+#[test]
+fn test_protected_policy_and_rekey_need_approval() {
+    let home = TempDir::new().unwrap();
+    let (keyfile, approver) = setup(&home);
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["protect", "--policy", "deploy"])
+        .assert()
+        .success();
+
+    let new_keyfile = home.path().join("new.key").to_str().unwrap().to_string();
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["rekey", "--generate-keyfile", &new_keyfile])
+        .assert()
+        .code(4);
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["rekey", "--generate-keyfile", &new_keyfile, "--approve-with", &approver])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &new_keyfile)
+        .args(["policy", "remove", "deploy"])
+        .assert()
+        .code(4);
+
+    // Lifting the protection needs the approver too
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &new_keyfile)
+        .args(["protect", "--policy", "deploy", "--off"])
+        .assert()
+        .code(4);
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &new_keyfile)
+        .args(["policy", "remove", "deploy", "--approve-with", &approver])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_approver_management() {
+    let home = TempDir::new().unwrap();
+    let (keyfile, approver) = setup(&home);
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["approver", "add", &keyfile])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("own keyfile"));
+
+    let recipient = std::fs::read_to_string(format!("{}.pub", approver)).unwrap();
+    let recipient = recipient.trim();
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["approver", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(recipient));
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["protect", "db-url"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &keyfile)
+        .args(["approver", "remove", recipient, "--approve-with", &approver])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("last approver"));
+}
@@ -91,3 +91,601 @@ fn test_audit_tamper_detection() {
             .or(predicate::str::contains("INTEGRITY"))
             .or(predicate::str::contains("violation")));
 }
+
+#[test]
+fn test_audit_show_relative() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--relative"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s ago"));
+}
+
+#[test]
+fn test_audit_show_fixed_offset() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--tz", "+05:30"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+05:30"));
+}
+
+#[test]
+fn test_audit_show_invalid_tz() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--tz", "Mars/Olympus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --tz"));
+}
+
+#[test]
+fn test_audit_auth_failures_logged() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .env("AUTHY_PASSPHRASE", "wrong")
+        .assert()
+        .failure();
+
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .env("AUTHY_TOKEN", "authy_v1.bogus")
+        .assert()
+        .failure();
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--auth"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auth.failure"))
+        .stdout(predicate::str::contains("passphrase(pid="))
+        .stdout(predicate::str::contains("code=decryption_error"))
+        .stdout(predicate::str::contains("token(pid="));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let key = home.path().join(".authy/auth-events.key");
+        let mode = std::fs::metadata(key).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--security", "--outcome", "failure"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 entries shown of 2 total"));
+
+    authy_cmd(&home)
+        .args(["audit", "verify", "--auth"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 entries"));
+
+    // Failures never reach the master-keyed audit log
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auth.failure").not());
+}
+
+#[test]
+fn test_audit_auth_show_empty() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--auth"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No audit log entries"));
+}
+
+#[test]
+fn test_audit_hashed_secret_names() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nsecret_names = \"hash\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["store", "db-password"])
+        .write_stdin("hunter2")
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["audit", "show", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let last = json["entries"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last["operation"], "store");
+    assert!(last["secret"].as_str().unwrap().starts_with("sha256:"));
+
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-password").not());
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--resolve-names"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-password"));
+
+    // Hashed entries still verify
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_audit_omitted_secret_names() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nsecret_names = \"omit\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["store", "db-password"])
+        .write_stdin("hunter2")
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["audit", "show", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let last = json["entries"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(last["operation"], "store");
+    assert!(last["secret"].is_null());
+}
+
+#[test]
+fn test_audit_blake3_chain() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nchain = \"blake3\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["store", "db-password"])
+        .write_stdin("hunter2")
+        .assert()
+        .success();
+
+    // Earlier entries keep HMAC-SHA256; new ones are tagged blake3
+    let log = std::fs::read_to_string(home.path().join(".authy/audit.log")).unwrap();
+    let lines: Vec<serde_json::Value> = log
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert!(lines.first().unwrap().get("chain_alg").is_none());
+    assert_eq!(lines.last().unwrap()["chain_alg"], "blake3");
+
+    // Mixed chain verifies
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("verified"));
+
+    // Relabelling a blake3 entry as HMAC breaks the chain
+    let tampered = log.replace(",\"chain_alg\":\"blake3\"", "");
+    std::fs::write(home.path().join(".authy/audit.log"), tampered).unwrap();
+    authy_cmd(&home).args(["audit", "verify"]).assert().failure();
+}
+
+#[test]
+fn test_audit_torn_tail_is_quarantined() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    // Simulate a crash partway through appending an entry
+    let log_path = home.path().join(".authy/audit.log");
+    let mut log = std::fs::read_to_string(&log_path).unwrap();
+    log.push_str("{\"timestamp\":\"2026-01-01T00:00:00Z\",\"operat");
+    std::fs::write(&log_path, log).unwrap();
+
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("store"));
+
+    let quarantined =
+        std::fs::read_to_string(home.path().join(".authy/audit.log.torn")).unwrap();
+    assert!(quarantined.contains("\"operat"));
+
+    // New entries chain onto the last complete one
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("verified"));
+}
+
+#[test]
+fn test_audit_fsync_always() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nfsync = \"always\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_audit_level_writes() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["policy", "create", "narrow", "--allow", "other-*"])
+        .assert()
+        .success();
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nlevel = \"writes\"\n",
+    )
+    .unwrap();
+
+    let operations = |home: &TempDir| -> Vec<(String, String)> {
+        let output = authy_cmd(home)
+            .args(["audit", "show", "--json"])
+            .output()
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["operation"].as_str().unwrap().to_string(),
+                    e["outcome"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    let before = operations(&home).len();
+
+    // Successful reads and listings are dropped; mutations and denials are kept
+    authy_cmd(&home).args(["get", "my-secret"]).assert().success();
+    authy_cmd(&home).args(["list"]).assert().success();
+    authy_cmd(&home)
+        .args(["get", "my-secret", "--scope", "narrow"])
+        .assert()
+        .failure();
+    authy_cmd(&home)
+        .args(["store", "new-secret"])
+        .write_stdin("v")
+        .assert()
+        .success();
+
+    let logged = operations(&home);
+    assert_eq!(
+        logged[before..].to_vec(),
+        vec![
+            ("get".to_string(), "denied".to_string()),
+            ("store".to_string(), "success".to_string()),
+        ]
+    );
+    authy_cmd(&home).args(["audit", "verify"]).assert().success();
+}
+
+#[test]
+fn test_audit_verify_offline() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    // Escrowed copies, checked from a machine with no authy setup
+    let escrow = TempDir::new().unwrap();
+    let log = escrow.path().join("audit.log");
+    let snapshot = escrow.path().join("vault.age");
+    let material = escrow.path().join("material.txt");
+    std::fs::copy(home.path().join(".authy/audit.log"), &log).unwrap();
+    std::fs::copy(home.path().join(".authy/vault.age"), &snapshot).unwrap();
+    std::fs::write(&material, "testpass\n").unwrap();
+
+    let empty = TempDir::new().unwrap();
+    let verify = |material: &std::path::Path| {
+        let mut cmd = Command::cargo_bin("authy").unwrap();
+        cmd.env("HOME", empty.path())
+            .env_remove("AUTHY_PASSPHRASE")
+            .env_remove("AUTHY_KEYFILE")
+            .env_remove("AUTHY_TOKEN")
+            .args(["audit", "verify", "--offline", "--key-material"])
+            .arg(material)
+            .arg("--log")
+            .arg(&log)
+            .arg("--vault")
+            .arg(&snapshot);
+        cmd
+    };
+
+    verify(&material)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("entries, chain intact"))
+        .stdout(predicate::str::contains("1 secrets, 0 policies"));
+    assert!(!empty.path().join(".authy").exists());
+
+    // Tampering with the log copy breaks the chain
+    let tampered = std::fs::read_to_string(&log).unwrap().replace("my-secret", "my-secreT");
+    std::fs::write(&log, tampered).unwrap();
+    verify(&material)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("INTEGRITY FAILURE"));
+
+    std::fs::copy(home.path().join(".authy/audit.log"), &log).unwrap();
+    std::fs::write(&material, "wrong\n").unwrap();
+    verify(&material).assert().failure();
+
+    authy_cmd(&home)
+        .args(["audit", "verify", "--offline", "--key-material", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--log and/or --vault"));
+}
+
+#[test]
+fn test_audit_syslog_sink() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    let receiver = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(std::time::Duration::from_secs(10)))
+        .unwrap();
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[audit]\n[[audit.sinks]]\ntype = \"syslog\"\naddress = \"udp://{}\"\n",
+            receiver.local_addr().unwrap()
+        ),
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .assert()
+        .success();
+
+    let mut buf = [0u8; 4096];
+    let len = receiver.recv(&mut buf).unwrap();
+    let message = String::from_utf8_lossy(&buf[..len]);
+    assert!(message.starts_with("<86>1 "), "{}", message);
+    assert!(message.contains(r#""operation":"get""#));
+    assert!(message.contains(r#""secret":"my-secret""#));
+
+    // The local chain is unaffected by forwarding
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_audit_rotation() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nrotate_bytes = 1\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .assert()
+        .success();
+
+    let archives: Vec<_> = std::fs::read_dir(home.path().join(".authy"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("audit-") && name.ends_with(".log.zst"))
+        .collect();
+    assert_eq!(archives.len(), 1, "{:?}", archives);
+
+    // Archived entries are still shown and verified
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("init"))
+        .stdout(predicate::str::contains("audit.rotate"));
+    authy_cmd(&home)
+        .args(["audit", "verify"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("chain intact"));
+}
+
+#[test]
+fn test_audit_show_filters() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["get", "my-secret"])
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["audit", "show", "--operation", "GET", "--format", "jsonl"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|e| e["operation"] == "get"));
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--secret", "my-*", "--count", "1", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "timestamp,operation,secret,actor,outcome,detail,chain_hmac\n",
+        ))
+        .stdout(predicate::str::contains(",get,my-secret,master(passphrase),success,"))
+        .stdout(predicate::str::contains("init").not())
+        .stdout(predicate::str::contains("store").not());
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--actor", "token", "--since", "1h"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No audit log entries match."));
+
+    authy_cmd(&home)
+        .args(["audit", "show", "--until", "last tuesday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid time"));
+}
+
+#[test]
+fn test_audit_stats() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["policy", "create", "narrow", "--allow", "other-*"])
+        .assert()
+        .success();
+    for _ in 0..3 {
+        authy_cmd(&home)
+            .args(["get", "my-secret", "--scope", "narrow"])
+            .assert()
+            .failure();
+    }
+
+    let output = authy_cmd(&home)
+        .args(["audit", "stats", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["outcomes"]["denied"], 3);
+    assert_eq!(json["secrets"][0]["secret"], "my-secret");
+    assert_eq!(json["secrets"][0]["reads"], 4);
+    assert_eq!(json["secrets"][0]["denied"], 3);
+    let anomalies = json["anomalies"].as_array().unwrap();
+    assert_eq!(anomalies.len(), 1);
+    assert_eq!(anomalies[0]["kind"], "repeated_denials");
+    assert_eq!(anomalies[0]["count"], 3);
+
+    authy_cmd(&home)
+        .args(["audit", "stats", "--denials", "4"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("my-secret: 4 reads, 1 other, 3 denied"))
+        .stdout(predicate::str::contains("Anomalies (0):"));
+
+    authy_cmd(&home)
+        .args(["audit", "stats", "--since", "2000-01-01", "--until", "2000-01-02"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No audit log entries."));
+}
+
+#[test]
+fn test_audit_signed_checkpoints() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    // A log from before checkpoints were enabled has none to check
+    authy_cmd(&home)
+        .args(["audit", "verify", "--checkpoints"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no [audit] checkpoint_key"));
+
+    let keys = TempDir::new().unwrap();
+    let key_path = keys.path().join("checkpoint.key");
+    authy_cmd(&home)
+        .args(["audit", "checkpoint-key"])
+        .arg(&key_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("authy-checkpoint-pub-"));
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[audit]\ncheckpoint_key = \"{}\"\ncheckpoint_every = 2\n",
+            key_path.display()
+        ),
+    )
+    .unwrap();
+
+    for _ in 0..3 {
+        authy_cmd(&home).args(["get", "my-secret"]).assert().success();
+    }
+    let output = authy_cmd(&home)
+        .args(["audit", "show", "--operation", "audit.checkpoint", "--format", "jsonl"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+
+    authy_cmd(&home)
+        .args(["audit", "verify", "--checkpoints"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("chain intact"))
+        .stdout(predicate::str::contains("2 signed checkpoints"));
+
+    let other = keys.path().join("other.key");
+    authy_cmd(&home)
+        .args(["audit", "checkpoint-key"])
+        .arg(&other)
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["audit", "verify", "--checkpoints", "--checkpoint-pubkey"])
+        .arg(keys.path().join("other.key.pub"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("signed with a different key"));
+}
@@ -0,0 +1,148 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd.env("AUTHY_BACKUP_PASSPHRASE", "backup-pass");
+    cmd
+}
+
+/// Keyfile vault with one secret.
+fn setup(home: &TempDir, keyfile: &str) {
+    authy_cmd(home)
+        .args(["init", "--generate-keyfile", keyfile])
+        .assert()
+        .success();
+    authy_cmd(home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["store", "db-url"])
+        .write_stdin("postgres://prod")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_backup_restore_to_new_home() {
+    let keys = TempDir::new().unwrap();
+    let keyfile = keys.path().join("master.key");
+    let keyfile = keyfile.to_str().unwrap();
+    let source = TempDir::new().unwrap();
+    setup(&source, keyfile);
+
+    let backup = keys.path().join("vault.backup");
+    let backup = backup.to_str().unwrap();
+    authy_cmd(&source)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["backup", "create", backup])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("and config"));
+
+    // Wrong backup passphrase
+    let target = TempDir::new().unwrap();
+    authy_cmd(&target)
+        .env("AUTHY_KEYFILE", keyfile)
+        .env("AUTHY_BACKUP_PASSPHRASE", "wrong")
+        .args(["backup", "restore", backup])
+        .assert()
+        .failure();
+    assert!(!target.path().join(".authy/vault.age").exists());
+
+    // Credentials that can't open the backed-up vault
+    let other = TempDir::new().unwrap();
+    authy_cmd(&other)
+        .env("AUTHY_PASSPHRASE", "not-the-key")
+        .args(["backup", "restore", backup])
+        .assert()
+        .failure();
+    assert!(!other.path().join(".authy/vault.age").exists());
+
+    let output = authy_cmd(&target)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["backup", "restore", backup, "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["config"], true);
+    assert_eq!(json["audit_entries"], 2);
+    assert!(json.get("kept").is_none());
+
+    authy_cmd(&target)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "db-url"])
+        .assert()
+        .success()
+        .stdout("postgres://prod");
+
+    // The restored audit chain continues unbroken, restore included
+    authy_cmd(&target)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["audit", "verify"])
+        .assert()
+        .success();
+    authy_cmd(&target)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["audit", "show", "--columns", "operation"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("restore"));
+}
+
+#[test]
+fn test_restore_conflict_requires_force() {
+    let keys = TempDir::new().unwrap();
+    let keyfile = keys.path().join("master.key");
+    let keyfile = keyfile.to_str().unwrap();
+    let home = TempDir::new().unwrap();
+    setup(&home, keyfile);
+
+    let backup = keys.path().join("vault.backup");
+    let backup = backup.to_str().unwrap();
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["backup", "create", backup])
+        .assert()
+        .success();
+
+    // Changed after the backup
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["store", "added-later"])
+        .write_stdin("x")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["backup", "restore", backup])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "added-later"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["backup", "restore", backup, "--force"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("vault.age.pre-restore"));
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "added-later"])
+        .assert()
+        .failure();
+    assert!(home.path().join(".authy/audit.log.pre-restore").exists());
+    assert!(home.path().join(".authy/authy.toml.pre-restore").exists());
+}
@@ -87,3 +87,72 @@ fn test_config_show() {
         .success()
         .stdout(predicate::str::contains("auth_method"));
 }
+
+#[test]
+fn test_config_set() {
+    let home = TempDir::new().unwrap();
+    let config = home.path().join(".authy/authy.toml");
+
+    authy_cmd(&home)
+        .args(["config", "set", "audit.level", "writes"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["config", "set", "limits.max_secrets", "50"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("level = \"writes\""))
+        .stdout(predicate::str::contains("max_secrets = 50"));
+
+    // Invalid values and unknown sections leave the file alone
+    let before = std::fs::read_to_string(&config).unwrap();
+    authy_cmd(&home)
+        .args(["config", "set", "audit.level", "loud"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid config"));
+    authy_cmd(&home)
+        .args(["config", "set", "audti.level", "reads"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown config section [audti]"));
+    authy_cmd(&home)
+        .args(["config", "set", "keyring", "true"])
+        .assert()
+        .failure();
+    assert_eq!(std::fs::read_to_string(&config).unwrap(), before);
+}
+
+#[test]
+fn test_config_alternate_path_and_env_overrides() {
+    let home = TempDir::new().unwrap();
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .success();
+
+    let alt = home.path().join("alt.toml");
+    std::fs::write(&alt, "[limits]\nmax_secrets = 7\n").unwrap();
+
+    authy_cmd(&home)
+        .env("AUTHY_CONFIG", &alt)
+        .env("AUTHY__VAULT__AUTH_METHOD", "keyfile")
+        .env("AUTHY__AUDIT__FSYNC_BATCH", "8")
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("max_secrets = 7"))
+        .stdout(predicate::str::contains("auth_method = \"keyfile\""))
+        .stdout(predicate::str::contains("fsync_batch = 8"));
+
+    authy_cmd(&home)
+        .env("AUTHY__VALT__AUTH_METHOD", "keyfile")
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown config section [valt]"));
+}
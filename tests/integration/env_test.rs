@@ -188,3 +188,31 @@ fn test_env_with_token_auth() {
         .success()
         .stdout(predicate::str::contains("my-secret"));
 }
+
+#[test]
+fn test_env_json_nested_format() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    let output = authy_cmd(&home)
+        .args(["env", "--scope", "agent", "--format", "json-nested"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["db"]["host"], "localhost");
+    assert_eq!(json["db"]["port"], "5432");
+    assert_eq!(json["api"]["key"], "sk-test");
+
+    // A value that is also a parent can't be represented
+    authy_cmd(&home)
+        .args(["store", "db"])
+        .write_stdin("x")
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["env", "--scope", "agent", "--format", "json-nested"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot nest"));
+}
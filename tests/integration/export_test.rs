@@ -216,3 +216,351 @@ fn test_import_export_roundtrip() {
         .success()
         .stdout("sk-123");
 }
+
+#[test]
+fn test_export_with_match_filter() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["export", "--format", "env", "--match", "db-*"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-host=localhost"))
+        .stdout(predicate::str::contains("api-key").not());
+}
+
+#[test]
+fn test_export_match_combines_with_scope() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "db-only", "--allow", "db-*"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["export", "--format", "env", "--scope", "db-only", "--match", "api-*"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_export_with_tag_filter_excludes_untagged() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    let output = authy_cmd(&home)
+        .args(["export", "--format", "json", "--tag", "prod"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_export_compose_writes_secret_files() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    let dir = home.path().join("secrets");
+
+    let output = authy_cmd(&home)
+        .args([
+            "export", "--format", "compose",
+            "--uppercase", "--replace-dash", "_",
+            "--secrets-dir", dir.to_str().unwrap(),
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let yaml: serde_yaml::Value = serde_yaml::from_slice(&output.stdout).unwrap();
+    let file = yaml["secrets"]["db-host"]["file"].as_str().unwrap();
+    assert_eq!(std::fs::read_to_string(file).unwrap(), "localhost");
+
+    let env_file = std::fs::read_to_string(dir.join("secrets.env")).unwrap();
+    assert!(env_file.contains("DB_HOST_FILE=/run/secrets/db-host"));
+    assert!(env_file.contains("API_KEY_FILE=/run/secrets/api-key"));
+    assert!(!env_file.contains("sk-123"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn test_export_compose_rejects_env_file_name() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["store", "secrets.env"])
+        .write_stdin("FOO=bar")
+        .assert()
+        .success();
+    let dir = home.path().join("secrets");
+
+    authy_cmd(&home)
+        .args(["export", "--format", "compose", "--secrets-dir", dir.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'secrets.env'"));
+    assert!(!dir.join("db-host").exists());
+}
+
+#[test]
+fn test_export_compose_requires_secrets_dir() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["export", "--format", "compose"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--secrets-dir"));
+}
+
+#[test]
+fn test_export_sops_requires_recipient() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["export", "--format", "sops"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--age-recipient"));
+}
+
+#[test]
+fn test_binary_secret_round_trip() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    let payload: Vec<u8> = vec![0x30, 0x82, 0x00, 0xff, b'\n', 0x7f, 0x00, b'\n'];
+    let keystore = home.path().join("keystore.p12");
+    std::fs::write(&keystore, &payload).unwrap();
+
+    authy_cmd(&home)
+        .args(["store", "tls-keystore", "--file", keystore.to_str().unwrap()])
+        .assert()
+        .success();
+
+    // Raw bytes on stdout and via --out, base64 in JSON
+    let output = authy_cmd(&home).args(["get", "tls-keystore"]).output().unwrap();
+    assert_eq!(output.stdout, payload);
+    let out = home.path().join("restored.p12");
+    authy_cmd(&home)
+        .args(["get", "tls-keystore", "--out", out.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Wrote 8 bytes"));
+    assert_eq!(std::fs::read(&out).unwrap(), payload);
+    let output = authy_cmd(&home)
+        .args(["--json", "get", "tls-keystore"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["encoding"], "base64");
+    assert_eq!(json["value"], "MIIA/wp/AAo=");
+
+    // The env export marks the binary value so import restores it as binary
+    let exported = authy_cmd(&home)
+        .args(["export", "--format", "env"])
+        .output()
+        .unwrap()
+        .stdout;
+    let exported = String::from_utf8(exported).unwrap();
+    assert!(exported.contains("# authy:binary\ntls-keystore=MIIA/wp/AAo=\n"));
+
+    let other = TempDir::new().unwrap();
+    setup(&other);
+    let env_file = other.path().join("export.env");
+    std::fs::write(&env_file, &exported).unwrap();
+    authy_cmd(&other)
+        .args(["import", env_file.to_str().unwrap(), "--keep-names", "--yes"])
+        .assert()
+        .success();
+    let output = authy_cmd(&other).args(["get", "tls-keystore"]).output().unwrap();
+    assert_eq!(output.stdout, payload);
+}
+
+#[test]
+fn test_encrypted_bundle_round_trip() {
+    let sender = TempDir::new().unwrap();
+    setup(&sender);
+    authy_cmd(&sender)
+        .args(["policy", "create", "db", "--allow", "db-*"])
+        .assert()
+        .success();
+
+    // The receiving vault's keyfile is the bundle's identity
+    let receiver = TempDir::new().unwrap();
+    let keyfile = receiver.path().join("receiver.key");
+    let keyfile = keyfile.to_str().unwrap();
+    authy_cmd(&receiver)
+        .env_remove("AUTHY_PASSPHRASE")
+        .args(["init", "--generate-keyfile", keyfile])
+        .assert()
+        .success();
+    let recipient = std::fs::read_to_string(format!("{}.pub", keyfile)).unwrap();
+
+    let output = authy_cmd(&sender)
+        .args(["export", "--format", "encrypted", "--scope", "db"])
+        .args(["--recipient", recipient.trim()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let bundle = String::from_utf8(output.stdout).unwrap();
+    assert!(bundle.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+    assert!(!bundle.contains("localhost"));
+    let bundle_path = receiver.path().join("bundle.age");
+    std::fs::write(&bundle_path, &bundle).unwrap();
+
+    // A different identity can't open it
+    let other_key = receiver.path().join("other.key");
+    authy_cmd(&TempDir::new().unwrap())
+        .env_remove("AUTHY_PASSPHRASE")
+        .args(["init", "--generate-keyfile", other_key.to_str().unwrap()])
+        .assert()
+        .success();
+    authy_cmd(&receiver)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["import", "--from", "encrypted", bundle_path.to_str().unwrap()])
+        .args(["--identity", other_key.to_str().unwrap(), "--yes"])
+        .assert()
+        .failure();
+
+    authy_cmd(&receiver)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["import", "--from", "encrypted", bundle_path.to_str().unwrap()])
+        .args(["--keep-names", "--yes"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 secret(s) imported"));
+    authy_cmd(&receiver)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "db-host"])
+        .assert()
+        .success()
+        .stdout("localhost");
+    authy_cmd(&receiver)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "api-key"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_export_encrypted_requires_recipient() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["export", "--format", "encrypted"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--recipient"));
+}
+
+#[test]
+fn test_export_metadata_only() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "db", "--allow", "db-*", "--run-only"])
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["export", "--format", "metadata-only"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("sk-123"));
+    assert!(!stdout.contains("localhost"));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entries = json.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["name"], "api-key");
+    assert_eq!(entries[0]["version"], 1);
+    assert_eq!(entries[0]["policies"], serde_json::json!(["agent"]));
+    assert_eq!(entries[1]["policies"], serde_json::json!(["agent", "db"]));
+    assert!(entries[1].get("value").is_none());
+
+    // No values leave the vault, so run-only scopes may take an inventory
+    let output = authy_cmd(&home)
+        .args(["export", "--format", "metadata-only", "--scope", "db"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_export_order_ignores_naming_transforms() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["store", "dbz"])
+        .write_stdin("x")
+        .assert()
+        .success();
+
+    // "db-host" sorts before "dbz", but "DB_HOST" sorts after "DBZ"
+    let output = authy_cmd(&home)
+        .args([
+            "export", "--format", "env",
+            "--uppercase", "--replace-dash", "_", "--prefix", "APP_",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let plain = authy_cmd(&home)
+        .args(["export", "--format", "env"])
+        .output()
+        .unwrap();
+
+    let keys = |out: &[u8]| -> Vec<String> {
+        String::from_utf8_lossy(out)
+            .lines()
+            .map(|l| l.split('=').next().unwrap().to_lowercase().replace('_', "-"))
+            .collect()
+    };
+    assert_eq!(
+        keys(&output.stdout),
+        ["app-api-key", "app-db-host", "app-dbz"]
+    );
+    assert_eq!(keys(&plain.stdout), ["api-key", "db-host", "dbz"]);
+}
+
+#[test]
+fn test_export_rejects_colliding_names() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    authy_cmd(&home)
+        .args(["store", "db_host"])
+        .write_stdin("other")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["export", "--format", "env", "--replace-dash", "_"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'db-host' and 'db_host'"));
+
+    authy_cmd(&home)
+        .args(["export", "--format", "env"])
+        .assert()
+        .success();
+}
@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+/// A command with no `HOME`, as under systemd or in a minimal container,
+/// keeping its state in `AUTHY_HOME` instead.
+fn authy_cmd(authy_home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env_remove("HOME");
+    cmd.env("AUTHY_HOME", authy_home.path());
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd.env_remove("AUTHY_CONFIG");
+    cmd
+}
+
+#[test]
+fn test_authy_home_without_home() {
+    let state = TempDir::new().unwrap();
+    let keyfile = state.path().join("master.key");
+    let keyfile = keyfile.to_str().unwrap();
+
+    authy_cmd(&state)
+        .args(["init", "--generate-keyfile", keyfile])
+        .assert()
+        .success();
+    authy_cmd(&state)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["store", "db-url"])
+        .write_stdin("postgres://svc")
+        .assert()
+        .success();
+    authy_cmd(&state)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "db-url"])
+        .assert()
+        .success()
+        .stdout("postgres://svc");
+
+    // Files live directly in AUTHY_HOME, not in a .authy subdirectory
+    assert!(state.path().join("vault.age").exists());
+    assert!(state.path().join("audit.log").exists());
+    assert!(state.path().join("authy.toml").exists());
+}
+
+#[test]
+fn test_authy_home_overrides_home() {
+    let home = TempDir::new().unwrap();
+    let state = TempDir::new().unwrap();
+
+    authy_cmd(&state)
+        .env("HOME", home.path())
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .success();
+
+    assert!(state.path().join("vault.age").exists());
+    assert!(!home.path().join(".authy").exists());
+}
@@ -124,3 +124,48 @@ fn test_hook_bash_handles_cleanup() {
         .success()
         .stdout(predicate::str::contains("authy alias --cleanup"));
 }
+
+#[test]
+fn test_hook_cached_uses_project_info_cache() {
+    let home = TempDir::new().unwrap();
+
+    authy_cmd(&home)
+        .args(["hook", "bash", "--cached"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("project-info --cached --field dir"))
+        .stdout(predicate::str::contains("_AUTHY_LAST_PWD"))
+        .stdout(predicate::str::contains("dirname").not());
+
+    authy_cmd(&home)
+        .args(["hook", "fish", "--cached"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("project-info --cached --field keyfile"));
+
+    // Uncached hooks are unchanged
+    authy_cmd(&home)
+        .args(["hook", "zsh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("dirname"))
+        .stdout(predicate::str::contains("--cached").not())
+        .stdout(predicate::str::contains("@").and(predicate::str::contains("@GUARD@")).not());
+}
+
+#[test]
+fn test_project_info_cached() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    std::fs::write(project.path().join(".authy.toml"), "[authy]\nscope = \"p\"\n").unwrap();
+
+    for _ in 0..2 {
+        authy_cmd(&home)
+            .args(["project-info", "--cached", "--field", "scope", "--dir"])
+            .arg(project.path())
+            .assert()
+            .success()
+            .stdout("p\n");
+    }
+    assert!(home.path().join(".authy/cache/project-discovery.json").exists());
+}
@@ -135,6 +135,45 @@ fn test_import_force_overwrite() {
         .stdout("new-value");
 }
 
+#[test]
+fn test_import_over_max_secrets_changes_nothing() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+    fs::write(home.path().join(".authy/authy.toml"), "[limits]\nmax_secrets = 2\n").unwrap();
+    authy_cmd(&home)
+        .args(["store", "existing"])
+        .write_stdin("x")
+        .assert()
+        .success();
+    let vault_before = fs::read(home.path().join(".authy/vault.age")).unwrap();
+    let audit_before = fs::read(home.path().join(".authy/audit.log")).unwrap();
+
+    let env_file = home.path().join("test.env");
+    fs::write(&env_file, "FOO=1\nBAR=2\n").unwrap();
+
+    authy_cmd(&home)
+        .args(["import", env_file.to_str().unwrap(), "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("max_secrets = 2"));
+    authy_cmd(&home)
+        .args(["import", env_file.to_str().unwrap(), "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("max_secrets = 2"));
+
+    assert_eq!(fs::read(home.path().join(".authy/vault.age")).unwrap(), vault_before);
+    assert_eq!(fs::read(home.path().join(".authy/audit.log")).unwrap(), audit_before);
+
+    // One new secret still fits
+    fs::write(&env_file, "FOO=1\n").unwrap();
+    authy_cmd(&home)
+        .args(["import", env_file.to_str().unwrap(), "--yes"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 secret(s) imported"));
+}
+
 #[test]
 fn test_import_dry_run() {
     let home = TempDir::new().unwrap();
@@ -158,6 +197,69 @@ fn test_import_dry_run() {
         .stderr(predicate::str::contains("not found"));
 }
 
+#[test]
+fn test_import_dry_run_preview() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    authy_cmd(&home)
+        .args(["store", "foo"])
+        .write_stdin("old")
+        .assert()
+        .success();
+
+    let env_file = home.path().join("test.env");
+    fs::write(&env_file, "FOO=hunter2\nBAR=xyz\n").unwrap();
+
+    // Values never appear, only their lengths
+    authy_cmd(&home)
+        .args(["import", env_file.to_str().unwrap(), "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ bar  new (3 bytes)"))
+        .stdout(predicate::str::contains("= foo  skipped"))
+        .stdout(predicate::str::contains("hunter2").not());
+
+    authy_cmd(&home)
+        .args(["import", env_file.to_str().unwrap(), "--dry-run", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~ foo  overwrite (3 → 7 bytes)"))
+        .stderr(predicate::str::contains("2 secret(s) imported, 0 skipped"));
+}
+
+#[test]
+fn test_import_preview_color_control() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+    let env_file = home.path().join("test.env");
+    fs::write(&env_file, "FOO=bar\n").unwrap();
+    let file = env_file.to_str().unwrap();
+
+    // --color always beats NO_COLOR
+    authy_cmd(&home)
+        .args(["import", file, "--dry-run", "--color", "always"])
+        .env("NO_COLOR", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b["));
+
+    // NO_COLOR wins over CLICOLOR_FORCE under auto, and never is never
+    authy_cmd(&home)
+        .args(["import", file, "--dry-run"])
+        .env("NO_COLOR", "1")
+        .env("CLICOLOR_FORCE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ foo").and(predicate::str::contains("\x1b[").not()));
+    authy_cmd(&home)
+        .args(["--color", "never", "import", file, "--dry-run"])
+        .env("CLICOLOR_FORCE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
+}
+
 #[test]
 fn test_import_quoted_values() {
     let home = TempDir::new().unwrap();
@@ -452,6 +554,97 @@ fn test_import_from_1password_with_vault_and_tag() {
         .stderr(predicate::str::contains("1Password CLI (`op`) not found"));
 }
 
+#[test]
+fn test_import_from_1password_export() {
+    use std::io::Write;
+
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    let data = serde_json::json!({
+        "accounts": [{
+            "attrs": { "name": "Test" },
+            "vaults": [
+                {
+                    "attrs": { "name": "Engineering" },
+                    "items": [
+                        {
+                            "state": "active",
+                            "overview": { "title": "GITHUB_TOKEN", "tags": ["api-keys"] },
+                            "details": {
+                                "loginFields": [
+                                    { "designation": "username", "value": "me" },
+                                    { "designation": "password", "value": "ghp-123" }
+                                ]
+                            }
+                        },
+                        {
+                            "state": "active",
+                            "overview": { "title": "STRIPE_KEY", "tags": ["api-keys"] },
+                            "details": {
+                                "sections": [{
+                                    "fields": [{ "id": "credential", "value": { "concealed": "sk-456" } }]
+                                }]
+                            }
+                        },
+                        {
+                            "state": "archived",
+                            "overview": { "title": "OLD_KEY", "tags": ["api-keys"] },
+                            "details": { "password": "old" }
+                        },
+                        {
+                            "state": "active",
+                            "overview": { "title": "WIFI", "tags": [] },
+                            "details": { "password": "wifi-pass" }
+                        }
+                    ]
+                },
+                {
+                    "attrs": { "name": "Personal" },
+                    "items": [{
+                        "state": "active",
+                        "overview": { "title": "BANK", "tags": ["api-keys"] },
+                        "details": { "password": "bank-pass" }
+                    }]
+                }
+            ]
+        }]
+    });
+    let export = home.path().join("export.1pux");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&export).unwrap());
+    zip.start_file("export.data", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(data.to_string().as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    authy_cmd(&home)
+        .args(["import", "--from", "1password", export.to_str().unwrap()])
+        .args(["--op-vault", "Engineering", "--tag", "api-keys"])
+        .env("PATH", "/nonexistent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 secret(s) imported"));
+
+    for (name, value) in [("github-token", "ghp-123"), ("stripe-key", "sk-456")] {
+        authy_cmd(&home)
+            .args(["get", name])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(value));
+    }
+    for name in ["old-key", "wifi", "bank"] {
+        authy_cmd(&home).args(["get", name]).assert().failure();
+    }
+
+    let not_zip = home.path().join("not.1pux");
+    fs::write(&not_zip, "plain text").unwrap();
+    authy_cmd(&home)
+        .args(["import", "--from", "1password", not_zip.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a 1Password .1pux export"));
+}
+
 #[test]
 fn test_import_from_pass_empty_store() {
     let home = TempDir::new().unwrap();
@@ -467,3 +660,73 @@ fn test_import_from_pass_empty_store() {
         .success()
         .stderr(predicate::str::contains("No secrets found in input"));
 }
+
+#[test]
+fn test_import_from_env_with_filter() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    authy_cmd(&home)
+        .env("MYAPP_TOKEN", "tok-123")
+        .env("MYAPP_URL", "https://example.com")
+        .env("OTHER_VAR", "ignored")
+        .args(["import", "--from", "env", "--filter", "MYAPP_*"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 secret(s) imported"));
+
+    authy_cmd(&home)
+        .args(["get", "myapp-token"])
+        .assert()
+        .success()
+        .stdout("tok-123");
+
+    authy_cmd(&home)
+        .args(["get", "other-var"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_import_from_env_requires_filter_or_all() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    authy_cmd(&home)
+        .args(["import", "--from", "env", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--filter"))
+        .stderr(predicate::str::contains("--all"));
+
+    authy_cmd(&home)
+        .env("MYAPP_TOKEN", "tok-123")
+        .args(["import", "--from", "env", "--all", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("(dry run)"));
+}
+
+#[test]
+fn test_import_from_env_skips_authy_credentials() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    authy_cmd(&home)
+        .args(["import", "--from", "env", "--filter", "AUTHY_*", "--dry-run"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No secrets found"));
+}
+
+#[test]
+fn test_import_from_env_unreadable_pid() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    authy_cmd(&home)
+        .args(["import", "--from", "env", "--pid", "4294967295", "--all"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot read environment"));
+}
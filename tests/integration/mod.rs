@@ -1,21 +1,31 @@
 #![allow(deprecated)]
 
+mod agent_test;
 mod alias_test;
+mod approval_test;
 mod audit_test;
+mod backup_test;
 mod cli_test;
 mod env_test;
 mod error_test;
 mod export_test;
 mod hook_test;
+mod home_test;
 mod import_test;
 mod json_test;
 mod noninteractive_test;
+#[cfg(unix)]
+mod plugin_test;
 mod policy_test;
 mod project_config_test;
+mod push_test;
 mod rekey_test;
 mod resolve_test;
 mod run_only_test;
 mod run_test;
 mod serve_test;
 mod session_test;
+#[cfg(unix)]
+mod ssh_agent_test;
+mod storage_test;
 mod vault_test;
@@ -152,3 +152,47 @@ fn test_rotate_works_with_piped_stdin() {
         .success()
         .stdout("v2");
 }
+
+/// Runs `authy list` on a pseudo-terminal via util-linux `script`, so the
+/// passphrase prompt is shown. `None` where `script` isn't available.
+#[cfg(target_os = "linux")]
+fn list_on_tty(home: &TempDir, timeout: &str) -> Option<std::process::Output> {
+    let authy = assert_cmd::cargo::cargo_bin("authy");
+    std::process::Command::new("script")
+        .env("HOME", home.path())
+        .env("AUTHY_PROMPT_TIMEOUT", timeout)
+        .env_remove("AUTHY_NON_INTERACTIVE")
+        .env_remove("AUTHY_PASSPHRASE")
+        .env_remove("AUTHY_KEYFILE")
+        .env_remove("AUTHY_TOKEN")
+        .args(["-qec", &format!("{} list", authy.display()), "/dev/null"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_prompt_timeout_fails_and_is_audited() {
+    let home = TempDir::new().unwrap();
+    init_vault(&home);
+
+    let output = match list_on_tty(&home, "1") {
+        Some(output) => output,
+        None => return,
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout.contains("No passphrase entered within 1s"), "{}", stdout);
+
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path())
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .env_remove("AUTHY_KEYFILE")
+        .env_remove("AUTHY_TOKEN")
+        .args(["audit", "show", "--security"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("auth.prompt_timeout"))
+        .stdout(predicate::str::contains("timeout"));
+}
@@ -0,0 +1,140 @@
+use std::path::Path;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd
+}
+
+/// A stand-in for age-plugin-yubikey speaking the age plugin protocol. It
+/// "wraps" the file key by passing it through unchanged, so it is only fit
+/// for tests, and asks for a touch before unwrapping.
+const FAKE_PLUGIN: &str = r##"#!/bin/sh
+read_stanza() {
+    IFS= read -r HDR || exit 1
+    BODY=""
+    while IFS= read -r line; do
+        BODY="$BODY$line"
+        [ ${#line} -lt 64 ] && break
+    done
+    return 0
+}
+
+case "$1" in
+--generate)
+    echo "#       Serial: 1, Slot: 1"
+    echo "#    Recipient: age1yubikey1qpzry9x8"
+    echo "AGE-PLUGIN-YUBIKEY-1QPZRY9X8"
+    ;;
+--age-plugin=recipient-v1)
+    KEY=""
+    while read_stanza; do
+        case "$HDR" in
+        "-> wrap-file-key"*) KEY="$BODY" ;;
+        "-> done"*) break ;;
+        esac
+    done
+    printf -- '-> recipient-stanza 0 yubikey-fake\n%s\n' "$KEY"
+    read_stanza
+    printf -- '-> done\n\n'
+    ;;
+--age-plugin=identity-v1)
+    KEY=""
+    while read_stanza; do
+        case "$HDR" in
+        "-> recipient-stanza 0 yubikey-fake"*) KEY="$BODY" ;;
+        "-> done"*) break ;;
+        esac
+    done
+    if [ -n "$KEY" ]; then
+        printf -- '-> msg\nVG91Y2ggeW91ciBZdWJpS2V5\n'
+        read_stanza
+        printf -- '-> file-key 0\n%s\n' "$KEY"
+        read_stanza
+    fi
+    printf -- '-> done\n\n'
+    ;;
+esac
+"##;
+
+/// Install the fake plugin in its own directory, returning a `PATH` that
+/// finds it first.
+fn install_fake_plugin(dir: &Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = dir.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    let plugin = bin.join("age-plugin-yubikey");
+    std::fs::write(&plugin, FAKE_PLUGIN).unwrap();
+    std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default())
+}
+
+#[test]
+fn test_init_piv_and_plugin_decryption() {
+    let home = TempDir::new().unwrap();
+    let path = install_fake_plugin(home.path());
+    let identity = home.path().join("yubikey.id");
+    let identity = identity.to_str().unwrap();
+
+    authy_cmd(&home)
+        .env("PATH", &path)
+        .args(["init", "--piv", identity])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Enrolled yubikey identity"));
+
+    let contents = std::fs::read_to_string(identity).unwrap();
+    assert!(contents.contains("AGE-PLUGIN-YUBIKEY-1QPZRY9X8"));
+    assert_eq!(
+        std::fs::read_to_string(format!("{}.pub", identity)).unwrap(),
+        "age1yubikey1qpzry9x8"
+    );
+
+    authy_cmd(&home)
+        .env("PATH", &path)
+        .env("AUTHY_KEYFILE", identity)
+        .args(["store", "api-key"])
+        .write_stdin("sk-hardware")
+        .assert()
+        .success();
+
+    // The plugin's messages reach the user
+    authy_cmd(&home)
+        .env("PATH", &path)
+        .env("AUTHY_KEYFILE", identity)
+        .args(["get", "api-key"])
+        .assert()
+        .success()
+        .stdout("sk-hardware")
+        .stderr(predicate::str::contains("Touch your YubiKey"));
+
+    // Without the plugin binary the vault can't be opened
+    authy_cmd(&home)
+        .env("PATH", "/nonexistent")
+        .env("AUTHY_KEYFILE", identity)
+        .args(["get", "api-key"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("age-plugin-yubikey not found"));
+}
+
+#[test]
+fn test_plugin_identity_needs_recipient() {
+    let home = TempDir::new().unwrap();
+    let identity = home.path().join("tpm.id");
+    std::fs::write(&identity, "AGE-PLUGIN-TPM-1QPZRY9X8\n").unwrap();
+
+    authy_cmd(&home)
+        .args(["init", "--piv", identity.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No recipient for the plugin identity"));
+}
@@ -144,13 +144,47 @@ fn test_policy_test_command() {
         .args(["policy", "test", "--scope", "deploy", "db-host"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("ALLOWED"));
+        .stdout(predicate::str::contains("ALLOWED"))
+        .stdout(predicate::str::contains("allow rule 'db-*'"));
 
     authy_cmd(&home)
         .args(["policy", "test", "--scope", "deploy", "ssh-key"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("DENIED"));
+        .stdout(predicate::str::contains("DENIED"))
+        .stdout(predicate::str::contains("no allow rule matches"));
+}
+
+#[test]
+fn test_denial_names_matched_rule() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "deploy", "--allow", "db-*", "--deny", "*-password"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["get", "db-password", "--scope", "deploy"])
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("(deny rule '*-password')"));
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("reason=deny rule"));
+
+    let output = authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "db-password", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["allowed"], false);
+    assert_eq!(json["rule_kind"], "deny");
+    assert_eq!(json["matched_rule"], "*-password");
 }
 
 #[test]
@@ -220,3 +254,342 @@ fn test_policy_list_command() {
         .stdout(predicate::str::contains("deploy"))
         .stdout(predicate::str::contains("ci"));
 }
+
+#[test]
+fn test_policy_test_all_json() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "deploy", "--allow", "db-*", "--deny", "db-password"])
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "--all", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["scope"], "deploy");
+    assert_eq!(json["results"].as_array().unwrap().len(), 5);
+    assert_eq!(json["allowed"], 2);
+    assert_eq!(json["denied"], 3);
+}
+
+#[test]
+fn test_policy_test_stdin() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "deploy", "--allow", "db-*"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "--stdin"])
+        .write_stdin("db-new\n\nprod-key\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ALLOWED  db-new"))
+        .stdout(predicate::str::contains("DENIED   prod-key"));
+}
+
+#[test]
+fn test_policy_test_requires_target() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_generate_follows_policy_rules() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args([
+            "policy", "create", "legacy-db", "--allow", "db-*",
+            "--gen-min-length", "40",
+            "--gen-require", "upper", "digit", "symbol",
+            "--gen-forbid", "$", "#",
+        ])
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["--json", "policy", "show", "legacy-db"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["generation"]["min_length"], 40);
+    assert_eq!(json["generation"]["require"][2], "symbol");
+
+    authy_cmd(&home)
+        .args(["generate", "db-admin", "--scope", "legacy-db", "--length", "16"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("40-character"));
+
+    let value = authy_cmd(&home)
+        .args(["get", "db-admin"])
+        .output()
+        .unwrap()
+        .stdout;
+    let value = String::from_utf8(value).unwrap();
+    assert_eq!(value.len(), 40);
+    assert!(value.chars().any(|c| c.is_ascii_uppercase()));
+    assert!(value.chars().any(|c| c.is_ascii_digit()));
+    assert!(value.chars().any(|c| !c.is_ascii_alphanumeric()));
+    assert!(!value.contains('$') && !value.contains('#'));
+
+    // Existing secrets need --force; rules can be cleared
+    authy_cmd(&home)
+        .args(["generate", "db-admin"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+    authy_cmd(&home)
+        .args(["policy", "update", "legacy-db", "--clear-gen"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "show", "legacy-db"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Generation rules").not());
+}
+
+#[test]
+fn test_policy_history_and_rollback() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "deploy", "--allow", "db-*"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "update", "deploy", "--allow", "*"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("revision 1"));
+
+    let output = authy_cmd(&home)
+        .args(["--json", "policy", "history", "deploy"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["revisions"][0]["rev"], 1);
+    assert_eq!(json["revisions"][0]["allow"][0], "db-*");
+
+    authy_cmd(&home)
+        .args(["policy", "rollback", "deploy", "--to", "1"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("revision 2"));
+
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "api-key"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DENIED"));
+
+    authy_cmd(&home)
+        .args(["policy", "history", "deploy"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rev 2"))
+        .stdout(predicate::str::contains("allow: *"));
+
+    authy_cmd(&home)
+        .args(["policy", "rollback", "deploy", "--to", "9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no revision 9"));
+}
+
+#[test]
+fn test_policy_extends() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "base-readonly", "--allow", "api-*", "--deny", "db-password"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "create", "db-secrets", "--allow", "db-*"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "create", "deploy", "--extends", "base-readonly", "db-secrets"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "db-host"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ALLOWED"));
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "api-token"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ALLOWED"));
+    // An inherited deny still wins over an inherited allow
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "deploy", "db-password"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DENIED"));
+
+    let output = authy_cmd(&home)
+        .args(["--json", "policy", "show", "deploy", "--effective"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["allow"], serde_json::json!(["api-*", "db-*"]));
+    assert_eq!(json["deny"], serde_json::json!(["db-password"]));
+    assert_eq!(json["extends"], serde_json::json!(["base-readonly", "db-secrets"]));
+
+    authy_cmd(&home)
+        .args(["policy", "update", "db-secrets", "--extends", "deploy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cycle"));
+    authy_cmd(&home)
+        .args(["policy", "create", "orphan", "--extends", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Policy not found: missing"));
+    authy_cmd(&home)
+        .args(["policy", "remove", "db-secrets"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("extended by deploy"));
+}
+
+#[test]
+fn test_policy_valid_days() {
+    use chrono::Datelike;
+
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    // Every day but today, so the policy is outside its window now
+    const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+    let today = chrono::Local::now().weekday().num_days_from_monday() as usize;
+    let other_days: Vec<&str> = (0..7).filter(|d| *d != today).map(|d| DAYS[d]).collect();
+
+    authy_cmd(&home)
+        .args(["policy", "create", "office", "--allow", "db-*"])
+        .args(["--valid-days", &other_days.join(","), "--valid-hours", "00:00-24:00"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["policy", "show", "office"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Valid hours: 00:00-24:00"))
+        .stdout(predicate::str::contains("Valid days:"));
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "office", "db-host"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DENIED"))
+        .stdout(predicate::str::contains("outside valid days"));
+    authy_cmd(&home)
+        .args(["get", "db-host", "--scope", "office"])
+        .assert()
+        .failure();
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("reason=outside valid days"));
+
+    authy_cmd(&home)
+        .args(["policy", "update", "office", "--valid-days", DAYS[today]])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "test", "--scope", "office", "db-host"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ALLOWED"));
+
+    authy_cmd(&home)
+        .args(["policy", "update", "office", "--clear-window"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "show", "office"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Valid days").not());
+
+    authy_cmd(&home)
+        .args(["policy", "create", "bad", "--allow", "x", "--valid-hours", "9am"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid hours"));
+}
+
+#[test]
+fn test_policy_coverage() {
+    let home = TempDir::new().unwrap();
+    setup_vault_with_secrets(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "backend", "--allow", "db-*", "--allow", "redis-*"])
+        .args(["--deny", "db-password"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["policy", "coverage", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Allowed (2):"))
+        .stdout(predicate::str::contains("- db-password (deny db-password)"))
+        .stdout(predicate::str::contains("- ssh-key (no allow match)"))
+        .stdout(predicate::str::contains("allow redis-*"));
+
+    let output = authy_cmd(&home)
+        .args(["policy", "coverage", "backend", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["allowed"], serde_json::json!(["db-host", "db-port"]));
+    assert_eq!(json["denied"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        json["dead_rules"],
+        serde_json::json!([{"list": "allow", "pattern": "redis-*"}])
+    );
+
+    authy_cmd(&home)
+        .args(["policy", "coverage", "backend", "--strict"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("1 pattern(s) that match no secret"));
+
+    authy_cmd(&home)
+        .args(["policy", "update", "backend", "--allow", "db-*"])
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "coverage", "backend", "--strict"])
+        .assert()
+        .success();
+}
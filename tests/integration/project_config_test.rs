@@ -84,6 +84,63 @@ fn test_project_info_json() {
     assert_eq!(json["uppercase"], true);
 }
 
+#[test]
+fn test_project_info_json_effective_settings() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let state = home.path().join("state");
+
+    fs::write(
+        project.path().join(".authy.toml"),
+        "[authy]\nscope = \"app\"\nkeyfile = \"/keys/app.key\"\nprefix = \"APP_\"\n",
+    )
+    .unwrap();
+
+    // The project keyfile wins over AUTHY_PASSPHRASE, as in `run`
+    let output = authy_cmd(&home)
+        .env("AUTHY_HOME", &state)
+        .env("AUTHY_PROMPT_TIMEOUT", "45")
+        .args(["--json", "project-info", "--dir"])
+        .arg(project.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let effective = &json["effective"];
+    assert_eq!(effective["scope"], "app");
+    assert_eq!(effective["scope_source"], "project");
+    assert_eq!(effective["prefix"], "APP_");
+    assert_eq!(effective["auth"]["method"], "keyfile");
+    assert_eq!(effective["auth"]["source"], "project");
+    assert_eq!(effective["auth"]["keyfile"], "/keys/app.key");
+    assert_eq!(effective["authy_dir"], state.to_str().unwrap());
+    assert_eq!(effective["authy_dir_source"], "AUTHY_HOME");
+    assert_eq!(effective["storage"], "local");
+    assert_eq!(effective["vault"], state.join("vault.age").to_str().unwrap());
+    assert_eq!(effective["prompt_timeout_secs"], 45);
+
+    // A session token pins its own scope; env overrides reach the config
+    let output = authy_cmd(&home)
+        .env("AUTHY_TOKEN", "authy_v1.x")
+        .env("AUTHY_KEYFILE", "/keys/env.key")
+        .env("AUTHY__STORAGE__BACKEND", "http")
+        .env("AUTHY__STORAGE__URL", "https://vault.example/v.age")
+        .args(["--json", "project-info", "--dir"])
+        .arg(project.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let effective = &json["effective"];
+    assert!(effective["scope"].is_null());
+    assert_eq!(effective["scope_source"], "token");
+    assert_eq!(effective["auth"]["method"], "token");
+    assert_eq!(effective["auth"]["source"], "env");
+    assert_eq!(effective["auth"]["keyfile"], "/keys/env.key");
+    assert_eq!(effective["storage"], "http");
+    assert_eq!(effective["vault"], "https://vault.example/v.age");
+}
+
 #[test]
 fn test_project_info_keyfile_tilde_expansion() {
     let home = TempDir::new().unwrap();
@@ -160,3 +217,70 @@ fn test_project_info_discovers_parent() {
         .success()
         .stdout(predicate::str::contains("parent-scope"));
 }
+
+#[test]
+fn test_enforce_prefix_namespaces_writes() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::write(
+        project.path().join(".authy.toml"),
+        "[authy]\nscope = \"myapp\"\nenforce_prefix = \"myapp-\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .current_dir(project.path())
+        .args(["store", "db-url"])
+        .write_stdin("postgres://")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("'myapp-db-url' stored"));
+
+    // Already-prefixed names are left alone
+    authy_cmd(&home)
+        .current_dir(project.path())
+        .args(["store", "myapp-api-key"])
+        .write_stdin("k")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("'myapp-api-key' stored"));
+
+    authy_cmd(&home)
+        .current_dir(project.path())
+        .args(["import", "-"])
+        .write_stdin("REDIS_URL=redis://\n")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .current_dir(project.path())
+        .args(["policy", "create", "myapp"])
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["policy", "show", "myapp"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("myapp-*"));
+
+    authy_cmd(&home)
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("myapp-db-url"))
+        .stdout(predicate::str::contains("myapp-redis-url"));
+
+    // Outside the project, --allow is still required
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["policy", "create", "other"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--allow is required"));
+}
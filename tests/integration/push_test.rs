@@ -0,0 +1,126 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env("AUTHY_PASSPHRASE", "testpass");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd
+}
+
+fn setup(home: &TempDir) {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path())
+        .env_remove("AUTHY_PASSPHRASE")
+        .env_remove("AUTHY_KEYFILE")
+        .env_remove("AUTHY_TOKEN")
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .success();
+
+    for (name, val) in [("db-host", "localhost"), ("api-key", "sk-123")] {
+        authy_cmd(home)
+            .args(["store", name])
+            .write_stdin(val)
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn test_push_dry_run_lists_selected() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args([
+            "push", "--to", "vault", "--path", "myapp", "--match", "db-*", "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[dry-run] push db-host"))
+        .stdout(predicate::str::contains("api-key").not())
+        .stdout(predicate::str::contains("localhost").not());
+}
+
+#[test]
+fn test_push_unknown_scope_fails() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args([
+            "push", "--to", "vault", "--path", "myapp", "--scope", "nope", "--dry-run",
+        ])
+        .assert()
+        .failure()
+        .code(3);
+}
+
+#[test]
+fn test_push_requires_path() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["push", "--to", "vault"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--path"));
+}
+
+#[test]
+fn test_push_to_pass() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+    let gnupg = home.path().join("gnupg");
+    std::fs::create_dir(&gnupg).unwrap();
+    let generated = std::process::Command::new("gpg")
+        .env("GNUPGHOME", &gnupg)
+        .args(["--batch", "--passphrase", "", "--quick-gen-key", "authy-test@example.com"])
+        .args(["default", "default", "never"])
+        .output();
+    if !generated.is_ok_and(|o| o.status.success()) {
+        eprintln!("gpg unavailable; skipping");
+        return;
+    }
+
+    let store = home.path().join("store");
+    // No recipient and no .gpg-id yet
+    authy_cmd(&home)
+        .args(["push", "--to", "pass", "--path", store.to_str().unwrap()])
+        .env("GNUPGHOME", &gnupg)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--gpg-id"));
+
+    authy_cmd(&home)
+        .args(["push", "--to", "pass", "--path", store.to_str().unwrap()])
+        .args(["--gpg-id", "authy-test@example.com", "--match", "db-*"])
+        .env("GNUPGHOME", &gnupg)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 secret(s) pushed to pass"));
+
+    assert!(store.join(".gpg-id").exists());
+    assert!(!store.join("api").exists());
+    let decrypted = std::process::Command::new("gpg")
+        .env("GNUPGHOME", &gnupg)
+        .args(["--quiet", "--batch", "--decrypt"])
+        .arg(store.join("db/host.gpg"))
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(decrypted.stdout).unwrap(), "localhost\n");
+
+    // Later pushes use the store's .gpg-id
+    authy_cmd(&home)
+        .args(["push", "--to", "pass", "--path", store.to_str().unwrap()])
+        .env("GNUPGHOME", &gnupg)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 secret(s) pushed to pass"));
+    assert!(store.join("api/key.gpg").exists());
+}
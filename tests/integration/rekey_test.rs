@@ -299,3 +299,43 @@ fn test_rekey_mutual_exclusivity() {
         .failure()
         .stderr(predicate::str::contains("Only one of"));
 }
+
+#[test]
+fn test_status_reports_vault_events() {
+    let home = TempDir::new().unwrap();
+    setup_with_passphrase(&home);
+
+    let new_keyfile = home.path().join("new.key");
+    let new_keyfile_str = new_keyfile.to_str().unwrap().to_string();
+    authy_cmd(&home)
+        .env("AUTHY_PASSPHRASE", "oldpass")
+        .args(["rekey", "--generate-keyfile", &new_keyfile_str])
+        .assert()
+        .success();
+
+    // History lives in the vault, so it survives losing the audit log
+    std::fs::remove_file(home.path().join(".authy/audit.log")).unwrap();
+
+    let output = authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &new_keyfile_str)
+        .args(["status", "--events", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["secrets"], 2);
+    assert_eq!(json["policies"], 1);
+    assert_eq!(json["last_rekey"]["kind"], "rekey");
+    assert_eq!(json["last_rekey"]["detail"], "to=keyfile");
+    assert_eq!(json["last_policy_change"]["kind"], "policy.create");
+    assert_eq!(json["last_policy_change"]["detail"], "policy=svc");
+    assert!(json["last_session_change"].is_null());
+    assert_eq!(json["events"].as_array().unwrap().len(), 2);
+
+    authy_cmd(&home)
+        .env("AUTHY_KEYFILE", &new_keyfile_str)
+        .args(["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Last session change: never"));
+}
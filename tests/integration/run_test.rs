@@ -116,3 +116,37 @@ fn test_run_does_not_leak_authy_passphrase() {
         .success()
         .stdout(predicate::str::contains("AUTHY_PASSPHRASE").not());
 }
+
+#[test]
+fn test_run_summary() {
+    let home = TempDir::new().unwrap();
+    setup(&home);
+
+    authy_cmd(&home)
+        .args(["run", "--scope", "deploy", "--summary", "--", "sh", "-c", "exit 3"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "authy run: cmd=sh, exit=3, wall_ms=",
+        ))
+        .stderr(predicate::str::contains("secrets=2, names=db-host,db-port"));
+
+    authy_cmd(&home)
+        .args(["run", "--scope", "deploy", "--summary", "audit", "--", "true"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("authy run:").not());
+
+    let output = authy_cmd(&home)
+        .args(["audit", "show", "--operation", "run_exit", "--format", "jsonl"])
+        .output()
+        .unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    let detail = lines[0]["detail"].as_str().unwrap();
+    assert!(detail.starts_with("cmd=true, exit=0, wall_ms="));
+    assert!(detail.ends_with("secrets=2, names=db-host,db-port"));
+}
@@ -111,6 +111,157 @@ fn test_session_token_is_read_only() {
         .stderr(predicate::str::contains("read-only"));
 }
 
+#[test]
+fn test_policy_operation_patterns() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "update", "deploy"])
+        .args(["--allow-write", "db-new-*", "--allow-rotate", "db-password", "--allow-delete", "db-new-*"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["policy", "show", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Allow rotate patterns:\n  + db-password"));
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--can", "rotate,write"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    let with_token = |args: &[&str], stdin: &str| {
+        authy_cmd(&home)
+            .args(args)
+            .env("AUTHY_KEYFILE", &keyfile)
+            .env("AUTHY_TOKEN", &token)
+            .write_stdin(stdin.to_string())
+            .assert()
+    };
+
+    // Readable, but only db-password may be rotated
+    with_token(&["rotate", "db-password"], "rotated").success();
+    with_token(&["rotate", "db-host"], "nope")
+        .failure()
+        .stderr(predicate::str::contains("denied"));
+    with_token(&["store", "db-new-cache"], "cache").success();
+    with_token(&["store", "db-other"], "nope")
+        .failure()
+        .stderr(predicate::str::contains("denied"));
+    with_token(&["remove", "db-host"], "")
+        .failure()
+        .stderr(predicate::str::contains("denied"));
+    with_token(&["remove", "db-new-cache"], "").success();
+    with_token(&["store", "db-new-x", "--scope", "other"], "x")
+        .failure()
+        .stderr(predicate::str::contains("does not match the session token's scope"));
+
+    // The master key is checked too when it names a scope
+    authy_cmd(&home)
+        .args(["store", "db-other", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .write_stdin("nope")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("denied"));
+    authy_cmd(&home)
+        .args(["store", "db-other"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .write_stdin("fine")
+        .assert()
+        .success();
+
+    // Clearing a list falls back to the allow patterns
+    authy_cmd(&home)
+        .args(["policy", "update", "deploy", "--allow-rotate"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    with_token(&["rotate", "db-host"], "rotated").success();
+}
+
+#[test]
+fn test_session_token_capabilities() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--can", "rotate,write"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    // Rotate and store inside the scope
+    authy_cmd(&home)
+        .args(["rotate", "db-password"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .write_stdin("rotated")
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["store", "db-replica"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .write_stdin("replica")
+        .assert()
+        .success();
+
+    // Outside the scope is denied
+    authy_cmd(&home)
+        .args(["rotate", "ssh-key"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .write_stdin("nope")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("denied"));
+
+    // No read or list capability, and policies stay master-only
+    authy_cmd(&home)
+        .args(["get", "db-password"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'read' capability"));
+    authy_cmd(&home)
+        .args(["list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'list' capability"));
+    authy_cmd(&home)
+        .args(["policy", "create", "wide", "--allow", "*"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("read-only"));
+
+    authy_cmd(&home)
+        .args(["get", "db-password"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout("rotated");
+    authy_cmd(&home)
+        .args(["session", "list", "--json"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""capabilities":["write","rotate"]"#));
+}
+
 #[test]
 fn test_session_list() {
     let home = TempDir::new().unwrap();
@@ -184,3 +335,655 @@ fn test_invalid_token_rejected() {
         .assert()
         .failure();
 }
+
+#[test]
+fn test_session_label_and_revoke_by_label() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    for _ in 0..2 {
+        authy_cmd(&home)
+            .args(["session", "create", "--scope", "deploy", "--ttl", "1h"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .assert()
+            .success();
+    }
+
+    let list_output = authy_cmd(&home)
+        .args(["session", "list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let list_str = String::from_utf8(list_output.stdout).unwrap();
+    let ids: Vec<String> = list_str
+        .lines()
+        .map(|l| l.split_whitespace().next().unwrap().to_string())
+        .collect();
+    assert_eq!(ids.len(), 2);
+
+    // Annotate only the first session
+    authy_cmd(&home)
+        .args(["session", "label", &ids[0], "ci-runner-3"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["session", "revoke", "--label", "ci-runner-3"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 session(s)"));
+
+    let list_str = String::from_utf8(
+        authy_cmd(&home)
+            .args(["session", "list", "--columns", "id,status,label"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+    let row = |id: &str| -> Vec<String> {
+        let line = list_str.lines().find(|l| l.starts_with(id)).unwrap();
+        line.split_whitespace().map(String::from).collect()
+    };
+    assert_eq!(row(&ids[0])[1..], ["revoked", "ci-runner-3"]);
+    assert_eq!(row(&ids[1])[1], "active");
+
+    // No active sessions left with that label
+    authy_cmd(&home)
+        .args(["session", "revoke", "--label", "ci-runner-3"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No active sessions match"));
+
+    // Clearing the label
+    authy_cmd(&home)
+        .args(["session", "label", &ids[0], "--clear"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_session_refresh_and_sliding() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--ttl", "10m", "--json"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let id = created["session_id"].as_str().unwrap();
+    assert_eq!(created["sliding"], false);
+
+    authy_cmd(&home)
+        .args(["session", "refresh", id, "--ttl", "2h"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("now expires"));
+
+    let output = authy_cmd(&home)
+        .args(["session", "list", "--json"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let expires = chrono::DateTime::parse_from_rfc3339(
+        listed["sessions"][0]["expires"].as_str().unwrap(),
+    )
+    .unwrap();
+    assert!(expires > chrono::Utc::now() + chrono::Duration::minutes(90));
+
+    authy_cmd(&home)
+        .args(["session", "refresh", "nope"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+
+    // Sliding sessions get a hard limit (24h unless --max-lifetime)
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--sliding", "--json"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(created["sliding"], true);
+    assert!(created["max_expires"].is_string());
+
+    authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--ttl", "2h", "--sliding", "--max-lifetime", "1h"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("shorter than --ttl"));
+}
+
+#[test]
+fn test_session_prune() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    let create = |home: &TempDir| {
+        let output = authy_cmd(home)
+            .args(["session", "create", "--scope", "deploy", "--json"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        created["session_id"].as_str().unwrap().to_string()
+    };
+    let listed = |home: &TempDir| {
+        let output = authy_cmd(home)
+            .args(["session", "list", "--json"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap();
+        let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        listed["sessions"].as_array().unwrap().len()
+    };
+
+    let revoked = create(&home);
+    create(&home);
+    authy_cmd(&home)
+        .args(["session", "revoke", &revoked])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    // The default 30-day retention keeps a session revoked just now
+    authy_cmd(&home)
+        .args(["session", "prune"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No sessions ended more than 30d ago"));
+    assert_eq!(listed(&home), 2);
+
+    authy_cmd(&home)
+        .args(["session", "prune", "--older-than", "0s", "--dry-run"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 session(s)"));
+    assert_eq!(listed(&home), 2);
+
+    authy_cmd(&home)
+        .args(["session", "prune", "--older-than", "0s"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Pruned 1 session(s)"));
+    assert_eq!(listed(&home), 1);
+
+    // With a zero retention window, any save drops sessions as they end
+    let config = home.path().join(".authy").join("authy.toml");
+    let toml = std::fs::read_to_string(&config).unwrap();
+    std::fs::write(&config, toml.replace("retention = \"30d\"", "retention = \"0s\"")).unwrap();
+
+    let id = create(&home);
+    authy_cmd(&home)
+        .args(["session", "revoke", &id])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    assert_eq!(listed(&home), 1);
+
+    authy_cmd(&home)
+        .args(["audit", "show"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("session.prune"))
+        .stdout(predicate::str::contains("auto-prune"));
+}
+
+#[test]
+fn test_session_label_unknown_id() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    authy_cmd(&home)
+        .args(["session", "label", "nope", "x"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .failure()
+        .code(3);
+}
+
+#[test]
+fn test_session_revoke_by_scope_and_age() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "prod", "--allow", "*"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    for scope in ["deploy", "prod", "prod"] {
+        authy_cmd(&home)
+            .args(["session", "create", "--scope", scope, "--ttl", "1h"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .assert()
+            .success();
+    }
+
+    // Nothing is a week old yet
+    authy_cmd(&home)
+        .args(["session", "revoke", "--scope", "prod", "--older-than", "7d"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("No active sessions match"));
+
+    authy_cmd(&home)
+        .args(["session", "revoke", "--scope", "prod"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 session(s) revoked (scope=prod)"));
+
+    let list_str = String::from_utf8(
+        authy_cmd(&home)
+            .args(["session", "list", "--columns", "scope,status"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+    for line in list_str.lines() {
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["prod", status] => assert_eq!(status, "revoked"),
+            [_, status] => assert_eq!(status, "active"),
+            _ => panic!("unexpected row: {}", line),
+        }
+    }
+
+    // A session ID can't be combined with filters
+    authy_cmd(&home)
+        .args(["session", "revoke", "abc", "--scope", "prod"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_session_limit_counts_active_sessions() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+    let config_path = home.path().join(".authy/authy.toml");
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    std::fs::write(&config_path, config.replace("max_sessions = 1000", "max_sessions = 1")).unwrap();
+
+    let create = |home: &TempDir| {
+        authy_cmd(home)
+            .args(["session", "create", "--scope", "deploy", "--ttl", "1h"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .assert()
+    };
+
+    create(&home).success();
+    create(&home)
+        .failure()
+        .stderr(predicate::str::contains("max_sessions = 1"));
+
+    // Revoked sessions free up the slot
+    authy_cmd(&home)
+        .args(["session", "revoke-all"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    create(&home).success();
+}
+
+#[test]
+fn test_run_infers_scope_from_token() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    authy_cmd(&home)
+        .args(["policy", "create", "ops", "--allow", "*"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--ttl", "1h", "--run-only"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["run", "--", "env"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-host=localhost"))
+        .stdout(predicate::str::contains("my-ssh-key").not());
+
+    // Naming the token's own scope is fine; another scope is rejected
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["run", "--scope", "deploy", "--", "true"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["run", "--scope", "ops", "--", "env"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match the session token's scope 'deploy'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_revocation_hooks() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+    let log = home.path().join("revoked.log");
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[hooks]\non_revoke = \"echo $AUTHY_EVENT $AUTHY_SCOPES $AUTHY_SESSIONS >> {}\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        authy_cmd(&home)
+            .args(["session", "create", "--scope", "deploy", "--ttl", "1h"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .assert()
+            .success();
+    }
+    let list_str = String::from_utf8(
+        authy_cmd(&home)
+            .args(["session", "list"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+    let ids: Vec<&str> = list_str
+        .lines()
+        .filter_map(|l| l.split_whitespace().next())
+        .collect();
+    assert_eq!(ids.len(), 2);
+
+    authy_cmd(&home)
+        .args(["session", "revoke", ids[0]])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    // Removing the policy reports the session still scoped to it
+    authy_cmd(&home)
+        .args(["policy", "remove", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    let logged = std::fs::read_to_string(&log).unwrap();
+    let lines: Vec<&str> = logged.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], format!("session.revoke deploy {}", ids[0]));
+    assert_eq!(lines[1], format!("policy.remove deploy {}", ids[1]));
+
+    // A failing hook warns, but the revocation stands
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[hooks]\non_revoke = \"exit 3\"\n",
+    )
+    .unwrap();
+    authy_cmd(&home)
+        .args(["session", "revoke-all"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Revocation hook failed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_canary_reads_alert() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+    let log = home.path().join("canary.log");
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[hooks]\non_canary = \"echo $AUTHY_SECRET $AUTHY_OPERATION $AUTHY_SCOPES >> {}\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["canary", "db-password"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["canary", "--list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout("db-password\n");
+
+    // Reads of other secrets don't alert
+    authy_cmd(&home)
+        .args(["get", "db-host"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    assert!(!log.exists());
+
+    // The reader gets the value with no hint that it's a canary
+    authy_cmd(&home)
+        .args(["get", "db-password", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout("secret123")
+        .stderr("");
+    authy_cmd(&home)
+        .args(["env", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    let logged = std::fs::read_to_string(&log).unwrap();
+    let lines: Vec<&str> = logged.lines().collect();
+    assert_eq!(lines, ["db-password get deploy", "db-password env_export deploy"]);
+
+    // Alerts are kept even when the audit level drops successful reads
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[audit]\nlevel = \"denials-only\"\n[hooks]\non_canary = \"exit 3\"\n",
+    )
+    .unwrap();
+    authy_cmd(&home)
+        .args(["get", "db-password"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stderr("");
+    let audit = String::from_utf8(
+        authy_cmd(&home)
+            .args(["audit", "show"])
+            .env("AUTHY_KEYFILE", &keyfile)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap();
+    assert_eq!(audit.matches(" alert ").count(), 3);
+    assert!(audit.contains("hook_error"));
+
+    authy_cmd(&home)
+        .args(["canary", "db-password", "--off"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["canary", "--list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_session_env_overrides_in_run() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+
+    authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--env", "1BAD=x"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid env override name"));
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy", "--run-only"])
+        .args(["--env", "ENVIRONMENT=staging", "--env", "db-host=shadowed"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    // Overrides are set next to the secrets; a secret with the same name wins
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["run", "--", "env"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ENVIRONMENT=staging"))
+        .stdout(predicate::str::contains("db-host=localhost"))
+        .stdout(predicate::str::contains("shadowed").not());
+
+    // Master-key runs don't pick up any session's overrides
+    authy_cmd(&home)
+        .current_dir(home.path())
+        .args(["run", "--scope", "deploy", "--", "env"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ENVIRONMENT=staging").not());
+
+    authy_cmd(&home)
+        .args(["session", "list", "--columns", "scope,env"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout("deploy  ENVIRONMENT,db-host\n");
+}
+
+#[test]
+fn test_token_list_pinned_to_scope() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+    authy_cmd(&home)
+        .args(["policy", "create", "everything", "--allow", "*"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    authy_cmd(&home)
+        .args(["list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-host"))
+        .stdout(predicate::str::contains("ssh-key").not());
+
+    // A broader --scope can't widen what the token sees
+    authy_cmd(&home)
+        .args(["list", "--scope", "everything"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not match the session token's scope"));
+    authy_cmd(&home)
+        .args(["expiring", "--scope", "everything"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_count_only_policy_hides_names_from_tokens() {
+    let home = TempDir::new().unwrap();
+    let keyfile = setup_vault_with_keyfile(&home);
+    authy_cmd(&home)
+        .args(["policy", "update", "deploy", "--count-only", "true"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success();
+
+    let output = authy_cmd(&home)
+        .args(["session", "create", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .output()
+        .unwrap();
+    let token = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    authy_cmd(&home)
+        .args(["list"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    let output = authy_cmd(&home)
+        .args(["list", "--json"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .env("AUTHY_TOKEN", &token)
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["count"], 2);
+    assert!(json.get("secrets").is_none());
+
+    // The master key still sees names under that scope
+    authy_cmd(&home)
+        .args(["list", "--scope", "deploy"])
+        .env("AUTHY_KEYFILE", &keyfile)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db-password"));
+}
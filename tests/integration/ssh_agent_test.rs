@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command as StdCommand};
+use std::time::{Duration, Instant};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir, agent: &Agent) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env("SSH_AUTH_SOCK", &agent.socket);
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd
+}
+
+/// A throwaway `ssh-agent`, killed on drop.
+struct Agent {
+    socket: PathBuf,
+    child: Child,
+}
+
+impl Agent {
+    fn start(dir: &Path) -> Self {
+        let socket = dir.join("agent.sock");
+        let child = StdCommand::new("ssh-agent")
+            .arg("-D")
+            .arg("-a")
+            .arg(&socket)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .expect("ssh-agent");
+        let started = Instant::now();
+        while !socket.exists() {
+            assert!(started.elapsed() < Duration::from_secs(10), "ssh-agent didn't start");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        Self { socket, child }
+    }
+
+    /// Generate a key of `key_type` and, if `load`, add it to the agent.
+    /// Returns the public key path.
+    fn key(&self, dir: &Path, key_type: &str, load: bool) -> String {
+        let path = dir.join(format!("id_{}", key_type));
+        let status = StdCommand::new("ssh-keygen")
+            .args(["-q", "-t", key_type, "-N", "", "-f"])
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        if load {
+            let status = StdCommand::new("ssh-add")
+                .arg("-q")
+                .arg(&path)
+                .env("SSH_AUTH_SOCK", &self.socket)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        format!("{}.pub", path.display())
+    }
+}
+
+impl Drop for Agent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_init_ssh_key_and_unlock_through_agent() {
+    let home = TempDir::new().unwrap();
+    let agent = Agent::start(home.path());
+    let public_key = agent.key(home.path(), "ed25519", true);
+
+    authy_cmd(&home, &agent)
+        .args(["init", "--ssh-key", &public_key])
+        .assert()
+        .success();
+    let config = std::fs::read_to_string(home.path().join(".authy/authy.toml")).unwrap();
+    assert!(config.contains("auth_method = \"ssh-agent\""));
+
+    authy_cmd(&home, &agent)
+        .args(["store", "api-key"])
+        .write_stdin("sk-agent")
+        .assert()
+        .success();
+    authy_cmd(&home, &agent)
+        .args(["get", "api-key"])
+        .assert()
+        .success()
+        .stdout("sk-agent");
+    authy_cmd(&home, &agent)
+        .args(["audit", "show", "--columns", "actor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("master(ssh-agent)"));
+
+    // No agent, no vault
+    authy_cmd(&home, &agent)
+        .env_remove("SSH_AUTH_SOCK")
+        .args(["get", "api-key"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No SSH agent"));
+
+    // Moving to a keyfile stops authy from asking the agent
+    let keyfile = home.path().join("master.key");
+    let keyfile = keyfile.to_str().unwrap();
+    authy_cmd(&home, &agent)
+        .args(["rekey", "--generate-keyfile", keyfile])
+        .assert()
+        .success();
+    let config = std::fs::read_to_string(home.path().join(".authy/authy.toml")).unwrap();
+    assert!(config.contains("auth_method = \"keyfile\""));
+    authy_cmd(&home, &agent)
+        .env("AUTHY_KEYFILE", keyfile)
+        .args(["get", "api-key"])
+        .assert()
+        .success()
+        .stdout("sk-agent");
+}
+
+#[test]
+fn test_ssh_rsa_key() {
+    let home = TempDir::new().unwrap();
+    let agent = Agent::start(home.path());
+    let public_key = agent.key(home.path(), "rsa", true);
+
+    authy_cmd(&home, &agent)
+        .args(["init", "--ssh-key", &public_key])
+        .assert()
+        .success();
+    authy_cmd(&home, &agent)
+        .args(["list"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_ssh_key_must_be_usable() {
+    let home = TempDir::new().unwrap();
+    let agent = Agent::start(home.path());
+
+    // Not loaded in the agent
+    let unloaded = agent.key(home.path(), "ed25519", false);
+    authy_cmd(&home, &agent)
+        .args(["init", "--ssh-key", &unloaded])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("refused to sign"));
+
+    // ECDSA signatures aren't deterministic
+    let ecdsa = agent.key(home.path(), "ecdsa", true);
+    authy_cmd(&home, &agent)
+        .args(["init", "--ssh-key", &ecdsa])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use ssh-ed25519 or ssh-rsa"));
+
+    assert!(!home.path().join(".authy/vault.age").exists());
+}
@@ -0,0 +1,241 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+fn authy_cmd(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("authy").unwrap();
+    cmd.env("HOME", home.path());
+    cmd.env_remove("AUTHY_PASSPHRASE");
+    cmd.env_remove("AUTHY_KEYFILE");
+    cmd.env_remove("AUTHY_TOKEN");
+    cmd
+}
+
+type StoredObject = Arc<Mutex<Option<Vec<u8>>>>;
+
+/// A minimal in-memory object server: GET/HEAD/PUT on any path, requiring
+/// `Authorization: Bearer s3cret`, with ETags and `If-Match` /
+/// `If-None-Match`. Returns the base URL, the stored body, and a flag that
+/// makes the server act as if another writer replaced the object right after
+/// the next GET.
+fn start_object_server() -> (String, StoredObject, Arc<AtomicBool>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let object: StoredObject = Arc::new(Mutex::new(None));
+    let stored = object.clone();
+    let interfere = Arc::new(AtomicBool::new(false));
+    let interfering = interfere.clone();
+    let mut version = 0u32;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let method = request_line.split_whitespace().next().unwrap_or("").to_string();
+            let mut length = 0;
+            let mut authorized = false;
+            let mut if_match = None;
+            let mut if_none_match = false;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                let (name, value) = line.split_once(':').unwrap_or((line, ""));
+                match name.to_ascii_lowercase().as_str() {
+                    "content-length" => length = value.trim().parse().unwrap_or(0),
+                    "authorization" => authorized = value.trim() == "Bearer s3cret",
+                    "if-match" => if_match = Some(value.trim().to_string()),
+                    "if-none-match" => if_none_match = value.trim() == "*",
+                    _ => {}
+                }
+            }
+            let mut body = vec![0u8; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let etag = format!("\"v{}\"", version);
+            let (status, payload) = if !authorized {
+                ("401 Unauthorized", Vec::new())
+            } else {
+                let mut object = stored.lock().unwrap();
+                let precondition_failed = match &if_match {
+                    Some(tag) => object.is_none() || *tag != etag,
+                    None => if_none_match && object.is_some(),
+                };
+                match (method.as_str(), object.as_ref()) {
+                    ("PUT", _) if precondition_failed => ("412 Precondition Failed", Vec::new()),
+                    ("PUT", _) => {
+                        *object = Some(body);
+                        version += 1;
+                        ("200 OK", Vec::new())
+                    }
+                    ("GET", Some(data)) => {
+                        let data = data.clone();
+                        if interfering.swap(false, Ordering::SeqCst) {
+                            version += 1;
+                        }
+                        ("200 OK", data)
+                    }
+                    ("HEAD", Some(_)) => ("200 OK", Vec::new()),
+                    _ => ("404 Not Found", Vec::new()),
+                }
+            };
+            // The ETag of the object as this response leaves it, except
+            // that a GET reports the version it returned
+            let etag = if method == "GET" { etag } else { format!("\"v{}\"", version) };
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nETag: {}\r\nConnection: close\r\n\r\n",
+                status,
+                if method == "HEAD" { 0 } else { payload.len() },
+                etag
+            );
+            let _ = stream.write_all(head.as_bytes());
+            if method != "HEAD" {
+                let _ = stream.write_all(&payload);
+            }
+        }
+    });
+
+    (url, object, interfere)
+}
+
+#[test]
+fn test_http_storage_backend() {
+    let home = TempDir::new().unwrap();
+    let (url, object, _) = start_object_server();
+    std::fs::create_dir_all(home.path().join(".authy")).unwrap();
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[storage]\nbackend = \"http\"\nurl = \"{}/team/vault.age\"\ntoken_env = \"VAULT_STORE_TOKEN\"\n",
+            url
+        ),
+    )
+    .unwrap();
+
+    // Wrong credentials surface the backend error
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("401"));
+
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["store", "db-url"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .write_stdin("postgres://remote")
+        .assert()
+        .success();
+
+    authy_cmd(&home)
+        .args(["get", "db-url"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .assert()
+        .success()
+        .stdout("postgres://remote");
+
+    // Only ciphertext leaves the machine, and nothing lands locally
+    let remote = object.lock().unwrap().clone().unwrap();
+    assert!(!String::from_utf8_lossy(&remote).contains("postgres://remote"));
+    assert!(!home.path().join(".authy/vault.age").exists());
+    // The pre-written config was kept
+    let config = std::fs::read_to_string(home.path().join(".authy/authy.toml")).unwrap();
+    assert!(config.contains("backend = \"http\""));
+
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already initialized"))
+        .stderr(predicate::str::contains("/team/vault.age"));
+}
+
+#[test]
+fn test_storage_config_requires_fields() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join(".authy")).unwrap();
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        "[storage]\nbackend = \"s3\"\n",
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("requires bucket"));
+}
+
+#[test]
+fn test_http_storage_write_is_conditional() {
+    let home = TempDir::new().unwrap();
+    let (url, object, interfere) = start_object_server();
+    std::fs::create_dir_all(home.path().join(".authy")).unwrap();
+    std::fs::write(
+        home.path().join(".authy/authy.toml"),
+        format!(
+            "[storage]\nbackend = \"http\"\nurl = \"{}/team/vault.age\"\ntoken_env = \"VAULT_STORE_TOKEN\"\n",
+            url
+        ),
+    )
+    .unwrap();
+
+    authy_cmd(&home)
+        .args(["init", "--passphrase", "testpass"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .assert()
+        .success();
+    let before = object.lock().unwrap().clone();
+
+    // Another writer replaces the vault between our read and our write
+    interfere.store(true, Ordering::SeqCst);
+    authy_cmd(&home)
+        .args(["--json", "store", "db-url"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .write_stdin("postgres://lost")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("vault_conflict"))
+        .stderr(predicate::str::contains("changed since it was read"));
+    assert_eq!(*object.lock().unwrap(), before);
+
+    // A retry reads the current vault and goes through
+    authy_cmd(&home)
+        .args(["store", "db-url"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .write_stdin("postgres://kept")
+        .assert()
+        .success();
+    authy_cmd(&home)
+        .args(["get", "db-url"])
+        .env("VAULT_STORE_TOKEN", "s3cret")
+        .env("AUTHY_PASSPHRASE", "testpass")
+        .assert()
+        .success()
+        .stdout("postgres://kept");
+}
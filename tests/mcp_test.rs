@@ -7,7 +7,7 @@ use serial_test::serial;
 use tempfile::TempDir;
 
 use authy::api::AuthyClient;
-use authy::mcp::McpServer;
+use authy::mcp::{McpLimits, McpServer};
 
 /// Set HOME to an isolated temp dir so vault operations don't collide.
 fn with_isolated_home(f: impl FnOnce(&TempDir)) {
@@ -65,14 +65,23 @@ fn test_mcp_tools_list() {
 
     let json = parse_response(&resp);
     let tools = json["result"]["tools"].as_array().unwrap();
-    assert_eq!(tools.len(), 5);
+    assert_eq!(tools.len(), 14);
 
     let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
     assert!(names.contains(&"get_secret"));
+    assert!(names.contains(&"get_secrets"));
     assert!(names.contains(&"list_secrets"));
+    assert!(names.contains(&"describe_secrets"));
+    assert!(names.contains(&"build_env"));
     assert!(names.contains(&"store_secret"));
     assert!(names.contains(&"remove_secret"));
+    assert!(names.contains(&"set_tags"));
     assert!(names.contains(&"test_policy"));
+    assert!(names.contains(&"list_policies"));
+    assert!(names.contains(&"get_policy"));
+    assert!(names.contains(&"create_policy"));
+    assert!(names.contains(&"update_policy"));
+    assert!(names.contains(&"delete_policy"));
 }
 
 // ── get_secret ──────────────────────────────────────────────────
@@ -101,6 +110,119 @@ fn test_mcp_get_secret() {
     });
 }
 
+#[test]
+#[serial]
+fn test_mcp_get_secrets_and_build_env() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("db-host", "localhost", false).unwrap();
+        client.store("db-pass", "hunter2", false).unwrap();
+        client.store("ssh-key", "private", false).unwrap();
+        client
+            .create_policy("db", vec!["db-*".into()], vec![], None, false)
+            .unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ));
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"get_secrets","arguments":{"names":["db-host","db-pass","missing"]}}}"#,
+        );
+        let json = parse_response(&resp);
+        let result = &json["result"]["structuredContent"];
+        assert!(json["result"]["isError"].is_null());
+        assert_eq!(result["secrets"]["db-host"], "localhost");
+        assert_eq!(result["secrets"]["db-pass"], "hunter2");
+        assert_eq!(result["errors"]["missing"]["code"], "not_found");
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"build_env","arguments":{"scope":"db","uppercase":true,"replace_dash":"_"}}}"#,
+        );
+        let json = parse_response(&resp);
+        let text = json["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(!text.contains("hunter2"));
+        let variables = json["result"]["structuredContent"]["variables"].as_array().unwrap();
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables[0]["name"], "DB_HOST");
+        assert_eq!(variables[0]["secret"], "db-host");
+        assert_eq!(variables[1]["name"], "DB_PASS");
+    });
+}
+
+#[test]
+#[serial]
+fn test_mcp_get_secret_expired_is_refused() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        let options = authy::api::StoreOptions {
+            expires_at: Some(chrono::Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        client.store_with_options("old-cert", "pem", &options).unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ));
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{"name":"get_secret","arguments":{"name":"old-cert"}}}"#,
+        );
+
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["isError"], true);
+        assert!(!resp.contains("pem"));
+        let error = &json["result"]["structuredContent"]["error"];
+        assert_eq!(error["code"], "access_denied");
+    });
+}
+
+#[test]
+#[serial]
+fn test_mcp_get_secret_not_found_structured_error() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ));
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{"name":"get_secret","arguments":{"name":"missing"}}}"#,
+        );
+
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["isError"], true);
+        let error = &json["result"]["structuredContent"]["error"];
+        assert_eq!(error["code"], "not_found");
+        assert_eq!(error["exit_code"], 3);
+        assert_eq!(error["secret"], "missing");
+    });
+}
+
+#[test]
+#[serial]
+fn test_mcp_missing_param_structured_error() {
+    let server = McpServer::new(Some(
+        AuthyClient::with_passphrase("test-pass").unwrap(),
+    ));
+    let resp = send_request(
+        &server,
+        r#"{"jsonrpc":"2.0","id":9,"method":"tools/call","params":{"name":"get_secret","arguments":{}}}"#,
+    );
+
+    let json = parse_response(&resp);
+    assert_eq!(json["result"]["isError"], true);
+    assert_eq!(
+        json["result"]["structuredContent"]["error"]["code"],
+        "invalid_params"
+    );
+}
+
 // ── store + list ────────────────────────────────────────────────
 
 #[test]
@@ -131,6 +253,97 @@ fn test_mcp_store_and_list() {
         let text = json["result"]["content"][0]["text"].as_str().unwrap();
         let names: Vec<String> = serde_json::from_str(text).unwrap();
         assert!(names.contains(&"mcp-key".to_string()));
+
+        // Metadata via MCP, without the value
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{"name":"describe_secrets","arguments":{}}}"#,
+        );
+        let json = parse_response(&resp);
+        let secrets = &json["result"]["structuredContent"]["secrets"];
+        assert_eq!(secrets[0]["name"], "mcp-key");
+        assert_eq!(secrets[0]["version"], 1);
+        assert!(secrets[0].get("value").is_none());
+        assert!(!json["result"]["content"][0]["text"].as_str().unwrap().contains("mcp-val"));
+
+        // Tags via MCP
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"set_tags","arguments":{"name":"mcp-key","tags":["prod"," db ","prod"]}}}"#,
+        );
+        assert!(parse_response(&resp)["result"]["isError"].is_null());
+        let tags = client.get_metadata("mcp-key").unwrap().unwrap().tags;
+        assert_eq!(tags, vec!["prod", "db"]);
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":8,"method":"tools/call","params":{"name":"set_tags","arguments":{"name":"mcp-key","tags":"prod"}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["structuredContent"]["error"]["code"], "invalid_params");
+    });
+}
+
+// ── policies ────────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_mcp_policy_management() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ));
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"create_policy","arguments":{"name":"app","allow":["app-*"]}}}"#,
+        );
+        assert!(parse_response(&resp)["result"]["isError"].is_null());
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"update_policy","arguments":{"name":"app","deny":["app-admin*"]}}}"#,
+        );
+        assert!(parse_response(&resp)["result"]["isError"].is_null());
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"get_policy","arguments":{"name":"app"}}}"#,
+        );
+        let json = parse_response(&resp);
+        let policy = &json["result"]["structuredContent"]["policy"];
+        assert_eq!(policy["allow"][0], "app-*");
+        assert_eq!(policy["deny"][0], "app-admin*");
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"update_policy","arguments":{"name":"app","allow":"app-*"}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["structuredContent"]["error"]["code"], "invalid_params");
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"delete_policy","arguments":{"name":"app"}}}"#,
+        );
+        assert!(parse_response(&resp)["result"]["isError"].is_null());
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":6,"method":"tools/call","params":{"name":"get_policy","arguments":{"name":"app"}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["structuredContent"]["error"]["policy"], "app");
+
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"list_policies","arguments":{}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert_eq!(json["result"]["structuredContent"]["policies"], serde_json::json!([]));
     });
 }
 
@@ -149,6 +362,10 @@ fn test_mcp_no_credentials() {
     assert_eq!(json["result"]["isError"], true);
     let text = json["result"]["content"][0]["text"].as_str().unwrap();
     assert!(text.contains("No credentials"));
+    assert_eq!(
+        json["result"]["structuredContent"]["error"]["code"],
+        "auth_failed"
+    );
 }
 
 // ── notification (no response) ──────────────────────────────────
@@ -185,3 +402,221 @@ fn test_mcp_unknown_method() {
         .unwrap()
         .contains("Method not found"));
 }
+
+// ── hardening ───────────────────────────────────────────────────
+
+#[test]
+#[serial]
+fn test_mcp_oversized_line_rejected() {
+    let server = McpServer::new(None).with_limits(McpLimits {
+        max_line_bytes: 64,
+        ..McpLimits::default()
+    });
+    let big = format!(
+        r#"{{"jsonrpc":"2.0","id":1,"method":"ping","params":{{"pad":"{}"}}}}"#,
+        "x".repeat(200)
+    );
+    let input = format!("{}\n{}\n", big, r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#);
+    let mut output = Vec::new();
+    server
+        .run(std::io::Cursor::new(input.into_bytes()), &mut output)
+        .unwrap();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(parse_response)
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["error"]["code"], -32600);
+    // The server keeps serving after dropping the oversized line
+    assert_eq!(lines[1]["id"], 2);
+    assert!(lines[1]["result"].is_object());
+}
+
+#[test]
+#[serial]
+fn test_mcp_timeout_spares_writes() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ))
+        .with_limits(McpLimits {
+            request_timeout: Some(std::time::Duration::ZERO),
+            ..McpLimits::default()
+        });
+
+        // A write runs to completion and reports its outcome
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"store_secret","arguments":{"name":"k","value":"v"}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert!(json["error"].is_null(), "{}", resp);
+        assert!(json["result"]["isError"].is_null());
+        assert_eq!(client.get("k").unwrap().as_deref(), Some("v"));
+
+        // A read is still cut off
+        let resp = send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_secret","arguments":{"name":"k"}}}"#,
+        );
+        let json = parse_response(&resp);
+        assert_eq!(json["error"]["code"], -32603);
+    });
+}
+
+#[test]
+#[serial]
+fn test_mcp_batch_request() {
+    let server = McpServer::new(None);
+    let resp = send_request(
+        &server,
+        r#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","method":"notifications/initialized"},{"jsonrpc":"2.0","id":2,"method":"bogus"}]"#,
+    );
+
+    let json = parse_response(&resp);
+    let responses = json.as_array().unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
+
+#[test]
+#[serial]
+fn test_mcp_empty_batch_invalid() {
+    let server = McpServer::new(None);
+    let resp = send_request(&server, "[]");
+    let json = parse_response(&resp);
+    assert_eq!(json["error"]["code"], -32600);
+}
+
+#[test]
+#[serial]
+fn test_mcp_invalid_request_shape() {
+    let server = McpServer::new(None);
+    let resp = server
+        .handle_message(serde_json::json!({"jsonrpc": "2.0", "id": 3}))
+        .unwrap();
+    let json = serde_json::to_value(resp).unwrap();
+    assert_eq!(json["error"]["code"], -32600);
+    assert_eq!(json["id"], 3);
+}
+
+#[test]
+#[serial]
+fn test_mcp_malformed_tool_params() {
+    let server = McpServer::new(None);
+
+    let resp = server
+        .handle_message(serde_json::json!({
+            "jsonrpc": "2.0", "id": 4, "method": "tools/call", "params": "get_secret"
+        }))
+        .unwrap();
+    assert_eq!(serde_json::to_value(resp).unwrap()["error"]["code"], -32602);
+
+    let resp = server
+        .handle_message(serde_json::json!({
+            "jsonrpc": "2.0", "id": 5, "method": "tools/call",
+            "params": {"name": "get_secret", "arguments": ["api-key"]}
+        }))
+        .unwrap();
+    assert_eq!(serde_json::to_value(resp).unwrap()["error"]["code"], -32602);
+}
+
+#[test]
+#[serial]
+fn test_mcp_read_cache() {
+    with_isolated_home(|_home| {
+        let client = AuthyClient::with_passphrase("test-pass").unwrap();
+        client.init_vault().unwrap();
+        client.store("api-key", "v1", false).unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_passphrase("test-pass").unwrap(),
+        ))
+        .with_cache_ttl(std::time::Duration::from_secs(300));
+        let get = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"get_secret","arguments":{"name":"api-key"}}}"#;
+        let text = |resp: &str| parse_response(resp)["result"]["content"][0]["text"].clone();
+
+        assert_eq!(text(&send_request(&server, get)), "v1");
+
+        // A write from another process isn't seen until the entry expires...
+        client.store("api-key", "v2", true).unwrap();
+        assert_eq!(text(&send_request(&server, get)), "v1");
+
+        // ...but a write through the server clears the cache
+        send_request(
+            &server,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"store_secret","arguments":{"name":"api-key","value":"v3","force":true}}}"#,
+        );
+        assert_eq!(text(&send_request(&server, get)), "v3");
+
+        // Cached reads are still audited
+        let entries = client.audit_entries().unwrap();
+        assert!(entries
+            .iter()
+            .any(|e| e.operation == "get" && e.detail.as_deref() == Some("cached")));
+    });
+}
+
+#[test]
+#[serial]
+fn test_mcp_read_cache_rechecks_access() {
+    use secrecy::ExposeSecret;
+
+    with_isolated_home(|home| {
+        let identity = age::x25519::Identity::generate();
+        let keyfile = home.path().join("master.key");
+        std::fs::write(&keyfile, identity.to_string().expose_secret()).unwrap();
+        let keyfile = keyfile.to_str().unwrap();
+        let master = AuthyClient::with_keyfile(keyfile).unwrap();
+        master.init_vault().unwrap();
+        master.store("app-db", "postgres://", false).unwrap();
+        master
+            .create_policy("app", vec!["app-*".into()], vec![], None, false)
+            .unwrap();
+        let session = master
+            .create_session("app", std::time::Duration::from_secs(3600), None, false)
+            .unwrap();
+
+        let server = McpServer::new(Some(
+            AuthyClient::with_token(&session.token, keyfile).unwrap(),
+        ))
+        .with_cache_ttl(std::time::Duration::from_secs(300));
+        let get = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"get_secret","arguments":{"name":"app-db"}}}"#;
+        let code = |resp: &str| parse_response(resp)["result"]["structuredContent"]["error"]["code"].clone();
+
+        let resp = send_request(&server, get);
+        assert_eq!(parse_response(&resp)["result"]["content"][0]["text"], "postgres://");
+
+        // Narrowing the scope denies the cached value
+        master
+            .update_policy(
+                "app",
+                &authy::api::PolicyUpdate {
+                    deny: Some(vec!["app-db".into()]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(code(&send_request(&server, get)), "access_denied");
+
+        // So does revoking the session
+        master
+            .update_policy(
+                "app",
+                &authy::api::PolicyUpdate {
+                    deny: Some(vec![]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        send_request(&server, get);
+        master.revoke_session(&session.session.id).unwrap();
+        assert_eq!(code(&send_request(&server, get)), "invalid_token");
+    });
+}